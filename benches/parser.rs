@@ -0,0 +1,61 @@
+//
+// Compare the ways of parsing a document: `read_xml` from a string, `read_from` streaming from a
+// byte source, and `read_quick_xml`, which also resolves namespace prefixes. Run with
+// `cargo bench --bench parser`; each is timed over the same generated document.
+//
+#[cfg(feature = "quick_parser")]
+use quick_xml::Reader;
+#[cfg(feature = "quick_parser")]
+use std::time::{Duration, Instant};
+#[cfg(feature = "quick_parser")]
+use xml_dom::parser::{read_from, read_quick_xml, read_xml};
+
+#[cfg(feature = "quick_parser")]
+const ROWS: usize = 20_000;
+
+#[cfg(feature = "quick_parser")]
+const ITERATIONS: u32 = 10;
+
+#[cfg(feature = "quick_parser")]
+fn document() -> String {
+    let mut xml = String::from(r#"<?xml version="1.0"?><t:table xmlns:t="urn:table">"#);
+    for index in 0..ROWS {
+        xml.push_str(&format!(
+            r#"<t:row t:id="{0}"><!-- row {0} --><name>Row &amp; {0}</name><![CDATA[<{0}>]]></t:row>"#,
+            index
+        ));
+    }
+    xml.push_str("</t:table>");
+    xml
+}
+
+#[cfg(feature = "quick_parser")]
+fn time<F: Fn()>(name: &str, f: F) {
+    f();
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    let each: Duration = start.elapsed() / ITERATIONS;
+    println!("{:<16} {:>10.2?} per document", name, each);
+}
+
+#[cfg(feature = "quick_parser")]
+fn main() {
+    let xml = document();
+    println!("{} rows, {} bytes", ROWS, xml.len());
+    time("read_xml", || {
+        let _safe_to_ignore = read_xml(&xml).unwrap();
+    });
+    time("read_from", || {
+        let _safe_to_ignore = read_from(xml.as_bytes()).unwrap();
+    });
+    time("read_quick_xml", || {
+        let mut reader = Reader::from_str(&xml);
+        let _safe_to_ignore = reader.trim_text(true);
+        let _safe_to_ignore = read_quick_xml(reader).unwrap();
+    });
+}
+
+#[cfg(not(feature = "quick_parser"))]
+fn main() {}
@@ -0,0 +1,429 @@
+/*!
+Provides a structural comparison of two DOM trees, reporting each difference with the path of the
+node where it was found, rather than only that their serializations differ.
+
+# Example
+
+```rust
+# #[cfg(feature = "quick_parser")]
+# fn main() {
+use xml_dom::diff::*;
+use xml_dom::parser::read_xml;
+
+let expected = read_xml(r#"<list><item id="1">one</item><item id="2">two</item></list>"#).unwrap();
+let actual = read_xml(r#"<list><item id="1">one</item><item id="3">two</item></list>"#).unwrap();
+
+let differences = diff(&expected, &actual, &DiffOptions::default());
+assert_eq!(differences.len(), 1);
+assert_eq!(differences[0].path, "/list/item[2]/@id");
+assert_eq!(differences[0].kind, DifferenceKind::DifferentAttributeValue);
+assert_eq!(
+    differences[0].to_string(),
+    r#"/list/item[2]/@id: different attribute value, "2" != "3""#
+);
+
+assert_dom_eq(&expected, &expected.clone(), &DiffOptions::default());
+# }
+# #[cfg(not(feature = "quick_parser"))]
+# fn main() {}
+```
+*/
+
+use crate::level2::trait_impls::normalized_value;
+use crate::level2::{Node, NodeType, RefNode};
+use crate::shared::text::is_xml_space;
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Options that control which differences are reported by [`diff`](fn.diff.html).
+///
+/// The default options report every difference.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DiffOptions {
+    ignore_comments: bool,
+    ignore_whitespace_text: bool,
+    cdata_as_text: bool,
+}
+
+///
+/// The kind of a [`Difference`](struct.Difference.html).
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DifferenceKind {
+    /// A node, or attribute, in the left tree has no counterpart in the right tree.
+    MissingNode,
+    /// A node, or attribute, in the right tree has no counterpart in the left tree.
+    ExtraNode,
+    /// The nodes have different names, or are of different types.
+    DifferentName,
+    /// The text, CDATA, comment, or processing instruction nodes have different data.
+    DifferentText,
+    /// The attributes have different values.
+    DifferentAttributeValue,
+}
+
+///
+/// A single difference between two trees.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Difference {
+    /// The path of the node in the left tree, or for an extra node in the right tree, for
+    /// example `/root/item[3]/@id`. An index is only included when a parent has more than one
+    /// child with the same name.
+    pub path: String,
+    /// The kind of difference.
+    pub kind: DifferenceKind,
+    /// The name, or value, from the left tree, `None` for an extra node.
+    pub left: Option<String>,
+    /// The name, or value, from the right tree, `None` for a missing node.
+    pub right: Option<String>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+//
+// A child as compared; with `cdata_as_text` adjacent text and CDATA nodes are a single `Text`.
+//
+#[derive(Clone, Debug)]
+enum Child {
+    Text(String),
+    Node(RefNode),
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Compare the tree under `left` with the tree under `right`, returning the differences in
+/// document order. An empty result means the trees are equal, ignoring anything excluded by
+/// `options`.
+///
+/// Elements and attributes are compared by qualified name, and attributes are compared
+/// regardless of their order. Once two nodes are found with different names the children of those
+/// nodes are not compared.
+///
+pub fn diff(left: &RefNode, right: &RefNode, options: &DiffOptions) -> Vec<Difference> {
+    let mut differences = Vec::new();
+    let path = match left.node_type() {
+        NodeType::Element => format!("/{}", left.node_name()),
+        _ => String::new(),
+    };
+    diff_nodes(left, right, &path, options, &mut differences);
+    differences
+}
+
+///
+/// Assert that the tree under `left` is equal to the tree under `right`, as determined by
+/// [`diff`](fn.diff.html); on failure the panic message lists every difference, one per line.
+///
+#[track_caller]
+pub fn assert_dom_eq(left: &RefNode, right: &RefNode, options: &DiffOptions) {
+    let differences = diff(left, right, options);
+    if !differences.is_empty() {
+        panic!(
+            "DOM trees are not equal, {} difference(s):\n{}",
+            differences.len(),
+            differences
+                .iter()
+                .map(|difference| format!("  {}", difference))
+                .collect::<Vec<String>>()
+                .join("\n")
+        );
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl DiffOptions {
+    ///
+    /// Construct a new `DiffOptions` instance with the default values.
+    ///
+    pub fn new() -> Self {
+        Default::default()
+    }
+    ///
+    /// Set whether comments are ignored.
+    ///
+    pub fn with_ignore_comments(self, ignore_comments: bool) -> Self {
+        Self {
+            ignore_comments,
+            ..self
+        }
+    }
+    ///
+    /// Set whether text nodes that contain only white space, such as indentation, are ignored.
+    ///
+    pub fn with_ignore_whitespace_text(self, ignore_whitespace_text: bool) -> Self {
+        Self {
+            ignore_whitespace_text,
+            ..self
+        }
+    }
+    ///
+    /// Set whether CDATA sections are compared as text, so that adjacent text and CDATA nodes
+    /// are compared by their combined content.
+    ///
+    pub fn with_cdata_as_text(self, cdata_as_text: bool) -> Self {
+        Self {
+            cdata_as_text,
+            ..self
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for DifferenceKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{}",
+            match self {
+                DifferenceKind::MissingNode => "missing node",
+                DifferenceKind::ExtraNode => "extra node",
+                DifferenceKind::DifferentName => "different name",
+                DifferenceKind::DifferentText => "different text",
+                DifferenceKind::DifferentAttributeValue => "different attribute value",
+            }
+        )
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Difference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}: {}", self.path, self.kind)?;
+        match (&self.left, &self.right) {
+            (Some(left), Some(right)) => write!(f, ", {:?} != {:?}", left, right),
+            (Some(left), None) => write!(f, ", {:?}", left),
+            (None, Some(right)) => write!(f, ", {:?}", right),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn diff_nodes(
+    left: &RefNode,
+    right: &RefNode,
+    path: &str,
+    options: &DiffOptions,
+    differences: &mut Vec<Difference>,
+) {
+    if left.node_type() != right.node_type()
+        || left.node_name().to_string() != right.node_name().to_string()
+    {
+        differences.push(Difference {
+            path: path.to_string(),
+            kind: DifferenceKind::DifferentName,
+            left: Some(left.node_name().to_string()),
+            right: Some(right.node_name().to_string()),
+        });
+        return;
+    }
+    match left.node_type() {
+        NodeType::Element => {
+            diff_attributes(left, right, path, differences);
+            diff_children(left, right, path, options, differences);
+        }
+        NodeType::Document | NodeType::DocumentFragment | NodeType::EntityReference => {
+            diff_children(left, right, path, options, differences)
+        }
+        _ => {
+            if left.node_value() != right.node_value() {
+                differences.push(Difference {
+                    path: path.to_string(),
+                    kind: DifferenceKind::DifferentText,
+                    left: left.node_value(),
+                    right: right.node_value(),
+                });
+            }
+        }
+    }
+}
+
+fn diff_attributes(left: &RefNode, right: &RefNode, path: &str, differences: &mut Vec<Difference>) {
+    let value_of = |element: &RefNode, name: &str| {
+        element
+            .attributes()
+            .into_iter()
+            .find(|(attribute_name, _)| attribute_name.to_string() == name)
+            .map(|(_, attribute)| normalized_value(&attribute).unwrap_or_default())
+    };
+    let names: BTreeSet<String> = left
+        .attributes()
+        .keys()
+        .chain(right.attributes().keys())
+        .map(|name| name.to_string())
+        .collect();
+    for name in names {
+        let (left_value, right_value) = (value_of(left, &name), value_of(right, &name));
+        let kind = match (&left_value, &right_value) {
+            (Some(_), None) => DifferenceKind::MissingNode,
+            (None, Some(_)) => DifferenceKind::ExtraNode,
+            (left_value, right_value) if left_value != right_value => {
+                DifferenceKind::DifferentAttributeValue
+            }
+            _ => continue,
+        };
+        differences.push(Difference {
+            path: format!("{}/@{}", path, name),
+            kind,
+            left: left_value,
+            right: right_value,
+        });
+    }
+}
+
+fn diff_children(
+    left: &RefNode,
+    right: &RefNode,
+    path: &str,
+    options: &DiffOptions,
+    differences: &mut Vec<Difference>,
+) {
+    let left_children = children(left, options);
+    let right_children = children(right, options);
+    let left_paths = child_paths(&left_children, path);
+    let right_paths = child_paths(&right_children, path);
+    for (index, left_child) in left_children.iter().enumerate() {
+        let path = &left_paths[index];
+        match (left_child, right_children.get(index)) {
+            (_, None) => differences.push(Difference {
+                path: path.clone(),
+                kind: DifferenceKind::MissingNode,
+                left: Some(describe(left_child)),
+                right: None,
+            }),
+            (Child::Text(left_text), Some(Child::Text(right_text))) => {
+                if left_text != right_text {
+                    differences.push(Difference {
+                        path: path.clone(),
+                        kind: DifferenceKind::DifferentText,
+                        left: Some(left_text.clone()),
+                        right: Some(right_text.clone()),
+                    });
+                }
+            }
+            (Child::Node(left_node), Some(Child::Node(right_node))) => {
+                diff_nodes(left_node, right_node, path, options, differences)
+            }
+            (left_child, Some(right_child)) => differences.push(Difference {
+                path: path.clone(),
+                kind: DifferenceKind::DifferentName,
+                left: Some(name_of(left_child)),
+                right: Some(name_of(right_child)),
+            }),
+        }
+    }
+    for (index, right_child) in right_children.iter().enumerate().skip(left_children.len()) {
+        differences.push(Difference {
+            path: right_paths[index].clone(),
+            kind: DifferenceKind::ExtraNode,
+            left: None,
+            right: Some(describe(right_child)),
+        });
+    }
+}
+
+//
+// The children of `node` that are compared, after applying `options`. The document type is not
+// compared.
+//
+fn children(node: &RefNode, options: &DiffOptions) -> Vec<Child> {
+    let mut children: Vec<Child> = Vec::new();
+    for child in node.child_nodes() {
+        match child.node_type() {
+            NodeType::Comment if options.ignore_comments => continue,
+            NodeType::DocumentType => continue,
+            NodeType::Text => (),
+            NodeType::CData if options.cdata_as_text => (),
+            _ => {
+                children.push(Child::Node(child));
+                continue;
+            }
+        }
+        let data = child.node_value().unwrap_or_default();
+        if let Some(Child::Text(text)) = children.last_mut() {
+            text.push_str(&data);
+        } else {
+            children.push(Child::Text(data));
+        }
+    }
+    if options.ignore_whitespace_text {
+        children.retain(|child| match child {
+            Child::Text(text) => !text.chars().all(is_xml_space),
+            Child::Node(_) => true,
+        });
+    }
+    children
+}
+
+//
+// The path of each of `children` below `parent`; the children are named as XPath node tests,
+// with an index only where a name is repeated.
+//
+fn child_paths(children: &[Child], parent: &str) -> Vec<String> {
+    let names: Vec<String> = children
+        .iter()
+        .map(|child| match child {
+            Child::Text(_) => "text()".to_string(),
+            Child::Node(node) => match node.node_type() {
+                NodeType::Element => node.node_name().to_string(),
+                NodeType::Text | NodeType::CData => "text()".to_string(),
+                NodeType::Comment => "comment()".to_string(),
+                NodeType::ProcessingInstruction => {
+                    format!("processing-instruction('{}')", node.node_name())
+                }
+                _ => "node()".to_string(),
+            },
+        })
+        .collect();
+    names
+        .iter()
+        .enumerate()
+        .map(|(index, name)| {
+            let count = names.iter().filter(|other| *other == name).count();
+            if count > 1 {
+                let position = names[..index].iter().filter(|other| *other == name).count() + 1;
+                format!("{}/{}[{}]", parent, name, position)
+            } else {
+                format!("{}/{}", parent, name)
+            }
+        })
+        .collect()
+}
+
+fn name_of(child: &Child) -> String {
+    match child {
+        Child::Text(_) => "#text".to_string(),
+        Child::Node(node) => node.node_name().to_string(),
+    }
+}
+
+fn describe(child: &Child) -> String {
+    match child {
+        Child::Text(text) => text.clone(),
+        Child::Node(node) => match node.node_type() {
+            NodeType::Element => node.node_name().to_string(),
+            _ => node
+                .node_value()
+                .unwrap_or_else(|| node.node_name().to_string()),
+        },
+    }
+}
@@ -0,0 +1,14 @@
+/*!
+Provides conversions between a DOM tree and the types of other XML crates, so that their parsers
+and writers may be used with this crate. Each is only included with the feature of the same name.
+
+* [`xml_rs`](xml_rs/index.html) reads a document from the events of an xml-rs `EventReader`, and
+  writes a tree as the events of an xml-rs `EventWriter`.
+*/
+
+// ------------------------------------------------------------------------------------------------
+// Public Modules
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(feature = "xml_rs")]
+pub mod xml_rs;
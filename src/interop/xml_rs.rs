@@ -0,0 +1,378 @@
+/*!
+Provides conversion between a DOM tree and the events of the [xml-rs](https://crates.io/crates/xml-rs)
+crate. This module is only included with the `xml_rs` feature.
+
+[`from_event_reader`](fn.from_event_reader.html) builds a new document from the events of an
+`EventReader`, and [`to_event_writer`](fn.to_event_writer.html) walks a tree writing the matching
+events to an `EventWriter`; the configuration of each, such as whether comments are ignored or
+output is indented, is left to the caller.
+
+# Namespaces
+
+The reader reports the namespaces in scope at each element, rather than the declarations on it;
+the declarations are recovered by comparing these with the namespaces in scope at the parent, so
+they are kept, as attributes, in order by prefix before any other attributes. When writing,
+namespace declarations are written as attributes, and a declaration is added where an element, or
+attribute, uses a namespace that is not declared in the output.
+
+# Limitations
+
+* The reader reports an XML declaration whether or not the document has one, so a document read
+  always has an XML declaration. A document without one is written without a `StartDocument`
+  event, leaving the writer to add a declaration or not.
+* The reader does not report the document type declaration as an event, so it is not kept, and a
+  `DocumentType` is not written.
+* White space outside the document element is dropped.
+
+# Example
+
+```rust
+use xml::{EmitterConfig, EventReader};
+use xml_dom::interop::xml_rs::*;
+use xml_dom::level2::*;
+
+let xml = r#"<a:root xmlns:a="urn:a"><a:item id="1">one &amp; two</a:item></a:root>"#;
+let document_node = from_event_reader(EventReader::from_str(xml)).unwrap();
+
+let item_node = document_node.document_element().unwrap().first_child().unwrap();
+assert_eq!(item_node.namespace_uri(), Some("urn:a".to_string()));
+
+let mut output = Vec::new();
+let writer = EmitterConfig::new()
+    .write_document_declaration(false)
+    .create_writer(&mut output);
+to_event_writer(&document_node, writer).unwrap();
+assert_eq!(
+    String::from_utf8(output).unwrap(),
+    r#"<?xml version="1.0" encoding="UTF-8"?><a:root xmlns:a="urn:a"><a:item id="1">one &amp; two</a:item></a:root>"#
+);
+```
+*/
+
+use crate::level2::ext::{DocumentDecl, XmlDecl, XmlVersion};
+use crate::level2::trait_impls::{
+    declaration_name, declare_namespaces, normalized_value, Namespaces,
+};
+use crate::level2::{get_implementation, Attribute, Document, Element, Node, NodeType, RefNode};
+use crate::shared::error::{Error as DOMError, MSG_INVALID_NODE_TYPE};
+use crate::shared::syntax::{XMLNS_NS_ATTRIBUTE, XMLNS_NS_URI, XML_NS_ATTRIBUTE};
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+use std::io::{Read, Write};
+use std::sync::Arc;
+use xml::attribute::{Attribute as XmlAttribute, OwnedAttribute};
+use xml::common::XmlVersion as XmlRsVersion;
+use xml::name::{Name as XmlName, OwnedName};
+use xml::namespace::Namespace;
+use xml::reader::XmlEvent as ReaderEvent;
+use xml::writer::XmlEvent as WriterEvent;
+use xml::{EventReader, EventWriter};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Errors converting between a DOM and xml-rs events.
+///
+#[derive(Clone, Debug)]
+pub enum Error {
+    /// An error creating, or reading, a node; this wraps the DOM error.
+    Dom(DOMError),
+    /// An error reading events, this wraps the original xml-rs error.
+    Reader(xml::reader::Error),
+    /// An error writing events, this wraps the original xml-rs error.
+    Writer(Arc<xml::writer::Error>),
+}
+
+///
+/// Result type for public function(s).
+///
+pub type Result<T> = std::result::Result<T, Error>;
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Build a new document from the events of `reader`, until the end of the document; if the
+/// result is OK, the result returned can be safely assumed to be a `Document` node.
+///
+/// Elements and attributes are created with the namespace URI reported by the reader, and the
+/// namespace declarations of each element are added as attributes; see the
+/// [module](index.html) documentation.
+///
+pub fn from_event_reader<R: Read>(mut reader: EventReader<R>) -> Result<RefNode> {
+    let mut document_node = get_implementation().create_document(None, None, None)?;
+    let mut open: Vec<(RefNode, Namespace)> = Vec::new();
+    loop {
+        let mut parent_node = match open.last() {
+            Some((element_node, _)) => element_node.clone(),
+            None => document_node.clone(),
+        };
+        let child_node = match reader.next()? {
+            ReaderEvent::StartDocument {
+                version,
+                encoding,
+                standalone,
+            } => {
+                let version = match version {
+                    XmlRsVersion::Version10 => XmlVersion::V10,
+                    XmlRsVersion::Version11 => XmlVersion::V11,
+                };
+                document_node.set_xml_declaration(XmlDecl::new(
+                    version,
+                    Some(encoding),
+                    standalone,
+                ))?;
+                None
+            }
+            ReaderEvent::EndDocument => break,
+            ReaderEvent::ProcessingInstruction { name, data } => {
+                Some(document_node.create_processing_instruction(&name, data.as_deref())?)
+            }
+            ReaderEvent::StartElement {
+                name,
+                attributes,
+                namespace,
+            } => {
+                let in_scope = open.last().map(|(_, namespace)| namespace);
+                let element_node =
+                    create_element(&document_node, &name, &attributes, &namespace, in_scope)?;
+                let element_node = parent_node.append_child(element_node)?;
+                open.push((element_node, namespace));
+                None
+            }
+            ReaderEvent::EndElement { .. } => {
+                let _safe_to_ignore = open.pop();
+                None
+            }
+            ReaderEvent::CData(data) => Some(document_node.create_cdata_section(&data)?),
+            ReaderEvent::Comment(data) => Some(document_node.create_comment(&data)?),
+            ReaderEvent::Characters(data) => Some(document_node.create_text_node(&data)),
+            ReaderEvent::Whitespace(data) if !open.is_empty() => {
+                Some(document_node.create_text_node(&data))
+            }
+            ReaderEvent::Whitespace(_) => None,
+        };
+        if let Some(child_node) = child_node {
+            let _safe_to_ignore = parent_node.append_child(child_node)?;
+        }
+    }
+    Ok(document_node)
+}
+
+///
+/// Write the tree under `node` as events to `writer`; a document is written with its XML
+/// declaration, if any, as a `StartDocument` event.
+///
+/// **Exceptions**
+///
+/// * `NOT_SUPPORTED_ERR`: Raised if `node` is an `Attribute`, `DocumentType`, `Entity`, or
+///   `Notation`, which have no matching event.
+///
+pub fn to_event_writer<W: Write>(node: &RefNode, mut writer: EventWriter<W>) -> Result<()> {
+    match node.node_type() {
+        NodeType::Document => {
+            if let Some(declaration) = node.xml_declaration() {
+                let encoding = declaration.encoding();
+                writer.write(WriterEvent::StartDocument {
+                    version: match declaration.version() {
+                        XmlVersion::V10 => XmlRsVersion::Version10,
+                        XmlVersion::V11 => XmlRsVersion::Version11,
+                    },
+                    encoding: encoding.as_deref(),
+                    standalone: declaration.standalone(),
+                })?;
+            }
+            write_children(node, &Namespaces::new(), &mut writer)
+        }
+        NodeType::Attribute | NodeType::DocumentType | NodeType::Entity | NodeType::Notation => {
+            warn!("{}", MSG_INVALID_NODE_TYPE);
+            Err(DOMError::NotSupported.into())
+        }
+        _ => write_node(node, &Namespaces::new(), &mut writer),
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Dom(err) => write!(f, "DOM Error: {}", err),
+            Error::Reader(err) => write!(f, "Error reading events: {}", err),
+            Error::Writer(err) => write!(f, "Error writing events: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Dom(err) => Some(err),
+            Error::Reader(err) => Some(err),
+            Error::Writer(err) => Some(err.as_ref()),
+        }
+    }
+}
+
+impl From<DOMError> for Error {
+    fn from(err: DOMError) -> Self {
+        Error::Dom(err)
+    }
+}
+
+impl From<xml::reader::Error> for Error {
+    fn from(err: xml::reader::Error) -> Self {
+        error!("xml::reader::Error: {:?}", err);
+        Error::Reader(err)
+    }
+}
+
+impl From<xml::writer::Error> for Error {
+    fn from(err: xml::writer::Error) -> Self {
+        error!("xml::writer::Error: {:?}", err);
+        Error::Writer(Arc::new(err))
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+//
+// Create an element, with its namespace declarations and attributes; the declarations are those
+// prefixes in `namespace` bound to a different URI in `in_scope`, that of the parent element.
+//
+fn create_element(
+    document: &RefNode,
+    name: &OwnedName,
+    attributes: &[OwnedAttribute],
+    namespace: &Namespace,
+    in_scope: Option<&Namespace>,
+) -> Result<RefNode> {
+    let mut element_node = match name.namespace.as_deref().filter(|uri| !uri.is_empty()) {
+        Some(namespace_uri) => document.create_element_ns(namespace_uri, &qualified_name(name))?,
+        None => document.create_element(&qualified_name(name))?,
+    };
+    for (prefix, namespace_uri) in &namespace.0 {
+        let inherited = in_scope
+            .and_then(|in_scope| in_scope.get(prefix))
+            .unwrap_or_default();
+        if prefix != XML_NS_ATTRIBUTE && prefix != XMLNS_NS_ATTRIBUTE && inherited != namespace_uri
+        {
+            let mut attribute_node =
+                document.create_attribute_ns(XMLNS_NS_URI, &declaration_name(prefix))?;
+            attribute_node.set_value(namespace_uri)?;
+            let _safe_to_ignore = element_node.set_attribute_node_ns(attribute_node)?;
+        }
+    }
+    for attribute in attributes {
+        let _safe_to_ignore = match attribute
+            .name
+            .namespace
+            .as_deref()
+            .filter(|uri| !uri.is_empty())
+        {
+            Some(namespace_uri) => {
+                let mut attribute_node = document
+                    .create_attribute_ns(namespace_uri, &qualified_name(&attribute.name))?;
+                attribute_node.set_value(&attribute.value)?;
+                element_node.set_attribute_node_ns(attribute_node)?
+            }
+            None => {
+                let attribute_node = document
+                    .create_attribute_with(&qualified_name(&attribute.name), &attribute.value)?;
+                element_node.set_attribute_node(attribute_node)?
+            }
+        };
+    }
+    Ok(element_node)
+}
+
+fn qualified_name(name: &OwnedName) -> String {
+    match &name.prefix {
+        Some(prefix) => format!("{}:{}", prefix, name.local_name),
+        None => name.local_name.clone(),
+    }
+}
+
+fn write_children<W: Write>(
+    node: &RefNode,
+    in_scope: &Namespaces,
+    writer: &mut EventWriter<W>,
+) -> Result<()> {
+    for child_node in node.child_nodes() {
+        write_node(&child_node, in_scope, writer)?;
+    }
+    Ok(())
+}
+
+fn write_node<W: Write>(
+    node: &RefNode,
+    in_scope: &Namespaces,
+    writer: &mut EventWriter<W>,
+) -> Result<()> {
+    let data = node.node_value().unwrap_or_default();
+    match node.node_type() {
+        NodeType::Element => write_element(node, in_scope, writer),
+        NodeType::Text => Ok(writer.write(WriterEvent::Characters(&data))?),
+        NodeType::CData => Ok(writer.write(WriterEvent::CData(&data))?),
+        NodeType::Comment => Ok(writer.write(WriterEvent::Comment(&data))?),
+        NodeType::ProcessingInstruction => {
+            Ok(writer.write(WriterEvent::ProcessingInstruction {
+                name: &node.node_name().to_string(),
+                data: node.node_value().as_deref(),
+            })?)
+        }
+        NodeType::DocumentType => Ok(()),
+        _ => write_children(node, in_scope, writer),
+    }
+}
+
+//
+// The namespace declarations are written as attributes, rather than left to the writer, which
+// does not undeclare a default namespace.
+//
+fn write_element<W: Write>(
+    element: &RefNode,
+    in_scope: &Namespaces,
+    writer: &mut EventWriter<W>,
+) -> Result<()> {
+    let mut in_scope = in_scope.clone();
+    let added = declare_namespaces(element, &mut in_scope);
+
+    let mut attributes: Vec<(String, String)> = element
+        .borrow()
+        .attribute_nodes()
+        .iter()
+        .map(|attribute_node| {
+            (
+                attribute_node.node_name().to_string(),
+                normalized_value(attribute_node).unwrap_or_default(),
+            )
+        })
+        .collect();
+    attributes.extend(
+        added
+            .into_iter()
+            .map(|(prefix, namespace_uri)| (declaration_name(&prefix), namespace_uri)),
+    );
+
+    let name = element.node_name().to_string();
+    writer.write(WriterEvent::StartElement {
+        name: XmlName::from(name.as_str()),
+        attributes: attributes
+            .iter()
+            .map(|(name, value)| XmlAttribute::new(XmlName::from(name.as_str()), value))
+            .collect(),
+        namespace: Cow::Owned(Namespace::empty()),
+    })?;
+    write_children(element, &in_scope, writer)?;
+    Ok(writer.write(WriterEvent::EndElement {
+        name: Some(XmlName::from(name.as_str())),
+    })?)
+}
@@ -0,0 +1,434 @@
+use crate::level2::trait_impls::{
+    add_namespace_declarations, is_namespace_declaration, normalized_value, Namespaces,
+};
+use crate::level2::*;
+use crate::shared::syntax::*;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Options that control the output of
+/// [`Serializer::to_canonical_string`](trait.Serializer.html#tymethod.to_canonical_string).
+///
+/// The default options produce Canonical XML without comments.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CanonicalOptions {
+    comments: bool,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+pub(crate) struct Canonical<'a> {
+    node: &'a RefNode,
+    options: &'a CanonicalOptions,
+}
+
+const C14N_ESC_AMP: &str = "&amp;";
+const C14N_ESC_LT: &str = "&lt;";
+const C14N_ESC_GT: &str = "&gt;";
+const C14N_ESC_QUOT: &str = "&quot;";
+const C14N_ESC_TAB: &str = "&#x9;";
+const C14N_ESC_LF: &str = "&#xA;";
+const C14N_ESC_CR: &str = "&#xD;";
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl CanonicalOptions {
+    ///
+    /// Construct a new `CanonicalOptions` instance with the default values.
+    ///
+    pub fn new() -> Self {
+        Default::default()
+    }
+    ///
+    /// Set whether comments are included in the output, that is _Canonical XML with comments_.
+    ///
+    pub fn with_comments(self, comments: bool) -> Self {
+        Self { comments }
+    }
+    ///
+    /// Return `true` if comments are included in the output, else `false`.
+    ///
+    pub fn comments(&self) -> bool {
+        self.comments
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl<'a> Canonical<'a> {
+    pub(crate) fn new(node: &'a RefNode, options: &'a CanonicalOptions) -> Self {
+        Self { node, options }
+    }
+
+    fn fmt_node(&self, node: &RefNode, rendered: &Namespaces, f: &mut Formatter<'_>) -> FmtResult {
+        match node.node_type() {
+            NodeType::Document => self.fmt_document(node, f),
+            NodeType::Element => self.fmt_element(node, rendered, f),
+            NodeType::Attribute => {
+                let value = normalized_value(node).unwrap_or_default();
+                write!(f, "{}=\"{}\"", node.node_name(), escape_value(&value))
+            }
+            NodeType::Text | NodeType::CData => {
+                write!(f, "{}", escape_content(&node.data_as_str()))
+            }
+            NodeType::ProcessingInstruction => match node.node_value() {
+                Some(data) if !data.is_empty() => write!(
+                    f,
+                    "{}{} {}{}",
+                    XML_PI_START,
+                    node.node_name(),
+                    data,
+                    XML_PI_END
+                ),
+                _ => write!(f, "{}{}{}", XML_PI_START, node.node_name(), XML_PI_END),
+            },
+            NodeType::Comment if self.options.comments => write!(
+                f,
+                "{}{}{}",
+                XML_COMMENT_START,
+                node.data_as_str(),
+                XML_COMMENT_END
+            ),
+            NodeType::EntityReference | NodeType::DocumentFragment => {
+                for child in node.child_nodes() {
+                    self.fmt_node(&child, rendered, f)?;
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn fmt_document(&self, document: &RefNode, f: &mut Formatter<'_>) -> FmtResult {
+        //
+        // The XML declaration and document type declaration are not part of the output, and
+        // nodes outside the document element are separated from it by a single line feed.
+        //
+        let mut before_element = true;
+        for child in document.child_nodes() {
+            match child.node_type() {
+                NodeType::Element => {
+                    self.fmt_element(&child, &Namespaces::new(), f)?;
+                    before_element = false;
+                }
+                NodeType::ProcessingInstruction => {
+                    self.fmt_outside_element(&child, before_element, f)?
+                }
+                NodeType::Comment if self.options.comments => {
+                    self.fmt_outside_element(&child, before_element, f)?
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn fmt_outside_element(
+        &self,
+        node: &RefNode,
+        before_element: bool,
+        f: &mut Formatter<'_>,
+    ) -> FmtResult {
+        if before_element {
+            self.fmt_node(node, &Namespaces::new(), f)?;
+            writeln!(f)
+        } else {
+            writeln!(f)?;
+            self.fmt_node(node, &Namespaces::new(), f)
+        }
+    }
+
+    fn fmt_element(
+        &self,
+        element: &RefNode,
+        rendered: &Namespaces,
+        f: &mut Formatter<'_>,
+    ) -> FmtResult {
+        let is_apex = element == self.node;
+        let mut in_scope = if is_apex {
+            inherited_namespaces(element)
+        } else {
+            rendered.clone()
+        };
+        add_namespace_declarations(element, &mut in_scope);
+
+        write!(f, "{}{}", XML_ELEMENT_START_START, element.node_name())?;
+        let mut now_rendered = rendered.clone();
+        for (prefix, namespace_uri) in &in_scope {
+            if prefix == XML_NS_ATTRIBUTE || rendered.get(prefix) == Some(namespace_uri) {
+                continue;
+            }
+            if prefix.is_empty() {
+                if namespace_uri.is_empty() && rendered.get(prefix).is_none() {
+                    continue;
+                }
+                write!(f, " {}=\"", XMLNS_NS_ATTRIBUTE)?;
+            } else {
+                write!(
+                    f,
+                    " {}{}{}=\"",
+                    XMLNS_NS_ATTRIBUTE, XML_NS_SEPARATOR, prefix
+                )?;
+            }
+            write!(f, "{}\"", escape_value(namespace_uri))?;
+            let _safe_to_ignore = now_rendered.insert(prefix.clone(), namespace_uri.clone());
+        }
+
+        let mut attributes: Vec<(String, String, RefNode)> = element
+            .borrow()
+            .attribute_nodes()
+            .into_iter()
+            .filter(|attribute| !is_namespace_declaration(&attribute.node_name()))
+            .map(|attribute| {
+                let name = attribute.node_name();
+                let namespace_uri = match (name.namespace_uri(), name.prefix()) {
+                    (Some(namespace_uri), _) => namespace_uri.clone(),
+                    (None, Some(prefix)) if prefix == XML_NS_ATTRIBUTE => XML_NS_URI.to_string(),
+                    (None, Some(prefix)) => in_scope.get(prefix).cloned().unwrap_or_default(),
+                    (None, None) => String::new(),
+                };
+                (namespace_uri, name.local_name().clone(), attribute)
+            })
+            .collect();
+        if is_apex {
+            for attribute in inherited_xml_attributes(element) {
+                let local_name = attribute.node_name().local_name().clone();
+                if !attributes
+                    .iter()
+                    .any(|(uri, name, _)| uri == XML_NS_URI && *name == local_name)
+                {
+                    attributes.push((XML_NS_URI.to_string(), local_name, attribute));
+                }
+            }
+        }
+        attributes.sort_by(|lhs, rhs| (&lhs.0, &lhs.1).cmp(&(&rhs.0, &rhs.1)));
+        for (_, _, attribute) in &attributes {
+            write!(f, " ")?;
+            self.fmt_node(attribute, &now_rendered, f)?;
+        }
+        write!(f, "{}", XML_ELEMENT_START_END)?;
+
+        for child in element.child_nodes() {
+            self.fmt_node(&child, &now_rendered, f)?;
+        }
+        write!(
+            f,
+            "{}{}{}",
+            XML_ELEMENT_END_START,
+            element.node_name(),
+            XML_ELEMENT_END_END
+        )
+    }
+}
+
+impl Display for Canonical<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        self.fmt_node(self.node, &Namespaces::new(), f)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+//
+// The namespace declarations in scope from the ancestors of `element`, which are not themselves
+// part of the output when canonicalizing a subtree.
+//
+fn inherited_namespaces(element: &RefNode) -> Namespaces {
+    let mut namespaces = Namespaces::new();
+    for ancestor in ancestor_elements(element).iter().rev() {
+        add_namespace_declarations(ancestor, &mut namespaces);
+    }
+    namespaces
+}
+
+//
+// The attributes in the `xml` namespace of the ancestors of `element`, nearest first, which are
+// inherited by an element at the top of a canonicalized subtree.
+//
+fn inherited_xml_attributes(element: &RefNode) -> Vec<RefNode> {
+    ancestor_elements(element)
+        .iter()
+        .flat_map(|ancestor| ancestor.borrow().attribute_nodes())
+        .filter(|attribute| {
+            let name = attribute.node_name();
+            name.prefix().as_deref() == Some(XML_NS_ATTRIBUTE)
+                || name.namespace_uri().as_deref() == Some(XML_NS_URI)
+        })
+        .fold(Vec::new(), |mut result: Vec<RefNode>, attribute| {
+            let local_name = attribute.node_name().local_name().clone();
+            if !result
+                .iter()
+                .any(|other| *other.node_name().local_name() == local_name)
+            {
+                result.push(attribute);
+            }
+            result
+        })
+}
+
+fn ancestor_elements(node: &RefNode) -> Vec<RefNode> {
+    let mut ancestors = Vec::new();
+    let mut current = node.parent_node();
+    while let Some(parent) = current {
+        if parent.node_type() == NodeType::Element {
+            ancestors.push(parent.clone());
+        }
+        current = parent.parent_node();
+    }
+    ancestors
+}
+
+fn escape_content(input: &str) -> String {
+    let mut result = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            XML_ESC_AMP_CHAR => result.push_str(C14N_ESC_AMP),
+            XML_ESC_LT_CHAR => result.push_str(C14N_ESC_LT),
+            XML_ESC_GT_CHAR => result.push_str(C14N_ESC_GT),
+            XML_ESC_CR_CHAR => result.push_str(C14N_ESC_CR),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+fn escape_value(input: &str) -> String {
+    let mut result = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            XML_ESC_AMP_CHAR => result.push_str(C14N_ESC_AMP),
+            XML_ESC_LT_CHAR => result.push_str(C14N_ESC_LT),
+            XML_ESC_QUOT_CHAR => result.push_str(C14N_ESC_QUOT),
+            XML_ESC_TAB_CHAR => result.push_str(C14N_ESC_TAB),
+            XML_ESC_LF_CHAR => result.push_str(C14N_ESC_LF),
+            XML_ESC_CR_CHAR => result.push_str(C14N_ESC_CR),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(all(test, feature = "quick_parser"))]
+mod tests {
+    use super::*;
+    use crate::io::Serializer;
+    use crate::level2::convert::*;
+    use crate::parser::read_xml;
+
+    //
+    // From the W3C Canonical XML specification, §3.3 "Start and End Tags", less the document type
+    // declaration that adds a default attribute to `e9`, and the white space between elements
+    // which the parser does not retain.
+    //
+    const W3C_START_END_TAGS: &str = r#"<doc><e1   /><e2   ></e2><e3   name = "elem3"   id="elem3"   /><e4   name="elem4"   id="elem4"   ></e4><e5 a:attr="out" b:attr="sorted" attr2="all" attr="I'm"
+      xmlns:b="http://www.ietf.org"
+      xmlns:a="http://www.w3.org"
+      xmlns="http://example.org"/><e6 xmlns="" xmlns:a="http://www.w3.org"><e7 xmlns="http://www.ietf.org"><e8 xmlns="" xmlns:a="http://www.w3.org"><e9 xmlns="" xmlns:a="http://www.ietf.org"/></e8></e7></e6></doc>"#;
+
+    const W3C_START_END_TAGS_C14N: &str = concat!(
+        r#"<doc><e1></e1><e2></e2><e3 id="elem3" name="elem3"></e3><e4 id="elem4" name="elem4"></e4>"#,
+        r#"<e5 xmlns="http://example.org" xmlns:a="http://www.w3.org" xmlns:b="http://www.ietf.org" attr="I'm" attr2="all" b:attr="sorted" a:attr="out"></e5>"#,
+        r#"<e6 xmlns:a="http://www.w3.org"><e7 xmlns="http://www.ietf.org"><e8 xmlns="">"#,
+        r#"<e9 xmlns:a="http://www.ietf.org"></e9></e8></e7></e6></doc>"#
+    );
+
+    #[test]
+    fn test_default_options() {
+        let options = CanonicalOptions::default();
+        assert!(!options.comments());
+        assert_eq!(options, CanonicalOptions::new());
+        assert!(options.with_comments(true).comments());
+    }
+
+    #[test]
+    fn test_w3c_start_end_tags() {
+        let document_node = read_xml(W3C_START_END_TAGS).unwrap();
+        assert_eq!(
+            document_node.to_canonical_string(&CanonicalOptions::default()),
+            W3C_START_END_TAGS_C14N
+        );
+    }
+
+    #[test]
+    fn test_comments_and_processing_instructions() {
+        let document_node = read_xml(
+            "<?xml version=\"1.0\"?><?pi  ?><!--before--><doc><!--inside-->text</doc><!--after-->",
+        )
+        .unwrap();
+        assert_eq!(
+            document_node.to_canonical_string(&CanonicalOptions::default()),
+            "<?pi?>\n<doc>text</doc>"
+        );
+        assert_eq!(
+            document_node.to_canonical_string(&CanonicalOptions::default().with_comments(true)),
+            "<?pi?>\n<!--before-->\n<doc><!--inside-->text</doc>\n<!--after-->"
+        );
+    }
+
+    #[test]
+    fn test_escaping() {
+        let implementation = get_implementation();
+        let mut document_node = implementation
+            .create_document(None, Some("doc"), None)
+            .unwrap();
+        let document = as_document_mut(&mut document_node).unwrap();
+        let mut root_node = document.document_element().unwrap();
+        let _safe_to_ignore = root_node.set_attribute("attr", "a < b & \"c\" > 'd'");
+        let _safe_to_ignore =
+            root_node.append_child(document.create_text_node("x < y & \"z\" > 'w'\r"));
+        let _safe_to_ignore =
+            root_node.append_child(document.create_cdata_section("<cdata>").unwrap());
+        assert_eq!(
+            document_node.to_canonical_string(&CanonicalOptions::default()),
+            "<doc attr=\"a &lt; b &amp; &quot;c&quot; > 'd'\">x &lt; y &amp; \"z\" &gt; 'w'&#xD;&lt;cdata&gt;</doc>"
+        );
+    }
+
+    #[test]
+    fn test_subtree_inherits_namespaces() {
+        let document_node = read_xml(
+            r#"<doc xmlns="http://example.org" xmlns:a="http://www.w3.org" xml:lang="en"><e1 xml:space="preserve"><e2 a:attr="1"/></e1></doc>"#,
+        )
+        .unwrap();
+        let document = as_document(&document_node).unwrap();
+        let root_node = document.document_element().unwrap();
+        let e1_node = root_node.first_child().unwrap();
+        assert_eq!(
+            e1_node.to_canonical_string(&CanonicalOptions::default()),
+            r#"<e1 xmlns="http://example.org" xmlns:a="http://www.w3.org" xml:lang="en" xml:space="preserve"><e2 a:attr="1"></e2></e1>"#
+        );
+    }
+
+    #[test]
+    fn test_namespaces_without_declarations() {
+        let implementation = get_implementation();
+        let mut document_node = implementation
+            .create_document(Some("http://example.org"), Some("x:doc"), None)
+            .unwrap();
+        let document = as_document_mut(&mut document_node).unwrap();
+        let mut root_node = document.document_element().unwrap();
+        let child_node = document
+            .create_element_ns("http://example.org", "x:child")
+            .unwrap();
+        let _safe_to_ignore = root_node.append_child(child_node);
+        assert_eq!(
+            document_node.to_canonical_string(&CanonicalOptions::default()),
+            "<x:doc xmlns:x=\"http://example.org\"><x:child></x:child></x:doc>"
+        );
+    }
+}
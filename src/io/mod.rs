@@ -0,0 +1,137 @@
+/*!
+Provides additional serialization capabilities for DOM trees beyond the single-line output of the
+`Display` implementation for `RefNode`.
+
+This module also provides the functions used by both serializations to escape text content and
+attribute values, [`escape_text`](fn.escape_text.html) and
+[`escape_attribute`](fn.escape_attribute.html), which are useful on their own.
+
+# Example
+
+```rust
+use xml_dom::io::{PrettyOptions, Serializer};
+use xml_dom::level2::*;
+use xml_dom::level2::convert::*;
+
+let implementation = get_implementation();
+let mut document_node = implementation
+    .create_document(None, Some("config"), None)
+    .unwrap();
+let document = as_document_mut(&mut document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+let mut child_node = document.create_element("name").unwrap();
+let _safe_to_ignore = child_node.append_child(document.create_text_node("example"));
+let _safe_to_ignore = root_node.append_child(child_node);
+
+let options = PrettyOptions::default().with_indent("    ");
+assert_eq!(
+    document_node.to_pretty_string(&options),
+    "<config>\n    <name>example</name>\n</config>"
+);
+```
+
+*/
+
+use crate::level2::RefNode;
+use std::io::{Result as IoResult, Write};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This trait provides serialization functions for `RefNode`, all of which leave the existing
+/// `Display` implementation unchanged.
+///
+pub trait Serializer {
+    ///
+    /// Serialize this node, and its children, into a string formatted according to the provided
+    /// options. Each element whose children are only elements, comments, or processing
+    /// instructions is written with its children indented on separate lines. Elements with mixed
+    /// content are written exactly as `Display` would, so that significant whitespace is not
+    /// altered; elements with only textual content are written on a single line unless
+    /// `PrettyOptions::with_indent_text_only` is set. Attributes are always written on the same
+    /// line as the element name.
+    ///
+    fn to_pretty_string(&self, options: &PrettyOptions) -> String;
+    ///
+    /// Serialize this node, and its children, as
+    /// [Canonical XML Version 1.0](https://www.w3.org/TR/xml-c14n/), with or without comments
+    /// according to the provided options. The XML and document type declarations are omitted,
+    /// empty elements are written as start and end tag pairs, namespace declarations are written
+    /// before attributes, each sorted, and text and attribute values are escaped as the
+    /// specification requires. The content of CDATA sections and entity references is written as
+    /// text.
+    ///
+    /// When this node is an element within a larger document, the namespace declarations and
+    /// `xml:` attributes in scope from its ancestors are written on this element.
+    ///
+    fn to_canonical_string(&self, options: &CanonicalOptions) -> String;
+    ///
+    /// Serialize this node, and its children, to `writer` according to the provided options, which
+    /// control the XML declaration written before a document, the quote character used for
+    /// attribute values, whether empty elements are self-closing, and a trailing new line. With
+    /// the default options the output is the same as `to_string()`.
+    ///
+    /// The output is UTF-8 regardless of any declared encoding. It is streamed to the writer as
+    /// each node is visited, using an explicit stack rather than recursion so that very deep
+    /// trees can be written; the writer is not flushed, and may usefully be buffered.
+    ///
+    /// Fails with `ErrorKind::InvalidData` if a node to be written is already mutably borrowed;
+    /// in the same case `Display` returns `fmt::Error` rather than panicking.
+    ///
+    fn write_to<W: Write>(&self, writer: W, options: &XmlWriteOptions) -> IoResult<()>;
+    ///
+    /// Describe this node, and its children, as an indented outline for debugging; unlike the
+    /// derived `Debug` output each node is a single line with its node type, name, a truncated
+    /// preview of its value, and for elements the list of attributes. The provided options
+    /// control the indentation, the depth below which children are only counted, and the length
+    /// of value previews.
+    ///
+    /// Only child links are followed, so the outline may be produced for nodes whose parent or
+    /// owner document has been dropped.
+    ///
+    fn to_tree_string(&self, options: &TreeOptions) -> String;
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Serializer for RefNode {
+    fn to_pretty_string(&self, options: &PrettyOptions) -> String {
+        pretty::Pretty::new(self, options).to_string()
+    }
+
+    fn to_canonical_string(&self, options: &CanonicalOptions) -> String {
+        canonical::Canonical::new(self, options).to_string()
+    }
+
+    fn write_to<W: Write>(&self, writer: W, options: &XmlWriteOptions) -> IoResult<()> {
+        writer::XmlWriter::new(writer, options).write(self)
+    }
+
+    fn to_tree_string(&self, options: &TreeOptions) -> String {
+        tree::Tree::new(self, options).to_string()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Modules
+// ------------------------------------------------------------------------------------------------
+
+mod canonical;
+pub use canonical::CanonicalOptions;
+
+mod pretty;
+pub use pretty::{NewLine, PrettyOptions};
+
+mod tree;
+pub use tree::TreeOptions;
+
+pub(crate) mod writer;
+pub use writer::{Declaration, EmptyElement, Quote, XmlWriteOptions};
+
+pub use crate::shared::syntax::{
+    escape_attribute, escape_attribute_with, escape_text, escape_text_with, InvalidCharHandling,
+};
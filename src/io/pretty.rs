@@ -0,0 +1,340 @@
+use crate::level2::ext::convert::as_document_decl;
+use crate::level2::*;
+use crate::shared::syntax::*;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The line ending written between lines of pretty-printed output.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum NewLine {
+    /// A single line feed, `"\n"`.
+    #[default]
+    Lf,
+    /// A carriage return followed by line feed, `"\r\n"`.
+    CrLf,
+}
+
+///
+/// Options that control the output of
+/// [`Serializer::to_pretty_string`](trait.Serializer.html#tymethod.to_pretty_string).
+///
+/// The default options indent with two spaces, use `NewLine::Lf` line endings, and leave elements
+/// with only textual content on a single line.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrettyOptions {
+    indent: String,
+    indent_text_only: bool,
+    new_line: NewLine,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+pub(crate) struct Pretty<'a> {
+    node: &'a RefNode,
+    options: &'a PrettyOptions,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Content {
+    Empty,
+    Markup,
+    Text,
+    Mixed,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for NewLine {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{}",
+            match self {
+                NewLine::Lf => "\n",
+                NewLine::CrLf => "\r\n",
+            }
+        )
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Default for PrettyOptions {
+    fn default() -> Self {
+        Self {
+            indent: "  ".to_string(),
+            indent_text_only: false,
+            new_line: NewLine::default(),
+        }
+    }
+}
+
+impl PrettyOptions {
+    ///
+    /// Construct a new `PrettyOptions` instance with the default values.
+    ///
+    pub fn new() -> Self {
+        Default::default()
+    }
+    ///
+    /// Set the string used for each level of indentation, usually some number of spaces, or a
+    /// single tab character.
+    ///
+    pub fn with_indent(self, indent: &str) -> Self {
+        Self {
+            indent: indent.to_string(),
+            ..self
+        }
+    }
+    ///
+    /// Set whether elements that contain only text will have that text indented on a separate
+    /// line.
+    ///
+    pub fn with_indent_text_only(self, indent_text_only: bool) -> Self {
+        Self {
+            indent_text_only,
+            ..self
+        }
+    }
+    ///
+    /// Set the line ending used between lines.
+    ///
+    pub fn with_new_line(self, new_line: NewLine) -> Self {
+        Self { new_line, ..self }
+    }
+    ///
+    /// Return the string used for each level of indentation.
+    ///
+    pub fn indent(&self) -> &str {
+        &self.indent
+    }
+    ///
+    /// Return `true` if elements containing only text have that text indented, else `false`.
+    ///
+    pub fn indent_text_only(&self) -> bool {
+        self.indent_text_only
+    }
+    ///
+    /// Return the line ending used between lines.
+    ///
+    pub fn new_line(&self) -> &NewLine {
+        &self.new_line
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl<'a> Pretty<'a> {
+    pub(crate) fn new(node: &'a RefNode, options: &'a PrettyOptions) -> Self {
+        Self { node, options }
+    }
+
+    fn fmt_indent(&self, depth: usize, f: &mut Formatter<'_>) -> FmtResult {
+        for _ in 0..depth {
+            write!(f, "{}", self.options.indent)?;
+        }
+        Ok(())
+    }
+
+    fn fmt_node(&self, node: &RefNode, depth: usize, f: &mut Formatter<'_>) -> FmtResult {
+        match node.node_type() {
+            NodeType::Document => self.fmt_document(node, f),
+            NodeType::Element => {
+                self.fmt_indent(depth, f)?;
+                self.fmt_element(node, depth, f)
+            }
+            _ => {
+                self.fmt_indent(depth, f)?;
+                write!(f, "{}", node)
+            }
+        }
+    }
+
+    fn fmt_document(&self, document: &RefNode, f: &mut Formatter<'_>) -> FmtResult {
+        let document = as_document_decl(document).unwrap();
+        let mut first = true;
+        if let Some(xml_declaration) = &document.xml_declaration() {
+            write!(f, "{}", xml_declaration)?;
+            first = false;
+        }
+        for child in document.child_nodes() {
+            if !first {
+                write!(f, "{}", self.options.new_line)?;
+            }
+            self.fmt_node(&child, 0, f)?;
+            first = false;
+        }
+        Ok(())
+    }
+
+    fn fmt_element(&self, element: &RefNode, depth: usize, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}{}", XML_ELEMENT_START_START, element.node_name())?;
+        let attributes = element.borrow().attribute_nodes();
+        for attr in attributes {
+            write!(f, " {}", attr)?;
+        }
+        write!(f, "{}", XML_ELEMENT_START_END)?;
+        let child_nodes = element.child_nodes().to_vec();
+        match content_of(&child_nodes) {
+            Content::Empty => {}
+            Content::Markup => {
+                for child in &child_nodes {
+                    write!(f, "{}", self.options.new_line)?;
+                    self.fmt_node(child, depth + 1, f)?;
+                }
+                write!(f, "{}", self.options.new_line)?;
+                self.fmt_indent(depth, f)?;
+            }
+            Content::Text if self.options.indent_text_only => {
+                write!(f, "{}", self.options.new_line)?;
+                self.fmt_indent(depth + 1, f)?;
+                for child in &child_nodes {
+                    write!(f, "{}", child)?;
+                }
+                write!(f, "{}", self.options.new_line)?;
+                self.fmt_indent(depth, f)?;
+            }
+            Content::Text | Content::Mixed => {
+                for child in &child_nodes {
+                    write!(f, "{}", child)?;
+                }
+            }
+        }
+        write!(
+            f,
+            "{}{}{}",
+            XML_ELEMENT_END_START,
+            element.node_name(),
+            XML_ELEMENT_END_END
+        )
+    }
+}
+
+impl Display for Pretty<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        self.fmt_node(self.node, 0, f)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn content_of(child_nodes: &[RefNode]) -> Content {
+    let mut has_markup = false;
+    let mut has_text = false;
+    for child in child_nodes {
+        match child.node_type() {
+            NodeType::Element | NodeType::Comment | NodeType::ProcessingInstruction => {
+                has_markup = true
+            }
+            _ => has_text = true,
+        }
+    }
+    match (has_markup, has_text) {
+        (false, false) => Content::Empty,
+        (true, false) => Content::Markup,
+        (false, true) => Content::Text,
+        (true, true) => Content::Mixed,
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::Serializer;
+    use crate::level2::convert::*;
+
+    fn make_document() -> RefNode {
+        let implementation = get_implementation();
+        let mut document_node = implementation
+            .create_document(None, Some("root"), None)
+            .unwrap();
+        let document = as_document_mut(&mut document_node).unwrap();
+        let mut root_node = document.document_element().unwrap();
+        let _safe_to_ignore = root_node.set_attribute("id", "1");
+
+        let mut list_node = document.create_element("list").unwrap();
+        for name in ["one", "two"] {
+            let mut item_node = document.create_element("item").unwrap();
+            let _safe_to_ignore = item_node.append_child(document.create_text_node(name));
+            let _safe_to_ignore = list_node.append_child(item_node);
+        }
+        let _safe_to_ignore =
+            list_node.append_child(document.create_comment("end of list").unwrap());
+        let _safe_to_ignore = root_node.append_child(list_node);
+
+        let mut mixed_node = document.create_element("p").unwrap();
+        let _safe_to_ignore = mixed_node.append_child(document.create_text_node("some "));
+        let mut bold_node = document.create_element("b").unwrap();
+        let _safe_to_ignore = bold_node.append_child(document.create_text_node("bold"));
+        let _safe_to_ignore = mixed_node.append_child(bold_node);
+        let _safe_to_ignore = mixed_node.append_child(document.create_text_node(" text"));
+        let _safe_to_ignore = root_node.append_child(mixed_node);
+
+        let _safe_to_ignore = root_node.append_child(document.create_element("empty").unwrap());
+        document_node
+    }
+
+    #[test]
+    fn test_default_options() {
+        let options = PrettyOptions::default();
+        assert_eq!(options.indent(), "  ");
+        assert!(!options.indent_text_only());
+        assert_eq!(options.new_line(), &NewLine::Lf);
+        assert_eq!(options, PrettyOptions::new());
+    }
+
+    #[test]
+    fn test_pretty_default() {
+        let document_node = make_document();
+        assert_eq!(
+            document_node.to_pretty_string(&PrettyOptions::default()),
+            r#"<root id="1">
+  <list>
+    <item>one</item>
+    <item>two</item>
+    <!--end of list-->
+  </list>
+  <p>some <b>bold</b> text</p>
+  <empty></empty>
+</root>"#
+        );
+    }
+
+    #[test]
+    fn test_pretty_indent_text_only() {
+        let document_node = make_document();
+        let options = PrettyOptions::default()
+            .with_indent("\t")
+            .with_indent_text_only(true)
+            .with_new_line(NewLine::CrLf);
+        assert_eq!(
+            document_node.to_pretty_string(&options),
+            "<root id=\"1\">\r\n\t<list>\r\n\t\t<item>\r\n\t\t\tone\r\n\t\t</item>\r\n\t\t<item>\r\n\t\t\ttwo\r\n\t\t</item>\r\n\t\t<!--end of list-->\r\n\t</list>\r\n\t<p>some <b>bold</b> text</p>\r\n\t<empty></empty>\r\n</root>"
+        );
+    }
+
+    #[test]
+    fn test_display_unchanged() {
+        let document_node = make_document();
+        assert_eq!(
+            document_node.to_string(),
+            r#"<root id="1"><list><item>one</item><item>two</item><!--end of list--></list><p>some <b>bold</b> text</p><empty></empty></root>"#
+        );
+    }
+}
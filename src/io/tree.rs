@@ -0,0 +1,298 @@
+use crate::level2::*;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Options that control the output of
+/// [`Serializer::to_tree_string`](trait.Serializer.html#tymethod.to_tree_string).
+///
+/// The default options indent with two spaces, show the whole tree, and truncate values to 40
+/// characters.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TreeOptions {
+    indent: String,
+    max_depth: Option<usize>,
+    max_value_length: usize,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+pub(crate) struct Tree<'a> {
+    node: &'a RefNode,
+    options: &'a TreeOptions,
+}
+
+const TRUNCATED: &str = "...";
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Default for TreeOptions {
+    fn default() -> Self {
+        Self {
+            indent: "  ".to_string(),
+            max_depth: None,
+            max_value_length: 40,
+        }
+    }
+}
+
+impl TreeOptions {
+    ///
+    /// Construct a new `TreeOptions` instance with the default values.
+    ///
+    pub fn new() -> Self {
+        Default::default()
+    }
+    ///
+    /// Set the string used for each level of indentation.
+    ///
+    pub fn with_indent(self, indent: &str) -> Self {
+        Self {
+            indent: indent.to_string(),
+            ..self
+        }
+    }
+    ///
+    /// Set the depth below which nodes are not shown; the children of a node at this depth are
+    /// summarized by a count. The node the outline starts from has a depth of zero.
+    ///
+    pub fn with_max_depth(self, max_depth: usize) -> Self {
+        Self {
+            max_depth: Some(max_depth),
+            ..self
+        }
+    }
+    ///
+    /// Set the number of characters of each node, or attribute, value that are shown.
+    ///
+    pub fn with_max_value_length(self, max_value_length: usize) -> Self {
+        Self {
+            max_value_length,
+            ..self
+        }
+    }
+    ///
+    /// Return the string used for each level of indentation.
+    ///
+    pub fn indent(&self) -> &str {
+        &self.indent
+    }
+    ///
+    /// Return the depth below which nodes are not shown, if set.
+    ///
+    pub fn max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+    ///
+    /// Return the number of characters of each value that are shown.
+    ///
+    pub fn max_value_length(&self) -> usize {
+        self.max_value_length
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl<'a> Tree<'a> {
+    pub(crate) fn new(node: &'a RefNode, options: &'a TreeOptions) -> Self {
+        Self { node, options }
+    }
+
+    fn fmt_indent(&self, depth: usize, f: &mut Formatter<'_>) -> FmtResult {
+        for _ in 0..depth {
+            write!(f, "{}", self.options.indent)?;
+        }
+        Ok(())
+    }
+
+    fn fmt_line(&self, node: &RefNode, depth: usize, f: &mut Formatter<'_>) -> FmtResult {
+        self.fmt_indent(depth, f)?;
+        let node_type = node.node_type();
+        write!(f, "{:?}", node_type)?;
+        match node_type {
+            NodeType::Text
+            | NodeType::CData
+            | NodeType::Comment
+            | NodeType::Document
+            | NodeType::DocumentFragment => {}
+            _ => write!(f, " {}", node.node_name())?,
+        }
+        if node_type == NodeType::Attribute {
+            write!(f, " {:?}", self.preview(&raw_value(node)))?;
+        } else if let Some(value) = node.node_value() {
+            write!(f, " {:?}", self.preview(&value))?;
+        }
+        if node_type == NodeType::Element {
+            let attributes = node.borrow().attribute_nodes();
+            if !attributes.is_empty() {
+                let attributes: Vec<String> = attributes
+                    .iter()
+                    .map(|attribute| {
+                        format!(
+                            "{}={:?}",
+                            attribute.node_name(),
+                            self.preview(&raw_value(attribute))
+                        )
+                    })
+                    .collect();
+                write!(f, " [{}]", attributes.join(", "))?;
+            }
+        }
+        writeln!(f)
+    }
+
+    fn preview(&self, value: &str) -> String {
+        if value.chars().count() > self.options.max_value_length {
+            let mut preview: String = value.chars().take(self.options.max_value_length).collect();
+            preview.push_str(TRUNCATED);
+            preview
+        } else {
+            value.to_string()
+        }
+    }
+}
+
+impl Display for Tree<'_> {
+    //
+    // This uses an explicit stack, and only follows child links, so that deep trees cannot
+    // overflow the call stack and no weak parent, or owner, reference is needed.
+    //
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let mut stack = vec![(self.node.clone(), 0)];
+        while let Some((node, depth)) = stack.pop() {
+            self.fmt_line(&node, depth, f)?;
+            let child_nodes = node.child_nodes().to_vec();
+            if node.node_type() == NodeType::Attribute || child_nodes.is_empty() {
+                continue;
+            }
+            if self
+                .options
+                .max_depth
+                .is_some_and(|max_depth| depth >= max_depth)
+            {
+                self.fmt_indent(depth + 1, f)?;
+                writeln!(f, "{} ({} children)", TRUNCATED, child_nodes.len())?;
+            } else {
+                stack.extend(
+                    child_nodes
+                        .into_iter()
+                        .rev()
+                        .map(|child_node| (child_node, depth + 1)),
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+//
+// The unnormalized value of an attribute; normalization requires the owner document to resolve
+// entities, which may no longer exist.
+//
+fn raw_value(attribute: &RefNode) -> String {
+    attribute
+        .child_nodes()
+        .iter()
+        .filter_map(|child_node| child_node.node_value())
+        .collect()
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::Serializer;
+    use crate::level2::convert::*;
+
+    fn make_document() -> RefNode {
+        let implementation = get_implementation();
+        let mut document_node = implementation
+            .create_document(None, Some("root"), None)
+            .unwrap();
+        let document = as_document_mut(&mut document_node).unwrap();
+        let mut root_node = document.document_element().unwrap();
+        let _safe_to_ignore = root_node.set_attribute("id", "1");
+        let mut item_node = document.create_element("item").unwrap();
+        let _safe_to_ignore =
+            item_node.append_child(document.create_text_node("a rather long\nline of text"));
+        let _safe_to_ignore = root_node.append_child(item_node);
+        let _safe_to_ignore = root_node.append_child(document.create_comment("note").unwrap());
+        document_node
+    }
+
+    #[test]
+    fn test_default_options() {
+        let options = TreeOptions::default();
+        assert_eq!(options.indent(), "  ");
+        assert!(options.max_depth().is_none());
+        assert_eq!(options.max_value_length(), 40);
+        assert_eq!(options, TreeOptions::new());
+    }
+
+    #[test]
+    fn test_tree_string() {
+        let document_node = make_document();
+        assert_eq!(
+            document_node.to_tree_string(&TreeOptions::default()),
+            r#"Document
+  Element root [id="1"]
+    Element item
+      Text "a rather long\nline of text"
+    Comment "note"
+"#
+        );
+    }
+
+    #[test]
+    fn test_tree_string_limits() {
+        let document_node = make_document();
+        let options = TreeOptions::default()
+            .with_indent("| ")
+            .with_max_depth(2)
+            .with_max_value_length(8);
+        assert_eq!(
+            document_node.to_tree_string(&options),
+            r#"Document
+| Element root [id="1"]
+| | Element item
+| | | ... (1 children)
+| | Comment "note"
+"#
+        );
+
+        let options = TreeOptions::default().with_max_value_length(8);
+        assert!(document_node
+            .to_tree_string(&options)
+            .contains("Text \"a rather...\""));
+    }
+
+    #[test]
+    fn test_tree_string_without_document() {
+        let element_node = {
+            let document_node = make_document();
+            let document = as_document(&document_node).unwrap();
+            let mut element_node = document.document_element().unwrap();
+            let _safe_to_ignore = element_node.set_attribute("lang", "en");
+            element_node
+        };
+        assert!(element_node.owner_document().is_none());
+        assert!(element_node
+            .to_tree_string(&TreeOptions::default())
+            .starts_with("Element root [id=\"1\", lang=\"en\"]\n"));
+    }
+}
@@ -0,0 +1,594 @@
+use crate::level2::ext::convert::as_document_decl;
+use crate::level2::ext::XmlVersion;
+use crate::level2::trait_impls::normalized_value;
+use crate::level2::*;
+use crate::shared::display;
+use crate::shared::syntax::*;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::io::{Error as IoError, ErrorKind, Result as IoResult, Write};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Determines whether an XML declaration is written before a document.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum Declaration {
+    /// Write the document's own declaration, if it has one.
+    #[default]
+    AsDocument,
+    /// Always write a declaration, using the document's version if it has a declaration, else
+    /// version 1.0.
+    Always,
+    /// Never write a declaration.
+    Never,
+}
+
+///
+/// The character used to delimit attribute values, and the values in the XML declaration.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum Quote {
+    /// The double quote character, `"`.
+    #[default]
+    Double,
+    /// The single quote, or apostrophe, character, `'`.
+    Single,
+}
+
+///
+/// Determines how an element with no children is written.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum EmptyElement {
+    /// As a start tag and end tag pair, `<name></name>`.
+    #[default]
+    Expanded,
+    /// As a single empty-element tag, `<name/>`.
+    SelfClosing,
+}
+
+///
+/// Options that control the output of
+/// [`Serializer::write_to`](trait.Serializer.html#tymethod.write_to).
+///
+/// The default options produce the same output as the `Display` implementation for `RefNode`,
+/// except that characters that are not legal in XML 1.0 are rejected rather than written as
+/// character references.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct XmlWriteOptions {
+    declaration: Declaration,
+    encoding: Option<String>,
+    standalone: Option<bool>,
+    quote: Quote,
+    empty_element: EmptyElement,
+    trailing_new_line: bool,
+    invalid_chars: InvalidCharHandling,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+pub(crate) struct XmlWriter<'a, W: Write> {
+    writer: W,
+    options: &'a XmlWriteOptions,
+}
+
+enum Step {
+    Start(RefNode),
+    End(RefNode),
+}
+
+//
+// Writes a document type, entity, or notation, using the `display` module.
+//
+struct MarkupDeclaration<'a>(&'a RefNode);
+
+//
+// Allows the `Display` implementation for `RefNode` to use `XmlWriter`.
+//
+pub(crate) struct FormatterWriter<'a, 'b>(pub(crate) &'a mut Formatter<'b>);
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Quote {
+    fn as_char(&self) -> char {
+        match self {
+            Quote::Double => XML_ESC_QUOT_CHAR,
+            Quote::Single => XML_ESC_APOS_CHAR,
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl XmlWriteOptions {
+    ///
+    /// Construct a new `XmlWriteOptions` instance with the default values.
+    ///
+    pub fn new() -> Self {
+        Default::default()
+    }
+    ///
+    /// Set whether an XML declaration is written before a document.
+    ///
+    pub fn with_declaration(self, declaration: Declaration) -> Self {
+        Self {
+            declaration,
+            ..self
+        }
+    }
+    ///
+    /// Set the encoding name written in the XML declaration, replacing any in the document's own
+    /// declaration. Note that the output is always UTF-8, this only changes the declared value.
+    ///
+    pub fn with_encoding(self, encoding: &str) -> Self {
+        Self {
+            encoding: Some(encoding.to_string()),
+            ..self
+        }
+    }
+    ///
+    /// Set the standalone value written in the XML declaration, replacing any in the document's
+    /// own declaration.
+    ///
+    pub fn with_standalone(self, standalone: bool) -> Self {
+        Self {
+            standalone: Some(standalone),
+            ..self
+        }
+    }
+    ///
+    /// Set the character used to delimit attribute values.
+    ///
+    pub fn with_quote(self, quote: Quote) -> Self {
+        Self { quote, ..self }
+    }
+    ///
+    /// Set how elements with no children are written.
+    ///
+    pub fn with_empty_element(self, empty_element: EmptyElement) -> Self {
+        Self {
+            empty_element,
+            ..self
+        }
+    }
+    ///
+    /// Set whether a single line feed is written at the end of the output.
+    ///
+    pub fn with_trailing_new_line(self, trailing_new_line: bool) -> Self {
+        Self {
+            trailing_new_line,
+            ..self
+        }
+    }
+    ///
+    /// Set how characters that are not legal in XML 1.0 are handled in text and attribute values;
+    /// with `InvalidCharHandling::Reject`, the default, writing fails with an error of kind
+    /// `InvalidData`.
+    ///
+    pub fn with_invalid_chars(self, invalid_chars: InvalidCharHandling) -> Self {
+        Self {
+            invalid_chars,
+            ..self
+        }
+    }
+    ///
+    /// Return whether an XML declaration is written before a document.
+    ///
+    pub fn declaration(&self) -> &Declaration {
+        &self.declaration
+    }
+    ///
+    /// Return the encoding name written in the XML declaration, if set.
+    ///
+    pub fn encoding(&self) -> Option<&String> {
+        self.encoding.as_ref()
+    }
+    ///
+    /// Return the standalone value written in the XML declaration, if set.
+    ///
+    pub fn standalone(&self) -> Option<bool> {
+        self.standalone
+    }
+    ///
+    /// Return the character used to delimit attribute values.
+    ///
+    pub fn quote(&self) -> &Quote {
+        &self.quote
+    }
+    ///
+    /// Return how elements with no children are written.
+    ///
+    pub fn empty_element(&self) -> &EmptyElement {
+        &self.empty_element
+    }
+    ///
+    /// Return `true` if a line feed is written at the end of the output, else `false`.
+    ///
+    pub fn trailing_new_line(&self) -> bool {
+        self.trailing_new_line
+    }
+    ///
+    /// Return how characters that are not legal in XML 1.0 are handled.
+    ///
+    pub fn invalid_chars(&self) -> InvalidCharHandling {
+        self.invalid_chars
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl<'a, W: Write> XmlWriter<'a, W> {
+    pub(crate) fn new(writer: W, options: &'a XmlWriteOptions) -> Self {
+        Self { writer, options }
+    }
+
+    //
+    // Write `node` and its descendants; this uses an explicit stack, rather than recursion, so
+    // that deeply nested documents cannot overflow the call stack.
+    //
+    pub(crate) fn write(&mut self, node: &RefNode) -> IoResult<()> {
+        //
+        // Attribute values are normalized using the document type, so the owner document must be
+        // readable as well as each node written.
+        //
+        readable(node)?;
+        if let Some(document) = node.owner_document() {
+            readable(&document)?;
+        }
+        let mut stack = vec![Step::Start(node.clone())];
+        while let Some(step) = stack.pop() {
+            match step {
+                Step::Start(node) => {
+                    readable(&node)?;
+                    let child_nodes = match node.node_type() {
+                        NodeType::Document => {
+                            self.write_document_start(&node)?;
+                            node.child_nodes()
+                        }
+                        NodeType::Element => {
+                            let child_nodes = node.child_nodes();
+                            let is_empty = child_nodes.is_empty();
+                            self.write_element_start(&node, is_empty)?;
+                            if is_empty && self.options.empty_element == EmptyElement::SelfClosing {
+                                continue;
+                            }
+                            stack.push(Step::End(node));
+                            child_nodes
+                        }
+                        NodeType::DocumentFragment => node.child_nodes(),
+                        _ => {
+                            self.write_leaf(&node)?;
+                            continue;
+                        }
+                    };
+                    stack.extend(child_nodes.to_vec().into_iter().rev().map(Step::Start));
+                }
+                Step::End(element) => write!(
+                    self.writer,
+                    "{}{}{}",
+                    XML_ELEMENT_END_START,
+                    element.node_name(),
+                    XML_ELEMENT_END_END
+                )?,
+            }
+        }
+        if self.options.trailing_new_line {
+            writeln!(self.writer)?;
+        }
+        Ok(())
+    }
+
+    fn write_leaf(&mut self, node: &RefNode) -> IoResult<()> {
+        match node.node_type() {
+            NodeType::Attribute => self.write_attribute(node),
+            NodeType::Text => {
+                let data = escape_text_with(&node.data_as_str(), self.options.invalid_chars)
+                    .map_err(invalid_data)?;
+                write!(self.writer, "{}", data)
+            }
+            NodeType::CData => write!(
+                self.writer,
+                "{}{}{}",
+                XML_CDATA_START,
+                node.data_as_str(),
+                XML_CDATA_END
+            ),
+            NodeType::Comment => write!(
+                self.writer,
+                "{}{}{}",
+                XML_COMMENT_START,
+                node.data_as_str(),
+                XML_COMMENT_END
+            ),
+            NodeType::ProcessingInstruction => match node.node_value() {
+                None => write!(
+                    self.writer,
+                    "{}{}{}",
+                    XML_PI_START,
+                    node.node_name(),
+                    XML_PI_END
+                ),
+                Some(data) => write!(
+                    self.writer,
+                    "{}{} {}{}",
+                    XML_PI_START,
+                    node.node_name(),
+                    data,
+                    XML_PI_END
+                ),
+            },
+            NodeType::EntityReference => write!(
+                self.writer,
+                "{}{}{}",
+                XML_ENTITYREF_START,
+                node.node_name(),
+                XML_ENTITYREF_END
+            ),
+            _ => write!(self.writer, "{}", MarkupDeclaration(node)),
+        }
+    }
+
+    fn write_document_start(&mut self, document: &RefNode) -> IoResult<()> {
+        let document = as_document_decl(document).unwrap();
+        let xml_declaration = document.xml_declaration();
+        let version = match (&self.options.declaration, &xml_declaration) {
+            (Declaration::Never, _) | (Declaration::AsDocument, None) => None,
+            (_, Some(xml_declaration)) => Some(xml_declaration.version()),
+            (Declaration::Always, None) => Some(XmlVersion::V10),
+        };
+        if let Some(version) = version {
+            let quote = self.options.quote.as_char();
+            write!(
+                self.writer,
+                "{} {}={}{}{}",
+                XML_DECL_START, XML_DECL_VERSION, quote, version, quote
+            )?;
+            let encoding = self
+                .options
+                .encoding
+                .clone()
+                .or_else(|| xml_declaration.as_ref().and_then(|decl| decl.encoding()));
+            if let Some(encoding) = encoding {
+                write!(
+                    self.writer,
+                    " {}={}{}{}",
+                    XML_DECL_ENCODING, quote, encoding, quote
+                )?;
+            }
+            let standalone = self
+                .options
+                .standalone
+                .or_else(|| xml_declaration.as_ref().and_then(|decl| decl.standalone()));
+            if let Some(standalone) = standalone {
+                write!(
+                    self.writer,
+                    " {}={}{}{}",
+                    XML_DECL_STANDALONE,
+                    quote,
+                    if standalone {
+                        XML_DECL_STANDALONE_YES
+                    } else {
+                        XML_DECL_STANDALONE_NO
+                    },
+                    quote
+                )?;
+            }
+            write!(self.writer, "{}", XML_DECL_END)?;
+        }
+        Ok(())
+    }
+
+    fn write_element_start(&mut self, element: &RefNode, is_empty: bool) -> IoResult<()> {
+        write!(
+            self.writer,
+            "{}{}",
+            XML_ELEMENT_START_START,
+            element.node_name()
+        )?;
+        let attributes = element.borrow().attribute_nodes();
+        for attribute in attributes {
+            write!(self.writer, " ")?;
+            self.write_attribute(&attribute)?;
+        }
+        if is_empty && self.options.empty_element == EmptyElement::SelfClosing {
+            write!(self.writer, "{}", XML_EMPTY_ELEMENT_END)
+        } else {
+            write!(self.writer, "{}", XML_ELEMENT_START_END)
+        }
+    }
+
+    fn write_attribute(&mut self, attribute: &RefNode) -> IoResult<()> {
+        readable(attribute)?;
+        for child_node in attribute.child_nodes() {
+            readable(&child_node)?;
+        }
+        let quote = self.options.quote.as_char();
+        let value = normalized_value(attribute).unwrap_or_default();
+        let value =
+            escape_attribute_with(&value, self.options.invalid_chars).map_err(invalid_data)?;
+        write!(
+            self.writer,
+            "{}={}{}{}",
+            attribute.node_name(),
+            quote,
+            value,
+            quote
+        )
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for MarkupDeclaration<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        display::fmt_declaration(self.0, f)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Write for FormatterWriter<'_, '_> {
+    fn write(&mut self, buf: &[u8]) -> IoResult<usize> {
+        let s = std::str::from_utf8(buf).map_err(|e| IoError::new(ErrorKind::InvalidData, e))?;
+        self.0.write_str(s).map_err(IoError::other)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> IoResult<()> {
+        Ok(())
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn invalid_data(error: Error) -> IoError {
+    IoError::new(ErrorKind::InvalidData, error)
+}
+
+//
+// Fail, rather than panic, if `node` is mutably borrowed while it is being written.
+//
+fn readable(node: &RefNode) -> IoResult<()> {
+    let _safe_to_ignore = node.try_borrow().map_err(invalid_data)?;
+    Ok(())
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::Serializer;
+    use crate::level2::convert::*;
+    use crate::level2::ext::convert::as_document_decl_mut;
+    use crate::level2::ext::XmlDecl;
+
+    fn make_document() -> RefNode {
+        let implementation = get_implementation();
+        let mut document_node = implementation
+            .create_document(None, Some("root"), None)
+            .unwrap();
+        let document = as_document_mut(&mut document_node).unwrap();
+        let mut root_node = document.document_element().unwrap();
+        let _safe_to_ignore = root_node.set_attribute("title", "it's \"quoted\"");
+        let mut item_node = document.create_element("item").unwrap();
+        let _safe_to_ignore = item_node.append_child(document.create_text_node("a < b"));
+        let _safe_to_ignore = root_node.append_child(item_node);
+        let _safe_to_ignore = root_node.append_child(document.create_element("empty").unwrap());
+        document_node
+    }
+
+    fn write_string(node: &RefNode, options: &XmlWriteOptions) -> String {
+        let mut buffer: Vec<u8> = Vec::new();
+        node.write_to(&mut buffer, options).unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    #[test]
+    fn test_default_options() {
+        let options = XmlWriteOptions::default();
+        assert_eq!(options.declaration(), &Declaration::AsDocument);
+        assert!(options.encoding().is_none());
+        assert!(options.standalone().is_none());
+        assert_eq!(options.quote(), &Quote::Double);
+        assert_eq!(options.empty_element(), &EmptyElement::Expanded);
+        assert!(!options.trailing_new_line());
+        assert_eq!(options.invalid_chars(), InvalidCharHandling::Reject);
+        assert_eq!(options, XmlWriteOptions::new());
+
+        let mut document_node = make_document();
+        assert_eq!(
+            write_string(&document_node, &options),
+            document_node.to_string()
+        );
+
+        let document = as_document_decl_mut(&mut document_node).unwrap();
+        let _safe_to_ignore =
+            document.set_xml_declaration(XmlDecl::new(XmlVersion::V11, None, Some(true)));
+        assert_eq!(
+            write_string(&document_node, &options),
+            document_node.to_string()
+        );
+    }
+
+    #[test]
+    fn test_declaration() {
+        let mut document_node = make_document();
+        let options = XmlWriteOptions::default()
+            .with_declaration(Declaration::Always)
+            .with_encoding("UTF-8");
+        assert!(write_string(&document_node, &options)
+            .starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?><root"));
+
+        let document = as_document_decl_mut(&mut document_node).unwrap();
+        let _safe_to_ignore = document.set_xml_declaration(XmlDecl::new(
+            XmlVersion::V11,
+            Some("ISO-8859-1".to_string()),
+            None,
+        ));
+        let options = options.with_standalone(false).with_quote(Quote::Single);
+        assert!(write_string(&document_node, &options)
+            .starts_with("<?xml version='1.1' encoding='UTF-8' standalone='no'?><root"));
+
+        let options = options.with_declaration(Declaration::Never);
+        assert!(write_string(&document_node, &options).starts_with("<root"));
+    }
+
+    #[test]
+    fn test_quote_empty_element_and_new_line() {
+        let document_node = make_document();
+        let options = XmlWriteOptions::default()
+            .with_quote(Quote::Single)
+            .with_empty_element(EmptyElement::SelfClosing)
+            .with_trailing_new_line(true);
+        assert_eq!(
+            write_string(&document_node, &options),
+            "<root title='it&#39;s &#34;quoted&#34;'><item>a &#60; b</item><empty/></root>\n"
+        );
+    }
+
+    #[test]
+    fn test_deeply_nested() {
+        const DEPTH: usize = 10_000;
+        let mut document_node = make_document();
+        let document = as_document_mut(&mut document_node).unwrap();
+        let mut parent_node = document.document_element().unwrap();
+        for _ in 0..DEPTH {
+            let child_node = document.create_element("n").unwrap();
+            parent_node = parent_node.append_child(child_node).unwrap();
+        }
+        let result = write_string(&document_node, &XmlWriteOptions::default());
+        assert!(result.ends_with(&format!("{}</root>", "</n>".repeat(DEPTH))));
+        assert_eq!(result, document_node.to_string());
+    }
+
+    #[test]
+    fn test_invalid_chars() {
+        let mut document_node = make_document();
+        document_node.set_strict_error_checking(false).unwrap();
+        let mut root_node = as_document_mut(&mut document_node)
+            .unwrap()
+            .document_element()
+            .unwrap();
+        let _safe_to_ignore = root_node.set_attribute("bell", "\u{7}");
+        let mut buffer: Vec<u8> = Vec::new();
+        let result = document_node.write_to(&mut buffer, &XmlWriteOptions::default());
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::InvalidData);
+
+        let options = XmlWriteOptions::default().with_invalid_chars(InvalidCharHandling::Escape);
+        assert!(write_string(&document_node, &options).contains("bell=\"&#7;\""));
+        assert!(document_node.to_string().contains("bell=\"&#7;\""));
+    }
+}
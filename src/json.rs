@@ -0,0 +1,583 @@
+/*!
+Provides a mapping between a DOM tree and JSON, for consumers such as JavaScript that would rather
+not parse XML. This module is only included with the `json` feature.
+
+Two conventions are supported, selected by the [`JsonConvention`](enum.JsonConvention.html) of
+the [`JsonOptions`](struct.JsonOptions.html) passed to [`to_json`](fn.to_json.html) and
+[`from_json`](fn.from_json.html).
+
+# JsonML
+
+[JsonML](http://www.jsonml.org/) maps an element to an array of its name, an object with its
+attributes if it has any, and its children in order; text, and CDATA sections, are strings. As the
+children are kept in order mixed content round-trips exactly, and the attribute order is kept.
+
+```json
+["p", {"class": "note"}, "mixed ", ["b", "bold"], " text"]
+```
+
+JsonML has no form for comments or processing instructions; they are dropped unless kept with
+[`with_comments`](struct.JsonOptions.html#method.with_comments) or
+[`with_processing_instructions`](struct.JsonOptions.html#method.with_processing_instructions),
+in which case they are written as `["!", data]` and `["?", target, data]`.
+
+# BadgerFish
+
+[BadgerFish](http://badgerfish.ning.com/) maps an element to an object keyed by its name. Within
+it each attribute is a key starting with `@`, the text is the `$` key, each child element is keyed
+by its name, and repeated child elements with the same name are an array. The namespaces in scope
+are the `@xmlns` key, with the default namespace as `$`.
+
+```json
+{"p": {"@class": "note", "$": "mixed  text", "b": {"$": "bold"}}}
+```
+
+BadgerFish does not keep the order of mixed content; the text of an element is joined, and its
+child elements are grouped by name, so on import the text comes before the child elements.
+Comments and processing instructions are always dropped.
+
+# Namespaces
+
+Namespace declarations are written as `xmlns` keys, and a declaration is added where an element
+or attribute uses a namespace that is not declared in the output. On import the prefixes of
+element and attribute names are resolved against these declarations.
+
+# Example
+
+```rust
+# #[cfg(feature = "quick_parser")]
+# fn main() {
+use quick_xml::Reader;
+use serde_json::json;
+use xml_dom::json::*;
+use xml_dom::parser::read_quick_xml;
+
+let xml = r#"<p class="note">mixed <b>bold</b> text</p>"#;
+let document_node = read_quick_xml(Reader::from_str(xml)).unwrap();
+
+let value = to_jsonml(&document_node);
+assert_eq!(value, json!(["p", {"class": "note"}, "mixed ", ["b", "bold"], " text"]));
+assert_eq!(from_jsonml(&value).unwrap().to_string(), document_node.to_string());
+
+let options = JsonOptions::new().with_convention(JsonConvention::BadgerFish);
+assert_eq!(
+    to_json(&document_node, &options),
+    json!({"p": {"@class": "note", "$": "mixed  text", "b": {"$": "bold"}}})
+);
+# }
+# #[cfg(not(feature = "quick_parser"))]
+# fn main() {}
+```
+*/
+
+use crate::level2::trait_impls::{
+    declaration_name, declare_namespaces, is_namespace_declaration, normalized_value, Namespaces,
+};
+use crate::level2::{get_implementation, Attribute, Document, Element, Node, NodeType, RefNode};
+use crate::shared::error::{Error, Result, MSG_INVALID_JSON};
+use crate::shared::name::Name;
+use crate::shared::syntax::{XMLNS_NS_ATTRIBUTE, XMLNS_NS_URI, XML_NS_ATTRIBUTE};
+use serde_json::{Map, Value};
+use std::str::FromStr;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The convention used to map a tree to, and from, JSON; see the [module](index.html)
+/// documentation.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum JsonConvention {
+    /// Elements are arrays of the name, attributes, and children in order.
+    #[default]
+    JsonMl,
+    /// Elements are objects with keys for attributes, text, and child elements.
+    BadgerFish,
+}
+
+///
+/// Options that control the mapping of [`to_json`](fn.to_json.html) and
+/// [`from_json`](fn.from_json.html).
+///
+/// The default options use JsonML, and drop comments and processing instructions.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct JsonOptions {
+    convention: JsonConvention,
+    comments: bool,
+    processing_instructions: bool,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+const JSONML_COMMENT: &str = "!";
+const JSONML_PROCESSING_INSTRUCTION: &str = "?";
+
+const BADGERFISH_TEXT: &str = "$";
+const BADGERFISH_ATTRIBUTE: &str = "@";
+const BADGERFISH_NAMESPACES: &str = "@xmlns";
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Map the tree under `node` to JSON using the convention in `options`. If `node` is a document
+/// its document element is mapped, any other node before or after it is dropped; any node other
+/// than a document or element is mapped to `Value::Null`.
+///
+pub fn to_json(node: &RefNode, options: &JsonOptions) -> Value {
+    let element = match node.node_type() {
+        NodeType::Document => node.document_element(),
+        NodeType::Element => Some(node.clone()),
+        _ => None,
+    };
+    match element {
+        None => Value::Null,
+        Some(element) => match options.convention {
+            JsonConvention::JsonMl => jsonml_element(&element, &Namespaces::new(), options),
+            JsonConvention::BadgerFish => {
+                let mut object = Map::new();
+                let _safe_to_ignore = object.insert(
+                    element.node_name().to_string(),
+                    badgerfish_element(&element, &Namespaces::new()),
+                );
+                Value::Object(object)
+            }
+        },
+    }
+}
+
+///
+/// Create a new document whose document element is mapped from `value`, using the convention in
+/// `options`. The result can be safely assumed to be a `Document` node.
+///
+/// **Exceptions**
+///
+/// * `SYNTAX_ERR`: Raised if `value` does not follow the convention, for example a JsonML element
+///   is not an array starting with its name.
+/// * `NAMESPACE_ERR`: Raised if an element or attribute name has a prefix that is not declared.
+///
+/// Any error creating a node, such as `INVALID_CHARACTER_ERR` for an invalid name, is also
+/// returned.
+///
+pub fn from_json(value: &Value, options: &JsonOptions) -> Result<RefNode> {
+    let mut document_node = get_implementation().create_document(None, None, None)?;
+    match options.convention {
+        JsonConvention::JsonMl => {
+            let root = value.as_array().ok_or_else(invalid_json)?;
+            let _safe_to_ignore = jsonml_append_element(&mut document_node, root, options)?;
+        }
+        JsonConvention::BadgerFish => match value.as_object() {
+            Some(object) if object.len() == 1 => {
+                let (name, value) = object.iter().next().unwrap();
+                let _safe_to_ignore = badgerfish_append_element(&mut document_node, name, value)?;
+            }
+            _ => return Err(invalid_json()),
+        },
+    }
+    Ok(document_node)
+}
+
+///
+/// Map the tree under `node` to JsonML, dropping comments and processing instructions; this is
+/// [`to_json`](fn.to_json.html) with the default options.
+///
+pub fn to_jsonml(node: &RefNode) -> Value {
+    to_json(node, &JsonOptions::default())
+}
+
+///
+/// Create a new document from JsonML, dropping comments and processing instructions; this is
+/// [`from_json`](fn.from_json.html) with the default options.
+///
+pub fn from_jsonml(value: &Value) -> Result<RefNode> {
+    from_json(value, &JsonOptions::default())
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl JsonOptions {
+    ///
+    /// Construct a new `JsonOptions` instance with the default values.
+    ///
+    pub fn new() -> Self {
+        Default::default()
+    }
+    ///
+    /// Set the convention used to map elements.
+    ///
+    pub fn with_convention(self, convention: JsonConvention) -> Self {
+        Self { convention, ..self }
+    }
+    ///
+    /// Set whether comments are kept; this is ignored by the BadgerFish convention.
+    ///
+    pub fn with_comments(self, comments: bool) -> Self {
+        Self { comments, ..self }
+    }
+    ///
+    /// Set whether processing instructions are kept; this is ignored by the BadgerFish
+    /// convention.
+    ///
+    pub fn with_processing_instructions(self, processing_instructions: bool) -> Self {
+        Self {
+            processing_instructions,
+            ..self
+        }
+    }
+    ///
+    /// Return the convention used to map elements.
+    ///
+    pub fn convention(&self) -> JsonConvention {
+        self.convention
+    }
+    ///
+    /// Return `true` if comments are kept, else `false`.
+    ///
+    pub fn comments(&self) -> bool {
+        self.comments
+    }
+    ///
+    /// Return `true` if processing instructions are kept, else `false`.
+    ///
+    pub fn processing_instructions(&self) -> bool {
+        self.processing_instructions
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn jsonml_element(element: &RefNode, in_scope: &Namespaces, options: &JsonOptions) -> Value {
+    let mut in_scope = in_scope.clone();
+    let added = declare_namespaces(element, &mut in_scope);
+
+    let mut values = vec![Value::String(element.node_name().to_string())];
+    let mut attributes = Map::new();
+    for attribute_node in element.borrow().attribute_nodes() {
+        let _safe_to_ignore = attributes.insert(
+            attribute_node.node_name().to_string(),
+            Value::String(normalized_value(&attribute_node).unwrap_or_default()),
+        );
+    }
+    for (prefix, namespace_uri) in added {
+        let _safe_to_ignore =
+            attributes.insert(declaration_name(&prefix), Value::String(namespace_uri));
+    }
+    if !attributes.is_empty() {
+        values.push(Value::Object(attributes));
+    }
+    jsonml_children(element, &in_scope, options, &mut values);
+    Value::Array(values)
+}
+
+fn jsonml_children(
+    node: &RefNode,
+    in_scope: &Namespaces,
+    options: &JsonOptions,
+    values: &mut Vec<Value>,
+) {
+    for child_node in node.child_nodes() {
+        match child_node.node_type() {
+            NodeType::Element => values.push(jsonml_element(&child_node, in_scope, options)),
+            NodeType::Text | NodeType::CData => {
+                values.push(Value::String(child_node.node_value().unwrap_or_default()))
+            }
+            NodeType::Comment if options.comments => values.push(Value::Array(vec![
+                Value::String(JSONML_COMMENT.to_string()),
+                Value::String(child_node.node_value().unwrap_or_default()),
+            ])),
+            NodeType::ProcessingInstruction if options.processing_instructions => {
+                let mut instruction = vec![
+                    Value::String(JSONML_PROCESSING_INSTRUCTION.to_string()),
+                    Value::String(child_node.node_name().to_string()),
+                ];
+                if let Some(data) = child_node.node_value() {
+                    instruction.push(Value::String(data));
+                }
+                values.push(Value::Array(instruction))
+            }
+            NodeType::EntityReference => jsonml_children(&child_node, in_scope, options, values),
+            _ => {}
+        }
+    }
+}
+
+fn jsonml_append_element(
+    parent_node: &mut RefNode,
+    values: &[Value],
+    options: &JsonOptions,
+) -> Result<RefNode> {
+    let (name, rest) = match values.split_first() {
+        Some((Value::String(name), rest)) => (name, rest),
+        _ => return Err(invalid_json()),
+    };
+    let (attributes, children) = match rest.split_first() {
+        Some((Value::Object(attributes), children)) => (
+            attributes
+                .iter()
+                .map(|(name, value)| Ok((Name::from_str(name)?, string_value(value)?)))
+                .collect::<Result<Vec<(Name, String)>>>()?,
+            children,
+        ),
+        _ => (Vec::new(), rest),
+    };
+    let mut element_node = append_element(parent_node, name, &attributes)?;
+    let document = element_node.owner_document().unwrap();
+    for child in children {
+        let child_node = match child {
+            Value::String(data) => Some(document.create_text_node(data)),
+            Value::Array(values) => match values.first().and_then(Value::as_str) {
+                Some(JSONML_COMMENT) => match values.as_slice() {
+                    [_, Value::String(data)] if options.comments => {
+                        Some(document.create_comment(data)?)
+                    }
+                    [_, Value::String(_)] => None,
+                    _ => return Err(invalid_json()),
+                },
+                Some(JSONML_PROCESSING_INSTRUCTION) => match values.as_slice() {
+                    [_, Value::String(target), data @ ..] if data.len() < 2 => {
+                        let data = data.first().map(string_value).transpose()?;
+                        if options.processing_instructions {
+                            Some(document.create_processing_instruction(target, data.as_deref())?)
+                        } else {
+                            None
+                        }
+                    }
+                    _ => return Err(invalid_json()),
+                },
+                _ => {
+                    let _safe_to_ignore =
+                        jsonml_append_element(&mut element_node, values, options)?;
+                    None
+                }
+            },
+            _ => return Err(invalid_json()),
+        };
+        if let Some(child_node) = child_node {
+            let _safe_to_ignore = element_node.append_child(child_node)?;
+        }
+    }
+    Ok(element_node)
+}
+
+fn badgerfish_element(element: &RefNode, in_scope: &Namespaces) -> Value {
+    let mut in_scope = in_scope.clone();
+    let _safe_to_ignore = declare_namespaces(element, &mut in_scope);
+
+    let mut object = Map::new();
+    let namespaces: Map<String, Value> = in_scope
+        .iter()
+        .filter(|(prefix, namespace_uri)| !namespace_uri.is_empty() && *prefix != XML_NS_ATTRIBUTE)
+        .map(|(prefix, namespace_uri)| {
+            (
+                if prefix.is_empty() {
+                    BADGERFISH_TEXT.to_string()
+                } else {
+                    prefix.clone()
+                },
+                Value::String(namespace_uri.clone()),
+            )
+        })
+        .collect();
+    if !namespaces.is_empty() {
+        let _safe_to_ignore =
+            object.insert(BADGERFISH_NAMESPACES.to_string(), Value::Object(namespaces));
+    }
+    for attribute_node in element.borrow().attribute_nodes() {
+        let name = attribute_node.node_name();
+        if !is_namespace_declaration(&name) {
+            let _safe_to_ignore = object.insert(
+                format!("{}{}", BADGERFISH_ATTRIBUTE, name),
+                Value::String(normalized_value(&attribute_node).unwrap_or_default()),
+            );
+        }
+    }
+    let mut text = String::new();
+    let mut children: Vec<(String, Vec<Value>)> = Vec::new();
+    badgerfish_children(element, &in_scope, &mut text, &mut children);
+    if !text.is_empty() {
+        let _safe_to_ignore = object.insert(BADGERFISH_TEXT.to_string(), Value::String(text));
+    }
+    for (name, mut values) in children {
+        let value = if values.len() == 1 {
+            values.remove(0)
+        } else {
+            Value::Array(values)
+        };
+        let _safe_to_ignore = object.insert(name, value);
+    }
+    Value::Object(object)
+}
+
+fn badgerfish_children(
+    node: &RefNode,
+    in_scope: &Namespaces,
+    text: &mut String,
+    children: &mut Vec<(String, Vec<Value>)>,
+) {
+    for child_node in node.child_nodes() {
+        match child_node.node_type() {
+            NodeType::Element => {
+                let name = child_node.node_name().to_string();
+                let value = badgerfish_element(&child_node, in_scope);
+                match children.iter_mut().find(|(other, _)| *other == name) {
+                    Some((_, values)) => values.push(value),
+                    None => children.push((name, vec![value])),
+                }
+            }
+            NodeType::Text | NodeType::CData => {
+                text.push_str(&child_node.node_value().unwrap_or_default())
+            }
+            NodeType::EntityReference => badgerfish_children(&child_node, in_scope, text, children),
+            _ => {}
+        }
+    }
+}
+
+fn badgerfish_append_element(
+    parent_node: &mut RefNode,
+    name: &str,
+    value: &Value,
+) -> Result<RefNode> {
+    let object = value.as_object().ok_or_else(invalid_json)?;
+    let mut attributes = Vec::new();
+    if let Some(namespaces) = object.get(BADGERFISH_NAMESPACES) {
+        let namespaces = namespaces.as_object().ok_or_else(invalid_json)?;
+        for (prefix, namespace_uri) in namespaces {
+            let namespace_uri = string_value(namespace_uri)?;
+            let prefix = Some(prefix.as_str()).filter(|prefix| *prefix != BADGERFISH_TEXT);
+            //
+            // Every namespace in scope is repeated on each element, so only those that differ
+            // from the parent are declarations.
+            //
+            if parent_node.lookup_namespace_uri(prefix) != Some(namespace_uri.clone()) {
+                attributes.push((
+                    Name::from_str(&declaration_name(prefix.unwrap_or_default()))?,
+                    namespace_uri,
+                ));
+            }
+        }
+    }
+    for (key, value) in object {
+        if key != BADGERFISH_NAMESPACES {
+            if let Some(name) = key.strip_prefix(BADGERFISH_ATTRIBUTE) {
+                attributes.push((Name::from_str(name)?, string_value(value)?));
+            }
+        }
+    }
+    let mut element_node = append_element(parent_node, name, &attributes)?;
+    let document = element_node.owner_document().unwrap();
+    for (key, value) in object {
+        if key == BADGERFISH_TEXT {
+            let text_node = document.create_text_node(&string_value(value)?);
+            let _safe_to_ignore = element_node.append_child(text_node)?;
+        } else if !key.starts_with(BADGERFISH_ATTRIBUTE) {
+            match value {
+                Value::Array(values) => {
+                    for value in values {
+                        let _safe_to_ignore =
+                            badgerfish_append_element(&mut element_node, key, value)?;
+                    }
+                }
+                _ => {
+                    let _safe_to_ignore = badgerfish_append_element(&mut element_node, key, value)?;
+                }
+            }
+        }
+    }
+    Ok(element_node)
+}
+
+//
+// Create an element named `name` with `attributes`, and append it to `parent_node`; the prefixes
+// of the element and attribute names are resolved against the namespace declarations in
+// `attributes`, and then those in scope at `parent_node`.
+//
+fn append_element(
+    parent_node: &mut RefNode,
+    name: &str,
+    attributes: &[(Name, String)],
+) -> Result<RefNode> {
+    let document = match parent_node.node_type() {
+        NodeType::Document => parent_node.clone(),
+        _ => parent_node.owner_document().unwrap(),
+    };
+    let prefix = Name::from_str(name)?.prefix().clone();
+    let element_node = match resolve_prefix(parent_node, attributes, prefix.as_deref()) {
+        Some(namespace_uri) => document.create_element_ns(&namespace_uri, name)?,
+        None if prefix.is_none() => document.create_element(name)?,
+        None => return Err(undeclared_prefix(name)),
+    };
+    let mut element_node = parent_node.append_child(element_node)?;
+
+    for (name, value) in attributes {
+        let qualified_name = name.to_string();
+        let namespace_uri = if is_namespace_declaration(name) {
+            Some(XMLNS_NS_URI.to_string())
+        } else if name.prefix().is_some() {
+            match resolve_prefix(parent_node, attributes, name.prefix().as_deref()) {
+                Some(namespace_uri) => Some(namespace_uri),
+                None => return Err(undeclared_prefix(&qualified_name)),
+            }
+        } else {
+            None
+        };
+        let _safe_to_ignore = match namespace_uri {
+            None => {
+                let attribute_node = document.create_attribute_with(&qualified_name, value)?;
+                element_node.set_attribute_node(attribute_node)?
+            }
+            Some(namespace_uri) => {
+                let mut attribute_node =
+                    document.create_attribute_ns(&namespace_uri, &qualified_name)?;
+                attribute_node.set_value(value)?;
+                element_node.set_attribute_node_ns(attribute_node)?
+            }
+        };
+    }
+    Ok(element_node)
+}
+
+//
+// The namespace URI bound to `prefix`, or the default namespace if `None`, for an element with
+// the given attributes that is to be added to `parent`; as for the parser.
+//
+fn resolve_prefix(
+    parent: &RefNode,
+    attributes: &[(Name, String)],
+    prefix: Option<&str>,
+) -> Option<String> {
+    let declaration = attributes.iter().find(|(name, _)| match prefix {
+        None => name.prefix().is_none() && name.local_name() == XMLNS_NS_ATTRIBUTE,
+        Some(prefix) => {
+            name.prefix().as_deref() == Some(XMLNS_NS_ATTRIBUTE) && name.local_name() == prefix
+        }
+    });
+    match declaration {
+        Some((_, namespace_uri)) => Some(namespace_uri.clone()).filter(|uri| !uri.is_empty()),
+        None => parent.lookup_namespace_uri(prefix),
+    }
+}
+
+fn string_value(value: &Value) -> Result<String> {
+    value.as_str().map(str::to_string).ok_or_else(invalid_json)
+}
+
+fn invalid_json() -> Error {
+    warn!("{}", MSG_INVALID_JSON);
+    Error::Syntax
+}
+
+fn undeclared_prefix(name: &str) -> Error {
+    warn!("Name {:?} uses an undeclared namespace prefix", name);
+    Error::Namespace
+}
@@ -0,0 +1,219 @@
+/*!
+Provides a builder for constructing an element, with its attributes and children, in a single
+expression rather than by creating, converting, and appending each node in turn.
+
+An `ElementBuilder` only records the nodes to create; nothing is created until one of the terminal
+methods, [`into_node`](struct.ElementBuilder.html#method.into_node) or
+[`attach_to`](struct.ElementBuilder.html#method.attach_to), is called. These create each node with
+the usual factory methods, such as `Document::create_element` and `Element::set_attribute`, so
+any validation is identical to building the tree by hand, and return the first error, in document
+order, that any of them returns. If an error is returned no part of the tree is attached.
+
+# Example
+
+```rust
+# #[cfg(feature = "quick_parser")]
+# fn main() {
+use xml_dom::level2::*;
+use xml_dom::level2::builder::*;
+use xml_dom::parser::read_xml;
+
+let mut document_node = read_xml("<catalog/>").unwrap();
+let mut root_node = document_node.document_element().unwrap();
+
+let item_node = document_node
+    .build_element("item")
+    .attribute("id", "1")
+    .child(document_node.build_element("name").text("one & two"))
+    .comment(" in stock ")
+    .attach_to(&mut root_node)
+    .unwrap();
+assert_eq!(item_node.parent_node(), Some(root_node));
+assert_eq!(
+    document_node.to_string(),
+    r#"<catalog><item id="1"><name>one &#38; two</name><!-- in stock --></item></catalog>"#
+);
+
+let result = document_node
+    .build_element("item")
+    .attribute("1d", "2")
+    .into_node(&document_node);
+assert_eq!(result, Err(Error::InvalidCharacter));
+# }
+# #[cfg(not(feature = "quick_parser"))]
+# fn main() {}
+```
+*/
+
+use crate::level2::convert::is_document;
+use crate::level2::node_impl::RefNode;
+use crate::level2::traits::{Document, Element, Node};
+use crate::shared::error::{Error, Result, MSG_INVALID_NODE_TYPE, MSG_WEAK_REF};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Extends `Document` with the creation of an [`ElementBuilder`](struct.ElementBuilder.html).
+///
+pub trait BuildElement: Node {
+    ///
+    /// Returns a new builder for an element named `tag_name`; this is the same as
+    /// `ElementBuilder::new(tag_name)`.
+    ///
+    fn build_element(&self, tag_name: &str) -> ElementBuilder;
+}
+
+///
+/// Records an element, its attributes, and its children, to be created together by
+/// [`into_node`](#method.into_node) or [`attach_to`](#method.attach_to).
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct ElementBuilder {
+    tag_name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<ChildBuilder>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Debug, PartialEq)]
+enum ChildBuilder {
+    Element(ElementBuilder),
+    Text(String),
+    CData(String),
+    Comment(String),
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl BuildElement for RefNode {
+    fn build_element(&self, tag_name: &str) -> ElementBuilder {
+        ElementBuilder::new(tag_name)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl ElementBuilder {
+    ///
+    /// Returns a new builder for an element named `tag_name`, with no attributes or children.
+    ///
+    pub fn new(tag_name: &str) -> Self {
+        Self {
+            tag_name: tag_name.to_string(),
+            attributes: Default::default(),
+            children: Default::default(),
+        }
+    }
+
+    ///
+    /// Adds the attribute `name` with `value`, as `Element::set_attribute` would; a later value
+    /// for the same name replaces an earlier one.
+    ///
+    pub fn attribute(mut self, name: &str, value: &str) -> Self {
+        self.attributes.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    ///
+    /// Adds the element described by `child` after any children already added.
+    ///
+    pub fn child(mut self, child: ElementBuilder) -> Self {
+        self.children.push(ChildBuilder::Element(child));
+        self
+    }
+
+    ///
+    /// Adds a `Text` node with `data` after any children already added.
+    ///
+    pub fn text(mut self, data: &str) -> Self {
+        self.children.push(ChildBuilder::Text(data.to_string()));
+        self
+    }
+
+    ///
+    /// Adds a `CDataSection` node with `data` after any children already added.
+    ///
+    pub fn cdata(mut self, data: &str) -> Self {
+        self.children.push(ChildBuilder::CData(data.to_string()));
+        self
+    }
+
+    ///
+    /// Adds a `Comment` node with `data` after any children already added.
+    ///
+    pub fn comment(mut self, data: &str) -> Self {
+        self.children.push(ChildBuilder::Comment(data.to_string()));
+        self
+    }
+
+    ///
+    /// Creates the element, owned by `document`, and all its attributes and children. The new
+    /// element has no parent, as with `Document::create_element`.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `INVALID_STATE_ERR`: Raised if `document` is not a `Document`.
+    ///
+    /// Any error creating a node, such as `INVALID_CHARACTER_ERR` for an invalid name, is also
+    /// returned.
+    ///
+    pub fn into_node(self, document: &RefNode) -> Result<RefNode> {
+        if !is_document(document) {
+            warn!("{}", MSG_INVALID_NODE_TYPE);
+            Err(Error::InvalidState)
+        } else {
+            self.create_node(document)
+        }
+    }
+
+    ///
+    /// Creates the element, owned by the owner document of `parent`, and all its attributes and
+    /// children, and then appends it to `parent`. The new element is returned.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `INVALID_STATE_ERR`: Raised if the owner document of `parent` has been dropped.
+    ///
+    /// Any error creating a node, or from `Node::append_child`, is also returned; in either case
+    /// `parent` is not modified.
+    ///
+    pub fn attach_to(self, parent: &mut RefNode) -> Result<RefNode> {
+        let document = if is_document(parent) {
+            parent.clone()
+        } else {
+            match parent.owner_document() {
+                None => {
+                    warn!("{}", MSG_WEAK_REF);
+                    return Err(Error::InvalidState);
+                }
+                Some(document) => document,
+            }
+        };
+        let element_node = self.create_node(&document)?;
+        parent.append_child(element_node)
+    }
+
+    fn create_node(self, document: &RefNode) -> Result<RefNode> {
+        let mut element_node = document.create_element(&self.tag_name)?;
+        for (name, value) in &self.attributes {
+            element_node.set_attribute(name, value)?;
+        }
+        for child in self.children {
+            let child_node = match child {
+                ChildBuilder::Element(builder) => builder.create_node(document)?,
+                ChildBuilder::Text(data) => document.create_text_node(&data),
+                ChildBuilder::CData(data) => document.create_cdata_section(&data)?,
+                ChildBuilder::Comment(data) => document.create_comment(&data)?,
+            };
+            let _safe_to_ignore = element_node.append_child(child_node)?;
+        }
+        Ok(element_node)
+    }
+}
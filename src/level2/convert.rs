@@ -1,16 +1,53 @@
 /*!
 Provides safe `RefNode` conversion functions.
 
+There are `is_{name}`, `as_{name}`, and `as_{name}_mut` functions for every node type, and for
+`CharacterData` which is any of `Text`, `CDataSection`, and `Comment`.
+
 Note that all of the `as_{name}` functions work as follows.
 
 * If the `node_type` corresponds to the correct type, it returns OK.
 * If the `node_type` does not correspond to the correct type, it returns `Error::InvalidState`.
 * If the `node_type` is not implemented it returns `Error::NotSupported`.
 
+The `is_{name}` functions only compare the `node_type`, and so are a cheaper test where the cast
+itself is not needed.
+
+Each **Ref** type also implements `TryFrom<&RefNode>`, and each mutable **Ref** type
+`TryFrom<&mut RefNode>`, with the same checks and `Error` as the `as_{name}` functions; so a cast
+may be written with `try_into` and composed with `?`.
+
+# Example
+
+```rust
+# #[cfg(feature = "quick_parser")]
+# fn main() {
+use std::convert::TryInto;
+use xml_dom::level2::convert::{RefElement, RefText};
+use xml_dom::level2::*;
+use xml_dom::parser::read_xml;
+
+fn first_text(node: &RefNode) -> Result<String> {
+    let element: RefElement<'_> = node.try_into()?;
+    let text_node = element.first_child().ok_or(Error::NotFound)?;
+    let text: RefText<'_> = (&text_node).try_into()?;
+    Ok(text.data().unwrap_or_default())
+}
+
+let document_node = read_xml("<greeting>hello</greeting>").unwrap();
+let root_node = document_node.document_element().unwrap();
+assert_eq!(first_text(&root_node), Ok("hello".to_string()));
+assert_eq!(first_text(&document_node), Err(Error::InvalidState));
+# }
+# #[cfg(not(feature = "quick_parser"))]
+# fn main() {}
+```
+
 */
 use crate::level2::node_impl::*;
 use crate::level2::traits::*;
 use crate::shared::error::{Error, Result, MSG_INVALID_NODE_TYPE};
+use std::convert::TryFrom;
 
 use crate::{make_is_as_functions, make_ref_type};
 
@@ -79,45 +116,34 @@ make_is_as_functions!(
 ///
 #[inline]
 pub fn is_character_data(ref_node: &RefNode) -> bool {
-    match ref_node.borrow().i_node_type {
-        NodeType::CData | NodeType::Comment | NodeType::Text => true,
-        _ => false,
-    }
+    matches!(
+        ref_node.borrow().i_node_type,
+        NodeType::CData | NodeType::Comment | NodeType::Text
+    )
 }
 
 ///
-/// Safely _cast_ the specified `RefNode` into a  `Text`.
+/// Safely _cast_ the specified `RefNode` into a `CharacterData`; this is the same as
+/// `TryFrom::try_from(ref_node)`.
 ///
 #[inline]
 pub fn as_character_data(ref_node: &RefNode) -> Result<RefCharacterData<'_>> {
-    match ref_node.borrow().i_node_type {
-        NodeType::CData | NodeType::Comment | NodeType::Text => {
-            Ok(ref_node as RefCharacterData<'_>)
-        }
-        _ => {
-            warn!("{}", MSG_INVALID_NODE_TYPE);
-            Err(Error::InvalidState)
-        }
-    }
+    TryFrom::try_from(ref_node)
 }
 
 ///
-/// Safely _cast_ the specified `RefNode` into a mutable `Text`.
+/// Safely _cast_ the specified `RefNode` into a mutable `CharacterData`; this is the same as
+/// `TryFrom::try_from(ref_node)`.
 ///
 #[inline]
 pub fn as_character_data_mut(ref_node: &mut RefNode) -> Result<MutRefCharacterData<'_>> {
-    let node_type = { &ref_node.borrow().i_node_type.clone() };
-    match node_type {
-        NodeType::CData | NodeType::Comment | NodeType::Text => {
-            Ok(ref_node as MutRefCharacterData<'_>)
-        }
-        _ => {
-            warn!("{}", MSG_INVALID_NODE_TYPE);
-            Err(Error::InvalidState)
-        }
-    }
+    TryFrom::try_from(ref_node)
 }
 
+make_is_as_functions!(@try_from is_character_data, RefCharacterData);
+
+make_is_as_functions!(@try_from_mut is_character_data, MutRefCharacterData);
+
 make_is_as_functions!(
     is_text,
     NodeType::Text,
@@ -146,7 +172,7 @@ make_is_as_functions!(
 );
 
 make_is_as_functions!(
-    is_entity_,
+    is_entity,
     NodeType::Entity,
     as_entity,
     RefEntity,
@@ -207,3 +233,12 @@ make_is_as_functions!(
     as_notation_mut,
     MutRefNotation
 );
+
+///
+/// Determines if the specified node is an `Entity`.
+///
+#[deprecated(since = "0.2.7", note = "Please use `is_entity` instead")]
+#[inline]
+pub fn is_entity_(ref_node: &RefNode) -> bool {
+    is_entity(ref_node)
+}
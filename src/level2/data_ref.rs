@@ -0,0 +1,76 @@
+use crate::level2::node_impl::NodeImpl;
+use crate::shared::rc_cell::ReadGuard;
+use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+use std::ops::Deref;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A borrowed view of the character data of a node, as returned by
+/// [`CharacterData::data_as_str`](trait.CharacterData.html#tymethod.data_as_str); this
+/// dereferences to `str` and so may be read without copying the data.
+///
+/// # Implementation
+///
+/// The node is borrowed for as long as this value is held, any attempt to modify the node in
+/// that time returns `Error::Borrowed`. With the `sync` feature this holds a read lock on the
+/// node, and so any attempt to modify it from another thread will wait.
+///
+/// ```rust
+/// # #[cfg(feature = "quick_parser")]
+/// # fn main() {
+/// use xml_dom::level2::*;
+/// use xml_dom::parser::read_xml;
+///
+/// let document_node = read_xml("<p>one &amp; two</p>").unwrap();
+/// let text_node = document_node.document_element().unwrap().first_child().unwrap();
+///
+/// let data = text_node.data_as_str();
+/// assert_eq!(&*data, "one & two");
+/// assert!(data.starts_with("one"));
+/// # }
+/// # #[cfg(not(feature = "quick_parser"))]
+/// # fn main() {}
+/// ```
+///
+pub struct DataRef<'a> {
+    node: ReadGuard<'a, NodeImpl>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl<'a> DataRef<'a> {
+    pub(crate) fn new(node: ReadGuard<'a, NodeImpl>) -> Self {
+        Self { node }
+    }
+}
+
+impl Deref for DataRef<'_> {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        self.node.i_value.as_deref().unwrap_or_default()
+    }
+}
+
+impl AsRef<str> for DataRef<'_> {
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl Debug for DataRef<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl Display for DataRef<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Display::fmt(&**self, f)
+    }
+}
@@ -1,7 +1,7 @@
 /*!
 This module implements certain capabilities required by, but not specified by, the DOM Core.
 */
-use crate::level2::node_impl::RefNode;
+use crate::level2::node_impl::{ImplementationRef, RefNode};
 use crate::level2::traits::DOMImplementation;
 
 // ------------------------------------------------------------------------------------------------
@@ -16,7 +16,7 @@ pub(crate) struct Implementation {}
 // Public Functions
 // ------------------------------------------------------------------------------------------------
 
-const THIS_IMPLEMENTATION: &'static dyn DOMImplementation<NodeRef = RefNode> = &Implementation {};
+pub(crate) const THIS_IMPLEMENTATION: ImplementationRef = &Implementation {};
 
 ///
 /// Return a reference to an instance of this `DOMImplementation` implementation.
@@ -0,0 +1,25 @@
+/*!
+This module provides support types for the [`SelfManipulation`](trait.SelfManipulation.html)
+trait.
+*/
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The position, relative to a node, at which
+/// [`SelfManipulation::insert_adjacent`](trait.SelfManipulation.html#tymethod.insert_adjacent)
+/// inserts another node; after the positions of the HTML DOM `insertAdjacentElement` method.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InsertPosition {
+    /// Before this node, as its previous sibling.
+    BeforeSelf,
+    /// After this node, as its next sibling.
+    AfterSelf,
+    /// Inside this node, before its first child.
+    FirstChild,
+    /// Inside this node, after its last child.
+    LastChild,
+}
@@ -18,6 +18,7 @@ use std::str::FromStr;
 /// Captures the supported version of the XML specification itself, as used in `XmlDecl`.
 ///
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum XmlVersion {
     /// Version 1.0 [https://www.w3.org/TR/xml]
     V10,
@@ -43,7 +44,8 @@ pub enum XmlVersion {
 /// SDDecl       ::=  S 'standalone' Eq (("'" ('yes' | 'no') "'") | ('"' ('yes' | 'no') '"'))
 /// ```
 ///
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct XmlDecl {
     version: XmlVersion,
     encoding: Option<String>,
@@ -57,15 +59,11 @@ pub struct XmlDecl {
 pub(crate) const ENCODING_SEP_CHAR: char = '-';
 
 fn is_encoding_start_char(c: char) -> bool {
-    (c >= 'A' && c <= 'Z') || (c >= 'a' && c <= 'z')
+    c.is_ascii_alphabetic()
 }
 
 fn is_encoding_rest_char(c: char) -> bool {
-    (c >= 'A' && c <= 'Z')
-        || (c >= 'a' && c <= 'z')
-        || (c >= '0' && c <= '9')
-        || c == '.'
-        || c == '_'
+    c.is_ascii_alphanumeric() || c == '.' || c == '_'
 }
 
 fn is_encoding_sub_string(s: &str) -> bool {
@@ -5,6 +5,7 @@ This module implements extended capabilities but not specified by the DOM Core.
 use crate::level2::dom_impl::Implementation;
 use crate::level2::ext::traits::DOMImplementation;
 use crate::level2::node_impl::{NodeImpl, RefNode};
+use crate::level2::traits::Node;
 use crate::shared::error::Result;
 use crate::shared::name::Name;
 use std::str::FromStr;
@@ -38,7 +39,7 @@ pub fn get_implementation_ext() -> &'static dyn DOMImplementation<NodeRef = RefN
 }
 
 ///
-/// Required to create instances of the [`Entity`](../trait.Entity.html) extended interface.
+/// Required to create instances of the [`Notation`](../trait.Notation.html) extended interface.
 ///
 /// Rather than add a non-standard member to the [`Document`](../trait.Document.html) trait
 /// this function takes a `Document` as the first parameter.
@@ -74,17 +75,28 @@ pub fn create_entity(
 }
 
 ///
-/// Required to create instances of the [`Notation`](../trait.Notation.html) Extended interface.
+/// Required to create instances of the [`Entity`](../trait.Entity.html) extended interface for
+/// an internal entity.
 ///
 /// Rather than add a non-standard member to the [`Document`](../trait.Document.html) trait
 /// this function takes a `Document` as the first parameter.
 ///
+/// The replacement text, `value`, is also added as a single `Text` child of the entity; once the
+/// entity is added to the document type (see [`DocumentTypeDecl`](../trait.DocumentTypeDecl.html))
+/// this is copied into each entity reference created by `Document::create_entity_reference`.
+///
 pub fn create_internal_entity(
     owner_document: RefNode,
     notation_name: &str,
     value: &str,
 ) -> Result<RefNode> {
     let name = Name::from_str(notation_name)?;
-    let node_impl = NodeImpl::new_internal_entity(Some(owner_document.downgrade()), name, value);
-    Ok(RefNode::new(node_impl))
+    let owner_document = owner_document.downgrade();
+    let node_impl = NodeImpl::new_internal_entity(Some(owner_document.clone()), name, value);
+    let mut entity = RefNode::new(node_impl);
+    if !value.is_empty() {
+        let text = RefNode::new(NodeImpl::new_text(owner_document, value));
+        let _safe_to_ignore = entity.append_child(text)?;
+    }
+    Ok(entity)
 }
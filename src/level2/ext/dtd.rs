@@ -0,0 +1,569 @@
+/*!
+This module provides support types for the [`DocumentTypeDecl`](trait.DocumentTypeDecl.html) trait.
+
+An element type declaration may be constructed directly, or its content specification parsed from
+the syntax used in a DTD.
+
+```rust
+use xml_dom::level2::ext::*;
+use std::str::FromStr;
+
+let content_spec = ContentSpec::from_str("(title, (para | list)*, note?)").unwrap();
+let element_decl = ElementDecl::new("section", content_spec).unwrap();
+assert_eq!(
+    element_decl.to_string(),
+    "<!ELEMENT section (title,(para|list)*,note?)>"
+);
+```
+*/
+
+use crate::shared::error::{Error, Result, MSG_INVALID_CONTENT_SPEC};
+use crate::shared::name::Name;
+use crate::shared::syntax::{
+    XML_ATTLIST_END, XML_ATTLIST_FIXED, XML_ATTLIST_IMPLIED, XML_ATTLIST_REQUIRED,
+    XML_ATTLIST_START, XML_ELEMENT_DECL_ANY, XML_ELEMENT_DECL_EMPTY, XML_ELEMENT_DECL_END,
+    XML_ELEMENT_DECL_PCDATA, XML_ELEMENT_DECL_START,
+};
+use crate::shared::text::{is_xml_name_char, is_xml_name_start_char, is_xml_space};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The type of an attribute declared in an attribute-list declaration.
+///
+/// ```ebnf
+/// AttType         ::=  StringType | TokenizedType | EnumeratedType
+/// StringType      ::=  'CDATA'
+/// TokenizedType   ::=  'ID' | 'IDREF' | 'IDREFS' | 'ENTITY' | 'ENTITIES' | 'NMTOKEN' | 'NMTOKENS'
+/// EnumeratedType  ::=  NotationType | Enumeration
+/// NotationType    ::=  'NOTATION' S '(' S? Name (S? '|' S? Name)* S? ')'
+/// Enumeration     ::=  '(' S? Nmtoken (S? '|' S? Nmtoken)* S? ')'
+/// ```
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AttributeType {
+    /// `CDATA`, any string value.
+    CData,
+    /// `ID`, a name unique within the document.
+    Id,
+    /// `IDREF`, the name of an `ID` in the document.
+    IdRef,
+    /// `IDREFS`, a space-separated list of `IDREF` values.
+    IdRefs,
+    /// `ENTITY`, the name of an unparsed entity.
+    Entity,
+    /// `ENTITIES`, a space-separated list of `ENTITY` values.
+    Entities,
+    /// `NMTOKEN`, a single name token.
+    NmToken,
+    /// `NMTOKENS`, a space-separated list of name tokens.
+    NmTokens,
+    /// `NOTATION (...)`, one of the listed notation names.
+    Notation(Vec<String>),
+    /// `(...)`, one of the listed name tokens.
+    Enumeration(Vec<String>),
+}
+
+///
+/// The default declaration of an attribute declared in an attribute-list declaration.
+///
+/// ```ebnf
+/// DefaultDecl  ::=  '#REQUIRED' | '#IMPLIED' | (('#FIXED' S)? AttValue)
+/// ```
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AttributeDefault {
+    /// `#REQUIRED`, the attribute must always be provided.
+    Required,
+    /// `#IMPLIED`, no default value is provided.
+    Implied,
+    /// `#FIXED "value"`, the attribute must always have the default value.
+    Fixed(String),
+    /// `"value"`, the default value for the attribute.
+    Value(String),
+}
+
+///
+/// A single attribute definition from an attribute-list declaration; the declaration
+/// `<!ATTLIST list type (bullets|ordered) "ordered">` is represented by one `AttributeDecl` with
+/// the element name `list` and attribute name `type`.
+///
+/// ```ebnf
+/// AttlistDecl  ::=  '<!ATTLIST' S Name AttDef* S? '>'
+/// AttDef       ::=  S Name S AttType S DefaultDecl
+/// ```
+///
+/// Note that DTDs are not namespace aware and so element and attribute names are matched by
+/// their qualified name.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AttributeDecl {
+    element_name: Name,
+    attribute_name: Name,
+    attribute_type: AttributeType,
+    default: AttributeDefault,
+}
+
+///
+/// The number of times a content particle may occur.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Occurrence {
+    /// No suffix, the particle occurs exactly once.
+    Once,
+    /// `?`, the particle occurs zero times or once.
+    Optional,
+    /// `*`, the particle occurs any number of times.
+    ZeroOrMore,
+    /// `+`, the particle occurs at least once.
+    OneOrMore,
+}
+
+///
+/// A content particle in the content model of an element type that contains only elements.
+///
+/// ```ebnf
+/// cp      ::=  (Name | choice | seq) ('?' | '*' | '+')?
+/// choice  ::=  '(' S? cp ( S? '|' S? cp )+ S? ')'
+/// seq     ::=  '(' S? cp ( S? ',' S? cp )* S? ')'
+/// ```
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ContentParticle {
+    /// An element of the named type.
+    Name(Name, Occurrence),
+    /// Any one of the particles.
+    Choice(Vec<ContentParticle>, Occurrence),
+    /// Each of the particles, in order.
+    Sequence(Vec<ContentParticle>, Occurrence),
+}
+
+///
+/// The content specification of an element type declaration.
+///
+/// ```ebnf
+/// contentspec  ::=  'EMPTY' | 'ANY' | Mixed | children
+/// Mixed        ::=  '(' S? '#PCDATA' (S? '|' S? Name)* S? ')*' | '(' S? '#PCDATA' S? ')'
+/// children     ::=  (choice | seq) ('?' | '*' | '+')?
+/// ```
+///
+/// When parsed with `from_str`, groups may be nested at most 128 deep; a deeper specification is
+/// rejected with `Error::Syntax` rather than risk overflowing the stack.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ContentSpec {
+    /// `EMPTY`, the element has no content.
+    Empty,
+    /// `ANY`, the element may contain any declared elements, and character data.
+    Any,
+    /// `(#PCDATA|...)*`, character data mixed with any number of the named element types.
+    Mixed(Vec<Name>),
+    /// Only elements, matching the content particle; the particle is always a choice or a
+    /// sequence.
+    Children(ContentParticle),
+}
+
+///
+/// An element type declaration; the declaration `<!ELEMENT list (item+)>` is represented by an
+/// `ElementDecl` with the name `list` and a `ContentSpec::Children` content specification.
+///
+/// ```ebnf
+/// elementdecl  ::=  '<!ELEMENT' S Name S contentspec S? '>'
+/// ```
+///
+/// As with attribute declarations, element types are matched by their qualified name.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ElementDecl {
+    name: Name,
+    content_spec: ContentSpec,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+struct ContentSpecParser<'a> {
+    rest: &'a str,
+    depth: usize,
+}
+
+//
+// The deepest nesting of groups accepted by the parser, and by validation; each level is a
+// recursive call when parsing, validating, or displaying a content specification.
+//
+pub(crate) const MAX_CONTENT_DEPTH: usize = 128;
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for AttributeType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            AttributeType::CData => write!(f, "CDATA"),
+            AttributeType::Id => write!(f, "ID"),
+            AttributeType::IdRef => write!(f, "IDREF"),
+            AttributeType::IdRefs => write!(f, "IDREFS"),
+            AttributeType::Entity => write!(f, "ENTITY"),
+            AttributeType::Entities => write!(f, "ENTITIES"),
+            AttributeType::NmToken => write!(f, "NMTOKEN"),
+            AttributeType::NmTokens => write!(f, "NMTOKENS"),
+            AttributeType::Notation(names) => write!(f, "NOTATION ({})", names.join("|")),
+            AttributeType::Enumeration(tokens) => write!(f, "({})", tokens.join("|")),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for AttributeDefault {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            AttributeDefault::Required => write!(f, "{}", XML_ATTLIST_REQUIRED),
+            AttributeDefault::Implied => write!(f, "{}", XML_ATTLIST_IMPLIED),
+            AttributeDefault::Fixed(value) => write!(f, "{} \"{}\"", XML_ATTLIST_FIXED, value),
+            AttributeDefault::Value(value) => write!(f, "\"{}\"", value),
+        }
+    }
+}
+
+impl AttributeDefault {
+    ///
+    /// The default value, if any, to be used when the attribute is not specified.
+    ///
+    pub fn value(&self) -> Option<&str> {
+        match self {
+            AttributeDefault::Fixed(value) | AttributeDefault::Value(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for AttributeDecl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{} {} {} {} {}{}",
+            XML_ATTLIST_START,
+            self.element_name,
+            self.attribute_name,
+            self.attribute_type,
+            self.default,
+            XML_ATTLIST_END
+        )
+    }
+}
+
+impl AttributeDecl {
+    ///
+    /// Construct a new attribute declaration; returns an error if either name is not a valid
+    /// XML name.
+    ///
+    pub fn new(
+        element_name: &str,
+        attribute_name: &str,
+        attribute_type: AttributeType,
+        default: AttributeDefault,
+    ) -> Result<Self> {
+        Ok(Self {
+            element_name: Name::from_str(element_name)?,
+            attribute_name: Name::from_str(attribute_name)?,
+            attribute_type,
+            default,
+        })
+    }
+
+    ///
+    /// Return the name of the element type this attribute is declared for.
+    ///
+    pub fn element_name(&self) -> &Name {
+        &self.element_name
+    }
+
+    ///
+    /// Return the name of the declared attribute.
+    ///
+    pub fn attribute_name(&self) -> &Name {
+        &self.attribute_name
+    }
+
+    ///
+    /// Return the type of the declared attribute.
+    ///
+    pub fn attribute_type(&self) -> &AttributeType {
+        &self.attribute_type
+    }
+
+    ///
+    /// Return the default declaration of the declared attribute.
+    ///
+    pub fn default(&self) -> &AttributeDefault {
+        &self.default
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Occurrence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Occurrence::Once => Ok(()),
+            Occurrence::Optional => write!(f, "?"),
+            Occurrence::ZeroOrMore => write!(f, "*"),
+            Occurrence::OneOrMore => write!(f, "+"),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for ContentParticle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            ContentParticle::Name(name, occurrence) => write!(f, "{}{}", name, occurrence),
+            ContentParticle::Choice(particles, occurrence) => {
+                write!(f, "({}){}", join_particles(particles, "|"), occurrence)
+            }
+            ContentParticle::Sequence(particles, occurrence) => {
+                write!(f, "({}){}", join_particles(particles, ","), occurrence)
+            }
+        }
+    }
+}
+
+impl ContentParticle {
+    ///
+    /// Return the number of times this particle may occur.
+    ///
+    pub fn occurrence(&self) -> Occurrence {
+        match self {
+            ContentParticle::Name(_, occurrence)
+            | ContentParticle::Choice(_, occurrence)
+            | ContentParticle::Sequence(_, occurrence) => *occurrence,
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for ContentSpec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            ContentSpec::Empty => write!(f, "{}", XML_ELEMENT_DECL_EMPTY),
+            ContentSpec::Any => write!(f, "{}", XML_ELEMENT_DECL_ANY),
+            ContentSpec::Mixed(names) if names.is_empty() => {
+                write!(f, "({})", XML_ELEMENT_DECL_PCDATA)
+            }
+            ContentSpec::Mixed(names) => {
+                write!(f, "({}", XML_ELEMENT_DECL_PCDATA)?;
+                for name in names {
+                    write!(f, "|{}", name)?;
+                }
+                write!(f, ")*")
+            }
+            ContentSpec::Children(particle) => write!(f, "{}", particle),
+        }
+    }
+}
+
+impl FromStr for ContentSpec {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        let mut parser = ContentSpecParser {
+            rest: value.trim_matches(is_xml_space),
+            depth: 1,
+        };
+        match parser.content_spec() {
+            Some(content_spec) if parser.rest.is_empty() => Ok(content_spec),
+            _ => {
+                warn!("{}", MSG_INVALID_CONTENT_SPEC);
+                Err(Error::Syntax)
+            }
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for ElementDecl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{} {} {}{}",
+            XML_ELEMENT_DECL_START, self.name, self.content_spec, XML_ELEMENT_DECL_END
+        )
+    }
+}
+
+impl ElementDecl {
+    ///
+    /// Construct a new element type declaration; returns an error if the name is not a valid XML
+    /// name.
+    ///
+    pub fn new(name: &str, content_spec: ContentSpec) -> Result<Self> {
+        Ok(Self {
+            name: Name::from_str(name)?,
+            content_spec,
+        })
+    }
+
+    ///
+    /// Return the name of the declared element type.
+    ///
+    pub fn name(&self) -> &Name {
+        &self.name
+    }
+
+    ///
+    /// Return the content specification of the declared element type.
+    ///
+    pub fn content_spec(&self) -> &ContentSpec {
+        &self.content_spec
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl<'a> ContentSpecParser<'a> {
+    fn content_spec(&mut self) -> Option<ContentSpec> {
+        if self.eat_str(XML_ELEMENT_DECL_EMPTY) {
+            Some(ContentSpec::Empty)
+        } else if self.eat_str(XML_ELEMENT_DECL_ANY) {
+            Some(ContentSpec::Any)
+        } else if self.eat_str("(") {
+            self.skip_space();
+            if self.eat_str(XML_ELEMENT_DECL_PCDATA) {
+                self.mixed()
+            } else {
+                self.group().map(ContentSpec::Children)
+            }
+        } else {
+            None
+        }
+    }
+
+    //
+    // The rest of a mixed content specification, after `(#PCDATA`.
+    //
+    fn mixed(&mut self) -> Option<ContentSpec> {
+        let mut names = Vec::new();
+        loop {
+            self.skip_space();
+            if self.eat_str(")") {
+                break;
+            } else if self.eat_str("|") {
+                self.skip_space();
+                names.push(self.name()?);
+            } else {
+                return None;
+            }
+        }
+        if !self.eat_str("*") && !names.is_empty() {
+            return None;
+        }
+        Some(ContentSpec::Mixed(names))
+    }
+
+    //
+    // The rest of a choice or sequence, after its `(`.
+    //
+    fn group(&mut self) -> Option<ContentParticle> {
+        let mut particles = vec![self.particle()?];
+        let mut separator = None;
+        loop {
+            self.skip_space();
+            if self.eat_str(")") {
+                break;
+            }
+            let next = self.rest.chars().next()?;
+            if !(next == ',' || next == '|') || separator.unwrap_or(next) != next {
+                return None;
+            }
+            separator = Some(next);
+            self.rest = &self.rest[1..];
+            self.skip_space();
+            particles.push(self.particle()?);
+        }
+        let occurrence = self.occurrence();
+        Some(if separator == Some('|') {
+            ContentParticle::Choice(particles, occurrence)
+        } else {
+            ContentParticle::Sequence(particles, occurrence)
+        })
+    }
+
+    fn particle(&mut self) -> Option<ContentParticle> {
+        if self.eat_str("(") {
+            if self.depth == MAX_CONTENT_DEPTH {
+                return None;
+            }
+            self.depth += 1;
+            self.skip_space();
+            let group = self.group();
+            self.depth -= 1;
+            group
+        } else {
+            let name = self.name()?;
+            Some(ContentParticle::Name(name, self.occurrence()))
+        }
+    }
+
+    fn occurrence(&mut self) -> Occurrence {
+        if self.eat_str("?") {
+            Occurrence::Optional
+        } else if self.eat_str("*") {
+            Occurrence::ZeroOrMore
+        } else if self.eat_str("+") {
+            Occurrence::OneOrMore
+        } else {
+            Occurrence::Once
+        }
+    }
+
+    fn name(&mut self) -> Option<Name> {
+        if !self.rest.starts_with(is_xml_name_start_char) {
+            return None;
+        }
+        let end = self
+            .rest
+            .find(|c| !is_xml_name_char(c))
+            .unwrap_or(self.rest.len());
+        let (name, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        Name::from_str(name).ok()
+    }
+
+    fn eat_str(&mut self, token: &str) -> bool {
+        match self.rest.strip_prefix(token) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn skip_space(&mut self) {
+        self.rest = self.rest.trim_start_matches(is_xml_space);
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn join_particles(particles: &[ContentParticle], separator: &str) -> String {
+    particles
+        .iter()
+        .map(|particle| particle.to_string())
+        .collect::<Vec<String>>()
+        .join(separator)
+}
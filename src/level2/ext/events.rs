@@ -0,0 +1,255 @@
+/*!
+This module provides support types for the [`XmlEvents`](trait.XmlEvents.html) trait, which
+presents an existing tree as the stream of events a streaming (SAX-like) parser would produce for
+it.
+
+Like the iterators in the [`iterators`](../iterators/index.html) module the events are produced
+lazily; the iterator holds the path to its current position in the tree and only borrows a node
+for long enough to read it, or to find the next one.
+
+```rust
+# #[cfg(feature = "quick_parser")]
+# fn main() {
+use xml_dom::level2::*;
+use xml_dom::level2::ext::*;
+use xml_dom::parser::read_xml;
+
+let document_node = read_xml(r#"<a id="1"><b>text</b><!--note--></a>"#).unwrap();
+let events: Vec<XmlEvent> = document_node.events().collect();
+assert_eq!(events.len(), 8);
+assert!(matches!(events[0], XmlEvent::StartDocument { .. }));
+assert!(
+    matches!(&events[1], XmlEvent::StartElement { name, attributes, .. }
+        if name.to_string() == "a" && attributes[0].1 == "1")
+);
+assert_eq!(events[3], XmlEvent::Characters("text".to_string()));
+assert_eq!(events[5], XmlEvent::Comment("note".to_string()));
+assert_eq!(events[7], XmlEvent::EndDocument);
+# }
+# #[cfg(not(feature = "quick_parser"))]
+# fn main() {}
+```
+*/
+
+use crate::level2::ext::decl::XmlDecl;
+use crate::level2::ext::traits::{DocumentDecl, XmlEvents};
+use crate::level2::node_impl::{Extension, RefNode};
+use crate::level2::trait_impls::{is_namespace_declaration, normalized_value};
+use crate::level2::traits::{DocumentType, NodeType};
+use crate::shared::name::Name;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An event produced by [`XmlEvents::events`](trait.XmlEvents.html#tymethod.events), these
+/// correspond to the callbacks of a SAX content handler.
+///
+/// The text of each event is the text of the node, without the escaping applied by `Display`, or
+/// by `CharacterData::data`.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum XmlEvent {
+    /// The start of a document, with its XML declaration if it has one.
+    StartDocument {
+        /// The XML declaration of the document.
+        declaration: Option<XmlDecl>,
+    },
+    /// A document type declaration.
+    DocumentType {
+        /// The name of the document element.
+        name: Name,
+        /// The public identifier of the external subset.
+        public_id: Option<String>,
+        /// The system identifier of the external subset.
+        system_id: Option<String>,
+        /// The internal subset, as a string.
+        internal_subset: Option<String>,
+    },
+    /// The start of an element.
+    StartElement {
+        /// The name of the element.
+        name: Name,
+        /// The attributes of the element, in document order, with their normalized values; this
+        /// does not include namespace declarations.
+        attributes: Vec<(Name, String)>,
+        /// The namespaces declared by `xmlns` attributes of the element, in document order, as
+        /// pairs of prefix (`None` for the default namespace) and namespace URI.
+        namespaces: Vec<(Option<String>, String)>,
+    },
+    /// The text of a `Text` node.
+    Characters(String),
+    /// The text of a `CDataSection` node.
+    CData(String),
+    /// The text of a `Comment` node.
+    Comment(String),
+    /// A processing instruction.
+    ProcessingInstruction {
+        /// The target of the processing instruction.
+        target: String,
+        /// The data of the processing instruction, if any.
+        data: Option<String>,
+    },
+    /// The end of an element.
+    EndElement {
+        /// The name of the element.
+        name: Name,
+    },
+    /// The end of a document.
+    EndDocument,
+}
+
+///
+/// An iterator over the events for a node and its descendants, see
+/// [`XmlEvents::events`](trait.XmlEvents.html#tymethod.events).
+///
+#[derive(Clone, Debug)]
+pub struct Events {
+    start: Option<RefNode>,
+    stack: Vec<(RefNode, usize)>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl XmlEvents for RefNode {
+    fn events(&self) -> Events {
+        Events {
+            start: Some(self.clone()),
+            stack: Vec::new(),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Iterator for Events {
+    type Item = XmlEvent;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(node) = self.start.take() {
+            if let Some(event) = self.enter(node) {
+                return Some(event);
+            }
+        }
+        //
+        // The stack holds each parent along the path to the current node, together with the index
+        // of the next child to visit; no borrow is held between calls.
+        //
+        while let Some((parent, index)) = self.stack.last_mut() {
+            let child = parent.borrow().i_child_nodes.get(*index).cloned();
+            match child {
+                None => {
+                    let (parent, _) = self.stack.pop().unwrap();
+                    if let Some(event) = end_event(&parent) {
+                        return Some(event);
+                    }
+                }
+                Some(child) => {
+                    *index += 1;
+                    if let Some(event) = self.enter(child) {
+                        return Some(event);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+impl Events {
+    //
+    // Return the event for `node`, and push it onto the stack if its children are to be visited.
+    //
+    fn enter(&mut self, node: RefNode) -> Option<XmlEvent> {
+        let node_type = node.borrow().i_node_type;
+        let event = match node_type {
+            NodeType::Document => Some(XmlEvent::StartDocument {
+                declaration: node.xml_declaration(),
+            }),
+            NodeType::DocumentType => Some(XmlEvent::DocumentType {
+                name: node.borrow().i_name.clone(),
+                public_id: node.public_id(),
+                system_id: node.system_id(),
+                internal_subset: node.internal_subset(),
+            }),
+            NodeType::Element => Some(start_element(&node)),
+            NodeType::Text => Some(XmlEvent::Characters(data(&node))),
+            NodeType::CData => Some(XmlEvent::CData(data(&node))),
+            NodeType::Comment => Some(XmlEvent::Comment(data(&node))),
+            NodeType::ProcessingInstruction => {
+                let ref_node = node.borrow();
+                Some(XmlEvent::ProcessingInstruction {
+                    target: ref_node.i_name.to_string(),
+                    data: ref_node.i_value.as_deref().map(String::from),
+                })
+            }
+            //
+            // The replacement text of an entity reference is reported as if it were expanded, and
+            // a fragment only for its children.
+            //
+            NodeType::EntityReference | NodeType::DocumentFragment => None,
+            NodeType::Attribute | NodeType::Entity | NodeType::Notation => return None,
+        };
+        if matches!(
+            node_type,
+            NodeType::Document
+                | NodeType::Element
+                | NodeType::EntityReference
+                | NodeType::DocumentFragment
+        ) {
+            self.stack.push((node, 0));
+        }
+        event
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn start_element(element: &RefNode) -> XmlEvent {
+    let ref_element = element.borrow();
+    let mut attributes = Vec::new();
+    let mut namespaces = Vec::new();
+    if let Extension::Element { i_attributes, .. } = &ref_element.i_extension {
+        for (name, attribute) in i_attributes.iter() {
+            let value = normalized_value(attribute).unwrap_or_default();
+            if is_namespace_declaration(name) {
+                let prefix = name
+                    .prefix()
+                    .as_ref()
+                    .map(|_| name.local_name().to_string());
+                namespaces.push((prefix, value));
+            } else {
+                attributes.push((name.clone(), value));
+            }
+        }
+    }
+    XmlEvent::StartElement {
+        name: ref_element.i_name.clone(),
+        attributes,
+        namespaces,
+    }
+}
+
+fn end_event(node: &RefNode) -> Option<XmlEvent> {
+    let ref_node = node.borrow();
+    match ref_node.i_node_type {
+        NodeType::Document => Some(XmlEvent::EndDocument),
+        NodeType::Element => Some(XmlEvent::EndElement {
+            name: ref_node.i_name.clone(),
+        }),
+        _ => None,
+    }
+}
+
+fn data(node: &RefNode) -> String {
+    node.borrow()
+        .i_value
+        .as_deref()
+        .map(String::from)
+        .unwrap_or_default()
+}
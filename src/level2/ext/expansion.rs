@@ -0,0 +1,91 @@
+/*!
+This module provides the limits type for the [`EntityLimits`](../trait.EntityLimits.html)
+extension, which bounds the work done when the replacement tree of an entity is copied into an
+entity reference, by `Document::create_entity_reference`, or into the tree, by
+[`EntityExpansion::expand_entity_references`](../trait.EntityExpansion.html#tymethod.expand_entity_references).
+
+Entities whose replacement trees refer to other entities can grow exponentially with each level
+of nesting (the "billion laughs" attack), and an entity that refers to itself, directly or through
+another entity, would never finish expanding. Each document therefore has a set of limits which
+are checked before any nodes are copied; the defaults are deliberately small, and may be raised
+for a document that is trusted.
+
+```rust
+use xml_dom::level2::*;
+use xml_dom::level2::ext::*;
+
+let implementation = get_implementation();
+let mut document_node = implementation.create_document(None, Some("doc"), None).unwrap();
+assert_eq!(document_node.expansion_limits(), ExpansionLimits::default());
+
+let limits = ExpansionLimits::new().with_max_nodes(1_000_000).with_max_depth(32);
+document_node.set_expansion_limits(limits).unwrap();
+assert_eq!(document_node.expansion_limits().max_nodes(), 1_000_000);
+```
+*/
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The limits on copying the replacement tree of entities, see the [module](index.html)
+/// documentation.
+///
+/// The default limits allow 10,000 nodes to be added by a single expansion, and entity
+/// references to be nested 8 deep. A reference to an entity from within its own replacement
+/// tree is always an error.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExpansionLimits {
+    max_nodes: usize,
+    max_depth: usize,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Default for ExpansionLimits {
+    fn default() -> Self {
+        Self {
+            max_nodes: 10_000,
+            max_depth: 8,
+        }
+    }
+}
+
+impl ExpansionLimits {
+    ///
+    /// Construct a new `ExpansionLimits` instance with the default values.
+    ///
+    pub fn new() -> Self {
+        Default::default()
+    }
+    ///
+    /// Set the number of nodes, other than entity references, that a single expansion may add;
+    /// exceeding this raises `Error::EntityExpansionSize`.
+    ///
+    pub fn with_max_nodes(self, max_nodes: usize) -> Self {
+        Self { max_nodes, ..self }
+    }
+    ///
+    /// Set the number of entity references that may be nested, one within the replacement tree
+    /// of another; exceeding this raises `Error::EntityExpansionDepth`.
+    ///
+    pub fn with_max_depth(self, max_depth: usize) -> Self {
+        Self { max_depth, ..self }
+    }
+    ///
+    /// Return the number of nodes that a single expansion may add.
+    ///
+    pub fn max_nodes(&self) -> usize {
+        self.max_nodes
+    }
+    ///
+    /// Return the number of entity references that may be nested.
+    ///
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+}
@@ -0,0 +1,80 @@
+/*!
+This module provides support types for the [`InheritedAttributes`](trait.InheritedAttributes.html)
+trait.
+*/
+
+use crate::shared::syntax::{XML_NS_ATTR_SPACE_DEFAULT, XML_NS_ATTR_SPACE_PRESERVE};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+
+// ------------------------------------------------------------------------------------------------
+//  Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The values of the `xml:space` attribute, from XML 1.1 [§2.10 White Space
+/// Handling](https://www.w3.org/TR/xml11/#sec-white-space).
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum XmlSpace {
+    /// Applications' default white-space processing modes are acceptable for this element.
+    Default,
+    /// Applications should preserve all the white space in this element.
+    Preserve,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for XmlSpace {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{}",
+            match self {
+                XmlSpace::Default => XML_NS_ATTR_SPACE_DEFAULT,
+                XmlSpace::Preserve => XML_NS_ATTR_SPACE_PRESERVE,
+            }
+        )
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl FromStr for XmlSpace {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == XML_NS_ATTR_SPACE_DEFAULT {
+            Ok(XmlSpace::Default)
+        } else if s == XML_NS_ATTR_SPACE_PRESERVE {
+            Ok(XmlSpace::Preserve)
+        } else {
+            Err(())
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_space_display() {
+        assert_eq!(format!("{}", XmlSpace::Default), "default".to_string());
+        assert_eq!(format!("{}", XmlSpace::Preserve), "preserve".to_string());
+    }
+
+    #[test]
+    fn test_space_from_str() {
+        assert_eq!(XmlSpace::from_str("default"), Ok(XmlSpace::Default));
+        assert_eq!(XmlSpace::from_str("preserve"), Ok(XmlSpace::Preserve));
+        assert!(XmlSpace::from_str("Preserve").is_err());
+        assert!(XmlSpace::from_str("").is_err());
+    }
+}
@@ -0,0 +1,234 @@
+/*!
+This module provides support types for the [`TreeIterators`](trait.TreeIterators.html) trait.
+
+All of the iterators in this module are lazy, they hold a reference to their current position in
+the tree and only borrow a node for long enough to find the next one. It is therefore safe to read,
+or modify, nodes as they are returned.
+
+```rust
+# #[cfg(feature = "quick_parser")]
+# fn main() {
+use xml_dom::level2::*;
+use xml_dom::level2::ext::*;
+use xml_dom::parser::read_xml;
+
+let document_node = read_xml(r#"<a><b><c/></b><d>text</d></a>"#).unwrap();
+let names: Vec<String> = document_node
+    .descendant_elements()
+    .map(|element| element.node_name().to_string())
+    .collect();
+assert_eq!(names, vec!["a", "b", "c", "d"]);
+# }
+# #[cfg(not(feature = "quick_parser"))]
+# fn main() {}
+```
+*/
+
+use crate::level2::ext::traits::TreeIterators;
+use crate::level2::node_impl::{Extension, RefNode, WeakRefNode};
+use crate::level2::traits::NodeType;
+use crate::shared::name::Name;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An iterator over the descendants of a node in document (pre-order) order, this does not
+/// include the node itself. See [`TreeIterators::descendants`](trait.TreeIterators.html#tymethod.descendants)
+/// and [`TreeIterators::descendant_elements`](trait.TreeIterators.html#tymethod.descendant_elements).
+///
+#[derive(Clone, Debug)]
+pub struct Descendants {
+    stack: Vec<(RefNode, usize)>,
+    node_type: Option<NodeType>,
+}
+
+///
+/// An iterator over the ancestors of a node, starting with its parent and ending with the
+/// document, or the top of a detached tree. See [`TreeIterators::ancestors`](trait.TreeIterators.html#tymethod.ancestors).
+///
+#[derive(Clone, Debug)]
+pub struct Ancestors {
+    next: Option<WeakRefNode>,
+}
+
+///
+/// An iterator over the siblings of a node, moving away from the node itself. See
+/// [`TreeIterators::preceding_siblings`](trait.TreeIterators.html#tymethod.preceding_siblings)
+/// and [`TreeIterators::following_siblings`](trait.TreeIterators.html#tymethod.following_siblings).
+///
+#[derive(Clone, Debug)]
+pub struct Siblings {
+    parent: Option<WeakRefNode>,
+    index: usize,
+    forward: bool,
+}
+
+///
+/// An iterator over the attributes of an element, in the order they were added, as pairs of name
+/// and attribute node. See [`TreeIterators::attribute_iter`](trait.TreeIterators.html#tymethod.attribute_iter).
+///
+#[derive(Clone, Debug)]
+pub struct Attributes {
+    element: RefNode,
+    index: usize,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl TreeIterators for RefNode {
+    fn descendants(&self) -> Descendants {
+        Descendants::new(self, None)
+    }
+
+    fn descendant_elements(&self) -> Descendants {
+        Descendants::new(self, Some(NodeType::Element))
+    }
+
+    fn ancestors(&self) -> Ancestors {
+        Ancestors {
+            next: self.borrow().i_parent_node.clone(),
+        }
+    }
+
+    fn preceding_siblings(&self) -> Siblings {
+        Siblings::new(self, false)
+    }
+
+    fn following_siblings(&self) -> Siblings {
+        Siblings::new(self, true)
+    }
+
+    fn attribute_iter(&self) -> Attributes {
+        Attributes {
+            element: self.clone(),
+            index: 0,
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Iterator for Descendants {
+    type Item = RefNode;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        //
+        // The stack holds each parent along the path to the current node, together with the index
+        // of the next child to visit; no borrow is held between calls.
+        //
+        while let Some((parent, index)) = self.stack.last_mut() {
+            let child = parent.borrow().i_child_nodes.get(*index).cloned();
+            match child {
+                None => {
+                    let _safe_to_ignore = self.stack.pop();
+                }
+                Some(child) => {
+                    *index += 1;
+                    self.stack.push((child.clone(), 0));
+                    let is_match = match &self.node_type {
+                        None => true,
+                        Some(node_type) => &child.borrow().i_node_type == node_type,
+                    };
+                    if is_match {
+                        return Some(child);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+impl Descendants {
+    fn new(node: &RefNode, node_type: Option<NodeType>) -> Self {
+        Self {
+            stack: vec![(node.clone(), 0)],
+            node_type,
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Iterator for Ancestors {
+    type Item = RefNode;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let ancestor = self.next.take()?.upgrade()?;
+        self.next = ancestor.borrow().i_parent_node.clone();
+        Some(ancestor)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Iterator for Siblings {
+    type Item = RefNode;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let parent = self.parent.clone()?.upgrade()?;
+        let ref_parent = parent.borrow();
+        if self.forward {
+            let sibling = ref_parent.i_child_nodes.get(self.index).cloned();
+            if sibling.is_some() {
+                self.index += 1;
+            }
+            sibling
+        } else if self.index > 0 {
+            self.index -= 1;
+            ref_parent.i_child_nodes.get(self.index).cloned()
+        } else {
+            None
+        }
+    }
+}
+
+impl Siblings {
+    fn new(node: &RefNode, forward: bool) -> Self {
+        //
+        // The position of `node` is only found once, from then on siblings are found by index.
+        //
+        let parent = node.borrow().i_parent_node.clone();
+        let position = parent
+            .clone()
+            .and_then(|parent| parent.upgrade())
+            .and_then(|parent| {
+                node.borrow()
+                    .i_index_hint
+                    .position_in(&parent.borrow().i_child_nodes, node)
+            });
+        match position {
+            None => Self {
+                parent: None,
+                index: 0,
+                forward,
+            },
+            Some(position) => Self {
+                parent,
+                index: if forward { position + 1 } else { position },
+                forward,
+            },
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Iterator for Attributes {
+    type Item = (Name, RefNode);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let ref_element = self.element.borrow();
+        if let Extension::Element { i_attributes, .. } = &ref_element.i_extension {
+            let (name, attribute) = i_attributes.get_index(self.index)?;
+            self.index += 1;
+            Some((name.clone(), attribute.clone()))
+        } else {
+            None
+        }
+    }
+}
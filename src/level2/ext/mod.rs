@@ -6,6 +6,9 @@ This module contains extensions above and beyond the Level 2 specification.
 // Public Modules
 // ------------------------------------------------------------------------------------------------
 
+pub mod adjacent;
+pub use adjacent::InsertPosition;
+
 pub mod convert;
 
 pub mod decl;
@@ -13,14 +16,51 @@ pub use decl::{XmlDecl, XmlVersion};
 
 pub mod dom_impl;
 
+pub mod dtd;
+pub use dtd::{
+    AttributeDecl, AttributeDefault, AttributeType, ContentParticle, ContentSpec, ElementDecl,
+    Occurrence,
+};
+
+pub mod expansion;
+pub use expansion::ExpansionLimits;
+
+pub mod events;
+pub use events::{Events, XmlEvent};
+
 pub mod options;
 pub use options::ProcessingOptions;
 
+pub mod iterators;
+pub use iterators::{Ancestors, Attributes, Descendants, Siblings};
+
+pub mod inherited;
+pub use inherited::XmlSpace;
+
 pub mod namespaced;
 pub use namespaced::NamespacePrefix;
 
+pub mod normalize;
+pub use normalize::NormalizeConfig;
+
+pub mod path;
+pub use path::{NameMatch, ValueMatch};
+
+pub mod replace;
+pub use replace::ReplaceConfig;
+
+pub mod tokens;
+pub use tokens::AttributeTokens;
+
+pub mod validate;
+pub use validate::ValidationError;
+
+pub mod visitor;
+pub use visitor::{NodeVisitor, VisitAction};
+
+pub mod wrap;
+
 pub(crate) mod traits;
 pub use traits::*;
 
 pub(crate) mod trait_impls;
-pub use trait_impls::*;
@@ -34,8 +34,6 @@ pub(crate) trait MutNamespaced: Namespaced {
         prefix: Option<&str>,
         namespace_uri: &str,
     ) -> Result<Option<String>>;
-    fn remove_mapping(&mut self, prefix: Option<&str>) -> Result<Option<String>>;
-    fn normalize_mappings(&mut self) -> Result<()>;
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -54,30 +52,21 @@ impl NamespacePrefix {
     /// Returns `true` of this is a `NamespacePrefix::None` value, otherwise `false`.
     ///
     pub fn is_none(&self) -> bool {
-        match *self {
-            NamespacePrefix::None => true,
-            _ => false,
-        }
+        matches!(*self, NamespacePrefix::None)
     }
 
     ///
     /// Returns `true` of this is a `NamespacePrefix::Default` value, otherwise `false`.
     ///
     pub fn is_default(&self) -> bool {
-        match *self {
-            NamespacePrefix::Default => true,
-            _ => false,
-        }
+        matches!(*self, NamespacePrefix::Default)
     }
 
     ///
     /// Returns `true` of this is a `NamespacePrefix::Some` value, otherwise `false`.
     ///
     pub fn is_some(&self) -> bool {
-        match *self {
-            NamespacePrefix::Some(_) => true,
-            _ => false,
-        }
+        matches!(*self, NamespacePrefix::Some(_))
     }
 
     ///
@@ -235,32 +224,6 @@ impl MutNamespaced for RefNode {
             Err(Error::InvalidState)
         }
     }
-
-    fn remove_mapping(&mut self, prefix: Option<&str>) -> Result<Option<String>> {
-        if !add_namespaces(self) {
-            return Ok(None);
-        }
-        let mut mut_self = self.borrow_mut();
-        if mut_self.i_node_type == NodeType::Element {
-            if let Extension::Element { i_namespaces, .. } = &mut mut_self.i_extension {
-                Ok(i_namespaces.remove(&prefix.map(String::from)))
-            } else {
-                warn!("{}", MSG_INVALID_EXTENSION);
-                Err(Error::InvalidState)
-            }
-        } else {
-            warn!("{}", MSG_INVALID_NODE_TYPE);
-            Err(Error::InvalidState)
-        }
-    }
-
-    fn normalize_mappings(&mut self) -> Result<()> {
-        // TODO: ensure this element has a mapping for it's own namespace and for any namespaced attributes
-        if !add_namespaces(self) {
-            return Ok(());
-        }
-        unimplemented!()
-    }
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -334,8 +297,8 @@ mod tests {
         // prefix
         let ns_result = Some(XSD.to_string());
 
-        assert_eq!(namespaced.contains_mapping(None), false);
-        assert_eq!(namespaced.contains_mapping(Some("xsd")), true);
+        assert!(!namespaced.contains_mapping(None));
+        assert!(namespaced.contains_mapping(Some("xsd")));
         assert_eq!(namespaced.get_namespace(None), None);
         assert_eq!(namespaced.get_namespace(Some("xsd")), ns_result);
         assert_eq!(namespaced.resolve_namespace(None), None);
@@ -344,8 +307,8 @@ mod tests {
         // namespace
         let prefix_result = NamespacePrefix::new_some("xsd");
 
-        assert_eq!(namespaced.contains_mapped_namespace(HTML), false);
-        assert_eq!(namespaced.contains_mapped_namespace(XSD), true);
+        assert!(!namespaced.contains_mapped_namespace(HTML));
+        assert!(namespaced.contains_mapped_namespace(XSD));
         assert_eq!(namespaced.get_prefix(XSD), prefix_result);
         assert_eq!(namespaced.resolve_prefix(XSD), prefix_result);
     }
@@ -362,8 +325,8 @@ mod tests {
         // prefix
         let ns_result = Some(XSD.to_string());
 
-        assert_eq!(namespaced.contains_mapping(None), true);
-        assert_eq!(namespaced.contains_mapping(Some("xsd")), false);
+        assert!(namespaced.contains_mapping(None));
+        assert!(!namespaced.contains_mapping(Some("xsd")));
         assert_eq!(namespaced.get_namespace(None), ns_result);
         assert_eq!(namespaced.get_namespace(Some("xsd")), None);
         assert_eq!(namespaced.resolve_namespace(None), ns_result);
@@ -372,8 +335,8 @@ mod tests {
         // namespace
         let prefix_result = NamespacePrefix::Default;
 
-        assert_eq!(namespaced.contains_mapped_namespace(HTML), false);
-        assert_eq!(namespaced.contains_mapped_namespace(XSD), true);
+        assert!(!namespaced.contains_mapped_namespace(HTML));
+        assert!(namespaced.contains_mapped_namespace(XSD));
         assert_eq!(namespaced.get_prefix(XSD), prefix_result);
         assert_eq!(namespaced.resolve_prefix(XSD), prefix_result);
     }
@@ -449,4 +412,30 @@ mod tests {
             NamespacePrefix::new_some("xslt")
         );
     }
+
+    #[test]
+    #[allow(unused_must_use)]
+    fn test_prefix_order() {
+        let mut document = make_document_node();
+        let mut ref_node = make_node(&mut document, "element");
+        let namespaced = &mut ref_node as MutRefNamespaced<'_>;
+
+        //
+        // When several prefixes map to the same namespace the first mapped is always found.
+        //
+        namespaced.insert_mapping(Some("z"), EX);
+        namespaced.insert_mapping(Some("a"), EX);
+        namespaced.insert_mapping(Some("m"), EX);
+        for _ in 0..20 {
+            assert_eq!(namespaced.get_prefix(EX), NamespacePrefix::new_some("z"));
+        }
+
+        //
+        // Replacing a mapping keeps its position.
+        //
+        namespaced.insert_mapping(Some("z"), HTML);
+        assert_eq!(namespaced.get_prefix(EX), NamespacePrefix::new_some("a"));
+        namespaced.insert_mapping(Some("z"), EX);
+        assert_eq!(namespaced.get_prefix(EX), NamespacePrefix::new_some("z"));
+    }
 }
@@ -0,0 +1,217 @@
+/*!
+This module provides the configuration type for the
+[`normalize_document`](../trait.NormalizeDocument.html#tymethod.normalize_document) extension.
+*/
+
+use std::fmt::{Display, Formatter, Result};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This type encapsulates the set of optional changes made by `normalize_document`; adjacent text
+/// nodes are always merged, and empty text nodes removed, as `Node::normalize` does. The default
+/// for `NormalizeConfig` is that none of the options are set.
+///
+/// This type has a set of methods that turn on options, i.e. `set_strip_comments`, and retrieve
+/// the state of an option, i.e. `has_strip_comments`.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "quick_parser")]
+/// # fn main() {
+/// use xml_dom::level2::*;
+/// use xml_dom::level2::ext::*;
+/// use xml_dom::parser::read_xml;
+///
+/// let mut document_node = read_xml("<a><!-- note -->one<![CDATA[ & two]]></a>").unwrap();
+///
+/// let mut config = NormalizeConfig::new();
+/// config.set_strip_comments();
+/// config.set_cdata_as_text();
+///
+/// document_node.normalize_document(&config).unwrap();
+/// assert_eq!(document_node.to_string(), "<a>one &#38; two</a>");
+/// # }
+/// # #[cfg(not(feature = "quick_parser"))]
+/// # fn main() {}
+/// ```
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NormalizeConfig(u8);
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+#[doc(hidden)]
+#[derive(Clone, Debug)]
+#[repr(u8)]
+enum NormalizeFlags {
+    CDataAsText = 0b0000_0001,
+    StripComments = 0b0000_0010,
+    ExpandEntityReferences = 0b0000_0100,
+    RemoveRedundantNamespaces = 0b0000_1000,
+    TrimElementContentWhitespace = 0b0001_0000,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for NormalizeConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "NormalizeConfig {{")?;
+
+        let mut option_strings: Vec<&str> = Vec::new();
+        if self.has_cdata_as_text() {
+            option_strings.push("CDataAsText");
+        }
+        if self.has_strip_comments() {
+            option_strings.push("StripComments");
+        }
+        if self.has_expand_entity_references() {
+            option_strings.push("ExpandEntityReferences");
+        }
+        if self.has_remove_redundant_namespaces() {
+            option_strings.push("RemoveRedundantNamespaces");
+        }
+        if self.has_trim_element_content_whitespace() {
+            option_strings.push("TrimElementContentWhitespace");
+        }
+        write!(f, "{}", option_strings.join(", "))?;
+
+        write!(f, "}}")
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl NormalizeConfig {
+    ///
+    /// Construct a new `NormalizeConfig` instance with all options off.
+    ///
+    pub fn new() -> Self {
+        Default::default()
+    }
+    ///
+    /// Returns true if all options are `false`.
+    ///
+    pub fn has_none(&self) -> bool {
+        self.0 == 0
+    }
+    ///
+    /// Returns `true` if CDATA sections will be replaced by text nodes, else `false`.
+    ///
+    pub fn has_cdata_as_text(&self) -> bool {
+        self.has(NormalizeFlags::CDataAsText)
+    }
+    ///
+    /// Returns `true` if comments will be removed, else `false`.
+    ///
+    pub fn has_strip_comments(&self) -> bool {
+        self.has(NormalizeFlags::StripComments)
+    }
+    ///
+    /// Returns `true` if entity references will be replaced by their content, else `false`.
+    ///
+    pub fn has_expand_entity_references(&self) -> bool {
+        self.has(NormalizeFlags::ExpandEntityReferences)
+    }
+    ///
+    /// Returns `true` if namespace declarations that repeat one already in scope will be removed,
+    /// else `false`.
+    ///
+    pub fn has_remove_redundant_namespaces(&self) -> bool {
+        self.has(NormalizeFlags::RemoveRedundantNamespaces)
+    }
+    ///
+    /// Returns `true` if whitespace-only text between elements will be removed, else `false`.
+    ///
+    pub fn has_trim_element_content_whitespace(&self) -> bool {
+        self.has(NormalizeFlags::TrimElementContentWhitespace)
+    }
+    ///
+    /// Replace each CDATA section with a text node holding the same data; this corresponds to
+    /// setting the DOM Level 3 `"cdata-sections"` parameter to `false`.
+    ///
+    pub fn set_cdata_as_text(&mut self) {
+        self.0 |= NormalizeFlags::CDataAsText as u8
+    }
+    ///
+    /// Remove all comments; this corresponds to setting the DOM Level 3 `"comments"` parameter to
+    /// `false`.
+    ///
+    pub fn set_strip_comments(&mut self) {
+        self.0 |= NormalizeFlags::StripComments as u8
+    }
+    ///
+    /// Replace each entity reference with writable copies of its content, as
+    /// [`expand_entity_references`](../trait.EntityExpansion.html#tymethod.expand_entity_references)
+    /// does; this corresponds to setting the DOM Level 3 `"entities"` parameter to `false`.
+    ///
+    pub fn set_expand_entity_references(&mut self) {
+        self.0 |= NormalizeFlags::ExpandEntityReferences as u8
+    }
+    ///
+    /// Remove each namespace declaration attribute that declares the same prefix, with the same
+    /// namespace URI, as the nearest declaration of that prefix on an ancestor element.
+    ///
+    pub fn set_remove_redundant_namespaces(&mut self) {
+        self.0 |= NormalizeFlags::RemoveRedundantNamespaces as u8
+    }
+    ///
+    /// Remove text nodes that contain only whitespace from elements that also have element
+    /// children; elements with only text content are left unchanged. This approximates setting the
+    /// DOM Level 3 `"element-content-whitespace"` parameter to `false` without a DTD.
+    ///
+    pub fn set_trim_element_content_whitespace(&mut self) {
+        self.0 |= NormalizeFlags::TrimElementContentWhitespace as u8
+    }
+
+    fn has(&self, flag: NormalizeFlags) -> bool {
+        self.0 & (flag as u8) != 0
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_none() {
+        let config = NormalizeConfig::default();
+
+        assert!(config.has_none());
+        assert!(!config.has_cdata_as_text());
+        assert!(!config.has_strip_comments());
+        assert!(!config.has_expand_entity_references());
+        assert!(!config.has_remove_redundant_namespaces());
+        assert!(!config.has_trim_element_content_whitespace());
+
+        assert_eq!(format!("{}", config), r"NormalizeConfig {}".to_string());
+        assert_eq!(config, NormalizeConfig::new());
+    }
+
+    #[test]
+    fn test_each() {
+        let mut config = NormalizeConfig::new();
+        config.set_strip_comments();
+        assert!(!config.has_none());
+        assert!(config.has_strip_comments());
+        assert!(!config.has_cdata_as_text());
+
+        config.set_trim_element_content_whitespace();
+        assert!(config.has_trim_element_content_whitespace());
+        assert_eq!(
+            format!("{}", config),
+            r"NormalizeConfig {StripComments, TrimElementContentWhitespace}".to_string()
+        );
+    }
+}
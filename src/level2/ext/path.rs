@@ -0,0 +1,213 @@
+/*!
+This module provides support types for the [`FindElements`](trait.FindElements.html) trait, and
+the parsing of the simple paths it uses.
+
+A path is a list of steps separated by `/`, each step is the name of a child element, or `*` for
+any child element, optionally followed by a one-based index in square brackets selecting one of
+the children that match. The last step may instead be `@` followed by the name of an attribute, or
+`*` for any attribute. For example `"book[2]/title"` or `"meta/@version"`.
+*/
+
+use crate::level2::node_impl::RefNode;
+use crate::level2::traits::{Node, NodeType};
+use crate::shared::error::{Error, Result, MSG_INVALID_PATH};
+use crate::shared::name::Name;
+use std::str::FromStr;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Determines how the names in a path are compared with the names of nodes.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NameMatch {
+    /// The qualified name in the path, including any prefix, must equal the node's name.
+    QualifiedName,
+    /// Only the local part of the name in the path is compared with the node's local name, any
+    /// prefix is ignored.
+    LocalName,
+}
+
+///
+/// Determines how an attribute value is compared with the value being searched for.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValueMatch {
+    /// The values must be equal.
+    Exact,
+    /// The values must be equal after leading and trailing white space is removed and each
+    /// sequence of white space characters is replaced by a single space.
+    CollapseWhitespace,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Debug, PartialEq)]
+enum Step {
+    Element(Option<Name>, Option<usize>),
+    Attribute(Option<Name>),
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+//
+// Find, in document order, up to `limit` nodes selected by `path` from `element`; only the
+// children of the elements matched by each step are examined.
+//
+pub(crate) fn find_nodes(
+    element: &RefNode,
+    path: &str,
+    name_match: NameMatch,
+    limit: usize,
+) -> Result<Vec<RefNode>> {
+    let steps = parse_path(path)?;
+    let mut found = Vec::new();
+    find_from(element, &steps, name_match, limit, &mut found);
+    Ok(found)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn find_from(
+    element: &RefNode,
+    steps: &[Step],
+    name_match: NameMatch,
+    limit: usize,
+    found: &mut Vec<RefNode>,
+) {
+    match steps.split_first() {
+        None => found.push(element.clone()),
+        Some((Step::Attribute(name), _)) => {
+            let mut attributes: Vec<RefNode> = element
+                .attributes()
+                .into_values()
+                .filter(|attribute| is_match(attribute, name, name_match))
+                .collect();
+            attributes.sort_by_key(|attribute| attribute.node_name());
+            found.extend(attributes.into_iter().take(limit - found.len()));
+        }
+        Some((Step::Element(name, index), rest)) => {
+            let children = element
+                .child_nodes()
+                .into_iter()
+                .filter(|child| child.node_type() == NodeType::Element)
+                .filter(|child| is_match(child, name, name_match));
+            let children: Vec<RefNode> = match index {
+                None => children.collect(),
+                Some(index) => children.skip(index - 1).take(1).collect(),
+            };
+            for child in &children {
+                if found.len() >= limit {
+                    return;
+                }
+                find_from(child, rest, name_match, limit, found);
+            }
+        }
+    }
+}
+
+fn is_match(node: &RefNode, name: &Option<Name>, name_match: NameMatch) -> bool {
+    match name {
+        None => true,
+        Some(name) => match name_match {
+            NameMatch::QualifiedName => node.node_name().to_string() == name.to_string(),
+            NameMatch::LocalName => &node.local_name() == name.local_name(),
+        },
+    }
+}
+
+fn parse_path(path: &str) -> Result<Vec<Step>> {
+    let segments: Vec<&str> = path.split('/').collect();
+    let mut steps = Vec::new();
+    for (index, segment) in segments.iter().enumerate() {
+        let step = match segment.strip_prefix('@') {
+            Some(name) if index == segments.len() - 1 => Step::Attribute(parse_name(name)?),
+            Some(_) => return invalid_path(),
+            None => match segment.strip_suffix(']') {
+                None => Step::Element(parse_name(segment)?, None),
+                Some(segment) => match segment.split_once('[') {
+                    None => return invalid_path(),
+                    Some((name, position)) => {
+                        if !position.chars().all(|c| c.is_ascii_digit()) {
+                            return invalid_path();
+                        }
+                        match usize::from_str(position) {
+                            Ok(position) if position > 0 => {
+                                Step::Element(parse_name(name)?, Some(position))
+                            }
+                            _ => return invalid_path(),
+                        }
+                    }
+                },
+            },
+        };
+        steps.push(step);
+    }
+    Ok(steps)
+}
+
+fn parse_name(name: &str) -> Result<Option<Name>> {
+    if name == "*" {
+        Ok(None)
+    } else {
+        match Name::from_str(name) {
+            Ok(name) => Ok(Some(name)),
+            Err(_) => invalid_path(),
+        }
+    }
+}
+
+fn invalid_path<T>() -> Result<T> {
+    warn!("{}", MSG_INVALID_PATH);
+    Err(Error::Syntax)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name(name: &str) -> Option<Name> {
+        Some(Name::from_str(name).unwrap())
+    }
+
+    #[test]
+    fn test_parse_path() {
+        assert_eq!(
+            parse_path("book[2]/dc:title").unwrap(),
+            vec![
+                Step::Element(name("book"), Some(2)),
+                Step::Element(name("dc:title"), None)
+            ]
+        );
+        assert_eq!(
+            parse_path("*/@*").unwrap(),
+            vec![Step::Element(None, None), Step::Attribute(None)]
+        );
+        assert_eq!(
+            parse_path("@version").unwrap(),
+            vec![Step::Attribute(name("version"))]
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_path() {
+        for path in [
+            "", "/a", "a/", "a//b", "a[0]", "a[]", "a[-1]", "a[x]", "a]", "a[1", "@a/b", "@", "1a",
+            "a:b:c", "a[1][2]", "a[+1]",
+        ] {
+            assert_eq!(parse_path(path), Err(Error::Syntax), "{}", path);
+        }
+    }
+}
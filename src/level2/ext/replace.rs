@@ -0,0 +1,180 @@
+/*!
+This module provides the configuration type for the
+[`ReplaceText`](../trait.ReplaceText.html) extension.
+*/
+
+use std::fmt::{Display, Formatter, Result};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This type encapsulates the set of options for `replace_text_with` and, with the `regex`
+/// feature, `replace_regex_with`; the text of `Text` nodes is always searched. The default for
+/// `ReplaceConfig` is that none of the options are set.
+///
+/// This type has a set of methods that turn on options, i.e. `set_include_attributes`, and
+/// retrieve the state of an option, i.e. `has_include_attributes`.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "quick_parser")]
+/// # fn main() {
+/// use xml_dom::level2::*;
+/// use xml_dom::level2::ext::*;
+/// use xml_dom::parser::read_xml;
+///
+/// let mut document_node =
+///     read_xml(r#"<a title="${name}">Hello ${name}<b>${name}</b><![CDATA[${name}]]></a>"#).unwrap();
+///
+/// let mut config = ReplaceConfig::new();
+/// config.set_include_attributes();
+/// config.set_exclude_cdata();
+///
+/// assert_eq!(document_node.replace_text_with("${name}", "World", &config), 3);
+/// assert_eq!(
+///     document_node.to_string(),
+///     r#"<a title="World">Hello World<b>World</b><![CDATA[${name}]]></a>"#
+/// );
+/// # }
+/// # #[cfg(not(feature = "quick_parser"))]
+/// # fn main() {}
+/// ```
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReplaceConfig(u8);
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+#[doc(hidden)]
+#[derive(Clone, Debug)]
+#[repr(u8)]
+enum ReplaceFlags {
+    IncludeAttributes = 0b0000_0001,
+    ExcludeCData = 0b0000_0010,
+    RemoveEmptyText = 0b0000_0100,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for ReplaceConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "ReplaceConfig {{")?;
+
+        let mut option_strings: Vec<&str> = Vec::new();
+        if self.has_include_attributes() {
+            option_strings.push("IncludeAttributes");
+        }
+        if self.has_exclude_cdata() {
+            option_strings.push("ExcludeCData");
+        }
+        if self.has_remove_empty_text() {
+            option_strings.push("RemoveEmptyText");
+        }
+        write!(f, "{}", option_strings.join(", "))?;
+
+        write!(f, "}}")
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl ReplaceConfig {
+    ///
+    /// Construct a new `ReplaceConfig` instance with all options off.
+    ///
+    pub fn new() -> Self {
+        Default::default()
+    }
+    ///
+    /// Returns true if all options are `false`.
+    ///
+    pub fn has_none(&self) -> bool {
+        self.0 == 0
+    }
+    ///
+    /// Returns `true` if attribute values will be searched, else `false`.
+    ///
+    pub fn has_include_attributes(&self) -> bool {
+        self.has(ReplaceFlags::IncludeAttributes)
+    }
+    ///
+    /// Returns `true` if CDATA sections will be left unchanged, else `false`.
+    ///
+    pub fn has_exclude_cdata(&self) -> bool {
+        self.has(ReplaceFlags::ExcludeCData)
+    }
+    ///
+    /// Returns `true` if text nodes left empty by a replacement will be removed, else `false`.
+    ///
+    pub fn has_remove_empty_text(&self) -> bool {
+        self.has(ReplaceFlags::RemoveEmptyText)
+    }
+    ///
+    /// Also search the values of attributes; namespace declarations are never changed.
+    ///
+    pub fn set_include_attributes(&mut self) {
+        self.0 |= ReplaceFlags::IncludeAttributes as u8
+    }
+    ///
+    /// Do not search the text of CDATA sections.
+    ///
+    pub fn set_exclude_cdata(&mut self) {
+        self.0 |= ReplaceFlags::ExcludeCData as u8
+    }
+    ///
+    /// Remove each `Text` or `CDataSection` node whose text is empty after a replacement; an
+    /// attribute value left empty is kept.
+    ///
+    pub fn set_remove_empty_text(&mut self) {
+        self.0 |= ReplaceFlags::RemoveEmptyText as u8
+    }
+
+    fn has(&self, flag: ReplaceFlags) -> bool {
+        self.0 & (flag as u8) != 0
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_none() {
+        let config = ReplaceConfig::default();
+
+        assert!(config.has_none());
+        assert!(!config.has_include_attributes());
+        assert!(!config.has_exclude_cdata());
+        assert!(!config.has_remove_empty_text());
+
+        assert_eq!(format!("{}", config), r"ReplaceConfig {}".to_string());
+        assert_eq!(config, ReplaceConfig::new());
+    }
+
+    #[test]
+    fn test_each() {
+        let mut config = ReplaceConfig::new();
+        config.set_exclude_cdata();
+        assert!(!config.has_none());
+        assert!(config.has_exclude_cdata());
+        assert!(!config.has_include_attributes());
+
+        config.set_remove_empty_text();
+        assert!(config.has_remove_empty_text());
+        assert_eq!(
+            format!("{}", config),
+            r"ReplaceConfig {ExcludeCData, RemoveEmptyText}".to_string()
+        );
+    }
+}
@@ -0,0 +1,169 @@
+/*!
+This module provides support types for the [`TokenAttributes`](trait.TokenAttributes.html) trait,
+which treats the value of an attribute as a set of white space separated tokens, as used by
+attributes such as `class`, `rel`, or the XML Schema `memberTypes`.
+
+The value is read, and written, each time a method is called; an `AttributeTokens` holds only the
+element and the name of the attribute, and so always reflects the current value.
+
+```rust
+# #[cfg(feature = "quick_parser")]
+# fn main() {
+use xml_dom::level2::*;
+use xml_dom::level2::ext::*;
+use xml_dom::parser::read_xml;
+
+let document_node = read_xml(r#"<a class=" one  two one "/>"#).unwrap();
+let root_node = document_node.first_child().unwrap();
+
+let mut classes = root_node.attribute_tokens("class");
+assert!(classes.contains("two"));
+assert_eq!(classes.iter().collect::<Vec<String>>(), vec!["one", "two"]);
+
+classes.add("three").unwrap();
+classes.remove("one").unwrap();
+assert!(!classes.toggle("two").unwrap());
+assert_eq!(root_node.to_string(), r#"<a class="three"></a>"#);
+# }
+# #[cfg(not(feature = "quick_parser"))]
+# fn main() {}
+```
+*/
+
+use crate::level2::ext::traits::TokenAttributes;
+use crate::level2::node_impl::RefNode;
+use crate::level2::trait_impls::normalized_value;
+use crate::level2::traits::Element;
+use crate::shared::error::{Error, Result, MSG_EMPTY_TOKEN, MSG_INVALID_TOKEN};
+use crate::shared::text::is_xml_space;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The tokens of an attribute value, see
+/// [`TokenAttributes::attribute_tokens`](trait.TokenAttributes.html#tymethod.attribute_tokens).
+///
+/// The tokens are read in the order they appear in the value, with any duplicates removed. Each
+/// method that changes the tokens writes the value back as the remaining tokens separated by a
+/// single space, without leading or trailing white space, and so also removes any duplicates.
+///
+/// **Exceptions**
+///
+/// Each method that takes a token raises the following if the token is not valid.
+///
+/// * `SYNTAX_ERR`: Raised if the token is empty.
+/// * `INVALID_CHARACTER_ERR`: Raised if the token contains white space.
+///
+#[derive(Clone, Debug)]
+pub struct AttributeTokens {
+    element: RefNode,
+    name: String,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl TokenAttributes for RefNode {
+    fn attribute_tokens(&self, name: &str) -> AttributeTokens {
+        AttributeTokens {
+            element: self.clone(),
+            name: name.to_string(),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl AttributeTokens {
+    ///
+    /// Returns `true` if `token` is one of the tokens of the attribute; returns `false` if the
+    /// attribute is not present, or if `token` is not a valid token.
+    ///
+    pub fn contains(&self, token: &str) -> bool {
+        self.tokens().iter().any(|t| t == token)
+    }
+    ///
+    /// Add `token`, if it is not already present, creating the attribute if necessary.
+    ///
+    pub fn add(&mut self, token: &str) -> Result<()> {
+        check_token(token)?;
+        let mut tokens = self.tokens();
+        if !tokens.iter().any(|t| t == token) {
+            tokens.push(token.to_string());
+        }
+        self.write(&tokens)
+    }
+    ///
+    /// Remove `token`, if it is present; if the attribute is not present it is not created.
+    ///
+    pub fn remove(&mut self, token: &str) -> Result<()> {
+        check_token(token)?;
+        if self.value().is_none() {
+            return Ok(());
+        }
+        let mut tokens = self.tokens();
+        tokens.retain(|t| t != token);
+        self.write(&tokens)
+    }
+    ///
+    /// Remove `token` if it is present, else add it; returns `true` if `token` is present
+    /// afterwards.
+    ///
+    pub fn toggle(&mut self, token: &str) -> Result<bool> {
+        if self.contains(token) {
+            self.remove(token)?;
+            Ok(false)
+        } else {
+            self.add(token)?;
+            Ok(true)
+        }
+    }
+    ///
+    /// Returns an iterator over the tokens of the attribute, this is empty if the attribute is not
+    /// present.
+    ///
+    pub fn iter(&self) -> std::vec::IntoIter<String> {
+        self.tokens().into_iter()
+    }
+
+    fn value(&self) -> Option<String> {
+        self.element
+            .get_attribute_node(&self.name)
+            .and_then(|attribute_node| normalized_value(&attribute_node))
+    }
+
+    fn tokens(&self) -> Vec<String> {
+        let mut tokens: Vec<String> = Vec::new();
+        if let Some(value) = self.value() {
+            for token in value.split(is_xml_space).filter(|t| !t.is_empty()) {
+                if !tokens.iter().any(|t| t == token) {
+                    tokens.push(token.to_string());
+                }
+            }
+        }
+        tokens
+    }
+
+    fn write(&mut self, tokens: &[String]) -> Result<()> {
+        self.element.set_attribute(&self.name, &tokens.join(" "))
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn check_token(token: &str) -> Result<()> {
+    if token.is_empty() {
+        warn!("{}", MSG_EMPTY_TOKEN);
+        Err(Error::Syntax)
+    } else if token.chars().any(is_xml_space) {
+        warn!("{}", MSG_INVALID_TOKEN);
+        Err(Error::InvalidCharacter)
+    } else {
+        Ok(())
+    }
+}
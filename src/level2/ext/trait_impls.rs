@@ -1,10 +1,41 @@
+use crate::level2::convert::{as_attribute, is_cdata_section, is_document, is_element, is_text};
 use crate::level2::dom_impl::Implementation;
+use crate::level2::ext::adjacent::InsertPosition;
 use crate::level2::ext::decl::*;
+use crate::level2::ext::dtd::{AttributeDecl, AttributeType, ElementDecl};
+use crate::level2::ext::expansion::ExpansionLimits;
+use crate::level2::ext::inherited::XmlSpace;
+use crate::level2::ext::normalize::NormalizeConfig;
 use crate::level2::ext::options::ProcessingOptions;
+use crate::level2::ext::path::{find_nodes, NameMatch, ValueMatch};
+use crate::level2::ext::replace::ReplaceConfig;
 use crate::level2::ext::traits::*;
 use crate::level2::node_impl::*;
-use crate::level2::trait_impls::create_document_with_options;
+use crate::level2::trait_impls::{
+    append_children, attribute_decls_for, check_expansion, check_writable,
+    create_document_with_options, expansion_limits, is_namespace_declaration, normalized_value,
+    retain_children, sort_children_by,
+};
+use crate::level2::traits::{Attribute, Document, Element, Node, NodeType, Text};
+use crate::level2::traversal::is_inclusive_ancestor;
 use crate::shared::error::*;
+use crate::shared::name::Name;
+use crate::shared::text;
+#[cfg(feature = "regex")]
+use regex::Regex;
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+#[cfg(feature = "quick_parser")]
+use crate::level2::convert::as_document;
+#[cfg(feature = "quick_parser")]
+use crate::level2::trait_impls::{add_namespace_declarations, Namespaces};
+#[cfg(feature = "quick_parser")]
+use crate::parser::{read_xml, Error as ParserError};
+#[cfg(feature = "quick_parser")]
+use crate::shared::syntax::*;
 
 // ------------------------------------------------------------------------------------------------
 // Implementations
@@ -37,6 +68,157 @@ impl DocumentDecl for RefNode {
             Err(Error::InvalidState)
         }
     }
+
+    fn xml_version(&self) -> String {
+        self.xml_declaration()
+            .unwrap_or_default()
+            .version()
+            .to_string()
+    }
+
+    fn set_xml_version(&mut self, version: &str) -> Result<()> {
+        let version = match XmlVersion::from_str(version) {
+            Ok(version) => version,
+            Err(_) => {
+                warn!("{}", MSG_UNSUPPORTED_XML_VERSION);
+                return Err(Error::NotSupported);
+            }
+        };
+        let xml_decl = self.xml_declaration().unwrap_or_default();
+        self.set_xml_declaration(XmlDecl::new(
+            version,
+            xml_decl.encoding(),
+            xml_decl.standalone(),
+        ))
+    }
+
+    fn xml_encoding(&self) -> Option<String> {
+        self.xml_declaration()
+            .and_then(|xml_decl| xml_decl.encoding())
+    }
+
+    fn xml_standalone(&self) -> bool {
+        self.xml_declaration()
+            .and_then(|xml_decl| xml_decl.standalone())
+            .unwrap_or_default()
+    }
+
+    fn set_xml_standalone(&mut self, standalone: bool) -> Result<()> {
+        let xml_decl = self.xml_declaration().unwrap_or_default();
+        self.set_xml_declaration(XmlDecl::new(
+            xml_decl.version(),
+            xml_decl.encoding(),
+            Some(standalone),
+        ))
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl DocumentTypeDecl for RefNode {
+    fn add_entity(&mut self, entity: RefNode) -> Result<bool> {
+        add_declaration(self, entity, NodeType::Entity)
+    }
+
+    fn add_notation(&mut self, notation: RefNode) -> Result<bool> {
+        add_declaration(self, notation, NodeType::Notation)
+    }
+
+    fn add_attribute_decl(&mut self, attribute_decl: AttributeDecl) -> Result<bool> {
+        let mut mut_self = self.borrow_mut();
+        if let Extension::DocumentType {
+            i_attribute_decls, ..
+        } = &mut mut_self.i_extension
+        {
+            if i_attribute_decls.iter().any(|existing| {
+                existing.element_name() == attribute_decl.element_name()
+                    && existing.attribute_name() == attribute_decl.attribute_name()
+            }) {
+                Ok(false)
+            } else {
+                i_attribute_decls.push(attribute_decl);
+                Ok(true)
+            }
+        } else {
+            warn!("{}", MSG_INVALID_EXTENSION);
+            Err(Error::InvalidState)
+        }
+    }
+
+    fn attribute_decls(&self) -> Vec<AttributeDecl> {
+        let ref_self = self.borrow();
+        if let Extension::DocumentType {
+            i_attribute_decls, ..
+        } = &ref_self.i_extension
+        {
+            i_attribute_decls.clone()
+        } else {
+            warn!("{}", MSG_INVALID_EXTENSION);
+            Vec::default()
+        }
+    }
+
+    fn add_element_decl(&mut self, element_decl: ElementDecl) -> Result<bool> {
+        let mut mut_self = self.borrow_mut();
+        if let Extension::DocumentType {
+            i_element_decls, ..
+        } = &mut mut_self.i_extension
+        {
+            if i_element_decls
+                .iter()
+                .any(|existing| existing.name() == element_decl.name())
+            {
+                Ok(false)
+            } else {
+                i_element_decls.push(element_decl);
+                Ok(true)
+            }
+        } else {
+            warn!("{}", MSG_INVALID_EXTENSION);
+            Err(Error::InvalidState)
+        }
+    }
+
+    fn element_decls(&self) -> Vec<ElementDecl> {
+        let ref_self = self.borrow();
+        if let Extension::DocumentType {
+            i_element_decls, ..
+        } = &ref_self.i_extension
+        {
+            i_element_decls.clone()
+        } else {
+            warn!("{}", MSG_INVALID_EXTENSION);
+            Vec::default()
+        }
+    }
+
+    fn set_internal_subset(&mut self, internal_subset: &str) -> Result<()> {
+        let mut mut_self = self.borrow_mut();
+        if let Extension::DocumentType {
+            i_internal_subset, ..
+        } = &mut mut_self.i_extension
+        {
+            *i_internal_subset = Some(internal_subset.to_string());
+            Ok(())
+        } else {
+            warn!("{}", MSG_INVALID_EXTENSION);
+            Err(Error::InvalidState)
+        }
+    }
+
+    fn unset_internal_subset(&mut self) -> Result<()> {
+        let mut mut_self = self.borrow_mut();
+        if let Extension::DocumentType {
+            i_internal_subset, ..
+        } = &mut mut_self.i_extension
+        {
+            *i_internal_subset = None;
+            Ok(())
+        } else {
+            warn!("{}", MSG_INVALID_EXTENSION);
+            Err(Error::InvalidState)
+        }
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -52,3 +234,1017 @@ impl DOMImplementation for Implementation {
         create_document_with_options(namespace_uri, qualified_name, doc_type, options)
     }
 }
+
+// ------------------------------------------------------------------------------------------------
+
+impl FindElements for RefNode {
+    fn find_value(&self, path: &str) -> Result<Option<String>> {
+        Ok(self.find(path)?.and_then(|node| match as_attribute(&node) {
+            Ok(attribute) => attribute.value(),
+            Err(_) => node.text_content(),
+        }))
+    }
+
+    fn find_by(&self, path: &str, name_match: NameMatch) -> Result<Option<RefNode>> {
+        Ok(find_nodes(self, path, name_match, 1)?.pop())
+    }
+
+    fn find_all_by(&self, path: &str, name_match: NameMatch) -> Result<Vec<RefNode>> {
+        find_nodes(self, path, name_match, usize::MAX)
+    }
+
+    fn elements_with_attribute(&self, name: &str) -> Vec<RefNode> {
+        elements_with(self, |element| element.get_attribute_node(name), None)
+    }
+
+    fn elements_with_attribute_ns(&self, namespace_uri: &str, local_name: &str) -> Vec<RefNode> {
+        elements_with(
+            self,
+            |element| element.get_attribute_node_ns(namespace_uri, local_name),
+            None,
+        )
+    }
+
+    fn elements_with_attribute_value(
+        &self,
+        name: &str,
+        value: &str,
+        value_match: ValueMatch,
+    ) -> Vec<RefNode> {
+        elements_with(
+            self,
+            |element| element.get_attribute_node(name),
+            Some((value, value_match)),
+        )
+    }
+
+    fn elements_with_attribute_value_ns(
+        &self,
+        namespace_uri: &str,
+        local_name: &str,
+        value: &str,
+        value_match: ValueMatch,
+    ) -> Vec<RefNode> {
+        elements_with(
+            self,
+            |element| element.get_attribute_node_ns(namespace_uri, local_name),
+            Some((value, value_match)),
+        )
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl InheritedAttributes for RefNode {
+    fn xml_space(&self) -> Option<XmlSpace> {
+        inherited_values(self, Name::XML_SPACE).find_map(|value| XmlSpace::from_str(&value).ok())
+    }
+
+    fn xml_lang(&self) -> Option<String> {
+        inherited_values(self, Name::XML_LANG).next()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl WholeText for RefNode {
+    fn whole_text(&self) -> String {
+        text_run(self)
+            .iter()
+            .map(|node| node.node_value().unwrap_or_default())
+            .collect()
+    }
+
+    fn replace_whole_text(&mut self, content: &str) -> Result<Option<RefNode>> {
+        let run = text_run(self);
+        if let Some(parent) = self.parent_node() {
+            check_writable(&parent)?;
+        }
+        for node in &run {
+            check_writable(node)?;
+        }
+        for node in run {
+            if &node != self || content.is_empty() {
+                if let Some(mut parent) = node.parent_node() {
+                    let _safe_to_ignore = parent.remove_child(node)?;
+                }
+            }
+        }
+        if content.is_empty() {
+            Ok(None)
+        } else {
+            self.set_node_value(content)?;
+            Ok(Some(self.clone()))
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl StripWhitespace for RefNode {
+    fn strip_whitespace_text_nodes(&mut self, respect_xml_space: bool) -> Result<()> {
+        let mut elements: Vec<RefNode> = self.descendant_elements().collect();
+        if is_element(self) {
+            elements.insert(0, self.clone());
+        }
+        for mut element in elements {
+            if element.borrow().i_read_only || (respect_xml_space && is_space_preserved(&element)) {
+                continue;
+            }
+            let child_nodes = element.child_nodes().to_vec();
+            let is_mixed = child_nodes.iter().any(|child_node| {
+                (is_text(child_node) || is_cdata_section(child_node))
+                    && !child_node.is_element_content_whitespace()
+            });
+            if is_mixed {
+                continue;
+            }
+            for child_node in child_nodes {
+                if is_text(&child_node) {
+                    let _safe_to_ignore = element.remove_child(child_node)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl EntityExpansion for RefNode {
+    fn expand_entity_references(&mut self) -> Result<()> {
+        check_expansion(self, vec![(self.clone(), Vec::new())])?;
+        loop {
+            //
+            // Only the outermost references are expanded in each pass, as copies of any nested
+            // references are made when their parent reference is expanded.
+            //
+            let entity_references: Vec<RefNode> = self
+                .descendants()
+                .filter(|node| {
+                    node.node_type() == NodeType::EntityReference
+                        && node.has_child_nodes()
+                        && !node
+                            .ancestors()
+                            .any(|ancestor| ancestor.node_type() == NodeType::EntityReference)
+                })
+                .collect();
+            if entity_references.is_empty() {
+                return Ok(());
+            }
+            for entity_reference in entity_references {
+                let mut parent = entity_reference.parent_node().unwrap();
+                for child_node in entity_reference.child_nodes() {
+                    if let Some(new_child) = child_node.clone_node(true) {
+                        let _safe_to_ignore =
+                            parent.insert_before(new_child, Some(entity_reference.clone()))?;
+                    }
+                }
+                let _safe_to_ignore = parent.remove_child(entity_reference)?;
+            }
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl EntityLimits for RefNode {
+    fn expansion_limits(&self) -> ExpansionLimits {
+        expansion_limits(self)
+    }
+
+    fn set_expansion_limits(&mut self, limits: ExpansionLimits) -> Result<()> {
+        let mut mut_self = self.borrow_mut();
+        if let Extension::Document {
+            i_expansion_limits, ..
+        } = &mut mut_self.i_extension
+        {
+            *i_expansion_limits = limits;
+            Ok(())
+        } else {
+            warn!("{}", MSG_INVALID_EXTENSION);
+            Err(Error::InvalidState)
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl NormalizeDocument for RefNode {
+    fn normalize_document(&mut self, config: &NormalizeConfig) -> Result<()> {
+        if !is_document(self) {
+            warn!("{}", MSG_INVALID_NODE_TYPE);
+            return Err(Error::InvalidState);
+        }
+        if config.has_expand_entity_references() {
+            self.expand_entity_references()?;
+        }
+        if config.has_strip_comments() || config.has_cdata_as_text() {
+            for node in self.descendants().collect::<Vec<RefNode>>() {
+                if node.borrow().i_read_only {
+                    continue;
+                }
+                let mut parent = match node.parent_node() {
+                    None => continue,
+                    Some(parent) => parent,
+                };
+                match node.node_type() {
+                    NodeType::Comment if config.has_strip_comments() => {
+                        let _safe_to_ignore = parent.remove_child(node)?;
+                    }
+                    NodeType::CData if config.has_cdata_as_text() => {
+                        let text_node =
+                            self.create_text_node(&node.node_value().unwrap_or_default());
+                        let _safe_to_ignore = parent.replace_child(text_node, node)?;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        self.normalize();
+        if config.has_trim_element_content_whitespace() {
+            for mut element in self.descendant_elements().collect::<Vec<RefNode>>() {
+                if element.borrow().i_read_only
+                    || !element
+                        .child_nodes()
+                        .iter()
+                        .any(|child_node| is_element(&child_node))
+                {
+                    continue;
+                }
+                for child_node in element.child_nodes().to_vec() {
+                    if is_text(&child_node) && child_node.is_element_content_whitespace() {
+                        let _safe_to_ignore = element.remove_child(child_node)?;
+                    }
+                }
+            }
+        }
+        if config.has_remove_redundant_namespaces() {
+            for mut element in self.descendant_elements().collect::<Vec<RefNode>>() {
+                let attribute_nodes = element.borrow().attribute_nodes();
+                for attribute_node in attribute_nodes {
+                    if is_namespace_declaration(&attribute_node.node_name())
+                        && is_redundant_declaration(&element, &attribute_node)
+                    {
+                        let _safe_to_ignore = element.remove_attribute_node(attribute_node)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl ReplaceText for RefNode {
+    fn replace_text(&mut self, pattern: &str, replacement: &str) -> usize {
+        self.replace_text_with(pattern, replacement, &ReplaceConfig::default())
+    }
+
+    fn replace_text_with(
+        &mut self,
+        pattern: &str,
+        replacement: &str,
+        config: &ReplaceConfig,
+    ) -> usize {
+        if pattern.is_empty() {
+            return 0;
+        }
+        replace_in_text(self, config, |text| {
+            let count = text.matches(pattern).count();
+            (count, text.replace(pattern, replacement))
+        })
+    }
+
+    #[cfg(feature = "regex")]
+    fn replace_regex(&mut self, pattern: &Regex, replacement: &str) -> usize {
+        self.replace_regex_with(pattern, replacement, &ReplaceConfig::default())
+    }
+
+    #[cfg(feature = "regex")]
+    fn replace_regex_with(
+        &mut self,
+        pattern: &Regex,
+        replacement: &str,
+        config: &ReplaceConfig,
+    ) -> usize {
+        replace_in_text(self, config, |text| {
+            let count = pattern.find_iter(text).count();
+            (count, pattern.replace_all(text, replacement).into_owned())
+        })
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl InnerXml for RefNode {
+    fn outer_xml(&self) -> String {
+        self.to_string()
+    }
+
+    fn inner_xml(&self) -> String {
+        self.child_nodes()
+            .iter()
+            .map(|child_node| child_node.to_string())
+            .collect()
+    }
+
+    #[cfg(feature = "quick_parser")]
+    fn set_inner_xml(&mut self, xml: &str) -> Result<()> {
+        check_writable(self)?;
+        if self.node_type() != NodeType::Element {
+            warn!("{}", MSG_INVALID_NODE_TYPE);
+            return Err(Error::InvalidState);
+        }
+        let new_children = parse_fragment(self, xml)?;
+        for child_node in self.child_nodes().to_vec() {
+            let _safe_to_ignore = self.remove_child(child_node)?;
+        }
+        for new_child in new_children {
+            let _safe_to_ignore = self.append_child(new_child)?;
+        }
+        Ok(())
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl ReleaseDocument for RefNode {
+    fn detach_all(&mut self) -> Result<()> {
+        if !is_document(self) {
+            warn!("{}", MSG_INVALID_NODE_TYPE);
+            return Err(Error::InvalidState);
+        }
+        let mut released = Vec::new();
+        {
+            let mut mut_self = self.borrow_mut();
+            if let Extension::Document {
+                i_document_type,
+                i_removal_observers,
+                i_mutation_listeners,
+                ..
+            } = &mut mut_self.i_extension
+            {
+                *i_document_type = None;
+                i_removal_observers.clear();
+                released.push(std::mem::take(i_mutation_listeners));
+            }
+        }
+        //
+        // Walk the tree, and any declarations, without recursion; attributes keep their value but
+        // lose their user data. The user data and listeners are only dropped once no node is
+        // borrowed, as dropping them may drop nodes.
+        //
+        let mut user_data = Vec::new();
+        let mut nodes = vec![self.clone()];
+        while let Some(node) = nodes.pop() {
+            let mut mut_node = node.borrow_mut();
+            user_data.push(std::mem::take(&mut mut_node.i_user_data));
+            for child_node in std::mem::take(&mut mut_node.i_child_nodes) {
+                child_node.borrow_mut().i_parent_node = None;
+                nodes.push(child_node);
+            }
+            match &mut_node.i_extension {
+                Extension::Element { i_attributes, .. } => user_data.extend(
+                    i_attributes
+                        .values()
+                        .map(|attribute| std::mem::take(&mut attribute.borrow_mut().i_user_data)),
+                ),
+                Extension::DocumentType {
+                    i_entities,
+                    i_notations,
+                    ..
+                } => nodes.extend(i_entities.values().chain(i_notations.values()).cloned()),
+                _ => (),
+            }
+        }
+        drop(user_data);
+        drop(released);
+        Ok(())
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl InternedNames for RefNode {
+    fn name_pool_len(&self) -> usize {
+        if let Extension::Document { i_name_pool, .. } = &self.borrow().i_extension {
+            i_name_pool.len()
+        } else {
+            warn!("{}", MSG_INVALID_NODE_TYPE);
+            0
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl TreeMembership for RefNode {
+    fn contains(&self, other: &RefNode) -> bool {
+        is_inclusive_ancestor(self, other)
+    }
+
+    fn root_node(&self) -> RefNode {
+        self.ancestors().last().unwrap_or_else(|| self.clone())
+    }
+
+    fn is_attached(&self) -> bool {
+        let node = match as_attribute(self) {
+            Ok(attribute) => match attribute.owner_element() {
+                None => return false,
+                Some(element) => element,
+            },
+            Err(_) => self.clone(),
+        };
+        match node.owner_document() {
+            None => is_document(&node),
+            Some(document_node) => document_node == node.root_node(),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl NodePath for RefNode {
+    fn node_path(&self) -> String {
+        if let Ok(attribute) = as_attribute(self) {
+            let step = format!("@{}", self.node_name());
+            return match attribute.owner_element() {
+                None => step,
+                Some(element) => format!("{}/{}", element.node_path(), step),
+            };
+        }
+        let mut steps: Vec<String> = Vec::new();
+        let mut node = self.clone();
+        while let Some(parent) = node.parent_node() {
+            steps.push(path_step(&parent, &node));
+            node = parent;
+        }
+        steps.reverse();
+        if is_document(&node) {
+            format!("/{}", steps.join("/"))
+        } else {
+            steps.insert(0, step_name(&node));
+            steps.join("/")
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl ResolvePath for RefNode {
+    fn node_at_path(&self, path: &str) -> Option<RefNode> {
+        let mut node = self.clone();
+        if path == "/" {
+            return Some(node);
+        }
+        let mut steps = path.strip_prefix('/')?.split('/').peekable();
+        while let Some(step) = steps.next() {
+            if let Some(name) = step.strip_prefix('@') {
+                if steps.peek().is_some() {
+                    return None;
+                }
+                return node
+                    .attribute_iter()
+                    .find(|(attribute_name, _)| attribute_name.to_string() == name)
+                    .map(|(_, attribute_node)| attribute_node);
+            }
+            let (name, position) = match step.strip_suffix(']').and_then(|s| s.rsplit_once('[')) {
+                None => (step, 1),
+                Some((name, position)) => (name, usize::from_str(position).ok()?),
+            };
+            node = node
+                .child_nodes()
+                .into_iter()
+                .filter(|child_node| step_name(child_node) == name)
+                .nth(position.checked_sub(1)?)?;
+        }
+        Some(node)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl CrossReferences for RefNode {
+    fn collect_ids(&self) -> HashMap<String, RefNode> {
+        let mut ids = HashMap::new();
+        for element in self.descendant_elements() {
+            for (_, attribute) in element.attribute_iter() {
+                if attribute.is_id() {
+                    if let Some(value) = normalized_value(&attribute) {
+                        let value = value.trim_matches(text::is_xml_space).to_string();
+                        let _safe_to_ignore = ids.entry(value).or_insert_with(|| element.clone());
+                    }
+                }
+            }
+        }
+        ids
+    }
+
+    fn unresolved_idrefs(&self) -> Vec<(RefNode, Name, String)> {
+        let ids = self.collect_ids();
+        let mut unresolved = Vec::new();
+        for element in self.descendant_elements() {
+            let idref_names: Vec<String> =
+                attribute_decls_for(&element, &element.node_name().to_string())
+                    .into_iter()
+                    .filter(|attribute_decl| {
+                        matches!(
+                            attribute_decl.attribute_type(),
+                            AttributeType::IdRef | AttributeType::IdRefs
+                        )
+                    })
+                    .map(|attribute_decl| attribute_decl.attribute_name().to_string())
+                    .collect();
+            if idref_names.is_empty() {
+                continue;
+            }
+            for (name, attribute) in element.attribute_iter() {
+                if !idref_names.contains(&name.to_string()) {
+                    continue;
+                }
+                let value = normalized_value(&attribute).unwrap_or_default();
+                for id in value.split(text::is_xml_space).filter(|id| !id.is_empty()) {
+                    if !ids.contains_key(id) {
+                        unresolved.push((element.clone(), name.clone(), id.to_string()));
+                    }
+                }
+            }
+        }
+        unresolved
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl ReferenceAttributes for RefNode {
+    fn referenced_element(&self, name: &str) -> Option<RefNode> {
+        let attribute = self.get_attribute_node(name)?;
+        let value = normalized_value(&attribute)?;
+        let id = value.split(text::is_xml_space).find(|id| !id.is_empty())?;
+        self.owner_document()?.get_element_by_id(id)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl SelfManipulation for RefNode {
+    fn remove_self(&mut self) -> Result<Self::NodeRef> {
+        match self.parent_node() {
+            None => Ok(self.clone()),
+            Some(mut parent_node) => parent_node.remove_child(self.clone()),
+        }
+    }
+
+    fn replace_with(&mut self, other: Self::NodeRef) -> Result<Self::NodeRef> {
+        parent_of(self)?.replace_child(other, self.clone())
+    }
+
+    fn insert_adjacent(
+        &mut self,
+        position: InsertPosition,
+        node: Self::NodeRef,
+    ) -> Result<Self::NodeRef> {
+        match position {
+            InsertPosition::BeforeSelf => parent_of(self)?.insert_before(node, Some(self.clone())),
+            InsertPosition::AfterSelf => parent_of(self)?.insert_before(node, self.next_sibling()),
+            InsertPosition::FirstChild => {
+                let first_node = self.first_child();
+                self.insert_before(node, first_node)
+            }
+            InsertPosition::LastChild => self.append_child(node),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl WrapElements for RefNode {
+    fn unwrap_children(&mut self) -> Result<Vec<Self::NodeRef>> {
+        let mut parent_node = parent_of(self)?;
+        check_writable(&parent_node)?;
+        let document_node = owner_document_of(self)?;
+        let mut fragment_node = document_node.create_document_fragment()?;
+        let child_nodes = self.child_nodes().to_vec();
+        for child_node in &child_nodes {
+            let _safe_to_ignore = fragment_node.append_child(child_node.clone())?;
+        }
+        //
+        // If the children may not replace this element, move them back before returning the
+        // error so that neither node is changed.
+        //
+        if let Err(error) = parent_node.replace_child(fragment_node, self.clone()) {
+            for child_node in &child_nodes {
+                let _safe_to_ignore = self.append_child(child_node.clone())?;
+            }
+            return Err(error);
+        }
+        Ok(child_nodes)
+    }
+
+    fn wrap_children(&mut self, tag_name: &str) -> Result<Self::NodeRef> {
+        let wrapper_node = owner_document_of(self)?.create_element(tag_name)?;
+        wrap_all_children(self, wrapper_node)
+    }
+
+    fn wrap_children_ns(
+        &mut self,
+        namespace_uri: &str,
+        qualified_name: &str,
+    ) -> Result<Self::NodeRef> {
+        let wrapper_node =
+            owner_document_of(self)?.create_element_ns(namespace_uri, qualified_name)?;
+        wrap_all_children(self, wrapper_node)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl ChildAccess for RefNode {
+    fn child_node_count(&self) -> usize {
+        self.borrow().i_child_nodes.len()
+    }
+
+    fn child(&self, index: usize) -> Option<RefNode> {
+        self.borrow().i_child_nodes.get(index).cloned()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl BulkChildren for RefNode {
+    fn append_children<I>(&mut self, nodes: I) -> Result<()>
+    where
+        I: IntoIterator<Item = Self::NodeRef>,
+    {
+        append_children(self, nodes)
+    }
+
+    fn retain_children<F>(&mut self, predicate: F) -> Result<Vec<Self::NodeRef>>
+    where
+        F: FnMut(&Self::NodeRef) -> bool,
+    {
+        retain_children(self, predicate)
+    }
+
+    fn sort_children_by<F>(&mut self, compare: F) -> Result<()>
+    where
+        F: FnMut(&Self::NodeRef, &Self::NodeRef) -> Ordering,
+    {
+        sort_children_by(self, compare)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+//
+// Whether whitespace in `element` is to be preserved, according to the nearest `xml:space`
+// attribute on the element or its ancestors.
+//
+fn is_space_preserved(element: &RefNode) -> bool {
+    element.xml_space() == Some(XmlSpace::Preserve)
+}
+
+//
+// The values of the attribute `name` on `element` and its ancestor elements, nearest first.
+//
+fn inherited_values(element: &RefNode, name: &'static str) -> impl Iterator<Item = String> {
+    std::iter::once(element.clone())
+        .chain(element.ancestors())
+        .filter(is_element)
+        .filter_map(move |element| element.get_attribute(name))
+}
+
+//
+// The step for `node` in a path, as an XPath node test.
+//
+fn step_name(node: &RefNode) -> String {
+    match node.node_type() {
+        NodeType::Element => node.node_name().to_string(),
+        NodeType::Text | NodeType::CData => "text()".to_string(),
+        NodeType::Comment => "comment()".to_string(),
+        NodeType::ProcessingInstruction => {
+            format!("processing-instruction('{}')", node.node_name())
+        }
+        _ => "node()".to_string(),
+    }
+}
+
+//
+// The step for `node` below `parent`, with a position if another child has the same step.
+//
+fn path_step(parent: &RefNode, node: &RefNode) -> String {
+    let name = step_name(node);
+    let mut position = 0;
+    let mut count = 0;
+    for child_node in parent.child_nodes() {
+        if step_name(&child_node) == name {
+            count += 1;
+            if &child_node == node {
+                position = count;
+            }
+        }
+    }
+    if count > 1 {
+        format!("{}[{}]", name, position)
+    } else {
+        name
+    }
+}
+
+//
+// The descendant elements of `element` that have the attribute returned by `attribute_node` and,
+// if `value` is given, where that attribute's value matches.
+//
+fn elements_with(
+    element: &RefNode,
+    attribute_node: impl Fn(&RefNode) -> Option<RefNode>,
+    value: Option<(&str, ValueMatch)>,
+) -> Vec<RefNode> {
+    let value = value.map(|(value, value_match)| (match_value(value, value_match), value_match));
+    element
+        .descendant_elements()
+        .filter(|element| match attribute_node(element) {
+            None => false,
+            Some(attribute_node) => match &value {
+                None => true,
+                Some((value, value_match)) => {
+                    let attribute_value = normalized_value(&attribute_node).unwrap_or_default();
+                    &match_value(&attribute_value, *value_match) == value
+                }
+            },
+        })
+        .collect()
+}
+
+fn match_value(value: &str, value_match: ValueMatch) -> String {
+    match value_match {
+        ValueMatch::Exact => value.to_string(),
+        ValueMatch::CollapseWhitespace => value
+            .split(text::is_xml_space)
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<&str>>()
+            .join(" "),
+    }
+}
+
+//
+// A namespace declaration on `element` is redundant if the nearest declaration of the same prefix
+// on an ancestor declares the same namespace URI.
+//
+fn is_redundant_declaration(element: &RefNode, attribute_node: &RefNode) -> bool {
+    let name = attribute_node.node_name().to_string();
+    element
+        .ancestors()
+        .filter(is_element)
+        .find_map(|ancestor| ancestor.get_attribute_node(&name))
+        .map(|ancestor_attribute| {
+            ancestor_attribute.text_content() == attribute_node.text_content()
+        })
+        .unwrap_or_default()
+}
+
+//
+// Add an entity, or notation, to the corresponding map of `doc_type` unless one of the same name
+// is already present.
+//
+fn add_declaration(
+    doc_type: &mut RefNode,
+    declaration: RefNode,
+    node_type: NodeType,
+) -> Result<bool> {
+    if declaration.node_type() != node_type {
+        warn!("{}", MSG_INVALID_NODE_TYPE);
+        return Err(Error::InvalidState);
+    }
+    let name = declaration.node_name();
+    let mut mut_doc_type = doc_type.borrow_mut();
+    if let Extension::DocumentType {
+        i_entities,
+        i_notations,
+        ..
+    } = &mut mut_doc_type.i_extension
+    {
+        let declarations = if node_type == NodeType::Entity {
+            i_entities
+        } else {
+            i_notations
+        };
+        match declarations.entry(name) {
+            Entry::Occupied(_) => Ok(false),
+            Entry::Vacant(entry) => {
+                let _safe_to_ignore = entry.insert(declaration);
+                Ok(true)
+            }
+        }
+    } else {
+        warn!("{}", MSG_INVALID_EXTENSION);
+        Err(Error::InvalidState)
+    }
+}
+
+//
+// The run of `Text` and `CDataSection` siblings containing `node`, in document order.
+//
+fn text_run(node: &RefNode) -> Vec<RefNode> {
+    let is_text = |node: &RefNode| {
+        let node_type = node.node_type();
+        node_type == NodeType::Text || node_type == NodeType::CData
+    };
+    let mut run: Vec<RefNode> = node.preceding_siblings().take_while(is_text).collect();
+    run.reverse();
+    run.push(node.clone());
+    run.extend(node.following_siblings().take_while(is_text));
+    run
+}
+
+//
+// Apply `replace`, which returns the number of matches and the replaced text, to each text node
+// selected by `config`; returns the total number of matches in the nodes that were changed.
+//
+fn replace_in_text<F>(node: &RefNode, config: &ReplaceConfig, replace: F) -> usize
+where
+    F: Fn(&str) -> (usize, String),
+{
+    let mut text_nodes: Vec<(RefNode, bool)> = Vec::new();
+    for node in node.descendants() {
+        if node.borrow().i_read_only {
+            continue;
+        }
+        match node.node_type() {
+            NodeType::Text => text_nodes.push((node, true)),
+            NodeType::CData if !config.has_exclude_cdata() => text_nodes.push((node, true)),
+            NodeType::Element if config.has_include_attributes() => {
+                let attribute_nodes = node.borrow().attribute_nodes();
+                for attribute_node in attribute_nodes {
+                    if !is_namespace_declaration(&attribute_node.node_name()) {
+                        text_nodes.extend(
+                            attribute_node
+                                .child_nodes()
+                                .into_iter()
+                                .filter(is_text)
+                                .map(|text_node| (text_node, false)),
+                        );
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    let mut total = 0;
+    for (mut text_node, is_content) in text_nodes {
+        let value = text_node.borrow().i_value.clone().unwrap_or_default();
+        let (count, new_value) = replace(&value);
+        if count == 0 {
+            continue;
+        }
+        let replaced = if new_value.is_empty() && is_content && config.has_remove_empty_text() {
+            match text_node.parent_node() {
+                None => text_node.set_node_value(&new_value).is_ok(),
+                Some(mut parent) => parent.remove_child(text_node).is_ok(),
+            }
+        } else {
+            text_node.set_node_value(&new_value).is_ok()
+        };
+        if replaced {
+            total += count;
+        }
+    }
+    total
+}
+
+//
+// Parse `xml` inside a copy of the start tag of `element` which declares every namespace in scope
+// for it, import the content into the element's document, and resolve the namespace of each
+// element and prefixed attribute.
+//
+#[cfg(feature = "quick_parser")]
+fn parse_fragment(element: &RefNode, xml: &str) -> Result<Vec<RefNode>> {
+    let mut namespaces = Namespaces::new();
+    for ancestor in element.ancestors().collect::<Vec<RefNode>>().iter().rev() {
+        add_namespace_declarations(ancestor, &mut namespaces);
+    }
+    add_namespace_declarations(element, &mut namespaces);
+
+    let mut wrapper = format!("{}{}", XML_ELEMENT_START_START, element.node_name());
+    for (prefix, namespace_uri) in &namespaces {
+        if prefix.is_empty() {
+            wrapper.push_str(&format!(" {}=\"", XMLNS_NS_ATTRIBUTE));
+        } else if prefix != XML_NS_ATTRIBUTE {
+            wrapper.push_str(&format!(
+                " {}{}{}=\"",
+                XMLNS_NS_ATTRIBUTE, XML_NS_SEPARATOR, prefix
+            ));
+        } else {
+            continue;
+        }
+        wrapper.push_str(&format!("{}\"", escape_attribute(namespace_uri)?));
+    }
+    wrapper.push_str(&format!(
+        "{}{}{}{}{}",
+        XML_ELEMENT_START_END,
+        xml,
+        XML_ELEMENT_END_START,
+        element.node_name(),
+        XML_ELEMENT_END_END
+    ));
+
+    let fragment_document = read_xml(&wrapper).map_err(|e| {
+        warn!("{}: {}", MSG_INVALID_FRAGMENT, e);
+        match e.error() {
+            ParserError::HierarchyRequest => Error::HierarchyRequest,
+            ParserError::InvalidCharacter => Error::InvalidCharacter,
+            ParserError::NotSupported => Error::NotSupported,
+            _ => Error::Syntax,
+        }
+    })?;
+    let fragment_element = fragment_document.first_child().unwrap();
+    let document_node = element.owner_document().unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut new_children = Vec::new();
+    for child_node in fragment_element.child_nodes() {
+        let new_child = document.import_node(child_node, true)?;
+        resolve_namespaces(&new_child, &namespaces);
+        new_children.push(new_child);
+    }
+    Ok(new_children)
+}
+
+//
+// Set the namespace URI of `node`, and its descendants, that were parsed with a prefix but no
+// namespace, from the declarations in `namespaces` and those within the sub-tree.
+//
+#[cfg(feature = "quick_parser")]
+fn resolve_namespaces(node: &RefNode, namespaces: &Namespaces) {
+    if node.node_type() != NodeType::Element {
+        return;
+    }
+    let mut namespaces = namespaces.clone();
+    add_namespace_declarations(node, &mut namespaces);
+    let resolve = |name: &Name, use_default: bool| -> Option<Name> {
+        if name.namespace_uri().is_some() || is_namespace_declaration(name) {
+            return None;
+        }
+        let prefix = match name.prefix() {
+            Some(prefix) if prefix == XML_NS_ATTRIBUTE => return None,
+            Some(prefix) => prefix.clone(),
+            None if use_default => String::new(),
+            None => return None,
+        };
+        match namespaces.get(&prefix) {
+            Some(namespace_uri) if !namespace_uri.is_empty() => {
+                Name::new_ns(namespace_uri, &name.to_string()).ok()
+            }
+            _ => None,
+        }
+    };
+    if let Some(name) = resolve(&node.node_name(), true) {
+        node.borrow_mut().i_name = name;
+    }
+    let attributes = node.borrow().attribute_nodes();
+    for attribute in attributes {
+        if let Some(name) = resolve(&attribute.node_name(), false) {
+            attribute.borrow_mut().i_name = name.clone();
+            let mut mut_node = node.borrow_mut();
+            if let Extension::Element { i_attributes, .. } = &mut mut_node.i_extension {
+                i_attributes.replace(&attribute, name, attribute.clone());
+            }
+        }
+    }
+    for child_node in node.child_nodes() {
+        resolve_namespaces(&child_node, &namespaces);
+    }
+}
+
+//
+// The parent of `node`, for the methods of `SelfManipulation` that modify it.
+//
+fn parent_of(node: &RefNode) -> Result<RefNode> {
+    node.parent_node().ok_or_else(|| {
+        warn!("{}", MSG_NO_PARENT_NODE);
+        Error::HierarchyRequest
+    })
+}
+
+//
+// The owner document of `node`, for methods that create new nodes.
+//
+fn owner_document_of(node: &RefNode) -> Result<RefNode> {
+    node.owner_document().ok_or_else(|| {
+        warn!("{}", MSG_WEAK_REF);
+        Error::WrongDocument
+    })
+}
+
+//
+// Move all the children of `element` into `wrapper_node`, and append it to `element`.
+//
+fn wrap_all_children(element: &mut RefNode, mut wrapper_node: RefNode) -> Result<RefNode> {
+    for child_node in element.child_nodes().to_vec() {
+        let _safe_to_ignore = wrapper_node.append_child(child_node)?;
+    }
+    element.append_child(wrapper_node)
+}
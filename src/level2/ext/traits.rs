@@ -1,8 +1,25 @@
+use crate::level2::ext::adjacent::InsertPosition;
 use crate::level2::ext::decl::XmlDecl;
+use crate::level2::ext::dtd::{AttributeDecl, ElementDecl};
+use crate::level2::ext::events::Events;
+use crate::level2::ext::expansion::ExpansionLimits;
+use crate::level2::ext::inherited::XmlSpace;
+use crate::level2::ext::iterators::{Ancestors, Attributes, Descendants, Siblings};
 use crate::level2::ext::namespaced::NamespacePrefix;
+use crate::level2::ext::normalize::NormalizeConfig;
 use crate::level2::ext::options::ProcessingOptions;
+use crate::level2::ext::path::{NameMatch, ValueMatch};
+use crate::level2::ext::replace::ReplaceConfig;
+use crate::level2::ext::tokens::AttributeTokens;
+use crate::level2::ext::validate::ValidationError;
+use crate::level2::ext::visitor::NodeVisitor;
 use crate::level2::traits as base;
 use crate::shared::error::Result;
+use crate::shared::name::Name;
+#[cfg(feature = "regex")]
+use regex::Regex;
+use std::cmp::Ordering;
+use std::collections::HashMap;
 
 // ------------------------------------------------------------------------------------------------
 // Public Traits
@@ -32,6 +49,111 @@ pub trait DocumentDecl: base::Document {
     /// Note that it is not possible to unset (set to `None`) this value.
     ///
     fn set_xml_declaration(&mut self, xml_decl: XmlDecl) -> Result<()>;
+    ///
+    /// The version of XML this document uses, from its XML declaration; this is `"1.0"` if the
+    /// document has no declaration.
+    ///
+    /// This corresponds to the DOM Level 3 `xmlVersion` attribute.
+    ///
+    fn xml_version(&self) -> String;
+    ///
+    /// Set the version of XML this document uses, creating an XML declaration if necessary; only
+    /// `"1.0"` and `"1.1"` are supported, any other version returns `Error::NotSupported`.
+    ///
+    fn set_xml_version(&mut self, version: &str) -> Result<()>;
+    ///
+    /// The encoding named in the XML declaration of this document, if any. This is read-only as
+    /// it describes how the document was read, and is set by the parser.
+    ///
+    /// This corresponds to the DOM Level 3 `xmlEncoding` attribute.
+    ///
+    fn xml_encoding(&self) -> Option<String>;
+    ///
+    /// Whether the XML declaration of this document declares it to be standalone; this is `false`
+    /// if the document has no declaration, or the declaration does not include `standalone`.
+    ///
+    /// This corresponds to the DOM Level 3 `xmlStandalone` attribute.
+    ///
+    fn xml_standalone(&self) -> bool;
+    ///
+    /// Set whether this document is standalone, creating an XML declaration if necessary; the
+    /// declaration will then always include `standalone`.
+    ///
+    fn set_xml_standalone(&mut self, standalone: bool) -> Result<()>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This interface extends the DOM standard `DocumentType`, which is read-only in Level 2, and
+/// allows the declarations of the document type to be added by a client or a parser.
+///
+/// Entities and notations are created with the functions [`create_entity`](dom_impl/fn.create_entity.html),
+/// [`create_internal_entity`](dom_impl/fn.create_internal_entity.html), and
+/// [`create_notation`](dom_impl/fn.create_notation.html) and then added to the document type.
+///
+/// When serialized the internal subset, if set, is written verbatim; otherwise the entities and
+/// notations added to the document type are written, in name order, followed by the element and
+/// then the attribute declarations.
+///
+pub trait DocumentTypeDecl: base::DocumentType {
+    ///
+    /// Add an `Entity` node to the `entities` map of this document type.
+    ///
+    /// As in a DTD the first declaration of an entity is binding; returns `Ok(false)`, leaving
+    /// the map unchanged, if an entity of the same name has already been added. Returns
+    /// `Error::InvalidState` if `entity` is not an `Entity` node.
+    ///
+    fn add_entity(&mut self, entity: Self::NodeRef) -> Result<bool>;
+    ///
+    /// Add a `Notation` node to the `notations` map of this document type.
+    ///
+    /// As in a DTD the first declaration of a notation is binding; returns `Ok(false)`, leaving
+    /// the map unchanged, if a notation of the same name has already been added. Returns
+    /// `Error::InvalidState` if `notation` is not a `Notation` node.
+    ///
+    fn add_notation(&mut self, notation: Self::NodeRef) -> Result<bool>;
+    ///
+    /// Add an attribute declaration, from an `<!ATTLIST ...>` declaration, to this document
+    /// type.
+    ///
+    /// Elements created after the declaration is added, by `create_element` or `create_element_ns`
+    /// with the same qualified name, are given an attribute with the default value (if any) for
+    /// which `Attribute::specified` returns `false`; removing such an attribute restores the
+    /// default.
+    ///
+    /// As in a DTD the first declaration of an attribute for an element type is binding; returns
+    /// `Ok(false)`, leaving the declarations unchanged, if the attribute has already been declared
+    /// for the element type.
+    ///
+    fn add_attribute_decl(&mut self, attribute_decl: AttributeDecl) -> Result<bool>;
+    ///
+    /// Return the attribute declarations added to this document type, in the order they were
+    /// added.
+    ///
+    fn attribute_decls(&self) -> Vec<AttributeDecl>;
+    ///
+    /// Add an element type declaration, from an `<!ELEMENT ...>` declaration, to this document
+    /// type.
+    ///
+    /// An element type may only be declared once; returns `Ok(false)`, leaving the declarations
+    /// unchanged, if the element type has already been declared.
+    ///
+    fn add_element_decl(&mut self, element_decl: ElementDecl) -> Result<bool>;
+    ///
+    /// Return the element type declarations added to this document type, in the order they were
+    /// added.
+    ///
+    fn element_decls(&self) -> Vec<ElementDecl>;
+    ///
+    /// Set the internal subset of this document type, the text between `[` and `]` in the
+    /// document type declaration.
+    ///
+    fn set_internal_subset(&mut self, internal_subset: &str) -> Result<()>;
+    ///
+    /// Remove the internal subset of this document type.
+    ///
+    fn unset_internal_subset(&mut self) -> Result<()>;
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -134,3 +256,772 @@ pub trait Namespaced: base::Element {
     ///  
     fn resolve_prefix(&self, namespace_uri: &str) -> NamespacePrefix;
 }
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface to the attributes in the `xml` namespace, `xml:space` and `xml:lang`,
+/// whose values apply to the element they are specified on and to all of its descendants.
+///
+pub trait InheritedAttributes: base::Element {
+    ///
+    /// Returns the white space handling in effect for this element, from the nearest `xml:space`
+    /// attribute on this element or its ancestors; `xml:space` attributes with a value other than
+    /// `"default"` or `"preserve"` are ignored. Returns `None` if no such attribute is found.
+    ///
+    fn xml_space(&self) -> Option<XmlSpace>;
+    ///
+    /// Returns the language of this element, from the nearest `xml:lang` attribute on this element
+    /// or its ancestors, or `None` if no such attribute is found. An empty string is returned as-is
+    /// as it explicitly states that there is no language information.
+    ///
+    fn xml_lang(&self) -> Option<String>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface to attributes whose value is a list of white space separated tokens,
+/// such as `class` or `rel`.
+///
+pub trait TokenAttributes: base::Element {
+    ///
+    /// Returns the tokens of the attribute named `name` on this element; see
+    /// [`AttributeTokens`](struct.AttributeTokens.html). The attribute need not be present, it is
+    /// created when the first token is added.
+    ///
+    fn attribute_tokens(&self, name: &str) -> AttributeTokens;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface to find elements, and attributes, below an element using a simple path
+/// of child element names, see the [`path`](path/index.html) module for the path syntax, or by
+/// their attributes.
+///
+/// Only the children of the elements matched by each step of a path are examined, so a path
+/// lookup does not scan the whole document.
+///
+/// **Exceptions**
+///
+/// * `SYNTAX_ERR`: Raised by each path method if the path is not valid.
+///
+pub trait FindElements: base::Element {
+    ///
+    /// Returns the first node, in document order, selected by `path`; names are compared as
+    /// qualified names.
+    ///
+    fn find(&self, path: &str) -> Result<Option<Self::NodeRef>> {
+        self.find_by(path, NameMatch::QualifiedName)
+    }
+    ///
+    /// Returns all of the nodes, in document order, selected by `path`; names are compared as
+    /// qualified names.
+    ///
+    fn find_all(&self, path: &str) -> Result<Vec<Self::NodeRef>> {
+        self.find_all_by(path, NameMatch::QualifiedName)
+    }
+    ///
+    /// Returns the value of the first node selected by `path`, the value of an attribute or the
+    /// text content of an element.
+    ///
+    fn find_value(&self, path: &str) -> Result<Option<String>>;
+    ///
+    /// Returns the first node, in document order, selected by `path`, comparing names as
+    /// determined by `name_match`.
+    ///
+    fn find_by(&self, path: &str, name_match: NameMatch) -> Result<Option<Self::NodeRef>>;
+    ///
+    /// Returns all of the nodes, in document order, selected by `path`, comparing names as
+    /// determined by `name_match`.
+    ///
+    fn find_all_by(&self, path: &str, name_match: NameMatch) -> Result<Vec<Self::NodeRef>>;
+    ///
+    /// Returns all of the descendant elements, in document order, that have an attribute with the
+    /// qualified name `name`.
+    ///
+    fn elements_with_attribute(&self, name: &str) -> Vec<Self::NodeRef>;
+    ///
+    /// Returns all of the descendant elements, in document order, that have an attribute with the
+    /// namespace URI `namespace_uri` and local name `local_name`.
+    ///
+    fn elements_with_attribute_ns(
+        &self,
+        namespace_uri: &str,
+        local_name: &str,
+    ) -> Vec<Self::NodeRef>;
+    ///
+    /// Returns all of the descendant elements, in document order, that have an attribute with the
+    /// qualified name `name` whose value matches `value`, as determined by `value_match`.
+    ///
+    fn elements_with_attribute_value(
+        &self,
+        name: &str,
+        value: &str,
+        value_match: ValueMatch,
+    ) -> Vec<Self::NodeRef>;
+    ///
+    /// Returns all of the descendant elements, in document order, that have an attribute with the
+    /// namespace URI `namespace_uri` and local name `local_name` whose value matches `value`, as
+    /// determined by `value_match`.
+    ///
+    fn elements_with_attribute_value_ns(
+        &self,
+        namespace_uri: &str,
+        local_name: &str,
+        value: &str,
+        value_match: ValueMatch,
+    ) -> Vec<Self::NodeRef>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface that provides iterators for navigating the DOM tree from a node, rather
+/// than walking `child_nodes` by hand. Iteration is not recursive and so is safe for deeply nested
+/// documents.
+///
+/// See the [`iterators`](iterators/index.html) module for the types returned.
+///
+pub trait TreeIterators: base::Node {
+    ///
+    /// Returns an iterator over all the descendants of this node, not including the node itself,
+    /// in document order (a pre-order traversal).
+    ///
+    /// Attributes are not children and so are not included, nor are the entities and notations
+    /// of a `DocumentType`.
+    ///
+    fn descendants(&self) -> Descendants;
+    ///
+    /// Returns an iterator over all the descendants of this node that are `Element`s; see
+    /// [`descendants`](#tymethod.descendants).
+    ///
+    fn descendant_elements(&self) -> Descendants;
+    ///
+    /// Returns an iterator over the ancestors of this node, its parent, its parent's parent, and
+    /// so on up to and including the `Document` node. The iterator stops at the first node
+    /// without a parent, or if an ancestor has already been dropped.
+    ///
+    /// As attributes do not have a parent this iterator is empty for an `Attribute` node; use
+    /// the attribute's `owner_element` and then its ancestors.
+    ///
+    fn ancestors(&self) -> Ancestors;
+    ///
+    /// Returns an iterator over the siblings that precede this node, the nearest first (i.e. in
+    /// reverse document order).
+    ///
+    fn preceding_siblings(&self) -> Siblings;
+    ///
+    /// Returns an iterator over the siblings that follow this node, in document order.
+    ///
+    fn following_siblings(&self) -> Siblings;
+    ///
+    /// Returns an iterator over the attributes of this node, if it is an `Element`, in the order
+    /// they were added, as pairs of name and attribute node; the iterator is empty for any other
+    /// type of node. Unlike `Node::attributes`, the attributes are not copied into a new map, each
+    /// is only read as the iterator reaches it.
+    ///
+    fn attribute_iter(&self) -> Attributes;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface, from DOM Level 3, for working with the run of logically-adjacent text
+/// that contains a `Text` (or `CDataSection`) node.
+///
+/// The run is this node together with the `Text` and `CDataSection` siblings on either side of it
+/// that are not separated from it by any other kind of node; so two runs separated by a comment
+/// are distinct. Unlike `Node::normalize` these methods only touch the single run.
+///
+pub trait WholeText: base::Text {
+    ///
+    /// Returns all text of `Text` nodes logically-adjacent to this node, concatenated in document
+    /// order.
+    ///
+    /// # Specification
+    ///
+    /// From DOM Level 3 -- `wholeText` of type `DOMString`, readonly: Returns all text of `Text`
+    /// nodes logically-adjacent text nodes to this node, concatenated in document order.
+    ///
+    fn whole_text(&self) -> String;
+    ///
+    /// Replaces the text of the current node and all logically-adjacent text nodes with the
+    /// specified text. All logically-adjacent text nodes are removed including the current node
+    /// unless it was the recipient of the replacement text.
+    ///
+    /// # Specification
+    ///
+    /// **Parameters**
+    ///
+    /// * `content` of type `DOMString`: The content of the replacing `Text` node.
+    ///
+    /// **Return Value**
+    ///
+    /// * `Text`: The `Text` node created with the specified content, this node in this
+    ///   implementation, or `null` if the content is empty.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `NO_MODIFICATION_ALLOWED_ERR`: Raised if one of the `Text` nodes being replaced is
+    ///   readonly.
+    ///
+    fn replace_whole_text(&mut self, content: &str) -> Result<Option<Self::NodeRef>>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface to remove the whitespace-only text nodes, such as indentation, that
+/// appear between elements.
+///
+pub trait StripWhitespace: base::Node {
+    ///
+    /// Remove every `Text` node, within this node or its descendants, for which
+    /// [`is_element_content_whitespace`](../trait.Text.html#tymethod.is_element_content_whitespace)
+    /// is `true`. The children of an element are left unchanged if any of them is a text, or CDATA,
+    /// node with non-whitespace content, as the whitespace is then likely significant.
+    ///
+    /// If `respect_xml_space` is `true` the children of an element are also left unchanged when
+    /// the nearest `xml:space` attribute, on the element itself or an ancestor, has the value
+    /// `"preserve"`; a nearer `xml:space="default"` cancels this.
+    ///
+    /// Nodes within an entity reference are read-only and are left unchanged.
+    ///
+    fn strip_whitespace_text_nodes(&mut self, respect_xml_space: bool) -> Result<()>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface for consumers that do not want `EntityReference` nodes in the tree.
+///
+pub trait EntityExpansion: base::Node {
+    ///
+    /// Replace every entity reference in the sub-tree under this node, usually a document, with
+    /// writable copies of its children; references nested within the replacement text are also
+    /// expanded.
+    ///
+    /// A reference to an unknown entity has no children and is left in place, so that it is not
+    /// lost when the tree is serialized.
+    ///
+    /// The whole sub-tree is checked against the
+    /// [`expansion_limits`](trait.EntityLimits.html#tymethod.expansion_limits) of the owner
+    /// document before any reference is expanded, and so if an error is returned the tree is
+    /// unchanged.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `EntityExpansionSize`: Raised if more nodes would be added than the limits allow.
+    /// * `EntityExpansionDepth`: Raised if entity references are nested more deeply than the
+    ///   limits allow.
+    /// * `EntityReferenceLoop`: Raised if a reference is nested within a reference to the same
+    ///   entity.
+    ///
+    fn expand_entity_references(&mut self) -> Result<()>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface to set the limits on expanding entities in a document, see
+/// [`ExpansionLimits`](struct.ExpansionLimits.html). The limits are checked by
+/// `Document::create_entity_reference`, when it copies the replacement tree of the entity, and by
+/// [`EntityExpansion::expand_entity_references`](trait.EntityExpansion.html#tymethod.expand_entity_references).
+///
+pub trait EntityLimits: base::Document {
+    ///
+    /// Return the limits on expanding entities in this document; a new document has the default
+    /// limits.
+    ///
+    fn expansion_limits(&self) -> ExpansionLimits;
+    ///
+    /// Set the limits on expanding entities in this document; returns `Error::InvalidState` if
+    /// this is not a document.
+    ///
+    fn set_expansion_limits(&mut self, limits: ExpansionLimits) -> Result<()>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface, after the DOM Level 3 `Document.normalizeDocument`, to put a whole
+/// document into a predictable shape, for example before it is compared or hashed.
+///
+pub trait NormalizeDocument: base::Document {
+    ///
+    /// Normalize this document according to `config`. Entity references are expanded first, if
+    /// configured; then comments and CDATA sections are removed or converted; then adjacent text
+    /// nodes are merged, and empty ones removed, as [`normalize`](../trait.Node.html#tymethod.normalize)
+    /// does; and finally whitespace-only text and redundant namespace declarations are removed.
+    ///
+    /// Nodes within an unexpanded entity reference are read-only and are left unchanged.
+    ///
+    fn normalize_document(&mut self, config: &NormalizeConfig) -> Result<()>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface to search and replace the text content of a document, for example to
+/// substitute `${placeholder}` values in a template.
+///
+/// Each `Text` node, `CDataSection` node, and attribute value, is searched on its own; a match is
+/// never found across the boundary between two nodes, for example where the text is split by a
+/// comment or child element, or in adjacent text nodes that have not been normalized. Nodes
+/// within an unexpanded entity reference are read-only and are left unchanged.
+///
+pub trait ReplaceText: base::Document {
+    ///
+    /// Replace every occurrence of `pattern` in the text of this document with `replacement`,
+    /// using the default [`ReplaceConfig`](struct.ReplaceConfig.html), and return the number of
+    /// occurrences replaced.
+    ///
+    fn replace_text(&mut self, pattern: &str, replacement: &str) -> usize;
+    ///
+    /// Replace every occurrence of `pattern` in the text of this document with `replacement`, as
+    /// selected by `config`, and return the number of occurrences replaced. An empty `pattern`
+    /// matches nothing.
+    ///
+    /// A node is left unchanged, and its occurrences are not counted, if the replaced text is not
+    /// legal for it; for example if it would contain a character that is not allowed in XML, or a
+    /// CDATA section would contain `"]]>"`.
+    ///
+    fn replace_text_with(
+        &mut self,
+        pattern: &str,
+        replacement: &str,
+        config: &ReplaceConfig,
+    ) -> usize;
+    ///
+    /// Replace every match of `pattern` in the text of this document with `replacement`, using
+    /// the default [`ReplaceConfig`](struct.ReplaceConfig.html), and return the number of matches
+    /// replaced. The replacement may refer to capture groups, as for `Regex::replace_all`.
+    ///
+    #[cfg(feature = "regex")]
+    fn replace_regex(&mut self, pattern: &Regex, replacement: &str) -> usize;
+    ///
+    /// Replace every match of `pattern` in the text of this document with `replacement`, as
+    /// selected by `config`, and return the number of matches replaced; as for
+    /// `replace_text_with`, a node is left unchanged if the replaced text is not legal for it.
+    ///
+    #[cfg(feature = "regex")]
+    fn replace_regex_with(
+        &mut self,
+        pattern: &Regex,
+        replacement: &str,
+        config: &ReplaceConfig,
+    ) -> usize;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface to read, and replace, the content of an element as XML text.
+///
+pub trait InnerXml: base::Element {
+    ///
+    /// Return the serialization of this element, including its own start and end tags; this is
+    /// the same as `to_string()`.
+    ///
+    fn outer_xml(&self) -> String;
+    ///
+    /// Return the serialization of the children of this element, without its own tags.
+    ///
+    fn inner_xml(&self) -> String;
+    ///
+    /// Parse `xml` as the content of this element, and replace the existing children with the
+    /// result. The text is parsed in the context of the namespace declarations in scope for this
+    /// element, so that prefixes declared on it, or its ancestors, resolve in the new children.
+    /// The existing children are only removed if `xml` is parsed successfully.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `NO_MODIFICATION_ALLOWED_ERR`: Raised if this node is readonly.
+    /// * `SYNTAX_ERR`: Raised if `xml` is not well-formed content for an element.
+    ///
+    #[cfg(feature = "quick_parser")]
+    fn set_inner_xml(&mut self, xml: &str) -> Result<()>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface to release the nodes of a document that would otherwise be kept alive by
+/// a reference cycle.
+///
+/// Nodes only hold their children, attributes, and declarations strongly; the references to a
+/// parent, owner element, or owner document are all weak, so dropping a document frees every node
+/// not otherwise referenced. However, a mutation listener, or user data, that holds a node of the
+/// document creates a cycle that keeps the document, and all its nodes, alive.
+///
+pub trait ReleaseDocument: base::Document {
+    ///
+    /// Remove every child from this document, and from each of its descendants, and remove all
+    /// mutation listeners and user data; any nodes still referenced by the application are left
+    /// without a parent. Removing user data does not call its handler, and no mutations are
+    /// reported.
+    ///
+    /// After this the document is empty, and any node that is not otherwise referenced is freed.
+    ///
+    fn detach_all(&mut self) -> Result<()>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface to the names shared by the nodes of a document.
+///
+/// A document keeps one copy of each distinct name given to the elements, attributes, entity
+/// references, and processing instructions it creates; every node with an equal name shares that
+/// copy, so a document with a million `row` elements holds the name `row` once.
+///
+pub trait InternedNames: base::Document {
+    ///
+    /// The number of distinct names held by this document; this is `0` for any node that is not
+    /// a document.
+    ///
+    fn name_pool_len(&self) -> usize;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface to find where a node is in its tree, for example to check that a node
+/// held for later use has not since been removed from its document. Nodes are compared by
+/// identity, as `Node::is_same_node` does.
+///
+pub trait TreeMembership: base::Node {
+    ///
+    /// Returns `true` if `other` is this node, or one of its descendants, else `false`. Attributes
+    /// are not children and so an attribute is only contained by itself.
+    ///
+    fn contains(&self, other: &Self::NodeRef) -> bool;
+    ///
+    /// Returns the topmost ancestor of this node, following parents until a node without a parent
+    /// is found; this is the `Document` for a node in a document, and is this node if it has no
+    /// parent. As attributes have no parent the root of an attribute is the attribute itself.
+    ///
+    fn root_node(&self) -> Self::NodeRef;
+    ///
+    /// Returns `true` if this node is part of the tree of its owner document, either in the
+    /// document element or in the prolog or epilog, else `false`. An attribute is attached if its
+    /// owner element is attached, and a `Document` node is always attached.
+    ///
+    fn is_attached(&self) -> bool;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface that describes where a node is with an XPath-like path, for example to
+/// report the location of a problem; see [`ResolvePath`](trait.ResolvePath.html) to find the node
+/// again from its path.
+///
+pub trait NodePath: base::Node {
+    ///
+    /// Returns the absolute path of this node from its document, such as `/root/items/item[3]/@id`
+    /// or `/root/p/text()[2]`; the path of the document itself is `/`.
+    ///
+    /// Each step names a child as an XPath node test: the qualified name of an element,
+    /// `text()` for a `Text` or `CDataSection` node, `comment()`, or
+    /// `processing-instruction('target')`; any other node, such as a `DocumentType`, is named
+    /// `node()`. A 1-based position is added where more than one sibling has the same step. An
+    /// attribute is the final step, `@` followed by its qualified name.
+    ///
+    /// If this node is not in a document the path is relative, starting with a step for the
+    /// topmost ancestor, such as `item/@id`, and cannot be resolved.
+    ///
+    fn node_path(&self) -> String;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface to find a node in a document from a path returned by
+/// [`NodePath::node_path`](trait.NodePath.html#tymethod.node_path).
+///
+pub trait ResolvePath: base::Document {
+    ///
+    /// Returns the node at `path` in this document, or `None` if `path` is not a valid absolute
+    /// path or no such node exists. A step without a position selects the first sibling with that
+    /// step, so `/root/item` and `/root/item[1]` are the same node.
+    ///
+    fn node_at_path(&self, path: &str) -> Option<Self::NodeRef>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface to index the IDs of a document, and to check the references to them made
+/// by its `IDREF` and `IDREFS` attributes. Each method searches the tree when it is called, and so
+/// always reflects the document as it is.
+///
+pub trait CrossReferences: base::Document {
+    ///
+    /// Returns a map from each ID in this document to the element it identifies, where an ID is
+    /// the value of any attribute for which
+    /// [`Attribute::is_id`](../trait.Attribute.html#tymethod.is_id) returns `true`. If the same
+    /// ID is used more than once the first element, in document order, is used, as
+    /// `get_element_by_id` does.
+    ///
+    fn collect_ids(&self) -> HashMap<String, Self::NodeRef>;
+    ///
+    /// Returns each reference, in document order, made by an attribute declared with the type
+    /// `IDREF` or `IDREFS` in this document's DTD that does not match an ID; each is returned as
+    /// the element, the name of the attribute, and the ID it refers to.
+    ///
+    fn unresolved_idrefs(&self) -> Vec<(Self::NodeRef, Name, String)>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface to follow a reference to another element of the same document.
+///
+pub trait ReferenceAttributes: base::Element {
+    ///
+    /// Returns the element with the ID that is the value of the attribute `name` of this element,
+    /// as `Document::get_element_by_id` would find it; for an `IDREFS` value the first ID is used.
+    /// Returns `None` if the attribute is not present, or no element has that ID. The attribute
+    /// need not be declared as an `IDREF`.
+    ///
+    fn referenced_element(&self, name: &str) -> Option<Self::NodeRef>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface to move, or replace, a node relative to itself rather than through its
+/// parent. Each method is implemented with the corresponding method of the parent, or of this
+/// node, and so has the same checks of the hierarchy, the owning document, and read-only nodes.
+///
+pub trait SelfManipulation: base::Node {
+    ///
+    /// Remove this node from its parent, as `Node::remove_child` would, and return it. A node
+    /// without a parent is returned unchanged.
+    ///
+    fn remove_self(&mut self) -> Result<Self::NodeRef>;
+    ///
+    /// Replace this node, in the children of its parent, with `other`, as `Node::replace_child`
+    /// would, and return this node.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `HIERARCHY_REQUEST_ERR`: Raised if this node has no parent.
+    ///
+    fn replace_with(&mut self, other: Self::NodeRef) -> Result<Self::NodeRef>;
+    ///
+    /// Insert `node` at `position` relative to this node, and return the inserted node. The
+    /// `BeforeSelf` and `AfterSelf` positions insert `node` as a sibling with
+    /// `Node::insert_before` on the parent, while `FirstChild` and `LastChild` insert it as a
+    /// child of this node.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `HIERARCHY_REQUEST_ERR`: Raised if `position` is `BeforeSelf` or `AfterSelf` and this
+    ///   node has no parent.
+    ///
+    fn insert_adjacent(
+        &mut self,
+        position: InsertPosition,
+        node: Self::NodeRef,
+    ) -> Result<Self::NodeRef>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface to remove an element while keeping its children, or to move its
+/// children into a new element; see also the [`wrap`](wrap/index.html) module for wrapping any
+/// run of nodes.
+///
+pub trait WrapElements: base::Element {
+    ///
+    /// Replace this element, in the children of its parent, with its own children, in order. The
+    /// moved children are returned, and this element is left without a parent or children.
+    ///
+    /// If the children may not be added to the parent, for example a document would then have
+    /// more than one element, the error is returned and neither node is changed.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `HIERARCHY_REQUEST_ERR`: Raised if this element has no parent, or if the children may
+    ///   not be added to the parent.
+    /// * `NO_MODIFICATION_ALLOWED_ERR`: Raised if this element, or its parent, is readonly.
+    ///
+    fn unwrap_children(&mut self) -> Result<Vec<Self::NodeRef>>;
+    ///
+    /// Create an element named `tag_name`, using the owner document of this element, move all the
+    /// children of this element, in order, into it, and append it to this element. The new
+    /// element is returned.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `WRONG_DOCUMENT_ERR`: Raised if the owner document of this element has been dropped.
+    ///
+    /// Any error from `Document::create_element`, or from moving the children, is also returned.
+    ///
+    fn wrap_children(&mut self, tag_name: &str) -> Result<Self::NodeRef>;
+    ///
+    /// Create an element with `namespace_uri` and `qualified_name`, move all the children of this
+    /// element into it, and append it to this element; see
+    /// [`wrap_children`](#tymethod.wrap_children).
+    ///
+    fn wrap_children_ns(
+        &mut self,
+        namespace_uri: &str,
+        qualified_name: &str,
+    ) -> Result<Self::NodeRef>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface to read the children of a node by position. Unlike `child_nodes`, these
+/// methods do not create a `NodeList`, and read the children of the node directly, so they are
+/// suited to code that visits many nodes.
+///
+pub trait ChildAccess: base::Node {
+    ///
+    /// Returns the number of children of this node; this is the same as `child_nodes().len()`.
+    ///
+    fn child_node_count(&self) -> usize;
+    ///
+    /// Returns the child of this node at `index`, or `None` if `index` is greater than or equal to
+    /// the number of children; this is the same as `child_nodes().item(index)`.
+    ///
+    fn child(&self, index: usize) -> Option<Self::NodeRef>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface to add, remove, or reorder many children of a node at once.
+///
+/// Each method makes the same checks as the corresponding `Node` method for each child, but
+/// checks the node itself only once, and does not search the children again for each change.
+/// Listeners, and the removal observers of ranges and iterators, are told of each child added or
+/// removed as usual.
+///
+pub trait BulkChildren: base::Node {
+    ///
+    /// Append each of `nodes`, in order, as `Node::append_child` would.
+    ///
+    /// If a node may not be appended the error is returned and no further nodes are appended;
+    /// the nodes before it remain appended.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `HIERARCHY_REQUEST_ERR`: Raised if this node is of a type that does not allow children
+    ///   of the type of one of `nodes`, or if one of `nodes` is this node, or one of its
+    ///   ancestors.
+    /// * `WRONG_DOCUMENT_ERR`: Raised if one of `nodes` was created from a different document
+    ///   than the one that created this node.
+    /// * `NO_MODIFICATION_ALLOWED_ERR`: Raised if this node is readonly, or if the previous parent
+    ///   of one of `nodes` is readonly.
+    ///
+    fn append_children<I>(&mut self, nodes: I) -> Result<()>
+    where
+        I: IntoIterator<Item = Self::NodeRef>;
+    ///
+    /// Remove each child for which `predicate` returns `false`, as `Node::remove_child` would,
+    /// and return the removed children in order; the removed children have no parent. The
+    /// predicate is called once for each child before any child is removed.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `NO_MODIFICATION_ALLOWED_ERR`: Raised if this node is readonly.
+    ///
+    fn retain_children<F>(&mut self, predicate: F) -> Result<Vec<Self::NodeRef>>
+    where
+        F: FnMut(&Self::NodeRef) -> bool;
+    ///
+    /// Reorder the children of this node using `compare`; the sort is stable, so children that
+    /// compare as equal keep their relative order. A child that moves is reported as removed and
+    /// then inserted again.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `HIERARCHY_REQUEST_ERR`: Raised if this node is a document and the new order would put
+    ///   the document type after the document element; the children are then left unchanged.
+    /// * `NO_MODIFICATION_ALLOWED_ERR`: Raised if this node is readonly.
+    ///
+    fn sort_children_by<F>(&mut self, compare: F) -> Result<()>
+    where
+        F: FnMut(&Self::NodeRef, &Self::NodeRef) -> Ordering;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface that presents a node, and its descendants, as the events a streaming
+/// (SAX-like) parser would produce for it, so that a tree can be passed to an event-based consumer
+/// without a recursive walk of its own.
+///
+pub trait XmlEvents: base::Node {
+    ///
+    /// Returns an iterator over the events for this node and its descendants, in document order.
+    ///
+    /// A `Document` produces `StartDocument` and `EndDocument`, and an `Element` `StartElement`
+    /// and `EndElement`, around the events of their children. Each other node produces a single
+    /// event, except that an `EntityReference` or `DocumentFragment` produces only the events of
+    /// its children; attributes are reported with the `StartElement` of their owner element.
+    ///
+    /// The iterator is lazy; if the tree is changed while iterating, the events that follow
+    /// reflect the tree as it is when they are produced.
+    ///
+    fn events(&self) -> Events;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface that walks a node, and its descendants, calling back to a
+/// [`NodeVisitor`](trait.NodeVisitor.html) for each node.
+///
+pub trait Visitable: base::Node {
+    ///
+    /// Walk this node and its descendants in document (pre-order) order, calling the method of
+    /// `visitor` for the type of each node; the walk continues as directed by the
+    /// [`VisitAction`](enum.VisitAction.html) each method returns.
+    ///
+    /// A `Document`, `DocumentFragment`, or `EntityReference` node is not passed to the visitor,
+    /// but its children are visited. `Attribute` and `DocumentType` nodes, and their children, are
+    /// not visited.
+    ///
+    /// The list of children of each node is copied before its children are visited, so a visitor
+    /// may remove or replace the node it is called with, or its siblings, without changing the
+    /// nodes that are visited after it; a sibling removed before it is reached is still visited,
+    /// although it no longer has a parent.
+    ///
+    fn accept(&self, visitor: &mut dyn NodeVisitor) -> Result<()>;
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An extended interface that checks a document against the declarations of its document type,
+/// see [`DocumentTypeDecl`](trait.DocumentTypeDecl.html).
+///
+pub trait Validate: base::Document {
+    ///
+    /// Check every element of this document against the declarations of its document type, and
+    /// return the validity errors found; the result is empty if the document is valid.
+    ///
+    /// The following are checked:
+    ///
+    /// * the document has a document type, with the same name as the document element;
+    /// * each element type is declared, and the children of each element match its content
+    ///   specification (`EMPTY`, `ANY`, mixed, or element content);
+    /// * each attribute is declared, its value matches its declared type and any `#FIXED` value,
+    ///   and each `#REQUIRED` attribute is present;
+    /// * each `ID` value is unique, and each `IDREF` value matches an `ID`.
+    ///
+    /// Errors are returned in document order, followed by any unmatched `IDREF` values; comments
+    /// and processing instructions are ignored in element and mixed content, and namespace
+    /// declarations need not be declared.
+    ///
+    fn validate(&self) -> Vec<ValidationError>;
+}
@@ -0,0 +1,572 @@
+/*!
+This module provides support types for the [`Validate`](trait.Validate.html) trait, which checks
+an already built tree against the element type and attribute-list declarations of its document
+type.
+
+Each content model of element-only content is compiled into a finite automaton, which is run
+over the child elements of each element of that type; as the automaton tracks every state it may
+be in at once it does not depend on the content model being deterministic, and it never
+backtracks.
+
+```rust
+# #[cfg(feature = "quick_parser")]
+# fn main() {
+use xml_dom::level2::*;
+use xml_dom::level2::ext::*;
+use xml_dom::level2::ext::convert::as_document_type_decl_mut;
+use xml_dom::parser::read_xml;
+use std::str::FromStr;
+
+let document_node = read_xml(r#"<!DOCTYPE list><list><item/><note/></list>"#).unwrap();
+let mut doc_type_node = document_node.doc_type().unwrap();
+let doc_type = as_document_type_decl_mut(&mut doc_type_node).unwrap();
+for (name, content_spec) in [("list", "(item+)"), ("item", "EMPTY"), ("note", "(#PCDATA)")] {
+    let content_spec = ContentSpec::from_str(content_spec).unwrap();
+    let _ = doc_type.add_element_decl(ElementDecl::new(name, content_spec).unwrap()).unwrap();
+}
+
+let errors = document_node.validate();
+assert_eq!(errors.len(), 1);
+assert_eq!(errors[0].path(), "/list/note");
+# }
+# #[cfg(not(feature = "quick_parser"))]
+# fn main() {}
+```
+*/
+
+use crate::level2::ext::dtd::{
+    AttributeDecl, AttributeDefault, AttributeType, ContentParticle, ContentSpec, ElementDecl,
+    Occurrence, MAX_CONTENT_DEPTH,
+};
+use crate::level2::ext::traits::{DocumentTypeDecl, NodePath, TreeIterators, Validate};
+use crate::level2::node_impl::RefNode;
+use crate::level2::trait_impls::{is_namespace_declaration, normalized_value};
+use crate::level2::traits::{Document, DocumentType, Element, Entity, Node, NodeType};
+use crate::shared::text::{is_xml_name, is_xml_nmtoken, is_xml_space};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A single validity error reported by [`Validate::validate`](trait.Validate.html#tymethod.validate).
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidationError {
+    path: String,
+    message: String,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+//
+// A nondeterministic finite automaton for a content particle; each edge is labelled with an
+// element type name, or is an empty (`None`) edge.
+//
+#[derive(Debug, Default)]
+struct ContentAutomaton {
+    edges: Vec<Vec<(Option<String>, usize)>>,
+    start: usize,
+    accept: usize,
+}
+
+//
+// The ways in which the children of an element may fail to match its content model.
+//
+enum ContentMismatch {
+    Unexpected(usize),
+    Incomplete(Vec<String>),
+}
+
+struct Validator<'a> {
+    doc_type: &'a RefNode,
+    element_decls: HashMap<String, ElementDecl>,
+    attribute_decls: HashMap<String, Vec<AttributeDecl>>,
+    automata: HashMap<String, Option<ContentAutomaton>>,
+    ids: HashSet<String>,
+    id_refs: Vec<(RefNode, String)>,
+    errors: Vec<ValidationError>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Validate for RefNode {
+    fn validate(&self) -> Vec<ValidationError> {
+        let doc_type = match self.doc_type() {
+            None => {
+                return vec![ValidationError::new(
+                    self,
+                    "the document has no document type declaration".to_string(),
+                )]
+            }
+            Some(doc_type) => doc_type,
+        };
+        let mut validator = Validator::new(&doc_type);
+        if let Some(document_element) = self.document_element() {
+            if document_element.node_name().to_string() != doc_type.node_name().to_string() {
+                validator.error(
+                    &document_element,
+                    format!(
+                        "the document element `{}` does not match the document type name `{}`",
+                        document_element.node_name(),
+                        doc_type.node_name()
+                    ),
+                );
+            }
+        }
+        for element in self.descendant_elements() {
+            validator.element(&element);
+        }
+        validator.finish()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+impl ValidationError {
+    fn new(node: &RefNode, message: String) -> Self {
+        Self {
+            path: node.node_path(),
+            message,
+        }
+    }
+
+    ///
+    /// Return the path, as returned by [`NodePath::node_path`](trait.NodePath.html#tymethod.node_path),
+    /// of the node that is not valid.
+    ///
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    ///
+    /// Return a description of the error.
+    ///
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl<'a> Validator<'a> {
+    fn new(doc_type: &'a RefNode) -> Self {
+        let element_decls = doc_type
+            .element_decls()
+            .into_iter()
+            .map(|element_decl| (element_decl.name().to_string(), element_decl))
+            .collect();
+        let mut attribute_decls: HashMap<String, Vec<AttributeDecl>> = HashMap::new();
+        for attribute_decl in doc_type.attribute_decls() {
+            attribute_decls
+                .entry(attribute_decl.element_name().to_string())
+                .or_default()
+                .push(attribute_decl);
+        }
+        Self {
+            doc_type,
+            element_decls,
+            attribute_decls,
+            automata: Default::default(),
+            ids: Default::default(),
+            id_refs: Default::default(),
+            errors: Default::default(),
+        }
+    }
+
+    //
+    // IDREF values can only be checked once every ID has been seen.
+    //
+    fn finish(mut self) -> Vec<ValidationError> {
+        for (attribute, value) in std::mem::take(&mut self.id_refs) {
+            if !self.ids.contains(&value) {
+                self.error(
+                    &attribute,
+                    format!("the IDREF `{}` does not match any ID", value),
+                );
+            }
+        }
+        self.errors
+    }
+
+    fn error(&mut self, node: &RefNode, message: String) {
+        self.errors.push(ValidationError::new(node, message));
+    }
+
+    fn element(&mut self, element: &RefNode) {
+        let name = element.node_name().to_string();
+        let content_spec = match self.element_decls.get(&name) {
+            None => {
+                self.error(
+                    element,
+                    format!("the element type `{}` is not declared", name),
+                );
+                return;
+            }
+            Some(element_decl) => element_decl.content_spec().clone(),
+        };
+        self.content(element, &name, &content_spec);
+        self.attributes(element, &name);
+    }
+
+    fn content(&mut self, element: &RefNode, name: &str, content_spec: &ContentSpec) {
+        let children = content_of(element);
+        match content_spec {
+            ContentSpec::Empty => {
+                if !element.child_nodes().is_empty() {
+                    self.error(
+                        element,
+                        format!("the element `{}` is declared EMPTY but has content", name),
+                    );
+                }
+            }
+            ContentSpec::Any => {}
+            ContentSpec::Mixed(names) => {
+                for child in children.iter().filter(|child| is_element_node(child)) {
+                    let child_name = child.node_name().to_string();
+                    if !names.iter().any(|name| name.to_string() == child_name) {
+                        self.error(
+                            child,
+                            format!(
+                                "the element `{}` is not allowed in the content of `{}`, {}",
+                                child_name, name, content_spec
+                            ),
+                        );
+                    }
+                }
+            }
+            ContentSpec::Children(particle) => {
+                if children.iter().any(is_character_data) {
+                    self.error(
+                        element,
+                        format!(
+                            "character data is not allowed in the element content of `{}`",
+                            name
+                        ),
+                    );
+                }
+                let child_elements: Vec<RefNode> =
+                    children.into_iter().filter(is_element_node).collect();
+                let child_names: Vec<String> = child_elements
+                    .iter()
+                    .map(|child| child.node_name().to_string())
+                    .collect();
+                let automaton = self
+                    .automata
+                    .entry(name.to_string())
+                    .or_insert_with(|| ContentAutomaton::new(particle));
+                let automaton = match automaton {
+                    Some(automaton) => automaton,
+                    None => {
+                        self.error(
+                            element,
+                            format!(
+                                "the content model of `{}` is nested more than {} deep",
+                                name, MAX_CONTENT_DEPTH
+                            ),
+                        );
+                        return;
+                    }
+                };
+                match automaton.matches(&child_names) {
+                    Ok(()) => {}
+                    Err(ContentMismatch::Unexpected(index)) => self.error(
+                        &child_elements[index],
+                        format!(
+                            "the element `{}` is not allowed here by the content model of `{}`, {}",
+                            child_names[index], name, content_spec
+                        ),
+                    ),
+                    Err(ContentMismatch::Incomplete(expected)) => self.error(
+                        element,
+                        format!(
+                            "the content of `{}` ends before its content model, {}, is complete; \
+                             expected `{}`",
+                            name,
+                            content_spec,
+                            expected.join("` or `")
+                        ),
+                    ),
+                }
+            }
+        }
+    }
+
+    fn attributes(&mut self, element: &RefNode, name: &str) {
+        let attribute_decls = self.attribute_decls.get(name).cloned().unwrap_or_default();
+        for (attribute_name, attribute) in element.attribute_iter() {
+            if is_namespace_declaration(&attribute_name) {
+                continue;
+            }
+            let attribute_name = attribute_name.to_string();
+            match attribute_decls
+                .iter()
+                .find(|decl| decl.attribute_name().to_string() == attribute_name)
+            {
+                None => self.error(
+                    &attribute,
+                    format!(
+                        "the attribute `{}` is not declared for the element `{}`",
+                        attribute_name, name
+                    ),
+                ),
+                Some(attribute_decl) => self.attribute_value(&attribute, attribute_decl),
+            }
+        }
+        for attribute_decl in &attribute_decls {
+            let attribute_name = attribute_decl.attribute_name().to_string();
+            if *attribute_decl.default() == AttributeDefault::Required
+                && element.get_attribute_node(&attribute_name).is_none()
+            {
+                self.error(
+                    element,
+                    format!("the required attribute `{}` is missing", attribute_name),
+                );
+            }
+        }
+    }
+
+    fn attribute_value(&mut self, attribute: &RefNode, attribute_decl: &AttributeDecl) {
+        let value = normalized_value(attribute).unwrap_or_default();
+        let attribute_type = attribute_decl.attribute_type();
+        //
+        // The value of any attribute that is not CDATA is further normalized, as a list of
+        // tokens separated by single spaces.
+        //
+        let tokens: Vec<&str> = value
+            .split(is_xml_space)
+            .filter(|token| !token.is_empty())
+            .collect();
+        let joined = tokens.join(" ");
+        let valid = match attribute_type {
+            AttributeType::CData => true,
+            AttributeType::Id => {
+                let valid = tokens.len() == 1 && is_xml_name(tokens[0]);
+                if valid && !self.ids.insert(joined.clone()) {
+                    self.error(attribute, format!("the ID `{}` is not unique", joined));
+                }
+                valid
+            }
+            AttributeType::IdRef | AttributeType::IdRefs => {
+                let valid = !tokens.is_empty()
+                    && (tokens.len() == 1 || *attribute_type == AttributeType::IdRefs)
+                    && tokens.iter().all(|token| is_xml_name(token));
+                if valid {
+                    for token in &tokens {
+                        self.id_refs.push((attribute.clone(), token.to_string()));
+                    }
+                }
+                valid
+            }
+            AttributeType::Entity | AttributeType::Entities => {
+                !tokens.is_empty()
+                    && (tokens.len() == 1 || *attribute_type == AttributeType::Entities)
+                    && tokens.iter().all(|token| self.is_unparsed_entity(token))
+            }
+            AttributeType::NmToken => tokens.len() == 1 && is_xml_nmtoken(tokens[0]),
+            AttributeType::NmTokens => {
+                !tokens.is_empty() && tokens.iter().all(|token| is_xml_nmtoken(token))
+            }
+            AttributeType::Notation(values) | AttributeType::Enumeration(values) => {
+                values.contains(&joined)
+            }
+        };
+        if !valid {
+            self.error(
+                attribute,
+                format!(
+                    "the value `{}` is not valid for the declared type {}",
+                    value, attribute_type
+                ),
+            );
+        }
+        if let AttributeDefault::Fixed(fixed) = attribute_decl.default() {
+            let expected = if *attribute_type == AttributeType::CData {
+                value.clone()
+            } else {
+                joined
+            };
+            if expected != *fixed {
+                self.error(
+                    attribute,
+                    format!(
+                        "the value `{}` does not match the #FIXED value `{}`",
+                        value, fixed
+                    ),
+                );
+            }
+        }
+    }
+
+    fn is_unparsed_entity(&self, name: &str) -> bool {
+        self.doc_type
+            .entities()
+            .iter()
+            .any(|(entity_name, entity)| {
+                entity_name.to_string() == name && entity.notation_name().is_some()
+            })
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl ContentAutomaton {
+    //
+    // Returns `None` if `particle` is nested too deeply to build, which can only happen if it was
+    // constructed directly rather than parsed.
+    //
+    fn new(particle: &ContentParticle) -> Option<Self> {
+        let mut automaton = Self::default();
+        let (start, accept) = automaton.particle(particle, 1)?;
+        automaton.start = start;
+        automaton.accept = accept;
+        Some(automaton)
+    }
+
+    //
+    // Add the states for `particle`, at `depth` within the content model, returning its entry and
+    // exit states; nothing outside the particle has an edge to its exit, or from its entry.
+    //
+    fn particle(&mut self, particle: &ContentParticle, depth: usize) -> Option<(usize, usize)> {
+        if depth > MAX_CONTENT_DEPTH {
+            return None;
+        }
+        let (entry, exit) = match particle {
+            ContentParticle::Name(name, _) => {
+                let (entry, exit) = (self.state(), self.state());
+                self.edges[entry].push((Some(name.to_string()), exit));
+                (entry, exit)
+            }
+            ContentParticle::Choice(particles, _) => {
+                let (entry, exit) = (self.state(), self.state());
+                for particle in particles {
+                    let (inner_entry, inner_exit) = self.particle(particle, depth + 1)?;
+                    self.edges[entry].push((None, inner_entry));
+                    self.edges[inner_exit].push((None, exit));
+                }
+                (entry, exit)
+            }
+            ContentParticle::Sequence(particles, _) => {
+                let entry = self.state();
+                let mut exit = entry;
+                for particle in particles {
+                    let (inner_entry, inner_exit) = self.particle(particle, depth + 1)?;
+                    self.edges[exit].push((None, inner_entry));
+                    exit = inner_exit;
+                }
+                let last = self.state();
+                self.edges[exit].push((None, last));
+                (entry, last)
+            }
+        };
+        match particle.occurrence() {
+            Occurrence::Once => {}
+            Occurrence::Optional => self.edges[entry].push((None, exit)),
+            Occurrence::ZeroOrMore => {
+                self.edges[entry].push((None, exit));
+                self.edges[exit].push((None, entry));
+            }
+            Occurrence::OneOrMore => self.edges[exit].push((None, entry)),
+        }
+        Some((entry, exit))
+    }
+
+    fn state(&mut self) -> usize {
+        self.edges.push(Vec::new());
+        self.edges.len() - 1
+    }
+
+    //
+    // Run the automaton over `names`, tracking the set of all states it may be in.
+    //
+    fn matches(&self, names: &[String]) -> Result<(), ContentMismatch> {
+        let mut states = self.closure(std::iter::once(self.start).collect());
+        for (index, name) in names.iter().enumerate() {
+            let next: BTreeSet<usize> = states
+                .iter()
+                .flat_map(|state| self.edges[*state].iter())
+                .filter(|(label, _)| label.as_ref() == Some(name))
+                .map(|(_, target)| *target)
+                .collect();
+            if next.is_empty() {
+                return Err(ContentMismatch::Unexpected(index));
+            }
+            states = self.closure(next);
+        }
+        if states.contains(&self.accept) {
+            Ok(())
+        } else {
+            let mut expected: Vec<String> = Vec::new();
+            for (label, _) in states.iter().flat_map(|state| self.edges[*state].iter()) {
+                if let Some(label) = label {
+                    if !expected.contains(label) {
+                        expected.push(label.clone());
+                    }
+                }
+            }
+            Err(ContentMismatch::Incomplete(expected))
+        }
+    }
+
+    fn closure(&self, mut states: BTreeSet<usize>) -> BTreeSet<usize> {
+        let mut pending: Vec<usize> = states.iter().copied().collect();
+        while let Some(state) = pending.pop() {
+            for (label, target) in &self.edges[state] {
+                if label.is_none() && states.insert(*target) {
+                    pending.push(*target);
+                }
+            }
+        }
+        states
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+//
+// The content of `element`, with the children of any entity reference in place of the reference.
+//
+fn content_of(element: &RefNode) -> Vec<RefNode> {
+    let mut content = Vec::new();
+    for child in element.child_nodes() {
+        if child.node_type() == NodeType::EntityReference {
+            content.extend(content_of(&child));
+        } else {
+            content.push(child);
+        }
+    }
+    content
+}
+
+fn is_element_node(node: &RefNode) -> bool {
+    node.node_type() == NodeType::Element
+}
+
+//
+// White space is allowed in element content, but not in a CDATA section.
+//
+fn is_character_data(node: &RefNode) -> bool {
+    match node.node_type() {
+        NodeType::Text => !node
+            .node_value()
+            .unwrap_or_default()
+            .chars()
+            .all(is_xml_space),
+        NodeType::CData => true,
+        _ => false,
+    }
+}
@@ -0,0 +1,157 @@
+/*!
+This module provides the [`NodeVisitor`](trait.NodeVisitor.html) trait, and support types, for the
+[`Visitable`](trait.Visitable.html) trait, which walks a node and its descendants in document
+(pre-order) order calling back to the visitor for each node.
+
+Before the children of a node are visited the walk takes a copy of its list of children, and no
+node is borrowed while a callback runs. A visitor may therefore change the tree as it is walked,
+including removing or replacing the node it was called with, without disturbing the walk.
+
+```rust
+# #[cfg(feature = "quick_parser")]
+# fn main() {
+use xml_dom::level2::*;
+use xml_dom::level2::ext::*;
+use xml_dom::parser::read_xml;
+
+//
+// Remove every comment, and the content of any `script` element.
+//
+struct Sanitizer;
+
+impl NodeVisitor for Sanitizer {
+    fn visit_element(&mut self, element: &mut RefNode) -> VisitAction {
+        if element.node_name().to_string() == "script" {
+            let mut parent = element.parent_node().unwrap();
+            let _safe_to_ignore = parent.remove_child(element.clone()).unwrap();
+            VisitAction::SkipChildren
+        } else {
+            VisitAction::Continue
+        }
+    }
+
+    fn visit_comment(&mut self, comment: &mut RefNode) -> VisitAction {
+        let mut parent = comment.parent_node().unwrap();
+        let _safe_to_ignore = parent.remove_child(comment.clone()).unwrap();
+        VisitAction::Continue
+    }
+}
+
+let document_node = read_xml(r#"<a><!--x--><script>run()</script><b>text</b></a>"#).unwrap();
+document_node.accept(&mut Sanitizer).unwrap();
+assert_eq!(document_node.to_string(), "<a><b>text</b></a>");
+# }
+# #[cfg(not(feature = "quick_parser"))]
+# fn main() {}
+```
+*/
+
+use crate::level2::ext::traits::Visitable;
+use crate::level2::node_impl::RefNode;
+use crate::level2::traits::NodeType;
+use crate::shared::error::Result;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The action returned by each [`NodeVisitor`](trait.NodeVisitor.html) callback, this determines
+/// how the walk continues.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VisitAction {
+    /// Continue the walk, visiting the children of the node.
+    Continue,
+    /// Continue the walk, but do not visit the children of the node.
+    SkipChildren,
+    /// End the walk; no further nodes are visited.
+    Stop,
+}
+
+///
+/// A visitor that is called for each node by [`Visitable::accept`](trait.Visitable.html#tymethod.accept).
+/// Each method has a default implementation that returns `VisitAction::Continue`, so a visitor need
+/// only implement the methods for the node types it is interested in.
+///
+/// Each callback is passed the node itself, which it may change, remove from its parent, or
+/// replace. Nodes that a callback adds to the children of its node are visited, nodes that it adds
+/// elsewhere in the tree, including a replacement for the node itself, are not.
+///
+pub trait NodeVisitor {
+    ///
+    /// Called for each `Element` node, before its children are visited.
+    ///
+    fn visit_element(&mut self, _element: &mut RefNode) -> VisitAction {
+        VisitAction::Continue
+    }
+    ///
+    /// Called for each `Text` node.
+    ///
+    fn visit_text(&mut self, _text: &mut RefNode) -> VisitAction {
+        VisitAction::Continue
+    }
+    ///
+    /// Called for each `Comment` node.
+    ///
+    fn visit_comment(&mut self, _comment: &mut RefNode) -> VisitAction {
+        VisitAction::Continue
+    }
+    ///
+    /// Called for each `CDataSection` node.
+    ///
+    fn visit_cdata(&mut self, _cdata: &mut RefNode) -> VisitAction {
+        VisitAction::Continue
+    }
+    ///
+    /// Called for each `ProcessingInstruction` node.
+    ///
+    fn visit_pi(&mut self, _pi: &mut RefNode) -> VisitAction {
+        VisitAction::Continue
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Visitable for RefNode {
+    fn accept(&self, visitor: &mut dyn NodeVisitor) -> Result<()> {
+        let _stopped = walk(self.clone(), visitor);
+        Ok(())
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+//
+// Visit `node` and then its children; returns `true` if the visitor stopped the walk.
+//
+fn walk(mut node: RefNode, visitor: &mut dyn NodeVisitor) -> bool {
+    let node_type = node.borrow().i_node_type;
+    let action = match node_type {
+        NodeType::Element => visitor.visit_element(&mut node),
+        NodeType::Text => visitor.visit_text(&mut node),
+        NodeType::CData => visitor.visit_cdata(&mut node),
+        NodeType::Comment => visitor.visit_comment(&mut node),
+        NodeType::ProcessingInstruction => visitor.visit_pi(&mut node),
+        NodeType::Document | NodeType::DocumentFragment | NodeType::EntityReference => {
+            VisitAction::Continue
+        }
+        NodeType::Attribute | NodeType::DocumentType | NodeType::Entity | NodeType::Notation => {
+            VisitAction::SkipChildren
+        }
+    };
+    match action {
+        VisitAction::Stop => true,
+        VisitAction::SkipChildren => false,
+        VisitAction::Continue => {
+            let child_nodes = node.borrow().i_child_nodes.clone();
+            child_nodes
+                .into_iter()
+                .any(|child_node| walk(child_node, visitor))
+        }
+    }
+}
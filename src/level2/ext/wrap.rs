@@ -0,0 +1,100 @@
+/*!
+This module provides functions to wrap a run of nodes in a new element, see also the
+[`WrapElements`](trait.WrapElements.html) trait.
+
+# Example
+
+```rust
+# #[cfg(feature = "quick_parser")]
+# fn main() {
+use xml_dom::level2::*;
+use xml_dom::level2::ext::wrap::wrap_nodes;
+use xml_dom::parser::read_xml;
+
+let document_node = read_xml("<p>one<b>two</b>three</p>").unwrap();
+let paragraph_node = document_node.document_element().unwrap();
+let nodes = paragraph_node.child_nodes().to_vec();
+
+let _span_node = wrap_nodes(&document_node, "span", &nodes[1..]).unwrap();
+assert_eq!(
+    document_node.to_string(),
+    "<p>one<span><b>two</b>three</span></p>"
+);
+# }
+# #[cfg(not(feature = "quick_parser"))]
+# fn main() {}
+```
+*/
+
+use crate::level2::convert::is_document;
+use crate::level2::node_impl::RefNode;
+use crate::level2::traits::{Document, Node};
+use crate::shared::error::{Error, Result, MSG_INVALID_NODE_TYPE};
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Create an element named `tag_name`, owned by `document`, insert it at the position of the first
+/// of `nodes`, and then move each of `nodes`, in order, into the new element. The new element is
+/// returned; if `nodes` is empty, or the first of them has no parent, the new element has no
+/// parent.
+///
+/// **Exceptions**
+///
+/// * `INVALID_STATE_ERR`: Raised if `document` is not a `Document`.
+///
+/// Any error from `Document::create_element`, `Node::insert_before`, or `Node::append_child` is
+/// also returned; nodes moved before the error remain in the new element.
+///
+pub fn wrap_nodes(document: &RefNode, tag_name: &str, nodes: &[RefNode]) -> Result<RefNode> {
+    check_document(document)?;
+    let wrapper_node = document.create_element(tag_name)?;
+    wrap_with(wrapper_node, nodes)
+}
+
+///
+/// Create an element with `namespace_uri` and `qualified_name`, owned by `document`, insert it at
+/// the position of the first of `nodes`, and then move each of `nodes`, in order, into the new
+/// element; see [`wrap_nodes`](fn.wrap_nodes.html).
+///
+pub fn wrap_nodes_ns(
+    document: &RefNode,
+    namespace_uri: &str,
+    qualified_name: &str,
+    nodes: &[RefNode],
+) -> Result<RefNode> {
+    check_document(document)?;
+    let wrapper_node = document.create_element_ns(namespace_uri, qualified_name)?;
+    wrap_with(wrapper_node, nodes)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn check_document(document: &RefNode) -> Result<()> {
+    if is_document(document) {
+        Ok(())
+    } else {
+        warn!("{}", MSG_INVALID_NODE_TYPE);
+        Err(Error::InvalidState)
+    }
+}
+
+//
+// Insert `wrapper_node` before the first of `nodes`, then move all of `nodes` into it.
+//
+fn wrap_with(mut wrapper_node: RefNode, nodes: &[RefNode]) -> Result<RefNode> {
+    if let Some(first_node) = nodes.first() {
+        if let Some(mut parent_node) = first_node.parent_node() {
+            let _safe_to_ignore =
+                parent_node.insert_before(wrapper_node.clone(), Some(first_node.clone()))?;
+        }
+    }
+    for node in nodes {
+        let _safe_to_ignore = wrapper_node.append_child(node.clone())?;
+    }
+    Ok(wrapper_node)
+}
@@ -18,7 +18,7 @@ Implementation for DOM Core Level 2.
 | `EntityReference`       | [`EntityReference`](level2/trait.EntityReference.html)             |
 | `NamedNodeMap`          | `HashMap<Name, RefNode>`                                    |
 | `Node`                  | [`Node`](level2/trait.Node.html)                                   |
-| `NodeList`              | `Vec<Rc<RefNode>>`                                          |
+| `NodeList`              | [`NodeList`](level2/struct.NodeList.html)                          |
 | `Notation`              | [`Notation`](level2/trait.Notation.html)                           |
 | `ProcessingInstruction` | [`ProcessingInstruction`](level2/trait.ProcessingInstruction.html) |
 | `Text`                  | [`Text`](level2/trait.Text.html)                                   |
@@ -58,8 +58,29 @@ The following extensions are provided beyond the DOM Level 2 specification, all
    optional behavior for a given `Document` instance.
 1. The trait [`DocumentDecl`](trait.DocumentDecl.html) extends `Document` with the ability to set
    and retrieve the XML declaration from the document's prolog.
+1. The trait [`DocumentTypeDecl`](trait.DocumentTypeDecl.html) extends `DocumentType` with the
+   ability to add entities, notations, attribute declarations, and the internal subset.
+1. The trait [`EntityExpansion`](trait.EntityExpansion.html) extends `Node` with the ability to
+   replace entity references with copies of their replacement text.
+1. The trait [`FindElements`](trait.FindElements.html) extends `Element` with the ability to find
+   child elements, and their attributes, using simple paths such as `"book[2]/@id"`, and to find
+   descendant elements by attribute name or value.
+1. The trait [`InheritedAttributes`](trait.InheritedAttributes.html) extends `Element` with the
+   ability to find the `xml:space` and `xml:lang` values in effect for an element.
+1. The trait [`InnerXml`](trait.InnerXml.html) extends `Element` with the ability to read, and
+   replace, the content of an element as XML text.
 1. The trait [`Namespaced`](trait.Namespaced.html) extends `Element` with the ability to look-up
    namespace mappings (using the standard `xmlns` attribute).
+1. The trait [`NormalizeDocument`](trait.NormalizeDocument.html) extends `Document` with a
+   configurable normalization, using [`NormalizeConfig`](normalize/struct.NormalizeConfig.html),
+   to remove comments, CDATA sections, entity references, and redundant whitespace and namespace
+   declarations.
+1. The trait [`StripWhitespace`](trait.StripWhitespace.html) extends `Node` with the ability to
+   remove whitespace-only text nodes from element content, optionally honoring `xml:space`.
+1. The trait [`TreeIterators`](trait.TreeIterators.html) extends `Node` with non-recursive
+   iterators over the descendants, ancestors, and siblings of a node.
+1. The trait [`WholeText`](trait.WholeText.html) extends `Text` with the ability to read, and
+   replace, the text of logically-adjacent text nodes.
 1. The functions [`create_entity`](dom_impl/fn.create_entity.html),
    [`create_internal_entity`](dom_impl/fn.create_internal_entity.html), and
    [`create_notation`](dom_impl/fn.create_notation.html) in the
@@ -73,6 +94,8 @@ The following extensions are provided beyond the DOM Level 2 specification, all
 // Public Modules
 // ------------------------------------------------------------------------------------------------
 
+pub mod builder;
+
 pub mod convert;
 
 pub mod dom_impl;
@@ -81,8 +104,26 @@ pub use dom_impl::get_implementation;
 pub(crate) mod node_impl;
 pub use node_impl::RefNode;
 
+pub(crate) mod node_list;
+pub use node_list::{NodeList, NodeListIter};
+
+pub(crate) mod data_ref;
+pub use data_ref::DataRef;
+
 pub mod ext;
 
+pub mod mutation;
+
+pub mod owned;
+
+pub mod range;
+
+pub mod selector;
+
+pub mod traversal;
+
+pub mod xpath;
+
 // ------------------------------------------------------------------------------------------------
 // Re-Export
 // ------------------------------------------------------------------------------------------------
@@ -91,6 +132,12 @@ pub use crate::shared::error::{Error, Result};
 
 pub use crate::shared::name::*;
 
+///
+/// The reference counted pointer used to share values, such as user data and node filters, with
+/// this implementation; this is `std::rc::Rc`, or `std::sync::Arc` with the `sync` feature.
+///
+pub use crate::shared::rc_cell::Shared;
+
 pub(crate) mod traits;
 pub use traits::*;
 
@@ -100,4 +147,4 @@ pub use traits::*;
 
 mod entity_resolver;
 
-mod trait_impls;
+pub(crate) mod trait_impls;
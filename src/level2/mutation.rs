@@ -0,0 +1,250 @@
+/*!
+Provides listeners that are told of changes to the nodes of a document, for example to keep an
+external index synchronized with the DOM.
+
+This is modeled on, but much simpler than, the mutation events of
+[Document Object Model Events](https://www.w3.org/TR/DOM-Level-2-Events/events.html#Events-eventgroupings-mutationevents);
+listeners are registered with a document, not a node, and there is no capture or bubbling.
+
+# Implementation
+
+A listener is called after the change has been made, for every change to a node owned by the
+document, whether or not the node is currently in the document's tree. The document's
+`RefCell`s are not borrowed while a listener is called, so a listener may read the document. A
+listener may also change the document, however the notifications for any such change are made
+immediately, before those for the original change have been made to any remaining listeners.
+
+Changes made by the parser, and changes to names by `Document::rename_node` or `Node::set_prefix`,
+are not reported.
+
+# Example
+
+```rust
+# #[cfg(feature = "quick_parser")]
+# fn main() {
+use xml_dom::level2::*;
+use xml_dom::level2::mutation::*;
+use xml_dom::parser::read_xml;
+use std::sync::{Arc, Mutex};
+
+let mut document_node = read_xml(r#"<a><b/></a>"#).unwrap();
+let inserted = Arc::new(Mutex::new(Vec::new()));
+let listener_inserted = inserted.clone();
+let listener = document_node.add_mutation_listener(Box::new(move |mutation| {
+    if let Mutation::NodeInserted { node, .. } = mutation {
+        listener_inserted.lock().unwrap().push(node.node_name().to_string());
+    }
+})).unwrap();
+
+let document = convert::as_document(&document_node).unwrap();
+let mut root_node = document.document_element().unwrap();
+let _safe_to_ignore = root_node
+    .append_child(document.create_element("c").unwrap())
+    .unwrap();
+assert_eq!(*inserted.lock().unwrap(), vec!["c".to_string()]);
+
+document_node.remove_mutation_listener(listener).unwrap();
+# }
+# #[cfg(not(feature = "quick_parser"))]
+# fn main() {}
+```
+*/
+
+use crate::level2::node_impl::{Extension, RefNode};
+use crate::level2::traits::Document;
+use crate::shared::error::{Error, Result, MSG_INVALID_EXTENSION, MSG_LISTENER_NOT_FOUND};
+use crate::shared::name::Name;
+use crate::shared::rc_cell::Shared;
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A change made to a node, as reported to the listeners added by
+/// [`add_mutation_listener`](trait.DocumentMutation.html#tymethod.add_mutation_listener).
+///
+#[derive(Clone, Debug)]
+pub enum Mutation {
+    /// The `node` was inserted as a child of `parent`.
+    NodeInserted {
+        /// The new parent of the node.
+        parent: RefNode,
+        /// The node that was inserted.
+        node: RefNode,
+    },
+    /// The `node` was removed from the children of `parent`.
+    NodeRemoved {
+        /// The previous parent of the node.
+        parent: RefNode,
+        /// The node that was removed.
+        node: RefNode,
+    },
+    /// The attribute `name` of `element` was added, changed, or removed.
+    AttributeChanged {
+        /// The element that owns the attribute.
+        element: RefNode,
+        /// The name of the attribute.
+        name: Name,
+        /// The value before the change, `None` if the attribute was added.
+        old_value: Option<String>,
+        /// The value after the change, `None` if the attribute was removed.
+        new_value: Option<String>,
+    },
+    /// The data of a `Text`, `CDATASection`, `Comment`, or `ProcessingInstruction` `node` was
+    /// changed.
+    CharacterDataChanged {
+        /// The node whose data changed.
+        node: RefNode,
+        /// The data before the change.
+        old_value: Option<String>,
+        /// The data after the change.
+        new_value: Option<String>,
+    },
+}
+
+///
+/// Identifies a listener added to a document, so that it may later be removed.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ListenerId(usize);
+
+///
+/// A function called with each change made to the nodes of a document; with the `sync` feature
+/// the function must also be `Send` and `Sync`.
+///
+#[cfg(not(feature = "sync"))]
+pub type MutationListener = Box<dyn Fn(&Mutation)>;
+
+///
+/// A function called with each change made to the nodes of a document; with the `sync` feature
+/// the function must also be `Send` and `Sync`.
+///
+#[cfg(feature = "sync")]
+pub type MutationListener = Box<dyn Fn(&Mutation) + Send + Sync>;
+
+///
+/// Extends `Document` with the ability to add, and remove, listeners for changes to the document's
+/// nodes.
+///
+pub trait DocumentMutation: Document {
+    ///
+    /// Add a listener to be called after each change to a node owned by this document; returns
+    /// the identifier needed to remove it.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `INVALID_STATE_ERR`: Raised if this node is not a document.
+    ///
+    fn add_mutation_listener(&mut self, listener: MutationListener) -> Result<ListenerId>;
+    ///
+    /// Remove a listener added by [`add_mutation_listener`](#tymethod.add_mutation_listener).
+    ///
+    /// **Exceptions**
+    ///
+    /// * `NOT_FOUND_ERR`: Raised if there is no such listener registered with this document.
+    ///
+    fn remove_mutation_listener(&mut self, listener_id: ListenerId) -> Result<()>;
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A listener as held by a document, shared so that it may be called without a borrow.
+///
+#[cfg(not(feature = "sync"))]
+pub(crate) type SharedListener = Shared<dyn Fn(&Mutation)>;
+
+///
+/// A listener as held by a document, shared so that it may be called without a borrow.
+///
+#[cfg(feature = "sync")]
+pub(crate) type SharedListener = Shared<dyn Fn(&Mutation) + Send + Sync>;
+
+///
+/// The listeners registered with a document; these are not copied when the document is cloned.
+///
+#[doc(hidden)]
+#[derive(Default)]
+pub(crate) struct MutationListeners {
+    next_id: usize,
+    listeners: Vec<(ListenerId, SharedListener)>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl DocumentMutation for RefNode {
+    fn add_mutation_listener(&mut self, listener: MutationListener) -> Result<ListenerId> {
+        let mut mut_self = self.borrow_mut();
+        if let Extension::Document {
+            i_mutation_listeners,
+            ..
+        } = &mut mut_self.i_extension
+        {
+            Ok(i_mutation_listeners.add(listener))
+        } else {
+            warn!("{}", MSG_INVALID_EXTENSION);
+            Err(Error::InvalidState)
+        }
+    }
+
+    fn remove_mutation_listener(&mut self, listener_id: ListenerId) -> Result<()> {
+        let mut mut_self = self.borrow_mut();
+        if let Extension::Document {
+            i_mutation_listeners,
+            ..
+        } = &mut mut_self.i_extension
+        {
+            if i_mutation_listeners.remove(listener_id) {
+                Ok(())
+            } else {
+                warn!("{}", MSG_LISTENER_NOT_FOUND);
+                Err(Error::NotFound)
+            }
+        } else {
+            warn!("{}", MSG_INVALID_EXTENSION);
+            Err(Error::InvalidState)
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Clone for MutationListeners {
+    fn clone(&self) -> Self {
+        Default::default()
+    }
+}
+
+impl Debug for MutationListeners {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_list()
+            .entries(self.listeners.iter().map(|(listener_id, _)| listener_id))
+            .finish()
+    }
+}
+
+impl MutationListeners {
+    pub(crate) fn add(&mut self, listener: MutationListener) -> ListenerId {
+        let listener_id = ListenerId(self.next_id);
+        self.next_id += 1;
+        self.listeners.push((listener_id, Shared::from(listener)));
+        listener_id
+    }
+    pub(crate) fn remove(&mut self, listener_id: ListenerId) -> bool {
+        let before = self.listeners.len();
+        self.listeners.retain(|(id, _)| *id != listener_id);
+        self.listeners.len() != before
+    }
+    pub(crate) fn listeners(&self) -> Vec<SharedListener> {
+        self.listeners
+            .iter()
+            .map(|(_, listener)| listener.clone())
+            .collect()
+    }
+}
@@ -1,11 +1,14 @@
+use crate::level2::dom_impl::THIS_IMPLEMENTATION;
 use crate::level2::ext::ProcessingOptions;
-use crate::level2::ext::XmlDecl;
-use crate::level2::traits::{Node, NodeType};
-use crate::level2::{get_implementation, DOMImplementation};
+use crate::level2::ext::{AttributeDecl, ElementDecl, ExpansionLimits, XmlDecl};
+use crate::level2::mutation::MutationListeners;
+use crate::level2::traits::{Node, NodeType, UserData, UserDataHandlerRef, UserDataOperation};
+use crate::level2::DOMImplementation;
 use crate::shared::name::Name;
-use crate::shared::rc_cell::{RcRefCell, WeakRefCell};
-use std::collections::HashMap;
+use crate::shared::rc_cell::{RcRefCell, Shared, SharedCell, WeakRefCell, WeakShared};
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Debug, Formatter};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 // ------------------------------------------------------------------------------------------------
 // Public Types
@@ -16,9 +19,18 @@ use std::fmt::{Debug, Formatter};
 /// type for the `NodeRef` associated type in the  [`Node`](trait.Node.html) trait.
 ///
 /// This is the common response type for DOM actions and can be cast to specific traits either
-/// by-hand or using the [`xml_dom::convert`](convert/index.html) module. Also, note that this type
-/// supports[`PartialEq`](https://doc.rust-lang.org/std/cmp/trait.PartialEq.html) and so two nodes
-/// can be tested to ensure they are the same.
+/// by-hand or using the [`xml_dom::convert`](convert/index.html) module.
+///
+/// # Equality
+///
+/// The [`PartialEq`](https://doc.rust-lang.org/std/cmp/trait.PartialEq.html), `Eq`, and `Hash`
+/// implementations of this type are based on identity: two references are equal if, and only if,
+/// they refer to the same node, as [`Node::is_same_node`](trait.Node.html#tymethod.is_same_node)
+/// tests. Nodes may therefore be kept in a `HashSet`, or used as `HashMap` keys, for example to
+/// record the nodes already visited, and a node stays equal to itself when its content changes;
+/// the Clippy `mutable_key_type` lint may be allowed for such collections.
+/// Use [`Node::is_equal_node`](trait.Node.html#tymethod.is_equal_node) to compare the structure
+/// and content of two nodes.
 ///
 pub type RefNode = RcRefCell<NodeImpl>;
 
@@ -30,6 +42,44 @@ pub type RefNode = RcRefCell<NodeImpl>;
 ///
 pub(crate) type WeakRefNode = WeakRefCell<NodeImpl>;
 
+///
+/// The implementation held by a document; this is `Sync` so that a document may be shared
+/// between threads with the `sync` feature.
+///
+pub(crate) type ImplementationRef = &'static (dyn DOMImplementation<NodeRef = RefNode> + Sync);
+
+///
+/// Internal hook for objects, such as a `NodeIterator`, that hold positions within a document and
+/// must be told when a node is about to be removed from its parent.
+///
+pub(crate) trait RemovalObserver {
+    fn node_removing(&mut self, node: &RefNode);
+}
+
+///
+/// The reference to a `RemovalObserver` held by the object observing removals.
+///
+#[cfg(not(feature = "sync"))]
+pub(crate) type SharedRemovalObserver = Shared<SharedCell<dyn RemovalObserver>>;
+
+///
+/// The reference to a `RemovalObserver` held by the object observing removals.
+///
+#[cfg(feature = "sync")]
+pub(crate) type SharedRemovalObserver = Shared<SharedCell<dyn RemovalObserver + Send + Sync>>;
+
+///
+/// The weak reference to a `RemovalObserver` held by its `Document`.
+///
+#[cfg(not(feature = "sync"))]
+pub(crate) type WeakRemovalObserver = WeakShared<SharedCell<dyn RemovalObserver>>;
+
+///
+/// The weak reference to a `RemovalObserver` held by its `Document`.
+///
+#[cfg(feature = "sync")]
+pub(crate) type WeakRemovalObserver = WeakShared<SharedCell<dyn RemovalObserver + Send + Sync>>;
+
 // ------------------------------------------------------------------------------------------------
 
 ///
@@ -41,13 +91,20 @@ pub(crate) enum Extension {
     None,
     Attribute {
         i_owner_element: Option<WeakRefNode>,
+        i_is_id: bool,
+        i_specified: bool,
     },
     Document {
-        i_implementation: &'static dyn DOMImplementation<NodeRef = RefNode>,
+        i_implementation: ImplementationRef,
         i_xml_declaration: Option<XmlDecl>,
         i_document_type: Option<RefNode>,
-        i_id_map: HashMap<String, WeakRefNode>,
+        i_document_uri: Option<String>,
         i_options: ProcessingOptions,
+        i_expansion_limits: ExpansionLimits,
+        i_strict_error_checking: bool,
+        i_removal_observers: Vec<WeakRemovalObserver>,
+        i_mutation_listeners: MutationListeners,
+        i_name_pool: NamePool,
     },
     DocumentType {
         i_entities: HashMap<Name, RefNode>,
@@ -55,10 +112,12 @@ pub(crate) enum Extension {
         i_public_id: Option<String>,
         i_system_id: Option<String>,
         i_internal_subset: Option<String>,
+        i_attribute_decls: Vec<AttributeDecl>,
+        i_element_decls: Vec<ElementDecl>,
     },
     Element {
-        i_attributes: HashMap<Name, RefNode>,
-        i_namespaces: HashMap<Option<String>, String>,
+        i_attributes: AttributeMap,
+        i_namespaces: NamespaceMap,
     },
     Entity {
         i_public_id: Option<String>,
@@ -71,6 +130,57 @@ pub(crate) enum Extension {
     },
 }
 
+///
+/// Internal storage for the attributes of an element, this keeps attributes in the order they were
+/// first added so that serialization is predictable; replacing an attribute keeps its position.
+///
+#[doc(hidden)]
+#[derive(Clone, Debug, Default)]
+pub(crate) struct AttributeMap {
+    entries: Vec<(Name, RefNode)>,
+}
+
+///
+/// Internal storage for the namespace mappings of an element, keyed by prefix (`None` for the
+/// default namespace). Like `AttributeMap` this keeps mappings in the order they were first added
+/// so that lookups by namespace URI, and any output, do not vary between runs.
+///
+#[doc(hidden)]
+#[derive(Clone, Debug, Default)]
+pub(crate) struct NamespaceMap {
+    entries: Vec<(Option<String>, String)>,
+}
+
+///
+/// Internal storage for the user data of a node, keyed by name. The data and handlers are shared,
+/// not copied, when this is cloned.
+///
+#[doc(hidden)]
+#[derive(Clone, Default)]
+pub(crate) struct UserDataMap {
+    entries: Vec<(String, UserData, Option<UserDataHandlerRef<RefNode>>)>,
+}
+
+///
+/// Internal storage for the index at which a node was last found in its parent's `child_nodes`.
+/// This is only a hint, it is checked before use and updated whenever the node is found elsewhere,
+/// so that finding the siblings of a node does not usually need a scan of its parent's children.
+/// The index is atomic so that it may be updated through a shared borrow.
+///
+#[doc(hidden)]
+#[derive(Debug, Default)]
+pub(crate) struct IndexHint(AtomicUsize);
+
+///
+/// Internal storage for the names of the nodes created by a document; each distinct name is kept
+/// once, and nodes created with an equal name share it rather than holding their own copy.
+///
+#[doc(hidden)]
+#[derive(Clone, Debug, Default)]
+pub(crate) struct NamePool {
+    names: HashSet<Name>,
+}
+
 ///
 /// Internal container for DOM tree node data and state.
 ///
@@ -81,18 +191,21 @@ pub(crate) enum Extension {
 pub struct NodeImpl {
     pub(crate) i_node_type: NodeType,
     pub(crate) i_name: Name,
-    pub(crate) i_value: Option<String>,
+    pub(crate) i_value: Option<Box<str>>,
     pub(crate) i_parent_node: Option<WeakRefNode>,
     pub(crate) i_owner_document: Option<WeakRefNode>,
     pub(crate) i_child_nodes: Vec<RefNode>,
     pub(crate) i_extension: Extension,
+    pub(crate) i_read_only: bool,
+    pub(crate) i_user_data: UserDataMap,
+    pub(crate) i_index_hint: IndexHint,
 }
 
 // ------------------------------------------------------------------------------------------------
 // Implementations
 // ------------------------------------------------------------------------------------------------
 
-impl Debug for &'static dyn DOMImplementation<NodeRef = RefNode> {
+impl Debug for ImplementationRef {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "DOMImplementation")
     }
@@ -113,6 +226,9 @@ impl NodeImpl {
                 i_attributes: Default::default(),
                 i_namespaces: Default::default(),
             },
+            i_read_only: false,
+            i_user_data: Default::default(),
+            i_index_hint: Default::default(),
         }
     }
     pub(crate) fn new_attribute(
@@ -134,29 +250,40 @@ impl NodeImpl {
             i_child_nodes: children,
             i_extension: Extension::Attribute {
                 i_owner_element: None,
+                i_is_id: false,
+                i_specified: true,
             },
+            i_read_only: false,
+            i_user_data: Default::default(),
+            i_index_hint: Default::default(),
         }
     }
     pub(crate) fn new_text(owner_document: WeakRefNode, data: &str) -> Self {
         Self {
             i_node_type: NodeType::Text,
             i_name: Name::for_text(),
-            i_value: Some(data.to_string()),
+            i_value: Some(data.into()),
             i_parent_node: None,
             i_owner_document: Some(owner_document),
             i_child_nodes: vec![],
             i_extension: Extension::None,
+            i_read_only: false,
+            i_user_data: Default::default(),
+            i_index_hint: Default::default(),
         }
     }
     pub(crate) fn new_cdata(owner_document: WeakRefNode, data: &str) -> Self {
         Self {
             i_node_type: NodeType::CData,
             i_name: Name::for_cdata(),
-            i_value: Some(data.to_string()),
+            i_value: Some(data.into()),
             i_parent_node: None,
             i_owner_document: Some(owner_document),
             i_child_nodes: vec![],
             i_extension: Extension::None,
+            i_read_only: false,
+            i_user_data: Default::default(),
+            i_index_hint: Default::default(),
         }
     }
     pub(crate) fn new_processing_instruction(
@@ -167,25 +294,31 @@ impl NodeImpl {
         Self {
             i_node_type: NodeType::ProcessingInstruction,
             i_name: target,
-            i_value: data.map(String::from),
+            i_value: data.map(Box::from),
             i_parent_node: None,
             i_owner_document: Some(owner_document),
             i_child_nodes: vec![],
             i_extension: Extension::None,
+            i_read_only: false,
+            i_user_data: Default::default(),
+            i_index_hint: Default::default(),
         }
     }
     pub(crate) fn new_comment(owner_document: WeakRefNode, data: &str) -> Self {
         Self {
             i_node_type: NodeType::Comment,
             i_name: Name::for_comment(),
-            i_value: Some(data.to_string()),
+            i_value: Some(data.into()),
             i_parent_node: None,
             i_owner_document: Some(owner_document),
             i_child_nodes: vec![],
             i_extension: Extension::None,
+            i_read_only: false,
+            i_user_data: Default::default(),
+            i_index_hint: Default::default(),
         }
     }
-    pub(crate) fn new_document(doc_type: Option<RefNode>, options: ProcessingOptions) -> Self {
+    pub(crate) fn new_document(options: ProcessingOptions) -> Self {
         Self {
             i_node_type: NodeType::Document,
             i_name: Name::for_document(),
@@ -194,12 +327,20 @@ impl NodeImpl {
             i_owner_document: None,
             i_child_nodes: vec![],
             i_extension: Extension::Document {
-                i_implementation: get_implementation(),
+                i_implementation: THIS_IMPLEMENTATION,
                 i_xml_declaration: None,
-                i_document_type: doc_type,
-                i_id_map: Default::default(),
+                i_document_type: None,
+                i_document_uri: None,
                 i_options: options,
+                i_expansion_limits: Default::default(),
+                i_strict_error_checking: true,
+                i_removal_observers: Default::default(),
+                i_mutation_listeners: Default::default(),
+                i_name_pool: Default::default(),
             },
+            i_read_only: false,
+            i_user_data: Default::default(),
+            i_index_hint: Default::default(),
         }
     }
     pub(crate) fn new_document_fragment(owner_document: WeakRefNode) -> Self {
@@ -211,6 +352,9 @@ impl NodeImpl {
             i_owner_document: Some(owner_document),
             i_child_nodes: vec![],
             i_extension: Extension::None,
+            i_read_only: false,
+            i_user_data: Default::default(),
+            i_index_hint: Default::default(),
         }
     }
     pub(crate) fn new_document_type(
@@ -232,7 +376,12 @@ impl NodeImpl {
                 i_public_id: public_id.map(String::from),
                 i_system_id: system_id.map(String::from),
                 i_internal_subset: None,
+                i_attribute_decls: Default::default(),
+                i_element_decls: Default::default(),
             },
+            i_read_only: false,
+            i_user_data: Default::default(),
+            i_index_hint: Default::default(),
         }
     }
     pub(crate) fn new_entity_reference(owner_document: WeakRefNode, name: Name) -> Self {
@@ -244,6 +393,9 @@ impl NodeImpl {
             i_owner_document: Some(owner_document),
             i_child_nodes: vec![],
             i_extension: Extension::None,
+            i_read_only: false,
+            i_user_data: Default::default(),
+            i_index_hint: Default::default(),
         }
     }
     pub(crate) fn new_entity(
@@ -264,6 +416,9 @@ impl NodeImpl {
                 i_system_id: system_id.map(String::from),
                 i_notation_name: None,
             },
+            i_read_only: false,
+            i_user_data: Default::default(),
+            i_index_hint: Default::default(),
         }
     }
     pub(crate) fn new_internal_entity(
@@ -274,7 +429,7 @@ impl NodeImpl {
         Self {
             i_node_type: NodeType::Entity,
             i_name: notation_name,
-            i_value: Some(value.to_string()),
+            i_value: Some(value.into()),
             i_parent_node: None,
             i_owner_document: owner_document,
             i_child_nodes: vec![],
@@ -283,6 +438,9 @@ impl NodeImpl {
                 i_system_id: None,
                 i_notation_name: None,
             },
+            i_read_only: false,
+            i_user_data: Default::default(),
+            i_index_hint: Default::default(),
         }
     }
     pub(crate) fn new_notation(
@@ -302,27 +460,54 @@ impl NodeImpl {
                 i_public_id: public_id.map(String::from),
                 i_system_id: system_id.map(String::from),
             },
+            i_read_only: true,
+            i_user_data: Default::default(),
+            i_index_hint: Default::default(),
         }
     }
-    #[allow(clippy::clone_double_ref)]
+    ///
+    /// The attributes of an element, in the order they were added; this is empty for any other
+    /// node type.
+    ///
+    pub(crate) fn attribute_nodes(&self) -> Vec<RefNode> {
+        match &self.i_extension {
+            Extension::Element { i_attributes, .. } => i_attributes.values().cloned().collect(),
+            _ => Vec::default(),
+        }
+    }
+    #[allow(suspicious_double_ref_op)]
     pub(crate) fn clone_node(&self, deep: bool) -> Self {
         let extension = match &self.i_extension {
             Extension::None => Extension::None,
-            Extension::Attribute { i_owner_element } => Extension::Attribute {
+            Extension::Attribute {
+                i_owner_element,
+                i_is_id,
+                ..
+            } => Extension::Attribute {
                 i_owner_element: i_owner_element.clone(),
+                i_is_id: *i_is_id,
+                i_specified: true,
             },
             Extension::Document {
                 i_implementation,
                 i_xml_declaration,
                 i_document_type,
-                i_id_map,
+                i_document_uri,
                 i_options,
+                i_expansion_limits,
+                i_strict_error_checking,
+                ..
             } => Extension::Document {
                 i_implementation: i_implementation.clone(),
                 i_xml_declaration: i_xml_declaration.clone(),
                 i_document_type: i_document_type.clone(),
-                i_id_map: i_id_map.clone(),
+                i_document_uri: i_document_uri.clone(),
                 i_options: i_options.clone(),
+                i_expansion_limits: i_expansion_limits.clone(),
+                i_strict_error_checking: *i_strict_error_checking,
+                i_removal_observers: Default::default(),
+                i_mutation_listeners: Default::default(),
+                i_name_pool: Default::default(),
             },
             Extension::DocumentType {
                 i_entities,
@@ -330,12 +515,16 @@ impl NodeImpl {
                 i_public_id,
                 i_system_id,
                 i_internal_subset,
+                i_attribute_decls,
+                i_element_decls,
             } => Extension::DocumentType {
                 i_entities: i_entities.clone(),
                 i_notations: i_notations.clone(),
                 i_public_id: i_public_id.clone(),
                 i_system_id: i_system_id.clone(),
                 i_internal_subset: i_internal_subset.clone(),
+                i_attribute_decls: i_attribute_decls.clone(),
+                i_element_decls: i_element_decls.clone(),
             },
             Extension::Element {
                 i_attributes,
@@ -348,7 +537,7 @@ impl NodeImpl {
             notation @ Extension::Notation { .. } => notation.clone(),
         };
         Self {
-            i_node_type: self.i_node_type.clone(),
+            i_node_type: self.i_node_type,
             i_name: self.i_name.clone(),
             i_value: self.i_value.clone(),
             i_parent_node: None,
@@ -362,6 +551,266 @@ impl NodeImpl {
                 vec![]
             },
             i_extension: extension,
+            // a clone of a read-only node is writable, although a notation never is.
+            i_read_only: self.i_node_type == NodeType::Notation,
+            // user data is never copied, although any handler is told of the clone.
+            i_user_data: Default::default(),
+            i_index_hint: Default::default(),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl AttributeMap {
+    pub(crate) fn get(&self, name: &Name) -> Option<&RefNode> {
+        self.entries
+            .iter()
+            .find(|(entry_name, _)| entry_name == name)
+            .map(|(_, node)| node)
+    }
+    pub(crate) fn insert(&mut self, name: Name, node: RefNode) -> Option<RefNode> {
+        match self
+            .entries
+            .iter_mut()
+            .find(|(entry_name, _)| entry_name == &name)
+        {
+            None => {
+                self.entries.push((name, node));
+                None
+            }
+            Some(entry) => Some(std::mem::replace(entry, (name, node)).1),
+        }
+    }
+    ///
+    /// Replace the entry for `old_node`, keeping its position, or add a new entry if `old_node`
+    /// is not present.
+    ///
+    pub(crate) fn replace(&mut self, old_node: &RefNode, name: Name, node: RefNode) {
+        match self
+            .entries
+            .iter()
+            .position(|(_, entry_node)| entry_node == old_node)
+        {
+            None => {
+                let _safe_to_ignore = self.insert(name, node);
+            }
+            Some(position) => {
+                self.entries[position] = (name, node);
+                //
+                // Drop any other entry that the new name now collides with.
+                //
+                let name = &self.entries[position].0;
+                let duplicate = self
+                    .entries
+                    .iter()
+                    .enumerate()
+                    .position(|(index, (entry_name, _))| index != position && entry_name == name);
+                if let Some(duplicate) = duplicate {
+                    let _safe_to_ignore = self.entries.remove(duplicate);
+                }
+            }
+        }
+    }
+    pub(crate) fn remove(&mut self, name: &Name) -> Option<RefNode> {
+        let position = self
+            .entries
+            .iter()
+            .position(|(entry_name, _)| entry_name == name)?;
+        Some(self.entries.remove(position).1)
+    }
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&Name, &RefNode)> {
+        self.entries.iter().map(|(name, node)| (name, node))
+    }
+    pub(crate) fn get_index(&self, index: usize) -> Option<(&Name, &RefNode)> {
+        self.entries.get(index).map(|(name, node)| (name, node))
+    }
+    pub(crate) fn keys(&self) -> impl Iterator<Item = &Name> {
+        self.entries.iter().map(|(name, _)| name)
+    }
+    pub(crate) fn values(&self) -> impl Iterator<Item = &RefNode> {
+        self.entries.iter().map(|(_, node)| node)
+    }
+    pub(crate) fn drain(&mut self) -> impl Iterator<Item = (Name, RefNode)> + '_ {
+        self.entries.drain(..)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl NamespaceMap {
+    pub(crate) fn get(&self, prefix: &Option<String>) -> Option<&String> {
+        self.entries
+            .iter()
+            .find(|(entry_prefix, _)| entry_prefix == prefix)
+            .map(|(_, namespace_uri)| namespace_uri)
+    }
+    pub(crate) fn contains_key(&self, prefix: &Option<String>) -> bool {
+        self.get(prefix).is_some()
+    }
+    pub(crate) fn insert(
+        &mut self,
+        prefix: Option<String>,
+        namespace_uri: String,
+    ) -> Option<String> {
+        match self
+            .entries
+            .iter_mut()
+            .find(|(entry_prefix, _)| entry_prefix == &prefix)
+        {
+            None => {
+                self.entries.push((prefix, namespace_uri));
+                None
+            }
+            Some(entry) => Some(std::mem::replace(&mut entry.1, namespace_uri)),
+        }
+    }
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&Option<String>, &String)> {
+        self.entries
+            .iter()
+            .map(|(prefix, namespace_uri)| (prefix, namespace_uri))
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Debug for UserDataMap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.entries.iter().map(|(key, _, _)| key))
+            .finish()
+    }
+}
+
+impl UserDataMap {
+    pub(crate) fn get(&self, key: &str) -> Option<&UserData> {
+        self.entries
+            .iter()
+            .find(|(entry_key, _, _)| entry_key == key)
+            .map(|(_, data, _)| data)
+    }
+    pub(crate) fn insert(
+        &mut self,
+        key: &str,
+        data: UserData,
+        handler: Option<UserDataHandlerRef<RefNode>>,
+    ) -> Option<UserData> {
+        match self
+            .entries
+            .iter_mut()
+            .find(|(entry_key, _, _)| entry_key == key)
+        {
+            None => {
+                self.entries.push((key.to_string(), data, handler));
+                None
+            }
+            Some(entry) => {
+                entry.2 = handler;
+                Some(std::mem::replace(&mut entry.1, data))
+            }
+        }
+    }
+    pub(crate) fn remove(&mut self, key: &str) -> Option<UserData> {
+        let position = self
+            .entries
+            .iter()
+            .position(|(entry_key, _, _)| entry_key == key)?;
+        Some(self.entries.remove(position).1)
+    }
+    pub(crate) fn handled(&self) -> Vec<(String, UserData, UserDataHandlerRef<RefNode>)> {
+        self.entries
+            .iter()
+            .filter_map(|(key, data, handler)| {
+                handler
+                    .as_ref()
+                    .map(|handler| (key.clone(), data.clone(), handler.clone()))
+            })
+            .collect()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl NamePool {
+    pub(crate) fn intern(&mut self, name: Name) -> Name {
+        match self.names.get(&name) {
+            Some(interned) => interned.clone(),
+            None => {
+                let _safe_to_ignore = self.names.insert(name.clone());
+                name
+            }
+        }
+    }
+    pub(crate) fn len(&self) -> usize {
+        self.names.len()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Clone for IndexHint {
+    fn clone(&self) -> Self {
+        Self(AtomicUsize::new(self.get()))
+    }
+}
+
+impl IndexHint {
+    pub(crate) fn get(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+    pub(crate) fn set(&self, index: usize) {
+        self.0.store(index, Ordering::Relaxed)
+    }
+    ///
+    /// The index of `node`, which holds this hint, within `child_nodes`. The hinted index is
+    /// checked first, then its neighbours, as a single insertion or removal before `node` moves it
+    /// by one. Only if `node` is not there are all the children searched, and then the hints of all
+    /// the children are reset, so that a run of insertions before `node` costs one search rather
+    /// than one for each sibling visited afterwards.
+    ///
+    pub(crate) fn position_in(&self, child_nodes: &[RefNode], node: &RefNode) -> Option<usize> {
+        let index = self.get();
+        let nearby = [Some(index), index.checked_add(1), index.checked_sub(1)];
+        if let Some(index) = nearby
+            .iter()
+            .flatten()
+            .find(|index| child_nodes.get(**index) == Some(node))
+        {
+            self.set(*index);
+            return Some(*index);
+        }
+        let mut found = None;
+        for (index, child_node) in child_nodes.iter().enumerate() {
+            if child_node == node {
+                self.set(index);
+                found = Some(index);
+            } else if let Some(ref_child) = child_node.as_inner().try_borrow() {
+                //
+                // A sibling that is mutably borrowed keeps its stale hint, which is only slower.
+                //
+                ref_child.i_index_hint.set(index);
+            }
+        }
+        found
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Drop for NodeImpl {
+    fn drop(&mut self) {
+        for (key, data, handler) in self.i_user_data.handled() {
+            handler.handle(UserDataOperation::NodeDeleted, &key, &data, None, None);
+        }
+        //
+        // Dropping a deeply nested tree recursively can overflow the stack, so the children of
+        // any child only referenced from here are moved onto a local stack before it is dropped.
+        //
+        let mut child_nodes = std::mem::take(&mut self.i_child_nodes);
+        while let Some(child_node) = child_nodes.pop() {
+            if Shared::strong_count(child_node.as_inner()) == 1 {
+                child_nodes.append(&mut child_node.borrow_mut().i_child_nodes);
+            }
         }
     }
 }
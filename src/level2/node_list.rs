@@ -0,0 +1,187 @@
+use crate::level2::node_impl::{RefNode, WeakRefNode};
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This corresponds to the DOM `NodeList` interface, an ordered collection of the children of a
+/// node, as returned by [`Node::child_nodes`](trait.Node.html#tymethod.child_nodes).
+///
+/// # Specification
+///
+/// The `NodeList` interface provides the abstraction of an ordered collection of nodes, without
+/// defining or constraining how this collection is implemented. `NodeList` objects in the DOM are
+/// live.
+///
+/// The items in the `NodeList` are accessible via an integral index, starting from 0.
+///
+/// # Implementation
+///
+/// A `NodeList` only holds a weak reference to the node whose children it lists, no children are
+/// copied when it is created. All methods, including iteration, reflect the children of that node
+/// at the time they are called; if the node itself has been dropped the list is empty.
+///
+/// Because the list is live, removing children while iterating over it will cause nodes to be
+/// skipped. In this case use [`to_vec`](#method.to_vec) to take a snapshot of the current
+/// children first.
+///
+/// ```rust
+/// use xml_dom::level2::*;
+/// use xml_dom::level2::convert::*;
+///
+/// let implementation = get_implementation();
+/// let document_node = implementation
+///     .create_document(None, Some("root"), None)
+///     .unwrap();
+/// let document = as_document(&document_node).unwrap();
+/// let mut root_node = document.document_element().unwrap();
+///
+/// let child_nodes = root_node.child_nodes();
+/// assert!(child_nodes.is_empty());
+///
+/// root_node.append_child(document.create_text_node("hello")).unwrap();
+/// assert_eq!(child_nodes.len(), 1);
+/// for child_node in &child_nodes {
+///     assert_eq!(child_node.node_type(), NodeType::Text);
+/// }
+/// ```
+///
+#[derive(Clone)]
+pub struct NodeList {
+    parent: WeakRefNode,
+}
+
+///
+/// An iterator over the nodes in a [`NodeList`](struct.NodeList.html), this is also live and will
+/// return the node currently at the next index of the list.
+///
+#[derive(Clone, Debug)]
+pub struct NodeListIter {
+    list: NodeList,
+    index: usize,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Debug for NodeList {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_list().entries(self.to_vec()).finish()
+    }
+}
+
+impl IntoIterator for NodeList {
+    type Item = RefNode;
+    type IntoIter = NodeListIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        NodeListIter {
+            list: self,
+            index: 0,
+        }
+    }
+}
+
+impl IntoIterator for &NodeList {
+    type Item = RefNode;
+    type IntoIter = NodeListIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl From<NodeList> for Vec<RefNode> {
+    fn from(list: NodeList) -> Self {
+        list.to_vec()
+    }
+}
+
+impl NodeList {
+    pub(crate) fn new(parent: &RefNode) -> Self {
+        Self {
+            parent: parent.clone().downgrade(),
+        }
+    }
+
+    ///
+    /// Returns the `index`th item in the collection. If `index` is greater than or equal to the
+    /// number of nodes in the list, this returns `None`.
+    ///
+    pub fn item(&self, index: usize) -> Option<RefNode> {
+        self.parent()
+            .and_then(|parent| parent.borrow().i_child_nodes.get(index).cloned())
+    }
+
+    ///
+    /// The number of nodes in the list. The range of valid child node indices is 0 to `len() - 1`
+    /// inclusive.
+    ///
+    pub fn len(&self) -> usize {
+        match self.parent() {
+            None => 0,
+            Some(parent) => parent.borrow().i_child_nodes.len(),
+        }
+    }
+
+    ///
+    /// Returns `true` if there are no nodes in the list, else `false`.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    ///
+    /// Returns the first node in the list, if any.
+    ///
+    pub fn first(&self) -> Option<RefNode> {
+        self.item(0)
+    }
+
+    ///
+    /// Returns the last node in the list, if any.
+    ///
+    pub fn last(&self) -> Option<RefNode> {
+        self.parent()
+            .and_then(|parent| parent.borrow().i_child_nodes.last().cloned())
+    }
+
+    ///
+    /// Returns an iterator over the nodes in the list.
+    ///
+    pub fn iter(&self) -> NodeListIter {
+        self.clone().into_iter()
+    }
+
+    ///
+    /// Returns a snapshot of the nodes currently in the list; unlike the list itself this will
+    /// not reflect subsequent changes.
+    ///
+    pub fn to_vec(&self) -> Vec<RefNode> {
+        match self.parent() {
+            None => Vec::default(),
+            Some(parent) => parent.borrow().i_child_nodes.clone(),
+        }
+    }
+
+    fn parent(&self) -> Option<RefNode> {
+        self.parent.clone().upgrade()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Iterator for NodeListIter {
+    type Item = RefNode;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.list.item(self.index);
+        if next.is_some() {
+            self.index += 1;
+        }
+        next
+    }
+}
@@ -0,0 +1,333 @@
+/*!
+Provides a plain, owned, value representation of a tree of nodes, without the shared and mutable
+references of `RefNode`, so that a snapshot of a document may be sent to another thread or stored.
+
+An `OwnedNode` only holds `String`s, `Name`s, and `Vec`s and so is both `Send` and `'static`. The
+conversion from a `RefNode` keeps the namespace URI of elements and attributes, the order of
+attributes, and the name, public and system identifiers, and internal subset of any document type.
+Entity references are kept by name only, their replacement text is not copied, and the
+declarations in the internal subset are re-created from its text.
+
+With the `serde` feature `OwnedNode`, and `RefNode` by way of it, implement `Serialize` and
+`Deserialize`; only a document may be deserialized into a `RefNode`.
+
+# Example
+
+```rust
+# #[cfg(feature = "quick_parser")]
+# fn main() {
+use xml_dom::level2::*;
+use xml_dom::level2::owned::*;
+use xml_dom::parser::read_xml;
+
+let document_node = read_xml(r#"<list><item id="1">one</item></list>"#).unwrap();
+let tree = document_node.to_owned_tree().unwrap();
+
+let handle = std::thread::spawn(move || {
+    let document_node = from_owned_tree(&tree).unwrap();
+    document_node.to_string()
+});
+assert_eq!(handle.join().unwrap(), r#"<list><item id="1">one</item></list>"#);
+# }
+# #[cfg(not(feature = "quick_parser"))]
+# fn main() {}
+```
+*/
+
+use crate::level2::convert::is_document;
+use crate::level2::ext::{DocumentDecl, DocumentTypeDecl, XmlDecl};
+use crate::level2::get_implementation;
+use crate::level2::node_impl::RefNode;
+use crate::level2::trait_impls::normalized_value;
+use crate::level2::traits::{Attribute, Document, DocumentType, Element, Node, NodeType};
+use crate::shared::error::{Error, Result, MSG_INVALID_NODE_TYPE};
+use crate::shared::name::Name;
+#[cfg(feature = "serde")]
+use std::result::Result as StdResult;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An owned copy of a node, and of all its descendants.
+///
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OwnedNode {
+    /// A `Document` with its XML declaration, if any, and its children in order; this includes
+    /// any document type.
+    Document {
+        /// The XML declaration of the document.
+        declaration: Option<XmlDecl>,
+        /// The children of the document.
+        children: Vec<OwnedNode>,
+    },
+    /// A `DocumentType`.
+    DocumentType {
+        /// The name of the document type, this is also the name of the document element.
+        name: String,
+        /// The public identifier of the external subset.
+        public_id: Option<String>,
+        /// The system identifier of the external subset.
+        system_id: Option<String>,
+        /// The text of the internal subset.
+        internal_subset: Option<String>,
+    },
+    /// A `DocumentFragment` with its children in order.
+    DocumentFragment {
+        /// The children of the fragment.
+        children: Vec<OwnedNode>,
+    },
+    /// An `Element` with its attributes and children in order.
+    Element {
+        /// The name of the element, including any namespace URI.
+        name: Name,
+        /// The name, including any namespace URI, and the value, without escapes, of each
+        /// attribute.
+        attributes: Vec<(Name, String)>,
+        /// The children of the element.
+        children: Vec<OwnedNode>,
+    },
+    /// A `Text` node's data.
+    Text(String),
+    /// A `CDataSection` node's data.
+    CData(String),
+    /// A `Comment` node's data.
+    Comment(String),
+    /// A `ProcessingInstruction`.
+    ProcessingInstruction {
+        /// The target of the processing instruction.
+        target: String,
+        /// The data of the processing instruction.
+        data: Option<String>,
+    },
+    /// An `EntityReference` by the name of the entity.
+    EntityReference(String),
+}
+
+///
+/// Extends `Node` with the conversion to, and from, an owned tree.
+///
+pub trait OwnedTree: Node {
+    ///
+    /// Returns an owned copy of this node and all its descendants.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `NOT_SUPPORTED_ERR`: Raised if this node is an `Attribute`, `Entity`, or `Notation`,
+    ///   which do not appear in the tree.
+    ///
+    fn to_owned_tree(&self) -> Result<OwnedNode>;
+    ///
+    /// Creates a node, owned by this document, and all its descendants from `tree`. The new node
+    /// has no parent, as with `Document::import_node`.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `NOT_SUPPORTED_ERR`: Raised if `tree` is a `Document`, use
+    ///   [`from_owned_tree`](fn.from_owned_tree.html) instead.
+    /// * `INVALID_STATE_ERR`: Raised if this node is not a `Document`.
+    ///
+    /// Any error creating a node, such as `INVALID_CHARACTER_ERR` for an invalid name, is also
+    /// returned.
+    ///
+    fn import_owned_tree(&self, tree: &OwnedNode) -> Result<Self::NodeRef>;
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Create a new document from `tree`, which must be an `OwnedNode::Document`; otherwise the error
+/// `NOT_SUPPORTED_ERR` is returned. The result can be safely assumed to be a `Document` node.
+///
+pub fn from_owned_tree(tree: &OwnedNode) -> Result<RefNode> {
+    if let OwnedNode::Document {
+        declaration,
+        children,
+    } = tree
+    {
+        let mut document_node = get_implementation().create_document(None, None, None)?;
+        if let Some(declaration) = declaration {
+            document_node.set_xml_declaration(declaration.clone())?;
+        }
+        for child in children {
+            let child_node = create_node(&document_node, child)?;
+            let _safe_to_ignore = document_node.append_child(child_node)?;
+        }
+        Ok(document_node)
+    } else {
+        warn!("{}", MSG_INVALID_NODE_TYPE);
+        Err(Error::NotSupported)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl OwnedTree for RefNode {
+    fn to_owned_tree(&self) -> Result<OwnedNode> {
+        let children = || -> Result<Vec<OwnedNode>> {
+            self.child_nodes()
+                .into_iter()
+                .map(|child_node| child_node.to_owned_tree())
+                .collect()
+        };
+        Ok(match self.node_type() {
+            NodeType::Document => OwnedNode::Document {
+                declaration: self.xml_declaration(),
+                children: children()?,
+            },
+            NodeType::DocumentType => OwnedNode::DocumentType {
+                name: self.node_name().to_string(),
+                public_id: DocumentType::public_id(self),
+                system_id: DocumentType::system_id(self),
+                internal_subset: self.internal_subset(),
+            },
+            NodeType::DocumentFragment => OwnedNode::DocumentFragment {
+                children: children()?,
+            },
+            NodeType::Element => OwnedNode::Element {
+                name: self.node_name(),
+                attributes: self
+                    .borrow()
+                    .attribute_nodes()
+                    .iter()
+                    .map(|attribute_node| {
+                        (
+                            attribute_node.node_name(),
+                            normalized_value(attribute_node).unwrap_or_default(),
+                        )
+                    })
+                    .collect(),
+                children: children()?,
+            },
+            NodeType::Text => OwnedNode::Text(self.node_value().unwrap_or_default()),
+            NodeType::CData => OwnedNode::CData(self.node_value().unwrap_or_default()),
+            NodeType::Comment => OwnedNode::Comment(self.node_value().unwrap_or_default()),
+            NodeType::ProcessingInstruction => OwnedNode::ProcessingInstruction {
+                target: self.node_name().to_string(),
+                data: self.node_value(),
+            },
+            NodeType::EntityReference => OwnedNode::EntityReference(self.node_name().to_string()),
+            NodeType::Attribute | NodeType::Entity | NodeType::Notation => {
+                warn!("{}", MSG_INVALID_NODE_TYPE);
+                return Err(Error::NotSupported);
+            }
+        })
+    }
+
+    fn import_owned_tree(&self, tree: &OwnedNode) -> Result<Self::NodeRef> {
+        if !is_document(self) {
+            warn!("{}", MSG_INVALID_NODE_TYPE);
+            Err(Error::InvalidState)
+        } else {
+            create_node(self, tree)
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// With the `serde` feature a node is serialized as its [`OwnedNode`](enum.OwnedNode.html) tree,
+/// tagged with the node type, so the attribute order and namespace URIs are kept.
+///
+#[cfg(feature = "serde")]
+impl serde::Serialize for RefNode {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> StdResult<S::Ok, S::Error> {
+        self.to_owned_tree()
+            .map_err(serde::ser::Error::custom)?
+            .serialize(serializer)
+    }
+}
+
+///
+/// With the `serde` feature a new document is deserialized from an
+/// [`OwnedNode::Document`](enum.OwnedNode.html) tree, using
+/// [`from_owned_tree`](fn.from_owned_tree.html); any other node fails with the message for
+/// `NOT_SUPPORTED_ERR`, as it needs an existing document to own it.
+///
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for RefNode {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> StdResult<Self, D::Error> {
+        from_owned_tree(&OwnedNode::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn create_node(document: &RefNode, tree: &OwnedNode) -> Result<RefNode> {
+    let append_children = |mut parent_node: RefNode, children: &[OwnedNode]| -> Result<RefNode> {
+        for child in children {
+            let child_node = create_node(document, child)?;
+            let _safe_to_ignore = parent_node.append_child(child_node)?;
+        }
+        Ok(parent_node)
+    };
+    match tree {
+        OwnedNode::Document { .. } => {
+            warn!("{}", MSG_INVALID_NODE_TYPE);
+            Err(Error::NotSupported)
+        }
+        OwnedNode::DocumentType {
+            name,
+            public_id,
+            system_id,
+            internal_subset,
+        } => {
+            let mut document_type = get_implementation().create_document_type(
+                name,
+                public_id.as_deref(),
+                system_id.as_deref(),
+            )?;
+            if let Some(internal_subset) = internal_subset {
+                document_type.set_internal_subset(internal_subset)?;
+            }
+            Ok(document_type)
+        }
+        OwnedNode::DocumentFragment { children } => {
+            append_children(document.create_document_fragment()?, children)
+        }
+        OwnedNode::Element {
+            name,
+            attributes,
+            children,
+        } => {
+            let mut element_node = match name.namespace_uri() {
+                None => document.create_element(&name.to_string())?,
+                Some(namespace_uri) => {
+                    document.create_element_ns(namespace_uri, &name.to_string())?
+                }
+            };
+            for (name, value) in attributes {
+                let _safe_to_ignore = match name.namespace_uri() {
+                    None => {
+                        let attribute_node =
+                            document.create_attribute_with(&name.to_string(), value)?;
+                        element_node.set_attribute_node(attribute_node)?
+                    }
+                    Some(namespace_uri) => {
+                        let mut attribute_node =
+                            document.create_attribute_ns(namespace_uri, &name.to_string())?;
+                        attribute_node.set_value(value)?;
+                        element_node.set_attribute_node_ns(attribute_node)?
+                    }
+                };
+            }
+            append_children(element_node, children)
+        }
+        OwnedNode::Text(data) => Ok(document.create_text_node(data)),
+        OwnedNode::CData(data) => document.create_cdata_section(data),
+        OwnedNode::Comment(data) => document.create_comment(data),
+        OwnedNode::ProcessingInstruction { target, data } => {
+            document.create_processing_instruction(target, data.as_deref())
+        }
+        OwnedNode::EntityReference(name) => document.create_entity_reference(name),
+    }
+}
@@ -0,0 +1,1197 @@
+/*!
+Implementation for DOM Level 2 Range.
+
+# Interface Mapping
+
+| IDL Interface    | Rust Mapping                                                                  |
+|------------------|-------------------------------------------------------------------------------|
+| `DocumentRange`  | [`DocumentRange`](trait.DocumentRange.html)                                   |
+| `Range`          | [`Range`](struct.Range.html), and [`CompareHow`](enum.CompareHow.html)        |
+| `RangeException` | [`Error::InvalidNodeType`](../enum.Error.html#variant.InvalidNodeType)        |
+
+# Specification
+
+From [Document Object Model Range](https://www.w3.org/TR/DOM-Level-2-Traversal-Range/ranges.html):
+
+A Range identifies a range of content in a Document, DocumentFragment or Attr. It is contiguous in
+the sense that it can be characterized as selecting all of the content between a pair of
+boundary-points.
+
+A DOM application may use the `hasFeature(feature, version)` method of the `DOMImplementation`
+interface with parameter values "Range" and "2.0" (respectively) to determine whether or not this
+module is supported by the implementation.
+
+# Implementation
+
+A boundary-point is a container node and an offset within it; where the container is a `Text`,
+`CDATASection`, `Comment`, or `ProcessingInstruction` node the offset is a count of characters in
+its data, otherwise it is a count of the container's children.
+
+A `Range` registers itself with the document that created it, and its boundary-points are moved
+as described in [§2.12 Range modification under document mutation](https://www.w3.org/TR/DOM-Level-2-Traversal-Range/ranges.html#Level-2-Range-Mutation)
+when nodes are removed from the tree. Insertions, and changes to character data, made through the
+`Range` itself also leave it in the position the specification requires; however such changes made
+directly through the `Node` and `CharacterData` traits are not tracked.
+
+# Example
+
+```rust
+# #[cfg(feature = "quick_parser")]
+# fn main() {
+use xml_dom::level2::*;
+use xml_dom::level2::ext::*;
+use xml_dom::level2::range::*;
+use xml_dom::parser::read_xml;
+
+let document_node = read_xml(r#"<a><b>one two</b><c>three</c></a>"#).unwrap();
+let first_text = document_node.descendants().nth(2).unwrap();
+let last_text = document_node.descendants().last().unwrap();
+
+let mut range = document_node.create_range();
+range.set_start(first_text, 4).unwrap();
+range.set_end(last_text, 3).unwrap();
+assert_eq!(range.to_string(), "twothr");
+
+let fragment = range.extract_contents().unwrap();
+assert_eq!(fragment.first_child().unwrap().to_string(), "<b>two</b>");
+assert_eq!(fragment.last_child().unwrap().to_string(), "<c>thr</c>");
+assert_eq!(document_node.to_string(), "<a><b>one </b><c>ee</c></a>");
+assert!(range.collapsed());
+# }
+# #[cfg(not(feature = "quick_parser"))]
+# fn main() {}
+```
+*/
+
+use crate::level2::convert::{as_document, is_cdata_section, is_text};
+use crate::level2::ext::traits::TreeIterators;
+use crate::level2::node_impl::{RefNode, RemovalObserver, SharedRemovalObserver};
+use crate::level2::trait_impls::{add_removal_observer, check_insert};
+use crate::level2::traits::{Document, Node, NodeType};
+use crate::level2::traversal::is_inclusive_ancestor;
+use crate::shared::error::{Error, Result};
+use crate::shared::rc_cell::{Shared, SharedCell};
+use std::cmp::Ordering;
+use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This corresponds to the `how` constants of the DOM `Range.compareBoundaryPoints` method, and
+/// selects which boundary-point of each range is compared.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u16)]
+pub enum CompareHow {
+    /// Compare the start boundary-point of `source_range` to the start boundary-point of this
+    /// range.
+    StartToStart = 0,
+    /// Compare the start boundary-point of `source_range` to the end boundary-point of this range.
+    StartToEnd,
+    /// Compare the end boundary-point of `source_range` to the end boundary-point of this range.
+    EndToEnd,
+    /// Compare the end boundary-point of `source_range` to the start boundary-point of this range.
+    EndToStart,
+}
+
+///
+/// This corresponds to the DOM `DocumentRange` interface.
+///
+pub trait DocumentRange: Document {
+    ///
+    /// This interface can be used to create a `Range`.
+    ///
+    /// # Specification
+    ///
+    /// **Return Value**
+    ///
+    /// * `Range`: The initial state of the Range returned from this method is such that both of
+    ///   its boundary-points are positioned at the beginning of the corresponding `Document`,
+    ///   before any content. The Range returned can only be used to select content associated with
+    ///   this `Document`, or with `DocumentFragments` and `Attrs` for which this `Document` is the
+    ///   `ownerDocument`.
+    ///
+    fn create_range(&self) -> Range;
+}
+
+///
+/// This corresponds to the DOM `Range` interface, created by
+/// [`DocumentRange::create_range`](trait.DocumentRange.html#tymethod.create_range).
+///
+/// The `Display` implementation corresponds to the DOM `toString` method, it returns the contents
+/// of the range as a string; only the data of `Text` and `CDATASection` nodes is included, and
+/// no markup.
+///
+/// Note that cloning a `Range` value returns another handle to the same range, use
+/// [`clone_range`](#method.clone_range) to create an independent copy.
+///
+#[derive(Clone)]
+pub struct Range {
+    state: Shared<SharedCell<RangeState>>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, PartialEq)]
+struct BoundaryPoint {
+    node: RefNode,
+    offset: usize,
+}
+
+struct RangeState {
+    document: RefNode,
+    start: BoundaryPoint,
+    end: BoundaryPoint,
+    detached: bool,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl DocumentRange for RefNode {
+    fn create_range(&self) -> Range {
+        Range::new(self.clone())
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Debug for Range {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let state = self.state.borrow();
+        f.debug_struct("Range")
+            .field("start_container", &state.start.node.node_name())
+            .field("start_offset", &state.start.offset)
+            .field("end_container", &state.end.node.node_name())
+            .field("end_offset", &state.end.offset)
+            .field("detached", &state.detached)
+            .finish()
+    }
+}
+
+impl Display for Range {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let (start, end) = {
+            let state = self.state.borrow();
+            if state.detached {
+                return Ok(());
+            }
+            (state.start.clone(), state.end.clone())
+        };
+        if start.node == end.node && is_text_or_cdata(&start.node) {
+            return write!(f, "{}", substring(&start.node, start.offset, end.offset));
+        }
+        if is_text_or_cdata(&start.node) {
+            let length = node_length(&start.node);
+            write!(f, "{}", substring(&start.node, start.offset, length))?;
+        }
+        let common_ancestor = common_ancestor(&start.node, &end.node);
+        for node in common_ancestor.descendants() {
+            if is_text_or_cdata(&node) && is_contained(&node, &start, &end) {
+                write!(f, "{}", node.node_value().unwrap_or_default())?;
+            }
+        }
+        if is_text_or_cdata(&end.node) {
+            write!(f, "{}", substring(&end.node, 0, end.offset))?;
+        }
+        Ok(())
+    }
+}
+
+impl Range {
+    fn new(document: RefNode) -> Self {
+        let start = BoundaryPoint::new(document.clone(), 0);
+        Self::with_boundary_points(document, start.clone(), start)
+    }
+
+    fn with_boundary_points(document: RefNode, start: BoundaryPoint, end: BoundaryPoint) -> Self {
+        let state = Shared::new(SharedCell::new(RangeState {
+            document: document.clone(),
+            start,
+            end,
+            detached: false,
+        }));
+        let observer: SharedRemovalObserver = state.clone();
+        add_removal_observer(&document, Shared::downgrade(&observer));
+        Self { state }
+    }
+
+    ///
+    /// Node within which the Range begins.
+    ///
+    pub fn start_container(&self) -> RefNode {
+        self.state.borrow().start.node.clone()
+    }
+
+    ///
+    /// Offset within the starting node of the Range.
+    ///
+    pub fn start_offset(&self) -> usize {
+        self.state.borrow().start.offset
+    }
+
+    ///
+    /// Node within which the Range ends.
+    ///
+    pub fn end_container(&self) -> RefNode {
+        self.state.borrow().end.node.clone()
+    }
+
+    ///
+    /// Offset within the ending node of the Range.
+    ///
+    pub fn end_offset(&self) -> usize {
+        self.state.borrow().end.offset
+    }
+
+    ///
+    /// `true` if the Range is collapsed, i.e. its start and end boundary-points are the same.
+    ///
+    pub fn collapsed(&self) -> bool {
+        let state = self.state.borrow();
+        state.start == state.end
+    }
+
+    ///
+    /// The deepest common ancestor container of the Range's two boundary-points.
+    ///
+    pub fn common_ancestor_container(&self) -> RefNode {
+        let state = self.state.borrow();
+        common_ancestor(&state.start.node, &state.end.node)
+    }
+
+    ///
+    /// Sets the attributes describing the start of the Range.
+    ///
+    /// # Specification
+    ///
+    /// **Parameters**
+    ///
+    /// * `refNode` of type `Node`: The `refNode` value. This parameter must be different from
+    ///   `null`.
+    /// * `offset` of type `long`: The `startOffset` value.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `RangeException INVALID_NODE_TYPE_ERR`: Raised if `refNode` or an ancestor of `refNode`
+    ///   is an `Entity`, `Notation`, or `DocumentType` node.
+    /// * `INDEX_SIZE_ERR`: Raised if `offset` is negative or greater than the number of child
+    ///   units in `refNode`. Child units are 16-bit units if `refNode` is a type of
+    ///   `CharacterData` node (e.g., a `Text` or `Comment` node) or a `ProcessingInstruction`
+    ///   node. Child units are `Nodes` in all other cases.
+    /// * `INVALID_STATE_ERR`: Raised if `detach()` has already been invoked on this object.
+    /// * `WRONG_DOCUMENT_ERR`: Raised if `refNode` was created from a different document than the
+    ///   one that created this range.
+    ///
+    /// If the start is set to be after the end, the Range is collapsed to that position.
+    ///
+    pub fn set_start(&mut self, ref_node: RefNode, offset: usize) -> Result<()> {
+        self.set_boundary_point(BoundaryPoint::new(ref_node, offset), true)
+    }
+
+    ///
+    /// Sets the attributes describing the end of a Range; see [`set_start`](#method.set_start).
+    ///
+    /// If the end is set to be before the start, the Range is collapsed to that position.
+    ///
+    pub fn set_end(&mut self, ref_node: RefNode, offset: usize) -> Result<()> {
+        self.set_boundary_point(BoundaryPoint::new(ref_node, offset), false)
+    }
+
+    ///
+    /// Sets the start position to be before a node.
+    ///
+    /// # Specification
+    ///
+    /// **Parameters**
+    ///
+    /// * `refNode` of type `Node`: Range starts before `refNode`.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `INVALID_STATE_ERR`: Raised if `detach()` has already been invoked on this object.
+    /// * `RangeException INVALID_NODE_TYPE_ERR`: Raised if the root container of `refNode` is not
+    ///   an `Attr`, `Document`, or `DocumentFragment` node or if `refNode` is a `Document`,
+    ///   `DocumentFragment`, `Attr`, `Entity`, or `Notation` node.
+    /// * `WRONG_DOCUMENT_ERR`: Raised if `refNode` was created from a different document than the
+    ///   one that created this range.
+    ///
+    pub fn set_start_before(&mut self, ref_node: RefNode) -> Result<()> {
+        let point = BoundaryPoint::before(&ref_node)?;
+        self.set_boundary_point(point, true)
+    }
+
+    ///
+    /// Sets the start position to be after a node; see
+    /// [`set_start_before`](#method.set_start_before).
+    ///
+    pub fn set_start_after(&mut self, ref_node: RefNode) -> Result<()> {
+        let point = BoundaryPoint::after(&ref_node)?;
+        self.set_boundary_point(point, true)
+    }
+
+    ///
+    /// Sets the end position to be before a node; see
+    /// [`set_start_before`](#method.set_start_before).
+    ///
+    pub fn set_end_before(&mut self, ref_node: RefNode) -> Result<()> {
+        let point = BoundaryPoint::before(&ref_node)?;
+        self.set_boundary_point(point, false)
+    }
+
+    ///
+    /// Sets the end of a Range to be after a node; see
+    /// [`set_start_before`](#method.set_start_before).
+    ///
+    pub fn set_end_after(&mut self, ref_node: RefNode) -> Result<()> {
+        let point = BoundaryPoint::after(&ref_node)?;
+        self.set_boundary_point(point, false)
+    }
+
+    ///
+    /// Collapse a Range onto one of its boundary-points.
+    ///
+    /// # Specification
+    ///
+    /// **Parameters**
+    ///
+    /// * `toStart` of type `boolean`: If `true`, collapses the Range onto its start; if `false`,
+    ///   collapses it onto its end.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `INVALID_STATE_ERR`: Raised if `detach()` has already been invoked on this object.
+    ///
+    pub fn collapse(&mut self, to_start: bool) -> Result<()> {
+        let mut state = self.state.borrow_mut();
+        state.check_attached()?;
+        if to_start {
+            state.end = state.start.clone();
+        } else {
+            state.start = state.end.clone();
+        }
+        Ok(())
+    }
+
+    ///
+    /// Select a node and its contents.
+    ///
+    /// # Specification
+    ///
+    /// **Parameters**
+    ///
+    /// * `refNode` of type `Node`: The node to select.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `RangeException INVALID_NODE_TYPE_ERR`: Raised if an ancestor of `refNode` is an
+    ///   `Entity`, `Notation` or `DocumentType` node or if `refNode` is a `Document`,
+    ///   `DocumentFragment`, `Attr`, `Entity`, or `Notation` node.
+    /// * `INVALID_STATE_ERR`: Raised if `detach()` has already been invoked on this object.
+    /// * `WRONG_DOCUMENT_ERR`: Raised if `refNode` was created from a different document than the
+    ///   one that created this range.
+    ///
+    pub fn select_node(&mut self, ref_node: RefNode) -> Result<()> {
+        let start = BoundaryPoint::before(&ref_node)?;
+        let end = BoundaryPoint::after(&ref_node)?;
+        self.set_boundary_points(start, end)
+    }
+
+    ///
+    /// Select the contents within a node.
+    ///
+    /// # Specification
+    ///
+    /// **Parameters**
+    ///
+    /// * `refNode` of type `Node`: `Node` to select from.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `RangeException INVALID_NODE_TYPE_ERR`: Raised if `refNode` or an ancestor of `refNode`
+    ///   is an `Entity`, `Notation` or `DocumentType` node.
+    /// * `INVALID_STATE_ERR`: Raised if `detach()` has already been invoked on this object.
+    /// * `WRONG_DOCUMENT_ERR`: Raised if `refNode` was created from a different document than the
+    ///   one that created this range.
+    ///
+    pub fn select_node_contents(&mut self, ref_node: RefNode) -> Result<()> {
+        let length = node_length(&ref_node);
+        let start = BoundaryPoint::new(ref_node.clone(), 0);
+        let end = BoundaryPoint::new(ref_node, length);
+        self.set_boundary_points(start, end)
+    }
+
+    ///
+    /// Compare the boundary-points of two Ranges in a document.
+    ///
+    /// # Specification
+    ///
+    /// **Parameters**
+    ///
+    /// * `how` of type `unsigned short`: A code representing the type of comparison, as defined
+    ///   above.
+    /// * `sourceRange` of type `Range`: The `Range` on which this current `Range` is compared to.
+    ///
+    /// **Return Value**
+    ///
+    /// * `short`: -1, 0 or 1 depending on whether the corresponding boundary-point of the Range is
+    ///   respectively before, equal to, or after the corresponding boundary-point of
+    ///   `sourceRange`.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `WRONG_DOCUMENT_ERR`: Raised if the two Ranges are not in the same `Document` or
+    ///   `DocumentFragment`.
+    /// * `INVALID_STATE_ERR`: Raised if `detach()` has already been invoked on this object.
+    ///
+    pub fn compare_boundary_points(
+        &self,
+        how: CompareHow,
+        source_range: &Range,
+    ) -> Result<Ordering> {
+        let (this_start, this_end) = self.boundary_points()?;
+        let (source_start, source_end) = source_range.boundary_points()?;
+        let (this_point, source_point) = match how {
+            CompareHow::StartToStart => (this_start, source_start),
+            CompareHow::StartToEnd => (this_end, source_start),
+            CompareHow::EndToEnd => (this_end, source_end),
+            CompareHow::EndToStart => (this_start, source_end),
+        };
+        match compare_points(&this_point, &source_point) {
+            None => {
+                warn!("Range::compare_boundary_points: ranges are in different trees");
+                Err(Error::WrongDocument)
+            }
+            Some(ordering) => Ok(ordering),
+        }
+    }
+
+    ///
+    /// Removes the contents of a Range from the containing document or document fragment without
+    /// returning a reference to the removed content.
+    ///
+    /// # Specification
+    ///
+    /// **Exceptions**
+    ///
+    /// * `NO_MODIFICATION_ALLOWED_ERR`: Raised if any portion of the content of the Range is
+    ///   read-only or any of the nodes that contain any of the content of the Range are
+    ///   read-only.
+    /// * `INVALID_STATE_ERR`: Raised if `detach()` has already been invoked on this object.
+    ///
+    pub fn delete_contents(&mut self) -> Result<()> {
+        let (start, end) = self.boundary_points()?;
+        if start == end {
+            return Ok(());
+        }
+        if start.node == end.node && is_data_node(&start.node) {
+            delete_data(&start.node, start.offset, end.offset)?;
+            return self.set_boundary_points(start.clone(), start);
+        }
+        let common_ancestor = common_ancestor(&start.node, &end.node);
+        let nodes_to_remove: Vec<RefNode> = common_ancestor
+            .descendants()
+            .filter(|node| {
+                is_contained(node, &start, &end)
+                    && !node
+                        .parent_node()
+                        .is_some_and(|parent| is_contained(&parent, &start, &end))
+            })
+            .collect();
+        let new_point = collapse_point(&start, &end);
+        if is_data_node(&start.node) {
+            delete_data(&start.node, start.offset, node_length(&start.node))?;
+        }
+        for node in nodes_to_remove {
+            if let Some(mut parent) = node.parent_node() {
+                let _safe_to_ignore = parent.remove_child(node)?;
+            }
+        }
+        if is_data_node(&end.node) {
+            delete_data(&end.node, 0, end.offset)?;
+        }
+        self.set_boundary_points(new_point.clone(), new_point)
+    }
+
+    ///
+    /// Moves the contents of a Range from the containing document or document fragment to a new
+    /// `DocumentFragment`.
+    ///
+    /// # Specification
+    ///
+    /// **Return Value**
+    ///
+    /// * `DocumentFragment`: A `DocumentFragment` containing the extracted contents.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `NO_MODIFICATION_ALLOWED_ERR`: Raised if any portion of the content of the Range is
+    ///   read-only or any of the nodes which contain any of the content of the Range are
+    ///   read-only.
+    /// * `HIERARCHY_REQUEST_ERR`: Raised if a `DocumentType` node would be extracted into the new
+    ///   `DocumentFragment`.
+    /// * `INVALID_STATE_ERR`: Raised if `detach()` has already been invoked on this object.
+    ///
+    pub fn extract_contents(&mut self) -> Result<RefNode> {
+        let (start, end) = self.boundary_points()?;
+        let document = self.state.borrow().document.clone();
+        let new_point = collapse_point(&start, &end);
+        let fragment = extract(&document, &start, &end)?;
+        if start != end {
+            self.set_boundary_points(new_point.clone(), new_point)?;
+        }
+        Ok(fragment)
+    }
+
+    ///
+    /// Duplicates the contents of a Range.
+    ///
+    /// # Specification
+    ///
+    /// **Return Value**
+    ///
+    /// * `DocumentFragment`: A `DocumentFragment` that contains content equivalent to this Range.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `HIERARCHY_REQUEST_ERR`: Raised if a `DocumentType` node would be extracted into the new
+    ///   `DocumentFragment`.
+    /// * `INVALID_STATE_ERR`: Raised if `detach()` has already been invoked on this object.
+    ///
+    pub fn clone_contents(&self) -> Result<RefNode> {
+        let (start, end) = self.boundary_points()?;
+        let document = self.state.borrow().document.clone();
+        clone_contents(&document, &start, &end)
+    }
+
+    ///
+    /// Inserts a node into the `Document` or `DocumentFragment` at the start of the Range. If the
+    /// container is a `Text` node, this will be split at the start of the Range (as if the `Text`
+    /// node's `splitText` method was performed at the insertion point) and the insertion will
+    /// occur between the two resulting `Text` nodes. Adjacent `Text` nodes will not be
+    /// automatically merged. If the node to be inserted is a `DocumentFragment` node, the
+    /// children will be inserted rather than the `DocumentFragment` node itself.
+    ///
+    /// # Specification
+    ///
+    /// **Parameters**
+    ///
+    /// * `newNode` of type `Node`: The node to insert at the start of the Range.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `NO_MODIFICATION_ALLOWED_ERR`: Raised if an ancestor container of the start of the Range
+    ///   is read-only.
+    /// * `WRONG_DOCUMENT_ERR`: Raised if `newNode` and the container of the start of the Range
+    ///   were not created from the same document.
+    /// * `HIERARCHY_REQUEST_ERR`: Raised if the container of the start of the Range is of a type
+    ///   that does not allow children of the type of `newNode` or if `newNode` is an ancestor of
+    ///   the container.
+    /// * `INVALID_STATE_ERR`: Raised if `detach()` has already been invoked on this object.
+    /// * `RangeException INVALID_NODE_TYPE_ERR`: Raised if `newNode` is an `Attr`, `Entity`,
+    ///   `Notation`, or `Document` node.
+    ///
+    pub fn insert_node(&mut self, new_node: RefNode) -> Result<()> {
+        if matches!(
+            new_node.node_type(),
+            NodeType::Attribute | NodeType::Entity | NodeType::Notation | NodeType::Document
+        ) {
+            warn!("Range::insert_node: cannot insert a node of this type");
+            return Err(Error::InvalidNodeType);
+        }
+        let (start, _) = self.boundary_points()?;
+        let start_is_text = is_text_or_cdata(&start.node);
+        if matches!(
+            start.node.node_type(),
+            NodeType::ProcessingInstruction | NodeType::Comment
+        ) || (start_is_text && start.node.parent_node().is_none())
+            || is_inclusive_ancestor(&new_node, &start.node)
+        {
+            warn!("Range::insert_node: cannot insert a node at the start of this range");
+            return Err(Error::HierarchyRequest);
+        }
+
+        //
+        // Check the insertion before splitting any text node, so that a failure changes nothing;
+        // the text after the split is inserted immediately after the start node.
+        //
+        let (mut parent, reference) = if start_is_text {
+            (start.node.parent_node().unwrap(), start.node.next_sibling())
+        } else {
+            (
+                start.node.clone(),
+                start.node.child_nodes().item(start.offset),
+            )
+        };
+        check_insert(&parent, &new_node, reference.as_ref())?;
+
+        let mut reference = if start_is_text {
+            let new_text = split_text(&start.node, start.offset)?;
+            //
+            // Splitting the text node moves any part of this range that was after the split.
+            //
+            let text_index = index_of(&start.node);
+            let mut state = self.state.borrow_mut();
+            if state.end.node == start.node && state.end.offset > start.offset {
+                state.end = BoundaryPoint::new(new_text.clone(), state.end.offset - start.offset);
+            } else if state.end.node == parent && state.end.offset > text_index {
+                state.end.offset += 1;
+            }
+            Some(new_text)
+        } else {
+            reference
+        };
+        if reference.as_ref() == Some(&new_node) {
+            reference = new_node.next_sibling();
+        }
+
+        let count = if new_node.node_type() == NodeType::DocumentFragment {
+            new_node.child_nodes().len()
+        } else {
+            1
+        };
+        //
+        // `insert_before` removes `new_node` from any parent, which may be `parent` itself, so the
+        // index of the inserted nodes is found once they are in place.
+        //
+        let _safe_to_ignore = parent.insert_before(new_node, reference.clone())?;
+        let index = match &reference {
+            None => parent.child_nodes().len(),
+            Some(reference) => index_of(reference),
+        } - count;
+
+        let mut state = self.state.borrow_mut();
+        if state.end.node == parent && state.end.offset > index {
+            state.end.offset += count;
+        }
+        if state.start == state.end {
+            state.end = BoundaryPoint::new(parent, index + count);
+        }
+        Ok(())
+    }
+
+    ///
+    /// Reparents the contents of the Range to the given node and inserts the node at the position
+    /// of the start of the Range.
+    ///
+    /// # Specification
+    ///
+    /// **Parameters**
+    ///
+    /// * `newParent` of type `Node`: The node to surround the contents with.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `NO_MODIFICATION_ALLOWED_ERR`: Raised if an ancestor container of either boundary-point
+    ///   of the Range is read-only.
+    /// * `WRONG_DOCUMENT_ERR`: Raised if `newParent` and the container of the start of the Range
+    ///   were not created from the same document.
+    /// * `HIERARCHY_REQUEST_ERR`: Raised if the container of the start of the Range is of a type
+    ///   that does not allow children of the type of `newParent` or if `newParent` is an ancestor
+    ///   of the container or if `node` would end up with a child node of a type not allowed by
+    ///   the type of `node`.
+    /// * `INVALID_STATE_ERR`: Raised if `detach()` has already been invoked on this object.
+    /// * `RangeException BAD_BOUNDARYPOINTS_ERR`: Raised if the Range partially selects a
+    ///   non-text node; this is reported as `Error::InvalidState`.
+    /// * `RangeException INVALID_NODE_TYPE_ERR`: Raised if `node` is an `Attr`, `Entity`,
+    ///   `DocumentType`, `Notation`, `Document`, or `DocumentFragment` node.
+    ///
+    pub fn surround_contents(&mut self, new_parent: RefNode) -> Result<()> {
+        if matches!(
+            new_parent.node_type(),
+            NodeType::Attribute
+                | NodeType::Entity
+                | NodeType::DocumentType
+                | NodeType::Notation
+                | NodeType::Document
+                | NodeType::DocumentFragment
+        ) {
+            warn!("Range::surround_contents: cannot surround with a node of this type");
+            return Err(Error::InvalidNodeType);
+        }
+        let (start, end) = self.boundary_points()?;
+        let common_ancestor = common_ancestor(&start.node, &end.node);
+        //
+        // The partially contained nodes are the inclusive ancestors of either container, below
+        // the common ancestor.
+        //
+        let partially_contains_non_text = |container: &RefNode| {
+            std::iter::once(container.clone())
+                .chain(container.ancestors())
+                .take_while(|node| node != &common_ancestor)
+                .any(|node| !is_text_or_cdata(&node))
+        };
+        if partially_contains_non_text(&start.node) || partially_contains_non_text(&end.node) {
+            warn!("Range::surround_contents: the range partially selects a non-text node");
+            return Err(Error::InvalidState);
+        }
+        let fragment = self.extract_contents()?;
+        let mut new_parent = new_parent;
+        for child_node in new_parent.child_nodes().to_vec() {
+            let _safe_to_ignore = new_parent.remove_child(child_node)?;
+        }
+        self.insert_node(new_parent.clone())?;
+        move_children(&fragment, &mut new_parent)?;
+        self.select_node(new_parent)
+    }
+
+    ///
+    /// Produces a new Range whose boundary-points are equal to the boundary-points of the Range.
+    ///
+    /// # Specification
+    ///
+    /// **Return Value**
+    ///
+    /// * `Range`: The duplicated Range.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `INVALID_STATE_ERR`: Raised if `detach()` has already been invoked on this object.
+    ///
+    pub fn clone_range(&self) -> Result<Range> {
+        let (start, end) = self.boundary_points()?;
+        let document = self.state.borrow().document.clone();
+        Ok(Range::with_boundary_points(document, start, end))
+    }
+
+    ///
+    /// Called to indicate that the Range is no longer in use and that the implementation may
+    /// relinquish any resources associated with this Range. Subsequent calls to any methods or
+    /// attribute getters on this Range will result in a `DOMException` being thrown with an
+    /// error code of `INVALID_STATE_ERR`.
+    ///
+    pub fn detach(&mut self) {
+        let mut state = self.state.borrow_mut();
+        state.detached = true;
+        state.start = BoundaryPoint::new(state.document.clone(), 0);
+        state.end = state.start.clone();
+    }
+
+    fn boundary_points(&self) -> Result<(BoundaryPoint, BoundaryPoint)> {
+        let state = self.state.borrow();
+        state.check_attached()?;
+        Ok((state.start.clone(), state.end.clone()))
+    }
+
+    fn set_boundary_point(&mut self, point: BoundaryPoint, is_start: bool) -> Result<()> {
+        let (start, end) = self.boundary_points()?;
+        self.check_point(&point)?;
+        if is_start {
+            match compare_points(&point, &end) {
+                Some(Ordering::Less) | Some(Ordering::Equal) => {
+                    self.set_boundary_points(point, end)
+                }
+                _ => self.set_boundary_points(point.clone(), point),
+            }
+        } else {
+            match compare_points(&start, &point) {
+                Some(Ordering::Less) | Some(Ordering::Equal) => {
+                    self.set_boundary_points(start, point)
+                }
+                _ => self.set_boundary_points(point.clone(), point),
+            }
+        }
+    }
+
+    fn set_boundary_points(&mut self, start: BoundaryPoint, end: BoundaryPoint) -> Result<()> {
+        self.check_point(&start)?;
+        self.check_point(&end)?;
+        let mut state = self.state.borrow_mut();
+        state.check_attached()?;
+        state.start = start;
+        state.end = end;
+        Ok(())
+    }
+
+    fn check_point(&self, point: &BoundaryPoint) -> Result<()> {
+        let document = self.state.borrow().document.clone();
+        let point_document = if point.node.node_type() == NodeType::Document {
+            Some(point.node.clone())
+        } else {
+            point.node.owner_document()
+        };
+        if point_document.as_ref() != Some(&document) {
+            warn!("Range: the boundary-point was created from a different document");
+            return Err(Error::WrongDocument);
+        }
+        let mut node = Some(point.node.clone());
+        while let Some(current) = node {
+            if matches!(
+                current.node_type(),
+                NodeType::DocumentType | NodeType::Entity | NodeType::Notation
+            ) {
+                warn!("Range: the boundary-point container is not a valid node type");
+                return Err(Error::InvalidNodeType);
+            }
+            node = current.parent_node();
+        }
+        if point.offset > node_length(&point.node) {
+            warn!("Range: the boundary-point offset is larger than the container");
+            return Err(Error::IndexSize);
+        }
+        Ok(())
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl RangeState {
+    fn check_attached(&self) -> Result<()> {
+        if self.detached {
+            warn!("Range used after `detach`");
+            Err(Error::InvalidState)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl RemovalObserver for RangeState {
+    fn node_removing(&mut self, node: &RefNode) {
+        if self.detached {
+            return;
+        }
+        let parent = match node.parent_node() {
+            None => return,
+            Some(parent) => parent,
+        };
+        let index = index_of(node);
+        for point in [&mut self.start, &mut self.end] {
+            if is_inclusive_ancestor(node, &point.node) {
+                *point = BoundaryPoint::new(parent.clone(), index);
+            } else if point.node == parent && point.offset > index {
+                point.offset -= 1;
+            }
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl BoundaryPoint {
+    fn new(node: RefNode, offset: usize) -> Self {
+        Self { node, offset }
+    }
+
+    fn before(node: &RefNode) -> Result<Self> {
+        let parent = Self::parent_of(node)?;
+        Ok(Self::new(parent, index_of(node)))
+    }
+
+    fn after(node: &RefNode) -> Result<Self> {
+        let parent = Self::parent_of(node)?;
+        Ok(Self::new(parent, index_of(node) + 1))
+    }
+
+    fn parent_of(node: &RefNode) -> Result<RefNode> {
+        match node.parent_node() {
+            None => {
+                warn!("Range: the reference node has no parent");
+                Err(Error::InvalidNodeType)
+            }
+            Some(parent) => Ok(parent),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+//
+// Nodes whose boundary-point offsets count characters, rather than children.
+//
+fn is_data_node(node: &RefNode) -> bool {
+    matches!(
+        node.node_type(),
+        NodeType::Text | NodeType::CData | NodeType::Comment | NodeType::ProcessingInstruction
+    )
+}
+
+fn is_text_or_cdata(node: &RefNode) -> bool {
+    is_text(node) || is_cdata_section(node)
+}
+
+//
+// The number of characters in a data node, else the number of children.
+//
+fn node_length(node: &RefNode) -> usize {
+    if is_data_node(node) {
+        node.node_value().unwrap_or_default().chars().count()
+    } else {
+        node.child_nodes().len()
+    }
+}
+
+fn index_of(node: &RefNode) -> usize {
+    node.preceding_siblings().count()
+}
+
+//
+// The characters of `node`'s data from `start` up to, but not including, `end`.
+//
+fn substring(node: &RefNode, start: usize, end: usize) -> String {
+    node.node_value()
+        .unwrap_or_default()
+        .chars()
+        .skip(start)
+        .take(end.saturating_sub(start))
+        .collect()
+}
+
+fn delete_data(node: &RefNode, start: usize, end: usize) -> Result<()> {
+    let data = node.node_value().unwrap_or_default();
+    let new_data: String = data
+        .chars()
+        .take(start)
+        .chain(data.chars().skip(end))
+        .collect();
+    node.clone().set_node_value(&new_data)
+}
+
+//
+// Split a text node at the character `offset`, the new node holding the remainder of the data is
+// inserted as the next sibling and returned.
+//
+fn split_text(node: &RefNode, offset: usize) -> Result<RefNode> {
+    let document_node = node.owner_document().unwrap();
+    let document = as_document(&document_node)?;
+    let length = node_length(node);
+    let new_data = substring(node, offset, length);
+    let new_node = if is_cdata_section(node) {
+        document.create_cdata_section(&new_data)?
+    } else {
+        document.create_text_node(&new_data)
+    };
+    delete_data(node, offset, length)?;
+    if let Some(mut parent) = node.parent_node() {
+        let _safe_to_ignore = parent.insert_before(new_node.clone(), node.next_sibling())?;
+    }
+    Ok(new_node)
+}
+
+//
+// The root of the tree containing `node`, and the path of child indices from that root to `node`.
+//
+fn root_and_path(node: &RefNode) -> (RefNode, Vec<usize>) {
+    let mut path = Vec::new();
+    let mut node = node.clone();
+    while let Some(parent) = node.parent_node() {
+        path.push(index_of(&node));
+        node = parent;
+    }
+    path.reverse();
+    (node, path)
+}
+
+//
+// The position of boundary-point `lhs` relative to `rhs`, or `None` if they are in different trees.
+// A boundary-point is ordered as the path to its container followed by its offset, so a point in
+// a container's children compares with its descendants by the index of the child containing them.
+//
+fn compare_points(lhs: &BoundaryPoint, rhs: &BoundaryPoint) -> Option<Ordering> {
+    let (lhs_root, mut lhs_path) = root_and_path(&lhs.node);
+    let (rhs_root, mut rhs_path) = root_and_path(&rhs.node);
+    if lhs_root != rhs_root {
+        return None;
+    }
+    lhs_path.push(lhs.offset);
+    rhs_path.push(rhs.offset);
+    Some(lhs_path.cmp(&rhs_path))
+}
+
+//
+// A node is contained in a range if it is entirely after the start and before the end.
+//
+fn is_contained(node: &RefNode, start: &BoundaryPoint, end: &BoundaryPoint) -> bool {
+    let node_start = BoundaryPoint::new(node.clone(), 0);
+    let node_end = BoundaryPoint::new(node.clone(), node_length(node));
+    compare_points(&node_start, start) == Some(Ordering::Greater)
+        && compare_points(&node_end, end) == Some(Ordering::Less)
+}
+
+fn common_ancestor(lhs: &RefNode, rhs: &RefNode) -> RefNode {
+    let mut ancestor = lhs.clone();
+    while !is_inclusive_ancestor(&ancestor, rhs) {
+        match ancestor.parent_node() {
+            None => break,
+            Some(parent) => ancestor = parent,
+        }
+    }
+    ancestor
+}
+
+//
+// The child of `ancestor` that is an inclusive ancestor of `node`.
+//
+fn child_containing(ancestor: &RefNode, node: &RefNode) -> Option<RefNode> {
+    let mut node = node.clone();
+    loop {
+        let parent = node.parent_node()?;
+        if &parent == ancestor {
+            return Some(node);
+        }
+        node = parent;
+    }
+}
+
+//
+// The position a range is collapsed to when its contents are removed; this is the start if it
+// contains the end, else just after the highest ancestor of the start not also containing the end.
+//
+fn collapse_point(start: &BoundaryPoint, end: &BoundaryPoint) -> BoundaryPoint {
+    if is_inclusive_ancestor(&start.node, &end.node) {
+        return start.clone();
+    }
+    let mut reference = start.node.clone();
+    while let Some(parent) = reference.parent_node() {
+        if is_inclusive_ancestor(&parent, &end.node) {
+            return BoundaryPoint::new(parent, index_of(&reference) + 1);
+        }
+        reference = parent;
+    }
+    start.clone()
+}
+
+fn move_children(from: &RefNode, to: &mut RefNode) -> Result<()> {
+    for child_node in from.child_nodes().to_vec() {
+        let _safe_to_ignore = to.append_child(child_node)?;
+    }
+    Ok(())
+}
+
+fn shallow_clone(node: &RefNode) -> RefNode {
+    node.clone_node(false).unwrap()
+}
+
+//
+// The parts of a range that are split for extraction, or cloning.
+//
+struct Partition {
+    first_partially_contained: Option<RefNode>,
+    contained_children: Vec<RefNode>,
+    last_partially_contained: Option<RefNode>,
+}
+
+fn partition(start: &BoundaryPoint, end: &BoundaryPoint) -> Result<Partition> {
+    let common_ancestor = common_ancestor(&start.node, &end.node);
+    let first_partially_contained = if is_inclusive_ancestor(&start.node, &end.node) {
+        None
+    } else {
+        child_containing(&common_ancestor, &start.node)
+    };
+    let last_partially_contained = if is_inclusive_ancestor(&end.node, &start.node) {
+        None
+    } else {
+        child_containing(&common_ancestor, &end.node)
+    };
+    let contained_children: Vec<RefNode> = common_ancestor
+        .child_nodes()
+        .iter()
+        .filter(|child_node| is_contained(child_node, start, end))
+        .collect();
+    if contained_children
+        .iter()
+        .any(|child_node| child_node.node_type() == NodeType::DocumentType)
+    {
+        warn!("Range: cannot move a document type into a document fragment");
+        return Err(Error::HierarchyRequest);
+    }
+    Ok(Partition {
+        first_partially_contained,
+        contained_children,
+        last_partially_contained,
+    })
+}
+
+fn extract(document: &RefNode, start: &BoundaryPoint, end: &BoundaryPoint) -> Result<RefNode> {
+    let mut fragment = as_document(document)?.create_document_fragment()?;
+    if start == end {
+        return Ok(fragment);
+    }
+    if start.node == end.node && is_data_node(&start.node) {
+        let mut new_node = shallow_clone(&start.node);
+        new_node.set_node_value(&substring(&start.node, start.offset, end.offset))?;
+        let _safe_to_ignore = fragment.append_child(new_node)?;
+        delete_data(&start.node, start.offset, end.offset)?;
+        return Ok(fragment);
+    }
+    let partition = partition(start, end)?;
+    if let Some(first) = partition.first_partially_contained {
+        let mut new_node = shallow_clone(&first);
+        if is_data_node(&first) {
+            let length = node_length(&first);
+            new_node.set_node_value(&substring(&first, start.offset, length))?;
+            delete_data(&first, start.offset, length)?;
+        } else {
+            let length = node_length(&first);
+            let sub_fragment = extract(document, start, &BoundaryPoint::new(first, length))?;
+            move_children(&sub_fragment, &mut new_node)?;
+        }
+        let _safe_to_ignore = fragment.append_child(new_node)?;
+    }
+    for child_node in partition.contained_children {
+        let _safe_to_ignore = fragment.append_child(child_node)?;
+    }
+    if let Some(last) = partition.last_partially_contained {
+        let mut new_node = shallow_clone(&last);
+        if is_data_node(&last) {
+            new_node.set_node_value(&substring(&last, 0, end.offset))?;
+            delete_data(&last, 0, end.offset)?;
+        } else {
+            let sub_fragment = extract(document, &BoundaryPoint::new(last, 0), end)?;
+            move_children(&sub_fragment, &mut new_node)?;
+        }
+        let _safe_to_ignore = fragment.append_child(new_node)?;
+    }
+    Ok(fragment)
+}
+
+fn clone_contents(
+    document: &RefNode,
+    start: &BoundaryPoint,
+    end: &BoundaryPoint,
+) -> Result<RefNode> {
+    let mut fragment = as_document(document)?.create_document_fragment()?;
+    if start == end {
+        return Ok(fragment);
+    }
+    if start.node == end.node && is_data_node(&start.node) {
+        let mut new_node = shallow_clone(&start.node);
+        new_node.set_node_value(&substring(&start.node, start.offset, end.offset))?;
+        let _safe_to_ignore = fragment.append_child(new_node)?;
+        return Ok(fragment);
+    }
+    let partition = partition(start, end)?;
+    if let Some(first) = partition.first_partially_contained {
+        let mut new_node = shallow_clone(&first);
+        if is_data_node(&first) {
+            let length = node_length(&first);
+            new_node.set_node_value(&substring(&first, start.offset, length))?;
+        } else {
+            let length = node_length(&first);
+            let sub_fragment = clone_contents(document, start, &BoundaryPoint::new(first, length))?;
+            move_children(&sub_fragment, &mut new_node)?;
+        }
+        let _safe_to_ignore = fragment.append_child(new_node)?;
+    }
+    for child_node in partition.contained_children {
+        let _safe_to_ignore = fragment.append_child(child_node.clone_node(true).unwrap())?;
+    }
+    if let Some(last) = partition.last_partially_contained {
+        let mut new_node = shallow_clone(&last);
+        if is_data_node(&last) {
+            new_node.set_node_value(&substring(&last, 0, end.offset))?;
+        } else {
+            let sub_fragment = clone_contents(document, &BoundaryPoint::new(last, 0), end)?;
+            move_children(&sub_fragment, &mut new_node)?;
+        }
+        let _safe_to_ignore = fragment.append_child(new_node)?;
+    }
+    Ok(fragment)
+}
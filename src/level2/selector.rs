@@ -0,0 +1,510 @@
+/*!
+Provides the selection of elements using a subset of CSS selectors, after the `querySelector`
+and `querySelectorAll` methods of the
+[Selectors API](https://www.w3.org/TR/selectors-api/#interface-definitions).
+
+# Supported Selectors
+
+| Selector            | Matches                                                                  |
+|---------------------|--------------------------------------------------------------------------|
+| `name`              | An element with the qualified name `name`.                               |
+| `prefix\|name`      | An element with the qualified name `prefix:name`.                        |
+| `*`                 | Any element.                                                             |
+| `#value`            | An element with an ID attribute, see `Attribute::is_id`, of `value`.     |
+| `[attr]`            | An element with the attribute `attr`.                                    |
+| `[attr=value]`      | An element whose `attr` attribute has the value `value`.                 |
+| `[attr^=value]`     | An element whose `attr` attribute value starts with `value`.             |
+| `[attr$=value]`     | An element whose `attr` attribute value ends with `value`.               |
+| `[attr*=value]`     | An element whose `attr` attribute value contains `value`.                |
+| `a b`               | An element matching `b` that is a descendant of an element matching `a`. |
+| `a > b`             | An element matching `b` that is a child of an element matching `a`.      |
+| `a, b`              | An element matching either `a` or `b`.                                   |
+
+Attribute values may be quoted, with either `'` or `"`, and names are case-sensitive, as in XML.
+Any other selector, such as a pseudo-class or a sibling combinator, is a syntax error.
+
+# Example
+
+```rust
+# #[cfg(feature = "quick_parser")]
+# fn main() {
+use xml_dom::level2::*;
+use xml_dom::level2::selector::*;
+use xml_dom::parser::read_xml;
+
+let document_node = read_xml(
+    r#"<menu><item href="/a">A</item><group><item href="https://b">B</item></group></menu>"#,
+)
+.unwrap();
+
+let nodes = document_node.query_selector_all("menu > item, item[href^=https]").unwrap();
+assert_eq!(nodes.len(), 2);
+assert_eq!(
+    document_node.query_selector("group item").unwrap(),
+    Some(nodes[1].clone())
+);
+assert_eq!(document_node.query_selector("item:first-child"), Err(Error::Syntax));
+# }
+# #[cfg(not(feature = "quick_parser"))]
+# fn main() {}
+```
+*/
+
+use crate::level2::convert::as_attribute;
+use crate::level2::ext::TreeIterators;
+use crate::level2::node_impl::RefNode;
+use crate::level2::trait_impls::normalized_value;
+use crate::level2::traits::{Node, NodeType};
+use crate::shared::error::{Error, Result, MSG_INVALID_SELECTOR, MSG_UNSUPPORTED_SELECTOR};
+use crate::shared::text::{is_xml_name_char, is_xml_name_start_char, is_xml_space};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Extends `Document`, `DocumentFragment`, and `Element` with the ability to find descendant
+/// elements using CSS selectors.
+///
+/// **Exceptions**
+///
+/// * `SYNTAX_ERR`: Raised by each method if `selectors` is not valid, or is not supported.
+///
+pub trait QuerySelector: Node {
+    ///
+    /// Returns the first descendant element, in document order, that matches any of the
+    /// comma-separated `selectors`.
+    ///
+    fn query_selector(&self, selectors: &str) -> Result<Option<Self::NodeRef>>;
+    ///
+    /// Returns all the descendant elements, in document order, that match any of the
+    /// comma-separated `selectors`.
+    ///
+    fn query_selector_all(&self, selectors: &str) -> Result<Vec<Self::NodeRef>>;
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+//
+// A complex selector, its compound selectors from right to left with the combinator that joins
+// each to the next.
+//
+#[derive(Clone, Debug, PartialEq)]
+struct Selector {
+    subject: Compound,
+    ancestors: Vec<(Combinator, Compound)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Combinator {
+    Child,
+    Descendant,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Compound {
+    name: Option<String>,
+    conditions: Vec<Condition>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Condition {
+    Id(String),
+    Attribute(String, Option<(AttributeOperator, String)>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum AttributeOperator {
+    Equals,
+    StartsWith,
+    EndsWith,
+    Contains,
+}
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl QuerySelector for RefNode {
+    fn query_selector(&self, selectors: &str) -> Result<Option<Self::NodeRef>> {
+        let selectors = parse(selectors)?;
+        Ok(self
+            .descendant_elements()
+            .find(|element| matches_any(element, &selectors)))
+    }
+
+    fn query_selector_all(&self, selectors: &str) -> Result<Vec<Self::NodeRef>> {
+        let selectors = parse(selectors)?;
+        Ok(self
+            .descendant_elements()
+            .filter(|element| matches_any(element, &selectors))
+            .collect())
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Compound {
+    fn matches(&self, element: &RefNode) -> bool {
+        if let Some(name) = &self.name {
+            if &element.node_name().to_string() != name {
+                return false;
+            }
+        }
+        self.conditions.iter().all(|condition| match condition {
+            Condition::Id(id) => element.attribute_iter().any(|(_, attribute)| {
+                as_attribute(&attribute)
+                    .map(|attribute| attribute.is_id())
+                    .unwrap_or_default()
+                    && normalized_value(&attribute).as_deref() == Some(id.as_str())
+            }),
+            Condition::Attribute(name, test) => {
+                let value = element
+                    .attribute_iter()
+                    .find(|(attribute_name, _)| &attribute_name.to_string() == name)
+                    .map(|(_, attribute)| normalized_value(&attribute).unwrap_or_default());
+                match (value, test) {
+                    (None, _) => false,
+                    (Some(_), None) => true,
+                    (Some(value), Some((operator, expected))) => match operator {
+                        AttributeOperator::Equals => &value == expected,
+                        AttributeOperator::StartsWith => {
+                            !expected.is_empty() && value.starts_with(expected.as_str())
+                        }
+                        AttributeOperator::EndsWith => {
+                            !expected.is_empty() && value.ends_with(expected.as_str())
+                        }
+                        AttributeOperator::Contains => {
+                            !expected.is_empty() && value.contains(expected.as_str())
+                        }
+                    },
+                }
+            }
+        })
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn skip_space(&mut self) -> bool {
+        let start = self.position;
+        while self.peek().map(is_xml_space).unwrap_or_default() {
+            self.position += 1;
+        }
+        self.position > start
+    }
+
+    fn selector_list(&mut self) -> Result<Vec<Selector>> {
+        let mut selectors = Vec::new();
+        loop {
+            let _safe_to_ignore = self.skip_space();
+            selectors.push(self.selector()?);
+            match self.peek() {
+                None => return Ok(selectors),
+                Some(',') => self.position += 1,
+                Some(_) => return self.invalid(),
+            }
+        }
+    }
+
+    fn selector(&mut self) -> Result<Selector> {
+        let mut compounds = vec![self.compound()?];
+        let mut combinators = Vec::new();
+        loop {
+            let had_space = self.skip_space();
+            let combinator = match self.peek() {
+                None | Some(',') => break,
+                Some('>') => {
+                    self.position += 1;
+                    let _safe_to_ignore = self.skip_space();
+                    Combinator::Child
+                }
+                Some('+') | Some('~') => return self.unsupported(),
+                Some(_) if had_space => Combinator::Descendant,
+                Some(_) => return self.invalid(),
+            };
+            combinators.push(combinator);
+            compounds.push(self.compound()?);
+        }
+        let subject = compounds.pop().unwrap();
+        Ok(Selector {
+            subject,
+            ancestors: combinators
+                .into_iter()
+                .rev()
+                .zip(compounds.into_iter().rev())
+                .collect(),
+        })
+    }
+
+    fn compound(&mut self) -> Result<Compound> {
+        let mut compound = Compound::default();
+        let mut is_universal = false;
+        match self.peek() {
+            Some('*') => {
+                self.position += 1;
+                is_universal = true;
+            }
+            Some(c) if is_name_start_char(c) => compound.name = Some(self.name()?),
+            _ => (),
+        }
+        loop {
+            match self.peek() {
+                Some('#') => {
+                    self.position += 1;
+                    let id = self.identifier();
+                    if id.is_empty() {
+                        return self.invalid();
+                    }
+                    compound.conditions.push(Condition::Id(id));
+                }
+                Some('[') => {
+                    self.position += 1;
+                    compound.conditions.push(self.attribute()?);
+                }
+                Some(':') | Some('.') => return self.unsupported(),
+                _ => break,
+            }
+        }
+        if compound.name.is_none() && compound.conditions.is_empty() && !is_universal {
+            self.invalid()
+        } else {
+            Ok(compound)
+        }
+    }
+
+    fn attribute(&mut self) -> Result<Condition> {
+        let _safe_to_ignore = self.skip_space();
+        let name = self.name()?;
+        let _safe_to_ignore = self.skip_space();
+        let operator = match self.peek() {
+            Some(']') => {
+                self.position += 1;
+                return Ok(Condition::Attribute(name, None));
+            }
+            Some('=') => {
+                self.position += 1;
+                AttributeOperator::Equals
+            }
+            Some(c) if self.chars.get(self.position + 1) == Some(&'=') => {
+                self.position += 2;
+                match c {
+                    '^' => AttributeOperator::StartsWith,
+                    '$' => AttributeOperator::EndsWith,
+                    '*' => AttributeOperator::Contains,
+                    '~' | '|' => return self.unsupported(),
+                    _ => return self.invalid(),
+                }
+            }
+            _ => return self.invalid(),
+        };
+        let _safe_to_ignore = self.skip_space();
+        let value = match self.peek() {
+            Some(quote) if quote == '"' || quote == '\'' => {
+                self.position += 1;
+                let start = self.position;
+                while self.peek().map(|c| c != quote).unwrap_or_default() {
+                    self.position += 1;
+                }
+                if self.peek().is_none() {
+                    return self.invalid();
+                }
+                self.position += 1;
+                self.chars[start..self.position - 1].iter().collect()
+            }
+            _ => {
+                let value = self.identifier();
+                if value.is_empty() {
+                    return self.invalid();
+                }
+                value
+            }
+        };
+        let _safe_to_ignore = self.skip_space();
+        if self.peek() != Some(']') {
+            return self.invalid();
+        }
+        self.position += 1;
+        Ok(Condition::Attribute(name, Some((operator, value))))
+    }
+
+    //
+    // A qualified name, written with the CSS namespace separator `|` in place of `:`.
+    //
+    fn name(&mut self) -> Result<String> {
+        if !self.peek().map(is_name_start_char).unwrap_or_default() {
+            return self.invalid();
+        }
+        let prefix = self.identifier();
+        if self.peek() == Some('|') && self.chars.get(self.position + 1) != Some(&'=') {
+            self.position += 1;
+            let local_name = self.identifier();
+            if local_name.is_empty() {
+                return self.invalid();
+            }
+            Ok(format!("{}:{}", prefix, local_name))
+        } else {
+            Ok(prefix)
+        }
+    }
+
+    fn identifier(&mut self) -> String {
+        let start = self.position;
+        while self.peek().map(is_name_char).unwrap_or_default() {
+            self.position += 1;
+        }
+        self.chars[start..self.position].iter().collect()
+    }
+
+    fn invalid<T>(&self) -> Result<T> {
+        warn!("{}", MSG_INVALID_SELECTOR);
+        Err(Error::Syntax)
+    }
+
+    fn unsupported<T>(&self) -> Result<T> {
+        warn!("{}", MSG_UNSUPPORTED_SELECTOR);
+        Err(Error::Syntax)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn parse(selectors: &str) -> Result<Vec<Selector>> {
+    Parser {
+        chars: selectors.chars().collect(),
+        position: 0,
+    }
+    .selector_list()
+}
+
+fn is_name_start_char(c: char) -> bool {
+    c != ':' && is_xml_name_start_char(c)
+}
+
+fn is_name_char(c: char) -> bool {
+    c != ':' && is_xml_name_char(c)
+}
+
+fn matches_any(element: &RefNode, selectors: &[Selector]) -> bool {
+    selectors.iter().any(|selector| {
+        selector.subject.matches(element) && matches_ancestors(element, &selector.ancestors)
+    })
+}
+
+//
+// Match the remaining compound selectors, right to left, against the ancestors of `element`; a
+// descendant combinator may try each ancestor in turn.
+//
+fn matches_ancestors(element: &RefNode, ancestors: &[(Combinator, Compound)]) -> bool {
+    match ancestors.split_first() {
+        None => true,
+        Some(((combinator, compound), rest)) => match combinator {
+            Combinator::Child => match element.parent_node() {
+                Some(parent) if parent.node_type() == NodeType::Element => {
+                    compound.matches(&parent) && matches_ancestors(&parent, rest)
+                }
+                _ => false,
+            },
+            Combinator::Descendant => element
+                .ancestors()
+                .filter(|ancestor| ancestor.node_type() == NodeType::Element)
+                .any(|ancestor| compound.matches(&ancestor) && matches_ancestors(&ancestor, rest)),
+        },
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn named(name: &str) -> Compound {
+        Compound {
+            name: Some(name.to_string()),
+            conditions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_selectors() {
+        assert_eq!(
+            parse("a b > dc|c, *").unwrap(),
+            vec![
+                Selector {
+                    subject: named("dc:c"),
+                    ancestors: vec![
+                        (Combinator::Child, named("b")),
+                        (Combinator::Descendant, named("a"))
+                    ],
+                },
+                Selector {
+                    subject: Compound::default(),
+                    ancestors: vec![],
+                }
+            ]
+        );
+        assert_eq!(
+            parse("#x[y][ z ^= 'a b' ]").unwrap(),
+            vec![Selector {
+                subject: Compound {
+                    name: None,
+                    conditions: vec![
+                        Condition::Id("x".to_string()),
+                        Condition::Attribute("y".to_string(), None),
+                        Condition::Attribute(
+                            "z".to_string(),
+                            Some((AttributeOperator::StartsWith, "a b".to_string()))
+                        ),
+                    ],
+                },
+                ancestors: vec![],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_selectors() {
+        for selectors in [
+            "",
+            "a,",
+            ",a",
+            "a >",
+            "> a",
+            "a >> b",
+            "[",
+            "[a",
+            "[a=]",
+            "[a='b]",
+            "#",
+            "a|",
+            "a:hover",
+            ":hover",
+            "a::before",
+            ".class",
+            "a + b",
+            "a ~ b",
+            "[a~=b]",
+            "[a|=b]",
+            "a!",
+        ] {
+            assert_eq!(parse(selectors), Err(Error::Syntax), "{}", selectors);
+        }
+    }
+}
@@ -1,16 +1,27 @@
 use crate::level2::convert::*;
-use crate::level2::dom_impl::{get_implementation, Implementation};
+use crate::level2::data_ref::DataRef;
+use crate::level2::dom_impl::Implementation;
 use crate::level2::ext::convert::as_element_namespaced_mut;
+use crate::level2::ext::dtd::{AttributeDecl, AttributeType};
+use crate::level2::ext::expansion::ExpansionLimits;
 use crate::level2::ext::options::ProcessingOptions;
+use crate::level2::ext::traits::{Namespaced, TreeIterators};
+use crate::level2::mutation::{Mutation, MutationListeners, SharedListener};
 use crate::level2::node_impl::*;
+use crate::level2::node_list::NodeList;
 use crate::level2::traits::*;
 use crate::shared::error::*;
 use crate::shared::name::Name;
+use crate::shared::rc_cell::{Shared, WeakShared};
 use crate::shared::syntax::*;
-use crate::shared::{display, text};
+use crate::shared::{display, text, uri};
+use indexmap::IndexMap;
+use std::cmp::Ordering;
 use std::collections::hash_map::RandomState;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
 use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::ops::Range;
 use std::str::FromStr;
 
 // ------------------------------------------------------------------------------------------------
@@ -62,6 +73,58 @@ macro_rules! unwrap_extension_field {
     }};
 }
 
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+//
+// The namespace declarations in scope, keyed by prefix where the default namespace has the empty
+// prefix; an empty URI is an undeclared default namespace.
+//
+pub(crate) type Namespaces = BTreeMap<String, String>;
+
+//
+// A feature, as named by `DOMImplementation::has_feature`, with the versions of it that are
+// implemented, and whether it is enabled in this build.
+//
+struct Feature {
+    name: &'static str,
+    versions: &'static [&'static str],
+    enabled: bool,
+}
+
+//
+// The single registry of features; both `DOMImplementation::has_feature` and
+// `Node::is_supported` answer from this.
+//
+const FEATURES: &[Feature] = &[
+    Feature {
+        name: XML_FEATURE_CORE,
+        versions: &[XML_FEATURE_V1, XML_FEATURE_V2],
+        enabled: true,
+    },
+    Feature {
+        name: XML_FEATURE_XML,
+        versions: &[XML_FEATURE_V1, XML_FEATURE_V2],
+        enabled: true,
+    },
+    Feature {
+        name: XML_FEATURE_TRAVERSAL,
+        versions: &[XML_FEATURE_V2],
+        enabled: true,
+    },
+    Feature {
+        name: XML_FEATURE_RANGE,
+        versions: &[XML_FEATURE_V2],
+        enabled: true,
+    },
+    Feature {
+        name: XML_FEATURE_XPATH,
+        versions: &[XML_FEATURE_V3],
+        enabled: true,
+    },
+];
+
 // ------------------------------------------------------------------------------------------------
 // Implementations
 // ------------------------------------------------------------------------------------------------
@@ -78,41 +141,33 @@ impl Attribute for RefNode {
     // text. See also the method `setAttribute` on the `Element` interface.
     //
     fn value(&self) -> Option<String> {
-        if self.has_child_nodes() {
-            let mut result = String::new();
-            for child_node in self.child_nodes() {
-                if child_node.node_type() == NodeType::EntityReference {
-                    if let Some(value) = child_node.node_value() {
-                        result.push_str(&value);
-                    }
-                } else if child_node.node_type() == NodeType::Text {
-                    //
-                    // Do not use the Text::data function as this will escape the response.
-                    //
-                    let ref_node = child_node.borrow();
-                    if let Some(data) = &ref_node.i_value {
-                        result.push_str(&data);
-                    }
-                }
-            }
-            let normalized = text::normalize_attribute_value(&result, self, false);
-            Some(text::escape(&normalized))
-        } else {
-            None
-        }
+        normalized_value(self).map(|value| {
+            escape_attribute_with(&value, InvalidCharHandling::Escape).unwrap_or_default()
+        })
     }
     fn set_value(&mut self, value: &str) -> Result<()> {
-        self.unset_value()?;
+        check_writable(self)?;
+        check_chars(strict_error_checking(self), value)?;
+        let change = value_change(self);
+        clear_value(self);
         let document_node = self.owner_document().unwrap();
         let document = as_document(&document_node).unwrap();
-        let _safe_to_ignore = self.append_child(document.create_text_node(value))?;
+        let text_node = document.create_text_node(value);
+        text_node.borrow_mut().i_parent_node = Some(self.clone().downgrade());
+        self.try_borrow_mut()?.i_child_nodes.push(text_node);
+        notify_value_change(change);
         Ok(())
     }
     fn unset_value(&mut self) -> Result<()> {
-        let mut mut_self = self.borrow_mut();
-        mut_self.i_child_nodes.clear();
+        check_writable(self)?;
+        let change = value_change(self);
+        clear_value(self);
+        notify_value_change(change);
         Ok(())
     }
+    fn specified(&self) -> bool {
+        unwrap_extension_field!(self, Attribute, i_specified)
+    }
     fn owner_element(&self) -> Option<Self::NodeRef> {
         unwrap_extension_field!(
             self,
@@ -132,6 +187,20 @@ impl Attribute for RefNode {
             }
         )
     }
+    fn is_id(&self) -> bool {
+        let is_id = unwrap_extension_field!(self, Attribute, i_is_id);
+        is_id || self.node_name().is_id_attribute(assume_ids(self)) || is_declared_id(self)
+    }
+    fn set_is_id(&mut self, is_id: bool) -> Result<()> {
+        let mut mut_self = self.try_borrow_mut()?;
+        if let Extension::Attribute { i_is_id, .. } = &mut mut_self.i_extension {
+            *i_is_id = is_id;
+            Ok(())
+        } else {
+            warn!("{}", MSG_INVALID_EXTENSION);
+            Err(Error::InvalidState)
+        }
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -141,84 +210,64 @@ impl CDataSection for RefNode {}
 // ------------------------------------------------------------------------------------------------
 
 impl CharacterData for RefNode {
+    fn data_as_str(&self) -> DataRef<'_> {
+        DataRef::new(self.borrow())
+    }
+
     fn substring_data(&self, offset: usize, count: usize) -> Result<String> {
-        if offset + count == offset {
-            return Ok(String::new());
-        }
-        let ref_self = self.borrow();
-        match &ref_self.i_value {
-            None => {
-                warn!("{}", MSG_INDEX_ERROR);
-                Err(Error::IndexSize)
-            }
-            Some(data) => {
-                if offset >= data.len() {
-                    warn!("{}", MSG_INDEX_ERROR);
-                    Err(Error::IndexSize)
-                } else if offset + count >= data.len() {
-                    Ok(data[offset..].to_string())
-                } else {
-                    Ok(data[offset..offset + count].to_string())
-                }
-            }
-        }
+        let data = self.data_as_str();
+        let range = char_range(&data, offset, count)?;
+        Ok(data[range].to_string())
     }
 
     fn append_data(&mut self, new_data: &str) -> Result<()> {
+        check_writable(self)?;
         if new_data.is_empty() {
             return Ok(());
         }
-        let mut mut_self = self.borrow_mut();
-        match &mut_self.i_value {
-            None => mut_self.i_value = Some(new_data.to_string()),
-            Some(old_data) => mut_self.i_value = Some(format!("{}{}", old_data, new_data)),
+        let node_type = self.node_type();
+        let strict = strict_error_checking(self);
+        let change = value_change(self);
+        {
+            let mut mut_self = self.try_borrow_mut()?;
+            let new_data = match &mut_self.i_value {
+                None => new_data.to_string(),
+                Some(old_data) => format!("{}{}", old_data, new_data),
+            };
+            check_data(&node_type, &new_data, strict)?;
+            mut_self.i_value = Some(new_data.into_boxed_str());
         }
+        notify_value_change(change);
         Ok(())
     }
 
     fn insert_data(&mut self, offset: usize, new_data: &str) -> Result<()> {
-        if new_data.is_empty() {
-            return Ok(());
-        }
         self.replace_data(offset, 0, new_data)
     }
 
     fn delete_data(&mut self, offset: usize, count: usize) -> Result<()> {
-        if offset + count == offset {
-            return Ok(());
-        }
         const NOTHING: &str = "";
         self.replace_data(offset, count, NOTHING)
     }
 
     fn replace_data(&mut self, offset: usize, count: usize, replace_data: &str) -> Result<()> {
-        let mut mut_self = self.borrow_mut();
-        match &mut_self.i_value {
-            None => {
-                if offset + count != 0 {
-                    warn!("{}", MSG_INDEX_ERROR);
-                    Err(Error::IndexSize)
-                } else {
-                    mut_self.i_value = Some(replace_data.to_string());
-                    Ok(())
-                }
-            }
-            Some(old_data) => {
-                if offset >= old_data.len() {
-                    warn!("{}", MSG_INDEX_ERROR);
-                    Err(Error::IndexSize)
-                } else {
-                    let mut new_data = old_data.clone();
-                    if offset + count >= old_data.len() {
-                        new_data.replace_range(offset.., replace_data);
-                    } else {
-                        new_data.replace_range(offset..offset + count, replace_data);
-                    }
-                    mut_self.i_value = Some(new_data);
-                    Ok(())
-                }
+        check_writable(self)?;
+        let node_type = self.node_type();
+        let strict = strict_error_checking(self);
+        let change = value_change(self);
+        {
+            let mut mut_self = self.try_borrow_mut()?;
+            let mut new_data = mut_self.i_value.as_deref().unwrap_or_default().to_string();
+            let range = char_range(&new_data, offset, count)?;
+            if range.is_empty() && replace_data.is_empty() {
+                return Ok(());
             }
+            new_data.replace_range(range, replace_data);
+            check_data(&node_type, &new_data, strict)?;
+            mut_self.i_value = Some(new_data.into_boxed_str());
         }
+        notify_value_change(change);
+        Ok(())
     }
 }
 
@@ -234,7 +283,7 @@ impl Document for RefNode {
     }
 
     fn document_element(&self) -> Option<RefNode> {
-        self.child_nodes().first().map(Clone::clone)
+        self.child_nodes().iter().find(is_element)
     }
 
     fn implementation(&self) -> &dyn DOMImplementation<NodeRef = RefNode> {
@@ -249,25 +298,62 @@ impl Document for RefNode {
         }
     }
 
+    fn document_uri(&self) -> Option<String> {
+        unwrap_extension_field!(self, Document, i_document_uri)
+    }
+
+    fn set_document_uri(&mut self, document_uri: &str) -> Result<()> {
+        set_document_uri(self, Some(document_uri.to_string()))
+    }
+
+    fn unset_document_uri(&mut self) -> Result<()> {
+        set_document_uri(self, None)
+    }
+
+    fn strict_error_checking(&self) -> bool {
+        unwrap_extension_field!(self, Document, i_strict_error_checking)
+    }
+
+    fn set_strict_error_checking(&mut self, strict_error_checking: bool) -> Result<()> {
+        let mut mut_self = self.try_borrow_mut()?;
+        if let Extension::Document {
+            i_strict_error_checking,
+            ..
+        } = &mut mut_self.i_extension
+        {
+            *i_strict_error_checking = strict_error_checking;
+            Ok(())
+        } else {
+            warn!("{}", MSG_INVALID_EXTENSION);
+            Err(Error::InvalidState)
+        }
+    }
+
     fn create_attribute(&self, name: &str) -> Result<RefNode> {
-        let name = Name::from_str(name)?;
+        let name = intern_name(self, parse_name(self.strict_error_checking(), name)?);
         let node_impl = NodeImpl::new_attribute(self.clone().downgrade(), name, None);
         Ok(RefNode::new(node_impl))
     }
 
     fn create_attribute_with(&self, name: &str, value: &str) -> Result<RefNode> {
-        let name = Name::from_str(name)?;
+        let strict = self.strict_error_checking();
+        let name = intern_name(self, parse_name(strict, name)?);
+        check_chars(strict, value)?;
         let node_impl = NodeImpl::new_attribute(self.clone().downgrade(), name, Some(value));
         Ok(RefNode::new(node_impl))
     }
 
     fn create_attribute_ns(&self, namespace_uri: &str, qualified_name: &str) -> Result<RefNode> {
-        let name = Name::new_ns(namespace_uri, qualified_name)?;
+        let name = intern_name(
+            self,
+            parse_name_ns(self.strict_error_checking(), namespace_uri, qualified_name)?,
+        );
         let node_impl = NodeImpl::new_attribute(self.clone().downgrade(), name, None);
         Ok(RefNode::new(node_impl))
     }
 
     fn create_cdata_section(&self, data: &str) -> Result<RefNode> {
+        check_data(&NodeType::CData, data, self.strict_error_checking())?;
         let node_impl = NodeImpl::new_cdata(self.clone().downgrade(), data);
         Ok(RefNode::new(node_impl))
     }
@@ -278,26 +364,56 @@ impl Document for RefNode {
     }
 
     fn create_entity_reference(&self, name: &str) -> Result<RefNode> {
-        let name = Name::from_str(name)?;
-        let node_impl = NodeImpl::new_entity_reference(self.clone().downgrade(), name);
-        Ok(RefNode::new(node_impl))
+        let name = intern_name(self, Name::from_str(name)?);
+        let entity = self.doc_type().and_then(|doc_type| {
+            let doc_type = as_document_type(&doc_type).ok()?;
+            doc_type.entities().get(&name).cloned()
+        });
+        let node_impl = NodeImpl::new_entity_reference(self.clone().downgrade(), name.clone());
+        let mut entity_reference = RefNode::new(node_impl);
+        if let Some(entity) = entity {
+            let roots = entity
+                .child_nodes()
+                .into_iter()
+                .map(|child_node| (child_node, vec![name.clone()]))
+                .collect();
+            check_expansion(self, roots)?;
+            //
+            // The children are copies of the entity's replacement tree, and `append_child` makes
+            // them read-only.
+            //
+            for child_node in entity.child_nodes() {
+                if let Some(new_child) = child_node.clone_node(true) {
+                    let _safe_to_ignore = entity_reference.append_child(new_child)?;
+                }
+            }
+        }
+        Ok(entity_reference)
     }
 
-    fn create_comment(&self, data: &str) -> RefNode {
+    fn create_comment(&self, data: &str) -> Result<RefNode> {
+        check_data(&NodeType::Comment, data, self.strict_error_checking())?;
         let node_impl = NodeImpl::new_comment(self.clone().downgrade(), data);
-        RefNode::new(node_impl)
+        Ok(RefNode::new(node_impl))
     }
 
     fn create_element(&self, tag_name: &str) -> Result<RefNode> {
-        let name = Name::from_str(tag_name)?;
+        let name = intern_name(self, parse_name(self.strict_error_checking(), tag_name)?);
         let node_impl = NodeImpl::new_element(self.clone().downgrade(), name);
-        Ok(RefNode::new(node_impl))
+        let mut element = RefNode::new(node_impl);
+        add_default_attributes(&mut element)?;
+        Ok(element)
     }
 
     fn create_element_ns(&self, namespace_uri: &str, qualified_name: &str) -> Result<RefNode> {
-        let name = Name::new_ns(namespace_uri, qualified_name)?;
+        let name = intern_name(
+            self,
+            parse_name_ns(self.strict_error_checking(), namespace_uri, qualified_name)?,
+        );
         let node_impl = NodeImpl::new_element(self.clone().downgrade(), name);
-        Ok(RefNode::new(node_impl))
+        let mut element = RefNode::new(node_impl);
+        add_default_attributes(&mut element)?;
+        Ok(element)
     }
 
     fn create_processing_instruction(&self, target: &str, data: Option<&str>) -> Result<RefNode> {
@@ -307,9 +423,17 @@ impl Document for RefNode {
         // `PITarget  ::=  Name - (('X' | 'x') ('M' | 'm') ('L' | 'l'))`
         //
         if target.to_ascii_lowercase() == XML_PI_RESERVED {
-            return Err(Error::Syntax);
+            warn!("{}", MSG_RESERVED_PI_TARGET);
+            return Err(Error::InvalidCharacter);
+        }
+        let target = intern_name(self, Name::from_str(target)?);
+        if let Some(data) = data {
+            check_data(
+                &NodeType::ProcessingInstruction,
+                data,
+                self.strict_error_checking(),
+            )?;
         }
-        let target = Name::from_str(target)?;
         let node_impl =
             NodeImpl::new_processing_instruction(self.clone().downgrade(), target, data);
         Ok(RefNode::new(node_impl))
@@ -320,45 +444,162 @@ impl Document for RefNode {
         RefNode::new(node_impl)
     }
 
+    fn try_create_text_node(&self, data: &str) -> Result<RefNode> {
+        check_chars(self.strict_error_checking(), data)?;
+        Ok(self.create_text_node(data))
+    }
+
     fn get_element_by_id(&self, id: &str) -> Option<RefNode> {
-        let ref_self = self.borrow();
-        if let Extension::Document { i_id_map, .. } = &ref_self.i_extension {
-            match i_id_map.get(&id.to_string()) {
-                None => None,
-                Some(weak_ref) => match weak_ref.clone().upgrade() {
-                    None => {
-                        warn!("{}", MSG_WEAK_REF);
-                        None
-                    }
-                    Some(ref_element) => Some(ref_element),
-                },
+        //
+        // The tree is searched, rather than keeping an index up to date, so that elements that
+        // have been moved or removed are never returned and document order decides duplicates.
+        // The walk is lazy, and stops at the first match.
+        //
+        self.descendant_elements().find(|element| {
+            element.attribute_iter().any(|(_, attribute)| {
+                attribute.is_id() && Attribute::value(&attribute).as_deref() == Some(id)
+            })
+        })
+    }
+
+    fn get_elements_by_tag_name(&self, tag_name: &str) -> Vec<RefNode> {
+        descendant_elements(self, |element| {
+            tag_name_match(&element.borrow().i_name.to_string(), tag_name)
+        })
+    }
+
+    fn get_elements_by_tag_name_ns(&self, namespace_uri: &str, local_name: &str) -> Vec<RefNode> {
+        descendant_elements(self, |element| {
+            element_name_ns_match(element, namespace_uri, local_name)
+        })
+    }
+
+    fn import_node(&self, imported_node: RefNode, deep: bool) -> Result<RefNode> {
+        match imported_node.node_type() {
+            NodeType::Document | NodeType::DocumentType => {
+                warn!("{}", MSG_INVALID_NODE_TYPE);
+                Err(Error::NotSupported)
             }
-        } else {
-            warn!("{}", MSG_INVALID_EXTENSION);
-            None
+            _ => copy_node(&imported_node, Some(self.clone().downgrade()), deep, true),
         }
     }
 
-    fn get_elements_by_tag_name(&self, tag_name: &str) -> Vec<RefNode> {
+    fn adopt_node(&self, source: RefNode) -> Result<RefNode> {
+        match source.node_type() {
+            NodeType::Document | NodeType::DocumentType | NodeType::Entity | NodeType::Notation => {
+                warn!("{}", MSG_INVALID_NODE_TYPE);
+                return Err(Error::NotSupported);
+            }
+            NodeType::Attribute => {
+                if let Some(mut owner_element) = source.owner_element() {
+                    let _safe_to_ignore = owner_element.remove_attribute_node(source.clone())?;
+                }
+            }
+            NodeType::EntityReference => {
+                let mut source = source.clone();
+                for child_node in source.child_nodes().to_vec() {
+                    let _safe_to_ignore = source.remove_child(child_node)?;
+                }
+            }
+            _ => (),
+        }
+        if let Some(mut parent_node) = source.parent_node() {
+            let _safe_to_ignore = parent_node.remove_child(source.clone())?;
+        }
+
         //
-        // Delegate this call to the document element
+        // Walk the subtree, and any attributes, without recursion.
         //
-        if let Some(root_element) = self.document_element() {
-            Element::get_elements_by_tag_name(&root_element, tag_name)
-        } else {
-            Vec::default()
+        let owner_document = Some(self.clone().downgrade());
+        let mut pending = vec![source.clone()];
+        let mut adopted = Vec::new();
+        while let Some(node) = pending.pop() {
+            {
+                let mut mut_node = node.borrow_mut();
+                mut_node.i_owner_document = owner_document.clone();
+                pending.extend(mut_node.i_child_nodes.iter().cloned());
+                if let Extension::Element { i_attributes, .. } = &mut_node.i_extension {
+                    pending.extend(i_attributes.values().cloned());
+                }
+            }
+            adopted.push(node);
         }
-    }
-
-    fn get_elements_by_tag_name_ns(&self, namespace_uri: &str, local_name: &str) -> Vec<RefNode> {
         //
-        // Delegate this call to the document element
+        // Defaulted attributes are discarded, and those of this document's DTD added.
         //
-        if let Some(root_element) = self.document_element() {
-            Element::get_elements_by_tag_name_ns(&root_element, namespace_uri, local_name)
+        for node in &adopted {
+            if is_element(node) {
+                reset_default_attributes(&mut node.clone())?;
+            }
+        }
+        for node in adopted {
+            notify_user_data_handlers(&node, UserDataOperation::NodeAdopted, None);
+        }
+        Ok(source)
+    }
+
+    fn rename_node(
+        &self,
+        node: RefNode,
+        namespace_uri: Option<&str>,
+        qualified_name: &str,
+    ) -> Result<RefNode> {
+        if !(is_element(&node) || is_attribute(&node)) {
+            warn!("{}", MSG_INVALID_NODE_TYPE);
+            return Err(Error::NotSupported);
+        }
+        let name = match namespace_uri {
+            Some(namespace_uri) => Name::new_ns(namespace_uri, qualified_name)?,
+            None => {
+                let name = Name::from_str(qualified_name)?;
+                if name.prefix().is_some() {
+                    warn!("{}", MSG_PREFIX_WITHOUT_NAMESPACE);
+                    return Err(Error::Namespace);
+                }
+                name
+            }
+        };
+        check_same_document(self, &node)?;
+        check_writable(&node)?;
+        if is_element(&node) {
+            node.borrow_mut().i_name = name;
+            //
+            // Defaults for the old name are dropped, and those for the new name added.
+            //
+            reset_default_attributes(&mut node.clone())?;
         } else {
-            Vec::default()
+            if is_namespace_declaration(&name) && !name.is_namespace_attribute() {
+                warn!("{}", MSG_INVALID_NAME);
+                return Err(Error::Namespace);
+            }
+            match node.owner_element() {
+                None => node.borrow_mut().i_name = name,
+                Some(mut element) => {
+                    check_writable(&element)?;
+                    node.borrow_mut().i_name = name.clone();
+                    //
+                    // Keep the attribute's position, replacing any other with the new name.
+                    //
+                    {
+                        let mut mut_element = element.borrow_mut();
+                        if let Extension::Element { i_attributes, .. } =
+                            &mut mut_element.i_extension
+                        {
+                            if let Some(replaced) = i_attributes
+                                .get(&name)
+                                .filter(|attribute_node| *attribute_node != &node)
+                            {
+                                set_owner_element(replaced, None);
+                            }
+                            i_attributes.replace(&node, name, node.clone());
+                        }
+                    }
+                    add_default_attributes(&mut element)?;
+                }
+            }
         }
+        notify_user_data_handlers(&node, UserDataOperation::NodeRenamed, None);
+        Ok(node)
     }
 }
 
@@ -418,8 +659,7 @@ impl DOMImplementation for Implementation {
     }
 
     fn has_feature(&self, feature: &str, version: &str) -> bool {
-        (feature == XML_FEATURE_CORE || feature == XML_FEATURE_XML)
-            && (version == XML_FEATURE_V1 || version == XML_FEATURE_V2)
+        has_feature(feature, version)
     }
 }
 
@@ -440,10 +680,16 @@ impl Element for RefNode {
     }
 
     fn set_attribute(&mut self, name: &str, value: &str) -> Result<()> {
-        let attr_name = Name::from_str(name)?;
+        let strict = strict_error_checking(self);
+        let attr_name = parse_name(strict, name)?;
+        check_chars(strict, value)?;
         let attr_node = {
-            let ref_self = &self.borrow_mut();
+            let ref_self = self.borrow();
             let document = ref_self.i_owner_document.as_ref().unwrap();
+            let attr_name = match document.clone().upgrade() {
+                Some(document) => intern_name(&document, attr_name),
+                None => attr_name,
+            };
             NodeImpl::new_attribute(document.clone(), attr_name, Some(value))
         };
         self.set_attribute_node(RefNode::new(attr_node)).map(|_| ())
@@ -458,7 +704,7 @@ impl Element for RefNode {
 
     fn get_attribute_node(&self, name: &str) -> Option<RefNode> {
         if is_element(self) {
-            match Name::from_str(name) {
+            match parse_name(strict_error_checking(self), name) {
                 Ok(name) => {
                     let ref_self = self.borrow();
                     if let Extension::Element { i_attributes, .. } = &ref_self.i_extension {
@@ -483,102 +729,60 @@ impl Element for RefNode {
         }
     }
 
-    fn set_attribute_node(&mut self, new_attribute: RefNode) -> Result<RefNode> {
-        if is_element(self) && is_attribute(&new_attribute) {
-            check_same_document(self, &new_attribute)?;
-
-            //
-            // Set the attribute's owner. This is *not* the same as parent which remains `None`.
-            //
-            {
-                let mut mut_child = new_attribute.borrow_mut();
-                if let Extension::Attribute {
-                    i_owner_element, ..
-                } = &mut mut_child.i_extension
-                {
-                    *i_owner_element = Some(self.clone().downgrade())
-                } else {
-                    panic!("{}", MSG_INVALID_EXTENSION);
-                }
-            }
-
-            let name: Name = new_attribute.node_name();
-            if name.is_namespace_attribute() {
-                //
-                // Add to the element's namespace mapping hash
-                //
-                let attribute = as_attribute(&new_attribute).unwrap();
-                let namespace_uri = attribute.value().unwrap();
-
-                let as_namespaced = as_element_namespaced_mut(self).unwrap();
-                let _ignore = match &name.prefix() {
-                    None => as_namespaced.insert_mapping(None, &namespace_uri),
-                    Some(prefix) => as_namespaced.insert_mapping(Some(prefix), &namespace_uri),
-                }?;
-            }
-
-            let mut mut_self = self.borrow_mut();
-            if let Extension::Element { i_attributes, .. } = &mut mut_self.i_extension {
-                let _safe_to_ignore =
-                    i_attributes.insert(new_attribute.node_name(), new_attribute.clone());
-                {
-                    //
-                    // Add to the owning document's id_map hash
-                    //
-                    let attribute = as_attribute(&new_attribute).unwrap();
-                    let document = attribute.owner_document().unwrap();
-                    let mut mut_document = document.borrow_mut();
-                    let lax =
-                        if let Extension::Document { i_options, .. } = &mut_document.i_extension {
-                            i_options.has_assume_ids()
-                        } else {
-                            warn!("{}", MSG_INVALID_EXTENSION);
-                            false
-                        };
-                    if name.is_id_attribute(lax) {
-                        //
-                        // Update the document ID mapping
-                        //
-                        if let Extension::Document { i_id_map, .. } = &mut mut_document.i_extension
-                        {
-                            let id_value = attribute.value().unwrap();
-                            if i_id_map.contains_key(&id_value) {
-                                warn!("{}", MSG_DUPLICATE_ID);
-                                return Err(Error::Syntax);
-                            }
-                            let _safe_to_ignore =
-                                i_id_map.insert(id_value, self.clone().downgrade());
-                        } else {
-                            warn!("{}", MSG_INVALID_EXTENSION);
-                        }
-                    }
-                }
-                Ok(new_attribute)
-            } else {
-                warn!("{}", MSG_INVALID_EXTENSION);
-                Err(Error::Syntax)
-            }
-        } else {
-            warn!("{}", MSG_INVALID_NODE_TYPE);
-            Err(Error::InvalidState)
+    fn set_attribute_node(&mut self, new_attribute: RefNode) -> Result<Option<RefNode>> {
+        check_attribute_node(self, &new_attribute)?;
+        if new_attribute.owner_element().as_ref() == Some(self) {
+            return Ok(None);
         }
+        let old_attribute = self.get_attribute_node(&new_attribute.node_name().to_string());
+        attach_attribute(self, new_attribute, old_attribute)
     }
 
     fn remove_attribute_node(&mut self, old_attribute: RefNode) -> Result<RefNode> {
         if is_element(self) {
-            let mut mut_self = self.borrow_mut();
+            check_writable(self)?;
+            check_unborrowed(&old_attribute)?;
+            let mut mut_self = self.try_borrow_mut()?;
             if let Extension::Element { i_attributes, .. } = &mut mut_self.i_extension {
-                let _safe_to_ignore = i_attributes.remove(&old_attribute.node_name());
+                let name = old_attribute.node_name();
+                if i_attributes.get(&name) != Some(&old_attribute) {
+                    warn!("remove_attribute_node: old_attribute not found in `attributes`");
+                    return Err(Error::NotFound);
+                }
+                let _safe_to_ignore = i_attributes.remove(&name);
                 let mut_old = old_attribute.clone();
                 let mut mut_old = mut_old.borrow_mut();
                 mut_old.i_parent_node = None;
+                if let Extension::Attribute {
+                    i_owner_element,
+                    i_specified,
+                    ..
+                } = &mut mut_old.i_extension
+                {
+                    *i_owner_element = None;
+                    *i_specified = true;
+                }
                 // TODO: remove from Element::namespaces
                 // TODO: remove from Document::id_map
-                Ok(old_attribute)
             } else {
                 warn!("{}", MSG_INVALID_EXTENSION);
-                Err(Error::Syntax)
+                return Err(Error::Syntax);
             }
+            drop(mut_self);
+            notify_mutation(
+                self,
+                Mutation::AttributeChanged {
+                    element: self.clone(),
+                    name: old_attribute.node_name(),
+                    old_value: attribute_value(&old_attribute),
+                    new_value: None,
+                },
+            );
+            //
+            // A removed attribute with a default value is immediately replaced by the default.
+            //
+            add_default_attributes(self)?;
+            Ok(old_attribute)
         } else {
             warn!("{}", MSG_INVALID_NODE_TYPE);
             Err(Error::InvalidState)
@@ -586,23 +790,14 @@ impl Element for RefNode {
     }
 
     fn get_elements_by_tag_name(&self, tag_name: &str) -> Vec<RefNode> {
-        let mut results = Vec::default();
         if is_element(self) {
-            let tag_name = tag_name.to_string();
-            let ref_self = self.borrow();
-            if tag_name_match(&ref_self.i_name.to_string(), &tag_name) {
-                results.push(self.clone());
-            }
-            for child_node in &ref_self.i_child_nodes {
-                match as_element(child_node) {
-                    Ok(ref_child) => results.extend(ref_child.get_elements_by_tag_name(&tag_name)),
-                    Err(_) => {
-                        warn!("{}", MSG_INVALID_NODE_TYPE);
-                    }
-                }
-            }
+            descendant_elements(self, |element| {
+                tag_name_match(&element.borrow().i_name.to_string(), tag_name)
+            })
+        } else {
+            warn!("{}", MSG_INVALID_NODE_TYPE);
+            Vec::default()
         }
-        results
     }
 
     fn get_attribute_ns(&self, namespace_uri: &str, local_name: &str) -> Option<String> {
@@ -624,13 +819,32 @@ impl Element for RefNode {
         qualified_name: &str,
         value: &str,
     ) -> Result<()> {
-        let attr_name = Name::new_ns(namespace_uri, qualified_name)?;
-        let attr_node = {
-            let ref_self = &self.borrow_mut();
-            let document = ref_self.i_owner_document.as_ref().unwrap();
-            NodeImpl::new_attribute(document.clone(), attr_name, Some(value))
-        };
-        self.set_attribute_node(RefNode::new(attr_node)).map(|_| ())
+        let strict = strict_error_checking(self);
+        let attr_name = parse_name_ns(strict, namespace_uri, qualified_name)?;
+        check_chars(strict, value)?;
+        match self.get_attribute_node_ns(namespace_uri, attr_name.local_name()) {
+            Some(mut attr_node) => {
+                //
+                // Keep the existing attribute, although it takes the new prefix.
+                //
+                check_writable(self)?;
+                attr_node.set_value(value)?;
+                attr_node.borrow_mut().i_name = attr_name;
+                attach_attribute(self, attr_node.clone(), Some(attr_node)).map(|_| ())
+            }
+            None => {
+                let attr_node = {
+                    let ref_self = self.borrow();
+                    let document = ref_self.i_owner_document.as_ref().unwrap();
+                    let attr_name = match document.clone().upgrade() {
+                        Some(document) => intern_name(&document, attr_name),
+                        None => attr_name,
+                    };
+                    NodeImpl::new_attribute(document.clone(), attr_name, Some(value))
+                };
+                self.set_attribute_node(RefNode::new(attr_node)).map(|_| ())
+            }
+        }
     }
 
     fn remove_attribute_ns(&mut self, namespace_uri: &str, local_name: &str) -> Result<()> {
@@ -642,28 +856,15 @@ impl Element for RefNode {
 
     fn get_attribute_node_ns(&self, namespace_uri: &str, local_name: &str) -> Option<RefNode> {
         if is_element(self) {
-            match Name::new_ns(namespace_uri, local_name) {
-                Ok(_) => {
-                    let ref_self = self.borrow();
-                    if let Extension::Element { i_attributes, .. } = &ref_self.i_extension {
-                        let namespace_uri = &Some(namespace_uri.to_string());
-                        let local_name = &local_name.to_string();
-                        i_attributes
-                            .iter()
-                            .find(|(name, _)| {
-                                name.namespace_uri() == namespace_uri
-                                    && name.local_name() == local_name
-                            })
-                            .map(|(_, node)| node.clone())
-                    } else {
-                        warn!("{}", MSG_INVALID_EXTENSION);
-                        None
-                    }
-                }
-                Err(_) => {
-                    warn!("{}: '{}'", MSG_INVALID_NAME, local_name);
-                    None
-                }
+            let ref_self = self.borrow();
+            if let Extension::Element { i_attributes, .. } = &ref_self.i_extension {
+                i_attributes
+                    .iter()
+                    .find(|(name, _)| name_ns_match(name, namespace_uri, local_name))
+                    .map(|(_, node)| node.clone())
+            } else {
+                warn!("{}", MSG_INVALID_EXTENSION);
+                None
             }
         } else {
             warn!("{}", MSG_INVALID_NODE_TYPE);
@@ -671,43 +872,37 @@ impl Element for RefNode {
         }
     }
 
-    fn set_attribute_node_ns(&mut self, new_attribute: RefNode) -> Result<RefNode> {
-        self.set_attribute_node(new_attribute)
+    fn set_attribute_node_ns(&mut self, new_attribute: RefNode) -> Result<Option<RefNode>> {
+        check_attribute_node(self, &new_attribute)?;
+        if new_attribute.owner_element().as_ref() == Some(self) {
+            return Ok(None);
+        }
+
+        //
+        // Replace any attribute with the same namespace and local name, whatever its prefix.
+        //
+        let name = new_attribute.node_name();
+        let old_attribute = match name.namespace_uri() {
+            Some(namespace_uri) => self.get_attribute_node_ns(namespace_uri, name.local_name()),
+            None => self.get_attribute_node(&name.to_string()),
+        };
+        attach_attribute(self, new_attribute, old_attribute)
     }
 
     fn get_elements_by_tag_name_ns(&self, namespace_uri: &str, local_name: &str) -> Vec<RefNode> {
-        let mut results = Vec::default();
         if is_element(self) {
-            let namespace_uri = namespace_uri.to_string();
-            let local_name = local_name.to_string();
-            let ref_self = self.borrow();
-            if namespaced_name_match(
-                match ref_self.i_name.namespace_uri() {
-                    None => None,
-                    Some(s) => Some(s.as_str()),
-                },
-                &ref_self.i_name.local_name(),
-                &namespace_uri,
-                &local_name,
-            ) {
-                results.push(self.clone());
-            }
-            for child_node in &ref_self.i_child_nodes {
-                match as_element(child_node) {
-                    Ok(ref_child) => results
-                        .extend(ref_child.get_elements_by_tag_name_ns(&namespace_uri, &local_name)),
-                    Err(_) => {
-                        warn!("{}", MSG_INVALID_NODE_TYPE);
-                    }
-                }
-            }
+            descendant_elements(self, |element| {
+                element_name_ns_match(element, namespace_uri, local_name)
+            })
+        } else {
+            warn!("{}", MSG_INVALID_NODE_TYPE);
+            Vec::default()
         }
-        results
     }
 
     fn has_attribute(&self, name: &str) -> bool {
         if is_element(self) {
-            match Name::from_str(name) {
+            match parse_name(strict_error_checking(self), name) {
                 Ok(name) => {
                     let ref_self = self.borrow();
                     if let Extension::Element { i_attributes, .. } = &ref_self.i_extension {
@@ -732,32 +927,45 @@ impl Element for RefNode {
 
     fn has_attribute_ns(&self, namespace_uri: &str, local_name: &str) -> bool {
         if is_element(self) {
-            match Name::new_ns(namespace_uri, local_name) {
-                Ok(name) => {
-                    let ref_self = self.borrow();
-                    if let Extension::Element { i_attributes, .. } = &ref_self.i_extension {
-                        i_attributes.keys().any(|n| {
-                            n.namespace_uri() == name.namespace_uri()
-                                && n.local_name() == name.local_name()
-                        })
-                    } else {
-                        warn!("{}", MSG_INVALID_EXTENSION);
-                        false
-                    }
-                }
-                Err(_) => {
-                    warn!("{}: '{}'", MSG_INVALID_NAME, local_name);
-                    false
-                }
+            let ref_self = self.borrow();
+            if let Extension::Element { i_attributes, .. } = &ref_self.i_extension {
+                i_attributes
+                    .keys()
+                    .any(|name| name_ns_match(name, namespace_uri, local_name))
+            } else {
+                warn!("{}", MSG_INVALID_EXTENSION);
+                false
             }
         } else {
             warn!("{}", MSG_INVALID_NODE_TYPE);
             false
         }
     }
-}
 
-// ------------------------------------------------------------------------------------------------
+    fn append_text(&mut self, data: &str) -> Result<RefNode> {
+        append_created(self, |document| Ok(document.create_text_node(data)))
+    }
+
+    fn append_element(&mut self, tag_name: &str) -> Result<RefNode> {
+        append_created(self, |document| document.create_element(tag_name))
+    }
+
+    fn append_element_ns(&mut self, namespace_uri: &str, qualified_name: &str) -> Result<RefNode> {
+        append_created(self, |document| {
+            document.create_element_ns(namespace_uri, qualified_name)
+        })
+    }
+
+    fn append_comment(&mut self, data: &str) -> Result<RefNode> {
+        append_created(self, |document| document.create_comment(data))
+    }
+
+    fn append_cdata(&mut self, data: &str) -> Result<RefNode> {
+        append_created(self, |document| document.create_cdata_section(data))
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
 
 impl Entity for RefNode {
     fn public_id(&self) -> Option<String> {
@@ -789,24 +997,62 @@ impl Node for RefNode {
 
     fn node_value(&self) -> Option<String> {
         let ref_self = self.borrow();
-        ref_self.i_value.clone()
+        ref_self.i_value.as_deref().map(String::from)
     }
 
     fn set_node_value(&mut self, value: &str) -> Result<()> {
-        let mut mut_self = self.borrow_mut();
-        mut_self.i_value = Some(value.to_string());
+        check_writable(self)?;
+        check_data(&self.node_type(), value, strict_error_checking(self))?;
+        let change = value_change(self);
+        self.borrow_mut().i_value = Some(value.into());
+        notify_value_change(change);
         Ok(())
     }
 
     fn unset_node_value(&mut self) -> Result<()> {
-        let mut mut_self = self.borrow_mut();
-        mut_self.i_value = None;
+        check_writable(self)?;
+        let change = value_change(self);
+        self.borrow_mut().i_value = None;
+        notify_value_change(change);
+        Ok(())
+    }
+
+    fn set_prefix(&mut self, prefix: Option<&str>) -> Result<()> {
+        let node_type = self.node_type();
+        if node_type != NodeType::Element && node_type != NodeType::Attribute {
+            return Ok(());
+        }
+        check_writable(self)?;
+        let name = self.node_name();
+        let new_name = match (name.namespace_uri(), prefix) {
+            (None, None) => return Ok(()),
+            (None, Some(_)) => {
+                warn!("{}", MSG_PREFIX_WITHOUT_NAMESPACE);
+                return Err(Error::Namespace);
+            }
+            (Some(namespace_uri), None) => Name::new_ns(namespace_uri, name.local_name())?,
+            (Some(namespace_uri), Some(prefix)) => Name::new_ns(
+                namespace_uri,
+                &format!("{}{}{}", prefix, XML_NS_SEPARATOR, name.local_name()),
+            )?,
+        };
+        self.borrow_mut().i_name = new_name;
+        if node_type == NodeType::Attribute {
+            //
+            // The owning element's attribute map is keyed by name, so re-attach the attribute in
+            // its current position.
+            //
+            if let Some(mut owner) = as_attribute(self)?.owner_element() {
+                let _safe_to_ignore =
+                    attach_attribute(&mut owner, self.clone(), Some(self.clone()))?;
+            }
+        }
         Ok(())
     }
 
     fn node_type(&self) -> NodeType {
         let ref_self = self.borrow();
-        ref_self.i_node_type.clone()
+        ref_self.i_node_type
     }
 
     fn parent_node(&self) -> Option<RefNode> {
@@ -820,9 +1066,8 @@ impl Node for RefNode {
         }
     }
 
-    fn child_nodes(&self) -> Vec<RefNode> {
-        let ref_self = self.borrow();
-        ref_self.i_child_nodes.clone()
+    fn child_nodes(&self) -> NodeList {
+        NodeList::new(self)
     }
 
     fn first_child(&self) -> Option<RefNode> {
@@ -849,10 +1094,9 @@ impl Node for RefNode {
                 let parent_node = parent_node.clone();
                 let parent_node = parent_node.upgrade()?;
                 let ref_parent = parent_node.borrow();
-                match ref_parent
-                    .i_child_nodes
-                    .iter()
-                    .position(|child| child == self)
+                match ref_self
+                    .i_index_hint
+                    .position_in(&ref_parent.i_child_nodes, self)
                 {
                     None => None,
                     Some(index) => {
@@ -882,10 +1126,9 @@ impl Node for RefNode {
                 let parent_node = parent_node.clone();
                 let parent_node = parent_node.upgrade()?;
                 let ref_parent = parent_node.borrow();
-                match ref_parent
-                    .i_child_nodes
-                    .iter()
-                    .position(|child| child == self)
+                match ref_self
+                    .i_index_hint
+                    .position_in(&ref_parent.i_child_nodes, self)
                 {
                     None => None,
                     Some(index) => {
@@ -897,12 +1140,12 @@ impl Node for RefNode {
         }
     }
 
-    fn attributes(&self) -> HashMap<Name, RefNode, RandomState> {
+    fn attributes(&self) -> IndexMap<Name, RefNode> {
         if is_element(self) {
-            unwrap_extension_field!(self, Element, i_attributes)
+            self.attribute_iter().collect()
         } else {
             warn!("{}", MSG_INVALID_NODE_TYPE);
-            HashMap::default()
+            IndexMap::default()
         }
     }
 
@@ -921,98 +1164,93 @@ impl Node for RefNode {
             insert_position: Option<usize>,
         ) {
             let mut mut_parent = parent_node.borrow_mut();
-            let new_child = new_child.clone();
-            match insert_position {
-                None => mut_parent.i_child_nodes.push(new_child),
-                Some(position) => mut_parent.i_child_nodes.insert(position, new_child),
-            }
+            let position = insert_position.unwrap_or(mut_parent.i_child_nodes.len());
+            new_child.borrow().i_index_hint.set(position);
+            mut_parent.i_child_nodes.insert(position, new_child.clone());
         }
 
-        if !is_child_allowed(self, &new_child) {
-            warn!("The child you tried to add is not valid for this parent.");
-            return Err(Error::HierarchyRequest);
-        }
+        check_writable(self)?;
+        check_unborrowed(&new_child)?;
+        check_hierarchy(self, &new_child, None, ref_child.as_ref())?;
 
         //
-        // Special case for Document only.
+        // Check that `ref_child` is a child of self, inserting a node before itself is then the
+        // same as inserting it before its next sibling.
         //
-        if is_document(self)
-            && is_element(&new_child)
-            && self
-                .child_nodes()
-                .iter()
-                .any(|n| n.node_type() == NodeType::Element)
-        {
-            warn!("cannot add more than one element to a document");
-            return Error::HierarchyRequest.into();
+        if let Some(ref_child) = &ref_child {
+            if child_position(self, ref_child).is_none() {
+                warn!("insert_before: ref_child not found in `child_nodes`");
+                return Error::NotFound.into();
+            }
         }
-
-        //
-        // Find the index in `child_nodes` of the `ref_child`.
-        //
-        let insert_position = match ref_child {
-            None => None,
-            Some(ref_child) => match self
-                .borrow()
-                .i_child_nodes
-                .iter()
-                .position(|child| child == &ref_child)
-            {
-                None => {
-                    warn!("insert_before: ref_child not found in `child_nodes`");
-                    return Error::NotFound.into();
-                }
-                position => position,
-            },
+        let ref_child = match ref_child {
+            Some(ref_child) if ref_child == new_child => new_child.next_sibling(),
+            ref_child => ref_child,
         };
 
         check_same_document(self, &new_child)?;
+        let change = value_change(self);
 
         //
-        // Remove from it's current parent
+        // Remove the nodes to insert from their current parent; for a document fragment these
+        // are its children, and the fragment itself is never inserted.
         //
-        match new_child.parent_node() {
-            None => (),
-            Some(mut parent_node) => {
+        let new_children = if is_document_fragment(&new_child) {
+            let mut fragment = new_child.clone();
+            let mut new_children = Vec::new();
+            for child in new_child.child_nodes().to_vec() {
+                new_children.push(fragment.remove_child(child)?);
+            }
+            new_children
+        } else {
+            if let Some(mut parent_node) = new_child.parent_node() {
                 let _safe_to_ignore = parent_node.remove_child(new_child.clone())?;
             }
-        }
+            vec![new_child.clone()]
+        };
 
         //
-        // update new child with references from self
+        // Find the index in `child_nodes` of the `ref_child`, this has to happen after removal as
+        // `new_child` may have been an earlier sibling.
         //
-        {
-            let ref_self = self.borrow();
-            let mut mut_child = new_child.borrow_mut();
-            mut_child.i_parent_node = Some(self.to_owned().downgrade());
-            if is_document(self) {
-                mut_child.i_owner_document = Some(self.clone().downgrade());
-            } else {
-                mut_child.i_owner_document = ref_self.i_owner_document.clone();
-            }
-        }
+        let insert_position = ref_child.and_then(|ref_child| child_position(self, &ref_child));
 
         //
-        // Special case
+        // update new children with references from self
         //
-        if is_document_fragment(&new_child) {
-            for (index, child) in new_child.child_nodes().iter().enumerate() {
-                match insert_position {
-                    None => insert_or_append(self, child, None),
-                    Some(position) => insert_or_append(self, child, Some(position + index)),
-                }
-            }
+        let owner_document = if is_document(self) {
+            Some(self.to_owned().downgrade())
         } else {
-            insert_or_append(self, &new_child, insert_position)
+            self.borrow().i_owner_document.clone()
+        };
+        let read_only = makes_read_only(self);
+        for (index, child) in new_children.iter().enumerate() {
+            {
+                let mut mut_child = child.borrow_mut();
+                mut_child.i_parent_node = Some(self.to_owned().downgrade());
+                mut_child.i_owner_document = owner_document.clone();
+            }
+            if read_only {
+                set_read_only(child, true);
+            }
+            insert_or_append(
+                self,
+                child,
+                insert_position.map(|position| position + index),
+            );
+        }
+        if is_document(self) && is_document_type(&new_child) {
+            set_document_type(self, Some(new_child.clone()));
         }
+        notify_children_change(self, change, new_children, true);
 
         Ok(new_child)
     }
 
     fn replace_child(&mut self, new_child: RefNode, old_child: RefNode) -> Result<RefNode> {
-        if !is_child_allowed(self, &new_child) {
-            return Err(Error::HierarchyRequest);
-        }
+        check_writable(self)?;
+        check_hierarchy(self, &new_child, Some(&old_child), Some(&old_child))?;
+        check_same_document(self, &new_child)?;
         let exists = {
             let ref_self = self.borrow();
             ref_self.i_child_nodes.contains(&old_child.clone())
@@ -1029,26 +1267,29 @@ impl Node for RefNode {
     }
 
     fn remove_child(&mut self, old_child: Self::NodeRef) -> Result<Self::NodeRef> {
-        let position = {
-            let ref_self = self.borrow();
-            ref_self
-                .i_child_nodes
-                .iter()
-                .position(|child| child == &old_child)
-        };
-        match position {
+        check_writable(self)?;
+        check_unborrowed(&old_child)?;
+        match child_position(self, &old_child) {
             None => {
                 warn!("remove_child: old_child not found in `child_nodes`");
                 Err(Error::NotFound)
             }
             Some(position) => {
+                notify_removal(self, &old_child);
+                let change = value_change(self);
                 let removed = {
                     let mut mut_self = self.borrow_mut();
                     mut_self.i_child_nodes.remove(position)
                 };
-                let mut mut_removed = removed.borrow_mut();
-                mut_removed.i_parent_node = None;
-                Ok(removed.clone())
+                removed.borrow_mut().i_parent_node = None;
+                if is_document(self) && is_document_type(&removed) {
+                    set_document_type(self, None);
+                }
+                if makes_read_only(self) {
+                    set_read_only(&removed, false);
+                }
+                notify_children_change(self, change, vec![removed.clone()], false);
+                Ok(removed)
             }
         }
     }
@@ -1058,46 +1299,252 @@ impl Node for RefNode {
     }
 
     fn has_child_nodes(&self) -> bool {
-        !self.child_nodes().is_empty()
+        !self.borrow().i_child_nodes.is_empty()
     }
 
     fn clone_node(&self, deep: bool) -> Option<RefNode> {
-        let ref_self = self.borrow();
-        let new_node = ref_self.clone_node(deep);
-        Some(RefNode::new(new_node))
+        //
+        // The only failure is adding the document's default attributes to a copied element,
+        // which has already been logged; there is no copy to return without them.
+        //
+        if is_document(self) {
+            clone_document(self, deep).ok()
+        } else {
+            let owner_document = self.borrow().i_owner_document.clone();
+            copy_node(self, owner_document, deep, false).ok()
+        }
     }
 
     fn normalize(&mut self) {
-        for child_node in self.child_nodes() {
+        let mut last_text_node: Option<RefNode> = None;
+        for mut child_node in self.child_nodes().to_vec() {
             if is_text(&child_node) {
-                if CharacterData::length(&child_node) == 0 {
-                    if self.remove_child(child_node).is_err() {
-                        panic!("Could not remove unnecessary text node");
-                    }
-                } else if let Some(last_child_node) = child_node.previous_sibling() {
-                    let last_child_node = &mut last_child_node.clone();
-                    if is_text(&last_child_node) {
-                        if last_child_node
-                            .append_data(&child_node.node_value().unwrap())
-                            .is_err()
-                        {
-                            panic!("Could not merge text nodes");
-                        }
-                        if self.remove_child(child_node).is_err() {
-                            panic!("Could not remove unnecessary text node");
-                        }
-                    }
+                let data = child_node.node_value().unwrap_or_default();
+                if data.is_empty() {
+                    let _safe_to_ignore = self.remove_child(child_node);
+                } else if let Some(last_text_node) = &mut last_text_node {
+                    let _safe_to_ignore = last_text_node.append_data(&data);
+                    let _safe_to_ignore = self.remove_child(child_node);
+                } else {
+                    last_text_node = Some(child_node);
                 }
+            } else {
+                last_text_node = None;
+                child_node.normalize();
+            }
+        }
+        if is_element(self) {
+            for (_, mut attribute_node) in self.attribute_iter() {
+                attribute_node.normalize();
             }
         }
     }
 
     fn is_supported(&self, feature: &str, version: &str) -> bool {
-        get_implementation().has_feature(feature, version)
+        has_feature(feature, version)
+    }
+
+    fn get_feature(&self, feature: &str, version: &str) -> Option<Self::NodeRef> {
+        if has_feature(feature, version) {
+            Some(self.clone())
+        } else {
+            None
+        }
     }
 
     fn has_attributes(&self) -> bool {
-        !self.attributes().is_empty()
+        self.attribute_iter().next().is_some()
+    }
+
+    fn base_uri(&self) -> Option<String> {
+        match self.node_type() {
+            NodeType::Document => self.document_uri(),
+            NodeType::Element => in_scope_elements(self)
+                .filter_map(|element| element.get_attribute(Name::XML_BASE))
+                .collect::<Vec<String>>()
+                .into_iter()
+                .rev()
+                .fold(document_base_uri(self), |base, reference| match base {
+                    None => Some(reference),
+                    Some(base) => Some(uri::resolve_reference(&base, &reference)),
+                }),
+            NodeType::Attribute => self.owner_element()?.base_uri(),
+            NodeType::DocumentType
+            | NodeType::Entity
+            | NodeType::Notation
+            | NodeType::DocumentFragment => None,
+            _ => self.parent_node()?.base_uri(),
+        }
+    }
+
+    fn text_content(&self) -> Option<String> {
+        match self.node_type() {
+            NodeType::Document | NodeType::DocumentType | NodeType::Notation => None,
+            NodeType::Text
+            | NodeType::CData
+            | NodeType::Comment
+            | NodeType::ProcessingInstruction => Some(self.data_as_str().to_string()),
+            _ => {
+                let mut text = String::new();
+                for node in self
+                    .descendants()
+                    .filter(|node| is_text(node) || is_cdata_section(node))
+                {
+                    text.push_str(&node.data_as_str());
+                }
+                Some(text)
+            }
+        }
+    }
+
+    fn set_text_content(&mut self, text: &str) -> Result<()> {
+        match self.node_type() {
+            NodeType::Document | NodeType::DocumentType | NodeType::Notation => Ok(()),
+            NodeType::Text
+            | NodeType::CData
+            | NodeType::Comment
+            | NodeType::ProcessingInstruction => self.set_node_value(text),
+            _ => {
+                for child_node in self.child_nodes().to_vec() {
+                    let _safe_to_ignore = self.remove_child(child_node)?;
+                }
+                if !text.is_empty() {
+                    let _safe_to_ignore = append_created(self, |document_node| {
+                        Ok(as_document(document_node)?.create_text_node(text))
+                    })?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn lookup_namespace_uri(&self, prefix: Option<&str>) -> Option<String> {
+        let prefix = prefix.filter(|prefix| !prefix.is_empty());
+        match prefix {
+            Some(XML_NS_ATTRIBUTE) => Some(XML_NS_URI.to_string()),
+            Some(XMLNS_NS_ATTRIBUTE) => Some(XMLNS_NS_URI.to_string()),
+            _ => locate_namespace(&namespace_context(self)?, prefix),
+        }
+    }
+
+    fn lookup_prefix(&self, namespace_uri: &str) -> Option<String> {
+        match namespace_uri {
+            XML_EMPTY => None,
+            XML_NS_URI => Some(XML_NS_ATTRIBUTE.to_string()),
+            XMLNS_NS_URI => Some(XMLNS_NS_ATTRIBUTE.to_string()),
+            _ => locate_prefix(&namespace_context(self)?, namespace_uri),
+        }
+    }
+
+    fn is_default_namespace(&self, namespace_uri: &str) -> bool {
+        let namespace_uri = Some(namespace_uri).filter(|uri| !uri.is_empty());
+        self.lookup_namespace_uri(None).as_deref() == namespace_uri
+    }
+
+    fn compare_document_position(&self, other: &RefNode) -> u16 {
+        if self == other {
+            return 0;
+        }
+        //
+        // Attributes are positioned by their owner element, see `attribute_position`.
+        //
+        let (node, node_attribute) = attribute_position(other);
+        let (reference, reference_attribute) = attribute_position(self);
+        let (node_root, mut node_path) = root_and_path(&node);
+        let (reference_root, mut reference_path) = root_and_path(&reference);
+        if node_root != reference_root {
+            let ordering = if Shared::as_ptr(node_root.as_inner())
+                < Shared::as_ptr(reference_root.as_inner())
+            {
+                document_position::PRECEDING
+            } else {
+                document_position::FOLLOWING
+            };
+            return document_position::DISCONNECTED
+                | document_position::IMPLEMENTATION_SPECIFIC
+                | ordering;
+        }
+        if node == reference {
+            return match (node_attribute, reference_attribute) {
+                (None, _) => document_position::CONTAINS | document_position::PRECEDING,
+                (_, None) => document_position::CONTAINED_BY | document_position::FOLLOWING,
+                (Some(lhs), Some(rhs)) if lhs < rhs => document_position::PRECEDING,
+                _ => document_position::FOLLOWING,
+            };
+        }
+        let is_prefix = |lhs: &Vec<usize>, rhs: &Vec<usize>| rhs.starts_with(lhs);
+        if node_attribute.is_none() && is_prefix(&node_path, &reference_path) {
+            document_position::CONTAINS | document_position::PRECEDING
+        } else if reference_attribute.is_none() && is_prefix(&reference_path, &node_path) {
+            document_position::CONTAINED_BY | document_position::FOLLOWING
+        } else {
+            //
+            // An attribute comes after its owner element, but before the element's children.
+            //
+            node_path.push(usize::from(node_attribute.is_none()));
+            reference_path.push(usize::from(reference_attribute.is_none()));
+            if node_path < reference_path {
+                document_position::PRECEDING
+            } else {
+                document_position::FOLLOWING
+            }
+        }
+    }
+
+    fn is_same_node(&self, other: &RefNode) -> bool {
+        self == other
+    }
+
+    fn set_user_data(
+        &mut self,
+        key: &str,
+        data: UserData,
+        handler: Option<UserDataHandlerRef<RefNode>>,
+    ) -> Option<UserData> {
+        self.borrow_mut().i_user_data.insert(key, data, handler)
+    }
+
+    fn unset_user_data(&mut self, key: &str) -> Option<UserData> {
+        self.borrow_mut().i_user_data.remove(key)
+    }
+
+    fn get_user_data(&self, key: &str) -> Option<UserData> {
+        self.borrow().i_user_data.get(key).cloned()
+    }
+
+    fn is_equal_node(&self, other: &RefNode) -> bool {
+        //
+        // Pairs of nodes still to be compared, this avoids recursion on deep trees.
+        //
+        let mut pending = vec![(self.clone(), other.clone())];
+        while let Some((lhs, rhs)) = pending.pop() {
+            if lhs == rhs {
+                continue;
+            }
+            if lhs.node_type() != rhs.node_type()
+                || lhs.node_name() != rhs.node_name()
+                || lhs.node_value() != rhs.node_value()
+                || !pair_named_nodes(lhs.attributes(), rhs.attributes(), &mut pending)
+            {
+                return false;
+            }
+            if is_document_type(&lhs)
+                && (DocumentType::public_id(&lhs) != DocumentType::public_id(&rhs)
+                    || DocumentType::system_id(&lhs) != DocumentType::system_id(&rhs)
+                    || lhs.internal_subset() != rhs.internal_subset()
+                    || !pair_named_nodes(lhs.entities(), rhs.entities(), &mut pending)
+                    || !pair_named_nodes(lhs.notations(), rhs.notations(), &mut pending))
+            {
+                return false;
+            }
+            let lhs_children = lhs.child_nodes().to_vec();
+            let rhs_children = rhs.child_nodes().to_vec();
+            if lhs_children.len() != rhs_children.len() {
+                return false;
+            }
+            pending.extend(lhs_children.into_iter().zip(rhs_children));
+        }
+        true
     }
 }
 
@@ -1121,17 +1568,13 @@ impl ProcessingInstruction for RefNode {}
 
 impl Text for RefNode {
     fn split(&mut self, offset: usize) -> Result<RefNode> {
+        check_writable(self)?;
         let new_data = {
             let text = as_character_data_mut(self)?;
-            let length = text.length();
-            if offset >= length {
-                String::new()
-            } else {
-                let count = length - offset;
-                let new_data = text.substring_data(offset, count)?;
-                text.delete_data(offset, count)?;
-                new_data
-            }
+            let count = text.length().saturating_sub(offset);
+            let new_data = text.substring_data(offset, count)?;
+            text.delete_data(offset, count)?;
+            new_data
         };
 
         let new_node = {
@@ -1161,6 +1604,15 @@ impl Text for RefNode {
         }
         Ok(new_node)
     }
+
+    fn is_element_content_whitespace(&self) -> bool {
+        self.borrow()
+            .i_value
+            .as_deref()
+            .unwrap_or_default()
+            .chars()
+            .all(text::is_xml_space)
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -1171,16 +1623,122 @@ impl Display for RefNode {
     }
 }
 
+// ------------------------------------------------------------------------------------------------
+
+impl From<NodeType> for u16 {
+    fn from(node_type: NodeType) -> Self {
+        node_type as u16
+    }
+}
+
+impl TryFrom<u16> for NodeType {
+    type Error = Error;
+
+    fn try_from(code: u16) -> Result<Self> {
+        Ok(match code {
+            1 => NodeType::Element,
+            2 => NodeType::Attribute,
+            3 => NodeType::Text,
+            4 => NodeType::CData,
+            5 => NodeType::EntityReference,
+            6 => NodeType::Entity,
+            7 => NodeType::ProcessingInstruction,
+            8 => NodeType::Comment,
+            9 => NodeType::Document,
+            10 => NodeType::DocumentType,
+            11 => NodeType::DocumentFragment,
+            12 => NodeType::Notation,
+            _ => {
+                warn!("{}", MSG_INVALID_NODE_TYPE_CODE);
+                return Err(Error::NotSupported);
+            }
+        })
+    }
+}
+
 // ------------------------------------------------------------------------------------------------
 // Private Functions
 // ------------------------------------------------------------------------------------------------
 
 const WILD_CARD: &str = "*";
 
+//
+// Whether `feature`, which is case-insensitive and may have a leading `'+'`, is enabled in
+// `version`, or in any version if `version` is empty.
+//
+fn has_feature(feature: &str, version: &str) -> bool {
+    let feature = feature
+        .strip_prefix(XML_FEATURE_REQUIRED)
+        .unwrap_or(feature);
+    FEATURES.iter().any(|entry| {
+        entry.enabled
+            && entry.name.eq_ignore_ascii_case(feature)
+            && (version.is_empty() || entry.versions.contains(&version))
+    })
+}
+
 fn tag_name_match(test: &str, against: &str) -> bool {
     (test == against) || test == WILD_CARD || against == WILD_CARD
 }
 
+//
+// Does the document that owns `node` treat plain `id` attributes as IDs.
+//
+fn assume_ids(node: &RefNode) -> bool {
+    match node.owner_document() {
+        None => false,
+        Some(document) => {
+            let ref_document = document.borrow();
+            if let Extension::Document { i_options, .. } = &ref_document.i_extension {
+                i_options.has_assume_ids()
+            } else {
+                warn!("{}", MSG_INVALID_EXTENSION);
+                false
+            }
+        }
+    }
+}
+
+//
+// Return all the elements below `node`, not including `node` itself, that match `predicate`, in
+// the order they are found by a pre-order traversal.
+//
+fn descendant_elements<P>(node: &RefNode, predicate: P) -> Vec<RefNode>
+where
+    P: Fn(&RefNode) -> bool,
+{
+    node.descendant_elements()
+        .filter(|element| predicate(element))
+        .collect()
+}
+
+//
+// Match an element against a namespace URI and local name, ignoring the prefix. Where the element
+// name does not carry a namespace URI it is resolved from the in-scope namespace declarations.
+//
+fn element_name_ns_match(element: &RefNode, namespace_uri: &str, local_name: &str) -> bool {
+    let name = element.borrow().i_name.clone();
+    let element_namespace_uri = match name.namespace_uri() {
+        Some(namespace_uri) => Some(namespace_uri.clone()),
+        None => element.resolve_namespace(name.prefix().as_deref()),
+    };
+    namespaced_name_match(
+        element_namespace_uri.as_deref(),
+        name.local_name(),
+        namespace_uri,
+        local_name,
+    )
+}
+
+//
+// Match an attribute name against a namespace URI and local name, ignoring the prefix. An empty
+// namespace URI matches a name with no namespace.
+//
+fn name_ns_match(name: &Name, namespace_uri: &str, local_name: &str) -> bool {
+    name.namespace_uri().as_deref().unwrap_or_default() == namespace_uri
+        && name.local_name() == local_name
+}
+
 fn namespaced_name_match(
     test_ns: Option<&str>,
     test_local: &str,
@@ -1189,7 +1747,7 @@ fn namespaced_name_match(
 ) -> bool {
     match test_ns {
         None => {
-            against_ns == WILD_CARD
+            (against_ns.is_empty() || against_ns == WILD_CARD)
                 && ((test_local == against_local)
                     || test_local == WILD_CARD
                     || against_local == WILD_CARD)
@@ -1208,125 +1766,1240 @@ fn namespaced_name_match(
 // document than the one that created this node.
 //
 fn check_same_document(self_node: &RefNode, new_child: &RefNode) -> Result<()> {
-    {
-        if self_node.node_type() == NodeType::Document {
-            let child_document = &new_child.borrow().i_owner_document;
-            if !match child_document {
-                None => true,
-                Some(child_document) => {
-                    let child_document = child_document.clone().upgrade().unwrap();
-                    self_node == &child_document
+    let child_document = new_child.borrow().i_owner_document.clone();
+    //
+    // Compare the weak references themselves, as the document may already have been dropped.
+    //
+    let is_same_document = match child_document {
+        None => true,
+        Some(child_document) => {
+            let self_document = if is_document(self_node) {
+                Some(self_node.clone().downgrade())
+            } else {
+                self_node.borrow().i_owner_document.clone()
+            };
+            match self_document {
+                None => false,
+                Some(self_document) => {
+                    WeakShared::ptr_eq(self_document.as_inner(), child_document.as_inner())
                 }
-            } {
-                warn!("Error::WrongDocument: child could not be added to the document node.");
-                return Err(Error::WrongDocument);
             }
-        } else {
-            let self_document = &self_node.borrow().i_owner_document;
-            let child_document = &new_child.borrow().i_owner_document;
-            if !match (self_document, child_document) {
-                (None, None) => true,
-                (Some(_), None) => true,
-                (None, Some(_)) => false,
-                (Some(self_document), Some(child_document)) => {
-                    let self_document = self_document.clone().upgrade().unwrap();
-                    let child_document = child_document.clone().upgrade().unwrap();
-                    self_document == child_document
+        }
+    };
+    if is_same_document {
+        Ok(())
+    } else {
+        warn!("{}", MSG_WRONG_DOCUMENT);
+        Err(Error::WrongDocument)
+    }
+}
+//
+// Create a copy of `node` owned by `owner_document`, with no parent. Attributes of an element are
+// always copied, by value, and attribute values are always copied regardless of `deep`; when
+// importing only the reference itself is copied for an entity reference.
+//
+fn copy_node(
+    node: &RefNode,
+    owner_document: Option<WeakRefNode>,
+    deep: bool,
+    import: bool,
+) -> Result<RefNode> {
+    let (new_node, attribute_nodes, child_nodes) = {
+        let ref_node = node.borrow();
+        let mut new_node = ref_node.clone_node(false);
+        new_node.i_owner_document = owner_document.clone();
+        let attribute_nodes: Vec<RefNode> = match &mut new_node.i_extension {
+            Extension::Element { i_attributes, .. } => i_attributes
+                .drain()
+                .map(|(_, attribute)| attribute)
+                .filter(|attribute| as_attribute(attribute).unwrap().specified())
+                .collect(),
+            Extension::Attribute {
+                i_owner_element, ..
+            } => {
+                *i_owner_element = None;
+                Vec::default()
+            }
+            Extension::DocumentType {
+                i_entities,
+                i_notations,
+                ..
+            } => {
+                for entity in i_entities.values_mut() {
+                    *entity = copy_node(entity, owner_document.clone(), true, import)?;
+                }
+                for notation in i_notations.values_mut() {
+                    *notation = copy_node(notation, owner_document.clone(), true, import)?;
                 }
-            } {
-                warn!("Error::WrongDocument: child could not be added to the current node.");
-                return Err(Error::WrongDocument);
+                Vec::default()
+            }
+            _ => Vec::default(),
+        };
+        let child_nodes = match ref_node.i_node_type {
+            NodeType::Attribute => ref_node.i_child_nodes.clone(),
+            NodeType::EntityReference if import => Vec::default(),
+            _ if deep => ref_node.i_child_nodes.clone(),
+            _ => Vec::default(),
+        };
+        (RefNode::new(new_node), attribute_nodes, child_nodes)
+    };
+    for attribute_node in attribute_nodes {
+        let new_attribute = copy_node(&attribute_node, owner_document.clone(), true, import)?;
+        {
+            let mut mut_attribute = new_attribute.borrow_mut();
+            if let Extension::Attribute {
+                i_owner_element, ..
+            } = &mut mut_attribute.i_extension
+            {
+                *i_owner_element = Some(new_node.clone().downgrade());
             }
         }
+        let name = new_attribute.node_name();
+        let mut mut_node = new_node.borrow_mut();
+        if let Extension::Element { i_attributes, .. } = &mut mut_node.i_extension {
+            let _safe_to_ignore = i_attributes.insert(name, new_attribute);
+        }
     }
-    Ok(())
+    //
+    // Defaulted attributes are not copied, instead the defaults of the owner document apply.
+    //
+    if new_node.node_type() == NodeType::Element {
+        add_default_attributes(&mut new_node.clone())?;
+    }
+    for child_node in child_nodes {
+        let new_child = copy_node(&child_node, owner_document.clone(), deep, import)?;
+        new_child.borrow_mut().i_parent_node = Some(new_node.clone().downgrade());
+        new_node.borrow_mut().i_child_nodes.push(new_child);
+    }
+    //
+    // The copy itself is writable, but the children of a copied entity reference are not.
+    //
+    if makes_read_only(&new_node) {
+        for child_node in new_node.child_nodes() {
+            set_read_only(&child_node, true);
+        }
+    }
+    let operation = if import {
+        UserDataOperation::NodeImported
+    } else {
+        UserDataOperation::NodeCloned
+    };
+    notify_user_data_handlers(node, operation, Some(&new_node));
+    Ok(new_node)
 }
+
 //
-// From [https://www.w3.org/TR/DOM-Level-2-Core/core.html#ID-1590626202]
+// Create a copy of the `document` node; if `deep` the copy will also own copies of the document
+// type and all child nodes.
 //
-// The DOM presents documents as a hierarchy of Node objects that also implement other, more
-// specialized interfaces. Some types of nodes may have child nodes of various types, and others
-// are leaf nodes that cannot have anything below them in the document structure. For XML and HTML,
-// the node types, and which node types they may have as children, are as follows:
+fn clone_document(document: &RefNode, deep: bool) -> Result<RefNode> {
+    let new_document = {
+        let mut new_document = document.borrow().clone_node(false);
+        if let Extension::Document {
+            i_document_type, ..
+        } = &mut new_document.i_extension
+        {
+            *i_document_type = None;
+        }
+        RefNode::new(new_document)
+    };
+    if deep {
+        let owner_document = Some(new_document.clone().downgrade());
+        for child_node in document.child_nodes() {
+            let new_child = copy_node(&child_node, owner_document.clone(), true, false)?;
+            new_child.borrow_mut().i_parent_node = owner_document.clone();
+            if is_document_type(&new_child) {
+                set_document_type(&new_document, Some(new_child.clone()));
+            }
+            new_document.borrow_mut().i_child_nodes.push(new_child);
+        }
+    }
+    notify_user_data_handlers(document, UserDataOperation::NodeCloned, Some(&new_document));
+    Ok(new_document)
+}
+
 //
-// * Document -- Element (maximum of one), ProcessingInstruction, Comment, DocumentType (maximum of one)
-// * DocumentFragment -- Element, ProcessingInstruction, Comment, Text, CDATASection, EntityReference
-// * DocumentType -- no children
-// * EntityReference -- Element, ProcessingInstruction, Comment, Text, CDATASection, EntityReference
-// * Element -- Element, Text, Comment, ProcessingInstruction, CDATASection, EntityReference
-// * Attr -- Text, EntityReference
-// * ProcessingInstruction -- no children
-// * Comment -- no children
-// * Text -- no children
-// * CDATASection -- no children
-// * Entity -- Element, ProcessingInstruction, Comment, Text, CDATASection, EntityReference
-// * Notation -- no children
+// Call the handler for each item of user data on `node`; the handlers are collected first so
+// that they may safely access `node`.
 //
-fn is_child_allowed(parent: &RefNode, child: &RefNode) -> bool {
-    let self_node_type = { &parent.borrow().i_node_type };
-    let child_node_type = { &child.borrow().i_node_type };
-    match self_node_type {
-        NodeType::Element => match child_node_type {
-            NodeType::Element
-            | NodeType::Text
-            | NodeType::Comment
-            | NodeType::ProcessingInstruction
-            | NodeType::CData
-            | NodeType::EntityReference => true,
-            _ => false,
-        },
-        NodeType::Attribute => match child_node_type {
-            NodeType::Text | NodeType::EntityReference => true,
-            _ => false,
-        },
-        NodeType::Text => false,
-        NodeType::CData => false,
-        NodeType::EntityReference => match child_node_type {
-            NodeType::Element
-            | NodeType::Text
-            | NodeType::Comment
-            | NodeType::ProcessingInstruction
-            | NodeType::CData
-            | NodeType::EntityReference => true,
-            _ => false,
+fn notify_user_data_handlers(
+    node: &RefNode,
+    operation: UserDataOperation,
+    destination: Option<&RefNode>,
+) {
+    let handled = node.borrow().i_user_data.handled();
+    for (key, data, handler) in handled {
+        handler.handle(operation, &key, &data, Some(node), destination);
+    }
+}
+
+//
+// Convert the `char` based `offset` and `count` used by `CharacterData` into a byte range within
+// `data`; a range running past the end of the data is clamped to the end.
+//
+fn char_range(data: &str, offset: usize, count: usize) -> Result<Range<usize>> {
+    let byte_index = |index: usize| {
+        data.char_indices()
+            .nth(index)
+            .map(|(byte_index, _)| byte_index)
+            .unwrap_or_else(|| data.len())
+    };
+    if offset > data.chars().count() {
+        warn!("{}", MSG_INDEX_ERROR);
+        Err(Error::IndexSize)
+    } else {
+        Ok(byte_index(offset)..byte_index(offset.saturating_add(count)))
+    }
+}
+
+//
+// Share `name` with any other node of `document` with an equal name. If the document is borrowed
+// the name is simply not shared.
+//
+fn intern_name(document: &RefNode, name: Name) -> Name {
+    match document.as_inner().try_borrow_mut() {
+        Some(mut mut_document) => match &mut mut_document.i_extension {
+            Extension::Document { i_name_pool, .. } => i_name_pool.intern(name),
+            _ => name,
         },
-        NodeType::Entity => match child_node_type {
+        None => name,
+    }
+}
+
+//
+// Fail with `Error::NoModificationAllowed` if `node` is read-only, or with `Error::Borrowed` if it
+// is already borrowed and so cannot be modified.
+//
+pub(crate) fn check_writable(node: &RefNode) -> Result<()> {
+    if node.try_borrow_mut()?.i_read_only {
+        warn!("{}", MSG_READ_ONLY);
+        Err(Error::NoModificationAllowed)
+    } else {
+        Ok(())
+    }
+}
+
+//
+// The index of `child` within the children of `parent`, if it is a child of `parent`.
+//
+fn child_position(parent: &RefNode, child: &RefNode) -> Option<usize> {
+    if parent == child {
+        None
+    } else {
+        child
+            .borrow()
+            .i_index_hint
+            .position_in(&parent.borrow().i_child_nodes, child)
+    }
+}
+
+//
+// Create a node using the owner document of `parent`, and append it; used by the `append_*`
+// methods of `Element`.
+//
+fn append_created<F>(parent: &mut RefNode, create: F) -> Result<RefNode>
+where
+    F: FnOnce(&RefNode) -> Result<RefNode>,
+{
+    match parent.owner_document() {
+        None => {
+            warn!("{}", MSG_WEAK_REF);
+            Err(Error::WrongDocument)
+        }
+        Some(document_node) => {
+            let new_node = create(&document_node)?;
+            parent.append_child(new_node)
+        }
+    }
+}
+
+//
+// Append each of `nodes` to `parent`, as `append_child` would, but checking that `parent` is
+// writable, and finding its ancestors, only once. A document, attribute, document fragment, or
+// document type has further checks, and so is appended with `append_child`. On an error the
+// nodes already appended are kept.
+//
+pub(crate) fn append_children<I>(parent: &mut RefNode, nodes: I) -> Result<()>
+where
+    I: IntoIterator<Item = RefNode>,
+{
+    if is_document(parent) || is_attribute(parent) {
+        for node in nodes {
+            let _safe_to_ignore = parent.append_child(node)?;
+        }
+        return Ok(());
+    }
+    check_writable(parent)?;
+    let ancestors: Vec<RefNode> = parent.ancestors().collect();
+    let owner_document = parent.borrow().i_owner_document.clone();
+    let read_only = makes_read_only(parent);
+    for node in nodes {
+        if is_document_fragment(&node) || is_document_type(&node) {
+            let _safe_to_ignore = parent.append_child(node)?;
+            continue;
+        }
+        check_appendable(parent, &node, &ancestors)?;
+        {
+            let mut mut_node = node.borrow_mut();
+            mut_node.i_parent_node = Some(parent.clone().downgrade());
+            mut_node.i_owner_document = owner_document.clone();
+        }
+        if read_only {
+            set_read_only(&node, true);
+        }
+        {
+            let mut mut_parent = parent.borrow_mut();
+            node.borrow()
+                .i_index_hint
+                .set(mut_parent.i_child_nodes.len());
+            mut_parent.i_child_nodes.push(node.clone());
+        }
+        notify_children_change(parent, value_change(parent), vec![node], true);
+    }
+    Ok(())
+}
+
+//
+// The checks of `insert_before` for a `node` to be appended to `parent`, which has `ancestors`;
+// if these pass `node` is removed from its current parent.
+//
+fn check_appendable(parent: &RefNode, node: &RefNode, ancestors: &[RefNode]) -> Result<()> {
+    check_unborrowed(node)?;
+    if strict_error_checking(parent) && !is_child_allowed(parent, node) {
+        warn!("{}", MSG_INVALID_CHILD);
+        return Err(Error::HierarchyRequest);
+    }
+    if node == parent || ancestors.contains(node) {
+        warn!("{}", MSG_CYCLIC_CHILD);
+        return Err(Error::HierarchyRequest);
+    }
+    check_same_document(parent, node)?;
+    if let Some(mut old_parent) = node.parent_node() {
+        let _safe_to_ignore = old_parent.remove_child(node.clone())?;
+    }
+    Ok(())
+}
+
+//
+// Remove each child of `parent` for which `predicate` returns `false`, as `remove_child` would,
+// returning the removed children in order. The predicate is called, for every child, before any
+// is removed.
+//
+pub(crate) fn retain_children<F>(parent: &mut RefNode, mut predicate: F) -> Result<Vec<RefNode>>
+where
+    F: FnMut(&RefNode) -> bool,
+{
+    check_writable(parent)?;
+    let child_nodes = parent.child_nodes().to_vec();
+    let (retained, removed): (Vec<RefNode>, Vec<RefNode>) =
+        child_nodes.into_iter().partition(|child| predicate(child));
+    if removed.is_empty() {
+        return Ok(removed);
+    }
+    for child in &removed {
+        check_unborrowed(child)?;
+    }
+    for child in &removed {
+        notify_removal(parent, child);
+    }
+    let change = value_change(parent);
+    set_children(parent, retained);
+    let read_only = makes_read_only(parent);
+    for child in &removed {
+        child.borrow_mut().i_parent_node = None;
+        if is_document(parent) && is_document_type(child) {
+            set_document_type(parent, None);
+        }
+        if read_only {
+            set_read_only(child, false);
+        }
+    }
+    notify_children_change(parent, change, removed.clone(), false);
+    Ok(removed)
+}
+
+//
+// Reorder the children of `parent` with a stable sort using `compare`. A child that moves is
+// reported, to removal observers and listeners, as if it were removed and then inserted again.
+//
+pub(crate) fn sort_children_by<F>(parent: &mut RefNode, mut compare: F) -> Result<()>
+where
+    F: FnMut(&RefNode, &RefNode) -> Ordering,
+{
+    check_writable(parent)?;
+    let child_nodes = parent.child_nodes().to_vec();
+    let mut sorted = child_nodes.clone();
+    sorted.sort_by(|lhs, rhs| compare(lhs, rhs));
+    let moved: Vec<RefNode> = child_nodes
+        .iter()
+        .zip(sorted.iter())
+        .filter(|(before, after)| before != after)
+        .map(|(before, _)| before.clone())
+        .collect();
+    if moved.is_empty() {
+        return Ok(());
+    }
+    if is_document(parent) {
+        let element = sorted.iter().position(is_element);
+        let document_type = sorted.iter().position(is_document_type);
+        if let (Some(element), Some(document_type)) = (element, document_type) {
+            if document_type > element {
+                warn!("{}", MSG_MISPLACED_DOCUMENT_TYPE);
+                return Err(Error::HierarchyRequest);
+            }
+        }
+    }
+    for child in &moved {
+        notify_removal(parent, child);
+    }
+    let change = value_change(parent);
+    notify_children_change(parent, change.clone(), moved.clone(), false);
+    set_children(parent, sorted);
+    notify_children_change(parent, change, moved, true);
+    Ok(())
+}
+
+//
+// Replace the children of `parent` with `child_nodes`, which must all have `parent` as their
+// parent, updating the index hint of each.
+//
+fn set_children(parent: &RefNode, child_nodes: Vec<RefNode>) {
+    for (index, child) in child_nodes.iter().enumerate() {
+        child.borrow().i_index_hint.set(index);
+    }
+    parent.borrow_mut().i_child_nodes = child_nodes;
+}
+
+//
+// Fail with `Error::Borrowed` if `node` is already borrowed, and so cannot be modified; this is
+// checked before any change is made so that an operation does not fail part way through.
+//
+fn check_unborrowed(node: &RefNode) -> Result<()> {
+    node.try_borrow_mut().map(|_| ())
+}
+
+//
+// Check that `data` can be serialized as the content of a node of `node_type`; comments,
+// CDATA sections and processing instructions may not contain their own terminators.
+//
+// `Comment  ::=  '<!--' ((Char - '-') | ('-' (Char - '-')))* '-->'`
+//
+fn check_data(node_type: &NodeType, data: &str, strict: bool) -> Result<()> {
+    check_chars(strict, data)?;
+    match node_type {
+        NodeType::Comment
+            if data.contains(XML_COMMENT_DOUBLE_HYPHEN) || data.ends_with(XML_COMMENT_HYPHEN) =>
+        {
+            warn!("{}", MSG_INVALID_COMMENT_DATA);
+            Err(Error::InvalidCharacter)
+        }
+        NodeType::CData if data.contains(XML_CDATA_END) => {
+            warn!("{}", MSG_INVALID_CDATA_DATA);
+            Err(Error::InvalidCharacter)
+        }
+        NodeType::ProcessingInstruction if data.contains(XML_PI_END) => {
+            warn!("{}", MSG_INVALID_PI_DATA);
+            Err(Error::InvalidCharacter)
+        }
+        _ => Ok(()),
+    }
+}
+
+//
+// Parse `name` as `Name::from_str` would, unless `strict` is `false` in which case it is not
+// validated.
+//
+fn parse_name(strict: bool, name: &str) -> Result<Name> {
+    if strict {
+        Name::from_str(name)
+    } else {
+        Ok(Name::new_unchecked(name))
+    }
+}
+
+//
+// Parse `qualified_name` as `Name::new_ns` would, unless `strict` is `false` in which case neither
+// the name nor its namespace is validated.
+//
+fn parse_name_ns(strict: bool, namespace_uri: &str, qualified_name: &str) -> Result<Name> {
+    if strict {
+        Name::new_ns(namespace_uri, qualified_name)
+    } else {
+        Ok(Name::new_ns_unchecked(namespace_uri, qualified_name))
+    }
+}
+
+//
+// Ensure each character of `data` is legal in XML 1.0, unless `strict` is `false`.
+//
+fn check_chars(strict: bool, data: &str) -> Result<()> {
+    if strict && !data.chars().all(text::is_xml_10_char) {
+        warn!("{}", MSG_INVALID_XML_CHARACTER);
+        Err(Error::InvalidCharacter)
+    } else {
+        Ok(())
+    }
+}
+
+//
+// Whether error checking is enforced by the document that owns `node`, or by `node` itself if it
+// is a document; this is `true` if the owner document has been dropped.
+//
+fn strict_error_checking(node: &RefNode) -> bool {
+    if is_document(node) {
+        node.strict_error_checking()
+    } else {
+        match node.owner_document() {
+            None => true,
+            Some(document) => document.strict_error_checking(),
+        }
+    }
+}
+
+//
+// Whether the children of `node` are read-only; this is true for the descendants of entity, and
+// entity reference, nodes.
+//
+fn makes_read_only(node: &RefNode) -> bool {
+    let ref_node = node.borrow();
+    ref_node.i_read_only
+        || matches!(
+            ref_node.i_node_type,
+            NodeType::Entity | NodeType::EntityReference
+        )
+}
+
+//
+// Set the read-only flag on `node`, its descendants, and any attributes; notation nodes are always
+// read-only. This walks the subtree without recursion.
+//
+fn set_read_only(node: &RefNode, read_only: bool) {
+    let mut pending = vec![node.clone()];
+    while let Some(node) = pending.pop() {
+        let mut mut_node = node.borrow_mut();
+        mut_node.i_read_only = read_only || mut_node.i_node_type == NodeType::Notation;
+        pending.extend(mut_node.i_child_nodes.iter().cloned());
+        if let Extension::Element { i_attributes, .. } = &mut_node.i_extension {
+            pending.extend(i_attributes.values().cloned());
+        }
+    }
+}
+
+//
+// Check that `new_attribute` may be added to `element`, which must be writable, from the same
+// document, and not already owned by a different element.
+//
+fn check_attribute_node(element: &RefNode, new_attribute: &RefNode) -> Result<()> {
+    if !is_element(element) || !is_attribute(new_attribute) {
+        warn!("{}", MSG_INVALID_NODE_TYPE);
+        return Err(Error::InvalidState);
+    }
+    check_writable(element)?;
+    check_unborrowed(new_attribute)?;
+    check_same_document(element, new_attribute)?;
+    match new_attribute.owner_element() {
+        Some(owner_element) if &owner_element != element => {
+            warn!("{}", MSG_ATTRIBUTE_IN_USE);
+            Err(Error::InUseAttribute)
+        }
+        _ => Ok(()),
+    }
+}
+
+//
+// Make `element` the owner of `new_attribute`, in place of `old_attribute` if present, and return
+// the attribute replaced, if any. The replacement takes the position of the old attribute.
+//
+fn attach_attribute(
+    element: &mut RefNode,
+    new_attribute: RefNode,
+    old_attribute: Option<RefNode>,
+) -> Result<Option<RefNode>> {
+    let old_attribute = old_attribute.filter(|old_attribute| old_attribute != &new_attribute);
+    //
+    // Re-attaching an attribute in place, as `set_prefix` does, does not change its value.
+    //
+    let is_new = new_attribute.owner_element().as_ref() != Some(element);
+    let old_value = old_attribute.as_ref().and_then(attribute_value);
+    if let Some(old_attribute) = &old_attribute {
+        set_owner_element(old_attribute, None);
+    }
+    //
+    // Set the attribute's owner. This is *not* the same as parent which remains `None`.
+    //
+    set_owner_element(&new_attribute, Some(element.clone().downgrade()));
+
+    let name: Name = new_attribute.node_name();
+    if name.is_namespace_attribute() {
+        //
+        // Add to the element's namespace mapping hash
+        //
+        let attribute = as_attribute(&new_attribute).unwrap();
+        let namespace_uri = attribute.value().unwrap();
+
+        let as_namespaced = as_element_namespaced_mut(element).unwrap();
+        let _ignore = match &name.prefix() {
+            None => as_namespaced.insert_mapping(None, &namespace_uri),
+            Some(_) => as_namespaced.insert_mapping(Some(name.local_name()), &namespace_uri),
+        }?;
+    }
+
+    let mut mut_element = element.borrow_mut();
+    if let Extension::Element { i_attributes, .. } = &mut mut_element.i_extension {
+        //
+        // Replacing in place keeps the attribute order; an attribute renamed by `set_attribute_ns`
+        // is already present, and so replaces itself.
+        //
+        let in_place_of = old_attribute.as_ref().unwrap_or(&new_attribute).clone();
+        i_attributes.replace(&in_place_of, name.clone(), new_attribute.clone());
+        drop(mut_element);
+        if is_new {
+            notify_mutation(
+                element,
+                Mutation::AttributeChanged {
+                    element: element.clone(),
+                    name,
+                    old_value,
+                    new_value: attribute_value(&new_attribute),
+                },
+            );
+        }
+        Ok(old_attribute)
+    } else {
+        warn!("{}", MSG_INVALID_EXTENSION);
+        Err(Error::Syntax)
+    }
+}
+
+//
+// Add an unspecified attribute to `element` for each attribute declared with a default value for
+// its element type in the document's DTD, unless the element already has that attribute.
+//
+pub(crate) fn add_default_attributes(element: &mut RefNode) -> Result<()> {
+    let element_name = element.node_name().to_string();
+    for attribute_decl in attribute_decls_for(element, &element_name) {
+        if let Some(value) = attribute_decl.default().value() {
+            let name = attribute_decl.attribute_name().to_string();
+            if element.get_attribute_node(&name).is_none() {
+                let owner_document = element.borrow().i_owner_document.clone().unwrap();
+                let mut node_impl = NodeImpl::new_attribute(
+                    owner_document,
+                    attribute_decl.attribute_name().clone(),
+                    Some(value),
+                );
+                if let Extension::Attribute { i_specified, .. } = &mut node_impl.i_extension {
+                    *i_specified = false;
+                }
+                let _safe_to_ignore = attach_attribute(element, RefNode::new(node_impl), None)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+//
+// Remove the unspecified attributes of `element`, added for the defaults of its previous name or
+// document, and add those for its current name and document.
+//
+fn reset_default_attributes(element: &mut RefNode) -> Result<()> {
+    {
+        let mut mut_element = element.borrow_mut();
+        if let Extension::Element { i_attributes, .. } = &mut mut_element.i_extension {
+            let defaults: Vec<Name> = i_attributes
+                .iter()
+                .filter(|(_, attribute_node)| !attribute_node.specified())
+                .map(|(name, _)| name.clone())
+                .collect();
+            for name in defaults {
+                if let Some(attribute_node) = i_attributes.remove(&name) {
+                    set_owner_element(&attribute_node, None);
+                }
+            }
+        }
+    }
+    add_default_attributes(element)
+}
+
+//
+// Check that copying `roots`, each with the names of the entity references it is within, keeps
+// within the expansion limits of `node`'s document; only nodes within a reference are counted.
+//
+pub(crate) fn check_expansion(node: &RefNode, roots: Vec<(RefNode, Vec<Name>)>) -> Result<()> {
+    let limits = expansion_limits(node);
+    let mut count = 0;
+    let mut pending = roots;
+    while let Some((node, mut enclosing)) = pending.pop() {
+        if node.node_type() == NodeType::EntityReference {
+            let name = node.node_name();
+            if enclosing.contains(&name) {
+                warn!("{}", MSG_ENTITY_REFERENCE_LOOP);
+                return Err(Error::EntityReferenceLoop);
+            }
+            if enclosing.len() >= limits.max_depth() {
+                warn!("{}", MSG_ENTITY_EXPANSION_DEPTH);
+                return Err(Error::EntityExpansionDepth);
+            }
+            enclosing.push(name);
+        } else if !enclosing.is_empty() {
+            count += 1;
+            if count > limits.max_nodes() {
+                warn!("{}", MSG_ENTITY_EXPANSION_SIZE);
+                return Err(Error::EntityExpansionSize);
+            }
+        }
+        for child_node in node.child_nodes() {
+            pending.push((child_node, enclosing.clone()));
+        }
+    }
+    Ok(())
+}
+
+//
+// The expansion limits of the document of `node`, or of `node` if it is a document.
+//
+pub(crate) fn expansion_limits(node: &RefNode) -> ExpansionLimits {
+    let document_node = if is_document(node) {
+        Some(node.clone())
+    } else {
+        node.owner_document()
+    };
+    match document_node {
+        None => Default::default(),
+        Some(document_node) => {
+            let ref_document = document_node.borrow();
+            if let Extension::Document {
+                i_expansion_limits, ..
+            } = &ref_document.i_extension
+            {
+                i_expansion_limits.clone()
+            } else {
+                warn!("{}", MSG_INVALID_EXTENSION);
+                Default::default()
+            }
+        }
+    }
+}
+
+//
+// The DTD declarations for the element type `element_name`, in the document type of `node`'s
+// owner document.
+//
+pub(crate) fn attribute_decls_for(node: &RefNode, element_name: &str) -> Vec<AttributeDecl> {
+    let doc_type = node.owner_document().and_then(|document_node| {
+        let document = as_document(&document_node).ok()?;
+        document.doc_type()
+    });
+    match &doc_type {
+        None => Vec::new(),
+        Some(doc_type) => unwrap_extension_field!(doc_type, DocumentType, i_attribute_decls)
+            .into_iter()
+            .filter(|attribute_decl| attribute_decl.element_name().to_string() == element_name)
+            .collect(),
+    }
+}
+
+//
+// Is `attribute` declared with the type `ID` in the document's DTD.
+//
+fn is_declared_id(attribute: &RefNode) -> bool {
+    match attribute.owner_element() {
+        None => false,
+        Some(element) => {
+            let attribute_name = attribute.node_name().to_string();
+            attribute_decls_for(attribute, &element.node_name().to_string())
+                .iter()
+                .any(|attribute_decl| {
+                    *attribute_decl.attribute_type() == AttributeType::Id
+                        && attribute_decl.attribute_name().to_string() == attribute_name
+                })
+        }
+    }
+}
+
+//
+// The value of `attribute` after attribute-value normalization, but before the escaping applied
+// by `Attribute::value`.
+//
+pub(crate) fn normalized_value(attribute: &RefNode) -> Option<String> {
+    if attribute.has_child_nodes() {
+        let mut result = String::new();
+        for child_node in attribute.child_nodes() {
+            if child_node.node_type() == NodeType::EntityReference {
+                if let Some(value) = child_node.node_value() {
+                    result.push_str(&value);
+                }
+            } else if child_node.node_type() == NodeType::Text {
+                //
+                // Do not use the Text::data function as this will escape the response.
+                //
+                let ref_node = child_node.borrow();
+                if let Some(data) = &ref_node.i_value {
+                    result.push_str(data);
+                }
+            }
+        }
+        Some(text::normalize_attribute_value(&result, attribute, false))
+    } else {
+        None
+    }
+}
+
+//
+// Add the namespace declarations of `element` to `namespaces`; as well as `xmlns` attributes
+// this includes the namespace of the element, and its attributes, when these were created with a
+// namespace URI but no corresponding declaration.
+//
+pub(crate) fn add_namespace_declarations(element: &RefNode, namespaces: &mut Namespaces) {
+    let attributes = element.borrow().attribute_nodes();
+    for attribute in &attributes {
+        let name = attribute.node_name();
+        if is_namespace_declaration(&name) {
+            let prefix = match name.prefix() {
+                Some(_) => name.local_name().clone(),
+                None => String::new(),
+            };
+            let namespace_uri = normalized_value(attribute).unwrap_or_default();
+            let _safe_to_ignore = namespaces.insert(prefix, namespace_uri);
+        }
+    }
+    let name = element.node_name();
+    if let Some(namespace_uri) = name.namespace_uri() {
+        let prefix = name.prefix().clone().unwrap_or_default();
+        let _safe_to_ignore = namespaces.insert(prefix, namespace_uri.clone());
+    }
+    for attribute in &attributes {
+        let name = attribute.node_name();
+        if let (Some(namespace_uri), Some(prefix)) = (name.namespace_uri(), name.prefix()) {
+            if !is_namespace_declaration(&name) && prefix != XML_NS_ATTRIBUTE {
+                let _safe_to_ignore = namespaces.insert(prefix.clone(), namespace_uri.clone());
+            }
+        }
+    }
+}
+
+//
+// Add the namespace declarations of `element` to `in_scope`, and return those, as a prefix, empty
+// for the default namespace, and URI, that must be added for the namespaces of the element and
+// its attributes that are not in scope; these are also added to `in_scope`.
+//
+#[cfg(any(feature = "json", feature = "xml_rs"))]
+pub(crate) fn declare_namespaces(
+    element: &RefNode,
+    in_scope: &mut Namespaces,
+) -> Vec<(String, String)> {
+    let attributes = element.borrow().attribute_nodes();
+    for attribute_node in &attributes {
+        let name = attribute_node.node_name();
+        if is_namespace_declaration(&name) {
+            let prefix = match name.prefix() {
+                Some(_) => name.local_name().clone(),
+                None => String::new(),
+            };
+            let namespace_uri = normalized_value(attribute_node).unwrap_or_default();
+            let _safe_to_ignore = in_scope.insert(prefix, namespace_uri);
+        }
+    }
+    let mut added = Vec::new();
+    let mut require = |prefix: &str, namespace_uri: &str| {
+        if in_scope.get(prefix).map(String::as_str).unwrap_or_default() != namespace_uri {
+            let _safe_to_ignore = in_scope.insert(prefix.to_string(), namespace_uri.to_string());
+            added.push((prefix.to_string(), namespace_uri.to_string()));
+        }
+    };
+    let name = element.node_name();
+    match (name.prefix(), name.namespace_uri()) {
+        (prefix, Some(namespace_uri)) => {
+            require(prefix.as_deref().unwrap_or_default(), namespace_uri)
+        }
+        (None, None) => require("", ""),
+        (Some(_), None) => {}
+    }
+    for attribute_node in &attributes {
+        let name = attribute_node.node_name();
+        if let (Some(prefix), Some(namespace_uri)) = (name.prefix(), name.namespace_uri()) {
+            if !is_namespace_declaration(&name) && prefix != XML_NS_ATTRIBUTE {
+                require(prefix, namespace_uri);
+            }
+        }
+    }
+    added
+}
+
+#[cfg(any(feature = "json", feature = "xml_rs"))]
+pub(crate) fn declaration_name(prefix: &str) -> String {
+    if prefix.is_empty() {
+        XMLNS_NS_ATTRIBUTE.to_string()
+    } else {
+        format!("{}:{}", XMLNS_NS_ATTRIBUTE, prefix)
+    }
+}
+
+//
+// Unlike `Name::is_namespace_attribute` this does not require the `xmlns` namespace URI, which
+// is not set on attributes created by name, as the parser does.
+//
+pub(crate) fn is_namespace_declaration(name: &Name) -> bool {
+    match name.prefix() {
+        Some(prefix) => prefix == XMLNS_NS_ATTRIBUTE,
+        None => name.local_name() == XMLNS_NS_ATTRIBUTE,
+    }
+}
+
+//
+// Set, or clear, the owner of `attribute`.
+//
+fn set_owner_element(attribute: &RefNode, owner_element: Option<WeakRefNode>) {
+    let mut mut_attribute = attribute.borrow_mut();
+    if let Extension::Attribute {
+        i_owner_element, ..
+    } = &mut mut_attribute.i_extension
+    {
+        *i_owner_element = owner_element;
+    } else {
+        panic!("{}", MSG_INVALID_EXTENSION);
+    }
+}
+
+//
+// Run the checks of `insert_before` that `new_child` may be inserted into `parent` before
+// `ref_child`; a caller with changes of its own to make first, such as splitting a text node, can
+// then fail without having made them.
+//
+pub(crate) fn check_insert(
+    parent: &RefNode,
+    new_child: &RefNode,
+    ref_child: Option<&RefNode>,
+) -> Result<()> {
+    check_writable(parent)?;
+    check_unborrowed(new_child)?;
+    check_hierarchy(parent, new_child, None, ref_child)?;
+    check_same_document(parent, new_child)
+}
+
+//
+// Check that `new_child` may be added to `parent` (in place of `replacing`, if present) before
+// `ref_child`, or at the end if `None`, without breaking the rules for which node types may appear
+// where, creating a cycle, giving a document a second element or document type, or placing the
+// document type after the document element.
+//
+fn check_hierarchy(
+    parent: &RefNode,
+    new_child: &RefNode,
+    replacing: Option<&RefNode>,
+    ref_child: Option<&RefNode>,
+) -> Result<()> {
+    //
+    // A cycle is always checked for, even without strict error checking, as it would leave a tree
+    // that could not be traversed or dropped.
+    //
+    let strict = strict_error_checking(parent);
+    if strict && !is_child_allowed(parent, new_child) {
+        warn!("{}", MSG_INVALID_CHILD);
+        return Err(Error::HierarchyRequest);
+    }
+    if new_child == parent || parent.ancestors().any(|ancestor| &ancestor == new_child) {
+        warn!("{}", MSG_CYCLIC_CHILD);
+        return Err(Error::HierarchyRequest);
+    }
+    if strict && is_document(parent) {
+        let new_elements = if is_document_fragment(new_child) {
+            new_child.child_nodes().iter().filter(is_element).count()
+        } else {
+            usize::from(is_element(new_child))
+        };
+        if new_elements > 1
+            || (new_elements == 1
+                && parent.child_nodes().iter().any(|child| {
+                    is_element(&child) && Some(&child) != replacing && &child != new_child
+                }))
+        {
+            warn!("{}", MSG_SECOND_ROOT);
+            return Err(Error::HierarchyRequest);
+        }
+        //
+        // Split the remaining children at the insertion point, a document type must precede the
+        // document element.
+        //
+        let siblings = parent.child_nodes().to_vec();
+        let position = ref_child
+            .and_then(|ref_child| siblings.iter().position(|child| child == ref_child))
+            .unwrap_or(siblings.len());
+        let is_remaining = |child: &&RefNode| Some(*child) != replacing && *child != new_child;
+        let (preceding, following) = siblings.split_at(position);
+        if is_document_type(new_child) {
+            if siblings.iter().filter(is_remaining).any(is_document_type) {
+                warn!("{}", MSG_SECOND_DOCUMENT_TYPE);
+                return Err(Error::HierarchyRequest);
+            }
+            if preceding.iter().filter(is_remaining).any(is_element) {
+                warn!("{}", MSG_MISPLACED_DOCUMENT_TYPE);
+                return Err(Error::HierarchyRequest);
+            }
+        } else if new_elements == 1 && following.iter().filter(is_remaining).any(is_document_type) {
+            warn!("{}", MSG_MISPLACED_DOCUMENT_TYPE);
+            return Err(Error::HierarchyRequest);
+        }
+    }
+    Ok(())
+}
+
+//
+// Keep the document type returned by `Document::doc_type` in step with the children of `document`.
+//
+fn set_document_type(document: &RefNode, doc_type: Option<RefNode>) {
+    let mut mut_document = document.borrow_mut();
+    if let Extension::Document {
+        i_document_type, ..
+    } = &mut mut_document.i_extension
+    {
+        *i_document_type = doc_type;
+    } else {
+        warn!("{}", MSG_INVALID_EXTENSION);
+    }
+}
+
+fn set_document_uri(document: &mut RefNode, document_uri: Option<String>) -> Result<()> {
+    let mut mut_document = document.borrow_mut();
+    if let Extension::Document { i_document_uri, .. } = &mut mut_document.i_extension {
+        *i_document_uri = document_uri;
+        Ok(())
+    } else {
+        warn!("{}", MSG_INVALID_EXTENSION);
+        Err(Error::InvalidState)
+    }
+}
+
+//
+// From [https://www.w3.org/TR/DOM-Level-2-Core/core.html#ID-1590626202]
+//
+// The DOM presents documents as a hierarchy of Node objects that also implement other, more
+// specialized interfaces. Some types of nodes may have child nodes of various types, and others
+// are leaf nodes that cannot have anything below them in the document structure. For XML and HTML,
+// the node types, and which node types they may have as children, are as follows:
+//
+// * Document -- Element (maximum of one), ProcessingInstruction, Comment, DocumentType (maximum of one)
+// * DocumentFragment -- Element, ProcessingInstruction, Comment, Text, CDATASection, EntityReference
+// * DocumentType -- no children
+// * EntityReference -- Element, ProcessingInstruction, Comment, Text, CDATASection, EntityReference
+// * Element -- Element, Text, Comment, ProcessingInstruction, CDATASection, EntityReference
+// * Attr -- Text, EntityReference
+// * ProcessingInstruction -- no children
+// * Comment -- no children
+// * Text -- no children
+// * CDATASection -- no children
+// * Entity -- Element, ProcessingInstruction, Comment, Text, CDATASection, EntityReference
+// * Notation -- no children
+//
+// A document fragment is never added itself, so it is allowed wherever all of its children are
+// allowed, although never under a node that may have no children at all.
+//
+fn is_child_allowed(parent: &RefNode, child: &RefNode) -> bool {
+    let self_node_type = { &parent.borrow().i_node_type };
+    let child_node_type = { &child.borrow().i_node_type };
+    if *child_node_type == NodeType::DocumentFragment {
+        return !matches!(
+            self_node_type,
+            NodeType::Text
+                | NodeType::CData
+                | NodeType::ProcessingInstruction
+                | NodeType::Comment
+                | NodeType::DocumentType
+                | NodeType::Notation
+        ) && child
+            .child_nodes()
+            .iter()
+            .all(|grandchild| is_child_allowed(parent, &grandchild));
+    }
+    match self_node_type {
+        NodeType::Element
+        | NodeType::EntityReference
+        | NodeType::Entity
+        | NodeType::DocumentFragment => matches!(
+            child_node_type,
             NodeType::Element
-            | NodeType::Text
-            | NodeType::Comment
-            | NodeType::ProcessingInstruction
-            | NodeType::CData
-            | NodeType::EntityReference => true,
-            _ => false,
-        },
+                | NodeType::Text
+                | NodeType::Comment
+                | NodeType::ProcessingInstruction
+                | NodeType::CData
+                | NodeType::EntityReference
+        ),
+        NodeType::Attribute => {
+            matches!(child_node_type, NodeType::Text | NodeType::EntityReference)
+        }
+        NodeType::Text => false,
+        NodeType::CData => false,
         NodeType::ProcessingInstruction => false,
         NodeType::Comment => false,
-        NodeType::Document => match child_node_type {
-            NodeType::Element | NodeType::Comment | NodeType::ProcessingInstruction => true,
-            _ => false,
-        },
-        NodeType::DocumentType => false,
-        NodeType::DocumentFragment => match child_node_type {
+        NodeType::Document => matches!(
+            child_node_type,
             NodeType::Element
-            | NodeType::Text
-            | NodeType::Comment
-            | NodeType::ProcessingInstruction
-            | NodeType::CData
-            | NodeType::EntityReference => true,
-            _ => false,
-        },
+                | NodeType::Comment
+                | NodeType::ProcessingInstruction
+                | NodeType::DocumentType
+        ),
+        NodeType::DocumentType => false,
         NodeType::Notation => false,
     }
 }
 
+//
+// If `lhs` and `rhs` have the same set of names, add each pair of nodes with the same name to
+// `pending` for comparison and return `true`.
+//
+fn pair_named_nodes<L, R>(lhs: L, rhs: R, pending: &mut Vec<(RefNode, RefNode)>) -> bool
+where
+    L: IntoIterator<Item = (Name, RefNode)>,
+    R: IntoIterator<Item = (Name, RefNode)>,
+{
+    let mut rhs: HashMap<Name, RefNode> = rhs.into_iter().collect();
+    for (name, lhs_node) in lhs {
+        match rhs.remove(&name) {
+            None => return false,
+            Some(rhs_node) => pending.push((lhs_node, rhs_node)),
+        }
+    }
+    rhs.is_empty()
+}
+
+//
+// The node used to position `node` in the tree; for an attribute this is the owner element (or the
+// attribute itself when it has none) together with the attribute's name.
+//
+fn attribute_position(node: &RefNode) -> (RefNode, Option<String>) {
+    if is_attribute(node) {
+        if let Some(owner_element) = node.owner_element() {
+            return (owner_element, Some(node.node_name().to_string()));
+        }
+    }
+    (node.clone(), None)
+}
+
+//
+// The root of the tree containing `node`, and the path of child indices from that root to `node`.
+//
+fn root_and_path(node: &RefNode) -> (RefNode, Vec<usize>) {
+    let mut path = Vec::new();
+    let mut node = node.clone();
+    while let Some(parent) = node.parent_node() {
+        path.push(node.preceding_siblings().count());
+        node = parent;
+    }
+    path.reverse();
+    (node, path)
+}
+
+//
+// The element from which namespace lookups on `node` start, if any.
+//
+fn namespace_context(node: &RefNode) -> Option<RefNode> {
+    match node.node_type() {
+        NodeType::Element => Some(node.clone()),
+        NodeType::Document => node.child_nodes().iter().find(is_element),
+        NodeType::Attribute => node.owner_element(),
+        NodeType::DocumentType
+        | NodeType::Entity
+        | NodeType::Notation
+        | NodeType::DocumentFragment => None,
+        _ => node.ancestors().find(is_element),
+    }
+}
+
+//
+// The document URI of the document whose tree contains `node`; nodes that are not, or not yet, in
+// the tree of a document have no document base URI.
+//
+fn document_base_uri(node: &RefNode) -> Option<String> {
+    node.ancestors()
+        .last()
+        .filter(is_document)
+        .and_then(|document| document.document_uri())
+}
+
+//
+// `element` and its ancestor elements, nearest first.
+//
+fn in_scope_elements(element: &RefNode) -> impl Iterator<Item = RefNode> {
+    std::iter::once(element.clone()).chain(element.ancestors().filter(is_element))
+}
+
+//
+// The namespace bound to `prefix` (or the default namespace) by the name or `xmlns` attributes of
+// `element` or its ancestors.
+//
+fn locate_namespace(element: &RefNode, prefix: Option<&str>) -> Option<String> {
+    for element in in_scope_elements(element) {
+        let name = element.node_name();
+        if name.namespace_uri().is_some() && name.prefix().as_deref() == prefix {
+            return name.namespace_uri().clone();
+        }
+        for (attribute_name, attribute_node) in element.attribute_iter() {
+            let is_declaration = match (attribute_name.prefix().as_deref(), prefix) {
+                (Some(XMLNS_NS_ATTRIBUTE), Some(prefix)) => attribute_name.local_name() == prefix,
+                (None, None) => attribute_name.local_name() == XMLNS_NS_ATTRIBUTE,
+                _ => false,
+            };
+            if is_declaration {
+                return attribute_node
+                    .text_content()
+                    .filter(|namespace_uri| !namespace_uri.is_empty());
+            }
+        }
+    }
+    None
+}
+
+//
+// A prefix bound to `namespace_uri` in the scope of `element`, that has not been re-bound to a
+// different namespace by a nearer declaration.
+//
+fn locate_prefix(element: &RefNode, namespace_uri: &str) -> Option<String> {
+    let is_in_scope =
+        |prefix: &str| locate_namespace(element, Some(prefix)).as_deref() == Some(namespace_uri);
+    for candidate in in_scope_elements(element) {
+        let name = candidate.node_name();
+        if name.namespace_uri().as_deref() == Some(namespace_uri) {
+            if let Some(prefix) = name.prefix() {
+                if is_in_scope(prefix) {
+                    return Some(prefix.clone());
+                }
+            }
+        }
+        let mut prefixes: Vec<String> = candidate
+            .attribute_iter()
+            .map(|(_, attribute_node)| attribute_node)
+            .filter(|attribute_node| {
+                attribute_node.node_name().prefix().as_deref() == Some(XMLNS_NS_ATTRIBUTE)
+                    && attribute_node.text_content().as_deref() == Some(namespace_uri)
+            })
+            .map(|attribute_node| attribute_node.node_name().local_name().clone())
+            .collect();
+        prefixes.sort();
+        if let Some(prefix) = prefixes.into_iter().find(|prefix| is_in_scope(prefix)) {
+            return Some(prefix);
+        }
+    }
+    None
+}
+
 pub(crate) fn create_document_with_options(
     namespace_uri: Option<&str>,
     qualified_name: Option<&str>,
     doc_type: Option<RefNode>,
     options: ProcessingOptions,
 ) -> Result<RefNode> {
-    let node_impl = NodeImpl::new_document(doc_type, options);
-    let mut document_node = RefNode::new(node_impl);
+    let mut document_node = RefNode::new(NodeImpl::new_document(options));
+
+    //
+    // If specified, the document type is the first child, and is now owned by this document.
+    //
+    if let Some(doc_type) = doc_type {
+        if doc_type.parent_node().is_some() {
+            warn!("{}", MSG_DOCUMENT_TYPE_IN_USE);
+            return Error::WrongDocument.into();
+        }
+        doc_type.borrow_mut().i_owner_document = Some(document_node.clone().downgrade());
+        let _safe_to_ignore = document_node.append_child(doc_type)?;
+    }
 
     //
     // If specified, create a new root element
@@ -1353,3 +3026,176 @@ pub(crate) fn create_document_with_options(
 
     Ok(document_node)
 }
+
+//
+// Register `observer` with the document that owns `node` (or `node` itself if it is a document),
+// it will be told about any node removed from the document's tree until it is dropped.
+//
+pub(crate) fn add_removal_observer(node: &RefNode, observer: WeakRemovalObserver) {
+    if let Some(document) = document_of(node) {
+        let mut mut_document = document.borrow_mut();
+        if let Extension::Document {
+            i_removal_observers,
+            ..
+        } = &mut mut_document.i_extension
+        {
+            i_removal_observers.push(observer);
+        } else {
+            warn!("{}", MSG_INVALID_EXTENSION);
+        }
+    }
+}
+
+//
+// Tell all the live observers registered with the document that owns `parent` that `node` is
+// about to be removed, and forget any that have been dropped.
+//
+fn notify_removal(parent: &RefNode, node: &RefNode) {
+    let observers: Vec<SharedRemovalObserver> = match document_of(parent) {
+        None => Vec::default(),
+        Some(document) => {
+            let mut mut_document = document.borrow_mut();
+            if let Extension::Document {
+                i_removal_observers,
+                ..
+            } = &mut mut_document.i_extension
+            {
+                i_removal_observers.retain(|observer| observer.strong_count() > 0);
+                i_removal_observers
+                    .iter()
+                    .filter_map(|observer| observer.upgrade())
+                    .collect()
+            } else {
+                Vec::default()
+            }
+        }
+    };
+    for observer in observers {
+        if let Some(mut observer) = observer.try_borrow_mut() {
+            observer.node_removing(node);
+        }
+    }
+}
+
+//
+// The mutation listeners of the document that owns `node`; these are copied so that no borrow is
+// held while they are called.
+//
+fn mutation_listeners(node: &RefNode) -> Vec<SharedListener> {
+    match document_of(node) {
+        None => Vec::default(),
+        Some(document) => unwrap_extension_field!(
+            document,
+            Document,
+            i_mutation_listeners,
+            |i_mutation_listeners: &MutationListeners| i_mutation_listeners.listeners()
+        ),
+    }
+}
+
+fn notify_mutation(node: &RefNode, mutation: Mutation) {
+    for listener in mutation_listeners(node) {
+        listener(&mutation);
+    }
+}
+
+//
+// Record the value of `node` before it is changed, only if there is a listener to tell of the
+// change; the value of an attribute includes its children, and so this is also used by
+// `insert_before` and `remove_child`.
+//
+fn value_change(node: &RefNode) -> Option<(RefNode, Option<String>)> {
+    if mutation_listeners(node).is_empty() {
+        None
+    } else {
+        Some((node.clone(), reported_value(node)))
+    }
+}
+
+fn notify_value_change(change: Option<(RefNode, Option<String>)>) {
+    if let Some((node, old_value)) = change {
+        let new_value = reported_value(&node);
+        let mutation = match node.node_type() {
+            NodeType::Attribute => match node.owner_element() {
+                None => return,
+                Some(element) => Mutation::AttributeChanged {
+                    element,
+                    name: node.node_name(),
+                    old_value,
+                    new_value,
+                },
+            },
+            NodeType::Text
+            | NodeType::CData
+            | NodeType::Comment
+            | NodeType::ProcessingInstruction => Mutation::CharacterDataChanged {
+                node: node.clone(),
+                old_value,
+                new_value,
+            },
+            _ => return,
+        };
+        notify_mutation(&node, mutation);
+    }
+}
+
+//
+// Tell listeners of the children inserted into, or removed from, `parent`; for an attribute this
+// is reported as a change to its value.
+//
+fn notify_children_change(
+    parent: &RefNode,
+    change: Option<(RefNode, Option<String>)>,
+    child_nodes: Vec<RefNode>,
+    inserted: bool,
+) {
+    if is_attribute(parent) {
+        notify_value_change(change);
+    } else if change.is_some() {
+        for node in child_nodes {
+            let mutation = if inserted {
+                Mutation::NodeInserted {
+                    parent: parent.clone(),
+                    node,
+                }
+            } else {
+                Mutation::NodeRemoved {
+                    parent: parent.clone(),
+                    node,
+                }
+            };
+            notify_mutation(parent, mutation);
+        }
+    }
+}
+
+fn reported_value(node: &RefNode) -> Option<String> {
+    if is_attribute(node) {
+        attribute_value(node)
+    } else {
+        node.node_value()
+    }
+}
+
+fn attribute_value(attribute_node: &RefNode) -> Option<String> {
+    as_attribute(attribute_node).ok()?.value()
+}
+
+//
+// Remove the children of an attribute without telling any listeners, the caller does so.
+//
+fn clear_value(attribute_node: &RefNode) {
+    let mut mut_attribute = attribute_node.borrow_mut();
+    mut_attribute.i_child_nodes.clear();
+    if let Extension::Attribute { i_specified, .. } = &mut mut_attribute.i_extension {
+        *i_specified = true;
+    }
+}
+
+fn document_of(node: &RefNode) -> Option<RefNode> {
+    if is_document(node) {
+        Some(node.clone())
+    } else {
+        node.owner_document()
+    }
+}
@@ -1,6 +1,11 @@
+use crate::level2::data_ref::DataRef;
+use crate::level2::node_list::NodeList;
 use crate::shared::error::Result;
 use crate::shared::name::Name;
-use crate::shared::text;
+use crate::shared::rc_cell::Shared;
+use crate::shared::syntax::{escape_text_with, InvalidCharHandling};
+use indexmap::IndexMap;
+use std::any::Any;
 use std::collections::HashMap;
 
 // ------------------------------------------------------------------------------------------------
@@ -67,7 +72,32 @@ pub trait Attribute: Node {
     ///
     /// The `Element` node this attribute is attached to or `null` if this attribute is not in use.
     ///
+    /// This is set by `Element::set_attribute_node` (and so the other attribute setters), and
+    /// cleared by `Element::remove_attribute_node`; a clone of an attribute has no owner. The
+    /// element is held by a weak reference, so this returns `None` once it has been dropped.
+    ///
     fn owner_element(&self) -> Option<Self::NodeRef>;
+    ///
+    /// Returns whether this attribute is known to be of type ID (i.e. to contain an identifier
+    /// for its owner element) or not.
+    ///
+    /// An attribute is an ID if it is named `xml:id`, if it was marked as one with
+    /// [`set_is_id`](#tymethod.set_is_id), if it is declared with the type `ID` in the document's
+    /// DTD, or if it is named `id` and the document was created with
+    /// [`ProcessingOptions::set_assume_ids`](struct.ProcessingOptions.html#method.set_assume_ids).
+    ///
+    /// # Specification
+    ///
+    /// This is the DOM Level 3 `Attr.isId` attribute.
+    ///
+    fn is_id(&self) -> bool;
+    ///
+    /// Declare, or undeclare, this attribute to be a user-determined ID attribute; see
+    /// [`is_id`](#tymethod.is_id). This mirrors the DOM Level 3 `Element.setIdAttributeNode`
+    /// method, and affects the results of
+    /// [`Document::get_element_by_id`](trait.Document.html#tymethod.get_element_by_id).
+    ///
+    fn set_is_id(&mut self, is_id: bool) -> Result<()>;
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -110,6 +140,11 @@ pub trait CDataSection: Text {}
 ///
 /// This corresponds to the DOM `CharacterData` interface.
 ///
+/// **Note:** when the owner document has
+/// [`strict_error_checking`](trait.Document.html#tymethod.strict_error_checking) set, each method
+/// that changes the data of the node also raises `INVALID_CHARACTER_ERR` if the new data contains a
+/// character that is not legal in XML 1.0.
+///
 /// # Specification
 ///
 /// The `CharacterData` interface extends [`Node`](trait.Node.html) with a set of attributes and
@@ -129,10 +164,14 @@ pub trait CharacterData: Node {
     ///
     /// **Note:** This implementation drops the `_data` suffix from the methods for clarity.
     ///
+    /// **Note:** Rust strings are UTF-8, so this implementation counts `char`s rather than 16-bit
+    /// units; the same is true of every `offset` and `count` parameter in this trait. These are
+    /// counted over the unescaped node value, not the escaped string returned by `data`.
+    ///
     fn length(&self) -> usize {
-        match self.data() {
+        match Node::node_value(self) {
             None => 0,
-            Some(s) => s.len(),
+            Some(s) => s.chars().count(),
         }
     }
     ///
@@ -162,10 +201,23 @@ pub trait CharacterData: Node {
         ) {
             (None, _) => None,
             (v @ Some(_), false) => v,
-            (Some(value), true) => Some(text::escape(&value)),
+            //
+            // As with `Display` there is no way to report an illegal character, so they are
+            // written as references.
+            //
+            (Some(value), true) => {
+                Some(escape_text_with(&value, InvalidCharHandling::Escape).unwrap_or_default())
+            }
         }
     }
     ///
+    /// The character data of the node, borrowed rather than copied; unlike
+    /// [data()](#method.data) this is not escaped, and is empty if the node has no data.
+    ///
+    /// The node cannot be modified while the result is held.
+    ///
+    fn data_as_str(&self) -> DataRef<'_>;
+    ///
     /// Set the `data` for the node; see [data()](#tymethod.data).
     ///
     fn set_data(&mut self, data: &str) -> Result<()> {
@@ -198,6 +250,9 @@ pub trait CharacterData: Node {
     ///   number of 16-bit units in data, or if the specified `count` is negative.
     /// * `DOMSTRING_SIZE_ERR`: Raised if the specified range of text does not fit into a `DOMString`.
     ///
+    /// **Note:** as `offset` and `count` are unsigned they can never be negative, this method
+    /// returns `Error::IndexSize` only when `offset` is greater than [length()](#method.length).
+    ///
     fn substring_data(&self, offset: usize, count: usize) -> Result<String>;
     ///
     /// Append the string to the end of the character data of the node.
@@ -263,6 +318,7 @@ pub trait CharacterData: Node {
     ///   replaced; (i.e., the effect is the same as a remove method call with the same range,
     ///   followed by an append method invocation).
     /// * `arg` of type `DOMString`: The `DOMString` with which the range must be replaced.
+    ///
     /// Exceptions
     ///
     /// INDEX_SIZE_ERR: Raised if the specified `offset` is negative or greater than the number
@@ -296,6 +352,12 @@ pub trait Document: Node {
     /// The Document Type Declaration (see [`DocumentType`](trait.DocumentType.html)) associated with
     /// this document.
     ///
+    /// The document type is also a child of this document, so that comments and processing
+    /// instructions may come before or after it. Unlike the specification below this
+    /// implementation allows the document type to be added, moved, or removed as a child, provided
+    /// there is only one and it comes before the document element; otherwise
+    /// `Error::HierarchyRequest` is returned.
+    ///
     /// # Specification
     ///
     /// For HTML documents as well as XML documents without a document type
@@ -308,6 +370,9 @@ pub trait Document: Node {
     /// This is a convenience attribute that allows direct access to the child node that is the
     /// root element of the document.
     ///
+    /// Comments, processing instructions, and the document type may precede this element in the
+    /// children of the document.
+    ///
     /// # Specification
     ///
     /// For HTML documents, this is the element with the tagName `"HTML"`.
@@ -325,6 +390,65 @@ pub trait Document: Node {
     ///
     fn implementation(&self) -> &dyn DOMImplementation<NodeRef = Self::NodeRef>;
     ///
+    /// The location of the document or `None` if undefined or if the document was created using
+    /// `DOMImplementation::create_document`. This is a DOM Level 3 member of `Document`.
+    ///
+    /// This is distinct from the namespace URI passed to `create_document`, which is only used to
+    /// name the document element. The document URI is the base URI of the document, and so of any
+    /// node in it without an `xml:base` attribute in scope; see
+    /// [`Node::base_uri`](trait.Node.html#tymethod.base_uri).
+    ///
+    /// # Specification
+    ///
+    /// No lexical checking is performed when setting this attribute; this could result in a
+    /// `null` value returned when using `Node.baseURI`.
+    ///
+    fn document_uri(&self) -> Option<String>;
+    ///
+    /// Set the location of the document; see [`document_uri`](#tymethod.document_uri).
+    ///
+    fn set_document_uri(&mut self, document_uri: &str) -> Result<()>;
+    ///
+    /// Remove the location of the document; see [`document_uri`](#tymethod.document_uri).
+    ///
+    fn unset_document_uri(&mut self) -> Result<()>;
+    ///
+    /// Whether error checking is enforced; this is `true` for a new document. This is a DOM Level
+    /// 3 member of `Document`.
+    ///
+    /// When this is `false` the following checks are skipped, which is only safe when the data
+    /// used to build the tree is already known to be valid:
+    ///
+    /// * that each character of the data of a node, or of the value of an attribute, is a legal
+    ///   XML 1.0 character;
+    /// * that the names passed to `create_element`, `create_element_ns`, `create_attribute`,
+    ///   `create_attribute_with`, `create_attribute_ns`, `Element::set_attribute`, and
+    ///   `Element::set_attribute_ns` are valid names, and are consistent with their namespace;
+    ///   nor are the names passed to `Element::get_attribute`, and the other lookups by name, so
+    ///   that such an attribute may still be found;
+    /// * that a node inserted by `insert_before`, `append_child`, or `replace_child` is of a type
+    ///   allowed as a child of the parent, and that a document has at most one element and document
+    ///   type, in that order.
+    ///
+    /// A node is never inserted into its own subtree, and nodes from another document are always
+    /// rejected. The flag is read by each operation as it is made, nothing is recorded on the nodes
+    /// created, and so changing it does not change, or check, any existing node.
+    ///
+    /// # Specification
+    ///
+    /// An attribute specifying whether error checking is enforced or not. When set to `false`, the
+    /// implementation is free to not test every possible error case normally defined on DOM
+    /// operations, and not raise any `DOMException` on DOM operations or report errors while using
+    /// `Document.normalizeDocument()`. In case of error, the behavior is undefined. This attribute
+    /// is `true` by default.
+    ///
+    fn strict_error_checking(&self) -> bool;
+    ///
+    /// Set whether error checking is enforced; see
+    /// [`strict_error_checking`](#tymethod.strict_error_checking).
+    ///
+    fn set_strict_error_checking(&mut self, strict_error_checking: bool) -> Result<()>;
+    ///
     /// Creates an [`Attribute`](trait.Attribute.html) of the given name. Note that the `Attr`
     /// instance can then be set on an [`Element`](trait.Element.html) using the `setAttributeNode`
     /// method.
@@ -409,6 +533,9 @@ pub trait Document: Node {
     /// **Exceptions**
     ///
     /// * `NOT_SUPPORTED_ERR`: Raised if this document is an HTML document.
+    /// * `INVALID_CHARACTER_ERR`: Raised if the specified data contains `]]>`, and so cannot be
+    ///   serialized as a CDATA section. Also raised if the data contains a character that is not
+    ///   legal in XML 1.0, unless `strict_error_checking` is `false`.
     ///
     fn create_cdata_section(&self, data: &str) -> Result<Self::NodeRef>;
     ///
@@ -448,6 +575,11 @@ pub trait Document: Node {
     /// * `INVALID_CHARACTER_ERR`: Raised if the specified name contains an illegal character.
     /// * `NOT_SUPPORTED_ERR`: Raised if this document is an HTML document.
     ///
+    /// As an extension, copying the replacement tree of the entity is checked against the
+    /// document's [`EntityLimits`](ext/trait.EntityLimits.html), and raises
+    /// `EntityExpansionSize`, `EntityExpansionDepth`, or `EntityReferenceLoop` if they are
+    /// exceeded.
+    ///
     fn create_entity_reference(&self, name: &str) -> Result<Self::NodeRef>;
     ///
     /// Creates a [`Comment`](trait.Comment.html) node given the specified string.
@@ -462,7 +594,13 @@ pub trait Document: Node {
     ///
     /// * `Comment`: The new `Comment` object.
     ///
-    fn create_comment(&self, data: &str) -> Self::NodeRef;
+    /// **Exceptions**
+    ///
+    /// * `INVALID_CHARACTER_ERR`: Raised if the specified data contains `--`, or ends with `-`,
+    ///   and so cannot be serialized as a comment. Also raised if the data contains a character
+    ///   that is not legal in XML 1.0, unless `strict_error_checking` is `false`.
+    ///
+    fn create_comment(&self, data: &str) -> Result<Self::NodeRef>;
     ///
     /// Creates an element of the type specified.
     ///
@@ -547,6 +685,8 @@ pub trait Document: Node {
     /// **Exceptions**
     ///
     /// * `INVALID_CHARACTER_ERR`: Raised if the specified target contains an illegal character.
+    ///   Also raised if the target is the reserved `xml` (in any case), or if the data contains
+    ///   `?>`.
     /// * `NOT_SUPPORTED_ERR`: Raised if this document is an HTML document.
     ///
     fn create_processing_instruction(
@@ -569,13 +709,24 @@ pub trait Document: Node {
     ///
     fn create_text_node(&self, data: &str) -> Self::NodeRef;
     ///
+    /// Implementation defined extension: this is the same as `create_text_node` except that, if
+    /// [`strict_error_checking`](#tymethod.strict_error_checking) is `true`, it returns
+    /// `INVALID_CHARACTER_ERR` if `data` contains a character that is not legal in XML 1.0. As
+    /// `create_text_node` may not raise an exception it does not make this check.
+    ///
+    fn try_create_text_node(&self, data: &str) -> Result<Self::NodeRef>;
+    ///
     /// Returns the [`Element`](trait.Element.html) whose ID is given by `elementId`.
     ///
     /// **Note:** This implementation will ensure that attributes named `xml:id` or `id` with the
     /// XML namespace will be treated as identifiers. If the
     /// [`ProcessingOptions::set_assume_ids`](struct.ProcessingOptions.html#method.set_assume_ids)
     /// method is used when constructing a document any attribute with the local name `id` will
-    /// be treated as identifiers.
+    /// be treated as identifiers. Any attribute marked with
+    /// [`Attribute::set_is_id`](trait.Attribute.html#tymethod.set_is_id) is also an identifier.
+    ///
+    /// Only elements that are currently in this document's tree are found; if more than one
+    /// element has this ID the first in document order is returned.
     ///
     /// # Specification
     ///
@@ -599,8 +750,6 @@ pub trait Document: Node {
     /// Returns a `NodeList` of all the [`Element`](trait.Element.html)s with a given tag name in the
     /// order in which they are encountered in a preorder traversal of the Document tree.
     ///
-    /// **Note:** This method will panic if `document_element` is not an `Element` node.
-    ///
     /// # Specification
     ///
     /// **Parameters**
@@ -618,7 +767,8 @@ pub trait Document: Node {
     /// namespace URI in the order in which they are encountered in a preorder traversal of the
     /// Document tree.
     ///
-    /// **Note:** This method will panic if `document_element` is not an `Element` node.
+    /// **Note:** prefixes are ignored, an element matches if the namespace URI it was created with,
+    /// or that its prefix resolves to, is the same as `namespace_uri`.
     ///
     /// # Specification
     ///
@@ -638,6 +788,171 @@ pub trait Document: Node {
         namespace_uri: &str,
         local_name: &str,
     ) -> Vec<Self::NodeRef>;
+    ///
+    /// Imports a node from another document to this document.
+    ///
+    /// # Specification
+    ///
+    /// The returned node has no parent; (`parentNode` is `null`). The source node is not altered
+    /// or removed from the original document; this method creates a new copy of the source node.
+    ///
+    /// For all nodes, importing a node creates a node object owned by the importing document,
+    /// with attribute values identical to the source node's `nodeName` and `nodeType`, plus the
+    /// attributes related to namespaces (`prefix`, `localName`, and `namespaceURI`). As in the
+    /// `cloneNode` operation on a `Node`, the source node is not altered.
+    ///
+    /// Additional information is copied as appropriate to the `nodeType`, attempting to mirror
+    /// the behavior expected if a fragment of XML or HTML source was copied from one document to
+    /// another, recognizing that the two documents may have different DTDs in the XML case. The
+    /// following list describes the specifics for each type of node.
+    ///
+    /// * `ATTRIBUTE_NODE`: The `ownerElement` attribute is set to `null` and the `specified` flag
+    ///   is set to `true` on the generated `Attr`. The descendants of the source `Attr` are
+    ///   recursively imported and the resulting nodes reassembled to form the corresponding
+    ///   subtree. Note that the `deep` parameter has no effect on `Attr` nodes; they always carry
+    ///   their children with them when imported.
+    /// * `DOCUMENT_FRAGMENT_NODE`: If the `deep` option was set to `true`, the descendants of the
+    ///   source element are recursively imported and the resulting nodes reassembled to form the
+    ///   corresponding subtree. Otherwise, this simply generates an empty `DocumentFragment`.
+    /// * `DOCUMENT_NODE`: `Document` nodes cannot be imported.
+    /// * `DOCUMENT_TYPE_NODE`: `DocumentType` nodes cannot be imported.
+    /// * `ELEMENT_NODE`: Specified attribute nodes of the source element are imported, and the
+    ///   generated `Attr` nodes are attached to the generated `Element`. Default attributes are
+    ///   not copied, though if the document being imported into defines default attributes for
+    ///   this element name, those are assigned. If the `importNode` `deep` parameter was set to
+    ///   `true`, the descendants of the source element are recursively imported and the resulting
+    ///   nodes reassembled to form the corresponding subtree.
+    /// * `ENTITY_NODE`: `Entity` nodes can be imported, however in the current release of the
+    ///   DOM the `DocumentType` is readonly. Ability to add these imported nodes to a
+    ///   `DocumentType` will be considered for addition to a future release of the DOM. On import,
+    ///   the `publicId`, `systemId`, and `notationName` attributes are copied. If a `deep` import
+    ///   is requested, the descendants of the the source `Entity` are recursively imported and
+    ///   the resulting nodes reassembled to form the corresponding subtree.
+    /// * `ENTITY_REFERENCE_NODE`: Only the `EntityReference` itself is copied, even if a `deep`
+    ///   import is requested, since the source and destination documents might have defined the
+    ///   entity differently. If the document being imported into provides a definition for this
+    ///   entity name, its value is assigned.
+    /// * `NOTATION_NODE`: `Notation` nodes can be imported, however in the current release of
+    ///   the DOM the `DocumentType` is readonly. Ability to add these imported nodes to a
+    ///   `DocumentType` will be considered for addition to a future release of the DOM. On
+    ///   import, the `publicId` and `systemId` attributes are copied. Note that the `deep`
+    ///   parameter has no effect on `Notation` nodes since they never have any children.
+    /// * `PROCESSING_INSTRUCTION_NODE`: The imported node copies its `target` and `data` values
+    ///   from those of the source node.
+    /// * `TEXT_NODE`, `CDATA_SECTION_NODE`, `COMMENT_NODE`: These three types of nodes inheriting
+    ///   from `CharacterData` copy their `data` and `length` attributes from those of the source
+    ///   node.
+    ///
+    /// **Parameters**
+    ///
+    /// * `importedNode` of type `Node`: The node to import.
+    /// * `deep` of type `boolean`: If `true`, recursively import the subtree under the specified
+    ///   node; if `false`, import only the node itself, as explained above. This has no effect on
+    ///   `Attr`, `EntityReference`, and `Notation` nodes.
+    ///
+    /// **Return Value**
+    ///
+    /// * `Node`: The imported node that belongs to this `Document`.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `NOT_SUPPORTED_ERR`: Raised if the type of node being imported is not supported.
+    ///
+    fn import_node(&self, imported_node: Self::NodeRef, deep: bool) -> Result<Self::NodeRef>;
+    ///
+    /// Attempts to adopt a node from another document to this document. If supported, it changes
+    /// the `ownerDocument` of the source node, its children, as well as the attached attribute
+    /// nodes if there are any. If the source node has a parent it is first removed from the child
+    /// list of its parent. This effectively allows moving a subtree from one document to another
+    /// (unlike `importNode()` which create a copy of the source node instead of moving it). This
+    /// is a DOM Level 3 member of `Document`.
+    ///
+    /// # Specification
+    ///
+    /// When it fails, applications should use `Document.importNode()` instead. Note that if the
+    /// adopted node is already part of this document (i.e. the source and target document are the
+    /// same), this method still has the effect of removing the source node from the child list of
+    /// its parent, if any. The following list describes the specifics for each type of node.
+    ///
+    /// * `ATTRIBUTE_NODE`: The `ownerElement` attribute is set to `null` and the `specified` flag
+    ///   is set to `true` on the adopted `Attr`. The descendants of the source `Attr` are
+    ///   recursively adopted.
+    /// * `DOCUMENT_FRAGMENT_NODE`: The descendants of the source node are recursively adopted.
+    /// * `DOCUMENT_NODE`: `Document` nodes cannot be adopted.
+    /// * `DOCUMENT_TYPE_NODE`: `DocumentType` nodes cannot be adopted.
+    /// * `ELEMENT_NODE`: Specified attribute nodes of the source element are adopted. Default
+    ///   attributes are discarded, though if the document being adopted into defines default
+    ///   attributes for this element name, those are assigned. The descendants of the source
+    ///   element are recursively adopted.
+    /// * `ENTITY_NODE`: `Entity` nodes cannot be adopted.
+    /// * `ENTITY_REFERENCE_NODE`: Only the `EntityReference` node itself is adopted, the
+    ///   descendants are discarded, since the source and destination documents might have defined
+    ///   the entity differently.
+    /// * `NOTATION_NODE`: `Notation` nodes cannot be adopted.
+    /// * `PROCESSING_INSTRUCTION_NODE`, `TEXT_NODE`, `CDATA_SECTION_NODE`, `COMMENT_NODE`: These
+    ///   nodes can all be adopted. No specifics.
+    ///
+    /// **Parameters**
+    ///
+    /// * `source` of type `Node`: The node to move into this document.
+    ///
+    /// **Return Value**
+    ///
+    /// * `Node`: The adopted node.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `NOT_SUPPORTED_ERR`: Raised if the source node is of type `DOCUMENT`, `DOCUMENT_TYPE`,
+    ///   `ENTITY`, or `NOTATION`.
+    /// * `NO_MODIFICATION_ALLOWED_ERR`: Raised when the source node is readonly.
+    ///
+    fn adopt_node(&self, source: Self::NodeRef) -> Result<Self::NodeRef>;
+    ///
+    /// Rename an existing node of type `ELEMENT_NODE` or `ATTRIBUTE_NODE`. This implementation
+    /// always renames the node in place, so its children, attributes, and position in the tree are
+    /// unchanged and the node itself is returned. This is a DOM Level 3 member of `Document`.
+    ///
+    /// # Specification
+    ///
+    /// When the node being renamed is an `Element` only the specified attributes are moved,
+    /// default attributes originated from the `DocumentType` interface are removed. Default
+    /// attributes for the new element are added.
+    ///
+    /// If the node being renamed is an `Attr` that is attached to an `Element`, the node is first
+    /// removed from the `Element` attributes map. Then, once renamed, it is put back.
+    ///
+    /// **Parameters**
+    ///
+    /// * `n` of type `Node`: The node to rename.
+    /// * `namespaceURI` of type `DOMString`: The new namespace URI.
+    /// * `qualifiedName` of type `DOMString`: The new qualified name.
+    ///
+    /// **Return Value**
+    ///
+    /// * `Node`: The renamed node.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `NOT_SUPPORTED_ERR`: Raised when the type of the specified node is neither
+    ///   `ELEMENT_NODE` nor `ATTRIBUTE_NODE`.
+    /// * `INVALID_CHARACTER_ERR`: Raised if the new qualified name is not an XML name.
+    /// * `WRONG_DOCUMENT_ERR`: Raised when the specified node was created from a different
+    ///   document than this document.
+    /// * `NAMESPACE_ERR`: Raised if the `qualifiedName` is a malformed qualified name, if the
+    ///   `qualifiedName` has a prefix and the `namespaceURI` is `null`, or if the `qualifiedName`
+    ///   has a prefix that is `"xml"` and the `namespaceURI` is different from
+    ///   "http://www.w3.org/XML/1998/namespace". Also raised when the node being renamed is an
+    ///   attribute, if the `qualifiedName`, or its prefix, is `"xmlns"` and the `namespaceURI` is
+    ///   different from "http://www.w3.org/2000/xmlns/".
+    /// * `NO_MODIFICATION_ALLOWED_ERR`: Raised when the node, or the element owning a renamed
+    ///   attribute, is readonly.
+    ///
+    fn rename_node(
+        &self,
+        node: Self::NodeRef,
+        namespace_uri: Option<&str>,
+        qualified_name: &str,
+    ) -> Result<Self::NodeRef>;
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -830,6 +1145,10 @@ pub trait DOMImplementation {
     ///
     /// Test if the DOM implementation implements a specific feature.
     ///
+    /// This implementation supports the features `Core` and `XML`, versions "1.0" and "2.0",
+    /// `Traversal` and `Range`, version "2.0", and `XPath`, version "3.0". As in DOM Level 3 the
+    /// feature name may be prefixed with `+`, which is ignored.
+    ///
     /// # Specification
     ///
     /// See DOM Level 2 Core [§1.3. Extended Interfaces](https://www.w3.org/TR/DOM-Level-2-Core/core.html#ID-E067D597)
@@ -910,7 +1229,7 @@ pub trait Element: Node {
     /// **Return Value**
     ///
     /// * `DOMString`: The `Attr` value as a string, or the empty string if that attribute does not
-    /// have a specified or default value.
+    ///   have a specified or default value.
     ///
     fn get_attribute(&self, name: &str) -> Option<String>;
     ///
@@ -931,11 +1250,13 @@ pub trait Element: Node {
     /// **Parameters**
     ///
     /// * `name` of type `DOMString`: The name of the attribute to create or alter.
-    /// `value` of type `DOMString`: Value to set in string form.
+    /// * `value` of type `DOMString`: Value to set in string form.
     ///
     /// **Exceptions**
     ///
-    /// * `INVALID_CHARACTER_ERR`: Raised if the specified name contains an illegal character.
+    /// * `INVALID_CHARACTER_ERR`: Raised if the specified name contains an illegal character. Also
+    ///   raised if the value contains a character that is not legal in XML 1.0, unless the owner
+    ///   document's `strict_error_checking` is `false`.
     /// * `NO_MODIFICATION_ALLOWED_ERR`: Raised if this node is readonly.
     ///
     fn set_attribute(&mut self, name: &str, value: &str) -> Result<()>;
@@ -972,7 +1293,7 @@ pub trait Element: Node {
     /// **Return Value**
     ///
     /// * `Attr`: The `Attr` node with the specified name (`nodeName`) or null if there is no such
-    /// attribute.
+    ///   attribute.
     ///
     fn get_attribute_node(&self, name: &str) -> Option<Self::NodeRef>;
     ///
@@ -1003,7 +1324,8 @@ pub trait Element: Node {
     /// * `INUSE_ATTRIBUTE_ERR`: Raised if `newAttr` is already an attribute of another `Element`
     ///   object. The DOM user must explicitly clone `Attr` nodes to re-use them in other elements.
     ///
-    fn set_attribute_node(&mut self, new_attribute: Self::NodeRef) -> Result<Self::NodeRef>;
+    fn set_attribute_node(&mut self, new_attribute: Self::NodeRef)
+        -> Result<Option<Self::NodeRef>>;
     ///
     /// Removes the specified attribute node.
     ///
@@ -1031,6 +1353,8 @@ pub trait Element: Node {
     /// Returns a `NodeList` of all descendant `Element`s with a given tag name, in the order in
     /// which they are encountered in a preorder traversal of this `Element` tree.
     ///
+    /// **Note:** the list does not include this element, even if its name matches.
+    ///
     /// # Specification
     ///
     /// **Parameters**
@@ -1087,12 +1411,14 @@ pub trait Element: Node {
     /// **Exceptions**
     ///
     /// * `INVALID_CHARACTER_ERR`: Raised if the specified qualified name contains an illegal character.
+    ///   Also raised if the value contains a character that is not legal in XML 1.0, unless the
+    ///   owner document's `strict_error_checking` is `false`.
     /// * `NO_MODIFICATION_ALLOWED_ERR`: Raised if this node is readonly.
     /// * `NAMESPACE_ERR`: Raised if the `qualifiedName` is malformed, if the `qualifiedName` has a
     ///   prefix and the `namespaceURI` is null, if the `qualifiedName` has a prefix that is "xml"
     ///   and the `namespaceURI` is different from "http://www.w3.org/XML/1998/namespace", or if
     ///   the `qualifiedName` is "xmlns" and the `namespaceURI` is different from
-    /// "http://www.w3.org/2000/xmlns/".
+    ///   "http://www.w3.org/2000/xmlns/".
     ///
     fn set_attribute_ns(
         &mut self,
@@ -1169,11 +1495,17 @@ pub trait Element: Node {
     /// * `INUSE_ATTRIBUTE_ERR`: Raised if `newAttr` is already an attribute of another `Element`
     ///   object. The DOM user must explicitly clone `Attr` nodes to re-use them in other elements.
     ///
-    fn set_attribute_node_ns(&mut self, _new_attribute: Self::NodeRef) -> Result<Self::NodeRef>;
+    fn set_attribute_node_ns(
+        &mut self,
+        _new_attribute: Self::NodeRef,
+    ) -> Result<Option<Self::NodeRef>>;
     ///
     /// Returns a `NodeList` of all the descendant `Element`s with a given local name and namespace
     /// URI in the order in which they are encountered in a preorder traversal of this Element tree.
     ///
+    /// **Note:** the list does not include this element, even if its name matches. As with
+    /// `Document::get_elements_by_tag_name_ns` prefixes are ignored.
+    ///
     /// # Specification
     ///
     /// HTML-only DOM implementations do not need to implement this method.
@@ -1221,7 +1553,7 @@ pub trait Element: Node {
     /// **Parameters**
     ///
     /// * `namespaceURI` of type `DOMString`: The namespace URI of the attribute to look for.
-    /// `localName` of type `DOMString`: The local name of the attribute to look for.
+    /// * `localName` of type `DOMString`: The local name of the attribute to look for.
     ///
     /// **Return Value**
     ///
@@ -1229,6 +1561,68 @@ pub trait Element: Node {
     ///   specified or has a default value on this element, `false` otherwise.
     ///
     fn has_attribute_ns(&self, namespace_uri: &str, local_name: &str) -> bool;
+    ///
+    /// Implementation defined extension: creates a `Text` node with `data`, using the owner
+    /// document of this element, and appends it to this element. The new node is returned.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `WRONG_DOCUMENT_ERR`: Raised if the owner document of this element has been dropped.
+    ///
+    /// Any error from `Node::append_child` is also returned.
+    ///
+    fn append_text(&mut self, data: &str) -> Result<Self::NodeRef>;
+    ///
+    /// Implementation defined extension: creates an element named `tag_name`, as
+    /// `Document::create_element` would, and appends it to this element. The new element is
+    /// returned, so that further children may be appended to it.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `WRONG_DOCUMENT_ERR`: Raised if the owner document of this element has been dropped.
+    ///
+    /// Any error from `Document::create_element` or `Node::append_child` is also returned.
+    ///
+    fn append_element(&mut self, tag_name: &str) -> Result<Self::NodeRef>;
+    ///
+    /// Implementation defined extension: creates an element with `namespace_uri` and
+    /// `qualified_name`, as `Document::create_element_ns` would, and appends it to this element.
+    /// The new element is returned, so that further children may be appended to it.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `WRONG_DOCUMENT_ERR`: Raised if the owner document of this element has been dropped.
+    ///
+    /// Any error from `Document::create_element_ns` or `Node::append_child` is also returned.
+    ///
+    fn append_element_ns(
+        &mut self,
+        namespace_uri: &str,
+        qualified_name: &str,
+    ) -> Result<Self::NodeRef>;
+    ///
+    /// Implementation defined extension: creates a `Comment` node with `data`, as
+    /// `Document::create_comment` would, and appends it to this element. The new node is returned.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `WRONG_DOCUMENT_ERR`: Raised if the owner document of this element has been dropped.
+    ///
+    /// Any error from `Document::create_comment` or `Node::append_child` is also returned.
+    ///
+    fn append_comment(&mut self, data: &str) -> Result<Self::NodeRef>;
+    ///
+    /// Implementation defined extension: creates a `CDataSection` node with `data`, as
+    /// `Document::create_cdata_section` would, and appends it to this element. The new node is
+    /// returned.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `WRONG_DOCUMENT_ERR`: Raised if the owner document of this element has been dropped.
+    ///
+    /// Any error from `Document::create_cdata_section` or `Node::append_child` is also returned.
+    ///
+    fn append_cdata(&mut self, data: &str) -> Result<Self::NodeRef>;
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -1236,6 +1630,10 @@ pub trait Element: Node {
 ///
 /// This corresponds to the DOM `Entity` interface.
 ///
+/// The descendants of an entity are read-only, and attempts to modify them return
+/// `Error::NoModificationAllowed`. As there is no other way to populate it, children may still be
+/// added to, or removed from, the entity itself; a node removed this way becomes writable.
+///
 /// # Specification
 ///
 /// This interface represents an entity, either parsed or unparsed, in an XML document. Note that
@@ -1299,6 +1697,11 @@ pub trait Entity: Node {
 ///
 /// This corresponds to the DOM `EntityReference` interface.
 ///
+/// The descendants of an entity reference are read-only, and attempts to modify them return
+/// `Error::NoModificationAllowed`. As there is no other way to populate it, children may still be
+/// added to, or removed from, the entity reference itself; a node removed this way becomes
+/// writable.
+///
 /// # Specification
 ///
 /// `EntityReference` objects may be inserted into the structure model when an entity reference
@@ -1377,7 +1780,7 @@ pub trait Node {
     /// **Exceptions on retrieval**
     ///
     /// * `DOMSTRING_SIZE_ERR`: Raised when it would return more characters than fit in a DOMString
-    /// variable on the implementation platform.
+    ///   variable on the implementation platform.
     ///
     fn node_value(&self) -> Option<String>;
     ///
@@ -1399,10 +1802,10 @@ pub trait Node {
     ///
     fn parent_node(&self) -> Option<Self::NodeRef>;
     ///
-    /// A `Vec` that contains all children of this node. If there are no children,
-    /// this is a `Vec` containing no nodes.
+    /// A [`NodeList`](struct.NodeList.html) that contains all children of this node. If there are
+    /// no children, this is a `NodeList` containing no nodes.
     ///
-    fn child_nodes(&self) -> Vec<Self::NodeRef>;
+    fn child_nodes(&self) -> NodeList;
     ///
     /// The first child of this node. If there is no such node, this returns `None`.
     ///
@@ -1420,10 +1823,13 @@ pub trait Node {
     ///
     fn next_sibling(&self) -> Option<Self::NodeRef>;
     ///
-    /// A `HashMap` containing the attributes of this node (if it is an `Element`) or
-    /// `None` otherwise.
+    /// An `IndexMap` containing the attributes of this node (if it is an `Element`) or
+    /// an empty map otherwise; the map iterates in the order the attributes were added.
+    ///
+    /// This is a new map on each call; to read the attributes in order, without copying them all,
+    /// see [`TreeIterators::attribute_iter`](ext/trait.TreeIterators.html#tymethod.attribute_iter).
     ///
-    fn attributes(&self) -> HashMap<Name, Self::NodeRef>;
+    fn attributes(&self) -> IndexMap<Name, Self::NodeRef>;
     ///
     /// The `Document` object associated with this node. This is also the `Document`
     /// object used to create new nodes. When this node is a `Document` or a `DocumentType` which is
@@ -1502,6 +1908,9 @@ pub trait Node {
     ///
     /// Removes the child node indicated by oldChild from the list of children, and returns it.
     ///
+    /// Children are matched by identity, not by [`Node::is_equal_node`]; the removed node has no
+    /// parent afterwards but is otherwise unchanged, and may be added to the tree again.
+    ///
     /// # Specification
     ///
     /// **Parameters**
@@ -1556,7 +1965,11 @@ pub trait Node {
     ///
     /// Returns a duplicate of this node, i.e., serves as a generic copy constructor for nodes.
     ///
-    /// **Note:** currently unsupported.
+    /// **Note:** attributes are always copied by value, so changes to the attributes of the
+    /// duplicate do not affect this node. A deep clone of a `Document` node will also clone the
+    /// document type and document element, and the resulting document owns all of the cloned
+    /// nodes; a shallow clone of a `Document` has neither. This returns `None` if the default
+    /// attributes declared in the document's DTD cannot be added to a copied element.
     ///
     /// # Specification
     ///
@@ -1606,7 +2019,8 @@ pub trait Node {
     /// Tests whether the DOM implementation implements a specific feature and that feature is
     /// supported by this node.
     ///
-    /// **Note:** currently unsupported.
+    /// Every node supports each feature of the implementation, and so this always returns the same
+    /// as [`DOMImplementation::has_feature`](trait.DOMImplementation.html#tymethod.has_feature).
     ///
     /// # Specification
     ///
@@ -1625,6 +2039,35 @@ pub trait Node {
     ///
     fn is_supported(&self, feature: &str, version: &str) -> bool;
     ///
+    /// Returns an object which implements the specialized APIs of the specified feature and
+    /// version. This is a DOM Level 3 member of `Node`.
+    ///
+    /// As the interfaces of every feature are implemented by the node itself this returns a clone
+    /// of this node if [`is_supported`](#tymethod.is_supported) returns `true`, and `None`
+    /// otherwise.
+    ///
+    /// # Specification
+    ///
+    /// This method returns a specialized object which implements the specialized APIs of the
+    /// specified feature and version, as specified in DOM Features. The specialized object may
+    /// also be obtained by using binding-specific casting methods but is not necessarily expected
+    /// to.
+    ///
+    /// **Parameters**
+    ///
+    /// * `feature` of type `DOMString`: The name of the feature requested. Note that any plus
+    ///   sign "+" prepended to the name of the feature will be ignored since it is not significant
+    ///   in the context of this method.
+    /// * `version` of type `DOMString`: This is the version number of the feature to test.
+    ///
+    /// **Return Value**
+    ///
+    /// * `DOMObject`: Returns an object which implements the specialized APIs of the specified
+    ///   feature and version, if any, or `null` if there is no object which implements interfaces
+    ///   associated with that feature.
+    ///
+    fn get_feature(&self, feature: &str, version: &str) -> Option<Self::NodeRef>;
+    ///
     /// Returns whether this node (if it is an element) has any attributes.
     ///
     /// **Return Value**
@@ -1633,6 +2076,263 @@ pub trait Node {
     ///
     fn has_attributes(&self) -> bool;
     ///
+    /// The absolute base URI of this node, or `None` if it cannot be determined. This is a DOM
+    /// Level 3 member of `Node`.
+    ///
+    /// The base URI of an element is the value of its `xml:base` attribute, resolved against the
+    /// base URI of its parent element, or of the document, as described in XML Base
+    /// [§4.2 Relative URI References](https://www.w3.org/TR/xmlbase/#resolution); relative
+    /// `xml:base` values nested within other `xml:base` scopes are therefore composed. An element
+    /// without `xml:base` has the base URI of its parent. Attributes have the base URI of their
+    /// owner element, and other nodes that of their parent node. The base URI of a document is
+    /// its [`document_uri`](trait.Document.html#tymethod.document_uri), and is only used by the
+    /// nodes in that document's tree. Document types, entities, notations and document fragments
+    /// return `None`. If no absolute base is in scope a relative base URI may be returned.
+    ///
+    /// # Specification
+    ///
+    /// The absolute base URI of this node or `null` if the implementation wasn't able to obtain an
+    /// absolute URI. This value is computed as described in Base URIs. However, when the
+    /// `Document` supports the feature "HTML" [DOM Level 2 HTML], the base URI is computed using
+    /// first the value of the href attribute of the HTML BASE element if any, and the value of the
+    /// `documentURI` attribute from the `Document` interface otherwise.
+    ///
+    fn base_uri(&self) -> Option<String>;
+    ///
+    /// This attribute returns the text content of this node and its descendants. This is a DOM
+    /// Level 3 member of `Node`.
+    ///
+    /// Only the data of `Text` and `CDATASection` descendants is returned, the contents of
+    /// comments and processing instructions are skipped.
+    ///
+    /// # Specification
+    ///
+    /// When it is defined to be `null`, setting it has no effect. On setting, any possible
+    /// children this node may have are removed and, if the new string is not empty or `null`,
+    /// replaced by a single `Text` node containing the string this attribute is set to.
+    ///
+    /// On getting, no serialization is performed, the returned string does not contain any
+    /// markup. No whitespace normalization is performed and the returned string does not contain
+    /// the white spaces in element content.
+    ///
+    /// The string returned is made of the text content of this node depending on its type, as
+    /// defined below:
+    ///
+    /// | Node type                                                                       | Content |
+    /// |---------------------------------------------------------------------------------|---------|
+    /// | `ELEMENT_NODE`, `ATTRIBUTE_NODE`, `ENTITY_NODE`, `ENTITY_REFERENCE_NODE`, `DOCUMENT_FRAGMENT_NODE` | concatenation of the `textContent` attribute value of every child node, excluding `COMMENT_NODE` and `PROCESSING_INSTRUCTION_NODE` nodes. This is the empty string if the node has no children. |
+    /// | `TEXT_NODE`, `CDATA_SECTION_NODE`, `COMMENT_NODE`, `PROCESSING_INSTRUCTION_NODE` | `nodeValue` |
+    /// | `DOCUMENT_NODE`, `DOCUMENT_TYPE_NODE`, `NOTATION_NODE`                           | `null`  |
+    ///
+    fn text_content(&self) -> Option<String>;
+    ///
+    /// Set the text content of this node; see [`text_content`](#tymethod.text_content). Setting
+    /// a non-empty string on a node whose owner document no longer exists, and so cannot create
+    /// the new `Text` node, returns `Error::WrongDocument`.
+    ///
+    /// # Specification
+    ///
+    /// **Exceptions on setting**
+    ///
+    /// * `NO_MODIFICATION_ALLOWED_ERR`: Raised when the node is readonly.
+    ///
+    fn set_text_content(&mut self, text: &str) -> Result<()>;
+    ///
+    /// Look up the namespace URI associated to the given prefix, starting from this node. This is
+    /// a DOM Level 3 member of `Node`.
+    ///
+    /// Namespace declarations are found in the `xmlns` and `xmlns:*` attributes of this node, if
+    /// it is an element, and its ancestor elements; the `xml` and `xmlns` prefixes are always
+    /// bound to their reserved namespaces. For an attribute the search starts at its owner
+    /// element, and for a document at its document element.
+    ///
+    /// # Specification
+    ///
+    /// See [Namespace URI Lookup](https://www.w3.org/TR/DOM-Level-3-Core/namespaces-algorithms.html#lookupNamespaceURIAlgo)
+    /// for details on the algorithm used by this method.
+    ///
+    /// **Parameters**
+    ///
+    /// * `prefix` of type `DOMString`: The prefix to look for. If this parameter is `null`, the
+    ///   method will return the default namespace URI if any.
+    ///
+    /// **Return Value**
+    ///
+    /// * `DOMString`: Returns the associated namespace URI or `null` if none is found.
+    ///
+    fn lookup_namespace_uri(&self, prefix: Option<&str>) -> Option<String>;
+    ///
+    /// Look up the prefix associated to the given namespace URI, starting from this node. The
+    /// default namespace declarations are ignored by this method. This is a DOM Level 3 member of
+    /// `Node`.
+    ///
+    /// # Specification
+    ///
+    /// See [Namespace Prefix Lookup](https://www.w3.org/TR/DOM-Level-3-Core/namespaces-algorithms.html#lookupNamespacePrefixAlgo)
+    /// for details on the algorithm used by this method.
+    ///
+    /// **Parameters**
+    ///
+    /// * `namespaceURI` of type `DOMString`: The namespace URI to look for.
+    ///
+    /// **Return Value**
+    ///
+    /// * `DOMString`: Returns an associated namespace prefix if found or `null` if none is found.
+    ///   If more than one prefix are associated to the namespace prefix, the returned namespace
+    ///   prefix is implementation dependent.
+    ///
+    fn lookup_prefix(&self, namespace_uri: &str) -> Option<String>;
+    ///
+    /// This method checks if the specified `namespace_uri` is the default namespace or not. This
+    /// is a DOM Level 3 member of `Node`.
+    ///
+    /// # Specification
+    ///
+    /// **Parameters**
+    ///
+    /// * `namespaceURI` of type `DOMString`: The namespace URI to look for.
+    ///
+    /// **Return Value**
+    ///
+    /// * `boolean`: Returns `true` if the specified `namespaceURI` is the default namespace,
+    ///   `false` otherwise.
+    ///
+    fn is_default_namespace(&self, namespace_uri: &str) -> bool;
+    ///
+    /// Compares the reference node, i.e. the node on which this method is being called, with a
+    /// node, i.e. the one passed as a parameter, with regard to their position in the document
+    /// and according to the document order. This is a DOM Level 3 member of `Node`.
+    ///
+    /// The result is a combination of the flags in [`document_position`](document_position/index.html).
+    /// Nodes in different documents, or different detached trees, are `DISCONNECTED`, and are
+    /// given an order that is stable for as long as both nodes exist. The attributes of an element
+    /// follow the element itself and precede its children, and are ordered among themselves by
+    /// name.
+    ///
+    /// # Specification
+    ///
+    /// **Parameters**
+    ///
+    /// * `other` of type `Node`: The node to compare against the reference node.
+    ///
+    /// **Return Value**
+    ///
+    /// * `unsigned short`: Returns how the node is positioned relatively to the reference node.
+    ///
+    fn compare_document_position(&self, other: &Self::NodeRef) -> u16;
+    ///
+    /// Returns whether this node is the same node as the given one. This is a DOM Level 3 member
+    /// of `Node`.
+    ///
+    /// # Specification
+    ///
+    /// This method provides a way to determine whether two `Node` references returned by the
+    /// implementation reference the same object. When two `Node` references are references to the
+    /// same object, even if through a proxy, the references may be used completely
+    /// interchangeably, such that all attributes have the same values and calling the same DOM
+    /// method on either reference always has exactly the same effect.
+    ///
+    /// **Parameters**
+    ///
+    /// * `other` of type `Node`: The node to test against.
+    ///
+    /// **Return Value**
+    ///
+    /// * `boolean`: Returns `true` if the nodes are the same, `false` otherwise.
+    ///
+    fn is_same_node(&self, other: &Self::NodeRef) -> bool;
+    ///
+    /// Tests whether two nodes are equal. This is a DOM Level 3 member of `Node`.
+    ///
+    /// # Specification
+    ///
+    /// This method tests for equality of nodes, not sameness (i.e., whether the two nodes are
+    /// references to the same object) which can be tested with `Node.isSameNode()`. All nodes
+    /// that are the same will also be equal, though the reverse may not be true.
+    ///
+    /// Two nodes are equal if and only if the following conditions are satisfied:
+    ///
+    /// * The two nodes are of the same type.
+    /// * The following string attributes are equal: `nodeName`, `localName`, `namespaceURI`,
+    ///   `prefix`, `nodeValue`. This is: they are both `null`, or they have the same length and
+    ///   are character for character identical.
+    /// * The `attributes` `NamedNodeMaps` are equal. This is: they are both `null`, or they have
+    ///   the same length and for each node that exists in one map there is a node that exists in
+    ///   the other map and is equal, although not necessarily at the same index.
+    /// * The `childNodes` `NodeLists` are equal. This is: they are both `null`, or they have the
+    ///   same length and contain equal nodes at the same index. Note that normalization can affect
+    ///   equality; to avoid this, nodes should be normalized before being compared.
+    ///
+    /// For two `DocumentType` nodes to be equal, the following conditions must also be satisfied:
+    ///
+    /// * The following string attributes are equal: `publicId`, `systemId`, `internalSubset`.
+    /// * The `entities` `NamedNodeMaps` are equal.
+    /// * The `notations` `NamedNodeMaps` are equal.
+    ///
+    /// **Parameters**
+    ///
+    /// * `arg` of type `Node`: The node to compare equality with.
+    ///
+    /// **Return Value**
+    ///
+    /// * `boolean`: Returns `true` if the nodes are equal, `false` otherwise.
+    ///
+    fn is_equal_node(&self, other: &Self::NodeRef) -> bool;
+    ///
+    /// Associate an object to a key on this node. The object can later be retrieved from this
+    /// node by calling [`get_user_data`](#tymethod.get_user_data) with the same key. This is a
+    /// DOM Level 3 member of `Node`.
+    ///
+    /// The data is shared, not copied, and is never copied to the nodes created by `clone_node` or
+    /// `Document::import_node`; instead the `handler`, if any, is called, see
+    /// [`UserDataHandler`](trait.UserDataHandler.html).
+    ///
+    /// # Specification
+    ///
+    /// **Parameters**
+    ///
+    /// * `key` of type `DOMString`: The key to associate the object to.
+    /// * `data` of type `DOMUserData`: The object to associate to the given key, or `null` to
+    ///   remove any existing association to that key.
+    /// * `handler` of type `UserDataHandler`: The handler to associate to that key, or `null`.
+    ///
+    /// **Return Value**
+    ///
+    /// * `DOMUserData`: Returns the `DOMUserData` previously associated to the given key on this
+    ///   node, or `null` if there was none.
+    ///
+    /// **Note:** This implementation provides [`unset_user_data`](#tymethod.unset_user_data)
+    /// rather than accepting a `null` value for `data`.
+    ///
+    fn set_user_data(
+        &mut self,
+        key: &str,
+        data: UserData,
+        handler: Option<UserDataHandlerRef<Self::NodeRef>>,
+    ) -> Option<UserData>;
+    ///
+    /// Remove the object, and any handler, associated to a key on this node; returns the object
+    /// previously associated to the key, if any.
+    ///
+    fn unset_user_data(&mut self, key: &str) -> Option<UserData>;
+    ///
+    /// Retrieves the object associated to a key on this node. The object must first have been set
+    /// to this node by calling [`set_user_data`](#tymethod.set_user_data) with the same key. This
+    /// is a DOM Level 3 member of `Node`.
+    ///
+    /// # Specification
+    ///
+    /// **Parameters**
+    ///
+    /// * `key` of type `DOMString`: The key the object is associated to.
+    ///
+    /// **Return Value**
+    ///
+    /// * `DOMUserData`: Returns the `DOMUserData` associated to the given key on this node, or
+    ///   `null` if there was none.
+    ///
+    fn get_user_data(&self, key: &str) -> Option<UserData>;
+    ///
     /// The namespace URI of this node, or null if it is unspecified.
     ///
     /// # Specification
@@ -1650,7 +2350,7 @@ pub trait Node {
     /// namespace, it simply has no namespace.
     ///
     fn namespace_uri(&self) -> Option<String> {
-        self.node_name().namespace_uri
+        self.node_name().namespace_uri().clone()
     }
     ///
     /// Returns the local part of the qualified name of this node.
@@ -1662,7 +2362,7 @@ pub trait Node {
     /// always `null`.
     ///
     fn local_name(&self) -> String {
-        self.node_name().local_name
+        self.node_name().local_name().clone()
     }
     ///
     /// The namespace prefix of this node, or null if it is unspecified.
@@ -1693,8 +2393,16 @@ pub trait Node {
     ///   of this node is "xmlns".
     ///
     fn prefix(&self) -> Option<String> {
-        self.node_name().prefix
+        self.node_name().prefix().clone()
     }
+    ///
+    /// Set, or with `None` remove, the `prefix` for the node; see [`prefix`](#method.prefix).
+    ///
+    /// The local name and namespace URI of the node are unchanged, so its `node_name` (and so its
+    /// serialized form) reflects the new prefix immediately. For nodes of any type other than
+    /// `Element` and `Attribute` this has no effect.
+    ///
+    fn set_prefix(&mut self, prefix: Option<&str>) -> Result<()>;
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -1840,15 +2548,84 @@ pub trait Text: CharacterData {
     ///   of 16-bit units in data.
     /// * `NO_MODIFICATION_ALLOWED_ERR`: Raised if this node is readonly.
     ///
+    /// **Note:** This implementation drops the `_text` suffix from the method name, and as with
+    /// [`CharacterData`](trait.CharacterData.html) the `offset` is counted in `char`s. Splitting a
+    /// `CDataSection` returns a new `CDataSection`.
+    ///
     fn split(&mut self, offset: usize) -> Result<Self::NodeRef>;
+    ///
+    /// Returns whether this text node contains only whitespace, as defined by XML 1.0 (space,
+    /// tab, carriage return, and line feed); an empty node is also whitespace. This is a DOM
+    /// Level 3 member of `Text`.
+    ///
+    /// # Specification
+    ///
+    /// Returns whether this text node contains element content whitespace, often abusively called
+    /// "ignorable whitespace". The text node is determined to contain whitespace in element
+    /// content during the load of the document or if validation occurs while using
+    /// `Document.normalizeDocument()`.
+    ///
+    /// **Note:** This implementation does not validate, so only the content of the node is
+    /// considered, not whether its parent is declared to have element content.
+    ///
+    fn is_element_content_whitespace(&self) -> bool;
 }
 
 // ------------------------------------------------------------------------------------------------
 
 ///
-/// This corresponds to the DOM `NodeType` set of constants.
+/// When associating an object to a key on a node using
+/// [`Node::set_user_data`](trait.Node.html#tymethod.set_user_data) the application can provide a
+/// handler that gets called when the node the object is associated to is being cloned, imported,
+/// renamed, adopted, or deleted. This is a DOM Level 3 interface.
 ///
-#[derive(Clone, Debug, PartialEq, Eq)]
+/// The handler is called once the operation is complete, for each node with user data that the
+/// operation applied to, including the descendants and attributes of the node passed to the
+/// operation. A node is deleted when the last reference to it is dropped; the handler is then
+/// called with neither a source nor a destination node.
+///
+/// # Specification
+///
+/// This method is called whenever the node for which this handler is registered is imported or
+/// cloned. DOM applications must not raise exceptions in a `UserDataHandler`. The effect of
+/// throwing exceptions from the handler is DOM implementation dependent.
+///
+/// **Parameters**
+///
+/// * `operation` of type `unsigned short`: Specifies the type of operation that is being
+///   performed on the node.
+/// * `key` of type `DOMString`: Specifies the key for which this handler is being called.
+/// * `data` of type `DOMUserData`: Specifies the data for which this handler is being called.
+/// * `src` of type `Node`: Specifies the node being cloned, adopted, imported, or renamed. This is
+///   `null` when the node is being deleted.
+/// * `dst` of type `Node`: Specifies the node newly created if any, or `null`.
+///
+pub trait UserDataHandler {
+    ///
+    /// The opaque reference type that wraps the implementation of a node within the DOM.
+    ///
+    type NodeRef;
+    ///
+    /// Called after `operation` is applied to `source`, for the object associated to `key`.
+    ///
+    fn handle(
+        &self,
+        operation: UserDataOperation,
+        key: &str,
+        data: &UserData,
+        source: Option<&Self::NodeRef>,
+        destination: Option<&Self::NodeRef>,
+    );
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This corresponds to the DOM `NodeType` set of constants. The numeric code of each, as given by
+/// the specification, is returned by `u16::from`, and `NodeType::try_from` returns the node type
+/// for a code or `Error::NotSupported`.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[repr(u16)]
 pub enum NodeType {
     /// The node is an [`Element`](trait.Element.html)
@@ -1876,3 +2653,73 @@ pub enum NodeType {
     /// The node is a `Notation`
     Notation,
 }
+
+///
+/// This corresponds to the DOM Level 3 `OperationType` set of constants, the operations for which
+/// a [`UserDataHandler`](trait.UserDataHandler.html) is called.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u16)]
+pub enum UserDataOperation {
+    /// The node is cloned, using `Node::clone_node`.
+    NodeCloned = 1,
+    /// The node is imported, using `Document::import_node`.
+    NodeImported,
+    /// The node is deleted.
+    NodeDeleted,
+    /// The node is renamed, using `Document::rename_node`.
+    NodeRenamed,
+    /// The node is adopted, using `Document::adopt_node`.
+    NodeAdopted,
+}
+
+///
+/// The object associated to a key on a node by
+/// [`Node::set_user_data`](trait.Node.html#tymethod.set_user_data); with the `sync` feature this
+/// must also be `Send` and `Sync`.
+///
+#[cfg(not(feature = "sync"))]
+pub type UserData = Shared<dyn Any>;
+
+///
+/// The object associated to a key on a node by
+/// [`Node::set_user_data`](trait.Node.html#tymethod.set_user_data); with the `sync` feature this
+/// must also be `Send` and `Sync`.
+///
+#[cfg(feature = "sync")]
+pub type UserData = Shared<dyn Any + Send + Sync>;
+
+///
+/// A shared reference to a [`UserDataHandler`](trait.UserDataHandler.html); with the `sync`
+/// feature the handler must also be `Send` and `Sync`.
+///
+#[cfg(not(feature = "sync"))]
+pub type UserDataHandlerRef<T> = Shared<dyn UserDataHandler<NodeRef = T>>;
+
+///
+/// A shared reference to a [`UserDataHandler`](trait.UserDataHandler.html); with the `sync`
+/// feature the handler must also be `Send` and `Sync`.
+///
+#[cfg(feature = "sync")]
+pub type UserDataHandlerRef<T> = Shared<dyn UserDataHandler<NodeRef = T> + Send + Sync>;
+
+///
+/// This corresponds to the DOM Level 3 `DocumentPosition` set of constants, the bit-mask values
+/// returned by [`Node::compare_document_position`](trait.Node.html#tymethod.compare_document_position).
+///
+pub mod document_position {
+    /// The two nodes are disconnected. Order between disconnected nodes is always
+    /// implementation-specific.
+    pub const DISCONNECTED: u16 = 0x01;
+    /// The second node precedes the reference node.
+    pub const PRECEDING: u16 = 0x02;
+    /// The node follows the reference node.
+    pub const FOLLOWING: u16 = 0x04;
+    /// The node contains the reference node. A node which contains is always preceding, too.
+    pub const CONTAINS: u16 = 0x08;
+    /// The node is contained by the reference node. A node which is contained is always
+    /// following, too.
+    pub const CONTAINED_BY: u16 = 0x10;
+    /// The determination of preceding versus following is implementation-specific.
+    pub const IMPLEMENTATION_SPECIFIC: u16 = 0x20;
+}
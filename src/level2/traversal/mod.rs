@@ -0,0 +1,501 @@
+/*!
+Implementation for DOM Level 2 Traversal.
+
+# Interface Mapping
+
+| IDL Interface       | Rust Mapping                                                    |
+|---------------------|-----------------------------------------------------------------|
+| `DocumentTraversal` | [`DocumentTraversal`](trait.DocumentTraversal.html)             |
+| `NodeFilter`        | [`NodeFilter`](trait.NodeFilter.html), [`FilterResult`](enum.FilterResult.html), and [`WhatToShow`](struct.WhatToShow.html) |
+| `NodeIterator`      | [`NodeIterator`](struct.NodeIterator.html)                      |
+| `TreeWalker`        | [`TreeWalker`](struct.TreeWalker.html)                          |
+
+# Specification
+
+From [Document Object Model Traversal](https://www.w3.org/TR/DOM-Level-2-Traversal-Range/traversal.html):
+
+This section describes the optional DOM Level 2 Traversal feature. A DOM application may use the
+`hasFeature(feature, version)` method of the `DOMImplementation` interface with parameter values
+"Traversal" and "2.0" (respectively) to determine whether or not this module is supported by the
+implementation.
+
+# Example
+
+```rust
+# #[cfg(feature = "quick_parser")]
+# fn main() {
+use xml_dom::level2::*;
+use xml_dom::level2::traversal::*;
+use xml_dom::parser::read_xml;
+
+let document_node = read_xml(r#"<a><b/>text<c><d/></c></a>"#).unwrap();
+let only_leaves: NodeFilterRef = Shared::new(|node: &RefNode| {
+    if node.has_child_nodes() {
+        FilterResult::Skip
+    } else {
+        FilterResult::Accept
+    }
+});
+let mut iterator = document_node
+    .create_node_iterator(document_node.clone(), WhatToShow::ELEMENT, Some(only_leaves), true)
+    .unwrap();
+
+let mut names = Vec::new();
+while let Some(node) = iterator.next_node().unwrap() {
+    names.push(node.node_name().to_string());
+}
+assert_eq!(names, vec!["b", "d"]);
+# }
+# #[cfg(not(feature = "quick_parser"))]
+# fn main() {}
+```
+*/
+
+use crate::level2::node_impl::RefNode;
+use crate::level2::traits::{Document, Node, NodeType};
+use crate::shared::error::Result;
+use crate::shared::rc_cell::Shared;
+use std::fmt::{Binary, Display, Formatter, Result as FmtResult};
+use std::ops::{BitAnd, BitOr};
+
+// ------------------------------------------------------------------------------------------------
+// Public Modules
+// ------------------------------------------------------------------------------------------------
+
+mod node_iterator;
+pub use node_iterator::NodeIterator;
+
+mod tree_walker;
+pub use tree_walker::TreeWalker;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This corresponds to the constants returned by the DOM `NodeFilter.acceptNode` method.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u16)]
+pub enum FilterResult {
+    /// Accept the node. Navigation methods defined for `NodeIterator` or `TreeWalker` will return
+    /// this node.
+    Accept = 1,
+    /// Reject the node. Navigation methods defined for `NodeIterator` or `TreeWalker` will not
+    /// return this node. For `TreeWalker`, the children of this node will also be rejected.
+    /// `NodeIterator`s treat this as a synonym for `Skip`.
+    Reject,
+    /// Skip this single node. Navigation methods defined for `NodeIterator` or `TreeWalker` will
+    /// not return this node. For both `NodeIterator` and `TreeWalker`, the children of this node
+    /// will still be considered.
+    Skip,
+}
+
+///
+/// This corresponds to the `SHOW_*` constants of the DOM `NodeFilter` interface, a set of flags
+/// that determine which node types are presented to the filter, and so may be returned by a
+/// `NodeIterator` or `TreeWalker`. Node types that are not shown are skipped.
+///
+/// Flags may be combined with the `|` operator:
+///
+/// ```rust
+/// use xml_dom::level2::NodeType;
+/// use xml_dom::level2::traversal::WhatToShow;
+///
+/// let what_to_show = WhatToShow::ELEMENT | WhatToShow::TEXT;
+/// assert!(what_to_show.shows(&NodeType::Text));
+/// assert!(!what_to_show.shows(&NodeType::Comment));
+/// ```
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WhatToShow(u32);
+
+///
+/// This corresponds to the DOM `NodeFilter` interface.
+///
+/// Filters are objects that know how to "filter out" nodes. If a `NodeIterator` or `TreeWalker` is
+/// given a `NodeFilter`, it applies the filter before it returns the next node. If the filter says
+/// to accept the node, the traversal logic returns it; otherwise, traversal looks for the next
+/// node and pretends that the node that was rejected was not there.
+///
+/// This trait is implemented for any closure of the form `Fn(&RefNode) -> FilterResult`.
+///
+/// # Specification
+///
+/// The DOM does not provide any filters. `NodeFilter` is just an interface that users can
+/// implement to provide their own filters.
+///
+/// `NodeFilters` do not need to know how to traverse from node to node, nor do they need to know
+/// anything about the data structure that is being traversed. This makes it very easy to write
+/// filters, since the only thing they have to know how to do is evaluate a single node. One filter
+/// may be used with a number of different kinds of traversals, encouraging code reuse.
+///
+pub trait NodeFilter {
+    ///
+    /// Test whether a specified node is visible in the logical view of a `TreeWalker` or
+    /// `NodeIterator`. This function will be called by the implementation of `TreeWalker` and
+    /// `NodeIterator`; it is not normally called directly from user code.
+    ///
+    /// # Specification
+    ///
+    /// **Parameters**
+    ///
+    /// * `n` of type `Node`: The node to check to see if it passes the filter or not.
+    ///
+    /// **Return Value**
+    ///
+    /// * `short`: a constant to determine whether the node is accepted, rejected, or skipped, as
+    ///   defined above.
+    ///
+    fn accept_node(&self, node: &RefNode) -> FilterResult;
+}
+
+///
+/// A shared reference to a [`NodeFilter`](trait.NodeFilter.html); with the `sync` feature the
+/// filter must also be `Send` and `Sync`.
+///
+#[cfg(not(feature = "sync"))]
+pub type NodeFilterRef = Shared<dyn NodeFilter>;
+
+///
+/// A shared reference to a [`NodeFilter`](trait.NodeFilter.html); with the `sync` feature the
+/// filter must also be `Send` and `Sync`.
+///
+#[cfg(feature = "sync")]
+pub type NodeFilterRef = Shared<dyn NodeFilter + Send + Sync>;
+
+///
+/// This corresponds to the DOM `DocumentTraversal` interface, it contains methods that create
+/// iterators to traverse a node and its children in document order (depth first, pre-order
+/// traversal).
+///
+pub trait DocumentTraversal: Document {
+    ///
+    /// Create a new `NodeIterator` over the subtree rooted at the specified node.
+    ///
+    /// # Specification
+    ///
+    /// **Parameters**
+    ///
+    /// * `root` of type `Node`: The node which will be iterated together with its children. The
+    ///   iterator is initially positioned just before this node. The `whatToShow` flags and the
+    ///   filter, if any, are not considered when setting this position. The root must not be
+    ///   `null`.
+    /// * `whatToShow` of type `unsigned long`: This flag specifies which node types may appear in
+    ///   the logical view of the tree presented by the iterator. See the description of
+    ///   `NodeFilter` for the set of possible `SHOW_` values. These flags can be combined using
+    ///   `OR`.
+    /// * `filter` of type `NodeFilter`: The `NodeFilter` to be used with this `NodeIterator`, or
+    ///   `null` to indicate no filter.
+    /// * `entityReferenceExpansion` of type `boolean`: The value of this flag determines whether
+    ///   entity reference nodes are expanded.
+    ///
+    /// **Return Value**
+    ///
+    /// * `NodeIterator`: The newly created `NodeIterator`.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `NOT_SUPPORTED_ERR`: Raised if the specified root is `null`.
+    ///
+    fn create_node_iterator(
+        &self,
+        root: RefNode,
+        what_to_show: WhatToShow,
+        filter: Option<NodeFilterRef>,
+        entity_reference_expansion: bool,
+    ) -> Result<NodeIterator>;
+    ///
+    /// Create a new `TreeWalker` over the subtree rooted at the specified node.
+    ///
+    /// # Specification
+    ///
+    /// **Parameters**
+    ///
+    /// * `root` of type `Node`: The node which will serve as the root for the `TreeWalker`. The
+    ///   `whatToShow` flags and the `NodeFilter` are not considered when setting this value; any
+    ///   node type will be accepted as the root. The `currentNode` of the `TreeWalker` is
+    ///   initialized to this node, whether or not it is visible. The root functions as a stopping
+    ///   point for traversal methods that look upward in the document structure, such as
+    ///   `parentNode` and `nextNode`. The root must not be `null`.
+    /// * `whatToShow` of type `unsigned long`: This flag specifies which node types may appear in
+    ///   the logical view of the tree presented by the tree-walker. See the description of
+    ///   `NodeFilter` for the set of possible `SHOW_` values. These flags can be combined using
+    ///   `OR`.
+    /// * `filter` of type `NodeFilter`: The `NodeFilter` to be used with this `TreeWalker`, or
+    ///   `null` to indicate no filter.
+    /// * `entityReferenceExpansion` of type `boolean`: If this flag is `false`, the contents of
+    ///   `EntityReference` nodes are not presented in the logical view.
+    ///
+    /// **Return Value**
+    ///
+    /// * `TreeWalker`: The newly created `TreeWalker`.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `NOT_SUPPORTED_ERR`: Raised if the specified root is `null`.
+    ///
+    fn create_tree_walker(
+        &self,
+        root: RefNode,
+        what_to_show: WhatToShow,
+        filter: Option<NodeFilterRef>,
+        entity_reference_expansion: bool,
+    ) -> Result<TreeWalker>;
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for FilterResult {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{}",
+            match self {
+                FilterResult::Accept => "FILTER_ACCEPT",
+                FilterResult::Reject => "FILTER_REJECT",
+                FilterResult::Skip => "FILTER_SKIP",
+            }
+        )
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Default for WhatToShow {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+impl Binary for WhatToShow {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        if f.alternate() {
+            write!(f, "{:#034b}", self.0)
+        } else {
+            write!(f, "{:032b}", self.0)
+        }
+    }
+}
+
+impl BitAnd for WhatToShow {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl BitOr for WhatToShow {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl From<NodeType> for WhatToShow {
+    fn from(node_type: NodeType) -> Self {
+        Self(1 << (node_type as u32 - 1))
+    }
+}
+
+impl From<u32> for WhatToShow {
+    fn from(flags: u32) -> Self {
+        Self(flags)
+    }
+}
+
+impl From<WhatToShow> for u32 {
+    fn from(what_to_show: WhatToShow) -> Self {
+        what_to_show.0
+    }
+}
+
+impl WhatToShow {
+    /// Show all `Node`s.
+    pub const ALL: Self = Self(0xFFFF_FFFF);
+    /// Show `Element` nodes.
+    pub const ELEMENT: Self = Self(0x0000_0001);
+    /// Show `Attr` nodes. This is meaningful only when creating an iterator or tree-walker with
+    /// an attribute node as its root.
+    pub const ATTRIBUTE: Self = Self(0x0000_0002);
+    /// Show `Text` nodes.
+    pub const TEXT: Self = Self(0x0000_0004);
+    /// Show `CDATASection` nodes.
+    pub const CDATA_SECTION: Self = Self(0x0000_0008);
+    /// Show `EntityReference` nodes.
+    pub const ENTITY_REFERENCE: Self = Self(0x0000_0010);
+    /// Show `Entity` nodes. This is meaningful only when creating an iterator or tree-walker with
+    /// an `Entity` node as its root.
+    pub const ENTITY: Self = Self(0x0000_0020);
+    /// Show `ProcessingInstruction` nodes.
+    pub const PROCESSING_INSTRUCTION: Self = Self(0x0000_0040);
+    /// Show `Comment` nodes.
+    pub const COMMENT: Self = Self(0x0000_0080);
+    /// Show `Document` nodes.
+    pub const DOCUMENT: Self = Self(0x0000_0100);
+    /// Show `DocumentType` nodes.
+    pub const DOCUMENT_TYPE: Self = Self(0x0000_0200);
+    /// Show `DocumentFragment` nodes.
+    pub const DOCUMENT_FRAGMENT: Self = Self(0x0000_0400);
+    /// Show `Notation` nodes. This is meaningful only when creating an iterator or tree-walker
+    /// with a `Notation` node as its root.
+    pub const NOTATION: Self = Self(0x0000_0800);
+
+    ///
+    /// Returns `true` if nodes of type `node_type` are shown, else `false`.
+    ///
+    pub fn shows(&self, node_type: &NodeType) -> bool {
+        self.0 & Self::from(*node_type).0 != 0
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl<F> NodeFilter for F
+where
+    F: Fn(&RefNode) -> FilterResult,
+{
+    fn accept_node(&self, node: &RefNode) -> FilterResult {
+        self(node)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl DocumentTraversal for RefNode {
+    fn create_node_iterator(
+        &self,
+        root: RefNode,
+        what_to_show: WhatToShow,
+        filter: Option<NodeFilterRef>,
+        entity_reference_expansion: bool,
+    ) -> Result<NodeIterator> {
+        Ok(NodeIterator::new(
+            root,
+            what_to_show,
+            filter,
+            entity_reference_expansion,
+        ))
+    }
+
+    fn create_tree_walker(
+        &self,
+        root: RefNode,
+        what_to_show: WhatToShow,
+        filter: Option<NodeFilterRef>,
+        entity_reference_expansion: bool,
+    ) -> Result<TreeWalker> {
+        Ok(TreeWalker::new(
+            root,
+            what_to_show,
+            filter,
+            entity_reference_expansion,
+        ))
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+//
+// Apply `what_to_show` and then, if shown, the `filter` to `node`.
+//
+pub(crate) fn filter_node(
+    node: &RefNode,
+    what_to_show: WhatToShow,
+    filter: &Option<NodeFilterRef>,
+) -> FilterResult {
+    if !what_to_show.shows(&node.node_type()) {
+        FilterResult::Skip
+    } else {
+        match filter {
+            None => FilterResult::Accept,
+            Some(filter) => filter.accept_node(node),
+        }
+    }
+}
+
+//
+// The first child of `node`, unless `node` is an entity reference and references are not to be
+// expanded.
+//
+pub(crate) fn first_child(node: &RefNode, expand: bool) -> Option<RefNode> {
+    if expand || node.node_type() != NodeType::EntityReference {
+        node.first_child()
+    } else {
+        None
+    }
+}
+
+//
+// The last child of `node`; see `first_child`.
+//
+pub(crate) fn last_child(node: &RefNode, expand: bool) -> Option<RefNode> {
+    if expand || node.node_type() != NodeType::EntityReference {
+        node.last_child()
+    } else {
+        None
+    }
+}
+
+//
+// The next sibling of `node`, or of its nearest ancestor that has one, without leaving the
+// subtree rooted at `root`.
+//
+pub(crate) fn following_outside(node: &RefNode, root: &RefNode) -> Option<RefNode> {
+    let mut node = node.clone();
+    while &node != root {
+        if let Some(sibling) = node.next_sibling() {
+            return Some(sibling);
+        }
+        node = node.parent_node()?;
+    }
+    None
+}
+
+//
+// The node following `node` in document order, without leaving the subtree rooted at `root`.
+//
+pub(crate) fn following(node: &RefNode, root: &RefNode, expand: bool) -> Option<RefNode> {
+    match first_child(node, expand) {
+        Some(child) => Some(child),
+        None => following_outside(node, root),
+    }
+}
+
+//
+// The node preceding `node` in document order, without leaving the subtree rooted at `root`.
+//
+pub(crate) fn preceding(node: &RefNode, root: &RefNode, expand: bool) -> Option<RefNode> {
+    if node == root {
+        return None;
+    }
+    match node.previous_sibling() {
+        None => node.parent_node(),
+        Some(mut sibling) => {
+            while let Some(child) = last_child(&sibling, expand) {
+                sibling = child;
+            }
+            Some(sibling)
+        }
+    }
+}
+
+//
+// Returns `true` if `ancestor` is `node`, or one of its ancestors.
+//
+pub(crate) fn is_inclusive_ancestor(ancestor: &RefNode, node: &RefNode) -> bool {
+    let mut node = Some(node.clone());
+    while let Some(current) = node {
+        if &current == ancestor {
+            return true;
+        }
+        node = current.parent_node();
+    }
+    false
+}
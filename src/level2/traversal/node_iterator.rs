@@ -0,0 +1,267 @@
+use crate::level2::node_impl::{RefNode, RemovalObserver, SharedRemovalObserver};
+use crate::level2::trait_impls::add_removal_observer;
+use crate::level2::traits::Node;
+use crate::level2::traversal::{
+    filter_node, following, following_outside, is_inclusive_ancestor, preceding, FilterResult,
+    NodeFilterRef, WhatToShow,
+};
+use crate::shared::error::{Error, Result};
+use crate::shared::rc_cell::{Shared, SharedCell};
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This corresponds to the DOM `NodeIterator` interface, created by
+/// [`DocumentTraversal::create_node_iterator`](trait.DocumentTraversal.html#tymethod.create_node_iterator).
+///
+/// # Specification
+///
+/// Iterators are used to step through a set of nodes, e.g. the set of nodes in a `NodeList`, the
+/// document subtree governed by a particular `Node`, the results of a query, or any other set of
+/// nodes. The set of nodes to be iterated is determined by the implementation of the
+/// `NodeIterator`. DOM Level 2 specifies a single `NodeIterator` implementation for document-order
+/// traversal of a document subtree.
+///
+/// # Implementation
+///
+/// The iterator registers itself with the document that owns its root, and if the node it is
+/// positioned at, or one of that node's ancestors, is removed from the tree the iterator is moved
+/// as described in [§1.1.1.1 Iterators and Mutation](https://www.w3.org/TR/DOM-Level-2-Traversal-Range/traversal.html#Iterator-Robustness).
+///
+#[derive(Clone)]
+pub struct NodeIterator {
+    state: Shared<SharedCell<NodeIteratorState>>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+struct NodeIteratorState {
+    root: RefNode,
+    what_to_show: WhatToShow,
+    filter: Option<NodeFilterRef>,
+    expand_entity_references: bool,
+    reference_node: RefNode,
+    pointer_before_reference_node: bool,
+    detached: bool,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Debug for NodeIterator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let state = self.state.borrow();
+        f.debug_struct("NodeIterator")
+            .field("root", &state.root.node_name())
+            .field("what_to_show", &state.what_to_show)
+            .field("has_filter", &state.filter.is_some())
+            .field("expand_entity_references", &state.expand_entity_references)
+            .field("reference_node", &state.reference_node.node_name())
+            .field(
+                "pointer_before_reference_node",
+                &state.pointer_before_reference_node,
+            )
+            .field("detached", &state.detached)
+            .finish()
+    }
+}
+
+impl NodeIterator {
+    pub(crate) fn new(
+        root: RefNode,
+        what_to_show: WhatToShow,
+        filter: Option<NodeFilterRef>,
+        expand_entity_references: bool,
+    ) -> Self {
+        let state = Shared::new(SharedCell::new(NodeIteratorState {
+            root: root.clone(),
+            what_to_show,
+            filter,
+            expand_entity_references,
+            reference_node: root.clone(),
+            pointer_before_reference_node: true,
+            detached: false,
+        }));
+        let observer: SharedRemovalObserver = state.clone();
+        add_removal_observer(&root, Shared::downgrade(&observer));
+        Self { state }
+    }
+
+    ///
+    /// The root node of the `NodeIterator`, as specified when it was created.
+    ///
+    pub fn root(&self) -> RefNode {
+        self.state.borrow().root.clone()
+    }
+
+    ///
+    /// This attribute determines which node types are presented via the iterator. The available
+    /// set of constants is defined in the [`WhatToShow`](struct.WhatToShow.html) type. Nodes not
+    /// accepted by `what_to_show` will be skipped, but their children may still be considered.
+    ///
+    pub fn what_to_show(&self) -> WhatToShow {
+        self.state.borrow().what_to_show
+    }
+
+    ///
+    /// The `NodeFilter` used to screen nodes.
+    ///
+    pub fn filter(&self) -> Option<NodeFilterRef> {
+        self.state.borrow().filter.clone()
+    }
+
+    ///
+    /// The value of this flag determines whether the children of entity reference nodes are
+    /// visible to the iterator. If `false`, they and their descendants will be rejected.
+    ///
+    pub fn expand_entity_references(&self) -> bool {
+        self.state.borrow().expand_entity_references
+    }
+
+    ///
+    /// The node the iterator is currently positioned relative to; initially this is the root.
+    ///
+    pub fn reference_node(&self) -> RefNode {
+        self.state.borrow().reference_node.clone()
+    }
+
+    ///
+    /// Returns `true` if the iterator is positioned before the
+    /// [`reference_node`](#method.reference_node), else `false` if it is after.
+    ///
+    pub fn pointer_before_reference_node(&self) -> bool {
+        self.state.borrow().pointer_before_reference_node
+    }
+
+    ///
+    /// Returns the next node in the set and advances the position of the iterator in the set.
+    /// After a `NodeIterator` is created, the first call to `next_node()` returns the first node
+    /// in the set.
+    ///
+    /// # Specification
+    ///
+    /// **Return Value**
+    ///
+    /// * `Node`: The next `Node` in the set being iterated over, or `null` if there are no more
+    ///   members in that set.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `INVALID_STATE_ERR`: Raised if this method is called after the `detach` method was
+    ///   invoked.
+    ///
+    pub fn next_node(&mut self) -> Result<Option<RefNode>> {
+        self.traverse(true)
+    }
+
+    ///
+    /// Returns the previous node in the set and moves the position of the `NodeIterator`
+    /// backwards in the set.
+    ///
+    /// # Specification
+    ///
+    /// **Return Value**
+    ///
+    /// * `Node`: The previous `Node` in the set being iterated over, or `null` if there are no
+    ///   more members in that set.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `INVALID_STATE_ERR`: Raised if this method is called after the `detach` method was
+    ///   invoked.
+    ///
+    pub fn previous_node(&mut self) -> Result<Option<RefNode>> {
+        self.traverse(false)
+    }
+
+    ///
+    /// Detaches the `NodeIterator` from the set which it iterated over, releasing any
+    /// computational resources and placing the iterator in the `INVALID` state. After `detach`
+    /// has been invoked, calls to `next_node` or `previous_node` will raise the exception
+    /// `INVALID_STATE_ERR`.
+    ///
+    pub fn detach(&mut self) {
+        let mut state = self.state.borrow_mut();
+        state.detached = true;
+        state.reference_node = state.root.clone();
+    }
+
+    fn traverse(&mut self, forward: bool) -> Result<Option<RefNode>> {
+        //
+        // The state is not borrowed while the filter is called, so that the filter may itself
+        // safely modify the tree.
+        //
+        let (root, what_to_show, filter, expand, mut node, mut before_node) = {
+            let state = self.state.borrow();
+            if state.detached {
+                warn!("NodeIterator used after `detach`");
+                return Err(Error::InvalidState);
+            }
+            (
+                state.root.clone(),
+                state.what_to_show,
+                state.filter.clone(),
+                state.expand_entity_references,
+                state.reference_node.clone(),
+                state.pointer_before_reference_node,
+            )
+        };
+        loop {
+            if forward {
+                if before_node {
+                    before_node = false;
+                } else {
+                    match following(&node, &root, expand) {
+                        None => return Ok(None),
+                        Some(next) => node = next,
+                    }
+                }
+            } else if before_node {
+                match preceding(&node, &root, expand) {
+                    None => return Ok(None),
+                    Some(previous) => node = previous,
+                }
+            } else {
+                before_node = true;
+            }
+            if filter_node(&node, what_to_show, &filter) == FilterResult::Accept {
+                break;
+            }
+        }
+        let mut state = self.state.borrow_mut();
+        state.reference_node = node.clone();
+        state.pointer_before_reference_node = before_node;
+        Ok(Some(node))
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl RemovalObserver for NodeIteratorState {
+    fn node_removing(&mut self, node: &RefNode) {
+        if self.detached || node == &self.root || !is_inclusive_ancestor(node, &self.reference_node)
+        {
+            return;
+        }
+        if self.pointer_before_reference_node {
+            if let Some(next) = following_outside(node, &self.root) {
+                self.reference_node = next;
+                return;
+            }
+            self.pointer_before_reference_node = false;
+        }
+        //
+        // The iterator is now positioned after the node that precedes the removed subtree.
+        //
+        self.reference_node = match preceding(node, &self.root, self.expand_entity_references) {
+            None => self.root.clone(),
+            Some(previous) => previous,
+        };
+    }
+}
@@ -0,0 +1,347 @@
+use crate::level2::node_impl::RefNode;
+use crate::level2::traits::Node;
+use crate::level2::traversal::{
+    filter_node, first_child, following_outside, last_child, FilterResult, NodeFilterRef,
+    WhatToShow,
+};
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// This corresponds to the DOM `TreeWalker` interface, created by
+/// [`DocumentTraversal::create_tree_walker`](trait.DocumentTraversal.html#tymethod.create_tree_walker).
+///
+/// # Specification
+///
+/// `TreeWalker` objects are used to navigate a document tree or subtree using the view of the
+/// document defined by their `whatToShow` flags and filter (if any). Any function which performs
+/// navigation using a `TreeWalker` will automatically support any view defined by a `TreeWalker`.
+///
+/// Omitting nodes from the logical view of a subtree can result in a structure that is
+/// substantially different from the same subtree in the complete, unfiltered document. Nodes that
+/// are siblings in the `TreeWalker` view may be children of different, widely separated nodes in
+/// the original view. For instance, consider a `NodeFilter` that skips all nodes except for `Text`
+/// nodes and the root node of a document. In the logical view that results, all text nodes will be
+/// siblings and appear as direct children of the root node, no matter how deeply nested the
+/// structure of the original document.
+///
+#[derive(Clone)]
+pub struct TreeWalker {
+    root: RefNode,
+    what_to_show: WhatToShow,
+    filter: Option<NodeFilterRef>,
+    expand_entity_references: bool,
+    current_node: RefNode,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Debug for TreeWalker {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("TreeWalker")
+            .field("root", &self.root.node_name())
+            .field("what_to_show", &self.what_to_show)
+            .field("has_filter", &self.filter.is_some())
+            .field("expand_entity_references", &self.expand_entity_references)
+            .field("current_node", &self.current_node.node_name())
+            .finish()
+    }
+}
+
+impl TreeWalker {
+    pub(crate) fn new(
+        root: RefNode,
+        what_to_show: WhatToShow,
+        filter: Option<NodeFilterRef>,
+        expand_entity_references: bool,
+    ) -> Self {
+        Self {
+            root: root.clone(),
+            what_to_show,
+            filter,
+            expand_entity_references,
+            current_node: root,
+        }
+    }
+
+    ///
+    /// The root node of the `TreeWalker`, as specified when it was created.
+    ///
+    pub fn root(&self) -> RefNode {
+        self.root.clone()
+    }
+
+    ///
+    /// This attribute determines which node types are presented via the `TreeWalker`. The
+    /// available set of constants is defined in the [`WhatToShow`](struct.WhatToShow.html) type.
+    /// Nodes not accepted by `what_to_show` will be skipped, but their children may still be
+    /// considered.
+    ///
+    pub fn what_to_show(&self) -> WhatToShow {
+        self.what_to_show
+    }
+
+    ///
+    /// The filter used to screen nodes.
+    ///
+    pub fn filter(&self) -> Option<NodeFilterRef> {
+        self.filter.clone()
+    }
+
+    ///
+    /// The value of this flag determines whether the children of entity reference nodes are
+    /// visible to the `TreeWalker`. If `false`, they and their descendants will be rejected.
+    ///
+    pub fn expand_entity_references(&self) -> bool {
+        self.expand_entity_references
+    }
+
+    ///
+    /// The node at which the `TreeWalker` is currently positioned.
+    ///
+    /// # Specification
+    ///
+    /// Alterations to the DOM tree may cause the current node to no longer be accepted by the
+    /// `TreeWalker`'s associated filter. `currentNode` may also be explicitly set to any node,
+    /// whether or not it is within the subtree specified by the root node or would be accepted by
+    /// the filter and `whatToShow` flags. Further traversal occurs relative to `currentNode` even
+    /// if it is not part of the current view, by applying the filters in the requested direction;
+    /// if no traversal is possible, `currentNode` is not changed.
+    ///
+    pub fn current_node(&self) -> RefNode {
+        self.current_node.clone()
+    }
+
+    ///
+    /// Set the node at which the `TreeWalker` is positioned; see
+    /// [`current_node`](#method.current_node).
+    ///
+    pub fn set_current_node(&mut self, current_node: RefNode) {
+        self.current_node = current_node;
+    }
+
+    ///
+    /// Moves to and returns the closest visible ancestor node of the current node. If the search
+    /// for `parent_node` attempts to step upward from the `TreeWalker`'s root node, or if it fails
+    /// to find a visible ancestor node, this method retains the current position and returns
+    /// `None`.
+    ///
+    pub fn parent_node(&mut self) -> Option<RefNode> {
+        let mut node = self.current_node.clone();
+        while node != self.root {
+            node = node.parent_node()?;
+            if self.accept(&node) == FilterResult::Accept {
+                self.current_node = node.clone();
+                return Some(node);
+            }
+        }
+        None
+    }
+
+    ///
+    /// Moves the `TreeWalker` to the first visible child of the current node, and returns the new
+    /// node. If the current node has no visible children, returns `None`, and retains the current
+    /// node.
+    ///
+    pub fn first_child(&mut self) -> Option<RefNode> {
+        self.traverse_children(true)
+    }
+
+    ///
+    /// Moves the `TreeWalker` to the last visible child of the current node, and returns the new
+    /// node. If the current node has no visible children, returns `None`, and retains the current
+    /// node.
+    ///
+    pub fn last_child(&mut self) -> Option<RefNode> {
+        self.traverse_children(false)
+    }
+
+    ///
+    /// Moves the `TreeWalker` to the previous sibling of the current node, and returns the new
+    /// node. If the current node has no visible previous sibling, returns `None`, and retains the
+    /// current node.
+    ///
+    pub fn previous_sibling(&mut self) -> Option<RefNode> {
+        self.traverse_siblings(false)
+    }
+
+    ///
+    /// Moves the `TreeWalker` to the next sibling of the current node, and returns the new node.
+    /// If the current node has no visible next sibling, returns `None`, and retains the current
+    /// node.
+    ///
+    pub fn next_sibling(&mut self) -> Option<RefNode> {
+        self.traverse_siblings(true)
+    }
+
+    ///
+    /// Moves the `TreeWalker` to the previous visible node in document order relative to the
+    /// current node, and returns the new node. If the current node has no previous node, or if
+    /// the search for `previous_node` attempts to step upward from the `TreeWalker`'s root node,
+    /// returns `None`, and retains the current node.
+    ///
+    pub fn previous_node(&mut self) -> Option<RefNode> {
+        let mut node = self.current_node.clone();
+        while node != self.root {
+            let mut sibling = node.previous_sibling();
+            while let Some(next_sibling) = sibling {
+                node = next_sibling;
+                let mut result = self.accept(&node);
+                while result != FilterResult::Reject {
+                    match self.last_child_of(&node) {
+                        None => break,
+                        Some(child) => {
+                            node = child;
+                            result = self.accept(&node);
+                        }
+                    }
+                }
+                if result == FilterResult::Accept {
+                    self.current_node = node.clone();
+                    return Some(node);
+                }
+                sibling = node.previous_sibling();
+            }
+            node = node.parent_node()?;
+            if self.accept(&node) == FilterResult::Accept {
+                self.current_node = node.clone();
+                return Some(node);
+            }
+        }
+        None
+    }
+
+    ///
+    /// Moves the `TreeWalker` to the next visible node in document order relative to the current
+    /// node, and returns the new node. If the current node has no next node, or if the search for
+    /// `next_node` attempts to step upward from the `TreeWalker`'s root node, returns `None`, and
+    /// retains the current node.
+    ///
+    pub fn next_node(&mut self) -> Option<RefNode> {
+        let mut node = self.current_node.clone();
+        let mut result = FilterResult::Accept;
+        loop {
+            while result != FilterResult::Reject {
+                match self.first_child_of(&node) {
+                    None => break,
+                    Some(child) => {
+                        node = child;
+                        result = self.accept(&node);
+                        if result == FilterResult::Accept {
+                            self.current_node = node.clone();
+                            return Some(node);
+                        }
+                    }
+                }
+            }
+            node = following_outside(&node, &self.root)?;
+            result = self.accept(&node);
+            if result == FilterResult::Accept {
+                self.current_node = node.clone();
+                return Some(node);
+            }
+        }
+    }
+
+    fn traverse_children(&mut self, first: bool) -> Option<RefNode> {
+        let mut node = self.child_of(&self.current_node.clone(), first);
+        while let Some(current) = node {
+            match self.accept(&current) {
+                FilterResult::Accept => {
+                    self.current_node = current.clone();
+                    return Some(current);
+                }
+                FilterResult::Skip => {
+                    if let Some(child) = self.child_of(&current, first) {
+                        node = Some(child);
+                        continue;
+                    }
+                }
+                FilterResult::Reject => {}
+            }
+            //
+            // Move to the next sibling of `current`, or of the nearest ancestor below the current
+            // node that has one.
+            //
+            let mut current = current;
+            node = loop {
+                if let Some(sibling) = sibling_of(&current, first) {
+                    break Some(sibling);
+                }
+                match current.parent_node() {
+                    None => return None,
+                    Some(parent) => {
+                        if parent == self.root || parent == self.current_node {
+                            return None;
+                        }
+                        current = parent;
+                    }
+                }
+            };
+        }
+        None
+    }
+
+    fn traverse_siblings(&mut self, next: bool) -> Option<RefNode> {
+        let mut node = self.current_node.clone();
+        if node == self.root {
+            return None;
+        }
+        loop {
+            let mut sibling = sibling_of(&node, next);
+            while let Some(current) = sibling {
+                node = current;
+                let result = self.accept(&node);
+                if result == FilterResult::Accept {
+                    self.current_node = node.clone();
+                    return Some(node);
+                }
+                sibling = self.child_of(&node, next);
+                if result == FilterResult::Reject || sibling.is_none() {
+                    sibling = sibling_of(&node, next);
+                }
+            }
+            node = node.parent_node()?;
+            if node == self.root || self.accept(&node) == FilterResult::Accept {
+                return None;
+            }
+        }
+    }
+
+    fn accept(&self, node: &RefNode) -> FilterResult {
+        filter_node(node, self.what_to_show, &self.filter)
+    }
+
+    fn child_of(&self, node: &RefNode, first: bool) -> Option<RefNode> {
+        if first {
+            self.first_child_of(node)
+        } else {
+            self.last_child_of(node)
+        }
+    }
+
+    fn first_child_of(&self, node: &RefNode) -> Option<RefNode> {
+        first_child(node, self.expand_entity_references)
+    }
+
+    fn last_child_of(&self, node: &RefNode) -> Option<RefNode> {
+        last_child(node, self.expand_entity_references)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn sibling_of(node: &RefNode, next: bool) -> Option<RefNode> {
+    if next {
+        node.next_sibling()
+    } else {
+        node.previous_sibling()
+    }
+}
@@ -0,0 +1,632 @@
+/*!
+Evaluation of a parsed `Expr` against a context node, following the data model, and the
+conversion and comparison rules, of XPath 1.0.
+*/
+
+use crate::level2::convert::as_attribute;
+use crate::level2::ext::{InheritedAttributes, TreeIterators};
+use crate::level2::node_impl::RefNode;
+use crate::level2::trait_impls::{is_namespace_declaration, normalized_value};
+use crate::level2::traits::{Node, NodeType};
+use crate::level2::xpath::parser::{ArithmeticOp, Axis, CompareOp, Expr, NodeTest, PathStart};
+use crate::level2::xpath::XPathValue;
+use crate::shared::error::{Error, Result, MSG_INVALID_EXPRESSION};
+use crate::shared::rc_cell::Shared;
+use crate::shared::text::is_xml_space;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+pub(crate) struct Context {
+    pub(crate) node: RefNode,
+    pub(crate) position: usize,
+    pub(crate) size: usize,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+pub(crate) fn evaluate(expr: &Expr, context: &Context) -> Result<XPathValue> {
+    Ok(match expr {
+        Expr::Or(lhs, rhs) => XPathValue::Boolean(
+            evaluate(lhs, context)?.boolean() || evaluate(rhs, context)?.boolean(),
+        ),
+        Expr::And(lhs, rhs) => XPathValue::Boolean(
+            evaluate(lhs, context)?.boolean() && evaluate(rhs, context)?.boolean(),
+        ),
+        Expr::Compare(op, lhs, rhs) => XPathValue::Boolean(compare(
+            *op,
+            &evaluate(lhs, context)?,
+            &evaluate(rhs, context)?,
+        )),
+        Expr::Arithmetic(op, lhs, rhs) => {
+            let lhs = evaluate(lhs, context)?.number();
+            let rhs = evaluate(rhs, context)?.number();
+            XPathValue::Number(match op {
+                ArithmeticOp::Add => lhs + rhs,
+                ArithmeticOp::Subtract => lhs - rhs,
+                ArithmeticOp::Multiply => lhs * rhs,
+                ArithmeticOp::Divide => lhs / rhs,
+                ArithmeticOp::Modulo => lhs % rhs,
+            })
+        }
+        Expr::Negate(operand) => XPathValue::Number(-evaluate(operand, context)?.number()),
+        Expr::Union(lhs, rhs) => {
+            let mut nodes = evaluate(lhs, context)?.into_node_set()?;
+            nodes.extend(evaluate(rhs, context)?.into_node_set()?);
+            XPathValue::NodeSet(document_order(nodes))
+        }
+        Expr::Literal(value) => XPathValue::String(value.clone()),
+        Expr::Number(value) => XPathValue::Number(*value),
+        Expr::Function(name, arguments) => call_function(name, arguments, context)?,
+        Expr::Filter(primary, predicates) => {
+            let nodes = evaluate(primary, context)?.into_node_set()?;
+            XPathValue::NodeSet(filter(nodes, predicates)?)
+        }
+        Expr::Path(start, steps) => {
+            let mut nodes = match start {
+                PathStart::Context => vec![context.node.clone()],
+                PathStart::Root => vec![root(&context.node)],
+                PathStart::Expr(expr) => evaluate(expr, context)?.into_node_set()?,
+            };
+            for step in steps {
+                let mut selected = Vec::new();
+                for node in &nodes {
+                    let mut candidates = Vec::new();
+                    for candidate in axis_nodes(node, step.axis) {
+                        if matches_node_test(&candidate, step.axis, &step.node_test) {
+                            candidates.push(candidate);
+                        }
+                    }
+                    selected.extend(filter(candidates, &step.predicates)?);
+                }
+                nodes = if nodes.len() == 1 && is_ordered_axis(step.axis) {
+                    selected
+                } else {
+                    document_order(selected)
+                };
+            }
+            XPathValue::NodeSet(nodes)
+        }
+    })
+}
+
+///
+/// The string-value of a node, from XPath 1.0 [§5 Data Model](https://www.w3.org/TR/xpath-10/#data-model).
+///
+pub(crate) fn string_value(node: &RefNode) -> String {
+    match node.node_type() {
+        NodeType::Document | NodeType::DocumentFragment | NodeType::Element => node
+            .descendants()
+            .filter(is_text)
+            .filter_map(|descendant| descendant.node_value())
+            .collect(),
+        NodeType::Attribute => normalized_value(node).unwrap_or_default(),
+        _ => node.node_value().unwrap_or_default(),
+    }
+}
+
+///
+/// The conversion of a string to a number, from XPath 1.0
+/// [§4.4 Number Functions](https://www.w3.org/TR/xpath-10/#function-number).
+///
+pub(crate) fn string_to_number(value: &str) -> f64 {
+    let value = value.trim_matches(is_xml_space);
+    let digits = value.strip_prefix('-').unwrap_or(value);
+    if digits.chars().all(|c| c.is_ascii_digit() || c == '.')
+        && digits.chars().filter(|c| *c == '.').count() <= 1
+        && digits.chars().any(|c| c.is_ascii_digit())
+    {
+        value.parse().unwrap_or(f64::NAN)
+    } else {
+        f64::NAN
+    }
+}
+
+///
+/// The conversion of a number to a string, from XPath 1.0
+/// [§4.2 String Functions](https://www.w3.org/TR/xpath-10/#function-string).
+///
+pub(crate) fn number_to_string(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "Infinity" } else { "-Infinity" }.to_string()
+    } else if value == 0.0 {
+        "0".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn invalid_expression<T>() -> Result<T> {
+    warn!("{}", MSG_INVALID_EXPRESSION);
+    Err(Error::InvalidExpression)
+}
+
+fn is_text(node: &RefNode) -> bool {
+    matches!(node.node_type(), NodeType::Text | NodeType::CData)
+}
+
+//
+// The root of the tree containing `node`, usually the document; an attribute is part of the tree
+// of its owner element.
+//
+fn root(node: &RefNode) -> RefNode {
+    let node = owner_element(node).unwrap_or_else(|| node.clone());
+    node.ancestors().last().unwrap_or(node)
+}
+
+fn owner_element(node: &RefNode) -> Option<RefNode> {
+    as_attribute(node)
+        .ok()
+        .and_then(|attribute| attribute.owner_element())
+}
+
+//
+// Sort `nodes` into document order, removing any duplicates.
+//
+fn document_order(nodes: Vec<RefNode>) -> Vec<RefNode> {
+    let mut seen = HashSet::new();
+    let mut nodes: Vec<RefNode> = nodes
+        .into_iter()
+        .filter(|node| seen.insert(Shared::as_ptr(node.as_inner())))
+        .collect();
+    nodes.sort_by(|lhs, rhs| {
+        let position = lhs.compare_document_position(rhs);
+        if position == 0 {
+            Ordering::Equal
+        } else if position & crate::level2::traits::document_position::FOLLOWING != 0 {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        }
+    });
+    nodes
+}
+
+//
+// Whether the nodes of `axis`, from a single context node, are already in document order.
+//
+fn is_ordered_axis(axis: Axis) -> bool {
+    matches!(
+        axis,
+        Axis::Child
+            | Axis::Descendant
+            | Axis::DescendantOrSelf
+            | Axis::FollowingSibling
+            | Axis::SelfNode
+            | Axis::Parent
+    )
+}
+
+//
+// The nodes on `axis` from `node`, in the axis' order; reverse axes list the nearest node first,
+// as required for the proximity positions used by predicates.
+//
+fn axis_nodes(node: &RefNode, axis: Axis) -> Vec<RefNode> {
+    let in_model = |node: &RefNode| node.node_type() != NodeType::DocumentType;
+    match axis {
+        Axis::Child => {
+            if node.node_type() == NodeType::Attribute {
+                Vec::new()
+            } else {
+                node.child_nodes().into_iter().filter(in_model).collect()
+            }
+        }
+        Axis::Descendant | Axis::DescendantOrSelf => {
+            let mut nodes = Vec::new();
+            if axis == Axis::DescendantOrSelf {
+                nodes.push(node.clone());
+            }
+            if node.node_type() != NodeType::Attribute {
+                nodes.extend(node.descendants().filter(in_model));
+            }
+            nodes
+        }
+        Axis::Attribute => {
+            if node.node_type() == NodeType::Element {
+                node.attribute_iter()
+                    .filter(|(name, _)| !is_namespace_declaration(name))
+                    .map(|(_, attribute)| attribute)
+                    .collect()
+            } else {
+                Vec::new()
+            }
+        }
+        Axis::Parent => match owner_element(node) {
+            Some(element) => vec![element],
+            None => node.parent_node().into_iter().collect(),
+        },
+        Axis::Ancestor | Axis::AncestorOrSelf => {
+            let mut nodes = Vec::new();
+            if axis == Axis::AncestorOrSelf {
+                nodes.push(node.clone());
+            }
+            if let Some(element) = owner_element(node) {
+                nodes.push(element.clone());
+                nodes.extend(element.ancestors());
+            } else {
+                nodes.extend(node.ancestors());
+            }
+            nodes
+        }
+        Axis::FollowingSibling => node.following_siblings().filter(in_model).collect(),
+        Axis::PrecedingSibling => node.preceding_siblings().filter(in_model).collect(),
+        Axis::SelfNode => vec![node.clone()],
+    }
+}
+
+//
+// Match `node` against `node_test`, in which the parser has replaced prefixes with namespace URIs.
+//
+fn matches_node_test(node: &RefNode, axis: Axis, node_test: &NodeTest) -> bool {
+    let principal_type = if axis == Axis::Attribute {
+        NodeType::Attribute
+    } else {
+        NodeType::Element
+    };
+    match node_test {
+        NodeTest::AnyName => node.node_type() == principal_type,
+        NodeTest::AnyLocalName(namespace_uri) => {
+            node.node_type() == principal_type
+                && effective_namespace_uri(node).as_ref() == Some(namespace_uri)
+        }
+        NodeTest::Name(namespace_uri, local_name) => {
+            node.node_type() == principal_type
+                && &node.local_name() == local_name
+                && &effective_namespace_uri(node) == namespace_uri
+        }
+        NodeTest::Node => true,
+        NodeTest::Text => is_text(node),
+        NodeTest::Comment => node.node_type() == NodeType::Comment,
+        NodeTest::ProcessingInstruction(target) => {
+            node.node_type() == NodeType::ProcessingInstruction
+                && target
+                    .as_ref()
+                    .map(|target| &node.node_name().to_string() == target)
+                    .unwrap_or(true)
+        }
+    }
+}
+
+//
+// The namespace URI of an element or attribute; nodes created by name, as the parser does, have
+// no namespace URI of their own and so this is found from the declarations in scope.
+//
+fn effective_namespace_uri(node: &RefNode) -> Option<String> {
+    let name = node.node_name();
+    if name.namespace_uri().is_some() {
+        return name.namespace_uri().clone();
+    }
+    match (node.node_type(), name.prefix()) {
+        (NodeType::Attribute, None) => None,
+        (NodeType::Attribute, Some(prefix)) => {
+            owner_element(node)?.lookup_namespace_uri(Some(prefix))
+        }
+        (_, prefix) => node.lookup_namespace_uri(prefix.as_deref()),
+    }
+}
+
+//
+// Apply each predicate in turn to `nodes`, which are in the order used for proximity positions.
+//
+fn filter(mut nodes: Vec<RefNode>, predicates: &[Expr]) -> Result<Vec<RefNode>> {
+    for predicate in predicates {
+        let size = nodes.len();
+        let mut retained = Vec::new();
+        for (index, node) in nodes.into_iter().enumerate() {
+            let context = Context {
+                node: node.clone(),
+                position: index + 1,
+                size,
+            };
+            let keep = match evaluate(predicate, &context)? {
+                XPathValue::Number(position) => position == (index + 1) as f64,
+                value => value.boolean(),
+            };
+            if keep {
+                retained.push(node);
+            }
+        }
+        nodes = retained;
+    }
+    Ok(nodes)
+}
+
+//
+// Comparisons, from XPath 1.0 [§3.4 Booleans](https://www.w3.org/TR/xpath-10/#booleans); a
+// comparison with a node-set is true if it is true for the string-value of any node in the set.
+//
+fn compare(op: CompareOp, lhs: &XPathValue, rhs: &XPathValue) -> bool {
+    match (lhs, rhs) {
+        (XPathValue::NodeSet(lhs), XPathValue::NodeSet(rhs)) => {
+            let rhs: Vec<XPathValue> = rhs
+                .iter()
+                .map(|node| XPathValue::String(string_value(node)))
+                .collect();
+            lhs.iter().any(|node| {
+                let lhs = XPathValue::String(string_value(node));
+                rhs.iter().any(|rhs| compare_values(op, &lhs, rhs))
+            })
+        }
+        (XPathValue::NodeSet(nodes), XPathValue::Boolean(_)) => {
+            compare_values(op, &XPathValue::Boolean(!nodes.is_empty()), rhs)
+        }
+        (XPathValue::Boolean(_), XPathValue::NodeSet(nodes)) => {
+            compare_values(op, lhs, &XPathValue::Boolean(!nodes.is_empty()))
+        }
+        (XPathValue::NodeSet(nodes), _) => nodes
+            .iter()
+            .any(|node| compare_values(op, &XPathValue::String(string_value(node)), rhs)),
+        (_, XPathValue::NodeSet(nodes)) => nodes
+            .iter()
+            .any(|node| compare_values(op, lhs, &XPathValue::String(string_value(node)))),
+        _ => compare_values(op, lhs, rhs),
+    }
+}
+
+fn compare_values(op: CompareOp, lhs: &XPathValue, rhs: &XPathValue) -> bool {
+    let is_boolean = |value: &XPathValue| matches!(value, XPathValue::Boolean(_));
+    let is_number = |value: &XPathValue| matches!(value, XPathValue::Number(_));
+    match op {
+        CompareOp::Equal | CompareOp::NotEqual => {
+            let equal = if is_boolean(lhs) || is_boolean(rhs) {
+                lhs.boolean() == rhs.boolean()
+            } else if is_number(lhs) || is_number(rhs) {
+                lhs.number() == rhs.number()
+            } else {
+                lhs.string() == rhs.string()
+            };
+            if op == CompareOp::Equal {
+                equal
+            } else {
+                !equal
+            }
+        }
+        CompareOp::Less => lhs.number() < rhs.number(),
+        CompareOp::LessOrEqual => lhs.number() <= rhs.number(),
+        CompareOp::Greater => lhs.number() > rhs.number(),
+        CompareOp::GreaterOrEqual => lhs.number() >= rhs.number(),
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+//
+// The core function library, from XPath 1.0 [§4 Core Function Library](https://www.w3.org/TR/xpath-10/#corelib),
+// except for `id`.
+//
+fn call_function(name: &str, arguments: &[Expr], context: &Context) -> Result<XPathValue> {
+    let (min, max) = match name {
+        "last" | "position" | "true" | "false" => (0, 0),
+        "local-name" | "namespace-uri" | "name" | "string" | "string-length"
+        | "normalize-space" | "number" => (0, 1),
+        "count" | "boolean" | "not" | "lang" | "sum" | "floor" | "ceiling" | "round" => (1, 1),
+        "starts-with" | "contains" | "substring-before" | "substring-after" => (2, 2),
+        "substring" => (2, 3),
+        "translate" => (3, 3),
+        "concat" => (2, usize::MAX),
+        _ => return invalid_expression(),
+    };
+    if arguments.len() < min || arguments.len() > max {
+        return invalid_expression();
+    }
+    let mut values = Vec::new();
+    for argument in arguments {
+        values.push(evaluate(argument, context)?);
+    }
+    //
+    // Functions with an optional argument default to the context node.
+    //
+    let string_argument = |values: &[XPathValue]| match values.first() {
+        Some(value) => value.string(),
+        None => string_value(&context.node),
+    };
+    let node_argument = |values: Vec<XPathValue>| -> Result<Option<RefNode>> {
+        match values.into_iter().next() {
+            Some(value) => Ok(value.into_node_set()?.into_iter().next()),
+            None => Ok(Some(context.node.clone())),
+        }
+    };
+    Ok(match name {
+        "last" => XPathValue::Number(context.size as f64),
+        "position" => XPathValue::Number(context.position as f64),
+        "count" => XPathValue::Number(values.remove(0).into_node_set()?.len() as f64),
+        "local-name" => XPathValue::String(
+            node_argument(values)?
+                .map(|node| match node.node_type() {
+                    NodeType::Element | NodeType::Attribute => node.local_name(),
+                    NodeType::ProcessingInstruction => node.node_name().to_string(),
+                    _ => String::new(),
+                })
+                .unwrap_or_default(),
+        ),
+        "namespace-uri" => XPathValue::String(
+            node_argument(values)?
+                .filter(|node| matches!(node.node_type(), NodeType::Element | NodeType::Attribute))
+                .and_then(|node| effective_namespace_uri(&node))
+                .unwrap_or_default(),
+        ),
+        "name" => XPathValue::String(
+            node_argument(values)?
+                .filter(|node| {
+                    matches!(
+                        node.node_type(),
+                        NodeType::Element | NodeType::Attribute | NodeType::ProcessingInstruction
+                    )
+                })
+                .map(|node| node.node_name().to_string())
+                .unwrap_or_default(),
+        ),
+        "string" => XPathValue::String(string_argument(&values)),
+        "concat" => XPathValue::String(values.iter().map(|value| value.string()).collect()),
+        "starts-with" => XPathValue::Boolean(values[0].string().starts_with(&values[1].string())),
+        "contains" => XPathValue::Boolean(values[0].string().contains(&values[1].string())),
+        "substring-before" => {
+            let value = values[0].string();
+            XPathValue::String(match value.find(&values[1].string()) {
+                Some(index) => value[..index].to_string(),
+                None => String::new(),
+            })
+        }
+        "substring-after" => {
+            let value = values[0].string();
+            let pattern = values[1].string();
+            XPathValue::String(match value.find(&pattern) {
+                Some(index) => value[index + pattern.len()..].to_string(),
+                None => String::new(),
+            })
+        }
+        "substring" => {
+            let start = round(values[1].number());
+            let end = values
+                .get(2)
+                .map(|length| start + round(length.number()))
+                .unwrap_or(f64::INFINITY);
+            XPathValue::String(
+                values[0]
+                    .string()
+                    .chars()
+                    .enumerate()
+                    .filter(|(index, _)| {
+                        let position = (index + 1) as f64;
+                        position >= start && position < end
+                    })
+                    .map(|(_, c)| c)
+                    .collect(),
+            )
+        }
+        "string-length" => XPathValue::Number(string_argument(&values).chars().count() as f64),
+        "normalize-space" => XPathValue::String(
+            string_argument(&values)
+                .split(is_xml_space)
+                .filter(|word| !word.is_empty())
+                .collect::<Vec<&str>>()
+                .join(" "),
+        ),
+        "translate" => {
+            let from: Vec<char> = values[1].string().chars().collect();
+            let to: Vec<char> = values[2].string().chars().collect();
+            XPathValue::String(
+                values[0]
+                    .string()
+                    .chars()
+                    .filter_map(|c| match from.iter().position(|other| *other == c) {
+                        Some(index) => to.get(index).copied(),
+                        None => Some(c),
+                    })
+                    .collect(),
+            )
+        }
+        "boolean" => XPathValue::Boolean(values[0].boolean()),
+        "not" => XPathValue::Boolean(!values[0].boolean()),
+        "true" => XPathValue::Boolean(true),
+        "false" => XPathValue::Boolean(false),
+        "lang" => {
+            let language = values[0].string().to_lowercase();
+            let element = if context.node.node_type() == NodeType::Element {
+                Some(context.node.clone())
+            } else {
+                owner_element(&context.node).or_else(|| {
+                    context
+                        .node
+                        .ancestors()
+                        .find(|node| node.node_type() == NodeType::Element)
+                })
+            };
+            XPathValue::Boolean(
+                element
+                    .and_then(|element| element.xml_lang())
+                    .map(|lang| {
+                        let lang = lang.to_lowercase();
+                        lang == language || lang.starts_with(&format!("{}-", language))
+                    })
+                    .unwrap_or_default(),
+            )
+        }
+        "number" => XPathValue::Number(match values.first() {
+            Some(value) => value.number(),
+            None => string_to_number(&string_value(&context.node)),
+        }),
+        "sum" => XPathValue::Number(
+            values
+                .remove(0)
+                .into_node_set()?
+                .iter()
+                .map(|node| string_to_number(&string_value(node)))
+                .sum(),
+        ),
+        "floor" => XPathValue::Number(values[0].number().floor()),
+        "ceiling" => XPathValue::Number(values[0].number().ceil()),
+        "round" => XPathValue::Number(round(values[0].number())),
+        _ => unreachable!(),
+    })
+}
+
+//
+// Round to the nearest integer, with halves rounded towards positive infinity; a value from -0.5
+// up to, and including, negative zero rounds to negative zero.
+//
+fn round(value: f64) -> f64 {
+    if value.is_nan() || value.is_infinite() {
+        value
+    } else if (-0.5..=0.0).contains(&value) && value.is_sign_negative() {
+        -0.0
+    } else {
+        (value + 0.5).floor()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_string_to_number() {
+        assert_eq!(string_to_number("12"), 12.0);
+        assert_eq!(string_to_number(" -1.5\n"), -1.5);
+        assert_eq!(string_to_number(".5"), 0.5);
+        assert_eq!(string_to_number("5."), 5.0);
+        assert!(string_to_number("").is_nan());
+        assert!(string_to_number("1e3").is_nan());
+        assert!(string_to_number("+1").is_nan());
+        assert!(string_to_number("-").is_nan());
+        assert!(string_to_number("1.2.3").is_nan());
+    }
+
+    #[test]
+    fn test_number_to_string() {
+        assert_eq!(number_to_string(1.0), "1");
+        assert_eq!(number_to_string(-0.0), "0");
+        assert_eq!(number_to_string(0.25), "0.25");
+        assert_eq!(number_to_string(-3.0), "-3");
+        assert_eq!(number_to_string(f64::NAN), "NaN");
+        assert_eq!(number_to_string(f64::INFINITY), "Infinity");
+        assert_eq!(number_to_string(f64::NEG_INFINITY), "-Infinity");
+    }
+
+    #[test]
+    fn test_round() {
+        assert_eq!(round(2.5), 3.0);
+        assert_eq!(round(-2.5), -2.0);
+        assert_eq!(round(1.4), 1.0);
+        assert!(round(-0.5).is_sign_negative() && round(-0.5) == 0.0);
+        assert!(round(-0.2).is_sign_negative() && round(-0.2) == 0.0);
+        assert!(round(-0.0).is_sign_negative());
+        assert!(round(0.2).is_sign_positive() && round(0.2) == 0.0);
+        assert!(round(f64::NAN).is_nan());
+    }
+}
@@ -0,0 +1,208 @@
+/*!
+Provides the evaluation of XPath 1.0 expressions against the nodes of a document.
+
+# Interface Mapping
+
+This is modeled on, but simpler than, the interfaces of
+[Document Object Model XPath](https://www.w3.org/TR/DOM-Level-3-XPath/xpath.html).
+
+| IDL Interface     | Rust Mapping                                                              |
+|-------------------|---------------------------------------------------------------------------|
+| `XPathEvaluator`  | [`evaluate`](fn.evaluate.html) and [`evaluate_ns`](fn.evaluate_ns.html)   |
+| `XPathException`  | [`Error::InvalidExpression`](../enum.Error.html#variant.InvalidExpression) and [`Error::XPathType`](../enum.Error.html#variant.XPathType) |
+| `XPathNSResolver` | `HashMap<String, String>`, from prefix to namespace URI                   |
+| `XPathResult`     | [`XPathValue`](enum.XPathValue.html)                                      |
+
+# Implementation
+
+The whole of the XPath 1.0 expression language is supported, with the exception of the
+`namespace`, `following`, and `preceding` axes, variable references, and the `id` function.
+
+The data model is that of the DOM, with the following adjustments:
+
+* The `DocumentType` node is not a child of the document.
+* Namespace declarations (`xmlns` attributes) are not attributes of an element.
+* Adjacent `Text` and `CDataSection` nodes are not merged, and `EntityReference` nodes are not
+  expanded; normalize the document first if this matters.
+* The namespace URI of an element or attribute created by name, as the parser does, is found
+  from the namespace declarations in scope.
+
+An unprefixed name in an expression matches only nodes with no namespace, as in XPath 1.0; a
+prefix is resolved using the map passed to [`evaluate_ns`](fn.evaluate_ns.html), with the `xml`
+prefix always available.
+
+# Example
+
+```rust
+# #[cfg(feature = "quick_parser")]
+# fn main() {
+use xml_dom::level2::*;
+use xml_dom::level2::xpath::*;
+use xml_dom::parser::read_xml;
+
+let document_node = read_xml(
+    r#"<list><item id="1">one</item><item id="2">two</item><item>three</item></list>"#,
+)
+.unwrap();
+
+let items = evaluate(&document_node, "/list/item[@id][last()]").unwrap();
+let items = items.into_node_set().unwrap();
+assert_eq!(items.len(), 1);
+assert_eq!(items[0].get_attribute("id"), Some("2".to_string()));
+
+let count = evaluate(&document_node, "count(//item[not(@id)])").unwrap();
+assert_eq!(count, XPathValue::Number(1.0));
+assert_eq!(
+    evaluate(&document_node, "string(//item[2])").unwrap().string(),
+    "two"
+);
+# }
+# #[cfg(not(feature = "quick_parser"))]
+# fn main() {}
+```
+*/
+
+use crate::level2::node_impl::RefNode;
+use crate::level2::xpath::eval::{number_to_string, string_to_number, string_value, Context};
+use crate::shared::error::{Error, Result, MSG_XPATH_TYPE};
+use std::collections::HashMap;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The result of evaluating an expression, one of the four types of XPath 1.0 value.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum XPathValue {
+    /// A set of nodes, without duplicates and in document order.
+    NodeSet(Vec<RefNode>),
+    /// A string.
+    String(String),
+    /// A double-precision floating point number.
+    Number(f64),
+    /// A boolean value.
+    Boolean(bool),
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Evaluate `expression` with `context` as the context node; the expression may not use
+/// namespace prefixes, other than `xml`.
+///
+/// **Exceptions**
+///
+/// * `INVALID_EXPRESSION_ERR`: Raised if the expression is not legal, or uses an unknown
+///   function, or the wrong number of arguments to a function, or is nested too deeply; that is
+///   more than 63 levels of parentheses, predicates, and function arguments, or a chain of more
+///   than about 250 operators.
+/// * `TYPE_ERR`: Raised if a node-set is required, for example as an argument to `count`, but
+///   the value is not a node-set.
+/// * `NAMESPACE_ERR`: Raised if the expression uses a namespace prefix.
+///
+pub fn evaluate(context: &RefNode, expression: &str) -> Result<XPathValue> {
+    evaluate_ns(context, expression, &HashMap::default())
+}
+
+///
+/// Evaluate `expression` with `context` as the context node, resolving namespace prefixes in
+/// the expression using `namespaces`, a map from prefix to namespace URI.
+///
+/// **Exceptions**
+///
+/// As for [`evaluate`](fn.evaluate.html), with `NAMESPACE_ERR` raised if the expression uses a
+/// prefix that is not in `namespaces`; this is checked before the expression is evaluated.
+///
+pub fn evaluate_ns(
+    context: &RefNode,
+    expression: &str,
+    namespaces: &HashMap<String, String>,
+) -> Result<XPathValue> {
+    let expr = parser::parse(expression, namespaces)?;
+    eval::evaluate(
+        &expr,
+        &Context {
+            node: context.clone(),
+            position: 1,
+            size: 1,
+        },
+    )
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl XPathValue {
+    ///
+    /// Convert this value to a boolean, as the XPath `boolean()` function does.
+    ///
+    pub fn boolean(&self) -> bool {
+        match self {
+            XPathValue::NodeSet(nodes) => !nodes.is_empty(),
+            XPathValue::String(value) => !value.is_empty(),
+            XPathValue::Number(value) => *value != 0.0 && !value.is_nan(),
+            XPathValue::Boolean(value) => *value,
+        }
+    }
+
+    ///
+    /// Convert this value to a number, as the XPath `number()` function does.
+    ///
+    pub fn number(&self) -> f64 {
+        match self {
+            XPathValue::Boolean(value) => {
+                if *value {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            XPathValue::Number(value) => *value,
+            _ => string_to_number(&self.string()),
+        }
+    }
+
+    ///
+    /// Convert this value to a string, as the XPath `string()` function does; a node-set is
+    /// converted to the string-value of its first node.
+    ///
+    pub fn string(&self) -> String {
+        match self {
+            XPathValue::NodeSet(nodes) => nodes.first().map(string_value).unwrap_or_default(),
+            XPathValue::String(value) => value.clone(),
+            XPathValue::Number(value) => number_to_string(*value),
+            XPathValue::Boolean(value) => value.to_string(),
+        }
+    }
+
+    ///
+    /// Return the nodes of a node-set value.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `TYPE_ERR`: Raised if this value is not a node-set; XPath does not convert other types
+    ///   to node-sets.
+    ///
+    pub fn into_node_set(self) -> Result<Vec<RefNode>> {
+        match self {
+            XPathValue::NodeSet(nodes) => Ok(nodes),
+            _ => {
+                warn!("{}", MSG_XPATH_TYPE);
+                Err(Error::XPathType)
+            }
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Modules
+// ------------------------------------------------------------------------------------------------
+
+mod eval;
+
+mod parser;
@@ -0,0 +1,980 @@
+/*!
+The tokenizer and parser that turn an XPath expression into an `Expr` tree, following the
+grammar, and the lexical disambiguation rules, of XPath 1.0
+[§3.7 Lexical Structure](https://www.w3.org/TR/xpath-10/#exprlex).
+*/
+
+use crate::shared::error::{Error, Result, MSG_INVALID_EXPRESSION, MSG_UNDECLARED_PREFIX};
+use crate::shared::syntax::{XML_NS_ATTRIBUTE, XML_NS_URI};
+use crate::shared::text::{is_xml_name_char, is_xml_name_start_char, is_xml_space};
+use std::collections::HashMap;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Compare(CompareOp, Box<Expr>, Box<Expr>),
+    Arithmetic(ArithmeticOp, Box<Expr>, Box<Expr>),
+    Negate(Box<Expr>),
+    Union(Box<Expr>, Box<Expr>),
+    Literal(String),
+    Number(f64),
+    Function(String, Vec<Expr>),
+    Filter(Box<Expr>, Vec<Expr>),
+    Path(PathStart, Vec<Step>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CompareOp {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ArithmeticOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum PathStart {
+    Context,
+    Root,
+    Expr(Box<Expr>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Step {
+    pub(crate) axis: Axis,
+    pub(crate) node_test: NodeTest,
+    pub(crate) predicates: Vec<Expr>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Axis {
+    Ancestor,
+    AncestorOrSelf,
+    Attribute,
+    Child,
+    Descendant,
+    DescendantOrSelf,
+    FollowingSibling,
+    Parent,
+    PrecedingSibling,
+    SelfNode,
+}
+
+//
+// As tokenized the strings in `AnyLocalName` and `Name` are prefixes; the parser replaces each
+// with the namespace URI it is bound to.
+//
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum NodeTest {
+    AnyName,
+    AnyLocalName(String),
+    Name(Option<String>, String),
+    Node,
+    Text,
+    Comment,
+    ProcessingInstruction(Option<String>),
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Slash,
+    DoubleSlash,
+    LeftParen,
+    RightParen,
+    LeftBracket,
+    RightBracket,
+    Dot,
+    DoubleDot,
+    At,
+    Comma,
+    DoubleColon,
+    Pipe,
+    Plus,
+    Minus,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Multiply,
+    And,
+    Or,
+    Mod,
+    Div,
+    Literal(String),
+    Number(f64),
+    NameTest(NodeTest),
+    NodeType(String),
+    FunctionName(String),
+    AxisName(String),
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    position: usize,
+    depth: usize,
+    namespaces: &'a HashMap<String, String>,
+}
+
+//
+// The deepest nesting of expressions that is accepted, which keeps the recursive parser and
+// evaluator within the stack. Each operator in a chain adds one to the depth, and each expression
+// nested in parentheses, a predicate, or a function argument adds `NESTED_DEPTH`, as the parser
+// uses far more stack for these.
+//
+const MAX_DEPTH: usize = 256;
+
+const NESTED_DEPTH: usize = 4;
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+//
+// Parse `expression`, resolving any namespace prefixes in name tests using `namespaces`.
+//
+pub(crate) fn parse(expression: &str, namespaces: &HashMap<String, String>) -> Result<Expr> {
+    let mut parser = Parser {
+        tokens: tokenize(expression)?,
+        position: 0,
+        depth: 0,
+        namespaces,
+    };
+    let expr = parser.expr()?;
+    if parser.position == parser.tokens.len() {
+        Ok(expr)
+    } else {
+        invalid()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Token {
+    //
+    // A token after which `*` is a name test, and a name is not an operator.
+    //
+    fn starts_operand(&self) -> bool {
+        matches!(
+            self,
+            Token::At
+                | Token::DoubleColon
+                | Token::LeftParen
+                | Token::LeftBracket
+                | Token::Comma
+                | Token::Slash
+                | Token::DoubleSlash
+                | Token::Pipe
+                | Token::Plus
+                | Token::Minus
+                | Token::Equal
+                | Token::NotEqual
+                | Token::Less
+                | Token::LessOrEqual
+                | Token::Greater
+                | Token::GreaterOrEqual
+                | Token::Multiply
+                | Token::And
+                | Token::Or
+                | Token::Mod
+                | Token::Div
+        )
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next_if(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &Token) -> Result<()> {
+        if self.next_if(token) {
+            Ok(())
+        } else {
+            invalid()
+        }
+    }
+
+    //
+    // Add `by` to the depth, failing if it is now more than `MAX_DEPTH`; each caller restores the
+    // depth it started with on a successful return.
+    //
+    fn deeper(&mut self, by: usize) -> Result<()> {
+        self.depth += by;
+        if self.depth > MAX_DEPTH {
+            invalid()
+        } else {
+            Ok(())
+        }
+    }
+
+    // Expr ::= OrExpr
+    fn expr(&mut self) -> Result<Expr> {
+        let depth = self.depth;
+        self.deeper(NESTED_DEPTH)?;
+        let mut lhs = self.and_expr()?;
+        while self.next_if(&Token::Or) {
+            self.deeper(1)?;
+            lhs = Expr::Or(Box::new(lhs), Box::new(self.and_expr()?));
+        }
+        self.depth = depth;
+        Ok(lhs)
+    }
+
+    fn and_expr(&mut self) -> Result<Expr> {
+        let depth = self.depth;
+        let mut lhs = self.equality_expr()?;
+        while self.next_if(&Token::And) {
+            self.deeper(1)?;
+            lhs = Expr::And(Box::new(lhs), Box::new(self.equality_expr()?));
+        }
+        self.depth = depth;
+        Ok(lhs)
+    }
+
+    fn equality_expr(&mut self) -> Result<Expr> {
+        let depth = self.depth;
+        let mut lhs = self.relational_expr()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Equal) => CompareOp::Equal,
+                Some(Token::NotEqual) => CompareOp::NotEqual,
+                _ => break,
+            };
+            self.position += 1;
+            self.deeper(1)?;
+            lhs = Expr::Compare(op, Box::new(lhs), Box::new(self.relational_expr()?));
+        }
+        self.depth = depth;
+        Ok(lhs)
+    }
+
+    fn relational_expr(&mut self) -> Result<Expr> {
+        let depth = self.depth;
+        let mut lhs = self.additive_expr()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Less) => CompareOp::Less,
+                Some(Token::LessOrEqual) => CompareOp::LessOrEqual,
+                Some(Token::Greater) => CompareOp::Greater,
+                Some(Token::GreaterOrEqual) => CompareOp::GreaterOrEqual,
+                _ => break,
+            };
+            self.position += 1;
+            self.deeper(1)?;
+            lhs = Expr::Compare(op, Box::new(lhs), Box::new(self.additive_expr()?));
+        }
+        self.depth = depth;
+        Ok(lhs)
+    }
+
+    fn additive_expr(&mut self) -> Result<Expr> {
+        let depth = self.depth;
+        let mut lhs = self.multiplicative_expr()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Plus) => ArithmeticOp::Add,
+                Some(Token::Minus) => ArithmeticOp::Subtract,
+                _ => break,
+            };
+            self.position += 1;
+            self.deeper(1)?;
+            lhs = Expr::Arithmetic(op, Box::new(lhs), Box::new(self.multiplicative_expr()?));
+        }
+        self.depth = depth;
+        Ok(lhs)
+    }
+
+    fn multiplicative_expr(&mut self) -> Result<Expr> {
+        let depth = self.depth;
+        let mut lhs = self.unary_expr()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Multiply) => ArithmeticOp::Multiply,
+                Some(Token::Div) => ArithmeticOp::Divide,
+                Some(Token::Mod) => ArithmeticOp::Modulo,
+                _ => break,
+            };
+            self.position += 1;
+            self.deeper(1)?;
+            lhs = Expr::Arithmetic(op, Box::new(lhs), Box::new(self.unary_expr()?));
+        }
+        self.depth = depth;
+        Ok(lhs)
+    }
+
+    fn unary_expr(&mut self) -> Result<Expr> {
+        if self.next_if(&Token::Minus) {
+            let depth = self.depth;
+            self.deeper(1)?;
+            let operand = self.unary_expr()?;
+            self.depth = depth;
+            Ok(Expr::Negate(Box::new(operand)))
+        } else {
+            self.union_expr()
+        }
+    }
+
+    fn union_expr(&mut self) -> Result<Expr> {
+        let depth = self.depth;
+        let mut lhs = self.path_expr()?;
+        while self.next_if(&Token::Pipe) {
+            self.deeper(1)?;
+            lhs = Expr::Union(Box::new(lhs), Box::new(self.path_expr()?));
+        }
+        self.depth = depth;
+        Ok(lhs)
+    }
+
+    // PathExpr ::= LocationPath | FilterExpr | FilterExpr ('/' | '//') RelativeLocationPath
+    fn path_expr(&mut self) -> Result<Expr> {
+        match self.peek() {
+            Some(Token::Slash) => {
+                self.position += 1;
+                let steps = if self.starts_step() {
+                    self.relative_location_path()?
+                } else {
+                    Vec::new()
+                };
+                Ok(Expr::Path(PathStart::Root, steps))
+            }
+            Some(Token::DoubleSlash) => {
+                self.position += 1;
+                let mut steps = vec![descendant_or_self_step()];
+                steps.extend(self.relative_location_path()?);
+                Ok(Expr::Path(PathStart::Root, steps))
+            }
+            Some(Token::LeftParen)
+            | Some(Token::Literal(_))
+            | Some(Token::Number(_))
+            | Some(Token::FunctionName(_)) => {
+                let primary = self.primary_expr()?;
+                let mut predicates = Vec::new();
+                while self.peek() == Some(&Token::LeftBracket) {
+                    predicates.push(self.predicate()?);
+                }
+                let filter = if predicates.is_empty() {
+                    primary
+                } else {
+                    Expr::Filter(Box::new(primary), predicates)
+                };
+                let mut steps = Vec::new();
+                match self.peek() {
+                    Some(Token::Slash) => {
+                        self.position += 1;
+                    }
+                    Some(Token::DoubleSlash) => {
+                        self.position += 1;
+                        steps.push(descendant_or_self_step());
+                    }
+                    _ => return Ok(filter),
+                }
+                steps.extend(self.relative_location_path()?);
+                Ok(Expr::Path(PathStart::Expr(Box::new(filter)), steps))
+            }
+            _ => Ok(Expr::Path(
+                PathStart::Context,
+                self.relative_location_path()?,
+            )),
+        }
+    }
+
+    fn primary_expr(&mut self) -> Result<Expr> {
+        match self.tokens.get(self.position).cloned() {
+            Some(Token::LeftParen) => {
+                self.position += 1;
+                let expr = self.expr()?;
+                self.expect(&Token::RightParen)?;
+                Ok(expr)
+            }
+            Some(Token::Literal(value)) => {
+                self.position += 1;
+                Ok(Expr::Literal(value))
+            }
+            Some(Token::Number(value)) => {
+                self.position += 1;
+                Ok(Expr::Number(value))
+            }
+            Some(Token::FunctionName(name)) => {
+                self.position += 1;
+                self.expect(&Token::LeftParen)?;
+                let mut arguments = Vec::new();
+                if !self.next_if(&Token::RightParen) {
+                    loop {
+                        arguments.push(self.expr()?);
+                        if self.next_if(&Token::RightParen) {
+                            break;
+                        }
+                        self.expect(&Token::Comma)?;
+                    }
+                }
+                Ok(Expr::Function(name, arguments))
+            }
+            _ => invalid(),
+        }
+    }
+
+    fn starts_step(&self) -> bool {
+        matches!(
+            self.peek(),
+            Some(Token::Dot)
+                | Some(Token::DoubleDot)
+                | Some(Token::At)
+                | Some(Token::AxisName(_))
+                | Some(Token::NameTest(_))
+                | Some(Token::NodeType(_))
+        )
+    }
+
+    fn relative_location_path(&mut self) -> Result<Vec<Step>> {
+        let mut steps = vec![self.step()?];
+        loop {
+            match self.peek() {
+                Some(Token::Slash) => {
+                    self.position += 1;
+                }
+                Some(Token::DoubleSlash) => {
+                    self.position += 1;
+                    steps.push(descendant_or_self_step());
+                }
+                _ => return Ok(steps),
+            }
+            steps.push(self.step()?);
+        }
+    }
+
+    // Step ::= AxisSpecifier NodeTest Predicate* | '.' | '..'
+    fn step(&mut self) -> Result<Step> {
+        if self.next_if(&Token::Dot) {
+            return Ok(Step {
+                axis: Axis::SelfNode,
+                node_test: NodeTest::Node,
+                predicates: Vec::new(),
+            });
+        }
+        if self.next_if(&Token::DoubleDot) {
+            return Ok(Step {
+                axis: Axis::Parent,
+                node_test: NodeTest::Node,
+                predicates: Vec::new(),
+            });
+        }
+        let axis = match self.tokens.get(self.position).cloned() {
+            Some(Token::At) => {
+                self.position += 1;
+                Axis::Attribute
+            }
+            Some(Token::AxisName(name)) => {
+                self.position += 1;
+                self.expect(&Token::DoubleColon)?;
+                axis_named(&name)?
+            }
+            _ => Axis::Child,
+        };
+        let node_test = match self.tokens.get(self.position).cloned() {
+            Some(Token::NameTest(node_test)) => {
+                self.position += 1;
+                match node_test {
+                    NodeTest::AnyLocalName(prefix) => {
+                        NodeTest::AnyLocalName(self.resolve_prefix(&prefix)?)
+                    }
+                    NodeTest::Name(Some(prefix), local_name) => {
+                        NodeTest::Name(Some(self.resolve_prefix(&prefix)?), local_name)
+                    }
+                    node_test => node_test,
+                }
+            }
+            Some(Token::NodeType(name)) => {
+                self.position += 1;
+                self.expect(&Token::LeftParen)?;
+                let node_test = match name.as_str() {
+                    "node" => NodeTest::Node,
+                    "text" => NodeTest::Text,
+                    "comment" => NodeTest::Comment,
+                    _ => match self.tokens.get(self.position).cloned() {
+                        Some(Token::Literal(target)) => {
+                            self.position += 1;
+                            NodeTest::ProcessingInstruction(Some(target))
+                        }
+                        _ => NodeTest::ProcessingInstruction(None),
+                    },
+                };
+                self.expect(&Token::RightParen)?;
+                node_test
+            }
+            _ => return invalid(),
+        };
+        let mut predicates = Vec::new();
+        while self.peek() == Some(&Token::LeftBracket) {
+            predicates.push(self.predicate()?);
+        }
+        Ok(Step {
+            axis,
+            node_test,
+            predicates,
+        })
+    }
+
+    fn resolve_prefix(&self, prefix: &str) -> Result<String> {
+        match self.namespaces.get(prefix) {
+            Some(namespace_uri) => Ok(namespace_uri.clone()),
+            None if prefix == XML_NS_ATTRIBUTE => Ok(XML_NS_URI.to_string()),
+            None => {
+                warn!("{}", MSG_UNDECLARED_PREFIX);
+                Err(Error::Namespace)
+            }
+        }
+    }
+
+    fn predicate(&mut self) -> Result<Expr> {
+        self.expect(&Token::LeftBracket)?;
+        let expr = self.expr()?;
+        self.expect(&Token::RightBracket)?;
+        Ok(expr)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn invalid<T>() -> Result<T> {
+    warn!("{}", MSG_INVALID_EXPRESSION);
+    Err(Error::InvalidExpression)
+}
+
+fn descendant_or_self_step() -> Step {
+    Step {
+        axis: Axis::DescendantOrSelf,
+        node_test: NodeTest::Node,
+        predicates: Vec::new(),
+    }
+}
+
+fn axis_named(name: &str) -> Result<Axis> {
+    Ok(match name {
+        "ancestor" => Axis::Ancestor,
+        "ancestor-or-self" => Axis::AncestorOrSelf,
+        "attribute" => Axis::Attribute,
+        "child" => Axis::Child,
+        "descendant" => Axis::Descendant,
+        "descendant-or-self" => Axis::DescendantOrSelf,
+        "following-sibling" => Axis::FollowingSibling,
+        "parent" => Axis::Parent,
+        "preceding-sibling" => Axis::PrecedingSibling,
+        "self" => Axis::SelfNode,
+        _ => return invalid(),
+    })
+}
+
+fn is_ncname_start_char(c: char) -> bool {
+    c != ':' && is_xml_name_start_char(c)
+}
+
+fn is_ncname_char(c: char) -> bool {
+    c != ':' && is_xml_name_char(c)
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = expression.chars().collect();
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut index = 0;
+    while index < chars.len() {
+        let c = chars[index];
+        let next = chars.get(index + 1).copied();
+        //
+        // Whether a preceding token means this token is an operator, see §3.7.
+        //
+        let is_operator_position = tokens
+            .last()
+            .map(|token| !token.starts_operand())
+            .unwrap_or_default();
+        let token = match c {
+            c if is_xml_space(c) => {
+                index += 1;
+                continue;
+            }
+            '/' if next == Some('/') => Token::DoubleSlash,
+            '/' => Token::Slash,
+            '(' => Token::LeftParen,
+            ')' => Token::RightParen,
+            '[' => Token::LeftBracket,
+            ']' => Token::RightBracket,
+            '.' if next == Some('.') => Token::DoubleDot,
+            '.' if !next.map(|c| c.is_ascii_digit()).unwrap_or_default() => Token::Dot,
+            '@' => Token::At,
+            ',' => Token::Comma,
+            ':' if next == Some(':') => Token::DoubleColon,
+            '|' => Token::Pipe,
+            '+' => Token::Plus,
+            '-' => Token::Minus,
+            '=' => Token::Equal,
+            '!' if next == Some('=') => Token::NotEqual,
+            '<' if next == Some('=') => Token::LessOrEqual,
+            '<' => Token::Less,
+            '>' if next == Some('=') => Token::GreaterOrEqual,
+            '>' => Token::Greater,
+            '*' if is_operator_position => Token::Multiply,
+            '*' => Token::NameTest(NodeTest::AnyName),
+            '"' | '\'' => {
+                let end = chars[index + 1..]
+                    .iter()
+                    .position(|other| *other == c)
+                    .map(|end| index + 1 + end);
+                match end {
+                    None => return invalid(),
+                    Some(end) => {
+                        tokens.push(Token::Literal(chars[index + 1..end].iter().collect()));
+                        index = end + 1;
+                        continue;
+                    }
+                }
+            }
+            c if c.is_ascii_digit() || c == '.' => {
+                let start = index;
+                while index < chars.len() && chars[index].is_ascii_digit() {
+                    index += 1;
+                }
+                if index < chars.len() && chars[index] == '.' {
+                    index += 1;
+                    while index < chars.len() && chars[index].is_ascii_digit() {
+                        index += 1;
+                    }
+                }
+                let number: String = chars[start..index].iter().collect();
+                match number.parse::<f64>() {
+                    Ok(number) => tokens.push(Token::Number(number)),
+                    Err(_) => return invalid(),
+                }
+                continue;
+            }
+            c if is_ncname_start_char(c) => {
+                let (name, end) = ncname(&chars, index);
+                index = end;
+                if is_operator_position {
+                    tokens.push(match name.as_str() {
+                        "and" => Token::And,
+                        "or" => Token::Or,
+                        "mod" => Token::Mod,
+                        "div" => Token::Div,
+                        _ => return invalid(),
+                    });
+                    continue;
+                }
+                let following = skip_space(&chars, index);
+                let token = if chars.get(following) == Some(&'(') {
+                    match name.as_str() {
+                        "comment" | "text" | "processing-instruction" | "node" => {
+                            Token::NodeType(name)
+                        }
+                        _ => Token::FunctionName(name),
+                    }
+                } else if chars.get(following) == Some(&':')
+                    && chars.get(following + 1) == Some(&':')
+                {
+                    Token::AxisName(name)
+                } else if chars.get(index) == Some(&':') && chars.get(index + 1) == Some(&'*') {
+                    index += 2;
+                    Token::NameTest(NodeTest::AnyLocalName(name))
+                } else if chars.get(index) == Some(&':')
+                    && chars
+                        .get(index + 1)
+                        .map(|c| is_ncname_start_char(*c))
+                        .unwrap_or_default()
+                {
+                    let (local_name, end) = ncname(&chars, index + 1);
+                    index = end;
+                    //
+                    // A prefixed name may also name a function, e.g. an extension function.
+                    //
+                    if chars.get(skip_space(&chars, index)) == Some(&'(') {
+                        Token::FunctionName(format!("{}:{}", name, local_name))
+                    } else {
+                        Token::NameTest(NodeTest::Name(Some(name), local_name))
+                    }
+                } else {
+                    Token::NameTest(NodeTest::Name(None, name))
+                };
+                tokens.push(token);
+                continue;
+            }
+            _ => return invalid(),
+        };
+        index += match token {
+            Token::DoubleSlash
+            | Token::DoubleDot
+            | Token::DoubleColon
+            | Token::NotEqual
+            | Token::LessOrEqual
+            | Token::GreaterOrEqual => 2,
+            _ => 1,
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+fn ncname(chars: &[char], start: usize) -> (String, usize) {
+    let mut end = start + 1;
+    while end < chars.len() && is_ncname_char(chars[end]) {
+        end += 1;
+    }
+    (chars[start..end].iter().collect(), end)
+}
+
+fn skip_space(chars: &[char], start: usize) -> usize {
+    let mut index = start;
+    while index < chars.len() && is_xml_space(chars[index]) {
+        index += 1;
+    }
+    index
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(expression: &str) -> Result<Expr> {
+        super::parse(expression, &HashMap::default())
+    }
+
+    fn name(local_name: &str) -> NodeTest {
+        NodeTest::Name(None, local_name.to_string())
+    }
+
+    fn child(node_test: NodeTest) -> Step {
+        Step {
+            axis: Axis::Child,
+            node_test,
+            predicates: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_tokenize_disambiguation() {
+        assert_eq!(
+            tokenize("* * *").unwrap(),
+            vec![
+                Token::NameTest(NodeTest::AnyName),
+                Token::Multiply,
+                Token::NameTest(NodeTest::AnyName)
+            ]
+        );
+        assert_eq!(
+            tokenize("div div div").unwrap(),
+            vec![
+                Token::NameTest(name("div")),
+                Token::Div,
+                Token::NameTest(name("div"))
+            ]
+        );
+        assert_eq!(
+            tokenize("child::dc:title | p:*").unwrap(),
+            vec![
+                Token::AxisName("child".to_string()),
+                Token::DoubleColon,
+                Token::NameTest(NodeTest::Name(Some("dc".to_string()), "title".to_string())),
+                Token::Pipe,
+                Token::NameTest(NodeTest::AnyLocalName("p".to_string())),
+            ]
+        );
+        assert_eq!(
+            tokenize("text() != 'a' and .5 <= ..").unwrap(),
+            vec![
+                Token::NodeType("text".to_string()),
+                Token::LeftParen,
+                Token::RightParen,
+                Token::NotEqual,
+                Token::Literal("a".to_string()),
+                Token::And,
+                Token::Number(0.5),
+                Token::LessOrEqual,
+                Token::DoubleDot,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_paths() {
+        assert_eq!(
+            parse("a/b").unwrap(),
+            Expr::Path(PathStart::Context, vec![child(name("a")), child(name("b"))])
+        );
+        assert_eq!(parse("/").unwrap(), Expr::Path(PathStart::Root, vec![]));
+        assert_eq!(
+            parse("//a").unwrap(),
+            Expr::Path(
+                PathStart::Root,
+                vec![descendant_or_self_step(), child(name("a"))]
+            )
+        );
+        assert_eq!(
+            parse("a[1]").unwrap(),
+            Expr::Path(
+                PathStart::Context,
+                vec![Step {
+                    axis: Axis::Child,
+                    node_test: name("a"),
+                    predicates: vec![Expr::Number(1.0)],
+                }]
+            )
+        );
+        assert_eq!(
+            parse("(a)[1]/@b").unwrap(),
+            Expr::Path(
+                PathStart::Expr(Box::new(Expr::Filter(
+                    Box::new(Expr::Path(PathStart::Context, vec![child(name("a"))])),
+                    vec![Expr::Number(1.0)]
+                ))),
+                vec![Step {
+                    axis: Axis::Attribute,
+                    node_test: name("b"),
+                    predicates: vec![],
+                }]
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_operators() {
+        assert_eq!(
+            parse("1 + 2 * 3 = 7 or false()").unwrap(),
+            Expr::Or(
+                Box::new(Expr::Compare(
+                    CompareOp::Equal,
+                    Box::new(Expr::Arithmetic(
+                        ArithmeticOp::Add,
+                        Box::new(Expr::Number(1.0)),
+                        Box::new(Expr::Arithmetic(
+                            ArithmeticOp::Multiply,
+                            Box::new(Expr::Number(2.0)),
+                            Box::new(Expr::Number(3.0))
+                        ))
+                    )),
+                    Box::new(Expr::Number(7.0))
+                )),
+                Box::new(Expr::Function("false".to_string(), vec![]))
+            )
+        );
+        assert_eq!(
+            parse("-count(a)").unwrap(),
+            Expr::Negate(Box::new(Expr::Function(
+                "count".to_string(),
+                vec![Expr::Path(PathStart::Context, vec![child(name("a"))])]
+            )))
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for expression in [
+            "",
+            "a/",
+            "a[",
+            "a]",
+            "'open",
+            "a b",
+            "unknown::a",
+            "@",
+            "f(a,)",
+            "1 +",
+            "!",
+        ] {
+            assert_eq!(
+                parse(expression),
+                Err(Error::InvalidExpression),
+                "{}",
+                expression
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_depth() {
+        let nested = |depth: usize| format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+        let deepest = MAX_DEPTH / NESTED_DEPTH - 1;
+        assert_eq!(parse(&nested(deepest)), Ok(Expr::Number(1.0)));
+        assert!(parse(&format!("{}1", "1 + ".repeat(MAX_DEPTH - NESTED_DEPTH))).is_ok());
+        for expression in [
+            nested(deepest + 1),
+            nested(3_000),
+            format!("{}1", "-".repeat(3_000)),
+            format!("{}1", "1 + ".repeat(3_000)),
+            format!("{}a{}", "a[".repeat(3_000), "]".repeat(3_000)),
+            format!("{}1{}", "f(".repeat(3_000), ")".repeat(3_000)),
+        ] {
+            assert_eq!(parse(&expression), Err(Error::InvalidExpression));
+        }
+    }
+
+    #[test]
+    fn test_parse_prefixes() {
+        let namespaces: HashMap<String, String> = vec![(
+            "dc".to_string(),
+            "http://purl.org/dc/elements/1.1/".to_string(),
+        )]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            super::parse("dc:title | xml:*", &namespaces).unwrap(),
+            Expr::Union(
+                Box::new(Expr::Path(
+                    PathStart::Context,
+                    vec![child(NodeTest::Name(
+                        Some("http://purl.org/dc/elements/1.1/".to_string()),
+                        "title".to_string()
+                    ))]
+                )),
+                Box::new(Expr::Path(
+                    PathStart::Context,
+                    vec![child(NodeTest::AnyLocalName(XML_NS_URI.to_string()))]
+                ))
+            )
+        );
+        for expression in ["a:b", "//a:b", "p:*", "count(a:b)", "x[a:b]"] {
+            assert_eq!(
+                super::parse(expression, &namespaces),
+                Err(Error::Namespace),
+                "{}",
+                expression
+            );
+        }
+    }
+}
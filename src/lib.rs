@@ -16,15 +16,41 @@ module.
 
 ## Features
 
-Currently only one feature, `quick_parser`, is provided which provides a new module `parser` with the
+The `quick_parser` feature provides a new module `parser` with the
 single public function. This feature is enabled by default.
 
 ``` rust,ignore
-pub fn read_xml(xml: &str) -> Result<RefNode>;
+pub fn read_xml(xml: &str) -> PositionedResult<RefNode>;
 ```
 
 This will parse the document and return a new `RefNode` that corresponds to the `Document` trait.
 
+The `sync` feature, which is not enabled by default, makes `RefNode` both `Send` and `Sync` so
+that a document may be used from more than one thread. Internally each node is then held in an
+`Arc<RwLock<_>>` rather than an `Rc<RefCell<_>>`, and user data, node filters, and mutation
+listeners must also be `Send` and `Sync`; the `level2::Shared` type is `Arc`
+rather than `Rc`. Note that every access to a node takes its lock, so a node modified on one
+thread while being read on another will block rather than fail, and a listener or filter that
+modifies the node it is called with may deadlock where it would otherwise panic.
+
+The `macros` feature, which is not enabled by default, provides the [`xml!`](macro.xml.html)
+macro to create an element, with its attributes and descendants, from a literal description of the
+tree; see the `macros` module for the syntax.
+
+The `xinclude` feature, which is not enabled by default, provides the [`xinclude`](xinclude/index.html)
+module to replace the `xi:include` elements of a document with the content they refer to.
+
+The `serde` feature, which is not enabled by default, implements serde's `Serialize` and
+`Deserialize` for `RefNode`, by way of the [`OwnedNode`](level2/owned/enum.OwnedNode.html) tree, so
+that a document may be stored or sent in formats such as JSON without writing it as XML.
+
+The `json` feature, which is not enabled by default, provides the [`json`](json/index.html) module
+to map a tree to, and from, JSON using either the JsonML or the BadgerFish convention.
+
+The `xml_rs` feature, which is not enabled by default, provides the
+[`interop::xml_rs`](interop/xml_rs/index.html) module to read a document from, and write a tree
+as, the events of the [xml-rs](https://crates.io/crates/xml-rs) crate.
+
 # Example
 
 ```rust
@@ -153,6 +179,24 @@ pub mod parser;
 
 pub mod level2;
 
+pub mod io;
+
+pub mod diff;
+
+pub mod text;
+
+#[cfg(feature = "macros")]
+pub mod macros;
+
+#[cfg(feature = "xinclude")]
+pub mod xinclude;
+
+#[cfg(feature = "json")]
+pub mod json;
+
+#[cfg(feature = "xml_rs")]
+pub mod interop;
+
 // ------------------------------------------------------------------------------------------------
 // Private Modules
 // ------------------------------------------------------------------------------------------------
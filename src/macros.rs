@@ -0,0 +1,259 @@
+/*!
+Provides the [`xml!`](../macro.xml.html) macro, which creates an element, and all its attributes
+and descendants, from a literal description of the tree. This module is only included with the
+`macros` feature.
+
+The macro takes the document that will own the new nodes, followed by a single element. An element
+is a name, optionally followed by its attributes in `[`...`]`, and then optionally by its children
+in `{`...`}`.
+
+| Form                | Creates                                                                     |
+|---------------------|-----------------------------------------------------------------------------|
+| `name`              | An element with the name `name`.                                            |
+| `prefix:name`       | An element with the qualified name `prefix:name`, see below.                |
+| `name = value`      | Within `[`...`]`, an attribute; `name` may also be `prefix:name` or a string literal, and `value` is any expression that implements `ToString`. Attributes are separated by `,`. |
+| `"text"`            | Within `{`...`}`, a `Text` node with the literal's value.                   |
+| `(value)`           | Within `{`...`}`, a `Text` node with the value of any expression that implements `ToString`. |
+
+Each node is created by the usual factory methods, such as `Document::create_element` and
+`Element::set_attribute`, so any validation is identical to building the tree by hand; the macro
+returns the first error as a `Result`. The new element has no parent, as with
+`Document::create_element`.
+
+A prefixed name, either of an element or of an attribute, is created in the namespace bound to the
+prefix by an `xmlns:prefix` attribute on the same element, or on any ancestor element created by
+the same macro. An element without a prefix is created in the default namespace, if an `xmlns`
+attribute declares one. A prefix that is not bound returns the error `NAMESPACE_ERR`.
+
+# Example
+
+```rust
+# #[cfg(feature = "quick_parser")]
+# fn main() {
+use xml_dom::level2::*;
+use xml_dom::parser::read_xml;
+use xml_dom::xml;
+
+let document_node = read_xml("<catalog/>").unwrap();
+let count = 2;
+let item_node = xml!(document_node,
+    item[id = count, "xmlns:c" = "urn:c"] {
+        "count: " (count)
+        c:note[c:lang = "en"] { "in stock" }
+    }
+)
+.unwrap();
+assert_eq!(
+    item_node.to_string(),
+    r#"<item id="2" xmlns:c="urn:c">count: 2<c:note c:lang="en">in stock</c:note></item>"#
+);
+
+let note_node = item_node.last_child().unwrap();
+assert_eq!(note_node.namespace_uri(), Some("urn:c".to_string()));
+
+assert_eq!(xml!(document_node, x:item), Err(Error::Namespace));
+# }
+# #[cfg(not(feature = "quick_parser"))]
+# fn main() {}
+```
+*/
+
+use crate::level2::convert::is_document;
+use crate::level2::node_impl::RefNode;
+use crate::level2::traits::{Document, Element, Node};
+use crate::shared::error::{Error, Result, MSG_INVALID_NODE_TYPE, MSG_PREFIX_WITHOUT_NAMESPACE};
+use crate::shared::syntax::{XMLNS_NS_ATTRIBUTE, XMLNS_NS_URI};
+
+// ------------------------------------------------------------------------------------------------
+// Public Macros
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Creates an element, and all its attributes and descendants, owned by a document; see the
+/// [`macros`](macros/index.html) module for the syntax.
+///
+#[macro_export]
+macro_rules! xml {
+    (@attributes $attributes:ident, ) => {};
+    (@attributes $attributes:ident, $prefix:ident : $local:ident = $value:expr $(, $($rest:tt)*)?) => {
+        $attributes.push((
+            concat!(stringify!($prefix), ":", stringify!($local)),
+            ($value).to_string(),
+        ));
+        $crate::xml!(@attributes $attributes, $($($rest)*)?);
+    };
+    (@attributes $attributes:ident, $name:ident = $value:expr $(, $($rest:tt)*)?) => {
+        $attributes.push((stringify!($name), ($value).to_string()));
+        $crate::xml!(@attributes $attributes, $($($rest)*)?);
+    };
+    (@attributes $attributes:ident, $name:literal = $value:expr $(, $($rest:tt)*)?) => {
+        $attributes.push(($name, ($value).to_string()));
+        $crate::xml!(@attributes $attributes, $($($rest)*)?);
+    };
+
+    (@children $document:ident, $parent:ident, ) => {};
+    (@children $document:ident, $parent:ident, $text:literal $($rest:tt)*) => {
+        $crate::macros::__text($document, &mut $parent, &$text.to_string())?;
+        $crate::xml!(@children $document, $parent, $($rest)*);
+    };
+    (@children $document:ident, $parent:ident, ($value:expr) $($rest:tt)*) => {
+        $crate::macros::__text($document, &mut $parent, &($value).to_string())?;
+        $crate::xml!(@children $document, $parent, $($rest)*);
+    };
+    (@children $document:ident, $parent:ident,
+     $prefix:ident : $local:ident [$($attributes:tt)*] {$($children:tt)*} $($rest:tt)*) => {
+        $crate::xml!(@child $document, $parent,
+            concat!(stringify!($prefix), ":", stringify!($local)),
+            [$($attributes)*] {$($children)*} $($rest)*);
+    };
+    (@children $document:ident, $parent:ident,
+     $prefix:ident : $local:ident [$($attributes:tt)*] $($rest:tt)*) => {
+        $crate::xml!(@child $document, $parent,
+            concat!(stringify!($prefix), ":", stringify!($local)),
+            [$($attributes)*] {} $($rest)*);
+    };
+    (@children $document:ident, $parent:ident,
+     $prefix:ident : $local:ident {$($children:tt)*} $($rest:tt)*) => {
+        $crate::xml!(@child $document, $parent,
+            concat!(stringify!($prefix), ":", stringify!($local)),
+            [] {$($children)*} $($rest)*);
+    };
+    (@children $document:ident, $parent:ident, $prefix:ident : $local:ident $($rest:tt)*) => {
+        $crate::xml!(@child $document, $parent,
+            concat!(stringify!($prefix), ":", stringify!($local)),
+            [] {} $($rest)*);
+    };
+    (@children $document:ident, $parent:ident,
+     $name:ident [$($attributes:tt)*] {$($children:tt)*} $($rest:tt)*) => {
+        $crate::xml!(@child $document, $parent, stringify!($name),
+            [$($attributes)*] {$($children)*} $($rest)*);
+    };
+    (@children $document:ident, $parent:ident, $name:ident [$($attributes:tt)*] $($rest:tt)*) => {
+        $crate::xml!(@child $document, $parent, stringify!($name),
+            [$($attributes)*] {} $($rest)*);
+    };
+    (@children $document:ident, $parent:ident, $name:ident {$($children:tt)*} $($rest:tt)*) => {
+        $crate::xml!(@child $document, $parent, stringify!($name),
+            [] {$($children)*} $($rest)*);
+    };
+    (@children $document:ident, $parent:ident, $name:ident $($rest:tt)*) => {
+        $crate::xml!(@child $document, $parent, stringify!($name), [] {} $($rest)*);
+    };
+
+    (@child $document:ident, $parent:ident, $name:expr,
+     [$($attributes:tt)*] {$($children:tt)*} $($rest:tt)*) => {
+        let _safe_to_ignore = $crate::xml!(@element $document, Some(&mut $parent), $name,
+            [$($attributes)*] {$($children)*});
+        $crate::xml!(@children $document, $parent, $($rest)*);
+    };
+
+    (@element $document:ident, $parent:expr, $name:expr,
+     [$($attributes:tt)*] {$($children:tt)*}) => {{
+        #[allow(unused_mut)]
+        let mut attributes: ::std::vec::Vec<(&str, ::std::string::String)> =
+            ::std::vec::Vec::new();
+        $crate::xml!(@attributes attributes, $($attributes)*);
+        #[allow(unused_mut)]
+        let mut element = $crate::macros::__element($document, $parent, $name, &attributes)?;
+        $crate::xml!(@children $document, element, $($children)*);
+        element
+    }};
+
+    ($document:expr, $prefix:ident : $local:ident $([$($attributes:tt)*])? $({$($children:tt)*})?) => {
+        $crate::macros::__build(&$document, |document| {
+            Ok($crate::xml!(@element document, None,
+                concat!(stringify!($prefix), ":", stringify!($local)),
+                [$($($attributes)*)?] {$($($children)*)?}))
+        })
+    };
+    ($document:expr, $name:ident $([$($attributes:tt)*])? $({$($children:tt)*})?) => {
+        $crate::macros::__build(&$document, |document| {
+            Ok($crate::xml!(@element document, None, stringify!($name),
+                [$($($attributes)*)?] {$($($children)*)?}))
+        })
+    };
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+#[doc(hidden)]
+pub fn __build<F>(document: &RefNode, build: F) -> Result<RefNode>
+where
+    F: FnOnce(&RefNode) -> Result<RefNode>,
+{
+    if !is_document(document) {
+        warn!("{}", MSG_INVALID_NODE_TYPE);
+        Err(Error::InvalidState)
+    } else {
+        build(document)
+    }
+}
+
+#[doc(hidden)]
+pub fn __element(
+    document: &RefNode,
+    parent: Option<&mut RefNode>,
+    name: &str,
+    attributes: &[(&str, String)],
+) -> Result<RefNode> {
+    let prefix = name.split_once(':').map(|(prefix, _)| prefix);
+    let namespace_uri = declared_namespace(attributes, prefix).or_else(|| {
+        parent
+            .as_ref()
+            .and_then(|parent| parent.lookup_namespace_uri(prefix))
+    });
+    let mut element_node = match (namespace_uri.filter(|uri| !uri.is_empty()), prefix) {
+        (Some(namespace_uri), _) => document.create_element_ns(&namespace_uri, name)?,
+        (None, None) => document.create_element(name)?,
+        (None, Some(_)) => {
+            warn!("{}", MSG_PREFIX_WITHOUT_NAMESPACE);
+            return Err(Error::Namespace);
+        }
+    };
+    if let Some(parent) = parent {
+        let _safe_to_ignore = parent.append_child(element_node.clone())?;
+    }
+    for (name, value) in attributes {
+        match name.split_once(':') {
+            None if *name != XMLNS_NS_ATTRIBUTE => element_node.set_attribute(name, value)?,
+            None | Some((XMLNS_NS_ATTRIBUTE, _)) => {
+                element_node.set_attribute_ns(XMLNS_NS_URI, name, value)?
+            }
+            Some((prefix, _)) => match declared_namespace(attributes, Some(prefix))
+                .or_else(|| element_node.lookup_namespace_uri(Some(prefix)))
+            {
+                Some(namespace_uri) => {
+                    element_node.set_attribute_ns(&namespace_uri, name, value)?
+                }
+                None => {
+                    warn!("{}", MSG_PREFIX_WITHOUT_NAMESPACE);
+                    return Err(Error::Namespace);
+                }
+            },
+        }
+    }
+    Ok(element_node)
+}
+
+#[doc(hidden)]
+pub fn __text(document: &RefNode, parent: &mut RefNode, data: &str) -> Result<()> {
+    let _safe_to_ignore = parent.append_child(document.create_text_node(data))?;
+    Ok(())
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn declared_namespace(attributes: &[(&str, String)], prefix: Option<&str>) -> Option<String> {
+    attributes
+        .iter()
+        .find(|(name, _)| match (name.split_once(':'), prefix) {
+            (None, None) => *name == XMLNS_NS_ATTRIBUTE,
+            (Some((XMLNS_NS_ATTRIBUTE, local)), Some(prefix)) => local == prefix,
+            _ => false,
+        })
+        .map(|(_, value)| value.clone())
+}
@@ -16,19 +16,66 @@ let dom = read_xml(r#"<?xml version="1.0"?><xml/>"#);
 assert!(dom.is_ok());
 ```
 
+Document type declarations are parsed into a `DocumentType` node, with any internal subset
+retained as text, see [`DocumentTypeDecl`](../level2/ext/trait.DocumentTypeDecl.html).
+
+An existing quick-xml `Reader` may also be parsed directly with [`read_quick_xml`](fn.read_quick_xml.html),
+in which case namespace declarations are processed and elements and attributes are created with
+the namespace URI in scope for their prefix.
+
+```rust
+use quick_xml::Reader;
+use xml_dom::level2::*;
+use xml_dom::parser::read_quick_xml;
+
+let xml = r#"<a:root xmlns:a="urn:a"><a:item/></a:root>"#;
+let dom = read_quick_xml(Reader::from_str(xml)).unwrap();
+let root = dom.document_element().unwrap();
+assert_eq!(root.namespace_uri(), Some("urn:a".to_string()));
+assert_eq!(root.local_name(), "root");
+```
+
+The [`FromReader`](trait.FromReader.html) trait provides the same as `RefNode::from_reader`. When
+a document is malformed the [`PositionedError`](struct.PositionedError.html) returned also has the
+byte offset in the input at which this was detected.
+
+```rust
+use xml_dom::parser::{read_xml, Error};
+
+let error = read_xml("<xml><item></xml>").unwrap_err();
+assert!(matches!(error.error(), Error::Malformed));
+assert!(error.position().is_some());
+```
+
+The `FromStr` trait is also implemented for `RefNode`, so the following is equivalent, and the
+resulting node may be written back out as text using `Display`.
+
+```rust
+use xml_dom::level2::RefNode;
+
+let dom: RefNode = r#"<xml><title>Hello</title></xml>"#.parse().unwrap();
+assert_eq!(dom.to_string(), r#"<xml><title>Hello</title></xml>"#);
+```
+
 */
 
 use crate::level2::convert::as_document_mut;
-use crate::level2::ext::{XmlDecl, XmlVersion};
+use crate::level2::ext::{DocumentTypeDecl, XmlDecl, XmlVersion};
 use crate::level2::node_impl::Extension;
+use crate::level2::trait_impls::is_namespace_declaration;
 use crate::level2::*;
 use crate::shared::error::Error as DOMError;
+use crate::shared::syntax::{
+    XMLNS_NS_ATTRIBUTE, XMLNS_NS_URI, XML_DOCTYPE_PUBLIC, XML_DOCTYPE_SYSTEM,
+};
 use quick_xml::events::{BytesCData, BytesDecl, BytesEnd, BytesStart, BytesText, Event};
 use quick_xml::Reader;
-use std::borrow::Borrow;
 use std::fmt::{Display, Formatter};
-use std::io::BufRead;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
 use std::str::FromStr;
+use std::sync::Arc;
 
 // ------------------------------------------------------------------------------------------------
 // Public Types
@@ -45,19 +92,58 @@ pub enum Error {
     InvalidCharacter,
     /// From the DOM Error.
     NotSupported,
-    /// From quick_xml Error.
-    IO,
+    /// An I/O error reading the underlying source, this wraps the original error.
+    IO(Arc<std::io::Error>),
     /// From quick_xml Error.
     Encoding,
     /// Everything else.
     Malformed,
 }
 
+///
+/// An error constructing a DOM, returned by the functions in this module. When the input is
+/// malformed this also has the byte offset in the input at which the error was detected.
+///
+#[derive(Clone, Debug)]
+pub struct PositionedError {
+    error: Error,
+    position: Option<usize>,
+}
+
+///
+/// Construct a DOM from a quick-xml `Reader`; this is implemented for `RefNode`, and the node
+/// returned is a `Document` node.
+///
+pub trait FromReader: Sized {
+    ///
+    /// Parse the events from an existing quick-xml `Reader` into a new document, as
+    /// [`read_quick_xml`](fn.read_quick_xml.html) does.
+    ///
+    fn from_reader<B: BufRead>(reader: Reader<B>) -> PositionedResult<Self>;
+}
+
 ///
 /// Result type for public function(s).
 ///
 pub type Result<T> = std::result::Result<T, Error>;
 
+///
+/// Result type for the public functions that parse a document.
+///
+pub type PositionedResult<T> = std::result::Result<T, PositionedError>;
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Debug, PartialEq)]
+struct DocTypeDecl {
+    name: String,
+    public_id: Option<String>,
+    system_id: Option<String>,
+    internal_subset: Option<String>,
+}
+
 // ------------------------------------------------------------------------------------------------
 // Public Functions
 // ------------------------------------------------------------------------------------------------
@@ -66,16 +152,53 @@ pub type Result<T> = std::result::Result<T, Error>;
 /// Parse the provided string into a DOM structure; if the result is OK, the result returned
 /// can be safely assumed to be a `Document` node.
 ///
-pub fn read_xml(xml: &str) -> Result<RefNode> {
-    inner_read(&mut Reader::from_str(xml))
+pub fn read_xml(xml: &str) -> PositionedResult<RefNode> {
+    inner_read(Reader::from_str(xml))
 }
 
 ///
 /// Parse the provided string into a DOM structure; if the result is OK, the result returned
 /// can be safely assumed to be a `Document` node.
 ///
-pub fn read_reader<B: BufRead>(reader: B) -> Result<RefNode> {
-    inner_read(&mut Reader::from_reader(reader))
+pub fn read_reader<B: BufRead>(reader: B) -> PositionedResult<RefNode> {
+    inner_read(Reader::from_reader(reader))
+}
+
+///
+/// Parse the events from an existing quick-xml `Reader` into a DOM structure; if the result is
+/// OK, the result returned can be safely assumed to be a `Document` node.
+///
+/// The configuration of the reader, such as `trim_text` or `expand_empty_elements`, is left as
+/// provided. Unlike the other functions in this module, namespace declarations are processed: an
+/// element or attribute with a prefix, or an element in the scope of a default namespace, is
+/// created with the namespace URI declared for it, and an undeclared prefix results in
+/// `Error::Malformed`.
+///
+pub fn read_quick_xml<B: BufRead>(mut reader: Reader<B>) -> PositionedResult<RefNode> {
+    let mut event_buffer: Vec<u8> = Vec::new();
+
+    let result = document(&mut reader, &mut event_buffer, true);
+    with_position(&reader, result)
+}
+
+///
+/// Parse the content of the provided reader into a DOM structure; if the result is OK, the
+/// result returned can be safely assumed to be a `Document` node.
+///
+/// The reader is buffered internally, and only read sequentially, so this may be used with files,
+/// byte slices, or network streams without first reading the entire source into memory. Any I/O
+/// error encountered is returned as `Error::IO`.
+///
+pub fn read_from<R: Read>(reader: R) -> PositionedResult<RefNode> {
+    read_reader(BufReader::new(reader))
+}
+
+///
+/// Parse the content of the file at `path` into a DOM structure; if the result is OK, the
+/// result returned can be safely assumed to be a `Document` node.
+///
+pub fn read_from_file<P: AsRef<Path>>(path: P) -> PositionedResult<RefNode> {
+    read_from(File::open(path)?)
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -84,28 +207,111 @@ pub fn read_reader<B: BufRead>(reader: B) -> Result<RefNode> {
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                Error::HierarchyRequest => "An attempt insert a node somewhere it doesn't belong",
-                Error::InvalidCharacter =>
-                    "An invalid or illegal character was specified, such as in a name",
-                Error::NotSupported =>
-                    "The implementation does not support the requested type of object or operation",
-                Error::IO => "I/O Error reading data",
-                Error::Encoding => "Issue decoding bytes to UTF-8",
-                Error::Malformed => "Input document malformed",
+        match self {
+            Error::HierarchyRequest => {
+                write!(f, "An attempt insert a node somewhere it doesn't belong")
             }
-        )
+            Error::InvalidCharacter => write!(
+                f,
+                "An invalid or illegal character was specified, such as in a name"
+            ),
+            Error::NotSupported => write!(
+                f,
+                "The implementation does not support the requested type of object or operation"
+            ),
+            Error::IO(err) => write!(f, "I/O Error reading data: {}", err),
+            Error::Encoding => write!(f, "Issue decoding bytes to UTF-8"),
+            Error::Malformed => write!(f, "Input document malformed"),
+        }
     }
 }
 
-impl std::error::Error for Error {}
+impl Display for PositionedError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.position {
+            Some(position) => write!(f, "{} at byte {}", self.error, position),
+            None => write!(f, "{}", self.error),
+        }
+    }
+}
 
-impl<T> Into<Result<T>> for Error {
-    fn into(self) -> Result<T> {
-        Err(self)
+impl std::error::Error for PositionedError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl From<PositionedError> for Error {
+    fn from(err: PositionedError) -> Self {
+        err.error
+    }
+}
+
+impl From<Error> for PositionedError {
+    fn from(error: Error) -> Self {
+        PositionedError {
+            error,
+            position: None,
+        }
+    }
+}
+
+impl From<std::io::Error> for PositionedError {
+    fn from(err: std::io::Error) -> Self {
+        Error::from(err).into()
+    }
+}
+
+impl PositionedError {
+    ///
+    /// Return the error constructing the DOM.
+    ///
+    pub fn error(&self) -> &Error {
+        &self.error
+    }
+
+    ///
+    /// Return the byte offset in the input at which a malformed document was detected; this is
+    /// `None` for any error other than `Error::Malformed`.
+    ///
+    pub fn position(&self) -> Option<usize> {
+        self.position
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::IO(err) => Some(err.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        error!("std::io::Error: {:?}", err);
+        Error::IO(Arc::new(err))
+    }
+}
+
+impl FromStr for RefNode {
+    type Err = PositionedError;
+
+    fn from_str(s: &str) -> PositionedResult<Self> {
+        read_xml(s)
+    }
+}
+
+impl FromReader for RefNode {
+    fn from_reader<B: BufRead>(reader: Reader<B>) -> PositionedResult<Self> {
+        read_quick_xml(reader)
+    }
+}
+
+impl<T> From<Error> for Result<T> {
+    fn from(val: Error) -> Self {
+        Err(val)
     }
 }
 
@@ -126,19 +332,16 @@ impl From<quick_xml::Error> for Error {
         error!("quick_xml::Error: {:?}", err);
         match err {
             quick_xml::Error::InvalidAttr(_) => Error::Malformed,
-            quick_xml::Error::Io(_) => Error::IO,
-            quick_xml::Error::Utf8(_) => Error::Encoding,
+            quick_xml::Error::Io(err) => Error::IO(Arc::new(err)),
+            quick_xml::Error::NonDecodable(_) => Error::Encoding,
             quick_xml::Error::UnexpectedEof(_) => Error::Malformed,
             quick_xml::Error::EndEventMismatch { .. } => Error::Malformed,
             quick_xml::Error::UnexpectedToken(_) => Error::Malformed,
             quick_xml::Error::UnexpectedBang(_) => Error::Malformed,
             quick_xml::Error::TextNotFound => Error::Malformed,
             quick_xml::Error::XmlDeclWithoutVersion(_) => Error::Malformed,
-            //            quick_xml::Error::NameWithQuote(_) => Error::Malformed,
-            //            quick_xml::Error::NoEqAfterName(_) => Error::Malformed,
-            //            quick_xml::Error::UnquotedValue(_) => Error::Malformed,
-            //            quick_xml::Error::DuplicatedAttribute(_, _) => Error::Malformed,
             quick_xml::Error::EscapeError(_) => Error::InvalidCharacter,
+            quick_xml::Error::UnknownPrefix(_) => Error::Malformed,
         }
     }
 }
@@ -147,12 +350,29 @@ impl From<quick_xml::Error> for Error {
 // Private Functions
 // ------------------------------------------------------------------------------------------------
 
-fn inner_read<T: BufRead>(reader: &mut Reader<T>) -> Result<RefNode> {
+fn inner_read<T: BufRead>(mut reader: Reader<T>) -> PositionedResult<RefNode> {
     let _safe_to_ignore = reader.trim_text(true);
 
     let mut event_buffer: Vec<u8> = Vec::new();
 
-    document(reader, &mut event_buffer)
+    let result = document(&mut reader, &mut event_buffer, false);
+    with_position(&reader, result)
+}
+
+//
+// Add the position of the reader to an `Error::Malformed`, this is where it was detected.
+//
+fn with_position<T: BufRead>(
+    reader: &Reader<T>,
+    result: Result<RefNode>,
+) -> PositionedResult<RefNode> {
+    result.map_err(|error| {
+        let position = match error {
+            Error::Malformed => Some(reader.buffer_position()),
+            _ => None,
+        };
+        PositionedError { error, position }
+    })
 }
 
 ///
@@ -176,13 +396,17 @@ fn inner_read<T: BufRead>(reader: &mut Reader<T>) -> Result<RefNode> {
 /// S                 ::= (#x20 | #x9 | #xD | #xA)+
 /// ```
 ///
-fn document<T: BufRead>(reader: &mut Reader<T>, event_buffer: &mut Vec<u8>) -> Result<RefNode> {
+fn document<T: BufRead>(
+    reader: &mut Reader<T>,
+    event_buffer: &mut Vec<u8>,
+    namespace_aware: bool,
+) -> Result<RefNode> {
     let mut document = get_implementation()
         .create_document(None, None, None)
         .unwrap();
 
     loop {
-        match reader.read_event(event_buffer) {
+        match reader.read_event_into(event_buffer) {
             Ok(Event::Decl(ev)) => {
                 let mut mut_document = document.borrow_mut();
                 if let Extension::Document {
@@ -194,21 +418,31 @@ fn document<T: BufRead>(reader: &mut Reader<T>, event_buffer: &mut Vec<u8>) -> R
                         return Error::Malformed.into();
                     } else {
                         let (version, encoding, standalone) = make_decl(reader, ev)?;
-                        *i_xml_declaration = Some(XmlDecl::new(
-                            XmlVersion::from_str(&version).unwrap(),
-                            encoding,
-                            standalone,
-                        ));
+                        let version = match XmlVersion::from_str(&version) {
+                            Ok(version) => version,
+                            Err(_) => {
+                                error!("XML declaration has unsupported version {:?}", version);
+                                return Error::NotSupported.into();
+                            }
+                        };
+                        *i_xml_declaration = Some(XmlDecl::new(version, encoding, standalone));
                     }
                 }
             }
             Ok(Event::Start(ev)) => {
-                let mut new_element = handle_start(reader, &mut document, None, ev)?;
-                let _safe_to_ignore =
-                    element(reader, event_buffer, &mut document, &mut new_element);
+                let mut new_element =
+                    handle_start(reader, &mut document, None, ev, namespace_aware)?;
+                let _safe_to_ignore = element(
+                    reader,
+                    event_buffer,
+                    &mut document,
+                    &mut new_element,
+                    namespace_aware,
+                )?;
             }
             Ok(Event::Empty(ev)) => {
-                let _safe_to_ignore = handle_start(reader, &mut document, None, ev)?;
+                let _safe_to_ignore =
+                    handle_start(reader, &mut document, None, ev, namespace_aware)?;
             }
             Ok(Event::End(ev)) => {
                 let _safe_to_ignore = handle_end(reader, &mut document, None, ev)?;
@@ -219,18 +453,17 @@ fn document<T: BufRead>(reader: &mut Reader<T>, event_buffer: &mut Vec<u8>) -> R
             Ok(Event::PI(ev)) => {
                 let _safe_to_ignore = handle_pi(reader, &mut document, None, ev)?;
             }
-            // Ok(Event::DocType(ev)) => {
-            //     if prolog_pre_nodes
-            //         .iter()
-            //         .find(|n| n.kind == PreNodeKind::DocType)
-            //         .is_some()
-            //     {
-            //         error!("only one document type allowed");
-            //         return Error::Malformed.into();
-            //     }
-            //     prolog_pre_nodes.push(make_doc_type(reader, ev)?);
-            // }
-            Ok(Event::Eof) => return Ok(document),
+            Ok(Event::DocType(ev)) => {
+                let _safe_to_ignore = handle_doc_type(reader, &mut document, ev)?;
+            }
+            Ok(Event::Text(ev)) if is_whitespace_text(&ev) => {}
+            Ok(Event::Eof) => {
+                if document.document_element().is_none() {
+                    error!("document has no root element");
+                    return Error::Malformed.into();
+                }
+                return Ok(document);
+            }
             Ok(ev) => {
                 error!("Unexpected parser event: {:?}", ev);
                 return Error::Malformed.into();
@@ -259,15 +492,24 @@ fn element<T: BufRead>(
     event_buffer: &mut Vec<u8>,
     document: &mut RefNode,
     parent_element: &mut RefNode,
+    namespace_aware: bool,
 ) -> Result<RefNode> {
     loop {
-        match reader.read_event(event_buffer) {
+        match reader.read_event_into(event_buffer) {
             Ok(Event::Start(ev)) => {
-                let mut new_element = handle_start(reader, document, Some(parent_element), ev)?;
-                let _safe_to_ignore = element(reader, event_buffer, document, &mut new_element)?;
+                let mut new_element =
+                    handle_start(reader, document, Some(parent_element), ev, namespace_aware)?;
+                let _safe_to_ignore = element(
+                    reader,
+                    event_buffer,
+                    document,
+                    &mut new_element,
+                    namespace_aware,
+                )?;
             }
             Ok(Event::Empty(ev)) => {
-                let _safe_to_ignore = handle_start(reader, document, Some(parent_element), ev)?;
+                let _safe_to_ignore =
+                    handle_start(reader, document, Some(parent_element), ev, namespace_aware)?;
             }
             Ok(Event::End(ev)) => {
                 let _safe_to_ignore = handle_end(reader, document, Some(parent_element), ev)?;
@@ -304,26 +546,64 @@ fn handle_start<T: BufRead>(
     document: &mut RefNode,
     parent_node: Option<&mut RefNode>,
     ev: BytesStart<'_>,
+    namespace_aware: bool,
 ) -> Result<RefNode> {
+    let mut attributes: Vec<(Name, String)> = Vec::new();
+    for attribute in ev.attributes() {
+        let attribute = attribute.map_err(quick_xml::Error::InvalidAttr)?;
+        let value = attribute.decode_and_unescape_value(reader)?;
+        let name = reader.decoder().decode(attribute.key.as_ref())?;
+        attributes.push((Name::from_str(&name)?, value.to_string()));
+    }
+
+    let mut actual_parent = match parent_node {
+        None => document.clone(),
+        Some(actual) => actual.clone(),
+    };
+
     let mut element = {
         let mut_document = as_document_mut(document).unwrap();
         let name = ev.name();
-        let name = reader.decode(name)?;
-        let new_node = mut_document.create_element(&name).unwrap();
-        let mut actual_parent = match parent_node {
-            None => document.clone(),
-            Some(actual) => actual.clone(),
+        let name = reader.decoder().decode(name.as_ref())?;
+        let new_node = if namespace_aware {
+            let prefix = Name::from_str(&name)?.prefix().clone();
+            match resolve_prefix(&actual_parent, &attributes, prefix.as_deref()) {
+                Some(namespace_uri) => mut_document.create_element_ns(&namespace_uri, &name)?,
+                None if prefix.is_none() => mut_document.create_element(&name)?,
+                None => return undeclared_prefix(&name).into(),
+            }
+        } else {
+            mut_document.create_element(&name)?
         };
         actual_parent.append_child(new_node)?
     };
 
-    for attribute in ev.attributes() {
-        let attribute = attribute.unwrap();
-        let value = attribute.unescape_and_decode_value(reader)?;
-        let name = reader.decode(attribute.key)?;
-        let attribute_node = document.create_attribute_with(name, &value)?;
-
-        let _safe_to_ignore = element.set_attribute_node(attribute_node)?;
+    for (name, value) in &attributes {
+        let qualified_name = name.to_string();
+        let namespace_uri = if !namespace_aware {
+            None
+        } else if is_namespace_declaration(name) {
+            Some(XMLNS_NS_URI.to_string())
+        } else if name.prefix().is_some() {
+            match resolve_prefix(&actual_parent, &attributes, name.prefix().as_deref()) {
+                Some(namespace_uri) => Some(namespace_uri),
+                None => return undeclared_prefix(&qualified_name).into(),
+            }
+        } else {
+            None
+        };
+        let _safe_to_ignore = match namespace_uri {
+            None => {
+                let attribute_node = document.create_attribute_with(&qualified_name, value)?;
+                element.set_attribute_node(attribute_node)?
+            }
+            Some(namespace_uri) => {
+                let mut attribute_node =
+                    document.create_attribute_ns(&namespace_uri, &qualified_name)?;
+                attribute_node.set_value(value)?;
+                element.set_attribute_node_ns(attribute_node)?
+            }
+        };
     }
 
     Ok(element)
@@ -342,6 +622,24 @@ fn handle_end<T: BufRead>(
     .clone())
 }
 
+fn handle_doc_type<T: BufRead>(
+    reader: &mut Reader<T>,
+    document: &mut RefNode,
+    ev: BytesText<'_>,
+) -> Result<RefNode> {
+    let text = reader.decoder().decode(ev.as_ref())?;
+    let doc_type_decl = parse_doc_type(&text)?;
+    let mut doc_type = get_implementation().create_document_type(
+        &doc_type_decl.name,
+        doc_type_decl.public_id.as_deref(),
+        doc_type_decl.system_id.as_deref(),
+    )?;
+    if let Some(internal_subset) = &doc_type_decl.internal_subset {
+        doc_type.set_internal_subset(internal_subset)?;
+    }
+    document.append_child(doc_type).map_err(|e| e.into())
+}
+
 fn handle_comment<T: BufRead>(
     reader: &mut Reader<T>,
     document: &mut RefNode,
@@ -349,8 +647,8 @@ fn handle_comment<T: BufRead>(
     ev: BytesText<'_>,
 ) -> Result<RefNode> {
     let mut_document = as_document_mut(document).unwrap();
-    let text = make_text(reader, ev)?;
-    let new_node = mut_document.create_comment(&text);
+    let text = make_comment(reader, ev)?;
+    let new_node = mut_document.create_comment(&text)?;
     let actual_parent = match parent_node {
         None => document,
         Some(actual) => actual,
@@ -382,7 +680,7 @@ fn handle_cdata<T: BufRead>(
 ) -> Result<RefNode> {
     let mut_document = as_document_mut(document).unwrap();
     let text = make_cdata(reader, ev)?;
-    let new_node = mut_document.create_cdata_section(text.as_ref()).unwrap();
+    let new_node = mut_document.create_cdata_section(text.as_ref())?;
     let actual_parent = match parent_node {
         None => document,
         Some(actual) => actual,
@@ -398,7 +696,7 @@ fn handle_pi<T: BufRead>(
 ) -> Result<RefNode> {
     let mut_document = as_document_mut(document).unwrap();
     let (target, data) = {
-        let text = ev.unescape_and_decode(&reader)?;
+        let text = reader.decoder().decode(ev.as_ref())?;
         let parts = text.splitn(2, ' ').collect::<Vec<&str>>();
         match parts.len() {
             1 => (parts[0].to_string(), None),
@@ -413,14 +711,7 @@ fn handle_pi<T: BufRead>(
             _ => return Error::Malformed.into(),
         }
     };
-    let new_node = match data {
-        None => mut_document
-            .create_processing_instruction(&target, None)
-            .unwrap(),
-        Some(s) => mut_document
-            .create_processing_instruction(&target, Some(s.as_str()))
-            .unwrap(),
-    };
+    let new_node = mut_document.create_processing_instruction(&target, data.as_deref())?;
     let actual_parent = match parent_node {
         None => document,
         Some(actual) => actual,
@@ -430,13 +721,17 @@ fn handle_pi<T: BufRead>(
 
 // ------------------------------------------------------------------------------------------------
 
-fn make_text<T: BufRead>(reader: &mut Reader<T>, ev: BytesText<'_>) -> Result<String> {
-    Ok(ev.unescape_and_decode(&reader)?)
+fn make_text<T: BufRead>(_reader: &mut Reader<T>, ev: BytesText<'_>) -> Result<String> {
+    Ok(ev.unescape()?.to_string())
+}
+
+fn make_comment<T: BufRead>(reader: &mut Reader<T>, ev: BytesText<'_>) -> Result<String> {
+    Ok(reader.decoder().decode(ev.as_ref())?.to_string())
 }
 
 fn make_cdata<T: BufRead>(reader: &mut Reader<T>, ev: BytesCData<'_>) -> Result<String> {
     let cdata_bytes = ev.into_inner();
-    let decoded_string = reader.decode(cdata_bytes.as_ref())?;
+    let decoded_string = reader.decoder().decode(cdata_bytes.as_ref())?;
     Ok(decoded_string.to_string())
 }
 
@@ -444,29 +739,123 @@ fn make_decl<T: BufRead>(
     reader: &mut Reader<T>,
     ev: BytesDecl<'_>,
 ) -> Result<(String, Option<String>, Option<bool>)> {
-    let version = ev.version().unwrap();
-    let version = version.borrow();
-    let version = reader.decode(version).unwrap();
+    let decoder = reader.decoder();
+    let version = ev.version()?;
+    let version = decoder.decode(version.as_ref())?;
     let version = unquote(version.to_string())?;
     let encoding = if let Some(ev_value) = ev.encoding() {
-        let encoding = ev_value.unwrap();
-        let encoding = encoding.borrow();
-        let encoding = reader.decode(encoding).unwrap();
-        Some(encoding.to_string())
+        let encoding = ev_value?;
+        let encoding = decoder.decode(encoding.as_ref())?;
+        Some(unquote(encoding.to_string())?)
     } else {
         None
     };
     let standalone = if let Some(ev_value) = ev.standalone() {
-        let standalone = ev_value.unwrap();
-        let standalone = standalone.borrow();
-        let standalone = reader.decode(standalone).unwrap();
-        Some(standalone == "yes")
+        let standalone = ev_value?;
+        let standalone = decoder.decode(standalone.as_ref())?;
+        Some(unquote(standalone.to_string())? == "yes")
     } else {
         None
     };
     Ok((version, encoding, standalone))
 }
 
+//
+// Split the content of a document type declaration, following `<!DOCTYPE`, into its name, any
+// external identifier, and any internal subset.
+//
+// ```ebnf
+// doctypedecl       ::= '<!DOCTYPE' S Name (S ExternalID)? S? ('[' intSubset ']' S?)? '>'
+//
+// ExternalID        ::= 'SYSTEM' S SystemLiteral | 'PUBLIC' S PubidLiteral S SystemLiteral
+// ```
+//
+fn parse_doc_type(text: &str) -> Result<DocTypeDecl> {
+    let mut tokens: Vec<&str> = Vec::new();
+    let mut internal_subset = None;
+    let mut rest = text.trim_start();
+    while !rest.is_empty() {
+        if let Some(quote) = rest.chars().next().filter(|c| *c == '"' || *c == '\'') {
+            match rest[1..].find(quote) {
+                Some(end) => {
+                    tokens.push(&rest[1..end + 1]);
+                    rest = &rest[end + 2..];
+                }
+                None => return malformed_doc_type(text),
+            }
+        } else if let Some(subset) = rest.strip_prefix('[') {
+            match subset.rfind(']') {
+                Some(end) if subset[end + 1..].trim().is_empty() => {
+                    internal_subset = Some(subset[..end].to_string());
+                    rest = "";
+                }
+                _ => return malformed_doc_type(text),
+            }
+        } else {
+            let end = rest
+                .find(|c: char| c.is_whitespace() || c == '"' || c == '\'' || c == '[')
+                .unwrap_or(rest.len());
+            tokens.push(&rest[..end]);
+            rest = &rest[end..];
+        }
+        rest = rest.trim_start();
+    }
+    let (name, public_id, system_id) = match tokens.as_slice() {
+        [name] => (name, None, None),
+        [name, keyword, system_id] if *keyword == XML_DOCTYPE_SYSTEM => {
+            (name, None, Some(system_id.to_string()))
+        }
+        [name, keyword, public_id, system_id] if *keyword == XML_DOCTYPE_PUBLIC => (
+            name,
+            Some(public_id.to_string()),
+            Some(system_id.to_string()),
+        ),
+        _ => return malformed_doc_type(text),
+    };
+    Ok(DocTypeDecl {
+        name: name.to_string(),
+        public_id,
+        system_id,
+        internal_subset,
+    })
+}
+
+fn malformed_doc_type<T>(text: &str) -> Result<T> {
+    error!("Malformed document type declaration {:?}", text);
+    Error::Malformed.into()
+}
+
+//
+// The namespace URI bound to `prefix`, or the default namespace if `None`, for an element with
+// the given attributes that is to be added to `parent`. A declaration in the attributes takes
+// precedence over any in scope at the parent; an empty URI removes the default namespace.
+//
+fn resolve_prefix(
+    parent: &RefNode,
+    attributes: &[(Name, String)],
+    prefix: Option<&str>,
+) -> Option<String> {
+    let declaration = attributes.iter().find(|(name, _)| match prefix {
+        None => name.prefix().is_none() && name.local_name() == XMLNS_NS_ATTRIBUTE,
+        Some(prefix) => {
+            name.prefix().as_deref() == Some(XMLNS_NS_ATTRIBUTE) && name.local_name() == prefix
+        }
+    });
+    match declaration {
+        Some((_, namespace_uri)) => Some(namespace_uri.clone()).filter(|uri| !uri.is_empty()),
+        None => parent.lookup_namespace_uri(prefix),
+    }
+}
+
+fn undeclared_prefix(name: &str) -> Error {
+    error!("Name {:?} uses an undeclared namespace prefix", name);
+    Error::Malformed
+}
+
+fn is_whitespace_text(ev: &BytesText<'_>) -> bool {
+    ev.iter().all(|b| matches!(b, b' ' | b'\t' | b'\r' | b'\n'))
+}
+
 #[allow(clippy::if_same_then_else)]
 fn unquote(s: String) -> Result<String> {
     if s.starts_with('"') && s.ends_with('"') {
@@ -514,6 +903,21 @@ mod tests {
         test_good_xml("<!-- start here --><xml/><!-- end here -->");
     }
 
+    #[test]
+    fn test_commented_document_element() {
+        let xml = "<!-- start here --><?go there?><xml/><!-- end here -->";
+        let document_node = read_xml(xml).unwrap();
+        let document_element = convert::as_document(&document_node)
+            .unwrap()
+            .document_element()
+            .unwrap();
+        assert_eq!(document_element.node_name().to_string(), "xml");
+        assert_eq!(
+            document_node.to_string(),
+            "<!-- start here --><?go there?><xml></xml><!-- end here -->"
+        );
+    }
+
     #[test]
     fn test_commented_element() {
         test_good_xml("<xml><!-- I'm inside --></xml>");
@@ -534,6 +938,203 @@ mod tests {
         test_good_xml("<xml id=\"11\"></xml>");
     }
 
+    fn test_bad_xml(xml: &str) {
+        let dom = read_xml(xml);
+        assert!(dom.is_err());
+    }
+
+    fn test_round_trip(xml: &str) {
+        let dom = RefNode::from_str(xml);
+        assert!(dom.is_ok());
+        let dom = dom.unwrap();
+        assert_eq!(dom.node_type(), NodeType::Document);
+        assert_eq!(dom.to_string(), xml);
+    }
+
+    #[test]
+    fn test_from_str() {
+        let dom: PositionedResult<RefNode> = "<xml/>".parse();
+        assert!(dom.is_ok());
+        let dom = dom.unwrap();
+        let root = dom.document_element().unwrap();
+        assert_eq!(root.node_name().to_string(), "xml");
+    }
+
+    #[test]
+    fn test_round_trip_simple() {
+        test_round_trip("<xml></xml>");
+        test_round_trip("<xml id=\"11\"><item>one</item><item>two</item></xml>");
+        test_round_trip("<xml><!-- comment --><?pi some data?></xml>");
+        test_round_trip("<xml><![CDATA[<not-markup/>]]></xml>");
+        test_round_trip("<!-- before --><xml></xml><!-- after -->");
+    }
+
+    #[test]
+    fn test_mismatched_tags() {
+        test_bad_xml("<xml></lmx>");
+        test_bad_xml("<xml><item></xml>");
+        test_bad_xml("<xml><item>");
+    }
+
+    #[test]
+    fn test_bad_names() {
+        test_bad_xml("<1xml/>");
+        test_bad_xml("<xml 1id=\"11\"/>");
+    }
+
+    #[test]
+    fn test_unterminated_literals() {
+        test_bad_xml("<xml id=\"11/>");
+        test_bad_xml("<xml><!-- comment </xml>");
+        test_bad_xml("<xml><![CDATA[ data </xml>");
+    }
+
+    #[test]
+    fn test_not_a_document() {
+        test_bad_xml("");
+        test_bad_xml("<xml/><xml/>");
+        test_bad_xml("just text");
+    }
+
+    #[test]
+    fn test_read_from() {
+        let dom = read_from("<xml><item/></xml>".as_bytes());
+        assert!(dom.is_ok());
+        assert_eq!(dom.unwrap().to_string(), "<xml><item></item></xml>");
+    }
+
+    #[test]
+    fn test_read_from_io_error() {
+        struct FailingReader;
+        impl Read for FailingReader {
+            fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("oops"))
+            }
+        }
+        let err = read_from(FailingReader).unwrap_err();
+        assert!(matches!(err.error(), Error::IO(_)));
+        assert_eq!(err.position(), None);
+    }
+
+    #[test]
+    fn test_read_from_file_missing() {
+        let err = read_from_file("no/such/file.xml").unwrap_err();
+        match err.error() {
+            Error::IO(err) => assert_eq!(err.kind(), std::io::ErrorKind::NotFound),
+            _ => panic!("expected an I/O error"),
+        }
+    }
+
+    #[test]
+    fn test_read_from_file() {
+        let path = std::env::temp_dir().join("xml_dom_test_read_from_file.xml");
+        std::fs::write(&path, "<?xml version=\"1.0\"?><xml><item/></xml>").unwrap();
+        let dom = read_from_file(&path);
+        let _safe_to_ignore = std::fs::remove_file(&path);
+        assert!(dom.is_ok());
+    }
+
+    #[test]
+    fn test_doc_type() {
+        let dom =
+            read_xml(r#"<!DOCTYPE html PUBLIC "-//W3C//DTD XHTML 1.0//EN" "xhtml1.dtd"><html/>"#)
+                .unwrap();
+        let doc_type = dom.doc_type().unwrap();
+        assert_eq!(doc_type.node_name().to_string(), "html");
+        assert_eq!(
+            DocumentType::public_id(&doc_type),
+            Some("-//W3C//DTD XHTML 1.0//EN".to_string())
+        );
+        assert_eq!(
+            DocumentType::system_id(&doc_type),
+            Some("xhtml1.dtd".to_string())
+        );
+
+        let dom =
+            read_xml(r#"<!DOCTYPE catalog SYSTEM "catalog.dtd" [<!ENTITY a "b">]><catalog/>"#)
+                .unwrap();
+        let doc_type = dom.doc_type().unwrap();
+        assert_eq!(DocumentType::public_id(&doc_type), None);
+        assert_eq!(
+            DocumentType::system_id(&doc_type),
+            Some("catalog.dtd".to_string())
+        );
+        assert_eq!(
+            doc_type.internal_subset(),
+            Some(r#"<!ENTITY a "b">"#.to_string())
+        );
+    }
+
+    #[test]
+    fn test_bad_doc_type() {
+        test_bad_xml(r#"<!DOCTYPE html PUBLIC "-//W3C//DTD XHTML 1.0//EN"><html/>"#);
+        test_bad_xml(r#"<!DOCTYPE html SYSTEM "xhtml1.dtd><html/>"#);
+    }
+
+    #[test]
+    fn test_read_quick_xml_namespaces() {
+        let xml = r#"<root xmlns="urn:default" xmlns:b="urn:b"><b:item b:id="1" id="2"/><plain xmlns=""/></root>"#;
+        let dom = read_quick_xml(Reader::from_str(xml)).unwrap();
+        let root = dom.document_element().unwrap();
+        assert_eq!(root.namespace_uri(), Some("urn:default".to_string()));
+
+        let item = root.first_child().unwrap();
+        assert_eq!(item.namespace_uri(), Some("urn:b".to_string()));
+        assert_eq!(item.local_name(), "item");
+        let id = item.get_attribute_node_ns("urn:b", "id").unwrap();
+        assert_eq!(id.value(), Some("1".to_string()));
+        assert_eq!(item.get_attribute("id"), Some("2".to_string()));
+
+        let plain = item.next_sibling().unwrap();
+        assert_eq!(plain.namespace_uri(), None);
+    }
+
+    #[test]
+    fn test_read_quick_xml_undeclared_prefix() {
+        let xml = r#"<root><b:item/></root>"#;
+        let err = read_quick_xml(Reader::from_str(xml)).unwrap_err();
+        assert!(matches!(err.error(), Error::Malformed));
+    }
+
+    #[test]
+    fn test_from_reader() {
+        let xml = r#"<a:root xmlns:a="urn:a"><![CDATA[x]]><!--y--></a:root>"#;
+        let dom = RefNode::from_reader(Reader::from_str(xml)).unwrap();
+        assert_eq!(dom.node_type(), NodeType::Document);
+        assert_eq!(dom.to_string(), xml);
+        let err = RefNode::from_reader(Reader::from_str("<xml><item></xml>")).unwrap_err();
+        assert!(matches!(err.error(), Error::Malformed));
+        assert!(err.position().is_some());
+    }
+
+    #[test]
+    fn test_error_position() {
+        let err = read_xml("<xml><item></xml>").unwrap_err();
+        assert!(matches!(err.error(), Error::Malformed));
+        assert!(err.position().unwrap() > "<xml><item>".len());
+
+        let err = read_reader("<xml><item></xml>".as_bytes()).unwrap_err();
+        assert!(matches!(err.error(), Error::Malformed));
+        assert!(err.position().unwrap() > "<xml><item>".len());
+
+        let err = read_quick_xml(Reader::from_str("<xml><item></xml>")).unwrap_err();
+        assert!(matches!(err.error(), Error::Malformed));
+        let position = err.position().unwrap();
+        assert!(position > "<xml><item>".len());
+        assert_eq!(
+            err.to_string(),
+            format!("Input document malformed at byte {}", position)
+        );
+
+        let err = read_quick_xml(Reader::from_str("<root><b:item/></root>")).unwrap_err();
+        assert!(matches!(err.error(), Error::Malformed));
+        assert!(err.position().unwrap() > 0);
+
+        let err = read_quick_xml(Reader::from_str(r#"<?xml version="9.0"?><xml/>"#)).unwrap_err();
+        assert!(matches!(err.error(), Error::NotSupported));
+        assert_eq!(err.position(), None);
+    }
+
     #[test]
     fn test_its_complicated() {
         test_good_xml(
@@ -16,29 +16,35 @@ macro_rules! make_ref_type {
 #[doc(hidden)]
 #[macro_export]
 macro_rules! make_is_as_functions {
-    ($is_f:ident, $is_t:expr, $as_f:ident, $as_t:ident) => {
-        ///
-        /// Determines if the specified node is of the correct node type.
-        ///
-        #[inline]
-        pub fn $is_f(ref_node: &RefNode) -> bool {
-            ref_node.borrow().i_node_type == $is_t
+    (@try_from $is_f:ident, $as_t:ident) => {
+        impl<'a> std::convert::TryFrom<&'a RefNode> for $as_t<'a> {
+            type Error = Error;
+
+            fn try_from(ref_node: &'a RefNode) -> Result<Self> {
+                if $is_f(ref_node) {
+                    Ok(ref_node as $as_t<'a>)
+                } else {
+                    warn!("{}", MSG_INVALID_NODE_TYPE);
+                    Err(Error::InvalidState)
+                }
+            }
         }
+    };
+    (@try_from_mut $is_f:ident, $as_mut_t:ident) => {
+        impl<'a> std::convert::TryFrom<&'a mut RefNode> for $as_mut_t<'a> {
+            type Error = Error;
 
-        ///
-        /// Safely _cast_ the specified `RefNode` into a **Ref** type.
-        ///
-        #[inline]
-        pub fn $as_f(ref_node: &RefNode) -> Result<$as_t<'_>> {
-            if ref_node.borrow().i_node_type == $is_t {
-                Ok(ref_node as $as_t<'_>)
-            } else {
-                warn!("{}", MSG_INVALID_NODE_TYPE);
-                Err(Error::InvalidState)
+            fn try_from(ref_node: &'a mut RefNode) -> Result<Self> {
+                if $is_f(ref_node) {
+                    Ok(ref_node as $as_mut_t<'a>)
+                } else {
+                    warn!("{}", MSG_INVALID_NODE_TYPE);
+                    Err(Error::InvalidState)
+                }
             }
         }
     };
-    ($is_f:ident, $is_t:expr, $as_f:ident, $as_t:ident, $as_mut_f:ident, $as_mut_t:ident) => {
+    ($is_f:ident, $is_t:expr, $as_f:ident, $as_t:ident) => {
         ///
         /// Determines if the specified node is of the correct node type.
         ///
@@ -48,29 +54,28 @@ macro_rules! make_is_as_functions {
         }
 
         ///
-        /// Safely _cast_ the specified `RefNode` into a **Ref** type.
+        /// Safely _cast_ the specified `RefNode` into a **Ref** type; this is the same as
+        /// `TryFrom::try_from(ref_node)`.
         ///
         #[inline]
         pub fn $as_f(ref_node: &RefNode) -> Result<$as_t<'_>> {
-            if ref_node.borrow().i_node_type == $is_t {
-                Ok(ref_node as $as_t<'_>)
-            } else {
-                warn!("{}", MSG_INVALID_NODE_TYPE);
-                Err(Error::InvalidState)
-            }
+            std::convert::TryFrom::try_from(ref_node)
         }
 
+        $crate::make_is_as_functions!(@try_from $is_f, $as_t);
+    };
+    ($is_f:ident, $is_t:expr, $as_f:ident, $as_t:ident, $as_mut_f:ident, $as_mut_t:ident) => {
+        $crate::make_is_as_functions!($is_f, $is_t, $as_f, $as_t);
+
         ///
-        /// Safely _cast_ the specified `RefNode` into a mutable **Ref** type.
+        /// Safely _cast_ the specified `RefNode` into a mutable **Ref** type; this is the same as
+        /// `TryFrom::try_from(ref_node)`.
         ///
         #[inline]
         pub fn $as_mut_f(ref_node: &mut RefNode) -> Result<$as_mut_t<'_>> {
-            if ref_node.borrow().i_node_type == $is_t {
-                Ok(ref_node as $as_mut_t<'_>)
-            } else {
-                warn!("{}", MSG_INVALID_NODE_TYPE);
-                Err(Error::InvalidState)
-            }
+            std::convert::TryFrom::try_from(ref_node)
         }
+
+        $crate::make_is_as_functions!(@try_from_mut $is_f, $as_mut_t);
     };
 }
@@ -1,124 +1,65 @@
+use crate::io::writer::{FormatterWriter, XmlWriter};
+use crate::io::XmlWriteOptions;
 use crate::level2::convert::*;
-use crate::level2::ext::convert::{as_document_decl, RefDocumentDecl};
+use crate::level2::ext::convert::{as_document_type_decl, RefDocumentTypeDecl};
 use crate::level2::*;
 use crate::shared::syntax::*;
-use std::fmt::{Formatter, Result as FmtResult};
+use std::fmt::{Error as FmtError, Formatter, Result as FmtResult};
 
 // ------------------------------------------------------------------------------------------------
 // Public Functions
 // ------------------------------------------------------------------------------------------------
 
-pub(crate) fn fmt_element(element: RefElement<'_>, f: &mut Formatter<'_>) -> FmtResult {
-    write!(f, "{}{}", XML_ELEMENT_START_START, element.node_name())?;
-    for attr in element.attributes().values() {
-        write!(f, " {}", attr.to_string())?;
-    }
-    write!(f, "{}", XML_ELEMENT_START_END)?;
-    for child in element.child_nodes() {
-        write!(f, "{}", child.to_string())?;
-    }
-    write!(
-        f,
-        "{}{}{}",
-        XML_ELEMENT_END_START,
-        element.node_name(),
-        XML_ELEMENT_END_END
-    )
-}
-
-pub(crate) fn fmt_attribute(attribute: RefAttribute<'_>, f: &mut Formatter<'_>) -> FmtResult {
-    write!(
-        f,
-        "{}=\"{}\"",
-        attribute.node_name(),
-        attribute.value().unwrap_or_default()
-    )
-}
-
-pub(crate) fn fmt_text(character_data: RefCharacterData<'_>, f: &mut Formatter<'_>) -> FmtResult {
-    match character_data.data() {
-        None => Ok(()),
-        Some(data) => write!(f, "{}", data),
-    }
-}
-
-pub(crate) fn fmt_cdata(character_data: RefCharacterData<'_>, f: &mut Formatter<'_>) -> FmtResult {
-    match character_data.data() {
-        None => Ok(()),
-        Some(data) => write!(f, "{} {} {}", XML_CDATA_START, data, XML_CDATA_END),
-    }
-}
-
-pub(crate) fn fmt_processing_instruction(
-    pi: RefProcessingInstruction<'_>,
-    f: &mut Formatter<'_>,
-) -> FmtResult {
-    match pi.data() {
-        None => write!(f, "{}{}{}", XML_PI_START, pi.target(), XML_PI_END),
-        Some(data) => write!(f, "{}{} {}{}", XML_PI_START, pi.target(), data, XML_PI_END),
-    }
-}
-
-pub(crate) fn fmt_comment(
-    character_data: RefCharacterData<'_>,
+pub(crate) fn fmt_document_type(
+    doc_type: RefDocumentTypeDecl<'_>,
     f: &mut Formatter<'_>,
 ) -> FmtResult {
-    match character_data.data() {
-        None => Ok(()),
-        Some(data) => write!(f, "{}{}{}", XML_COMMENT_START, data, XML_COMMENT_END),
-    }
-}
-
-pub(crate) fn fmt_document(document: RefDocumentDecl<'_>, f: &mut Formatter<'_>) -> FmtResult {
-    if let Some(xml_declaration) = &document.xml_declaration() {
-        write!(f, "{}", xml_declaration)?;
-    }
-    if let Some(doc_type) = &document.doc_type() {
-        write!(f, "{}", doc_type)?;
-    }
-    for child in document.child_nodes() {
-        write!(f, "{}", child.to_string())?;
-    }
-    Ok(())
-}
-
-pub(crate) fn fmt_document_type(doc_type: RefDocumentType<'_>, f: &mut Formatter<'_>) -> FmtResult {
     write!(f, "{} {}", XML_DOCTYPE_START, doc_type.node_name())?;
-    if let Some(id) = &doc_type.public_id() {
-        write!(f, " {} \"{}\"", XML_DOCTYPE_PUBLIC, id)?;
-    }
-    if let Some(id) = &doc_type.system_id() {
-        write!(f, " {} \"{}\"", XML_DOCTYPE_SYSTEM, id)?;
-    }
-    if (doc_type.entities().len() + doc_type.notations().len() > 0)
-        || doc_type.internal_subset().is_some()
+    match (&doc_type.public_id(), &doc_type.system_id()) {
+        (Some(public_id), Some(system_id)) => write!(
+            f,
+            " {} \"{}\" \"{}\"",
+            XML_DOCTYPE_PUBLIC, public_id, system_id
+        )?,
+        (Some(public_id), None) => write!(f, " {} \"{}\"", XML_DOCTYPE_PUBLIC, public_id)?,
+        (None, Some(system_id)) => write!(f, " {} \"{}\"", XML_DOCTYPE_SYSTEM, system_id)?,
+        (None, None) => {}
+    }
+    if let Some(internal_subset) = doc_type.internal_subset() {
+        write!(
+            f,
+            " {}{}{}",
+            XML_DOCTYPE_ENTITY_START, internal_subset, XML_DOCTYPE_ENTITY_END
+        )?;
+    } else if doc_type.entities().len() + doc_type.notations().len() > 0
+        || !doc_type.element_decls().is_empty()
+        || !doc_type.attribute_decls().is_empty()
     {
-        write!(f, "{}", XML_DOCTYPE_ENTITY_START)?;
-        for (_, entity) in doc_type.entities() {
+        //
+        // The maps are unordered, so sort by name for a stable output.
+        //
+        let mut entities: Vec<(Name, RefNode)> = doc_type.entities().into_iter().collect();
+        entities.sort_by(|lhs, rhs| lhs.0.cmp(&rhs.0));
+        let mut notations: Vec<(Name, RefNode)> = doc_type.notations().into_iter().collect();
+        notations.sort_by(|lhs, rhs| lhs.0.cmp(&rhs.0));
+        write!(f, " {}", XML_DOCTYPE_ENTITY_START)?;
+        for (_, entity) in entities {
             write!(f, "{}", entity)?;
         }
-        for (_, notation) in doc_type.notations() {
+        for (_, notation) in notations {
             write!(f, "{}", notation)?;
         }
-        if let Some(internal_subset) = doc_type.internal_subset() {
-            write!(f, "{}", internal_subset)?;
+        for element_decl in doc_type.element_decls() {
+            write!(f, "{}", element_decl)?;
+        }
+        for attribute_decl in doc_type.attribute_decls() {
+            write!(f, "{}", attribute_decl)?;
         }
         write!(f, "{}", XML_DOCTYPE_ENTITY_END)?;
     }
     write!(f, "{}", XML_DOCTYPE_END)
 }
 
-pub(crate) fn fmt_document_fragment(
-    fragment: RefDocumentFragment<'_>,
-    f: &mut Formatter<'_>,
-) -> FmtResult {
-    write!(f, "{}{} ", XML_CDATA_START, fragment.node_name())?;
-    for child in fragment.child_nodes() {
-        write!(f, "{}", child.to_string())?;
-    }
-    write!(f, "{}", XML_CDATA_END)
-}
-
 pub(crate) fn fmt_entity(entity: RefEntity<'_>, f: &mut Formatter<'_>) -> FmtResult {
     write!(f, "{} {}", XML_ENTITY_START, entity.node_name())?;
     if entity.public_id().is_none() && entity.system_id().is_none() {
@@ -132,24 +73,11 @@ pub(crate) fn fmt_entity(entity: RefEntity<'_>, f: &mut Formatter<'_>) -> FmtRes
         write!(f, " {} \"{}\"", XML_DOCTYPE_SYSTEM, system_id)?;
     }
     if let Some(entity_name) = entity.notation_name() {
-        write!(f, " {}", entity_name)?;
+        write!(f, " {} {}", XML_ENTITY_NOTATION, entity_name)?;
     }
     write!(f, "{}", XML_ENTITY_END)
 }
 
-pub(crate) fn fmt_entity_reference(
-    entity_ref: RefEntityReference<'_>,
-    f: &mut Formatter<'_>,
-) -> FmtResult {
-    write!(
-        f,
-        "{}{}{}",
-        XML_ENTITYREF_START,
-        entity_ref.node_name(),
-        XML_ENTITYREF_END
-    )
-}
-
 pub(crate) fn fmt_notation(notation: RefNotation<'_>, f: &mut Formatter<'_>) -> FmtResult {
     write!(f, "{} {}", XML_NOTATION_START, notation.node_name())?;
     if let Some(public_id) = notation.public_id() {
@@ -164,20 +92,23 @@ pub(crate) fn fmt_notation(notation: RefNotation<'_>, f: &mut Formatter<'_>) ->
 }
 
 pub(crate) fn fmt_node(node: &RefNode, f: &mut Formatter<'_>) -> FmtResult {
+    //
+    // `Display` has no way to report an illegal character, so they are written as references.
+    //
+    let options = XmlWriteOptions::default().with_invalid_chars(InvalidCharHandling::Escape);
+    XmlWriter::new(FormatterWriter(f), &options)
+        .write(node)
+        .map_err(|_| FmtError)
+}
+
+//
+// Format the node types that `XmlWriter` does not write itself.
+//
+pub(crate) fn fmt_declaration(node: &RefNode, f: &mut Formatter<'_>) -> FmtResult {
     match node.node_type() {
-        NodeType::Element => fmt_element(as_element(node).unwrap(), f),
-        NodeType::Attribute => fmt_attribute(as_attribute(node).unwrap(), f),
-        NodeType::Text => fmt_text(as_character_data(node).unwrap(), f),
-        NodeType::CData => fmt_cdata(as_character_data(node).unwrap(), f),
-        NodeType::ProcessingInstruction => {
-            fmt_processing_instruction(as_processing_instruction(node).unwrap(), f)
-        }
-        NodeType::Comment => fmt_comment(as_character_data(node).unwrap(), f),
-        NodeType::Document => fmt_document(as_document_decl(node).unwrap(), f),
-        NodeType::DocumentType => fmt_document_type(as_document_type(node).unwrap(), f),
-        NodeType::DocumentFragment => fmt_document_fragment(as_document_fragment(node).unwrap(), f),
+        NodeType::DocumentType => fmt_document_type(as_document_type_decl(node).unwrap(), f),
         NodeType::Entity => fmt_entity(as_entity(node).unwrap(), f),
-        NodeType::EntityReference => fmt_entity_reference(as_entity_reference(node).unwrap(), f),
         NodeType::Notation => fmt_notation(as_notation(node).unwrap(), f),
+        _ => Ok(()),
     }
 }
@@ -19,8 +19,12 @@ use std::result::Result as StdResult;
 /// implementation has become unstable). In general, DOM methods return specific error values in
 /// ordinary processing situation, such as out-of-bound errors when using `NodeList`.
 ///
+/// The enumeration is non-exhaustive, so that exceptions introduced by later DOM specifications
+/// may be added; matches on it must include a wildcard arm.
+///
 #[derive(Clone, Debug, PartialEq)]
 #[repr(u16)]
+#[non_exhaustive]
 pub enum Error {
     /// If index or size is negative, or greater than the allowed value
     IndexSize = 1,
@@ -58,6 +62,28 @@ pub enum Error {
     /// If a parameter or an operation is not supported by the underlying object (introduced in
     /// DOM Level 2)
     InvalidAccess,
+    /// If the container of a boundary-point of a `Range` is being set to either a node of an
+    /// invalid type or a node with an ancestor of an invalid type (introduced in DOM Level 2
+    /// Range as `RangeException.INVALID_NODE_TYPE_ERR`, this is the code assigned by DOM4)
+    InvalidNodeType = 24,
+    /// If an XPath expression is not syntactically legal, or uses an unknown function
+    /// (introduced in DOM Level 3 XPath as `XPathException.INVALID_EXPRESSION_ERR`)
+    InvalidExpression = 51,
+    /// If an XPath expression cannot be converted to, or its operands are not of, the required
+    /// type (introduced in DOM Level 3 XPath as `XPathException.TYPE_ERR`)
+    XPathType,
+    /// If a node is modified while it is already borrowed, for example by a reference held across
+    /// the call (this is not a DOM exception)
+    Borrowed,
+    /// If copying the replacement tree of an entity would add more nodes than the document's
+    /// expansion limits allow (this is not a DOM exception)
+    EntityExpansionSize,
+    /// If copying the replacement tree of an entity would nest entity references more deeply than
+    /// the document's expansion limits allow (this is not a DOM exception)
+    EntityExpansionDepth,
+    /// If the replacement tree of an entity contains a reference to the same entity, directly or
+    /// through other entities (this is not a DOM exception)
+    EntityReferenceLoop,
 }
 
 ///
@@ -89,11 +115,86 @@ pub(crate) const MSG_INVALID_NAME: &str = "The provided value could not be parse
 ///
 pub(crate) const MSG_NO_PARENT_NODE: &str = "This node is missing a `parent_node` value.";
 ///
-/// Error message: "Cannot append or insert a child node created in a different document."
+/// Error message: "Cannot add a node created in a different document, use `Document::adopt_node`
+/// or `Document::import_node` first."
 ///
-#[allow(dead_code)]
 pub(crate) const MSG_WRONG_DOCUMENT: &str =
-    "Cannot append or insert a child node created in a different document.";
+    "Cannot add a node created in a different document, use `Document::adopt_node` or `Document::import_node` first.";
+///
+/// Error message: "The child you tried to add is not valid for this parent."
+///
+pub(crate) const MSG_INVALID_CHILD: &str =
+    "The child you tried to add is not valid for this parent.";
+///
+/// Error message: "Cannot add a node as a child of itself, or of one of its descendants."
+///
+pub(crate) const MSG_CYCLIC_CHILD: &str =
+    "Cannot add a node as a child of itself, or of one of its descendants.";
+///
+/// Error message: "Cannot add more than one element to a document."
+///
+pub(crate) const MSG_SECOND_ROOT: &str = "Cannot add more than one element to a document.";
+///
+/// Error message: "Only XML versions 1.0 and 1.1 are supported."
+///
+pub(crate) const MSG_UNSUPPORTED_XML_VERSION: &str = "Only XML versions 1.0 and 1.1 are supported.";
+///
+/// Error message: "Cannot add more than one document type to a document."
+///
+pub(crate) const MSG_SECOND_DOCUMENT_TYPE: &str =
+    "Cannot add more than one document type to a document.";
+///
+/// Error message: "The document type must come before the document element."
+///
+pub(crate) const MSG_MISPLACED_DOCUMENT_TYPE: &str =
+    "The document type must come before the document element.";
+///
+/// Error message: "The document type is already used by another document."
+///
+pub(crate) const MSG_DOCUMENT_TYPE_IN_USE: &str =
+    "The document type is already used by another document.";
+///
+/// Error message: "The processing instruction target 'xml' (in any case) is reserved."
+///
+pub(crate) const MSG_RESERVED_PI_TARGET: &str =
+    "The processing instruction target 'xml' (in any case) is reserved.";
+///
+/// Error message: "Processing instruction data may not contain '?>'."
+///
+pub(crate) const MSG_INVALID_PI_DATA: &str = "Processing instruction data may not contain '?>'.";
+///
+/// Error message: "Comment data may not contain '--', or end with '-'."
+///
+pub(crate) const MSG_INVALID_COMMENT_DATA: &str =
+    "Comment data may not contain '--', or end with '-'.";
+///
+/// Error message: "CDATA section data may not contain ']]>'."
+///
+pub(crate) const MSG_INVALID_CDATA_DATA: &str = "CDATA section data may not contain ']]>'.";
+///
+/// Error message: "The string contains a character that is not legal in XML 1.0."
+///
+pub(crate) const MSG_INVALID_XML_CHARACTER: &str =
+    "The string contains a character that is not legal in XML 1.0.";
+///
+/// Error message: "The XML content could not be parsed as a fragment."
+///
+#[cfg(feature = "quick_parser")]
+pub(crate) const MSG_INVALID_FRAGMENT: &str = "The XML content could not be parsed as a fragment.";
+///
+/// Error message: "A prefix may not be set on a node without a namespace URI."
+///
+pub(crate) const MSG_PREFIX_WITHOUT_NAMESPACE: &str =
+    "A prefix may not be set on a node without a namespace URI.";
+///
+/// Error message: "This node is read-only and cannot be modified."
+///
+pub(crate) const MSG_READ_ONLY: &str = "This node is read-only and cannot be modified.";
+///
+/// Error message: "This attribute is owned by another element, remove or clone it first."
+///
+pub(crate) const MSG_ATTRIBUTE_IN_USE: &str =
+    "This attribute is owned by another element, remove or clone it first.";
 ///
 /// Error message: "Either `offset` or `count` invalid for string operation."
 ///
@@ -103,18 +204,131 @@ pub(crate) const MSG_INDEX_ERROR: &str = "Either `offset` or `count` invalid for
 ///
 pub(crate) const MSG_WEAK_REF: &str = "Could not upgrade a weak reference.";
 ///
-/// Error message: "Violation of `xml:id` §4, attempt to insert duplicate ID value."
+/// Error message: "The node is already borrowed, and cannot be borrowed again for this operation."
+///
+#[cfg(not(feature = "sync"))]
+pub(crate) const MSG_ALREADY_BORROWED: &str =
+    "The node is already borrowed, and cannot be borrowed again for this operation.";
+///
+/// Error message: "No mutation listener with this identifier is registered with the document."
+///
+pub(crate) const MSG_LISTENER_NOT_FOUND: &str =
+    "No mutation listener with this identifier is registered with the document.";
+///
+/// Error message: "The XPath expression is not valid."
+///
+pub(crate) const MSG_INVALID_EXPRESSION: &str = "The XPath expression is not valid.";
 ///
-pub(crate) const MSG_DUPLICATE_ID: &str =
-    "Violation of `xml:id` §4, attempt to insert duplicate ID value.";
+/// Error message: "The XPath expression, or one of its operands, is not of the required type."
+///
+pub(crate) const MSG_XPATH_TYPE: &str =
+    "The XPath expression, or one of its operands, is not of the required type.";
+///
+/// Error message: "The XPath expression uses a namespace prefix that has not been declared."
+///
+pub(crate) const MSG_UNDECLARED_PREFIX: &str =
+    "The XPath expression uses a namespace prefix that has not been declared.";
+///
+/// Error message: "The element path is not valid."
+///
+pub(crate) const MSG_INVALID_PATH: &str = "The element path is not valid.";
+///
+/// Error message: "The selector is not valid."
+///
+pub(crate) const MSG_INVALID_SELECTOR: &str = "The selector is not valid.";
+///
+/// Error message: "Pseudo-classes, pseudo-elements, class selectors, and sibling combinators are
+/// not supported."
+///
+pub(crate) const MSG_UNSUPPORTED_SELECTOR: &str =
+    "Pseudo-classes, pseudo-elements, class selectors, and sibling combinators are not supported.";
+///
+/// Error message: "The string contains an entity or character reference that is not valid."
+///
+pub(crate) const MSG_INVALID_REFERENCE: &str =
+    "The string contains an entity or character reference that is not valid.";
+///
+/// Error message: "The value is not the code of a node type."
+///
+pub(crate) const MSG_INVALID_NODE_TYPE_CODE: &str = "The value is not the code of a node type.";
+///
+/// Error message: "A token may not be empty."
+///
+pub(crate) const MSG_EMPTY_TOKEN: &str = "A token may not be empty.";
+///
+/// Error message: "A token may not contain white space."
+///
+pub(crate) const MSG_INVALID_TOKEN: &str = "A token may not contain white space.";
+///
+/// Error message: "The provided value could not be parsed into a content specification."
+///
+pub(crate) const MSG_INVALID_CONTENT_SPEC: &str =
+    "The provided value could not be parsed into a content specification.";
+///
+/// Error message: "Expanding this entity would add more nodes than the expansion limits allow."
+///
+pub(crate) const MSG_ENTITY_EXPANSION_SIZE: &str =
+    "Expanding this entity would add more nodes than the expansion limits allow.";
+///
+/// Error message: "Expanding this entity would nest entity references more deeply than the
+/// expansion limits allow."
+///
+pub(crate) const MSG_ENTITY_EXPANSION_DEPTH: &str =
+    "Expanding this entity would nest entity references more deeply than the expansion limits allow.";
+///
+/// Error message: "This entity refers to itself, directly or through other entities."
+///
+pub(crate) const MSG_ENTITY_REFERENCE_LOOP: &str =
+    "This entity refers to itself, directly or through other entities.";
+///
+/// Error message: "The `xi:include` element is not valid."
+///
+#[cfg(feature = "xinclude")]
+pub(crate) const MSG_INVALID_XINCLUDE: &str = "The `xi:include` element is not valid.";
+///
+/// Error message: "The `xi:include` element includes a resource that is already being included."
+///
+#[cfg(feature = "xinclude")]
+pub(crate) const MSG_XINCLUDE_LOOP: &str =
+    "The `xi:include` element includes a resource that is already being included.";
+///
+/// Error message: "The resource of an `xi:include` element could not be included, and there is no
+/// fallback."
+///
+#[cfg(feature = "xinclude")]
+pub(crate) const MSG_XINCLUDE_NOT_FOUND: &str =
+    "The resource of an `xi:include` element could not be included, and there is no fallback.";
+///
+/// Error message: "The resource of an `xi:include` element is not well-formed XML."
+///
+#[cfg(feature = "xinclude")]
+pub(crate) const MSG_XINCLUDE_NOT_WELL_FORMED: &str =
+    "The resource of an `xi:include` element is not well-formed XML.";
+///
+/// Error message: "The JSON value does not follow the selected convention."
+///
+#[cfg(feature = "json")]
+pub(crate) const MSG_INVALID_JSON: &str = "The JSON value does not follow the selected convention.";
 
 // ------------------------------------------------------------------------------------------------
 // Implementations
 // ------------------------------------------------------------------------------------------------
 
+impl Error {
+    ///
+    /// Returns the `unsigned short` code of the exception, as defined by the specification that
+    /// introduced it; for example `Error::Namespace.code()` returns `14`, the value of
+    /// `NAMESPACE_ERR`. `Error::Borrowed`, and the entity expansion errors, are not DOM
+    /// exceptions and return implementation defined codes.
+    ///
+    pub fn code(&self) -> u16 {
+        self.clone() as u16
+    }
+}
+
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", match self {
+        write!(f, "{} (code {})", match self {
             Error::IndexSize => "Either `index` or `size` is negative, or greater than the allowed value",
             Error::StringSize => "The specified range of text does not fit into a DOMString",
             Error::HierarchyRequest => "An attempt insert a node somewhere it doesn't belong",
@@ -130,14 +344,21 @@ impl Display for Error {
             Error::InvalidModification => "An attempt was made to modify the type of the underlying object",
             Error::Namespace => "An attempt was made to create or change an object in a way which is incorrect with regard to namespaces",
             Error::InvalidAccess => "A parameter or an operation is not supported by the underlying object",
-        })
+            Error::InvalidNodeType => "An attempt was made to set a range boundary-point in a node of an invalid type",
+            Error::InvalidExpression => "The expression is not syntactically legal, or uses an unknown function",
+            Error::XPathType => "The expression cannot be converted to, or its operands are not of, the required type",
+            Error::Borrowed => "An attempt was made to use a node while it is already borrowed",
+            Error::EntityExpansionSize => "Expanding an entity would add more nodes than allowed",
+            Error::EntityExpansionDepth => "Expanding an entity would nest entity references more deeply than allowed",
+            Error::EntityReferenceLoop => "An entity refers to itself, directly or through other entities",
+        }, self.code())
     }
 }
 
 impl std::error::Error for Error {}
 
-impl<T> Into<Result<T>> for Error {
-    fn into(self) -> Result<T> {
-        Err(self)
+impl<T> From<Error> for Result<T> {
+    fn from(val: Error) -> Self {
+        Err(val)
     }
 }
@@ -1,10 +1,13 @@
 use crate::shared::error::*;
 use crate::shared::syntax::*;
 use crate::shared::text::is_xml_name;
+use std::cmp::Ordering;
 use std::convert::TryFrom;
-use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+use std::hash::{Hash, Hasher};
 use std::result::Result as StdResult;
 use std::str::{from_utf8, FromStr};
+use std::sync::{Arc, OnceLock};
 
 // ------------------------------------------------------------------------------------------------
 // Public Types
@@ -55,11 +58,33 @@ use std::str::{from_utf8, FromStr};
 /// >   "http://www.w3.org/2000/xmlns/", or if this node is an attribute and the qualifiedName of
 /// >   this node is "xmlns".
 ///
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+/// # Implementation
+///
+/// The parts of a name are shared, not copied, when a name is cloned; and a document interns the
+/// names of the nodes it creates so that every node with the same name shares one copy. Names
+/// that share their parts are compared without comparing strings.
+///
+#[derive(Clone)]
 pub struct Name {
-    pub(crate) namespace_uri: Option<String>,
-    pub(crate) prefix: Option<String>,
-    pub(crate) local_name: String,
+    parts: Arc<NameParts>,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct NameParts {
+    namespace_uri: Option<String>,
+    prefix: Option<String>,
+    local_name: String,
+}
+
+//
+// The form in which a `Name` is serialized with serde, a qualified name and any namespace URI.
+//
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SerdeName {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    namespace_uri: Option<String>,
+    name: String,
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -68,13 +93,61 @@ pub struct Name {
 
 impl Display for Name {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
-        match &self.prefix {
-            Some(prefix) => write!(f, "{}{}{}", prefix, XML_NS_SEPARATOR, self.local_name),
-            None => write!(f, "{}", self.local_name),
+        match &self.parts.prefix {
+            Some(prefix) => write!(f, "{}{}{}", prefix, XML_NS_SEPARATOR, self.parts.local_name),
+            None => write!(f, "{}", self.parts.local_name),
         }
     }
 }
 
+impl Debug for Name {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("Name")
+            .field("namespace_uri", &self.parts.namespace_uri)
+            .field("prefix", &self.parts.prefix)
+            .field("local_name", &self.parts.local_name)
+            .finish()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl PartialEq for Name {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.parts, &other.parts) || self.parts == other.parts
+    }
+}
+
+impl Eq for Name {}
+
+impl Hash for Name {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.parts.hash(state)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl PartialOrd for Name {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Name {
+    ///
+    /// Names are ordered by prefix (un-prefixed names first), then local name, and finally
+    /// namespace URI; so sorting names groups them by prefix as they would appear in serialized
+    /// output.
+    ///
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.prefix()
+            .cmp(other.prefix())
+            .then_with(|| self.local_name().cmp(other.local_name()))
+            .then_with(|| self.namespace_uri().cmp(other.namespace_uri()))
+    }
+}
+
 // ------------------------------------------------------------------------------------------------
 
 impl FromStr for Name {
@@ -89,10 +162,10 @@ impl FromStr for Name {
                 .map(|s| s.to_string())
                 .collect::<Vec<String>>();
             match parts.len() {
-                1 => Name::new(Name::check_part(parts.get(0).unwrap())?, None, None),
+                1 => Name::new(Name::check_part(parts.first().unwrap())?, None, None),
                 2 => Name::new(
                     Name::check_part(parts.get(1).unwrap())?,
-                    Some(Name::check_part(parts.get(0).unwrap())?),
+                    Some(Name::check_part(parts.first().unwrap())?),
                     None,
                 ),
                 _ => Err(Error::Syntax),
@@ -103,6 +176,34 @@ impl FromStr for Name {
 
 // ------------------------------------------------------------------------------------------------
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for Name {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> StdResult<S::Ok, S::Error> {
+        SerdeName {
+            namespace_uri: self.namespace_uri().clone(),
+            name: self.to_string(),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Name {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> StdResult<Self, D::Error> {
+        let SerdeName {
+            namespace_uri,
+            name,
+        } = SerdeName::deserialize(deserializer)?;
+        match namespace_uri {
+            None => Name::from_str(&name),
+            Some(namespace_uri) => Name::new_ns(&namespace_uri, &name),
+        }
+        .map_err(serde::de::Error::custom)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
 impl TryFrom<&[u8]> for Name {
     type Error = Error;
 
@@ -120,21 +221,98 @@ impl TryFrom<&[u8]> for Name {
 // ------------------------------------------------------------------------------------------------
 
 impl Name {
+    /// The namespace URI bound to the `xml` prefix.
+    pub const XML_NS_URI: &'static str = XML_NS_URI;
+    /// The namespace URI of namespace declaration attributes.
+    pub const XMLNS_NS_URI: &'static str = XMLNS_NS_URI;
+    /// The qualified name of the default namespace declaration attribute.
+    pub const XMLNS: &'static str = "xmlns";
+    /// The qualified name of the `xml:base` attribute.
+    pub const XML_BASE: &'static str = "xml:base";
+    /// The qualified name of the `xml:id` attribute.
+    pub const XML_ID: &'static str = "xml:id";
+    /// The qualified name of the `xml:lang` attribute.
+    pub const XML_LANG: &'static str = "xml:lang";
+    /// The qualified name of the `xml:space` attribute.
+    pub const XML_SPACE: &'static str = "xml:space";
+
     ///
     /// Construct a new `Name` from the specified namespace URI and qualified name.
     ///
-    /// Note, errors include a malformed URI, or malformed prefix or local name.
+    /// Note, errors include a malformed URI, or malformed prefix or local name. A qualified name
+    /// with illegal characters returns `Error::InvalidCharacter`, one that is not a well-formed
+    /// `QName` (such as `a:b:c` or `a:`) returns `Error::Namespace`, as does a prefix without a
+    /// namespace URI or a misuse of the `xml` and `xmlns` prefixes.
     ///
     pub fn new_ns(namespace_uri: &str, qualified_name: &str) -> Result<Self> {
-        let mut parsed = Name::from_str(qualified_name)?;
-        parsed.namespace_uri = Some(Self::check_namespace_uri(
-            namespace_uri,
-            &parsed.prefix,
-            &parsed.local_name,
-        )?);
+        let mut parsed = match Name::from_str(qualified_name) {
+            Err(Error::Syntax) if !qualified_name.is_empty() => {
+                warn!("qualified name is not a well-formed QName");
+                return Err(Error::Namespace);
+            }
+            parsed => parsed?,
+        };
+        let namespace_uri =
+            Self::check_namespace_uri(namespace_uri, parsed.prefix(), parsed.local_name())?;
+        Arc::make_mut(&mut parsed.parts).namespace_uri = Some(namespace_uri);
         Ok(parsed)
     }
 
+    ///
+    /// Returns `true` if `value` would be accepted by `Name::from_str`, that is it is either a
+    /// local name, or a prefix and local name separated by `':'`, where each part is a valid XML
+    /// 1.0 (Fifth Edition) `Name` that does not itself contain `':'`.
+    ///
+    /// # Specification
+    ///
+    /// ```ebnf
+    /// NameStartChar   ::=  ":" | [A-Z] | "_" | [a-z] | [#xC0-#xD6] | [#xD8-#xF6] | [#xF8-#x2FF] |
+    ///                      [#x370-#x37D] | [#x37F-#x1FFF] | [#x200C-#x200D] | [#x2070-#x218F] |
+    ///                      [#x2C00-#x2FEF] | [#x3001-#xD7FF] | [#xF900-#xFDCF] | [#xFDF0-#xFFFD] |
+    ///                      [#x10000-#xEFFFF]
+    /// NameChar        ::=  NameStartChar | "-" | "." | [0-9] | #xB7 | [#x0300-#x036F] |
+    ///                      [#x203F-#x2040]
+    /// Name            ::=  NameStartChar (NameChar)*
+    /// ```
+    ///
+    pub fn is_valid(value: &str) -> bool {
+        let mut parts = value.split(XML_NS_SEPARATOR);
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(local), None, None) => is_xml_name(local),
+            (Some(prefix), Some(local), None) => is_xml_name(prefix) && is_xml_name(local),
+            _ => false,
+        }
+    }
+
+    ///
+    /// Construct a new `Name` from a qualified name **without** any validation; the value is
+    /// split into prefix and local name at the first `':'`, if any.
+    ///
+    /// This is intended for callers that knowingly need to represent names that are not valid
+    /// XML, such as when comparing against the output of another, more lenient, tool. Nothing
+    /// prevents such a name from being serialized, and the result may not be well-formed XML.
+    ///
+    pub fn new_unchecked(qualified_name: &str) -> Self {
+        match qualified_name.split_once(XML_NS_SEPARATOR) {
+            Some((prefix, local_name)) if !prefix.is_empty() => {
+                Self::from_parts(None, Some(prefix.to_string()), local_name.to_string())
+            }
+            _ => Self::from_parts(None, None, qualified_name.to_string()),
+        }
+    }
+
+    ///
+    /// Construct a new `Name` from a namespace URI and qualified name **without** any validation,
+    /// as `new_unchecked`; an empty namespace URI is treated as no namespace.
+    ///
+    pub(crate) fn new_ns_unchecked(namespace_uri: &str, qualified_name: &str) -> Self {
+        let mut name = Self::new_unchecked(qualified_name);
+        if !namespace_uri.is_empty() {
+            Arc::make_mut(&mut name.parts).namespace_uri = Some(namespace_uri.to_string());
+        }
+        name
+    }
+
     ///
     /// Construct a new `Name` from any combination of local name, prefix, and namespace URI.
     ///
@@ -161,11 +339,29 @@ impl Name {
                 return Err(Error::Syntax);
             }
         }
-        Ok(Self {
-            namespace_uri,
-            prefix,
-            local_name,
-        })
+        Ok(Self::from_parts(namespace_uri, prefix, local_name))
+    }
+
+    //
+    // The reserved names are created once, and then shared by every node that uses them.
+    //
+    fn reserved(name: &'static OnceLock<Name>, local_name: &str) -> Self {
+        name.get_or_init(|| Self::from_parts(None, None, local_name.to_string()))
+            .clone()
+    }
+
+    fn from_parts(
+        namespace_uri: Option<String>,
+        prefix: Option<String>,
+        local_name: String,
+    ) -> Self {
+        Self {
+            parts: Arc::new(NameParts {
+                namespace_uri,
+                prefix,
+                local_name,
+            }),
+        }
     }
 
     fn check_part(part: &str) -> Result<String> {
@@ -184,22 +380,31 @@ impl Name {
         local: &str,
     ) -> Result<String> {
         if namespace_uri.is_empty() {
-            Err(Error::Syntax)
-        } else {
-            if let Some(prefix) = prefix {
-                if (prefix == XML_NS_ATTRIBUTE && namespace_uri != XML_NS_URI)
-                    || (prefix == XMLNS_NS_ATTRIBUTE && namespace_uri != XMLNS_NS_URI)
-                {
-                    return Err(Error::Namespace);
-                }
-            }
-            if (local == XML_NS_ATTRIBUTE && namespace_uri != XML_NS_URI)
-                || (local == XMLNS_NS_ATTRIBUTE && namespace_uri != XMLNS_NS_URI)
-            {
+            return if prefix.is_some() {
+                warn!("a prefixed name requires a namespace URI");
                 Err(Error::Namespace)
             } else {
-                Ok(namespace_uri.to_string())
-            }
+                Err(Error::Syntax)
+            };
+        }
+        //
+        // Either the qualified name is `xmlns` or the prefix is `xmlns`, but only for the XMLNS
+        // namespace; and that namespace may not be used by any other name. Similarly the `xml`
+        // prefix is bound to, and only to, the XML namespace.
+        //
+        let is_xmlns = match prefix {
+            None => local == XMLNS_NS_ATTRIBUTE,
+            Some(prefix) => prefix == XMLNS_NS_ATTRIBUTE,
+        };
+        let is_xml = prefix.as_deref() == Some(XML_NS_ATTRIBUTE);
+        if is_xmlns != (namespace_uri == XMLNS_NS_URI)
+            || is_xml != (namespace_uri == XML_NS_URI)
+            || (local == XML_NS_ATTRIBUTE && namespace_uri != XML_NS_URI)
+            || (local == XMLNS_NS_ATTRIBUTE && namespace_uri != XMLNS_NS_URI)
+        {
+            Err(Error::Namespace)
+        } else {
+            Ok(namespace_uri.to_string())
         }
     }
 
@@ -207,77 +412,54 @@ impl Name {
     /// Return the reserved name for `CDATA` section nodes
     ///
     pub fn for_cdata() -> Self {
-        Self {
-            namespace_uri: None,
-            prefix: None,
-            local_name: XML_NAME_CDATA.to_string(),
-        }
+        static NAME: OnceLock<Name> = OnceLock::new();
+        Self::reserved(&NAME, XML_NAME_CDATA)
     }
 
     ///
     /// Return the reserved name for `Comment` nodes
     ///
     pub fn for_comment() -> Self {
-        Self {
-            namespace_uri: None,
-            prefix: None,
-            local_name: XML_NAME_COMMENT.to_string(),
-        }
+        static NAME: OnceLock<Name> = OnceLock::new();
+        Self::reserved(&NAME, XML_NAME_COMMENT)
     }
 
     ///
     /// Return the reserved name for `Document` nodes
     ///
     pub fn for_document() -> Self {
-        Self {
-            namespace_uri: None,
-            prefix: None,
-            local_name: XML_NAME_DOCUMENT.to_string(),
-        }
+        static NAME: OnceLock<Name> = OnceLock::new();
+        Self::reserved(&NAME, XML_NAME_DOCUMENT)
     }
 
     ///
     /// Return the reserved name for `Document` nodes
     ///
     pub fn for_document_fragment() -> Self {
-        Self {
-            namespace_uri: None,
-            prefix: None,
-            local_name: XML_NAME_DOCUMENT_FRAGMENT.to_string(),
-        }
+        static NAME: OnceLock<Name> = OnceLock::new();
+        Self::reserved(&NAME, XML_NAME_DOCUMENT_FRAGMENT)
     }
 
     ///
     /// Return the reserved name for `Text` nodes
     ///
     pub fn for_text() -> Self {
-        Self {
-            namespace_uri: None,
-            prefix: None,
-            local_name: XML_NAME_TEXT.to_string(),
-        }
+        static NAME: OnceLock<Name> = OnceLock::new();
+        Self::reserved(&NAME, XML_NAME_TEXT)
     }
 
     ///
     /// Return the reserved name for `DocumentType` `public_id` attribute
     ///
     pub fn for_public_id() -> Self {
-        Self {
-            namespace_uri: None,
-            prefix: None,
-            local_name: XML_DOCTYPE_PUBLIC.to_string(),
-        }
+        Self::from_parts(None, None, XML_DOCTYPE_PUBLIC.to_string())
     }
 
     ///
     /// Return the reserved name for `DocumentType` `system_id` attribute
     ///
     pub fn for_system_id() -> Self {
-        Self {
-            namespace_uri: None,
-            prefix: None,
-            local_name: XML_DOCTYPE_SYSTEM.to_string(),
-        }
+        Self::from_parts(None, None, XML_DOCTYPE_SYSTEM.to_string())
     }
 
     ///
@@ -285,11 +467,7 @@ impl Name {
     ///
     #[allow(dead_code)]
     pub(crate) fn for_null() -> Self {
-        Self {
-            namespace_uri: None,
-            prefix: None,
-            local_name: "null".to_string(),
-        }
+        Self::from_parts(None, None, "null".to_string())
     }
 
     ///
@@ -298,9 +476,9 @@ impl Name {
     pub fn is_namespace_attribute(&self) -> bool {
         let xmlns_ns = Some(XMLNS_NS_URI.to_string());
         let xmlns_attribute = XMLNS_NS_ATTRIBUTE.to_string();
-        self.namespace_uri == xmlns_ns
-            && ((self.local_name == xmlns_attribute && self.prefix == None)
-                || self.prefix == Some(xmlns_attribute))
+        self.parts.namespace_uri == xmlns_ns
+            && ((self.parts.local_name == xmlns_attribute && self.parts.prefix.is_none())
+                || self.parts.prefix == Some(xmlns_attribute))
     }
 
     ///
@@ -324,14 +502,15 @@ impl Name {
             //
             // any attribute with the local_name 'id'
             //
-            self.local_name == id_attribute
+            self.parts.local_name == id_attribute
         } else {
             let xml_ns = XML_NS_URI.to_string();
             let xml_prefix = XML_NS_ATTRIBUTE.to_string();
             //
             // has to be 'xml:id', either by the prefix 'xml' or using the correct namespace
-            self.local_name == id_attribute
-                && (self.namespace_uri == Some(xml_ns) || self.prefix == Some(xml_prefix))
+            self.parts.local_name == id_attribute
+                && (self.parts.namespace_uri == Some(xml_ns)
+                    || self.parts.prefix == Some(xml_prefix))
         }
     }
 
@@ -339,39 +518,56 @@ impl Name {
     /// Construct a name for an `xml:id` attribute.
     /// ///
     pub fn for_xml_id() -> Self {
-        Self {
-            namespace_uri: Some(XML_NS_URI.to_string()),
-            prefix: Some(XML_NS_ATTRIBUTE.to_string()),
-            local_name: XML_NS_ATTR_ID.to_string(),
-        }
+        Self::from_parts(
+            Some(XML_NS_URI.to_string()),
+            Some(XML_NS_ATTRIBUTE.to_string()),
+            XML_NS_ATTR_ID.to_string(),
+        )
     }
 
     ///
     /// Return this name's namespace URI.
     ///
     pub fn namespace_uri(&self) -> &Option<String> {
-        &self.namespace_uri
+        &self.parts.namespace_uri
     }
 
     ///
     /// Return this name's local name.
     ///
     pub fn local_name(&self) -> &String {
-        &self.local_name
+        &self.parts.local_name
     }
 
     ///
     /// Return this name's prefix.
     ///
     pub fn prefix(&self) -> &Option<String> {
-        &self.prefix
+        &self.parts.prefix
+    }
+
+    ///
+    /// Return a copy of this name with the prefix replaced, or removed if `prefix` is `None`.
+    ///
+    /// Unlike [`set_prefix`](#method.set_prefix) the new prefix is validated, and if this name
+    /// has a namespace URI the same namespace rules as [`new_ns`](#method.new_ns) apply.
+    ///
+    pub fn with_prefix(&self, prefix: Option<&str>) -> Result<Self> {
+        let qualified_name = match prefix {
+            None => self.parts.local_name.clone(),
+            Some(prefix) => format!("{}{}{}", prefix, XML_NS_SEPARATOR, self.parts.local_name),
+        };
+        match &self.parts.namespace_uri {
+            None => Self::from_str(&qualified_name),
+            Some(namespace_uri) => Self::new_ns(namespace_uri, &qualified_name),
+        }
     }
 
     ///
     /// Set this name's prefix.
     ///
     pub fn set_prefix(&mut self, new_prefix: Option<&str>) -> Result<()> {
-        self.prefix = new_prefix.map(String::from);
+        Arc::make_mut(&mut self.parts).prefix = new_prefix.map(String::from);
         Ok(())
     }
 }
@@ -395,10 +591,111 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_unicode() {
+        for value in [
+            "é",
+            "ñame",
+            "名前",
+            "x:名前",
+            "Ωmega",
+            "_\u{B7}\u{300}",
+            "a\u{203F}b",
+        ] {
+            assert!(Name::is_valid(value), "{:?} should be valid", value);
+            assert!(Name::from_str(value).is_ok());
+        }
+        for value in [
+            "123 <bad>",
+            "1name",
+            "-name",
+            "\u{B7}name",
+            "\u{300}name",
+            "na\u{D7}me",
+            "na\u{2000}me",
+            "a:b:c",
+            "",
+        ] {
+            assert!(!Name::is_valid(value), "{:?} should be invalid", value);
+            assert!(Name::from_str(value).is_err());
+        }
+        assert_eq!(
+            Name::from_str("123 <bad>").err().unwrap(),
+            Error::InvalidCharacter
+        );
+    }
+
+    #[test]
+    fn test_ordering() {
+        let mut names = [
+            Name::new_ns("http://example.org/b", "b:item").unwrap(),
+            Name::from_str("zed").unwrap(),
+            Name::new_ns("http://example.org/a", "a:item").unwrap(),
+            Name::from_str("alpha").unwrap(),
+            Name::new_ns("http://example.org/a2", "a:item").unwrap(),
+        ];
+        names.sort();
+        let names: Vec<String> = names
+            .iter()
+            .map(|name| format!("{}={:?}", name, name.namespace_uri()))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "alpha=None",
+                "zed=None",
+                "a:item=Some(\"http://example.org/a\")",
+                "a:item=Some(\"http://example.org/a2\")",
+                "b:item=Some(\"http://example.org/b\")",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_with_prefix() {
+        let name = Name::new_ns("http://example.org/x", "x:hello").unwrap();
+        let renamed = name.with_prefix(Some("y")).unwrap();
+        assert_eq!(renamed.to_string(), "y:hello".to_string());
+        assert_eq!(renamed.namespace_uri(), name.namespace_uri());
+        assert_eq!(name.to_string(), "x:hello".to_string());
+
+        let renamed = name.with_prefix(None).unwrap();
+        assert_eq!(renamed.to_string(), "hello".to_string());
+
+        assert_eq!(
+            name.with_prefix(Some("xml")).err().unwrap(),
+            Error::Namespace
+        );
+        assert_eq!(
+            name.with_prefix(Some("1x")).err().unwrap(),
+            Error::InvalidCharacter
+        );
+
+        let name = Name::from_str(Name::XML_LANG).unwrap();
+        assert_eq!(name.prefix(), &Some("xml".to_string()));
+        assert_eq!(name.local_name(), "lang");
+        let name = Name::new_ns(Name::XMLNS_NS_URI, Name::XMLNS).unwrap();
+        assert!(name.is_namespace_attribute());
+        let name = Name::new_ns(Name::XML_NS_URI, Name::XML_ID).unwrap();
+        assert!(name.is_id_attribute(false));
+    }
+
+    #[test]
+    fn test_new_unchecked() {
+        let name = Name::new_unchecked("123 <bad>");
+        assert_eq!(name.local_name(), &"123 <bad>".to_string());
+        assert!(name.prefix().is_none());
+
+        let name = Name::new_unchecked("p:1:x");
+        assert_eq!(name.prefix(), &Some("p".to_string()));
+        assert_eq!(name.local_name(), &"1:x".to_string());
+        assert_eq!(name.to_string(), "p:1:x".to_string());
+    }
+
     #[test]
     fn test_parse_local() {
         let name = Name::from_str("hello").unwrap();
-        assert_eq!(name.local_name, "hello".to_string());
+        assert_eq!(name.local_name(), &"hello".to_string());
         assert!(name.prefix().is_none());
         assert!(name.namespace_uri().is_none());
     }
@@ -406,7 +703,7 @@ mod tests {
     #[test]
     fn test_parse_qualified() {
         let name = Name::from_str("x:hello").unwrap();
-        assert_eq!(name.local_name, "hello".to_string());
+        assert_eq!(name.local_name(), &"hello".to_string());
         assert_eq!(name.prefix(), &Some("x".to_string()));
         assert!(name.namespace_uri().is_none());
     }
@@ -414,7 +711,7 @@ mod tests {
     #[test]
     fn test_parse_namespaced() {
         let name = Name::new_ns("http://example.org/schema/x", "x:hello").unwrap();
-        assert_eq!(name.local_name, "hello".to_string());
+        assert_eq!(name.local_name(), &"hello".to_string());
         assert_eq!(name.prefix(), &Some("x".to_string()));
         assert_eq!(
             name.namespace_uri(),
@@ -433,10 +730,29 @@ mod tests {
         let name = Name::from_str("prefix:");
         assert_eq!(name.err().unwrap(), Error::Syntax);
 
+        let name = Name::new_ns("", "name");
+        assert_eq!(name.err().unwrap(), Error::Syntax);
+
         let name = Name::new_ns("", "prefix:name");
+        assert_eq!(name.err().unwrap(), Error::Namespace);
+
+        let name = Name::new_ns("http://example.org/schema/x", "");
         assert_eq!(name.err().unwrap(), Error::Syntax);
     }
 
+    #[test]
+    fn test_malformed_qualified_names() {
+        const NS: &str = "http://example.org/schema/x";
+        for qualified_name in [":name", "prefix:", "a:b:c", "::"] {
+            let name = Name::new_ns(NS, qualified_name);
+            assert_eq!(name.err().unwrap(), Error::Namespace);
+        }
+        for qualified_name in ["1name", "x:1name", "na me", "x:na<me"] {
+            let name = Name::new_ns(NS, qualified_name);
+            assert_eq!(name.err().unwrap(), Error::InvalidCharacter);
+        }
+    }
+
     #[test]
     fn test_xml_ns_names() {
         const RDF_NS: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
@@ -481,6 +797,12 @@ mod tests {
         let name = Name::new_ns(RDF_NS, "xmlns:rdf");
         assert_eq!(name.err().unwrap(), Error::Namespace);
 
+        let name = Name::new_ns(XMLNS_NS_URI, "rdf:p");
+        assert_eq!(name.err().unwrap(), Error::Namespace);
+
+        let name = Name::new_ns(XMLNS_NS_URI, "p");
+        assert_eq!(name.err().unwrap(), Error::Namespace);
+
         let name = Name::from_str("x:hello").unwrap();
         assert!(!name.is_namespace_attribute());
     }
@@ -1,26 +1,141 @@
-use std::cell::{Ref, RefCell, RefMut};
-use std::rc::{Rc, Weak};
+/*!
+The shared, mutable, reference used for nodes. By default this is an `Rc<RefCell<T>>`; with the
+`sync` feature it is an `Arc<RwLock<T>>`, so that nodes are `Send` and `Sync`.
+
+Note that unlike a `RefCell`, which panics, a `RwLock` will block, or deadlock, if a write lock is
+requested while a read or write lock is held on the same thread.
+
+The `try_borrow` and `try_borrow_mut` methods of `RcRefCell` return `Error::Borrowed`, rather than
+panic, if the value is already borrowed in a conflicting way. With the `sync` feature a lock held
+by another thread cannot be told apart from one held by the current thread, and so these methods
+wait for the lock, as `borrow` and `borrow_mut` do.
+*/
+
+use crate::shared::error::Result;
+#[cfg(not(feature = "sync"))]
+use crate::shared::error::{Error, MSG_ALREADY_BORROWED};
+use std::hash::{Hash, Hasher};
+
+#[cfg(not(feature = "sync"))]
+pub(crate) use std::cell::Ref as ReadGuard;
+#[cfg(not(feature = "sync"))]
+use std::cell::{RefCell as Lock, RefMut as WriteGuard};
+#[cfg(feature = "sync")]
+pub(crate) use std::sync::RwLockReadGuard as ReadGuard;
+#[cfg(feature = "sync")]
+use std::sync::{RwLock as Lock, RwLockWriteGuard as WriteGuard};
+
+#[cfg(not(feature = "sync"))]
+pub use std::rc::Rc as Shared;
+#[cfg(not(feature = "sync"))]
+pub(crate) use std::rc::Weak as WeakShared;
+#[cfg(feature = "sync")]
+pub use std::sync::Arc as Shared;
+#[cfg(feature = "sync")]
+pub(crate) use std::sync::Weak as WeakShared;
 
 // ------------------------------------------------------------------------------------------------
 // Public Types
 // ------------------------------------------------------------------------------------------------
 
+#[doc(hidden)]
+#[derive(Debug, Default)]
+pub struct SharedCell<T: ?Sized> {
+    inner: Lock<T>,
+}
+
 #[doc(hidden)]
 #[derive(Debug)]
 pub struct RcRefCell<T: Sized> {
-    inner: Rc<RefCell<T>>,
+    inner: Shared<SharedCell<T>>,
 }
 
 #[doc(hidden)]
 #[derive(Debug)]
 pub struct WeakRefCell<T: Sized> {
-    inner: Weak<RefCell<T>>,
+    inner: WeakShared<SharedCell<T>>,
 }
 
 // ------------------------------------------------------------------------------------------------
 // Implementations
 // ------------------------------------------------------------------------------------------------
 
+impl<T> SharedCell<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            inner: Lock::new(value),
+        }
+    }
+
+    #[cfg(not(feature = "sync"))]
+    pub fn into_inner(self) -> T {
+        self.inner.into_inner()
+    }
+
+    #[cfg(feature = "sync")]
+    pub fn into_inner(self) -> T {
+        self.inner
+            .into_inner()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<T: ?Sized> SharedCell<T> {
+    #[cfg(not(feature = "sync"))]
+    pub fn borrow(&self) -> ReadGuard<'_, T> {
+        self.inner.borrow()
+    }
+
+    #[cfg(feature = "sync")]
+    pub fn borrow(&self) -> ReadGuard<'_, T> {
+        self.inner
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    #[cfg(not(feature = "sync"))]
+    pub fn borrow_mut(&self) -> WriteGuard<'_, T> {
+        self.inner.borrow_mut()
+    }
+
+    #[cfg(feature = "sync")]
+    pub fn borrow_mut(&self) -> WriteGuard<'_, T> {
+        self.inner
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    #[cfg(not(feature = "sync"))]
+    pub fn try_borrow(&self) -> Option<ReadGuard<'_, T>> {
+        self.inner.try_borrow().ok()
+    }
+
+    #[cfg(feature = "sync")]
+    pub fn try_borrow(&self) -> Option<ReadGuard<'_, T>> {
+        match self.inner.try_read() {
+            Ok(guard) => Some(guard),
+            Err(std::sync::TryLockError::Poisoned(poisoned)) => Some(poisoned.into_inner()),
+            Err(std::sync::TryLockError::WouldBlock) => None,
+        }
+    }
+
+    #[cfg(not(feature = "sync"))]
+    pub fn try_borrow_mut(&self) -> Option<WriteGuard<'_, T>> {
+        self.inner.try_borrow_mut().ok()
+    }
+
+    #[cfg(feature = "sync")]
+    pub fn try_borrow_mut(&self) -> Option<WriteGuard<'_, T>> {
+        match self.inner.try_write() {
+            Ok(guard) => Some(guard),
+            Err(std::sync::TryLockError::Poisoned(poisoned)) => Some(poisoned.into_inner()),
+            Err(std::sync::TryLockError::WouldBlock) => None,
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
 impl<T> Clone for RcRefCell<T> {
     fn clone(&self) -> Self {
         Self {
@@ -32,7 +147,16 @@ impl<T> Clone for RcRefCell<T> {
 impl<T> PartialEq for RcRefCell<T> {
     fn eq(&self, other: &Self) -> bool {
         // RefNodes are equal if the two Rc point to the same RefCell.
-        Rc::ptr_eq(&self.inner, &other.inner)
+        Shared::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl<T> Eq for RcRefCell<T> {}
+
+impl<T> Hash for RcRefCell<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Consistent with `eq`, only the address of the shared value is hashed.
+        std::ptr::hash(Shared::as_ptr(&self.inner), state)
     }
 }
 
@@ -41,34 +165,60 @@ impl<T> PartialEq for RcRefCell<T> {
 impl<T> RcRefCell<T> {
     pub fn new(value: T) -> Self {
         Self {
-            inner: Rc::new(RefCell::new(value)),
+            inner: Shared::new(SharedCell::new(value)),
         }
     }
 
-    pub fn as_inner(&self) -> &Rc<RefCell<T>> {
+    pub fn as_inner(&self) -> &Shared<SharedCell<T>> {
         &self.inner
     }
 
     pub fn unwrap(self) -> T {
-        match Rc::try_unwrap(self.inner) {
-            Ok(ref_cell) => ref_cell.into_inner(),
-            _ => panic!("could not unwrap the std::rc::Rc value"),
+        match Shared::try_unwrap(self.inner) {
+            Ok(cell) => cell.into_inner(),
+            _ => panic!("could not unwrap the shared reference value"),
         }
     }
 
     pub fn downgrade(self) -> WeakRefCell<T> {
         WeakRefCell {
-            inner: Rc::downgrade(&self.inner),
+            inner: Shared::downgrade(&self.inner),
         }
     }
 
-    pub fn borrow(&self) -> Ref<'_, T> {
+    pub fn borrow(&self) -> ReadGuard<'_, T> {
         self.inner.borrow()
     }
 
-    pub fn borrow_mut(&self) -> RefMut<'_, T> {
+    pub fn borrow_mut(&self) -> WriteGuard<'_, T> {
         self.inner.borrow_mut()
     }
+
+    #[cfg(not(feature = "sync"))]
+    pub fn try_borrow(&self) -> Result<ReadGuard<'_, T>> {
+        self.inner.inner.try_borrow().map_err(|_| {
+            warn!("{}", MSG_ALREADY_BORROWED);
+            Error::Borrowed
+        })
+    }
+
+    #[cfg(feature = "sync")]
+    pub fn try_borrow(&self) -> Result<ReadGuard<'_, T>> {
+        Ok(self.inner.borrow())
+    }
+
+    #[cfg(not(feature = "sync"))]
+    pub fn try_borrow_mut(&self) -> Result<WriteGuard<'_, T>> {
+        self.inner.inner.try_borrow_mut().map_err(|_| {
+            warn!("{}", MSG_ALREADY_BORROWED);
+            Error::Borrowed
+        })
+    }
+
+    #[cfg(feature = "sync")]
+    pub fn try_borrow_mut(&self) -> Result<WriteGuard<'_, T>> {
+        Ok(self.inner.borrow_mut())
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -84,7 +234,7 @@ impl<T> Clone for WeakRefCell<T> {
 // ------------------------------------------------------------------------------------------------
 
 impl<T> WeakRefCell<T> {
-    pub fn as_inner(&self) -> &Weak<RefCell<T>> {
+    pub fn as_inner(&self) -> &WeakShared<SharedCell<T>> {
         &self.inner
     }
 
@@ -163,4 +313,21 @@ mod tests {
         assert_eq!(ref2.borrow().name, "name-2");
         assert_eq!(ref1.borrow().name, "name-2");
     }
+
+    #[test]
+    #[cfg(not(feature = "sync"))]
+    fn test_try_borrow() {
+        let ref1: NodeRef = RcRefCell::new(Node::new("name-1"));
+        {
+            let _inner = ref1.borrow();
+            assert!(ref1.try_borrow().is_ok());
+            assert_eq!(ref1.try_borrow_mut().err(), Some(Error::Borrowed));
+        }
+        {
+            let _mut_inner = ref1.borrow_mut();
+            assert_eq!(ref1.try_borrow().err(), Some(Error::Borrowed));
+            assert_eq!(ref1.try_borrow_mut().err(), Some(Error::Borrowed));
+        }
+        assert_eq!(ref1.try_borrow_mut().unwrap().name, "name-1");
+    }
 }
@@ -1,5 +1,8 @@
 #![allow(dead_code)]
 
+use crate::shared::error::{Error, Result, MSG_INVALID_XML_CHARACTER};
+use crate::shared::text::{is_xml_10_char, to_entity};
+
 // ------------------------------------------------------------------------------------------------
 // Pure Syntactic Tokens
 // ------------------------------------------------------------------------------------------------
@@ -13,6 +16,8 @@ pub(crate) const XML_PI_RESERVED: &str = "xml";
 
 pub(crate) const XML_COMMENT_START: &str = "<!--";
 pub(crate) const XML_COMMENT_END: &str = "-->";
+pub(crate) const XML_COMMENT_HYPHEN: &str = "-";
+pub(crate) const XML_COMMENT_DOUBLE_HYPHEN: &str = "--";
 
 pub(crate) const XML_CDATA_START: &str = "<![CDATA[";
 pub(crate) const XML_CDATA_END: &str = "]]>";
@@ -24,10 +29,23 @@ pub(crate) const XML_DOCTYPE_ENTITY_END: &str = "]";
 pub(crate) const XML_DOCTYPE_PUBLIC: &str = "PUBLIC";
 pub(crate) const XML_DOCTYPE_SYSTEM: &str = "SYSTEM";
 
+pub(crate) const XML_ATTLIST_START: &str = "<!ATTLIST";
+pub(crate) const XML_ATTLIST_END: &str = ">";
+pub(crate) const XML_ATTLIST_REQUIRED: &str = "#REQUIRED";
+pub(crate) const XML_ATTLIST_IMPLIED: &str = "#IMPLIED";
+pub(crate) const XML_ATTLIST_FIXED: &str = "#FIXED";
+
+pub(crate) const XML_ELEMENT_DECL_START: &str = "<!ELEMENT";
+pub(crate) const XML_ELEMENT_DECL_END: &str = ">";
+pub(crate) const XML_ELEMENT_DECL_EMPTY: &str = "EMPTY";
+pub(crate) const XML_ELEMENT_DECL_ANY: &str = "ANY";
+pub(crate) const XML_ELEMENT_DECL_PCDATA: &str = "#PCDATA";
+
 pub(crate) const XML_ELEMENT_START_START: &str = "<";
 pub(crate) const XML_ELEMENT_START_END: &str = ">";
 pub(crate) const XML_ELEMENT_END_START: &str = "</";
 pub(crate) const XML_ELEMENT_END_END: &str = ">";
+pub(crate) const XML_EMPTY_ELEMENT_END: &str = "/>";
 
 pub(crate) const XML_ENTITY_START: &str = "<!ENTITY";
 pub(crate) const XML_ENTITY_END: &str = ">";
@@ -99,9 +117,14 @@ pub(crate) const XML_NAME_TEXT: &str = "#text";
 
 pub(crate) const XML_FEATURE_CORE: &str = "Core"; // DOM Level-2 "Fundamental Interfaces"
 pub(crate) const XML_FEATURE_XML: &str = "XML"; // DOM Level-2 "Extended Interfaces"
+pub(crate) const XML_FEATURE_TRAVERSAL: &str = "Traversal"; // DOM Level-2 Traversal
+pub(crate) const XML_FEATURE_RANGE: &str = "Range"; // DOM Level-2 Range
+pub(crate) const XML_FEATURE_XPATH: &str = "XPath"; // DOM Level-3 XPath
 
 pub(crate) const XML_FEATURE_V1: &str = "1.0";
 pub(crate) const XML_FEATURE_V2: &str = "2.0";
+pub(crate) const XML_FEATURE_V3: &str = "3.0";
+pub(crate) const XML_FEATURE_REQUIRED: char = '+';
 
 // ------------------------------------------------------------------------------------------------
 // Pre-Defined Reserved Characters
@@ -112,3 +135,164 @@ pub(crate) const XML_ESC_APOS_CHAR: char = '\'';
 pub(crate) const XML_ESC_GT_CHAR: char = '>';
 pub(crate) const XML_ESC_LT_CHAR: char = '<';
 pub(crate) const XML_ESC_QUOT_CHAR: char = '"';
+
+pub(crate) const XML_ESC_AMP_NAME: &str = "amp";
+pub(crate) const XML_ESC_APOS_NAME: &str = "apos";
+pub(crate) const XML_ESC_GT_NAME: &str = "gt";
+pub(crate) const XML_ESC_LT_NAME: &str = "lt";
+pub(crate) const XML_ESC_QUOT_NAME: &str = "quot";
+
+pub(crate) const XML_ESC_TAB_CHAR: char = '\t';
+pub(crate) const XML_ESC_LF_CHAR: char = '\n';
+pub(crate) const XML_ESC_CR_CHAR: char = '\r';
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Determines how [`escape_text_with`](fn.escape_text_with.html) and
+/// [`escape_attribute_with`](fn.escape_attribute_with.html) handle characters that are not legal
+/// in an XML 1.0 document, such as most of the C0 control characters.
+///
+/// The default is to reject such characters, as the alternative cannot be read back by a
+/// conforming XML 1.0 parser; this is used by [`escape_text`](fn.escape_text.html),
+/// [`escape_attribute`](fn.escape_attribute.html), and the serializers in the `io` module. The
+/// `Display` implementation of `RefNode` has no way to report an error, and so escapes them.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InvalidCharHandling {
+    ///
+    /// Write the character as a numeric character reference. Note that such references are only
+    /// well-formed in an XML 1.1 document.
+    ///
+    Escape,
+    ///
+    /// Fail with `Error::InvalidCharacter`.
+    ///
+    #[default]
+    Reject,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Escape a string for use as the content of a text node. The markup characters `&` and `<` are
+/// always written as character references, as is `>` so that the sequence `]]>` cannot appear.
+/// Both quote characters are also escaped so that the result may be used in any context that
+/// accepts character data. Characters that are not legal in XML 1.0 are rejected, as is the
+/// default for [`InvalidCharHandling`](enum.InvalidCharHandling.html); see
+/// [`escape_text_with`](fn.escape_text_with.html) to write them as character references instead.
+///
+/// **Errors**
+///
+/// * `INVALID_CHARACTER_ERR`: Raised if `input` contains a character that is not legal in XML 1.0.
+///
+/// # Specification
+///
+/// From XML 1.1 [§2.4 Character Data and Markup](https://www.w3.org/TR/xml11/#dt-chardata):
+///
+/// Text consists of intermingled character data and markup. [Definition: **Markup** takes the form
+/// of start-tags, end-tags, empty-element tags, entity references, character references, comments,
+/// CDATA section delimiters, document type declarations, processing instructions, XML declarations,
+/// text declarations, and any white space that is at the top level of the document entity (that is,
+/// outside the document element and not inside any other markup).]
+///
+/// [Definition: All text that is not markup constitutes the **character data** of the document].
+///
+/// The ampersand character (&) and the left angle bracket (<) must not appear in their literal
+/// form, except when used as markup delimiters, or within a comment, a processing instruction, or
+/// a CDATA section. If they are needed elsewhere, they must be escaped using either numeric
+/// character references or the strings "&amp;" and "&lt;" respectively. The right angle bracket
+/// (>) may be represented using the string "&gt;", and must, for compatibility, be escaped using
+/// either "&gt;" or a character reference when it appears in the string "]]>" in content, when that
+/// string is not marking the end of a CDATA section.
+///
+/// In the content of elements, character data is any string of characters which does not contain
+/// the start-delimiter of any markup or the CDATA-section-close delimiter, "]]>". In a CDATA
+/// section, character data is any string of characters not including the CDATA-section-close
+/// delimiter.
+///
+/// To allow attribute values to contain both single and double quotes, the apostrophe or
+/// single-quote character (') may be represented as "&apos;", and the double-quote character (")
+/// as "&quot;".
+///
+pub fn escape_text(input: &str) -> Result<String> {
+    escape_chars(input, false, None, InvalidCharHandling::default())
+}
+
+///
+/// Escape a string for use as the content of a text node, as [`escape_text`](fn.escape_text.html),
+/// but with the handling of characters that are not legal in XML 1.0 determined by `handling`.
+///
+pub fn escape_text_with(input: &str, handling: InvalidCharHandling) -> Result<String> {
+    escape_chars(input, false, None, handling)
+}
+
+///
+/// Escape a string for use as an attribute value, delimited by either quote character. In
+/// addition to the characters escaped by [`escape_text`](fn.escape_text.html) this writes tab,
+/// line feed and carriage return as character references so that they are not replaced by spaces
+/// during attribute-value normalization when the document is parsed. As with `escape_text`,
+/// characters that are not legal in XML 1.0 are rejected.
+///
+/// **Errors**
+///
+/// * `INVALID_CHARACTER_ERR`: Raised if `input` contains a character that is not legal in XML 1.0.
+///
+pub fn escape_attribute(input: &str) -> Result<String> {
+    escape_chars(input, true, None, InvalidCharHandling::default())
+}
+
+///
+/// Escape a string for use as an attribute value, as
+/// [`escape_attribute`](fn.escape_attribute.html), but with the handling of characters that are
+/// not legal in XML 1.0 determined by `handling`.
+///
+pub fn escape_attribute_with(input: &str, handling: InvalidCharHandling) -> Result<String> {
+    escape_chars(input, true, None, handling)
+}
+
+///
+/// Escape a string for use as an attribute value delimited by `quote`, which must be one of the
+/// two quote characters; only that quote character is escaped, the other is written literally.
+///
+pub(crate) fn escape_attribute_for_quote(input: &str, quote: char) -> Result<String> {
+    escape_chars(input, true, Some(quote), InvalidCharHandling::default())
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn escape_chars(
+    input: &str,
+    in_attribute: bool,
+    quote: Option<char>,
+    handling: InvalidCharHandling,
+) -> Result<String> {
+    let mut result = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            XML_ESC_APOS_CHAR | XML_ESC_QUOT_CHAR if quote.is_some_and(|quote| quote != c) => {
+                result.push(c)
+            }
+            XML_ESC_AMP_CHAR | XML_ESC_APOS_CHAR | XML_ESC_GT_CHAR | XML_ESC_LT_CHAR
+            | XML_ESC_QUOT_CHAR => result.push_str(&to_entity(c)),
+            XML_ESC_TAB_CHAR | XML_ESC_LF_CHAR | XML_ESC_CR_CHAR if in_attribute => {
+                result.push_str(&to_entity(c))
+            }
+            c if !is_xml_10_char(c) => match handling {
+                InvalidCharHandling::Escape => result.push_str(&to_entity(c)),
+                InvalidCharHandling::Reject => {
+                    warn!("{}", MSG_INVALID_XML_CHARACTER);
+                    return Err(Error::InvalidCharacter);
+                }
+            },
+            c => result.push(c),
+        }
+    }
+    Ok(result)
+}
@@ -1,18 +1,12 @@
 use crate::shared::syntax::*;
+use regex::Regex;
 use std::convert::TryFrom;
-use std::fmt::{Display, Formatter, Result as FmtResult};
-use std::str::FromStr;
+use std::sync::OnceLock;
 
 // ------------------------------------------------------------------------------------------------
 //  Public Types
 // ------------------------------------------------------------------------------------------------
 
-#[derive(Clone, Debug, PartialEq)]
-pub(crate) enum SpaceHandling {
-    Default,
-    Preserve,
-}
-
 pub(crate) trait EntityResolver {
     fn resolve(&self, entity: &str) -> Option<String>;
 }
@@ -69,10 +63,13 @@ pub(crate) fn normalize_attribute_value(
     let step_3 = if step_1.is_empty() {
         step_1
     } else {
-        let find = regex::Regex::new(
-            r"(?P<entity_ref>[&%][\pL_][\pL\.\d_\-]*;)|(?P<char>&#\d+;)|(?P<char_hex>&#x[0-9a-fA-F]+;)|(?P<ws>[\u{09}\u{0A}\u{0D}])",
-        )
-        .unwrap();
+        static FIND: OnceLock<Regex> = OnceLock::new();
+        let find = FIND.get_or_init(|| {
+            Regex::new(
+                r"(?P<entity_ref>[&%][\pL_][\pL\.\d_\-]*;)|(?P<char>&#\d+;)|(?P<char_hex>&#x[0-9a-fA-F]+;)|(?P<ws>[\u{09}\u{0A}\u{0D}])",
+            )
+            .unwrap()
+        });
         let mut step_2 = String::new();
         let mut last_end = 0;
         for capture in find.captures_iter(&step_1) {
@@ -141,63 +138,17 @@ pub(crate) fn normalize_end_of_lines(value: &str) -> String {
     if value.is_empty() {
         value.to_string()
     } else {
-        let line_ends = regex::Regex::new(r"\u{0D}[\u{0A}\u{85}]?|\u{85}|\u{2028}").unwrap();
+        static LINE_ENDS: OnceLock<Regex> = OnceLock::new();
+        let line_ends =
+            LINE_ENDS.get_or_init(|| Regex::new(r"\u{0D}[\u{0A}\u{85}]?|\u{85}|\u{2028}").unwrap());
         line_ends.replace_all(value, "\u{0A}").to_string()
     }
 }
 
-///
-/// Escape character data according to XML 1.1
-/// [§2.4 Character Data and Markup](https://www.w3.org/TR/xml11/#dt-chardata). This is the
-/// do-everything version, not attempting to separate the rules defined below by node type.
-///
-/// # Specification
-///
-/// Text consists of intermingled character data and markup. [Definition: **Markup** takes the form
-/// of start-tags, end-tags, empty-element tags, entity references, character references, comments,
-/// CDATA section delimiters, document type declarations, processing instructions, XML declarations,
-/// text declarations, and any white space that is at the top level of the document entity (that is,
-/// outside the document element and not inside any other markup).]
-///
-/// [Definition: All text that is not markup constitutes the **character data** of the document].
-///
-/// The ampersand character (&) and the left angle bracket (<) must not appear in their literal
-/// form, except when used as markup delimiters, or within a comment, a processing instruction, or
-/// a CDATA section. If they are needed elsewhere, they must be escaped using either numeric
-/// character references or the strings "&amp;" and "&lt;" respectively. The right angle bracket
-/// (>) may be represented using the string "&gt;", and must, for compatibility, be escaped using
-/// either "&gt;" or a character reference when it appears in the string "]]>" in content, when that
-/// string is not marking the end of a CDATA section.
-///
-/// In the content of elements, character data is any string of characters which does not contain
-/// the start-delimiter of any markup or the CDATA-section-close delimiter, "]]>". In a CDATA
-/// section, character data is any string of characters not including the CDATA-section-close
-/// delimiter.
-///
-/// To allow attribute values to contain both single and double quotes, the apostrophe or
-/// single-quote character (') may be represented as "&apos;", and the double-quote character (")
-/// as "&quot;".
-///
-pub(crate) fn escape(input: &str) -> String {
-    let mut result = String::with_capacity(input.len());
-
-    for c in input.chars() {
-        match c {
-            XML_ESC_AMP_CHAR => result.push_str(&to_entity(XML_ESC_AMP_CHAR)),
-            XML_ESC_APOS_CHAR => result.push_str(&to_entity(XML_ESC_APOS_CHAR)),
-            XML_ESC_GT_CHAR => result.push_str(&to_entity(XML_ESC_GT_CHAR)),
-            XML_ESC_LT_CHAR => result.push_str(&to_entity(XML_ESC_LT_CHAR)),
-            XML_ESC_QUOT_CHAR => result.push_str(&to_entity(XML_ESC_QUOT_CHAR)),
-            o => result.push(o),
-        }
-    }
-    result
-}
-
 pub(crate) fn to_entity(c: char) -> String {
     format!(
         "{}{}{}",
-        XML_NUMBERED_ENTITYREF_START, c as u16, XML_ENTITYREF_END
+        XML_NUMBERED_ENTITYREF_START, c as u32, XML_ENTITYREF_END
     )
 }
 
@@ -205,7 +156,7 @@ pub(crate) fn to_entity(c: char) -> String {
 pub(crate) fn to_entity_hex(c: char) -> String {
     format!(
         "{}{:X}{}",
-        XML_HEX_NUMBERED_ENTITYREF_START, c as u16, XML_ENTITYREF_END
+        XML_HEX_NUMBERED_ENTITYREF_START, c as u32, XML_ENTITYREF_END
     )
 }
 
@@ -217,7 +168,7 @@ fn char_from_entity(entity: &str) -> String {
         u32::from_str_radix(code_point, 16).unwrap()
     } else {
         let code_point = &entity[2..entity.len() - 1];
-        u32::from_str_radix(code_point, 10).unwrap()
+        code_point.parse::<u32>().unwrap()
     };
     let character = char::try_from(code_point).unwrap();
     character.to_string()
@@ -253,14 +204,13 @@ fn char_from_entity(entity: &str) -> String {
 /// [#x10FFFE-#x10FFFF].
 /// ```
 ///
-#[allow(dead_code)]
 pub(crate) fn is_xml_10_char(c: char) -> bool {
     c == '\u{0009}'
         || c == '\u{000A}'
         || c == '\u{000D}'
-        || (c >= '\u{0020}' && c <= '\u{D7FF}')
-        || (c >= '\u{E000}' && c <= '\u{FFFD}')
-        || (c >= '\u{10000}' && c <= '\u{10FFF}')
+        || ('\u{0020}'..='\u{D7FF}').contains(&c)
+        || ('\u{E000}'..='\u{FFFD}').contains(&c)
+        || ('\u{10000}'..='\u{10FFFF}').contains(&c)
 }
 
 #[allow(dead_code)]
@@ -286,9 +236,9 @@ pub(crate) fn is_xml_11_char(c: char) -> bool {
     // below ranges are always valid for XML 1.1 documents
     // from https://en.wikipedia.org/wiki/XML#Valid_characters
     //
-    (c >= '\u{0001}' && c <= '\u{D7FF}')
-        || (c >= '\u{E000}' && c <= '\u{FFFD}')
-        || (c >= '\u{10000}' && c <= '\u{10FFF}')
+    ('\u{0001}'..='\u{D7FF}').contains(&c)
+        || ('\u{E000}'..='\u{FFFD}').contains(&c)
+        || ('\u{10000}'..='\u{10FFFF}').contains(&c)
 }
 
 ///
@@ -304,11 +254,11 @@ pub(crate) fn is_xml_11_restricted_char(c: char) -> bool {
     // below ranges are always valid for XML 1.1 documents
     // from https://en.wikipedia.org/wiki/XML#Valid_characters
     //
-    (c >= '\u{01}' && c <= '\u{08}')
-        || (c >= '\u{0B}' && c <= '\u{0C}')
-        || (c >= '\u{0E}' && c <= '\u{1F}')
-        || (c >= '\u{7F}' && c <= '\u{84}')
-        || (c >= '\u{86}' && c <= '\u{9F}')
+    ('\u{01}'..='\u{08}').contains(&c)
+        || ('\u{0B}'..='\u{0C}').contains(&c)
+        || ('\u{0E}'..='\u{1F}').contains(&c)
+        || ('\u{7F}'..='\u{84}').contains(&c)
+        || ('\u{86}'..='\u{9F}').contains(&c)
 }
 
 ///
@@ -341,21 +291,21 @@ pub(crate) fn is_xml_space(c: char) -> bool {
 #[allow(dead_code)]
 pub(crate) fn is_xml_name_start_char(c: char) -> bool {
     c == ':'
-        || (c >= 'A' && c <= 'Z')
+        || c.is_ascii_uppercase()
         || c == '_'
-        || (c >= 'a' && c <= 'z')
-        || (c >= '\u{C0}' && c <= '\u{D6}')
-        || (c >= '\u{D8}' && c <= '\u{F6}')
-        || (c >= '\u{0F8}' && c <= '\u{2FF}')
-        || (c >= '\u{370}' && c <= '\u{37D}')
-        || (c >= '\u{037F}' && c <= '\u{1FFF}')
-        || (c >= '\u{200C}' && c <= '\u{200D}')
-        || (c >= '\u{2070}' && c <= '\u{218F}')
-        || (c >= '\u{2C00}' && c <= '\u{2FEF}')
-        || (c >= '\u{3001}' && c <= '\u{D7FF}')
-        || (c >= '\u{F900}' && c <= '\u{FDCF}')
-        || (c >= '\u{FDF0}' && c <= '\u{FFFD}')
-        || (c >= '\u{10000}' && c <= '\u{EFFFF}')
+        || c.is_ascii_lowercase()
+        || ('\u{C0}'..='\u{D6}').contains(&c)
+        || ('\u{D8}'..='\u{F6}').contains(&c)
+        || ('\u{0F8}'..='\u{2FF}').contains(&c)
+        || ('\u{370}'..='\u{37D}').contains(&c)
+        || ('\u{037F}'..='\u{1FFF}').contains(&c)
+        || ('\u{200C}'..='\u{200D}').contains(&c)
+        || ('\u{2070}'..='\u{218F}').contains(&c)
+        || ('\u{2C00}'..='\u{2FEF}').contains(&c)
+        || ('\u{3001}'..='\u{D7FF}').contains(&c)
+        || ('\u{F900}'..='\u{FDCF}').contains(&c)
+        || ('\u{FDF0}'..='\u{FFFD}').contains(&c)
+        || ('\u{10000}'..='\u{EFFFF}').contains(&c)
 }
 
 ///
@@ -368,10 +318,10 @@ pub(crate) fn is_xml_name_char(c: char) -> bool {
     is_xml_name_start_char(c)
         || c == '-'
         || c == '.'
-        || (c >= '0' && c <= '9')
+        || c.is_ascii_digit()
         || c == '\u{B7}'
-        || (c >= '\u{0300}' && c <= '\u{036F}')
-        || (c >= '\u{203F}' && c <= '\u{2040}')
+        || ('\u{0300}'..='\u{036F}').contains(&c)
+        || ('\u{203F}'..='\u{2040}').contains(&c)
 }
 
 ///
@@ -380,7 +330,11 @@ pub(crate) fn is_xml_name_char(c: char) -> bool {
 /// ```
 ///
 pub(crate) fn is_xml_name(s: &str) -> bool {
-    !s.is_empty() && s.starts_with(is_xml_name_start_char) && s[1..].chars().all(is_xml_name_char)
+    let mut chars = s.chars();
+    match chars.next() {
+        None => false,
+        Some(first) => is_xml_name_start_char(first) && chars.all(is_xml_name_char),
+    }
 }
 
 ///
@@ -413,50 +367,6 @@ pub(crate) fn is_xml_nmtokens(s: &str) -> bool {
     !s.is_empty() && s.split(' ').all(is_xml_nmtoken)
 }
 
-// ------------------------------------------------------------------------------------------------
-// Implementations
-// ------------------------------------------------------------------------------------------------
-
-impl Default for SpaceHandling {
-    fn default() -> Self {
-        SpaceHandling::Default
-    }
-}
-
-// ------------------------------------------------------------------------------------------------
-
-impl Display for SpaceHandling {
-    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
-        write!(
-            f,
-            "{}{}{}=\"{}\"",
-            XML_NS_ATTRIBUTE,
-            XML_NS_SEPARATOR,
-            XML_NS_ATTR_SPACE,
-            match self {
-                SpaceHandling::Default => XML_NS_ATTR_SPACE_DEFAULT,
-                SpaceHandling::Preserve => XML_NS_ATTR_SPACE_PRESERVE,
-            }
-        )
-    }
-}
-
-// ------------------------------------------------------------------------------------------------
-
-impl FromStr for SpaceHandling {
-    type Err = ();
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s == XML_NS_ATTR_SPACE_DEFAULT {
-            Ok(SpaceHandling::Default)
-        } else if s == XML_NS_ATTR_SPACE_PRESERVE {
-            Ok(SpaceHandling::Preserve)
-        } else {
-            Err(())
-        }
-    }
-}
-
 // ------------------------------------------------------------------------------------------------
 // Unit Tests
 // ------------------------------------------------------------------------------------------------
@@ -467,48 +377,10 @@ mod tests {
     use std::borrow::Borrow;
     use std::collections::HashMap;
 
-    #[test]
-    fn test_space_handling_default() {
-        let sh = SpaceHandling::default();
-        assert_eq!(sh, SpaceHandling::Default);
-    }
-
-    #[test]
-    fn test_space_handling_display() {
-        assert_eq!(
-            format!("{}", SpaceHandling::Default),
-            format!(
-                "{}{}{}=\"{}\"",
-                XML_NS_ATTRIBUTE, XML_NS_SEPARATOR, XML_NS_ATTR_SPACE, XML_NS_ATTR_SPACE_DEFAULT
-            )
-        );
-        assert_eq!(
-            format!("{}", SpaceHandling::Preserve),
-            format!(
-                "{}{}{}=\"{}\"",
-                XML_NS_ATTRIBUTE, XML_NS_SEPARATOR, XML_NS_ATTR_SPACE, XML_NS_ATTR_SPACE_PRESERVE
-            )
-        );
-    }
-
-    #[test]
-    fn test_space_handling_from_str() {
-        assert_eq!(
-            SpaceHandling::from_str(XML_NS_ATTR_SPACE_DEFAULT).unwrap(),
-            SpaceHandling::Default
-        );
-        assert_eq!(
-            SpaceHandling::from_str(XML_NS_ATTR_SPACE_PRESERVE).unwrap(),
-            SpaceHandling::Preserve
-        );
-        assert!(SpaceHandling::from_str("").is_err());
-        assert!(SpaceHandling::from_str("other").is_err());
-    }
-
     #[test]
     fn test_end_of_line_handling() {
         let input = "one\u{0D}two\u{0D}\u{0A}\u{0A}three\u{0A}\u{0D}\u{85}four\u{85}five\u{2028}";
-        let output = normalize_end_of_lines(&input.to_string());
+        let output = normalize_end_of_lines(input);
         assert_eq!(
             output,
             "one\u{0A}two\u{0A}\u{0A}three\u{0A}\u{0A}four\u{0A}five\u{0A}".to_string()
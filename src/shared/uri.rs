@@ -0,0 +1,273 @@
+/*!
+Resolution of URI references, as used for `xml:base` and the DOM Level 3 `baseURI`.
+*/
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Debug, Default)]
+struct Components<'a> {
+    scheme: Option<&'a str>,
+    authority: Option<&'a str>,
+    path: &'a str,
+    query: Option<&'a str>,
+    fragment: Option<&'a str>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Resolve `reference` against `base`, following RFC 3986
+/// [§5.2 Relative Resolution](https://www.rfc-editor.org/rfc/rfc3986#section-5.2). A relative
+/// `base` is not an error, the result is then also relative.
+///
+pub(crate) fn resolve_reference(base: &str, reference: &str) -> String {
+    let base = Components::parse(base);
+    let reference = Components::parse(reference);
+    if reference.scheme.is_some() {
+        Components {
+            path: "",
+            ..reference
+        }
+        .with_path(remove_dot_segments(reference.path))
+    } else if reference.authority.is_some() {
+        Components {
+            scheme: base.scheme,
+            path: "",
+            ..reference
+        }
+        .with_path(remove_dot_segments(reference.path))
+    } else if reference.path.is_empty() {
+        Components {
+            scheme: base.scheme,
+            authority: base.authority,
+            path: "",
+            query: reference.query.or(base.query),
+            fragment: reference.fragment,
+        }
+        .with_path(base.path.to_string())
+    } else {
+        let path = if reference.path.starts_with('/') {
+            remove_dot_segments(reference.path)
+        } else {
+            let merged = merge(&base, reference.path);
+            let path = remove_dot_segments(&merged);
+            if merged.starts_with('/') {
+                path
+            } else {
+                //
+                // A relative base keeps the result relative, where RFC 3986 assumes the base is
+                // absolute and so may introduce a leading "/".
+                //
+                path.trim_start_matches('/').to_string()
+            }
+        };
+        Components {
+            scheme: base.scheme,
+            authority: base.authority,
+            path: "",
+            query: reference.query,
+            fragment: reference.fragment,
+        }
+        .with_path(path)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl<'a> Components<'a> {
+    //
+    // Split `uri` using the regular expression from RFC 3986, Appendix B.
+    //
+    fn parse(uri: &'a str) -> Self {
+        let parser =
+            regex::Regex::new(r"^(([^:/?#]+):)?(//([^/?#]*))?([^?#]*)(\?([^#]*))?(#(.*))?")
+                .unwrap();
+        match parser.captures(uri) {
+            None => Self {
+                path: uri,
+                ..Default::default()
+            },
+            Some(captures) => Self {
+                scheme: captures.get(2).map(|m| m.as_str()),
+                authority: captures.get(4).map(|m| m.as_str()),
+                path: captures.get(5).map(|m| m.as_str()).unwrap_or_default(),
+                query: captures.get(7).map(|m| m.as_str()),
+                fragment: captures.get(9).map(|m| m.as_str()),
+            },
+        }
+    }
+
+    //
+    // Recompose the components, with `path` in place of the current path, following RFC 3986
+    // §5.3 Component Recomposition.
+    //
+    fn with_path(self, path: String) -> String {
+        let mut result = String::new();
+        if let Some(scheme) = self.scheme {
+            result.push_str(scheme);
+            result.push(':');
+        }
+        if let Some(authority) = self.authority {
+            result.push_str("//");
+            result.push_str(authority);
+        }
+        result.push_str(&path);
+        if let Some(query) = self.query {
+            result.push('?');
+            result.push_str(query);
+        }
+        if let Some(fragment) = self.fragment {
+            result.push('#');
+            result.push_str(fragment);
+        }
+        result
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+//
+// RFC 3986 §5.2.3 Merge Paths.
+//
+fn merge(base: &Components<'_>, path: &str) -> String {
+    if base.authority.is_some() && base.path.is_empty() {
+        format!("/{}", path)
+    } else {
+        match base.path.rfind('/') {
+            None => path.to_string(),
+            Some(index) => format!("{}{}", &base.path[..=index], path),
+        }
+    }
+}
+
+//
+// RFC 3986 §5.2.4 Remove Dot Segments.
+//
+fn remove_dot_segments(path: &str) -> String {
+    let mut input = path;
+    let mut output: Vec<&str> = Vec::new();
+    while !input.is_empty() {
+        if let Some(rest) = input.strip_prefix("../") {
+            input = rest;
+        } else if let Some(rest) = input.strip_prefix("./") {
+            input = rest;
+        } else if input.starts_with("/./") {
+            input = &input[2..];
+        } else if input == "/." {
+            input = "/";
+        } else if input.starts_with("/../") || input == "/.." {
+            input = if input == "/.." { "/" } else { &input[3..] };
+            let _safe_to_ignore = output.pop();
+        } else if input == "." || input == ".." {
+            input = "";
+        } else {
+            //
+            // Move the first segment, including any leading "/", to the output.
+            //
+            let start = usize::from(input.starts_with('/'));
+            let end = input[start..]
+                .find('/')
+                .map(|index| index + start)
+                .unwrap_or(input.len());
+            output.push(&input[..end]);
+            input = &input[end..];
+        }
+    }
+    output.concat()
+}
+
+// ------------------------------------------------------------------------------------------------
+// Unit Tests
+// ------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = "http://a/b/c/d;p?q";
+
+    #[test]
+    fn test_normal_examples() {
+        // RFC 3986 §5.4.1
+        for (reference, expected) in [
+            ("g:h", "g:h"),
+            ("g", "http://a/b/c/g"),
+            ("./g", "http://a/b/c/g"),
+            ("g/", "http://a/b/c/g/"),
+            ("/g", "http://a/g"),
+            ("//g", "http://g"),
+            ("?y", "http://a/b/c/d;p?y"),
+            ("g?y", "http://a/b/c/g?y"),
+            ("#s", "http://a/b/c/d;p?q#s"),
+            ("g#s", "http://a/b/c/g#s"),
+            ("g?y#s", "http://a/b/c/g?y#s"),
+            (";x", "http://a/b/c/;x"),
+            ("g;x", "http://a/b/c/g;x"),
+            ("g;x?y#s", "http://a/b/c/g;x?y#s"),
+            ("", "http://a/b/c/d;p?q"),
+            (".", "http://a/b/c/"),
+            ("./", "http://a/b/c/"),
+            ("..", "http://a/b/"),
+            ("../", "http://a/b/"),
+            ("../g", "http://a/b/g"),
+            ("../..", "http://a/"),
+            ("../../", "http://a/"),
+            ("../../g", "http://a/g"),
+        ] {
+            assert_eq!(
+                resolve_reference(BASE, reference),
+                expected,
+                "{}",
+                reference
+            );
+        }
+    }
+
+    #[test]
+    fn test_abnormal_examples() {
+        // RFC 3986 §5.4.2
+        for (reference, expected) in [
+            ("../../../g", "http://a/g"),
+            ("../../../../g", "http://a/g"),
+            ("/./g", "http://a/g"),
+            ("/../g", "http://a/g"),
+            ("g.", "http://a/b/c/g."),
+            (".g", "http://a/b/c/.g"),
+            ("g..", "http://a/b/c/g.."),
+            ("..g", "http://a/b/c/..g"),
+            ("./../g", "http://a/b/g"),
+            ("./g/.", "http://a/b/c/g/"),
+            ("g/./h", "http://a/b/c/g/h"),
+            ("g/../h", "http://a/b/c/h"),
+            ("g;x=1/./y", "http://a/b/c/g;x=1/y"),
+            ("g;x=1/../y", "http://a/b/c/y"),
+            ("g?y/./x", "http://a/b/c/g?y/./x"),
+            ("g#s/../x", "http://a/b/c/g#s/../x"),
+        ] {
+            assert_eq!(
+                resolve_reference(BASE, reference),
+                expected,
+                "{}",
+                reference
+            );
+        }
+    }
+
+    #[test]
+    fn test_relative_base() {
+        assert_eq!(
+            resolve_reference("docs/", "images/a.png"),
+            "docs/images/a.png"
+        );
+        assert_eq!(resolve_reference("docs/index.xml", "a.xml"), "docs/a.xml");
+        assert_eq!(resolve_reference("docs/", "../images/"), "images/");
+    }
+}
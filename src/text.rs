@@ -0,0 +1,156 @@
+/*!
+Provides the character classification, escaping, and unescaping used by the DOM implementation, for
+use when generating or cleaning XML text outside of a DOM tree.
+
+The escaping functions are the same as those in the [`io`](../io/index.html) module, and are the
+escaping used when a tree is serialized. [`unescape`](fn.unescape.html) reverses them, replacing
+each of the five predefined entity references, and each numeric character reference, with the
+character it refers to.
+
+# Example
+
+```rust
+use xml_dom::io::Quote;
+use xml_dom::level2::Error;
+use xml_dom::text::*;
+
+assert_eq!(escape_text("1 < 2 & 3").unwrap(), "1 &#60; 2 &#38; 3");
+assert_eq!(
+    escape_attribute_quoted(r#"it's "quoted""#, Quote::Single).unwrap(),
+    r#"it&#39;s "quoted""#
+);
+assert_eq!(escape_text("bell\u{7}"), Err(Error::InvalidCharacter));
+
+assert_eq!(unescape("1 &lt; 2 &amp; 3 &#x3E; &#50;").unwrap(), "1 < 2 & 3 > 2");
+assert_eq!(unescape("&#0;"), Err(Error::InvalidCharacter));
+assert_eq!(unescape("&nbsp;"), Err(Error::Syntax));
+
+assert!(is_xml_char('\t'));
+assert!(!is_xml_char('\u{0}'));
+assert!(is_xml_whitespace('\n'));
+assert!(!is_xml_whitespace('\u{A0}'));
+```
+*/
+
+use crate::io::Quote;
+use crate::shared::error::{Error, Result, MSG_INVALID_REFERENCE, MSG_INVALID_XML_CHARACTER};
+use crate::shared::syntax::*;
+use crate::shared::text::{is_xml_10_char, is_xml_space};
+use std::convert::TryFrom;
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+pub use crate::shared::syntax::{
+    escape_attribute, escape_attribute_with, escape_text, escape_text_with, InvalidCharHandling,
+};
+
+///
+/// Escape a string for use as an attribute value delimited by `quote`. This is the same as
+/// [`escape_attribute`](fn.escape_attribute.html) except that only the delimiting quote character
+/// is escaped; the other is written literally.
+///
+/// **Errors**
+///
+/// * `INVALID_CHARACTER_ERR`: Raised if `input` contains a character that is not legal in XML 1.0.
+///
+pub fn escape_attribute_quoted(input: &str, quote: Quote) -> Result<String> {
+    let quote = match quote {
+        Quote::Double => XML_ESC_QUOT_CHAR,
+        Quote::Single => XML_ESC_APOS_CHAR,
+    };
+    escape_attribute_for_quote(input, quote)
+}
+
+///
+/// Replace each entity and character reference in `input` with the character it refers to. The
+/// references recognized are the five predefined entities, `&amp;`, `&apos;`, `&gt;`, `&lt;`, and
+/// `&quot;`, and decimal, `&#NN;`, or hexadecimal, `&#xNN;`, character references.
+///
+/// **Errors**
+///
+/// * `SYNTAX_ERR`: Raised if `input` contains an `&` that does not begin one of the references
+///   above, including a reference to any other entity.
+/// * `INVALID_CHARACTER_ERR`: Raised if a character reference refers to a code point that is not
+///   legal in an XML 1.0 document.
+///
+pub fn unescape(input: &str) -> Result<String> {
+    let mut result = String::with_capacity(input.len());
+    let mut remaining = input;
+    while let Some(start) = remaining.find(XML_ENTITYREF_START) {
+        result.push_str(&remaining[..start]);
+        remaining = &remaining[start + XML_ENTITYREF_START.len()..];
+        let end = match remaining.find(XML_ENTITYREF_END) {
+            None => {
+                warn!("{}", MSG_INVALID_REFERENCE);
+                return Err(Error::Syntax);
+            }
+            Some(end) => end,
+        };
+        result.push(reference_char(&remaining[..end])?);
+        remaining = &remaining[end + XML_ENTITYREF_END.len()..];
+    }
+    result.push_str(remaining);
+    Ok(result)
+}
+
+///
+/// Returns `true` if `c` is a legal character in an XML 1.0 document; see the production `Char` in
+/// [XML 1.0 §2.2](https://www.w3.org/TR/REC-xml/#charsets).
+///
+pub fn is_xml_char(c: char) -> bool {
+    is_xml_10_char(c)
+}
+
+///
+/// Returns `true` if `c` is an XML white space character, one of space, tab, carriage return, or
+/// line feed; see the production `S` in [XML 1.0 §2.3](https://www.w3.org/TR/REC-xml/#sec-common-syn).
+///
+pub fn is_xml_whitespace(c: char) -> bool {
+    is_xml_space(c)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn reference_char(reference: &str) -> Result<char> {
+    //
+    // A reference with too many digits for a `u32` is well-formed, but cannot be a legal character.
+    //
+    let code_point = if let Some(hex) = reference.strip_prefix("#x") {
+        Some(hex)
+            .filter(|hex| !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit()))
+            .map(|hex| u32::from_str_radix(hex, 16).unwrap_or(u32::MAX))
+    } else if let Some(decimal) = reference.strip_prefix('#') {
+        Some(decimal)
+            .filter(|decimal| !decimal.is_empty() && decimal.chars().all(|c| c.is_ascii_digit()))
+            .map(|decimal| decimal.parse::<u32>().unwrap_or(u32::MAX))
+    } else {
+        return match reference {
+            XML_ESC_AMP_NAME => Ok(XML_ESC_AMP_CHAR),
+            XML_ESC_APOS_NAME => Ok(XML_ESC_APOS_CHAR),
+            XML_ESC_GT_NAME => Ok(XML_ESC_GT_CHAR),
+            XML_ESC_LT_NAME => Ok(XML_ESC_LT_CHAR),
+            XML_ESC_QUOT_NAME => Ok(XML_ESC_QUOT_CHAR),
+            _ => {
+                warn!("{}", MSG_INVALID_REFERENCE);
+                Err(Error::Syntax)
+            }
+        };
+    };
+    match code_point {
+        None => {
+            warn!("{}", MSG_INVALID_REFERENCE);
+            Err(Error::Syntax)
+        }
+        Some(code_point) => match char::try_from(code_point) {
+            Ok(c) if is_xml_10_char(c) => Ok(c),
+            _ => {
+                warn!("{}", MSG_INVALID_XML_CHARACTER);
+                Err(Error::InvalidCharacter)
+            }
+        },
+    }
+}
@@ -0,0 +1,380 @@
+/*!
+Provides [XInclude 1.0](https://www.w3.org/TR/xinclude/) processing of a DOM tree, replacing each
+`xi:include` element with the content of the resource it refers to. This module is only included
+with the `xinclude` feature.
+
+Resources are never read directly; each is requested from an
+[`IncludeResolver`](trait.IncludeResolver.html) supplied by the caller, which may read files, make
+network requests, or look up content held in memory, and which decides which resources may be
+included at all. The resolver is given the URI of the resource, resolved against the base URI of
+the `xi:include` element; within included content that is the URI of the resource it came from.
+
+# Supported features
+
+* `parse="xml"`, the default, includes the children of the resource's document, or with an
+  `xpointer` attribute the element with that ID. Only the shorthand form of XPointer, a bare ID,
+  is supported; any other pointer is a resource error. An `xpointer` without an `href` selects an
+  element from the document containing the `xi:include` element.
+* `parse="text"` includes the resource as a single text node. The `encoding` attribute is ignored
+  as the resolver returns text.
+* A resource error, where the resolver fails or the pointer selects nothing, is recovered by
+  replacing the `xi:include` element with the children of its `xi:fallback` element, if any.
+* Included content is processed in turn, and a resource that includes itself, directly or
+  through other resources, is reported as an error rather than processed without end.
+
+The included nodes are imported into the including document; the base URI and language fix-ups of
+§4.5.5 and §4.5.6 of the specification are not applied.
+
+# Example
+
+```rust
+use std::collections::HashMap;
+use xml_dom::level2::*;
+use xml_dom::parser::read_xml;
+use xml_dom::xinclude::*;
+
+let mut resources = HashMap::new();
+let _ = resources.insert("chapter.xml".to_string(), "<chapter>One</chapter>".to_string());
+let _ = resources.insert("notice.txt".to_string(), "(c) 2024".to_string());
+
+let mut document_node = read_xml(
+    r#"<book xmlns:xi="http://www.w3.org/2001/XInclude"><xi:include href="chapter.xml"/><p><xi:include href="notice.txt" parse="text"/></p><xi:include href="missing.xml"><xi:fallback><missing/></xi:fallback></xi:include></book>"#,
+)
+.unwrap();
+
+document_node.process_xincludes(&resources).unwrap();
+assert_eq!(
+    document_node.to_string(),
+    r#"<book xmlns:xi="http://www.w3.org/2001/XInclude"><chapter>One</chapter><p>(c) 2024</p><missing></missing></book>"#
+);
+```
+*/
+
+use crate::level2::convert::{as_document, is_element};
+use crate::level2::ext::{TreeIterators, TreeMembership};
+use crate::level2::node_impl::RefNode;
+use crate::level2::trait_impls::normalized_value;
+use crate::level2::traits::{Document, Element, Node, NodeType};
+use crate::parser::{read_xml, Error as ParserError};
+use crate::shared::error::{
+    Error, Result, MSG_INVALID_XINCLUDE, MSG_XINCLUDE_LOOP, MSG_XINCLUDE_NOT_FOUND,
+    MSG_XINCLUDE_NOT_WELL_FORMED,
+};
+use crate::shared::name::Name;
+use crate::shared::uri;
+use std::collections::HashMap;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The namespace of the XInclude elements.
+///
+pub const XINCLUDE_NS_URI: &str = "http://www.w3.org/2001/XInclude";
+
+///
+/// A source of the resources included by `xi:include` elements.
+///
+/// This is implemented for a `HashMap<String, String>` from URI to content, and for any function
+/// or closure with the same signature as [`resolve`](#tymethod.resolve).
+///
+pub trait IncludeResolver {
+    ///
+    /// Return the content of the resource at `uri`, or an error if the resource cannot be found,
+    /// or may not be included. Any error is a resource error, and so the fallback of the
+    /// `xi:include` element, if it has one, is used.
+    ///
+    fn resolve(&self, uri: &str) -> Result<String>;
+}
+
+///
+/// An extended interface to process the XInclude elements of a document.
+///
+pub trait XIncludes: Document {
+    ///
+    /// Replace each `xi:include` element in this document with the content it refers to, using
+    /// `resolver` to read each resource; see the [module](index.html) documentation.
+    ///
+    /// If an error is returned the document may have been partly processed.
+    ///
+    /// **Exceptions**
+    ///
+    /// * `SYNTAX_ERR`: Raised if an `xi:include` element is not valid, for example it has neither
+    ///   an `href` nor an `xpointer` attribute, an unknown `parse` value, or more than one
+    ///   `xi:fallback` child; or if an included resource is not well-formed.
+    /// * `NOT_FOUND_ERR`: Raised if a resource could not be included and the `xi:include`
+    ///   element has no `xi:fallback` child.
+    /// * `HIERARCHY_REQUEST_ERR`: Raised if a resource includes itself, or if the included
+    ///   content may not replace the `xi:include` element, for example a second document element.
+    ///
+    fn process_xincludes(&mut self, resolver: &dyn IncludeResolver) -> Result<()>;
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+const XINCLUDE_INCLUDE: &str = "include";
+const XINCLUDE_FALLBACK: &str = "fallback";
+const XINCLUDE_ATTR_HREF: &str = "href";
+const XINCLUDE_ATTR_PARSE: &str = "parse";
+const XINCLUDE_ATTR_XPOINTER: &str = "xpointer";
+const XINCLUDE_PARSE_XML: &str = "xml";
+const XINCLUDE_PARSE_TEXT: &str = "text";
+
+//
+// A resource, and the element selected from it, that is being included; used to find loops.
+//
+type Inclusion = (String, Option<String>);
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl IncludeResolver for HashMap<String, String> {
+    fn resolve(&self, uri: &str) -> Result<String> {
+        self.get(uri).cloned().ok_or(Error::NotFound)
+    }
+}
+
+impl<F> IncludeResolver for F
+where
+    F: Fn(&str) -> Result<String>,
+{
+    fn resolve(&self, uri: &str) -> Result<String> {
+        self(uri)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl XIncludes for RefNode {
+    fn process_xincludes(&mut self, resolver: &dyn IncludeResolver) -> Result<()> {
+        let base = self.document_uri();
+        let mut inclusions = vec![(base.clone().unwrap_or_default(), None)];
+        process_tree(self, base.as_deref(), resolver, &mut inclusions)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+//
+// Process each `xi:include` element below `root`, in document order. Content that replaces an
+// `xi:include` element has already been processed, but the children of a fallback are processed
+// as they are reached. The base URI of `root` is given as it may be a fragment holding content
+// from another resource.
+//
+fn process_tree(
+    root: &RefNode,
+    base: Option<&str>,
+    resolver: &dyn IncludeResolver,
+    inclusions: &mut Vec<Inclusion>,
+) -> Result<()> {
+    for element in root.descendant_elements().collect::<Vec<RefNode>>() {
+        if is_xinclude(&element, XINCLUDE_INCLUDE) && root.contains(&element) {
+            process_include(&element, base, resolver, inclusions)?;
+        }
+    }
+    Ok(())
+}
+
+fn process_include(
+    include: &RefNode,
+    base: Option<&str>,
+    resolver: &dyn IncludeResolver,
+    inclusions: &mut Vec<Inclusion>,
+) -> Result<()> {
+    let href = attribute_value(include, XINCLUDE_ATTR_HREF).unwrap_or_default();
+    let xpointer = attribute_value(include, XINCLUDE_ATTR_XPOINTER);
+    let parse = attribute_value(include, XINCLUDE_ATTR_PARSE)
+        .unwrap_or_else(|| XINCLUDE_PARSE_XML.to_string());
+    let mut fallbacks = Vec::new();
+    for child_node in include.child_nodes() {
+        if is_xinclude(&child_node, XINCLUDE_FALLBACK) {
+            fallbacks.push(child_node);
+        } else if is_xinclude(&child_node, XINCLUDE_INCLUDE) {
+            return invalid_include();
+        }
+    }
+    if (href.is_empty() && xpointer.is_none())
+        || href.contains('#')
+        || fallbacks.len() > 1
+        || (parse == XINCLUDE_PARSE_TEXT && xpointer.is_some())
+        || (parse != XINCLUDE_PARSE_TEXT && parse != XINCLUDE_PARSE_XML)
+    {
+        return invalid_include();
+    }
+
+    let document_node = include.owner_document().unwrap();
+    let uri = match (href.is_empty(), base_uri(include, base)) {
+        (true, _) => None,
+        (false, None) => Some(href),
+        (false, Some(base)) => Some(uri::resolve_reference(&base, &href)),
+    };
+    let nodes = if parse == XINCLUDE_PARSE_TEXT {
+        match resolver.resolve(uri.as_deref().unwrap_or_default()) {
+            Err(_) => None,
+            Ok(text) => Some(vec![
+                as_document(&document_node)?.try_create_text_node(&text)?
+            ]),
+        }
+    } else {
+        include_xml(include, uri, xpointer, resolver, inclusions)?
+    };
+
+    let nodes = match (nodes, fallbacks.pop()) {
+        (Some(nodes), _) => nodes,
+        (None, Some(fallback)) => fallback.child_nodes().to_vec(),
+        (None, None) => {
+            warn!("{}", MSG_XINCLUDE_NOT_FOUND);
+            return Err(Error::NotFound);
+        }
+    };
+    replace_include(include, nodes)
+}
+
+//
+// The nodes included from the XML resource at `uri`, or from the document of `include` if there
+// is no `uri`, imported into the document of `include`; returns `None` on a resource error.
+//
+fn include_xml(
+    include: &RefNode,
+    uri: Option<String>,
+    xpointer: Option<String>,
+    resolver: &dyn IncludeResolver,
+    inclusions: &mut Vec<Inclusion>,
+) -> Result<Option<Vec<RefNode>>> {
+    let document_node = include.owner_document().unwrap();
+    let source_uri = match &uri {
+        None => document_node.document_uri().unwrap_or_default(),
+        Some(uri) => uri.clone(),
+    };
+    let inclusion = (source_uri, xpointer.clone());
+    if inclusions.contains(&inclusion) {
+        warn!("{}", MSG_XINCLUDE_LOOP);
+        return Err(Error::HierarchyRequest);
+    }
+    if let Some(xpointer) = &xpointer {
+        if xpointer.contains('(') {
+            return Ok(None);
+        }
+    }
+
+    let source_node = match &uri {
+        None => document_node.clone(),
+        Some(uri) => {
+            let text = match resolver.resolve(uri) {
+                Err(_) => return Ok(None),
+                Ok(text) => text,
+            };
+            let mut source_node = read_xml(&text).map_err(|e| {
+                warn!("{}: {}", MSG_XINCLUDE_NOT_WELL_FORMED, e);
+                match e.error() {
+                    ParserError::HierarchyRequest => Error::HierarchyRequest,
+                    ParserError::InvalidCharacter => Error::InvalidCharacter,
+                    ParserError::NotSupported => Error::NotSupported,
+                    _ => Error::Syntax,
+                }
+            })?;
+            source_node.set_document_uri(uri)?;
+            source_node
+        }
+    };
+    let selected = match &xpointer {
+        None => source_node
+            .child_nodes()
+            .into_iter()
+            .filter(|child_node| child_node.node_type() != NodeType::DocumentType)
+            .collect(),
+        Some(xpointer) => match source_node.get_element_by_id(xpointer) {
+            None => return Ok(None),
+            Some(element) if element.contains(include) => {
+                warn!("{}", MSG_XINCLUDE_LOOP);
+                return Err(Error::HierarchyRequest);
+            }
+            Some(element) => vec![element],
+        },
+    };
+
+    let document = as_document(&document_node)?;
+    let mut nodes = Vec::new();
+    let source_base = uri.clone().or_else(|| document_node.document_uri());
+    inclusions.push(inclusion);
+    for node in selected {
+        let node = if uri.is_none() {
+            node.clone_node(true).unwrap()
+        } else {
+            document.import_node(node, true)?
+        };
+        //
+        // A copy of a single element is processed through a temporary fragment, so that an
+        // `xi:include` at its top is also replaced.
+        //
+        let mut fragment_node = document.create_document_fragment()?;
+        let _safe_to_ignore = fragment_node.append_child(node)?;
+        process_tree(&fragment_node, source_base.as_deref(), resolver, inclusions)?;
+        nodes.extend(fragment_node.child_nodes().to_vec());
+    }
+    let _safe_to_ignore = inclusions.pop();
+    Ok(Some(nodes))
+}
+
+//
+// Replace `include` with `nodes`; the element is removed first so that a document element may be
+// replaced by another.
+//
+fn replace_include(include: &RefNode, nodes: Vec<RefNode>) -> Result<()> {
+    let mut parent_node = include.parent_node().unwrap();
+    let next_node = include.next_sibling();
+    let _safe_to_ignore = parent_node.remove_child(include.clone())?;
+    for node in nodes {
+        let _safe_to_ignore = parent_node.insert_before(node, next_node.clone())?;
+    }
+    Ok(())
+}
+
+//
+// The namespace of an element is taken from its declarations in scope, as the parser does not
+// set the namespace of each element.
+//
+fn is_xinclude(node: &RefNode, local_name: &str) -> bool {
+    is_element(node) && node.local_name() == local_name && {
+        let name = node.node_name();
+        let namespace_uri = match name.namespace_uri() {
+            Some(namespace_uri) => Some(namespace_uri.clone()),
+            None => node.lookup_namespace_uri(name.prefix().as_deref()),
+        };
+        namespace_uri.as_deref() == Some(XINCLUDE_NS_URI)
+    }
+}
+
+//
+// The base URI of `element`, from the `xml:base` attributes of it and its ancestors, resolved
+// against `base` rather than the URI of its document.
+//
+fn base_uri(element: &RefNode, base: Option<&str>) -> Option<String> {
+    std::iter::once(element.clone())
+        .chain(element.ancestors().filter(is_element))
+        .filter_map(|element| attribute_value(&element, Name::XML_BASE))
+        .collect::<Vec<String>>()
+        .into_iter()
+        .rev()
+        .fold(base.map(String::from), |base, reference| match base {
+            None => Some(reference),
+            Some(base) => Some(uri::resolve_reference(&base, &reference)),
+        })
+}
+
+fn attribute_value(element: &RefNode, name: &str) -> Option<String> {
+    element
+        .get_attribute_node(name)
+        .and_then(|attribute_node| normalized_value(&attribute_node))
+}
+
+fn invalid_include<T>() -> Result<T> {
+    warn!("{}", MSG_INVALID_XINCLUDE);
+    Err(Error::Syntax)
+}
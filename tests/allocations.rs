@@ -0,0 +1,71 @@
+#![cfg(feature = "quick_parser")]
+
+//
+// A micro-benchmark of the allocations made by the child access methods, using a global allocator
+// that counts the allocations made on the current thread.
+//
+use common::allocator::{thread_allocations, CountingAllocator};
+use xml_dom::level2::ext::*;
+use xml_dom::level2::*;
+use xml_dom::parser::read_xml;
+
+mod common;
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const ITERATIONS: usize = 10_000;
+
+fn allocations<F: FnMut(usize)>(mut f: F) -> usize {
+    let before = thread_allocations();
+    for index in 0..ITERATIONS {
+        f(index);
+    }
+    thread_allocations() - before
+}
+
+#[test]
+fn test_child_access_allocations() {
+    let xml = format!("<root>{}</root>", "<child/>".repeat(100));
+    let document_node = read_xml(&xml).unwrap();
+    let root_node = document_node.first_child().unwrap();
+
+    assert!(
+        allocations(|_| {
+            assert_eq!(
+                root_node.child_nodes().to_vec()[0].node_name().to_string(),
+                "child"
+            );
+        }) >= ITERATIONS
+    );
+    assert_eq!(
+        allocations(|_| {
+            assert!(root_node.first_child().is_some());
+        }),
+        0
+    );
+    assert_eq!(
+        allocations(|_| {
+            assert!(root_node.last_child().is_some());
+        }),
+        0
+    );
+    assert_eq!(
+        allocations(|_| {
+            assert!(root_node.has_child_nodes());
+        }),
+        0
+    );
+    assert_eq!(
+        allocations(|_| {
+            assert_eq!(root_node.child_node_count(), 100);
+        }),
+        0
+    );
+    assert_eq!(
+        allocations(|index| {
+            assert!(root_node.child(index % 100).is_some());
+        }),
+        0
+    );
+}
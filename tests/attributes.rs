@@ -1,5 +1,11 @@
-use xml_dom::level2::convert::{as_attribute, as_attribute_mut, as_document, as_element_mut};
+use xml_dom::level2::convert::{
+    as_attribute, as_attribute_mut, as_document, as_element, as_element_mut,
+};
+use xml_dom::level2::ext::convert::as_document_type_decl_mut;
+use xml_dom::level2::ext::{AttributeDecl, AttributeDefault, AttributeType};
 use xml_dom::level2::*;
+#[cfg(feature = "quick_parser")]
+use xml_dom::parser::read_xml;
 pub mod common;
 
 #[test]
@@ -110,6 +116,132 @@ fn test_model_parent_owner() {
     assert!(!attribute.has_attributes());
 }
 
+#[test]
+fn test_set_attribute_node_replaces() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    let mut element_node = document.document_element().unwrap();
+
+    let first = document.create_attribute_with("test", "one").unwrap();
+    assert_eq!(element_node.set_attribute_node(first.clone()), Ok(None));
+    assert_eq!(element_node.get_attribute_node("test"), Some(first.clone()));
+
+    //
+    // Setting an attribute already owned by this element changes nothing.
+    //
+    assert_eq!(element_node.set_attribute_node(first.clone()), Ok(None));
+
+    let second = document.create_attribute_with("test", "two").unwrap();
+    assert_eq!(
+        element_node.set_attribute_node(second.clone()),
+        Ok(Some(first.clone()))
+    );
+    assert!(first.owner_element().is_none());
+    assert_eq!(second.owner_element(), Some(element_node.clone()));
+    assert_eq!(element_node.get_attribute("test"), Some("two".to_string()));
+
+    let first_ns = document
+        .create_attribute_ns(common::DC_NS, "a:test")
+        .unwrap();
+    assert_eq!(
+        element_node.set_attribute_node_ns(first_ns.clone()),
+        Ok(None)
+    );
+    let second_ns = document
+        .create_attribute_ns(common::DC_NS, "b:test")
+        .unwrap();
+    assert_eq!(
+        element_node.set_attribute_node_ns(second_ns),
+        Ok(Some(first_ns))
+    );
+}
+
+#[test]
+fn test_set_attribute_node_in_use() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    let mut element_node = document.document_element().unwrap();
+    let mut other_node = document.create_element("other").unwrap();
+    let _safe_to_ignore = element_node.append_child(other_node.clone()).unwrap();
+
+    let attribute_node = document.create_attribute_with("test", "value").unwrap();
+    assert!(element_node
+        .set_attribute_node(attribute_node.clone())
+        .is_ok());
+    assert_eq!(
+        other_node.set_attribute_node(attribute_node.clone()),
+        Err(Error::InUseAttribute)
+    );
+    assert_eq!(
+        other_node.set_attribute_node_ns(attribute_node.clone()),
+        Err(Error::InUseAttribute)
+    );
+    assert_eq!(
+        other_node.remove_attribute_node(attribute_node.clone()),
+        Err(Error::NotFound)
+    );
+
+    //
+    // Once removed the attribute, and its children, can be moved to the other element.
+    //
+    let attribute_node = element_node.remove_attribute_node(attribute_node).unwrap();
+    assert!(attribute_node.owner_element().is_none());
+    assert!(!element_node.has_attribute("test"));
+    assert_eq!(
+        other_node.set_attribute_node(attribute_node.clone()),
+        Ok(None)
+    );
+    assert_eq!(attribute_node.owner_element(), Some(other_node.clone()));
+    let text_node = attribute_node.first_child().unwrap();
+    assert_eq!(text_node.node_type(), NodeType::Text);
+    assert_eq!(text_node.node_value(), Some("value".to_string()));
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_owner_element() {
+    let document_node = read_xml(r#"<root a="1" b="2"><child c="3"/></root>"#).unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let mut child_node = root_node.first_child().unwrap();
+    for attribute_node in root_node.attributes().values() {
+        assert_eq!(attribute_node.owner_element(), Some(root_node.clone()));
+    }
+
+    assert!(root_node.set_attribute("d", "4").is_ok());
+    let attribute_node = root_node.get_attribute_node("d").unwrap();
+    assert_eq!(attribute_node.owner_element(), Some(root_node.clone()));
+    assert!(attribute_node
+        .clone_node(true)
+        .unwrap()
+        .owner_element()
+        .is_none());
+
+    //
+    // Moving an attribute between elements updates the link, and removal clears it.
+    //
+    let attribute_node = root_node.get_attribute_node("a").unwrap();
+    let attribute_node = root_node.remove_attribute_node(attribute_node).unwrap();
+    assert!(attribute_node.owner_element().is_none());
+    assert!(child_node
+        .set_attribute_node(attribute_node.clone())
+        .is_ok());
+    assert_eq!(attribute_node.owner_element(), Some(child_node.clone()));
+    assert!(child_node.remove_attribute("a").is_ok());
+    assert!(attribute_node.owner_element().is_none());
+
+    //
+    // A cloned element owns its own copies of the attributes.
+    //
+    let clone_node = child_node.clone_node(true).unwrap();
+    let cloned_attribute = clone_node.get_attribute_node("c").unwrap();
+    assert_eq!(cloned_attribute.owner_element(), Some(clone_node.clone()));
+    assert_ne!(
+        cloned_attribute,
+        child_node.get_attribute_node("c").unwrap()
+    );
+}
+
 #[test]
 fn test_model_siblings() {
     //
@@ -243,3 +375,206 @@ fn test_normalization_entity_not_found() {
         Some("hello£world".to_string())
     );
 }
+
+#[test]
+fn test_attribute_insertion_order() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    let mut element_node = document.create_element("test").unwrap();
+    let element = as_element_mut(&mut element_node).unwrap();
+    element.set_attribute("z", "1").unwrap();
+    element.set_attribute("a", "2").unwrap();
+    element.set_attribute("m", "3").unwrap();
+    assert_eq!(
+        element_node.to_string(),
+        r#"<test z="1" a="2" m="3"></test>"#
+    );
+
+    // replacing an attribute keeps its original position.
+    let element = as_element_mut(&mut element_node).unwrap();
+    element.set_attribute("a", "4").unwrap();
+    let replacement = document.create_attribute_with("z", "5").unwrap();
+    element.set_attribute_node(replacement).unwrap();
+    assert_eq!(
+        element_node.to_string(),
+        r#"<test z="5" a="4" m="3"></test>"#
+    );
+
+    // removing and re-adding an attribute moves it to the end.
+    let element = as_element_mut(&mut element_node).unwrap();
+    element.remove_attribute("z").unwrap();
+    element.set_attribute("z", "6").unwrap();
+    assert_eq!(
+        element_node.to_string(),
+        r#"<test a="4" m="3" z="6"></test>"#
+    );
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_attribute_order_round_trip() {
+    let xml = r#"<?xml version="1.0"?><root zeta="1" alpha="2" mu="3" beta="4"/>"#;
+    let document_node = read_xml(xml).unwrap();
+    let document = as_document(&document_node).unwrap();
+    let root = document.document_element().unwrap();
+    assert_eq!(
+        root.to_string(),
+        r#"<root zeta="1" alpha="2" mu="3" beta="4"></root>"#
+    );
+}
+
+#[test]
+fn test_attributes_in_order() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    let mut element_node = document.document_element().unwrap();
+    let element = as_element_mut(&mut element_node).unwrap();
+    for (name, value) in [("zeta", "1"), ("alpha", "2"), ("mu", "3"), ("alpha", "two")] {
+        assert!(element.set_attribute(name, value).is_ok());
+    }
+    let names = |element: &RefNode| -> Vec<String> {
+        element
+            .attributes()
+            .keys()
+            .map(|name| name.to_string())
+            .collect()
+    };
+    assert_eq!(names(&element_node), vec!["zeta", "alpha", "mu"]);
+
+    let element = as_element_mut(&mut element_node).unwrap();
+    assert!(element.remove_attribute("alpha").is_ok());
+    assert!(element.set_attribute("alpha", "2").is_ok());
+    assert_eq!(names(&element_node), vec!["zeta", "mu", "alpha"]);
+}
+
+#[test]
+fn test_default_attributes() {
+    let implementation = get_implementation();
+    let mut document_type = implementation
+        .create_document_type("catalog", None, None)
+        .unwrap();
+    let doc_type_decl = as_document_type_decl_mut(&mut document_type).unwrap();
+    for (attribute_name, default) in [
+        ("type", AttributeDefault::Value("ordered".to_string())),
+        ("version", AttributeDefault::Fixed("1.0".to_string())),
+        ("style", AttributeDefault::Implied),
+    ] {
+        let attribute_decl =
+            AttributeDecl::new("list", attribute_name, AttributeType::CData, default).unwrap();
+        assert_eq!(doc_type_decl.add_attribute_decl(attribute_decl), Ok(true));
+    }
+    let duplicate = AttributeDecl::new(
+        "list",
+        "type",
+        AttributeType::Enumeration(vec!["bullets".to_string()]),
+        AttributeDefault::Value("bullets".to_string()),
+    )
+    .unwrap();
+    assert_eq!(doc_type_decl.add_attribute_decl(duplicate), Ok(false));
+    assert_eq!(doc_type_decl.attribute_decls().len(), 3);
+    assert_eq!(
+        document_type.to_string(),
+        r#"<!DOCTYPE catalog [<!ATTLIST list type CDATA "ordered"><!ATTLIST list version CDATA #FIXED "1.0"><!ATTLIST list style CDATA #IMPLIED>]>"#
+    );
+
+    let document_node = implementation
+        .create_document(None, Some("catalog"), Some(document_type))
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+
+    let mut element_node = document.create_element("list").unwrap();
+    let element = as_element_mut(&mut element_node).unwrap();
+    assert_eq!(element.attributes().len(), 2);
+    assert_eq!(element.get_attribute("type"), Some("ordered".to_string()));
+    assert_eq!(element.get_attribute("version"), Some("1.0".to_string()));
+    assert!(!element.has_attribute("style"));
+    let attribute_node = element.get_attribute_node("type").unwrap();
+    assert!(!as_attribute(&attribute_node).unwrap().specified());
+
+    // setting a value makes the attribute specified.
+    element.set_attribute("type", "bullets").unwrap();
+    let attribute_node = element.get_attribute_node("type").unwrap();
+    assert!(as_attribute(&attribute_node).unwrap().specified());
+
+    // removing the attribute restores the default.
+    element.remove_attribute("type").unwrap();
+    assert_eq!(element.get_attribute("type"), Some("ordered".to_string()));
+    let default_node = element.get_attribute_node("type").unwrap();
+    assert!(!as_attribute(&default_node).unwrap().specified());
+    assert_ne!(default_node, attribute_node);
+    assert!(as_attribute(&attribute_node).unwrap().specified());
+
+    // elements of other types are unaffected.
+    let other_node = document.create_element("item").unwrap();
+    assert!(!as_element(&other_node).unwrap().has_attributes());
+}
+
+#[test]
+fn test_default_attributes_not_copied() {
+    let implementation = get_implementation();
+    let mut document_type = implementation
+        .create_document_type("catalog", None, None)
+        .unwrap();
+    let doc_type_decl = as_document_type_decl_mut(&mut document_type).unwrap();
+    let attribute_decl = AttributeDecl::new(
+        "list",
+        "type",
+        AttributeType::CData,
+        AttributeDefault::Value("ordered".to_string()),
+    )
+    .unwrap();
+    assert_eq!(doc_type_decl.add_attribute_decl(attribute_decl), Ok(true));
+    let document_node = implementation
+        .create_document(None, Some("catalog"), Some(document_type))
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+
+    let mut element_node = document.create_element("list").unwrap();
+    let element = as_element_mut(&mut element_node).unwrap();
+    element.set_attribute("id", "one").unwrap();
+
+    // a clone in the same document gets new default attributes.
+    let clone_node = element_node.clone_node(false).unwrap();
+    let clone = as_element(&clone_node).unwrap();
+    assert_eq!(clone.get_attribute("id"), Some("one".to_string()));
+    let default_node = clone.get_attribute_node("type").unwrap();
+    assert!(!as_attribute(&default_node).unwrap().specified());
+    assert_ne!(
+        Some(default_node),
+        as_element(&element_node)
+            .unwrap()
+            .get_attribute_node("type")
+    );
+
+    // an import into a document without the declaration gets none.
+    let other_document_node = implementation
+        .create_document(None, Some("catalog"), None)
+        .unwrap();
+    let other_document = as_document(&other_document_node).unwrap();
+    let imported_node = other_document
+        .import_node(element_node.clone(), true)
+        .unwrap();
+    let imported = as_element(&imported_node).unwrap();
+    assert_eq!(imported.get_attribute("id"), Some("one".to_string()));
+    assert!(!imported.has_attribute("type"));
+
+    // an import back into the document with the declaration gets the default again.
+    let imported_node = document.import_node(imported_node, true).unwrap();
+    let default_node = as_element(&imported_node)
+        .unwrap()
+        .get_attribute_node("type")
+        .unwrap();
+    assert!(!as_attribute(&default_node).unwrap().specified());
+
+    // an adopted element loses the defaults of its old document, and gains those of the new.
+    let adopted_node = other_document.adopt_node(element_node).unwrap();
+    let adopted = as_element(&adopted_node).unwrap();
+    assert_eq!(adopted.get_attribute("id"), Some("one".to_string()));
+    assert!(!adopted.has_attribute("type"));
+    let adopted_node = document.adopt_node(adopted_node).unwrap();
+    let adopted = as_element(&adopted_node).unwrap();
+    assert_eq!(adopted.get_attribute("type"), Some("ordered".to_string()));
+    let default_node = adopted.get_attribute_node("type").unwrap();
+    assert!(!as_attribute(&default_node).unwrap().specified());
+    assert_eq!(default_node.owner_document(), Some(document_node.clone()));
+}
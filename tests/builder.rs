@@ -0,0 +1,85 @@
+#![cfg(feature = "quick_parser")]
+
+use xml_dom::level2::builder::*;
+use xml_dom::level2::*;
+use xml_dom::parser::read_xml;
+
+#[test]
+fn test_into_node() {
+    let document_node = read_xml("<catalog></catalog>").unwrap();
+    let item_node = document_node
+        .build_element("item")
+        .attribute("id", "1")
+        .attribute("id", "2")
+        .text("one")
+        .cdata("<two/>")
+        .comment("three")
+        .child(ElementBuilder::new("four").attribute("n", "4"))
+        .into_node(&document_node)
+        .unwrap();
+
+    assert!(item_node.parent_node().is_none());
+    assert_eq!(item_node.owner_document(), Some(document_node.clone()));
+    assert_eq!(
+        item_node.to_string(),
+        r#"<item id="2">one<![CDATA[<two/>]]><!--three--><four n="4"></four></item>"#
+    );
+    assert_eq!(document_node.to_string(), "<catalog></catalog>");
+}
+
+#[test]
+fn test_attach_to() {
+    let mut document_node = get_implementation()
+        .create_document(None, None, None)
+        .unwrap();
+    let mut root_node = document_node
+        .build_element("catalog")
+        .attach_to(&mut document_node)
+        .unwrap();
+    assert_eq!(document_node.document_element(), Some(root_node.clone()));
+
+    let item_node = ElementBuilder::new("item")
+        .child(ElementBuilder::new("name").text("one"))
+        .attach_to(&mut root_node)
+        .unwrap();
+    assert_eq!(item_node.parent_node(), Some(root_node));
+    assert_eq!(
+        document_node.to_string(),
+        "<catalog><item><name>one</name></item></catalog>"
+    );
+}
+
+#[test]
+fn test_errors() {
+    let mut document_node = read_xml("<catalog></catalog>").unwrap();
+    let mut root_node = document_node.document_element().unwrap();
+
+    assert_eq!(
+        ElementBuilder::new("item").into_node(&root_node),
+        Err(Error::InvalidState)
+    );
+    assert_eq!(
+        ElementBuilder::new("1item").into_node(&document_node),
+        Err(Error::InvalidCharacter)
+    );
+    assert_eq!(
+        ElementBuilder::new("item")
+            .child(ElementBuilder::new("name").comment("a -- b"))
+            .attach_to(&mut root_node),
+        Err(Error::InvalidCharacter)
+    );
+    assert_eq!(
+        ElementBuilder::new("item")
+            .cdata("]]>")
+            .child(ElementBuilder::new("1name"))
+            .attach_to(&mut root_node),
+        Err(Error::InvalidCharacter)
+    );
+    assert!(!root_node.has_child_nodes());
+
+    assert_eq!(
+        ElementBuilder::new("second").attach_to(&mut document_node),
+        Err(Error::HierarchyRequest)
+    );
+    assert_eq!(document_node.to_string(), "<catalog></catalog>");
+}
@@ -0,0 +1,45 @@
+//
+// A global allocator that counts allocations, for the tests that measure memory use. A test file
+// installs it with:
+//
+// #[global_allocator]
+// static GLOBAL: CountingAllocator = CountingAllocator;
+//
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub struct CountingAllocator;
+
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static THREAD_ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _safe_to_ignore = ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::SeqCst);
+        let _safe_to_ignore = THREAD_ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let _safe_to_ignore = ALLOCATED_BYTES.fetch_sub(layout.size(), Ordering::SeqCst);
+        System.dealloc(ptr, layout)
+    }
+}
+
+///
+/// The number of bytes currently allocated, by all threads.
+///
+pub fn allocated_bytes() -> usize {
+    ALLOCATED_BYTES.load(Ordering::SeqCst)
+}
+
+///
+/// The number of allocations made so far by the current thread.
+///
+pub fn thread_allocations() -> usize {
+    THREAD_ALLOCATIONS.with(|count| count.get())
+}
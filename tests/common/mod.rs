@@ -1,5 +1,7 @@
 #![allow(dead_code)]
 
+pub mod allocator;
+
 use std::fmt::Display;
 use xml_dom::level2::convert::*;
 use xml_dom::level2::ext::dom_impl::get_implementation_ext;
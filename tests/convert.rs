@@ -0,0 +1,201 @@
+use std::convert::{TryFrom, TryInto};
+use xml_dom::level2::convert::*;
+use xml_dom::level2::ext::dom_impl as ext_dom_impl;
+use xml_dom::level2::*;
+
+type IsFn = fn(&RefNode) -> bool;
+type AsFn = fn(&RefNode) -> Result<()>;
+type AsMutFn = fn(&mut RefNode) -> Result<()>;
+
+fn create_nodes() -> Vec<RefNode> {
+    let implementation = get_implementation();
+    let document_type = implementation
+        .create_document_type("root", None, None)
+        .unwrap();
+    let document_node = implementation
+        .create_document(None, Some("root"), Some(document_type.clone()))
+        .unwrap();
+    vec![
+        document_node.create_attribute("attribute").unwrap(),
+        document_node.create_cdata_section("cdata").unwrap(),
+        document_node.create_comment("comment").unwrap(),
+        document_node.clone(),
+        document_node.create_document_fragment().unwrap(),
+        document_type,
+        document_node.document_element().unwrap(),
+        ext_dom_impl::create_internal_entity(document_node.clone(), "entity", "value").unwrap(),
+        document_node.create_entity_reference("entity").unwrap(),
+        ext_dom_impl::create_notation(document_node.clone(), "notation", None, Some("n")).unwrap(),
+        document_node
+            .create_processing_instruction("target", None)
+            .unwrap(),
+        document_node.create_text_node("text"),
+    ]
+}
+
+#[test]
+fn test_conversion_matrix() {
+    let matrix: Vec<(&str, IsFn, AsFn, AsMutFn, Vec<NodeType>)> = vec![
+        (
+            "attribute",
+            is_attribute,
+            |node| as_attribute(node).map(|_| ()),
+            |node| as_attribute_mut(node).map(|_| ()),
+            vec![NodeType::Attribute],
+        ),
+        (
+            "cdata_section",
+            is_cdata_section,
+            |node| as_cdata_section(node).map(|_| ()),
+            |node| as_cdata_section_mut(node).map(|_| ()),
+            vec![NodeType::CData],
+        ),
+        (
+            "character_data",
+            is_character_data,
+            |node| as_character_data(node).map(|_| ()),
+            |node| as_character_data_mut(node).map(|_| ()),
+            vec![NodeType::CData, NodeType::Comment, NodeType::Text],
+        ),
+        (
+            "comment",
+            is_comment,
+            |node| as_comment(node).map(|_| ()),
+            |node| as_comment_mut(node).map(|_| ()),
+            vec![NodeType::Comment],
+        ),
+        (
+            "document",
+            is_document,
+            |node| as_document(node).map(|_| ()),
+            |node| as_document_mut(node).map(|_| ()),
+            vec![NodeType::Document],
+        ),
+        (
+            "document_fragment",
+            is_document_fragment,
+            |node| as_document_fragment(node).map(|_| ()),
+            |node| as_document_fragment_mut(node).map(|_| ()),
+            vec![NodeType::DocumentFragment],
+        ),
+        (
+            "document_type",
+            is_document_type,
+            |node| as_document_type(node).map(|_| ()),
+            |node| as_document_type_mut(node).map(|_| ()),
+            vec![NodeType::DocumentType],
+        ),
+        (
+            "element",
+            is_element,
+            |node| as_element(node).map(|_| ()),
+            |node| as_element_mut(node).map(|_| ()),
+            vec![NodeType::Element],
+        ),
+        (
+            "entity",
+            is_entity,
+            |node| as_entity(node).map(|_| ()),
+            |node| as_entity_mut(node).map(|_| ()),
+            vec![NodeType::Entity],
+        ),
+        (
+            "entity_reference",
+            is_entity_reference,
+            |node| as_entity_reference(node).map(|_| ()),
+            |node| as_entity_reference_mut(node).map(|_| ()),
+            vec![NodeType::EntityReference],
+        ),
+        (
+            "notation",
+            is_notation,
+            |node| as_notation(node).map(|_| ()),
+            |node| as_notation_mut(node).map(|_| ()),
+            vec![NodeType::Notation],
+        ),
+        (
+            "processing_instruction",
+            is_processing_instruction,
+            |node| as_processing_instruction(node).map(|_| ()),
+            |node| as_processing_instruction_mut(node).map(|_| ()),
+            vec![NodeType::ProcessingInstruction],
+        ),
+        (
+            "text",
+            is_text,
+            |node| as_text(node).map(|_| ()),
+            |node| as_text_mut(node).map(|_| ()),
+            vec![NodeType::Text],
+        ),
+    ];
+
+    let mut nodes = create_nodes();
+    assert_eq!(nodes.len(), 12);
+    for (index, node) in nodes.iter().enumerate() {
+        assert!(!nodes[..index]
+            .iter()
+            .any(|other| other.node_type() == node.node_type()));
+    }
+    for (name, is_fn, as_fn, as_mut_fn, node_types) in matrix {
+        for node in nodes.iter_mut() {
+            let node_type = node.node_type();
+            let expected = if node_types.contains(&node_type) {
+                Ok(())
+            } else {
+                Err(Error::InvalidState)
+            };
+            assert_eq!(
+                is_fn(node),
+                expected.is_ok(),
+                "is_{} of {:?}",
+                name,
+                node_type
+            );
+            assert_eq!(as_fn(node), expected, "as_{} of {:?}", name, node_type);
+            assert_eq!(
+                as_mut_fn(node),
+                expected,
+                "as_{}_mut of {:?}",
+                name,
+                node_type
+            );
+        }
+    }
+}
+
+#[test]
+fn test_try_from() {
+    let mut nodes = create_nodes();
+    for node in nodes.iter_mut() {
+        let node_type = node.node_type();
+        assert_eq!(
+            RefElement::try_from(&*node).is_ok(),
+            node_type == NodeType::Element
+        );
+        let result: Result<MutRefCharacterData<'_>> = node.try_into();
+        assert_eq!(result.map(|_| ()), as_character_data(node).map(|_| ()),);
+    }
+
+    let mut text_node = nodes.pop().unwrap();
+    let text: MutRefText<'_> = (&mut text_node).try_into().unwrap();
+    text.set_data("changed").unwrap();
+    assert_eq!(text_node.node_value(), Some("changed".to_string()));
+    assert_eq!(
+        RefDocument::try_from(&text_node).map(|_| ()),
+        Err(Error::InvalidState)
+    );
+}
+
+#[test]
+fn test_node_type_codes() {
+    for node in create_nodes() {
+        let node_type = node.node_type();
+        let code = u16::from(node_type);
+        assert_eq!(code, node_type as u16);
+        assert_eq!(NodeType::try_from(code), Ok(node_type));
+    }
+    assert_eq!(u16::from(NodeType::Element), 1);
+    assert_eq!(u16::from(NodeType::Notation), 12);
+    assert_eq!(NodeType::try_from(0), Err(Error::NotSupported));
+    assert_eq!(NodeType::try_from(13), Err(Error::NotSupported));
+}
@@ -0,0 +1,99 @@
+#![cfg(feature = "quick_parser")]
+
+use xml_dom::diff::*;
+use xml_dom::parser::read_xml;
+
+fn differences(left: &str, right: &str, options: &DiffOptions) -> Vec<String> {
+    diff(&read_xml(left).unwrap(), &read_xml(right).unwrap(), options)
+        .iter()
+        .map(|difference| difference.to_string())
+        .collect()
+}
+
+#[test]
+fn test_equal_trees() {
+    let document_node = read_xml(r#"<a x="1" y="2"><b>text</b><!--c--><?pi data?></a>"#).unwrap();
+    assert!(diff(&document_node, &document_node, &DiffOptions::default()).is_empty());
+
+    //
+    // Attribute order is not significant.
+    //
+    assert!(differences(
+        r#"<a x="1" y="2"/>"#,
+        r#"<a y="2" x="1"/>"#,
+        &DiffOptions::default()
+    )
+    .is_empty());
+}
+
+#[test]
+fn test_differences() {
+    assert_eq!(
+        differences(
+            r#"<root><item id="1"/><item id="2">two</item><item id="3" extra="x"/><last/></root>"#,
+            r#"<root><item id="1"/><item id="9">2</item><item id="3" more="y"/><final/><added/></root>"#,
+            &DiffOptions::default()
+        ),
+        vec![
+            r#"/root/item[2]/@id: different attribute value, "2" != "9""#,
+            r#"/root/item[2]/text(): different text, "two" != "2""#,
+            r#"/root/item[3]/@extra: missing node, "x""#,
+            r#"/root/item[3]/@more: extra node, "y""#,
+            r#"/root/last: different name, "last" != "final""#,
+            r#"/root/added: extra node, "added""#,
+        ]
+    );
+    assert_eq!(
+        differences(
+            r#"<root><a/>text<!--one--></root>"#,
+            r#"<root>text<a/></root>"#,
+            &DiffOptions::default()
+        ),
+        vec![
+            r##"/root/a: different name, "a" != "#text""##,
+            r##"/root/text(): different name, "#text" != "a""##,
+            r#"/root/comment(): missing node, "one""#,
+        ]
+    );
+}
+
+#[test]
+fn test_diff_options() {
+    let left = "<root><!--note--><a>one<![CDATA[ & two]]></a>\n  <b/></root>";
+    let right = "<root><a>one &amp; two</a><b/></root>";
+    assert_eq!(differences(left, right, &DiffOptions::default()).len(), 3);
+
+    let options = DiffOptions::new()
+        .with_ignore_comments(true)
+        .with_ignore_whitespace_text(true)
+        .with_cdata_as_text(true);
+    assert!(differences(left, right, &options).is_empty());
+
+    assert_eq!(
+        differences(left, right, &options.with_cdata_as_text(false)),
+        vec![
+            r#"/root/a/text()[1]: different text, "one" != "one & two""#,
+            r#"/root/a/text()[2]: missing node, " & two""#,
+        ]
+    );
+}
+
+#[test]
+fn test_assert_dom_eq() {
+    let document_node = read_xml(r#"<a><b x="1"/></a>"#).unwrap();
+    assert_dom_eq(
+        &document_node,
+        &document_node.clone(),
+        &DiffOptions::default(),
+    );
+}
+
+#[test]
+#[should_panic(
+    expected = "DOM trees are not equal, 2 difference(s):\n  /a/b/@x: different attribute value, \"1\" != \"2\"\n  /a/c: extra node, \"c\""
+)]
+fn test_assert_dom_eq_fails() {
+    let left = read_xml(r#"<a><b x="1"/></a>"#).unwrap();
+    let right = read_xml(r#"<a><b x="2"/><c/></a>"#).unwrap();
+    assert_dom_eq(&left, &right, &DiffOptions::default());
+}
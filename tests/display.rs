@@ -1,10 +1,16 @@
+use std::str::FromStr;
+use xml_dom::io::{
+    escape_attribute, escape_attribute_with, escape_text, escape_text_with, InvalidCharHandling,
+};
 use xml_dom::level2::convert::{
     as_attribute_mut, as_document, as_document_fragment_mut, as_element_mut,
 };
-use xml_dom::level2::ext::convert::as_document_decl_mut;
+use xml_dom::level2::ext::convert::{as_document_decl_mut, as_document_type_decl_mut};
 use xml_dom::level2::ext::dom_impl as ext_dom_impl;
 use xml_dom::level2::ext::{XmlDecl, XmlVersion};
-use xml_dom::level2::get_implementation;
+use xml_dom::level2::{get_implementation, Error, Name};
+#[cfg(feature = "quick_parser")]
+use xml_dom::parser::read_xml;
 
 pub mod common;
 
@@ -77,6 +83,65 @@ fn test_display_text() {
     assert_eq!(result, "this is textual test data");
 }
 
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_display_escaping() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+
+    let mut test_node = document.create_element("test").unwrap();
+    {
+        let element = as_element_mut(&mut test_node).unwrap();
+        assert!(element.set_attribute("attr", "a < b & \"c\"").is_ok());
+        assert!(element
+            .append_child(document.create_text_node("x < y && ]]> \u{1}"))
+            .is_ok());
+    }
+    let result = format!("{}", test_node);
+    assert_eq!(
+        result,
+        "<test attr=\"a &#60; b &#38; &#34;c&#34;\">x &#60; y &#38;&#38; ]]&#62; &#1;</test>"
+    );
+
+    let without_control = result.replace(" &#1;", "");
+    let round_trip = read_xml(&without_control).unwrap();
+    assert_eq!(round_trip.to_string(), without_control);
+}
+
+#[test]
+fn test_escape_functions() {
+    assert_eq!(
+        escape_text("a < b & c > d"),
+        Ok("a &#60; b &#38; c &#62; d".to_string())
+    );
+    assert_eq!(escape_text("]]>"), Ok("]]&#62;".to_string()));
+    assert_eq!(escape_text("tab\there"), Ok("tab\there".to_string()));
+    assert_eq!(escape_text("bell\u{7}"), Err(Error::InvalidCharacter));
+    assert_eq!(
+        escape_text_with("bell\u{7}", InvalidCharHandling::Escape),
+        Ok("bell&#7;".to_string())
+    );
+    assert_eq!(
+        escape_text_with("no bell", InvalidCharHandling::Reject),
+        Ok("no bell".to_string())
+    );
+
+    assert_eq!(
+        escape_attribute("\"it's\""),
+        Ok("&#34;it&#39;s&#34;".to_string())
+    );
+    assert_eq!(
+        escape_attribute("tab\there\n"),
+        Ok("tab&#9;here&#10;".to_string())
+    );
+    assert_eq!(escape_attribute("\u{1F600}"), Ok("\u{1F600}".to_string()));
+    assert_eq!(escape_attribute("\u{FFFE}"), Err(Error::InvalidCharacter));
+    assert_eq!(
+        escape_attribute_with("\u{FFFE}", InvalidCharHandling::Escape),
+        Ok("&#65534;".to_string())
+    );
+}
+
 #[test]
 fn test_display_cdata() {
     let document_node = common::create_empty_rdf_document();
@@ -87,7 +152,7 @@ fn test_display_cdata() {
         .unwrap();
 
     let result = format!("{}", test_node);
-    assert_eq!(result, "<![CDATA[ this is textual test data ]]>");
+    assert_eq!(result, "<![CDATA[this is textual test data]]>");
 }
 
 #[test]
@@ -124,7 +189,9 @@ fn test_display_comment() {
     let document_node = common::create_empty_rdf_document();
     let document = as_document(&document_node).unwrap();
 
-    let test_node = document.create_comment("this is textual test data");
+    let test_node = document
+        .create_comment("this is textual test data")
+        .unwrap();
 
     let result = format!("{}", test_node);
     assert_eq!(result, "<!--this is textual test data-->");
@@ -149,7 +216,7 @@ fn test_display_document() {
         .unwrap();
 
     let result = format!("{}", test_node);
-    assert_eq!(result, "<!DOCTYPE html PUBLIC \"-//W3C//DTD XHTML 1.0 Transitional//EN\" SYSTEM \"http://www.w3.org/TR/xhtml1/DTD/xhtml1-transitional.dtd\"><html></html>");
+    assert_eq!(result, "<!DOCTYPE html PUBLIC \"-//W3C//DTD XHTML 1.0 Transitional//EN\" \"http://www.w3.org/TR/xhtml1/DTD/xhtml1-transitional.dtd\"><html></html>");
 }
 
 #[test]
@@ -176,7 +243,52 @@ fn test_display_document_with_decl() {
     assert!(result.is_ok());
 
     let result = format!("{}", test_node);
-    assert_eq!(result, "<?xml version=\"1.1\" encoding=\"UTF-8\"?><!DOCTYPE html PUBLIC \"-//W3C//DTD XHTML 1.0 Transitional//EN\" SYSTEM \"http://www.w3.org/TR/xhtml1/DTD/xhtml1-transitional.dtd\"><html></html>");
+    assert_eq!(result, "<?xml version=\"1.1\" encoding=\"UTF-8\"?><!DOCTYPE html PUBLIC \"-//W3C//DTD XHTML 1.0 Transitional//EN\" \"http://www.w3.org/TR/xhtml1/DTD/xhtml1-transitional.dtd\"><html></html>");
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_display_document_decl_accessors() {
+    let implementation = get_implementation();
+    let mut test_node = implementation
+        .create_document(None, Some("config"), None)
+        .unwrap();
+
+    let mut_document = as_document_decl_mut(&mut test_node).unwrap();
+    assert_eq!(mut_document.xml_version(), "1.0");
+    assert!(mut_document.xml_encoding().is_none());
+    assert!(!mut_document.xml_standalone());
+
+    assert_eq!(
+        mut_document.set_xml_version("2.0"),
+        Err(Error::NotSupported)
+    );
+    assert!(mut_document.xml_declaration().is_none());
+    assert!(mut_document.set_xml_version("1.1").is_ok());
+    assert!(mut_document.set_xml_standalone(true).is_ok());
+    assert_eq!(mut_document.xml_version(), "1.1");
+    assert!(mut_document.xml_standalone());
+    assert_eq!(
+        format!("{}", test_node),
+        "<?xml version=\"1.1\" standalone=\"yes\"?><config></config>"
+    );
+
+    //
+    // Round-trip a parsed declaration.
+    //
+    let xml = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?><config></config>";
+    let mut test_node = read_xml(xml).unwrap();
+    let mut_document = as_document_decl_mut(&mut test_node).unwrap();
+    assert_eq!(mut_document.xml_encoding(), Some("UTF-8".to_string()));
+    assert!(mut_document.xml_standalone());
+    assert_eq!(format!("{}", test_node), xml);
+
+    let mut_document = as_document_decl_mut(&mut test_node).unwrap();
+    assert!(mut_document.set_xml_standalone(false).is_ok());
+    assert_eq!(
+        format!("{}", test_node),
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\"?><config></config>"
+    );
 }
 
 #[test]
@@ -191,30 +303,82 @@ fn test_display_document_type() {
         .unwrap();
 
     let result = format!("{}", test_node);
-    assert_eq!(result, "<!DOCTYPE html PUBLIC \"-//W3C//DTD XHTML 1.0 Transitional//EN\" SYSTEM \"http://www.w3.org/TR/xhtml1/DTD/xhtml1-transitional.dtd\">");
+    assert_eq!(result, "<!DOCTYPE html PUBLIC \"-//W3C//DTD XHTML 1.0 Transitional//EN\" \"http://www.w3.org/TR/xhtml1/DTD/xhtml1-transitional.dtd\">");
+}
+
+#[test]
+fn test_display_document_type_declarations() {
+    let implementation = get_implementation();
+    let mut document_type = implementation
+        .create_document_type("catalog", None, Some("catalog.dtd"))
+        .unwrap();
+    let document_node = implementation
+        .create_document(None, Some("catalog"), Some(document_type.clone()))
+        .unwrap();
+    assert_eq!(
+        document_type.to_string(),
+        "<!DOCTYPE catalog SYSTEM \"catalog.dtd\">"
+    );
+
+    let doc_type_decl = as_document_type_decl_mut(&mut document_type).unwrap();
+    let notation =
+        ext_dom_impl::create_notation(document_node.clone(), "gif", None, Some("image/gif"))
+            .unwrap();
+    assert_eq!(doc_type_decl.add_notation(notation), Ok(true));
+    for (name, value) in [("publisher", "ACME"), ("copy", "(c)")] {
+        let entity =
+            ext_dom_impl::create_internal_entity(document_node.clone(), name, value).unwrap();
+        assert_eq!(doc_type_decl.add_entity(entity), Ok(true));
+    }
+    let duplicate =
+        ext_dom_impl::create_internal_entity(document_node.clone(), "copy", "(C)").unwrap();
+    assert_eq!(doc_type_decl.add_entity(duplicate.clone()), Ok(false));
+    assert!(doc_type_decl.add_notation(duplicate).is_err());
+
+    let entities = doc_type_decl.entities();
+    assert_eq!(entities.len(), 2);
+    let copy = entities.get(&Name::from_str("copy").unwrap()).unwrap();
+    assert_eq!(copy.to_string(), "<!ENTITY copy \"(c)\">");
+    assert_eq!(doc_type_decl.notations().len(), 1);
+    assert_eq!(
+        document_type.to_string(),
+        "<!DOCTYPE catalog SYSTEM \"catalog.dtd\" [<!ENTITY copy \"(c)\"><!ENTITY publisher \"ACME\"><!NOTATION gif SYSTEM \"image/gif\">]>"
+    );
+
+    let doc_type_decl = as_document_type_decl_mut(&mut document_type).unwrap();
+    doc_type_decl
+        .set_internal_subset("<!ENTITY copy \"(c)\">")
+        .unwrap();
+    assert_eq!(
+        doc_type_decl.internal_subset(),
+        Some("<!ENTITY copy \"(c)\">".to_string())
+    );
+    assert_eq!(
+        document_node.to_string(),
+        "<!DOCTYPE catalog SYSTEM \"catalog.dtd\" [<!ENTITY copy \"(c)\">]><catalog></catalog>"
+    );
+    doc_type_decl.unset_internal_subset().unwrap();
+    assert!(doc_type_decl.internal_subset().is_none());
 }
 
 #[test]
 fn test_display_document_fragment() {
     let implementation = get_implementation();
-    let mut document_node = implementation
+    let document_node = implementation
         .create_document(Some(common::RDF_NS), Some("rdf:RDF"), None)
         .unwrap();
-    let document = as_document(&mut document_node).unwrap();
+    let document = as_document(&document_node).unwrap();
 
     let mut test_node = document.create_document_fragment().unwrap();
     let mut_fragment = as_document_fragment_mut(&mut test_node).unwrap();
 
-    for name in vec!["one", "two", "three"] {
+    for name in ["one", "two", "three"] {
         let node = document.create_element(name).unwrap();
         let _safe_to_ignore = mut_fragment.append_child(node).unwrap();
     }
 
     let result = format!("{}", test_node);
-    assert_eq!(
-        result,
-        "<![CDATA[#document-fragment <one></one><two></two><three></three>]]>"
-    );
+    assert_eq!(result, "<one></one><two></two><three></three>");
 }
 #[test]
 fn test_display_entity() {
@@ -290,7 +454,7 @@ fn test_display_entity() {
             let result = format!("{}", test_node);
             assert_eq!(
                 result,
-                "<!ENTITY name PUBLIC \"foo-bar\" \"file-name.xml\" GIF>"
+                "<!ENTITY name PUBLIC \"foo-bar\" \"file-name.xml\" NDATA GIF>"
             );
     */
 }
@@ -334,3 +498,27 @@ fn test_display_notation() {
         "<!NOTATION name PUBLIC \"foo-bar\" \"file-name.xml\">"
     );
 }
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_display_namespace_declarations_stable() {
+    const XML: &str = r#"<root xmlns="urn:default" xmlns:z="urn:z" xmlns:a="urn:a" xmlns:m="urn:m"><a:child z:attr="1" m:attr="2"></a:child></root>"#;
+
+    let serialize = || {
+        let document_node = read_xml(XML).unwrap();
+        let root_node = as_document(&document_node)
+            .unwrap()
+            .document_element()
+            .unwrap();
+        (document_node.to_string(), root_node.to_string())
+    };
+
+    let (expected_document, expected_root) = serialize();
+    assert_eq!(expected_root, XML);
+    assert!(expected_document.ends_with(XML));
+    for _ in 0..20 {
+        let (document, root) = serialize();
+        assert_eq!(document.as_bytes(), expected_document.as_bytes());
+        assert_eq!(root.as_bytes(), expected_root.as_bytes());
+    }
+}
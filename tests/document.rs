@@ -1,9 +1,19 @@
 use std::str::FromStr;
 use xml_dom::level2::convert::{
     as_attribute, as_cdata_section, as_comment, as_document, as_document_fragment, as_document_mut,
-    as_element, as_entity_reference, as_processing_instruction, as_text,
+    as_element, as_element_mut, as_entity_reference, as_processing_instruction, as_text,
 };
-use xml_dom::level2::{get_implementation, Error, Name};
+use xml_dom::level2::ext::convert::as_document_type_decl_mut;
+use xml_dom::level2::ext::dom_impl as ext_dom_impl;
+use xml_dom::level2::ext::{
+    EntityExpansion, EntityLimits, ExpansionLimits, NormalizeConfig, NormalizeDocument,
+};
+use xml_dom::level2::{
+    get_implementation, Attribute, Element, Error, Name, Node, NodeType, RefNode,
+};
+
+#[cfg(feature = "quick_parser")]
+use xml_dom::parser::read_xml;
 
 pub mod common;
 
@@ -100,11 +110,190 @@ fn test_create_entity_reference() {
     assert!(!entity_reference.has_child_nodes());
 }
 
+#[test]
+fn test_create_known_entity_reference() {
+    let document_node = create_document_with_entities();
+    let document = as_document(&document_node).unwrap();
+
+    let node = document.create_entity_reference("company").unwrap();
+    assert_eq!(node.child_nodes().len(), 1);
+    let mut text_node = node.first_child().unwrap();
+    assert_eq!(text_node.node_value(), Some("ACME Corp.".to_string()));
+    assert_eq!(
+        text_node.set_node_value("other"),
+        Err(Error::NoModificationAllowed)
+    );
+    assert_eq!(node.to_string(), "&company;".to_string());
+
+    let node = document.create_entity_reference("unknown").unwrap();
+    assert!(!node.has_child_nodes());
+}
+
+#[test]
+fn test_expand_entity_references() {
+    let mut document_node = create_document_with_entities();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    for node in [
+        document.create_text_node("Made by "),
+        document.create_entity_reference("company").unwrap(),
+        document.create_text_node(" "),
+        document.create_entity_reference("unknown").unwrap(),
+    ] {
+        let _safe_to_ignore = root_node.append_child(node).unwrap();
+    }
+    assert_eq!(
+        root_node.to_string(),
+        "<catalog>Made by &company; &unknown;</catalog>".to_string()
+    );
+
+    document_node.expand_entity_references().unwrap();
+    assert_eq!(
+        root_node.to_string(),
+        "<catalog>Made by ACME Corp. &unknown;</catalog>".to_string()
+    );
+    let mut expanded = root_node.child_nodes().item(1).unwrap();
+    assert_eq!(expanded.node_type(), NodeType::Text);
+    assert!(expanded.set_node_value("Someone").is_ok());
+}
+
+fn create_document_with_entities() -> RefNode {
+    let implementation = get_implementation();
+    let document_type = implementation
+        .create_document_type("catalog", None, None)
+        .unwrap();
+    let document_node = implementation
+        .create_document(None, Some("catalog"), Some(document_type))
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut document_type = document.doc_type().unwrap();
+    let doc_type_decl = as_document_type_decl_mut(&mut document_type).unwrap();
+    let entity =
+        ext_dom_impl::create_internal_entity(document_node.clone(), "company", "ACME Corp.")
+            .unwrap();
+    assert_eq!(doc_type_decl.add_entity(entity), Ok(true));
+    document_node
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_normalize_document_default() {
+    let mut document_node = read_xml("<a><!-- note --><![CDATA[<b>]]></a>").unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let _safe_to_ignore = root_node.append_child(document.create_text_node("one"));
+    let _safe_to_ignore = root_node.append_child(document.create_text_node(""));
+    let _safe_to_ignore = root_node.append_child(document.create_text_node(" two"));
+    assert_eq!(root_node.child_nodes().len(), 5);
+
+    //
+    // Only text is merged, comments and CDATA sections are kept.
+    //
+    document_node
+        .normalize_document(&NormalizeConfig::new())
+        .unwrap();
+    assert_eq!(root_node.child_nodes().len(), 3);
+    assert_eq!(
+        root_node.to_string(),
+        "<a><!-- note --><![CDATA[<b>]]>one two</a>"
+    );
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_normalize_document_cdata_as_text() {
+    let mut document_node = read_xml("<a>one<![CDATA[ <b> ]]>two</a>").unwrap();
+    let mut config = NormalizeConfig::new();
+    config.set_cdata_as_text();
+    document_node.normalize_document(&config).unwrap();
+
+    let root_node = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    assert_eq!(root_node.child_nodes().len(), 1);
+    assert_eq!(root_node.to_string(), "<a>one &#60;b&#62; two</a>");
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_normalize_document_strip_comments() {
+    let mut document_node =
+        read_xml("<!-- license --><a>one<!-- note -->two<b><!-- inner --></b></a>").unwrap();
+    let mut config = NormalizeConfig::new();
+    config.set_strip_comments();
+    document_node.normalize_document(&config).unwrap();
+    assert_eq!(document_node.to_string(), "<a>onetwo<b></b></a>");
+}
+
+#[test]
+fn test_normalize_document_expand_entity_references() {
+    let mut document_node = create_document_with_entities();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    for node in [
+        document.create_text_node("Made by "),
+        document.create_entity_reference("company").unwrap(),
+        document.create_text_node("!"),
+    ] {
+        let _safe_to_ignore = root_node.append_child(node).unwrap();
+    }
+
+    document_node
+        .normalize_document(&NormalizeConfig::new())
+        .unwrap();
+    assert_eq!(root_node.child_nodes().len(), 3);
+
+    let mut config = NormalizeConfig::new();
+    config.set_expand_entity_references();
+    document_node.normalize_document(&config).unwrap();
+    assert_eq!(root_node.child_nodes().len(), 1);
+    assert_eq!(
+        root_node.to_string(),
+        "<catalog>Made by ACME Corp.!</catalog>"
+    );
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_normalize_document_remove_redundant_namespaces() {
+    let mut document_node = read_xml(
+        r#"<a xmlns="urn:a" xmlns:x="urn:x"><b xmlns="urn:a" xmlns:x="urn:y"><c xmlns:x="urn:y" xmlns:z="urn:z"/></b></a>"#,
+    )
+    .unwrap();
+    let mut config = NormalizeConfig::new();
+    config.set_remove_redundant_namespaces();
+    document_node.normalize_document(&config).unwrap();
+    assert_eq!(
+        document_node.to_string(),
+        r#"<a xmlns="urn:a" xmlns:x="urn:x"><b xmlns:x="urn:y"><c xmlns:z="urn:z"></c></b></a>"#
+    );
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_normalize_document_trim_element_content_whitespace() {
+    let mut document_node = read_xml("<a><b>x</b><c></c></a>").unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let mut c_node = root_node.last_child().unwrap();
+    let b_node = root_node.first_child().unwrap();
+    let _safe_to_ignore = root_node.insert_before(document.create_text_node("\n  "), Some(b_node));
+    let _safe_to_ignore = root_node.append_child(document.create_text_node("\n"));
+    let _safe_to_ignore = c_node.append_child(document.create_text_node("  "));
+    assert_eq!(root_node.to_string(), "<a>\n  <b>x</b><c>  </c>\n</a>");
+
+    let mut config = NormalizeConfig::new();
+    config.set_trim_element_content_whitespace();
+    document_node.normalize_document(&config).unwrap();
+    assert_eq!(root_node.to_string(), "<a><b>x</b><c>  </c></a>");
+}
+
 #[test]
 fn test_create_comment() {
     let document_node = common::create_empty_rdf_document();
     let document = as_document(&document_node).unwrap();
-    let node = document.create_comment(TEST_TEXT);
+    let node = document.create_comment(TEST_TEXT).unwrap();
     let comment = as_comment(&node).unwrap();
     assert!(comment.parent_node().is_none());
     assert!(comment.owner_document().is_some());
@@ -146,6 +335,86 @@ fn test_create_element_ns() {
     assert!(!element.has_child_nodes());
 }
 
+#[test]
+fn test_create_element_ns_names() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    let mut node = document
+        .create_element_ns(common::DC_NS, "dc:title")
+        .unwrap();
+    assert_eq!(node.namespace_uri(), Some(common::DC_NS.to_string()));
+    assert_eq!(node.prefix(), Some("dc".to_string()));
+    assert_eq!(node.local_name(), "title".to_string());
+    assert_eq!(node.node_name().to_string(), "dc:title".to_string());
+
+    let attribute = document
+        .create_attribute_ns(common::XML_NS_URI, "xml:lang")
+        .unwrap();
+    assert_eq!(
+        attribute.namespace_uri(),
+        Some(common::XML_NS_URI.to_string())
+    );
+    assert_eq!(attribute.prefix(), Some("xml".to_string()));
+    assert_eq!(attribute.local_name(), "lang".to_string());
+
+    let element = as_element_mut(&mut node).unwrap();
+    let _safe_to_ignore = element.set_attribute_node_ns(attribute).unwrap();
+    let element = as_element(&node).unwrap();
+    assert!(element.has_attribute_ns(common::XML_NS_URI, "lang"));
+    assert_eq!(node.to_string(), r#"<dc:title xml:lang=""></dc:title>"#);
+}
+
+#[test]
+fn test_create_ns_errors() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    let cases = [
+        ("", "dc:title", Error::Namespace),
+        (common::DC_NS, "dc:ti:tle", Error::Namespace),
+        (common::DC_NS, "dc:", Error::Namespace),
+        (common::DC_NS, "xml:title", Error::Namespace),
+        (common::XML_NS_URI, "dc:title", Error::Namespace),
+        (common::DC_NS, "xmlns", Error::Namespace),
+        (common::DC_NS, "xmlns:dc", Error::Namespace),
+        (common::XMLNS_NS, "dc:title", Error::Namespace),
+        (common::DC_NS, "dc:ti tle", Error::InvalidCharacter),
+        (common::DC_NS, "1title", Error::InvalidCharacter),
+    ];
+    for (namespace_uri, qualified_name, error) in cases {
+        assert_eq!(
+            document.create_element_ns(namespace_uri, qualified_name),
+            Err(error.clone())
+        );
+        assert_eq!(
+            document.create_attribute_ns(namespace_uri, qualified_name),
+            Err(error)
+        );
+    }
+}
+
+#[test]
+fn test_create_invalid_names() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    for name in ["123 <bad>", "-name", "na me", "\u{B7}name"] {
+        assert_eq!(document.create_element(name), Err(Error::InvalidCharacter));
+        assert_eq!(
+            document.create_attribute(name),
+            Err(Error::InvalidCharacter)
+        );
+        assert_eq!(
+            document.create_entity_reference(name),
+            Err(Error::InvalidCharacter)
+        );
+        assert_eq!(
+            document.create_processing_instruction(name, None),
+            Err(Error::InvalidCharacter)
+        );
+    }
+    assert!(document.create_element("名前").is_ok());
+    assert!(document.create_processing_instruction("é", None).is_ok());
+}
+
 #[test]
 fn test_create_processing_instruction() {
     let document_node = common::create_empty_rdf_document();
@@ -220,6 +489,60 @@ fn test_get_elements_all() {
     assert_eq!(elements.len(), 6);
 }
 
+#[test]
+fn test_get_elements_from_element() {
+    let root_node = common::create_example_rdf_document();
+    let document = as_document(&root_node).unwrap();
+    let root_element = document.document_element().unwrap();
+
+    let elements = root_element.get_elements_by_tag_name("*");
+    let names: Vec<String> = elements.iter().map(|e| e.node_name().to_string()).collect();
+    assert_eq!(
+        names,
+        vec![
+            "rdf:Description",
+            "dc:creator",
+            "dc:title",
+            "dc:Description",
+            "dc:date"
+        ]
+    );
+
+    let elements = root_element.get_elements_by_tag_name("rdf:RDF");
+    assert_eq!(elements.len(), 0);
+
+    let description = root_element.first_child().unwrap();
+    let elements = description.get_elements_by_tag_name("rdf:Description");
+    assert_eq!(elements.len(), 0);
+    let elements = description.get_elements_by_tag_name("dc:date");
+    assert_eq!(elements.len(), 1);
+}
+
+#[test]
+fn test_get_elements_deeply_nested() {
+    let mut document_node = common::create_empty_rdf_document();
+    let document = as_document_mut(&mut document_node).unwrap();
+    let root_element = document.document_element().unwrap();
+
+    let mut parent = root_element.clone();
+    for depth in 0..100 {
+        let mut child = document.create_element("level").unwrap();
+        child.set_attribute("depth", &depth.to_string()).unwrap();
+        let _safe_to_ignore = child.append_child(document.create_text_node("text"));
+        parent = parent.append_child(child).unwrap();
+    }
+
+    let elements = root_element.get_elements_by_tag_name("level");
+    assert_eq!(elements.len(), 100);
+    for (depth, element) in elements.iter().enumerate() {
+        assert_eq!(element.get_attribute("depth"), Some(depth.to_string()));
+    }
+
+    let elements = document.get_elements_by_tag_name("*");
+    assert_eq!(elements.len(), 101);
+    assert_eq!(elements.first().unwrap(), &root_element);
+}
+
 #[test]
 fn test_get_elements_ns_none() {
     let root_node = common::create_example_rdf_document();
@@ -265,6 +588,59 @@ fn test_get_elements_ns_all_descriptions() {
     assert_eq!(elements.len(), 2);
 }
 
+#[test]
+fn test_get_elements_ns_ignores_prefix() {
+    const XMLNS_NS: &str = "http://www.w3.org/2000/xmlns/";
+    let implementation = get_implementation();
+    let document_node = implementation
+        .create_document(Some(common::RDF_NS), Some("rdf:RDF"), None)
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+
+    let first = document.create_element_ns(common::DC_NS, "a:item").unwrap();
+    let _safe_to_ignore = root_node.append_child(first).unwrap();
+    let second = document.create_element_ns(common::DC_NS, "b:item").unwrap();
+    let _safe_to_ignore = root_node.append_child(second).unwrap();
+
+    let mut container = document.create_element("container").unwrap();
+    {
+        let container_element = as_element_mut(&mut container).unwrap();
+        container_element
+            .set_attribute_ns(XMLNS_NS, "xmlns:c", common::DC_NS)
+            .unwrap();
+        let third = document.create_element("c:item").unwrap();
+        let _safe_to_ignore = container_element.append_child(third).unwrap();
+        let other = document.create_element("d:item").unwrap();
+        let _safe_to_ignore = container_element.append_child(other).unwrap();
+    }
+    let _safe_to_ignore = root_node.append_child(container.clone()).unwrap();
+
+    let elements = document.get_elements_by_tag_name_ns(common::DC_NS, "item");
+    let names: Vec<String> = elements
+        .iter()
+        .map(|element| element.node_name().to_string())
+        .collect();
+    assert_eq!(names, vec!["a:item", "b:item", "c:item"]);
+
+    let elements = document.get_elements_by_tag_name_ns(common::DC_NS, "*");
+    assert_eq!(elements.len(), 3);
+
+    let elements = document.get_elements_by_tag_name_ns("*", "item");
+    assert_eq!(elements.len(), 4);
+
+    let elements = document.get_elements_by_tag_name_ns("*", "*");
+    assert_eq!(elements.len(), 6);
+
+    let container_element = as_element(&container).unwrap();
+    let elements = container_element.get_elements_by_tag_name_ns(common::DC_NS, "item");
+    assert_eq!(elements.len(), 1);
+    assert_eq!(elements.first().unwrap().node_name().to_string(), "c:item");
+
+    let elements = container_element.get_elements_by_tag_name_ns("*", "container");
+    assert!(elements.is_empty());
+}
+
 #[test]
 fn test_only_one_root() {
     let implementation = get_implementation();
@@ -279,6 +655,26 @@ fn test_only_one_root() {
     assert_eq!(result, Err(Error::HierarchyRequest));
 }
 
+#[test]
+fn test_only_one_root_replace() {
+    let implementation = get_implementation();
+    let mut document_node = implementation
+        .create_document(Some(common::RDF_NS), Some("rdf:RDF"), None)
+        .unwrap();
+    let document = as_document_mut(&mut document_node).unwrap();
+    let comment = document.create_comment("replace me").unwrap();
+    assert!(document.append_child(comment.clone()).is_ok());
+
+    //
+    // The check happens before `comment` is removed, so the document is left unchanged.
+    //
+    let second = document.create_element("should_not_work").unwrap();
+    let result = document.replace_child(second, comment.clone());
+    assert_eq!(result, Err(Error::HierarchyRequest));
+    assert_eq!(comment.parent_node(), Some(document_node.clone()));
+    assert_eq!(document_node.child_nodes().len(), 2);
+}
+
 #[test]
 fn test_remove_root() {
     let implementation = get_implementation();
@@ -319,3 +715,681 @@ fn test_replace_root() {
     let expected_name = Name::from_str("should_work").unwrap();
     assert_eq!(element.node_name(), expected_name);
 }
+
+#[test]
+fn test_document_prolog() {
+    let implementation = get_implementation();
+    let doc_type = implementation
+        .create_document_type("catalog", None, Some("catalog.dtd"))
+        .unwrap();
+    let mut document_node = implementation
+        .create_document(None, Some("catalog"), Some(doc_type.clone()))
+        .unwrap();
+    let document = as_document_mut(&mut document_node).unwrap();
+    assert_eq!(document.doc_type(), Some(doc_type.clone()));
+    assert!(doc_type.parent_node().is_some());
+
+    let root = document.document_element().unwrap();
+    let license = document.create_comment(" Licensed under MIT ").unwrap();
+    let stylesheet = document
+        .create_processing_instruction("xml-stylesheet", Some("href=\"style.css\""))
+        .unwrap();
+    let trailer = document.create_comment(" end ").unwrap();
+    assert!(document
+        .insert_before(license.clone(), Some(doc_type.clone()))
+        .is_ok());
+    assert!(document
+        .insert_before(stylesheet.clone(), Some(root.clone()))
+        .is_ok());
+    assert!(document.append_child(trailer.clone()).is_ok());
+
+    assert_eq!(document.document_element(), Some(root.clone()));
+    assert_eq!(
+        document_node.child_nodes().to_vec(),
+        vec![license, doc_type, stylesheet, root, trailer]
+    );
+    assert_eq!(
+        document_node.to_string(),
+        "<!-- Licensed under MIT --><!DOCTYPE catalog SYSTEM \"catalog.dtd\">\
+<?xml-stylesheet href=\"style.css\"?><catalog></catalog><!-- end -->"
+    );
+}
+
+#[test]
+fn test_document_prolog_errors() {
+    let implementation = get_implementation();
+    let doc_type = implementation
+        .create_document_type("catalog", None, None)
+        .unwrap();
+    let mut document_node = implementation
+        .create_document(None, Some("catalog"), Some(doc_type.clone()))
+        .unwrap();
+    let document = as_document_mut(&mut document_node).unwrap();
+    let root = document.document_element().unwrap();
+
+    //
+    // Only one document type, and only before the document element.
+    //
+    let second = implementation
+        .create_document_type("other", None, None)
+        .unwrap();
+    assert_eq!(
+        document.insert_before(second.clone(), Some(doc_type.clone())),
+        Err(Error::HierarchyRequest)
+    );
+    assert_eq!(
+        document.append_child(doc_type.clone()),
+        Err(Error::HierarchyRequest)
+    );
+    let element = document.create_element("other").unwrap();
+    assert_eq!(
+        document.insert_before(element.clone(), Some(doc_type.clone())),
+        Err(Error::HierarchyRequest)
+    );
+    assert_eq!(document_node.child_nodes().len(), 2);
+
+    //
+    // Replacing the document type, and removing it.
+    //
+    let document = as_document_mut(&mut document_node).unwrap();
+    assert!(document
+        .replace_child(second.clone(), doc_type.clone())
+        .is_ok());
+    assert_eq!(document.doc_type(), Some(second.clone()));
+    assert!(doc_type.parent_node().is_none());
+    let trailer = document.create_comment(" end ").unwrap();
+    assert!(document.append_child(trailer.clone()).is_ok());
+    assert_eq!(
+        document.replace_child(second.clone(), trailer),
+        Err(Error::HierarchyRequest)
+    );
+    assert!(document.remove_child(second).is_ok());
+    assert!(document.doc_type().is_none());
+    assert!(document.insert_before(doc_type.clone(), Some(root)).is_ok());
+    assert_eq!(document.doc_type(), Some(doc_type));
+}
+
+#[test]
+fn test_rename_element() {
+    let implementation = get_implementation();
+    let mut document_node = implementation
+        .create_document(None, Some("root"), None)
+        .unwrap();
+    let document = as_document_mut(&mut document_node).unwrap();
+    let mut root = document.document_element().unwrap();
+    let before = document.create_element("before").unwrap();
+    let mut element = document.create_element("old").unwrap();
+    let after = document.create_element("after").unwrap();
+    let _safe_to_ignore = element.set_attribute("id", "1");
+    let _safe_to_ignore = element.append_child(document.create_text_node("text"));
+    let _safe_to_ignore = root.append_child(before);
+    let _safe_to_ignore = root.append_child(element.clone());
+    let _safe_to_ignore = root.append_child(after);
+
+    let renamed = document
+        .rename_node(element.clone(), Some(common::DC_NS), "dc:new")
+        .unwrap();
+    assert_eq!(renamed, element);
+    assert_eq!(element.node_name().to_string(), "dc:new");
+    assert_eq!(element.namespace_uri(), Some(common::DC_NS.to_string()));
+    assert_eq!(element.get_attribute("id"), Some("1".to_string()));
+    assert_eq!(
+        root.to_string(),
+        "<root><before></before><dc:new id=\"1\">text</dc:new><after></after></root>"
+    );
+
+    let renamed = document
+        .rename_node(element.clone(), None, "plain")
+        .unwrap();
+    assert_eq!(renamed.node_name().to_string(), "plain");
+    assert!(renamed.namespace_uri().is_none());
+}
+
+#[test]
+fn test_rename_attribute() {
+    let implementation = get_implementation();
+    let mut document_node = implementation
+        .create_document(None, Some("root"), None)
+        .unwrap();
+    let document = as_document_mut(&mut document_node).unwrap();
+    let mut root = document.document_element().unwrap();
+    let _safe_to_ignore = root.set_attribute("a", "1");
+    let _safe_to_ignore = root.set_attribute("b", "2");
+    let _safe_to_ignore = root.set_attribute("c", "3");
+
+    //
+    // The attribute keeps its position, and is found by its new name only.
+    //
+    let attribute = root.get_attribute_node("b").unwrap();
+    let renamed = document.rename_node(attribute.clone(), None, "z").unwrap();
+    assert_eq!(renamed, attribute);
+    assert!(root.get_attribute_node("b").is_none());
+    assert_eq!(root.get_attribute_node("z"), Some(attribute.clone()));
+    assert_eq!(root.to_string(), "<root a=\"1\" z=\"2\" c=\"3\"></root>");
+    let names: Vec<String> = root
+        .attributes()
+        .keys()
+        .map(|name| name.to_string())
+        .collect();
+    assert!(names.contains(&"z".to_string()));
+    assert!(!names.contains(&"b".to_string()));
+
+    //
+    // Renaming onto an existing name replaces that attribute.
+    //
+    let replaced = root.get_attribute_node("c").unwrap();
+    let _safe_to_ignore = document.rename_node(attribute.clone(), None, "c").unwrap();
+    assert_eq!(root.get_attribute_node("c"), Some(attribute));
+    assert!(replaced.owner_element().is_none());
+    assert_eq!(root.to_string(), "<root a=\"1\" c=\"2\"></root>");
+}
+
+#[test]
+fn test_rename_node_errors() {
+    let implementation = get_implementation();
+    let mut document_node = implementation
+        .create_document(None, Some("root"), None)
+        .unwrap();
+    let document = as_document_mut(&mut document_node).unwrap();
+    let element = document.document_element().unwrap();
+    let text = document.create_text_node("text");
+    assert_eq!(
+        document.rename_node(text, None, "text"),
+        Err(Error::NotSupported)
+    );
+    assert_eq!(
+        document.rename_node(element.clone(), None, "dc:title"),
+        Err(Error::Namespace)
+    );
+    assert_eq!(
+        document.rename_node(element.clone(), None, "1st"),
+        Err(Error::InvalidCharacter)
+    );
+    let attribute = document.create_attribute("a").unwrap();
+    assert_eq!(
+        document.rename_node(attribute, None, "xmlns"),
+        Err(Error::Namespace)
+    );
+
+    let other_node = implementation
+        .create_document(None, Some("other"), None)
+        .unwrap();
+    let other = as_document(&other_node).unwrap();
+    assert_eq!(
+        other.rename_node(element.clone(), None, "renamed"),
+        Err(Error::WrongDocument)
+    );
+    assert_eq!(element.node_name().to_string(), "root");
+}
+
+#[test]
+fn test_import_node() {
+    let source_node = common::create_example_rdf_document();
+    let source = as_document(&source_node).unwrap();
+    let source_description = source.get_elements_by_tag_name("rdf:Description");
+    let source_description = source_description.first().unwrap();
+
+    let target_node = common::create_empty_rdf_document();
+    let target = as_document(&target_node).unwrap();
+    let imported_node = target
+        .import_node(source_description.clone(), true)
+        .unwrap();
+
+    assert!(imported_node.parent_node().is_none());
+    assert_eq!(imported_node.owner_document().unwrap(), target_node);
+    assert_eq!(imported_node.node_name(), source_description.node_name());
+    assert_eq!(imported_node.child_nodes().len(), 4);
+    for (imported, source) in imported_node
+        .child_nodes()
+        .iter()
+        .zip(source_description.child_nodes().iter())
+    {
+        assert_ne!(imported, source);
+        assert_eq!(imported.node_name(), source.node_name());
+        assert_eq!(imported.parent_node().unwrap(), imported_node);
+        assert_eq!(imported.owner_document().unwrap(), target_node);
+    }
+    assert_eq!(imported_node.to_string(), source_description.to_string());
+
+    // the source is unaltered
+    assert!(source_description.parent_node().is_some());
+    assert_eq!(source_description.child_nodes().len(), 4);
+}
+
+#[test]
+fn test_import_node_shallow() {
+    let source_node = common::create_example_rdf_document();
+    let source = as_document(&source_node).unwrap();
+    let source_title = source.get_element_by_id("title").unwrap();
+
+    let target_node = common::create_empty_rdf_document();
+    let target = as_document(&target_node).unwrap();
+    let mut imported_node = target.import_node(source_title.clone(), false).unwrap();
+
+    assert!(!imported_node.has_child_nodes());
+    assert!(imported_node.has_attributes());
+    let attribute = imported_node.get_attribute_node("xml:id").unwrap();
+    assert_eq!(attribute.owner_element().unwrap(), imported_node);
+    assert_eq!(attribute.owner_document().unwrap(), target_node);
+
+    // attributes are copied by value
+    let _safe_to_ignore = imported_node.set_attribute("xml:id", "changed");
+    assert_eq!(
+        source_title.get_attribute("xml:id"),
+        Some("title".to_string())
+    );
+}
+
+#[test]
+fn test_import_node_attribute() {
+    let source_node = common::create_example_rdf_document();
+    let source = as_document(&source_node).unwrap();
+    let source_title = source.get_element_by_id("title").unwrap();
+    let source_attribute = source_title.get_attribute_node("xml:id").unwrap();
+
+    let target_node = common::create_empty_rdf_document();
+    let target = as_document(&target_node).unwrap();
+    let imported_node = target.import_node(source_attribute, false).unwrap();
+    let attribute = as_attribute(&imported_node).unwrap();
+    assert!(attribute.owner_element().is_none());
+    assert!(attribute.specified());
+    assert_eq!(attribute.value(), Some("title".to_string()));
+}
+
+#[test]
+fn test_import_node_not_supported() {
+    let source_node = common::create_example_rdf_document();
+
+    let target_node = common::create_empty_rdf_document();
+    let target = as_document(&target_node).unwrap();
+    assert_eq!(
+        target.import_node(source_node, true),
+        Err(Error::NotSupported)
+    );
+
+    let document_type = get_implementation()
+        .create_document_type("rdf:RDF", None, None)
+        .unwrap();
+    assert_eq!(
+        target.import_node(document_type, true),
+        Err(Error::NotSupported)
+    );
+}
+
+#[test]
+fn test_adopt_node() {
+    let implementation = get_implementation();
+    let source_document_node = implementation
+        .create_document(None, Some("source"), None)
+        .unwrap();
+    let target_document_node = implementation
+        .create_document(None, Some("target"), None)
+        .unwrap();
+    let source_document = as_document(&source_document_node).unwrap();
+    let target_document = as_document(&target_document_node).unwrap();
+
+    let mut source_root = source_document.document_element().unwrap();
+    let mut element_node = source_document.create_element("moved").unwrap();
+    {
+        let element = as_element_mut(&mut element_node).unwrap();
+        assert!(element.set_attribute("x", "1").is_ok());
+        let _safe_to_ignore = element
+            .append_child(source_document.create_text_node(TEST_TEXT))
+            .unwrap();
+    }
+    let _safe_to_ignore = source_root.append_child(element_node.clone()).unwrap();
+
+    let adopted = target_document.adopt_node(element_node.clone()).unwrap();
+    assert_eq!(adopted, element_node);
+    assert!(!source_root.has_child_nodes());
+    assert_eq!(element_node.parent_node(), None);
+    assert_eq!(
+        element_node.owner_document(),
+        Some(target_document_node.clone())
+    );
+    assert_eq!(
+        element_node.first_child().unwrap().owner_document(),
+        Some(target_document_node.clone())
+    );
+    let attribute_node = as_element(&element_node)
+        .unwrap()
+        .get_attribute_node("x")
+        .unwrap();
+    assert_eq!(
+        attribute_node.owner_document(),
+        Some(target_document_node.clone())
+    );
+
+    let mut target_root = target_document.document_element().unwrap();
+    assert!(target_root.append_child(element_node.clone()).is_ok());
+    assert_eq!(element_node.parent_node(), Some(target_root));
+
+    //
+    // Adopting an attribute removes it from its owner element.
+    //
+    let adopted = source_document.adopt_node(attribute_node.clone()).unwrap();
+    assert_eq!(as_attribute(&adopted).unwrap().owner_element(), None);
+    assert!(!as_element(&element_node).unwrap().has_attribute("x"));
+    assert_eq!(
+        attribute_node.owner_document(),
+        Some(source_document_node.clone())
+    );
+
+    assert_eq!(
+        target_document.adopt_node(source_document_node.clone()),
+        Err(Error::NotSupported)
+    );
+    let document_type = implementation
+        .create_document_type("source", None, None)
+        .unwrap();
+    assert_eq!(
+        target_document.adopt_node(document_type),
+        Err(Error::NotSupported)
+    );
+}
+
+#[test]
+fn test_document_uri() {
+    let implementation = get_implementation();
+    let mut document_node = implementation
+        .create_document(Some(common::DC_NS), Some("dc:catalog"), None)
+        .unwrap();
+    let document = as_document_mut(&mut document_node).unwrap();
+    assert_eq!(document.document_uri(), None);
+    assert_eq!(document.base_uri(), None);
+
+    let mut root_node = document.document_element().unwrap();
+    assert_eq!(root_node.namespace_uri(), Some(common::DC_NS.to_string()));
+
+    let mut child_node = document.create_element("item").unwrap();
+    let text_node = document.create_text_node("text");
+    let _safe_to_ignore = child_node.append_child(text_node.clone()).unwrap();
+    assert_eq!(
+        as_document(&child_node.owner_document().unwrap())
+            .unwrap()
+            .document_uri(),
+        None
+    );
+
+    document
+        .set_document_uri("http://example.org/catalog.xml")
+        .unwrap();
+    assert_eq!(
+        document.document_uri(),
+        Some("http://example.org/catalog.xml".to_string())
+    );
+    assert_eq!(root_node.namespace_uri(), Some(common::DC_NS.to_string()));
+
+    //
+    // The document URI is reachable from any node, but only a base URI for nodes in the tree.
+    //
+    for node in [&root_node, &child_node, &text_node] {
+        let owner_node = node.owner_document().unwrap();
+        assert_eq!(
+            as_document(&owner_node).unwrap().document_uri(),
+            Some("http://example.org/catalog.xml".to_string())
+        );
+    }
+    assert_eq!(
+        document.base_uri(),
+        Some("http://example.org/catalog.xml".to_string())
+    );
+    assert_eq!(
+        root_node.base_uri(),
+        Some("http://example.org/catalog.xml".to_string())
+    );
+    assert_eq!(text_node.base_uri(), None);
+
+    let _safe_to_ignore = root_node.append_child(child_node.clone()).unwrap();
+    child_node.set_attribute("xml:base", "items/").unwrap();
+    assert_eq!(
+        text_node.base_uri(),
+        Some("http://example.org/items/".to_string())
+    );
+
+    document.unset_document_uri().unwrap();
+    assert_eq!(document.document_uri(), None);
+    assert_eq!(text_node.base_uri(), Some("items/".to_string()));
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_strict_error_checking() {
+    let mut document_node = get_implementation()
+        .create_document(None, Some("root"), None)
+        .unwrap();
+    let document = as_document_mut(&mut document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let comment_node = document.create_comment("comment").unwrap();
+    assert!(document.strict_error_checking());
+    assert_eq!(document.create_element("1a"), Err(Error::InvalidCharacter));
+    assert_eq!(
+        document.create_attribute_ns("urn:a", "xml:a"),
+        Err(Error::Namespace)
+    );
+    assert_eq!(
+        root_node.set_attribute("a b", "c"),
+        Err(Error::InvalidCharacter)
+    );
+    let second_root = document.create_element("second").unwrap();
+    assert_eq!(
+        document.append_child(second_root.clone()),
+        Err(Error::HierarchyRequest)
+    );
+    let attribute_node = document.create_attribute("a").unwrap();
+    assert_eq!(
+        root_node.append_child(attribute_node.clone()),
+        Err(Error::HierarchyRequest)
+    );
+
+    document.set_strict_error_checking(false).unwrap();
+    assert!(!document.strict_error_checking());
+    let element_node = document.create_element("1a").unwrap();
+    assert_eq!(element_node.node_name().to_string(), "1a");
+    let element_node = document.create_element_ns("urn:a", "p:1a").unwrap();
+    assert_eq!(element_node.namespace_uri(), Some("urn:a".to_string()));
+    assert_eq!(element_node.prefix(), Some("p".to_string()));
+    assert!(document.create_attribute_ns("urn:a", "xml:a").is_ok());
+    root_node.set_attribute("a b", "c").unwrap();
+    assert_eq!(root_node.get_attribute("a b"), Some("c".to_string()));
+    let _safe_to_ignore = document.append_child(second_root).unwrap();
+    let _safe_to_ignore = root_node.append_child(attribute_node).unwrap();
+    assert_eq!(root_node.child_nodes().len(), 1);
+
+    //
+    // Cycles and nodes from other documents are always rejected.
+    //
+    let mut parent_node = root_node.clone();
+    assert_eq!(
+        parent_node.append_child(root_node.clone()),
+        Err(Error::HierarchyRequest)
+    );
+    let other_node = read_xml("<other/>").unwrap();
+    assert_eq!(
+        root_node.append_child(other_node.first_child().unwrap()),
+        Err(Error::WrongDocument)
+    );
+
+    //
+    // Existing nodes are unchanged when the flag is set again.
+    //
+    document.set_strict_error_checking(true).unwrap();
+    assert!(root_node.to_string().starts_with(r#"<root a b="c">"#));
+    assert_eq!(document.child_nodes().len(), 2);
+    assert_eq!(comment_node.node_value(), Some("comment".to_string()));
+    assert_eq!(document.create_element("1a"), Err(Error::InvalidCharacter));
+}
+
+//
+// Add the entities `lol1`, with the text "lol", and `lol2` to `lol<levels>`, each with ten
+// references to the previous one.
+//
+fn add_nested_entities(document_node: &RefNode, levels: usize) {
+    let document = as_document(document_node).unwrap();
+    let mut document_type = document.doc_type().unwrap();
+    let doc_type_decl = as_document_type_decl_mut(&mut document_type).unwrap();
+    let entity =
+        ext_dom_impl::create_internal_entity(document_node.clone(), "lol1", "lol").unwrap();
+    assert_eq!(doc_type_decl.add_entity(entity), Ok(true));
+    for level in 2..=levels {
+        let mut entity = ext_dom_impl::create_internal_entity(
+            document_node.clone(),
+            &format!("lol{}", level),
+            "",
+        )
+        .unwrap();
+        for _ in 0..10 {
+            let reference = document
+                .create_entity_reference(&format!("lol{}", level - 1))
+                .unwrap();
+            let _safe_to_ignore = entity.append_child(reference).unwrap();
+        }
+        assert_eq!(doc_type_decl.add_entity(entity), Ok(true));
+    }
+}
+
+#[test]
+fn test_expansion_limits() {
+    let mut document_node = create_document_with_entities();
+    assert_eq!(
+        document_node.expansion_limits(),
+        ExpansionLimits::new()
+            .with_max_nodes(10_000)
+            .with_max_depth(8)
+    );
+    let limits = ExpansionLimits::new().with_max_nodes(100).with_max_depth(3);
+    document_node.set_expansion_limits(limits.clone()).unwrap();
+    assert_eq!(document_node.expansion_limits(), limits);
+    assert_eq!(limits.max_nodes(), 100);
+    assert_eq!(limits.max_depth(), 3);
+
+    let mut element_node = as_document(&document_node)
+        .unwrap()
+        .create_element("item")
+        .unwrap();
+    assert_eq!(element_node.expansion_limits(), limits);
+    assert_eq!(
+        element_node.set_expansion_limits(ExpansionLimits::new()),
+        Err(Error::InvalidState)
+    );
+}
+
+#[test]
+fn test_expansion_limits_nested_entities() {
+    let mut document_node = create_document_with_entities();
+    document_node
+        .set_expansion_limits(ExpansionLimits::new().with_max_nodes(100))
+        .unwrap();
+    add_nested_entities(&document_node, 4);
+    let document = as_document(&document_node).unwrap();
+
+    // `lol3` adds 100 text nodes, and `lol4` would add 1,000.
+    let reference = document.create_entity_reference("lol3").unwrap();
+    assert_eq!(reference.child_nodes().len(), 10);
+    assert_eq!(
+        document.create_entity_reference("lol4"),
+        Err(Error::EntityExpansionSize)
+    );
+
+    document_node
+        .set_expansion_limits(ExpansionLimits::new().with_max_depth(2))
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    assert!(document.create_entity_reference("lol2").is_ok());
+    assert_eq!(
+        document.create_entity_reference("lol3"),
+        Err(Error::EntityExpansionDepth)
+    );
+
+    //
+    // An explicit increase allows the larger expansion.
+    //
+    document_node
+        .set_expansion_limits(ExpansionLimits::new().with_max_nodes(1_000))
+        .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let reference = document.create_entity_reference("lol4").unwrap();
+    let _safe_to_ignore = root_node.append_child(reference).unwrap();
+    root_node.expand_entity_references().unwrap();
+    assert_eq!(root_node.child_nodes().len(), 1_000);
+}
+
+#[test]
+fn test_expansion_limits_expand() {
+    let mut document_node = create_document_with_entities();
+    add_nested_entities(&document_node, 3);
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    for _ in 0..2 {
+        let reference = document.create_entity_reference("lol3").unwrap();
+        let _safe_to_ignore = root_node.append_child(reference).unwrap();
+    }
+
+    //
+    // The limit applies to all of the references expanded together, and the tree is unchanged
+    // by a failed expansion.
+    //
+    document_node
+        .set_expansion_limits(ExpansionLimits::new().with_max_nodes(150))
+        .unwrap();
+    assert_eq!(
+        document_node.expand_entity_references(),
+        Err(Error::EntityExpansionSize)
+    );
+    document_node
+        .set_expansion_limits(ExpansionLimits::new().with_max_depth(2))
+        .unwrap();
+    assert_eq!(
+        document_node.expand_entity_references(),
+        Err(Error::EntityExpansionDepth)
+    );
+    assert_eq!(root_node.child_nodes().len(), 2);
+
+    document_node
+        .set_expansion_limits(ExpansionLimits::new())
+        .unwrap();
+    document_node.expand_entity_references().unwrap();
+    assert_eq!(root_node.child_nodes().len(), 200);
+}
+
+#[test]
+fn test_expansion_limits_loop() {
+    let document_node = create_document_with_entities();
+    let document = as_document(&document_node).unwrap();
+    let mut document_type = document.doc_type().unwrap();
+    let doc_type_decl = as_document_type_decl_mut(&mut document_type).unwrap();
+    let mut entity_a =
+        ext_dom_impl::create_internal_entity(document_node.clone(), "a", "a").unwrap();
+    let mut entity_b =
+        ext_dom_impl::create_internal_entity(document_node.clone(), "b", "b").unwrap();
+    assert_eq!(doc_type_decl.add_entity(entity_a.clone()), Ok(true));
+    assert_eq!(doc_type_decl.add_entity(entity_b.clone()), Ok(true));
+
+    //
+    // `a` refers to `b`, which refers to `a`; the reference in `a` holds a copy of `b` from
+    // before `b` referred to `a`, so only `b` contains the loop.
+    //
+    let reference = document.create_entity_reference("b").unwrap();
+    let _safe_to_ignore = entity_a.append_child(reference).unwrap();
+    let reference = document.create_entity_reference("a").unwrap();
+    let _safe_to_ignore = entity_b.append_child(reference).unwrap();
+    assert!(document.create_entity_reference("a").is_ok());
+    assert_eq!(
+        document.create_entity_reference("b"),
+        Err(Error::EntityReferenceLoop)
+    );
+
+    //
+    // `self` refers to itself.
+    //
+    let mut entity_self =
+        ext_dom_impl::create_internal_entity(document_node.clone(), "self", "").unwrap();
+    assert_eq!(doc_type_decl.add_entity(entity_self.clone()), Ok(true));
+    let reference = document.create_entity_reference("self").unwrap();
+    let _safe_to_ignore = entity_self.append_child(reference).unwrap();
+    assert_eq!(
+        document.create_entity_reference("self"),
+        Err(Error::EntityReferenceLoop)
+    );
+}
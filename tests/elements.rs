@@ -2,8 +2,18 @@
 // `get_elements_by_tag_name` and `get_elements_by_tag_name_ns` tested in `document.rs`.
 //
 
-use xml_dom::level2::convert::{as_attribute_mut, as_document, as_element, as_element_mut};
-use xml_dom::level2::RefNode;
+use xml_dom::level2::convert::{
+    as_attribute, as_attribute_mut, as_document, as_element, as_element_mut,
+};
+#[cfg(feature = "quick_parser")]
+use xml_dom::level2::ext::{
+    FindElements, InheritedAttributes, InnerXml, NameMatch, TreeIterators, ValueMatch, XmlSpace,
+};
+#[cfg(feature = "quick_parser")]
+use xml_dom::level2::{Document, Element};
+use xml_dom::level2::{Error, Node, RefNode};
+#[cfg(feature = "quick_parser")]
+use xml_dom::parser::read_xml;
 
 pub mod common;
 
@@ -122,3 +132,378 @@ fn test_get_attribute() {
     assert!(element.get_attribute_ns(common::DC_NS, "three").is_none());
     assert!(element.get_attribute_ns(common::XMLNS_NS, "two").is_none());
 }
+
+#[test]
+fn test_set_attribute_ns_replaces_by_local_name() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    let mut element_node = document.document_element().unwrap();
+    let element = as_element_mut(&mut element_node).unwrap();
+    assert!(element
+        .set_attribute_ns(common::DC_NS, "a:attr", "A")
+        .is_ok());
+    let attribute_node = element
+        .get_attribute_node_ns(common::DC_NS, "attr")
+        .unwrap();
+    assert!(element
+        .set_attribute_ns(common::DC_NS, "b:attr", "B")
+        .is_ok());
+
+    //
+    // Only one attribute survives, the original node with the new prefix and value.
+    //
+    assert_eq!(
+        element
+            .attributes()
+            .keys()
+            .filter(|name| name.local_name() == "attr")
+            .count(),
+        1
+    );
+    assert_eq!(
+        element.get_attribute_ns(common::DC_NS, "attr"),
+        Some("B".to_string())
+    );
+    assert_eq!(
+        element.get_attribute_node_ns(common::DC_NS, "attr"),
+        Some(attribute_node.clone())
+    );
+    assert_eq!(attribute_node.node_name().to_string(), "b:attr");
+    assert!(element.get_attribute("a:attr").is_none());
+    assert!(element.has_attribute("b:attr"));
+
+    assert!(element.remove_attribute_ns(common::DC_NS, "attr").is_ok());
+    assert!(!element.has_attribute_ns(common::DC_NS, "attr"));
+}
+
+#[test]
+fn test_set_attribute_node_ns_replaces_by_local_name() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    let mut element_node = document.document_element().unwrap();
+    let element = as_element_mut(&mut element_node).unwrap();
+
+    let first = document
+        .create_attribute_ns(common::DC_NS, "a:attr")
+        .unwrap();
+    assert!(element.set_attribute_node_ns(first.clone()).is_ok());
+    let second = document
+        .create_attribute_ns(common::DC_NS, "b:attr")
+        .unwrap();
+    assert!(element.set_attribute_node_ns(second.clone()).is_ok());
+
+    assert!(!element.has_attribute("a:attr"));
+    assert_eq!(
+        element.get_attribute_node_ns(common::DC_NS, "attr"),
+        Some(second)
+    );
+    assert!(as_attribute(&first).unwrap().owner_element().is_none());
+}
+
+#[test]
+fn test_set_attribute_ns_errors() {
+    let mut element_node = create_example_element();
+    let element = as_element_mut(&mut element_node).unwrap();
+    assert_eq!(
+        element.set_attribute_ns("", "p:attr", "value"),
+        Err(Error::Namespace)
+    );
+    assert_eq!(
+        element.set_attribute_ns(common::DC_NS, "xml:lang", "en"),
+        Err(Error::Namespace)
+    );
+    assert_eq!(
+        element.set_attribute_ns(common::DC_NS, "xmlns", "value"),
+        Err(Error::Namespace)
+    );
+    assert_eq!(
+        element.set_attribute_ns(common::XMLNS_NS, "attr", "value"),
+        Err(Error::Namespace)
+    );
+    assert!(element
+        .set_attribute_ns(common::XML_NS_URI, "xml:lang", "en")
+        .is_ok());
+    assert_eq!(element.attributes().len(), 5);
+
+    //
+    // An empty namespace URI finds attributes without a namespace.
+    //
+    assert_eq!(element.get_attribute_ns("", "one"), Some("ONE".to_string()));
+    assert!(!element.has_attribute_ns("", "two"));
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_inner_and_outer_xml() {
+    let document_node =
+        read_xml("<root a=\"1\"><one>text &#38; more</one><two/>tail</root>").unwrap();
+    let document = as_document(&document_node).unwrap();
+    let root_node = document.document_element().unwrap();
+    assert_eq!(
+        root_node.outer_xml(),
+        "<root a=\"1\"><one>text &#38; more</one><two></two>tail</root>"
+    );
+    assert_eq!(
+        root_node.inner_xml(),
+        "<one>text &#38; more</one><two></two>tail"
+    );
+    assert_eq!(root_node.outer_xml(), root_node.to_string());
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_set_inner_xml() {
+    let document_node = read_xml(
+        "<root xmlns=\"http://example.org/default\" xmlns:p=\"http://example.org/p\"><old/></root>",
+    )
+    .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+
+    assert!(root_node
+        .set_inner_xml("<p:new p:attr=\"x\">text</p:new><plain/>")
+        .is_ok());
+    assert_eq!(
+        root_node.inner_xml(),
+        "<p:new p:attr=\"x\">text</p:new><plain></plain>"
+    );
+    let new_node = root_node.first_child().unwrap();
+    assert_eq!(new_node.owner_document(), Some(document_node.clone()));
+    assert_eq!(
+        new_node.namespace_uri(),
+        Some("http://example.org/p".to_string())
+    );
+    let new_element = as_element(&new_node).unwrap();
+    assert_eq!(
+        new_element.get_attribute_ns("http://example.org/p", "attr"),
+        Some("x".to_string())
+    );
+    assert_eq!(
+        root_node.last_child().unwrap().namespace_uri(),
+        Some("http://example.org/default".to_string())
+    );
+
+    assert_eq!(root_node.set_inner_xml("<unclosed>"), Err(Error::Syntax));
+    assert_eq!(
+        root_node.inner_xml(),
+        "<p:new p:attr=\"x\">text</p:new><plain></plain>"
+    );
+
+    assert!(root_node.set_inner_xml("").is_ok());
+    assert!(!root_node.has_child_nodes());
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_inherited_attributes() {
+    let document_node = read_xml(
+        r#"<root xml:lang="en"><a xml:space="preserve"><b xml:lang="fr"><c xml:space="default"/></b></a><d xml:space="other" xml:lang=""/></root>"#,
+    )
+    .unwrap();
+    let root_node = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    assert_eq!(root_node.xml_space(), None);
+    assert_eq!(root_node.xml_lang(), Some("en".to_string()));
+
+    let elements: Vec<RefNode> = root_node.descendant_elements().collect();
+    let expected = [
+        ("a", Some(XmlSpace::Preserve), Some("en")),
+        ("b", Some(XmlSpace::Preserve), Some("fr")),
+        ("c", Some(XmlSpace::Default), Some("fr")),
+        ("d", None, Some("")),
+    ];
+    assert_eq!(elements.len(), expected.len());
+    for (element, (name, space, lang)) in elements.iter().zip(expected) {
+        assert_eq!(element.node_name().to_string(), name);
+        assert_eq!(element.xml_space(), space);
+        assert_eq!(element.xml_lang(), lang.map(String::from));
+    }
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_find_elements() {
+    let document_node = read_xml(
+        r#"<library version="2"><meta version="1.0" lang="en"/><book><title>One</title></book><book id="b2"><dc:title xmlns:dc="urn:dc">Two</dc:title><title>Second</title></book></library>"#,
+    )
+    .unwrap();
+    let root_node = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+
+    let title = root_node.find("book[2]/title").unwrap().unwrap();
+    assert_eq!(title.text_content(), Some("Second".to_string()));
+    assert_eq!(
+        root_node.find_value("book/title").unwrap(),
+        Some("One".to_string())
+    );
+    assert_eq!(
+        root_node.find_value("meta/@version").unwrap(),
+        Some("1.0".to_string())
+    );
+    assert_eq!(root_node.find_value("meta/@missing").unwrap(), None);
+    assert!(root_node.find("book[3]").unwrap().is_none());
+
+    let titles: Vec<Option<String>> = root_node
+        .find_all("book/title")
+        .unwrap()
+        .iter()
+        .map(|node| node.text_content())
+        .collect();
+    assert_eq!(
+        titles,
+        vec![Some("One".to_string()), Some("Second".to_string())]
+    );
+    assert_eq!(root_node.find_all("*/*").unwrap().len(), 3);
+    assert_eq!(root_node.find_all("meta/@*").unwrap().len(), 2);
+    assert_eq!(root_node.find_all("book/@id").unwrap().len(), 1);
+
+    //
+    // Qualified names must match exactly, unless only local names are compared.
+    //
+    assert_eq!(root_node.find_all("book/dc:title").unwrap().len(), 1);
+    assert_eq!(
+        root_node
+            .find_all_by("book/title", NameMatch::LocalName)
+            .unwrap()
+            .len(),
+        3
+    );
+    assert_eq!(
+        root_node
+            .find_by("book[2]/x:title", NameMatch::LocalName)
+            .unwrap()
+            .and_then(|node| node.text_content()),
+        Some("Two".to_string())
+    );
+
+    for path in [
+        "",
+        "/book",
+        "book/",
+        "book[0]",
+        "book[x]",
+        "@version/book",
+        "a b",
+    ] {
+        assert_eq!(root_node.find(path), Err(Error::Syntax), "{}", path);
+        assert_eq!(root_node.find_all(path), Err(Error::Syntax), "{}", path);
+    }
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_elements_with_attribute() {
+    let document_node = read_xml(
+        r#"<root class="top"><a class="x  y"><b class="x y"/><c id="c1"/></a><d class="x   y"/></root>"#,
+    )
+    .unwrap();
+    let mut root_node = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    let names = |nodes: Vec<RefNode>| -> Vec<String> {
+        nodes
+            .iter()
+            .map(|node| node.node_name().to_string())
+            .collect()
+    };
+
+    //
+    // The element itself is not included.
+    //
+    assert_eq!(
+        names(root_node.elements_with_attribute("class")),
+        vec!["a", "b", "d"]
+    );
+    assert_eq!(names(root_node.elements_with_attribute("id")), vec!["c"]);
+    assert!(root_node.elements_with_attribute("missing").is_empty());
+
+    assert_eq!(
+        names(root_node.elements_with_attribute_value("class", "x y", ValueMatch::Exact)),
+        vec!["b"]
+    );
+    assert_eq!(
+        names(root_node.elements_with_attribute_value(
+            "class",
+            "x y",
+            ValueMatch::CollapseWhitespace
+        )),
+        vec!["a", "b", "d"]
+    );
+    assert_eq!(
+        names(root_node.elements_with_attribute_value(
+            "class",
+            "  x\ty",
+            ValueMatch::CollapseWhitespace
+        )),
+        vec!["a", "b", "d"]
+    );
+
+    let mut c_node = root_node.find("a/c").unwrap().unwrap();
+    as_element_mut(&mut c_node)
+        .unwrap()
+        .set_attribute_ns("urn:test", "t:kind", "one")
+        .unwrap();
+    as_element_mut(&mut root_node)
+        .unwrap()
+        .set_attribute_ns("urn:test", "t:kind", "one")
+        .unwrap();
+    assert_eq!(
+        names(root_node.elements_with_attribute_ns("urn:test", "kind")),
+        vec!["c"]
+    );
+    assert_eq!(
+        names(root_node.elements_with_attribute_value_ns(
+            "urn:test",
+            "kind",
+            "one",
+            ValueMatch::Exact
+        )),
+        vec!["c"]
+    );
+    assert!(root_node
+        .elements_with_attribute_value_ns("urn:test", "kind", "two", ValueMatch::Exact)
+        .is_empty());
+    assert!(root_node
+        .elements_with_attribute_ns("urn:other", "kind")
+        .is_empty());
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_append_children() {
+    let document_node = read_xml("<catalog/>").unwrap();
+    let mut root_node = document_node.document_element().unwrap();
+    let element = as_element_mut(&mut root_node).unwrap();
+
+    let mut item_node = element.append_element("item").unwrap();
+    let _safe_to_ignore = item_node.append_text("one & two").unwrap();
+    let _safe_to_ignore = item_node.append_cdata("<raw/>").unwrap();
+    let note_node = element.append_element_ns("urn:note", "n:note").unwrap();
+    assert_eq!(note_node.namespace_uri(), Some("urn:note".to_string()));
+    let comment_node = element.append_comment(" end ").unwrap();
+    assert_eq!(comment_node.parent_node(), Some(root_node.clone()));
+    assert_eq!(
+        document_node.to_string(),
+        "<catalog><item>one &#38; two<![CDATA[<raw/>]]></item><n:note></n:note><!-- end --></catalog>"
+    );
+
+    assert_eq!(
+        item_node.append_element("1item"),
+        Err(Error::InvalidCharacter)
+    );
+    assert_eq!(
+        item_node.append_comment("a -- b"),
+        Err(Error::InvalidCharacter)
+    );
+    assert_eq!(item_node.append_cdata("]]>"), Err(Error::InvalidCharacter));
+    assert_eq!(item_node.child_nodes().len(), 2);
+
+    drop(root_node);
+    drop(document_node);
+    assert_eq!(item_node.append_text("three"), Err(Error::WrongDocument));
+}
@@ -0,0 +1,35 @@
+use std::error::Error as StdError;
+use xml_dom::level2::*;
+
+#[test]
+fn test_codes() {
+    assert_eq!(Error::IndexSize.code(), 1);
+    assert_eq!(Error::HierarchyRequest.code(), 3);
+    assert_eq!(Error::NotFound.code(), 8);
+    assert_eq!(Error::InUseAttribute.code(), 10);
+    assert_eq!(Error::Namespace.code(), 14);
+    assert_eq!(Error::InvalidAccess.code(), 15);
+    assert_eq!(Error::InvalidNodeType.code(), 24);
+    assert_eq!(Error::InvalidExpression.code(), 51);
+    assert_eq!(Error::XPathType.code(), 52);
+}
+
+#[test]
+fn test_display() {
+    let document_node = create_document();
+    let error = document_node.create_element("1item").unwrap_err();
+    assert_eq!(error, Error::InvalidCharacter);
+    assert!(error.to_string().ends_with(" (code 5)"));
+
+    let boxed: Box<dyn StdError> = Box::new(error);
+    assert!(boxed.source().is_none());
+    assert!(boxed
+        .to_string()
+        .starts_with("An invalid or illegal character"));
+}
+
+fn create_document() -> RefNode {
+    get_implementation()
+        .create_document(None, None, None)
+        .unwrap()
+}
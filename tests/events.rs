@@ -0,0 +1,118 @@
+#![cfg(feature = "quick_parser")]
+
+use xml_dom::level2::ext::*;
+use xml_dom::level2::*;
+use xml_dom::parser::read_xml;
+
+fn describe(node: &RefNode) -> Vec<String> {
+    node.events()
+        .map(|event| match event {
+            XmlEvent::StartDocument { declaration } => {
+                format!("start-document {}", declaration.is_some())
+            }
+            XmlEvent::DocumentType {
+                name, system_id, ..
+            } => format!("doctype {} {:?}", name, system_id),
+            XmlEvent::StartElement {
+                name,
+                attributes,
+                namespaces,
+            } => format!(
+                "start {} {:?} {:?}",
+                name,
+                attributes
+                    .iter()
+                    .map(|(name, value)| format!("{}={}", name, value))
+                    .collect::<Vec<String>>(),
+                namespaces
+            ),
+            XmlEvent::Characters(data) => format!("text {}", data),
+            XmlEvent::CData(data) => format!("cdata {}", data),
+            XmlEvent::Comment(data) => format!("comment {}", data),
+            XmlEvent::ProcessingInstruction { target, data } => {
+                format!("pi {} {:?}", target, data)
+            }
+            XmlEvent::EndElement { name } => format!("end {}", name),
+            XmlEvent::EndDocument => "end-document".to_string(),
+        })
+        .collect()
+}
+
+#[test]
+fn test_document_events() {
+    let document_node = read_xml(
+        r#"<?xml version="1.0"?><!DOCTYPE root SYSTEM "root.dtd"><root xmlns="urn:d" xmlns:p="urn:p" p:id="1" z="a &amp; b"><p:item>one &lt; two</p:item><![CDATA[<raw/>]]><?go now?><empty/></root><!--end-->"#,
+    )
+    .unwrap();
+    assert_eq!(
+        describe(&document_node),
+        vec![
+            "start-document true",
+            r#"doctype root Some("root.dtd")"#,
+            r#"start root ["p:id=1", "z=a & b"] [(None, "urn:d"), (Some("p"), "urn:p")]"#,
+            "start p:item [] []",
+            "text one < two",
+            "end p:item",
+            "cdata <raw/>",
+            r#"pi go Some("now")"#,
+            "start empty [] []",
+            "end empty",
+            "end root",
+            "comment end",
+            "end-document",
+        ]
+    );
+}
+
+#[test]
+fn test_subtree_events() {
+    let document_node = read_xml("<root><a>one</a><b/></root>").unwrap();
+    let root_node = document_node.first_child().unwrap();
+    let a_node = root_node.first_child().unwrap();
+    assert_eq!(
+        describe(&a_node),
+        vec!["start a [] []", "text one", "end a"]
+    );
+    assert_eq!(describe(&a_node.first_child().unwrap()), vec!["text one"]);
+
+    let mut fragment_node = document_node.create_document_fragment().unwrap();
+    let _safe_to_ignore = fragment_node
+        .append_child(document_node.create_text_node("x"))
+        .unwrap();
+    let _safe_to_ignore = fragment_node
+        .append_child(document_node.create_element("y").unwrap())
+        .unwrap();
+    assert_eq!(
+        describe(&fragment_node),
+        vec!["text x", "start y [] []", "end y"]
+    );
+
+    let attribute_node = document_node.create_attribute("id").unwrap();
+    assert_eq!(attribute_node.events().count(), 0);
+}
+
+#[test]
+fn test_events_are_lazy() {
+    let document_node = read_xml("<root><a/><b/></root>").unwrap();
+    let mut root_node = document_node.first_child().unwrap();
+    let mut events = root_node.events();
+    assert!(matches!(events.next(), Some(XmlEvent::StartElement { .. })));
+    assert!(matches!(events.next(), Some(XmlEvent::StartElement { .. })));
+
+    //
+    // The tree may be read, and changed, between events.
+    //
+    let b_node = root_node.last_child().unwrap();
+    let _safe_to_ignore = root_node.remove_child(b_node).unwrap();
+    let _safe_to_ignore = root_node
+        .append_child(document_node.create_element("c").unwrap())
+        .unwrap();
+    let names: Vec<String> = events
+        .map(|event| match event {
+            XmlEvent::StartElement { name, .. } => format!("<{}>", name),
+            XmlEvent::EndElement { name } => format!("</{}>", name),
+            other => panic!("unexpected event {:?}", other),
+        })
+        .collect();
+    assert_eq!(names, vec!["</a>", "<c>", "</c>", "</root>"]);
+}
@@ -1,6 +1,15 @@
 use xml_dom::level2::convert::{as_document, as_element, as_element_mut};
+#[cfg(feature = "quick_parser")]
+use xml_dom::level2::ext::convert::as_document_type_decl_mut;
 use xml_dom::level2::ext::ProcessingOptions;
+#[cfg(feature = "quick_parser")]
+use xml_dom::level2::ext::{
+    AttributeDecl, AttributeDefault, AttributeType, CrossReferences, NodePath, ReferenceAttributes,
+    TreeIterators,
+};
 use xml_dom::level2::*;
+#[cfg(feature = "quick_parser")]
+use xml_dom::parser::read_xml;
 
 pub mod common;
 
@@ -71,28 +80,185 @@ fn test_get_element_by_id_lax() {
 
 #[test]
 #[allow(unused_must_use)]
-fn test_no_duplicates() {
+fn test_duplicates_document_order() {
     let document = common::create_empty_rdf_document();
     let ref_document = as_document(&document).unwrap();
     let mut root_node = ref_document.document_element().unwrap();
     let root_element = as_element_mut(&mut root_node).unwrap();
 
-    let mut new_element = common::create_element_with(
+    let mut first = common::create_element_with(
         ref_document,
         common::DC_NS,
         "dc:title",
         "A Guide to Growing Roses",
     );
-    new_element.set_attribute_ns(common::XML_NS_URI, "xml:id", "title");
-    root_element.append_child(new_element);
+    first.set_attribute_ns(common::XML_NS_URI, "xml:id", "title");
 
-    let mut new_element = common::create_element_with(
+    let mut second = common::create_element_with(
         ref_document,
         common::DC_NS,
         "dc:title-2",
         "Another Guide to Growing Roses",
     );
-    let result = new_element.set_attribute_ns(common::XML_NS_URI, "xml:id", "title");
-    assert!(result.is_err());
-    assert_eq!(result.err().unwrap(), Error::Syntax);
+    let result = second.set_attribute_ns(common::XML_NS_URI, "xml:id", "title");
+    assert!(result.is_ok());
+
+    root_element.append_child(second.clone());
+    assert_eq!(ref_document.get_element_by_id("title").unwrap(), second);
+
+    root_element.insert_before(first.clone(), Some(second.clone()));
+    assert_eq!(ref_document.get_element_by_id("title").unwrap(), first);
+}
+
+#[test]
+#[allow(unused_must_use)]
+fn test_removed_and_moved_elements() {
+    let document = common::create_empty_rdf_document();
+    let ref_document = as_document(&document).unwrap();
+    let mut root_node = ref_document.document_element().unwrap();
+    let root_element = as_element_mut(&mut root_node).unwrap();
+
+    let mut container = ref_document.create_element("container").unwrap();
+    root_element.append_child(container.clone());
+    let mut element = common::create_element_with(
+        ref_document,
+        common::DC_NS,
+        "dc:title",
+        "A Guide to Growing Roses",
+    );
+    element.set_attribute_ns(common::XML_NS_URI, "xml:id", "title");
+
+    //
+    // Not found until it is part of the document tree.
+    //
+    assert!(ref_document.get_element_by_id("title").is_none());
+    container.append_child(element.clone());
+    assert_eq!(ref_document.get_element_by_id("title").unwrap(), element);
+
+    //
+    // Moving within the tree is fine, removal from the tree is not.
+    //
+    root_element.append_child(element.clone());
+    assert_eq!(ref_document.get_element_by_id("title").unwrap(), element);
+    root_element.remove_child(element.clone());
+    assert!(ref_document.get_element_by_id("title").is_none());
+
+    //
+    // Changing, or removing, the ID attribute is reflected.
+    //
+    root_element.append_child(element.clone());
+    element.set_attribute_ns(common::XML_NS_URI, "xml:id", "subject");
+    assert!(ref_document.get_element_by_id("title").is_none());
+    assert_eq!(ref_document.get_element_by_id("subject").unwrap(), element);
+    element.remove_attribute("xml:id");
+    assert!(ref_document.get_element_by_id("subject").is_none());
+}
+
+#[test]
+#[allow(unused_must_use)]
+fn test_set_is_id() {
+    let document = common::create_empty_rdf_document();
+    let ref_document = as_document(&document).unwrap();
+    let mut root_node = ref_document.document_element().unwrap();
+    let root_element = as_element_mut(&mut root_node).unwrap();
+
+    let mut element = ref_document.create_element("item").unwrap();
+    element.set_attribute("key", "first");
+    root_element.append_child(element.clone());
+
+    let mut attribute = element.get_attribute_node("key").unwrap();
+    assert!(!attribute.is_id());
+    assert!(ref_document.get_element_by_id("first").is_none());
+
+    assert!(attribute.set_is_id(true).is_ok());
+    assert!(attribute.is_id());
+    assert_eq!(ref_document.get_element_by_id("first").unwrap(), element);
+
+    assert!(attribute.set_is_id(false).is_ok());
+    assert!(ref_document.get_element_by_id("first").is_none());
+
+    let xml_id = ref_document
+        .create_attribute_ns(common::XML_NS_URI, "xml:id")
+        .unwrap();
+    assert!(xml_id.is_id());
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_cross_references() {
+    let document_node = read_xml(
+        r#"<!DOCTYPE doc><doc><part key="p1" xml:id="x1"/><part key=" p2 "/><part key="p1"/><ref to="p2"/><ref to="p3" also="p1 x1 x2"/><link target="p1"/></doc>"#,
+    )
+    .unwrap();
+    let mut doc_type_node = document_node.doc_type().unwrap();
+    let doc_type = as_document_type_decl_mut(&mut doc_type_node).unwrap();
+    for (element_name, attribute_name, attribute_type) in [
+        ("part", "key", AttributeType::Id),
+        ("ref", "to", AttributeType::IdRef),
+        ("ref", "also", AttributeType::IdRefs),
+    ] {
+        let attribute_decl = AttributeDecl::new(
+            element_name,
+            attribute_name,
+            attribute_type,
+            AttributeDefault::Implied,
+        )
+        .unwrap();
+        assert_eq!(doc_type.add_attribute_decl(attribute_decl), Ok(true));
+    }
+    let parts = document_node
+        .descendant_elements()
+        .collect::<Vec<RefNode>>();
+    let parts = &parts[1..4];
+
+    //
+    // An attribute declared with the type ID is an ID.
+    //
+    let key = parts[0].get_attribute_node("key").unwrap();
+    assert!(key.is_id());
+    assert_eq!(
+        document_node.get_element_by_id("p1"),
+        Some(parts[0].clone())
+    );
+
+    let ids = document_node.collect_ids();
+    assert_eq!(ids.len(), 3);
+    assert_eq!(ids.get("p1"), Some(&parts[0]));
+    assert_eq!(ids.get("x1"), Some(&parts[0]));
+    assert_eq!(ids.get("p2"), Some(&parts[1]));
+
+    let unresolved: Vec<(String, String, String)> = document_node
+        .unresolved_idrefs()
+        .into_iter()
+        .map(|(element, name, id)| (element.node_path(), name.to_string(), id))
+        .collect();
+    assert_eq!(
+        unresolved,
+        vec![
+            (
+                "/doc/ref[2]".to_string(),
+                "to".to_string(),
+                "p3".to_string()
+            ),
+            (
+                "/doc/ref[2]".to_string(),
+                "also".to_string(),
+                "x2".to_string()
+            ),
+        ]
+    );
+
+    let elements = document_node
+        .descendant_elements()
+        .collect::<Vec<RefNode>>();
+    let (first_ref, second_ref, link) = (&elements[4], &elements[5], &elements[6]);
+    assert_eq!(first_ref.referenced_element("to"), Some(parts[1].clone()));
+    assert_eq!(second_ref.referenced_element("to"), None);
+    assert_eq!(
+        second_ref.referenced_element("also"),
+        Some(parts[0].clone())
+    );
+    assert_eq!(second_ref.referenced_element("missing"), None);
+    // the attribute need not be declared.
+    assert_eq!(link.referenced_element("target"), Some(parts[0].clone()));
 }
@@ -1,6 +1,6 @@
 use std::str::FromStr;
 use xml_dom::level2::convert::{as_document, as_document_type, as_element};
-use xml_dom::level2::{get_implementation, Name};
+use xml_dom::level2::{get_implementation, Name, Node};
 
 pub mod common;
 
@@ -88,3 +88,46 @@ fn test_create_document_type() {
     let stored_doc_type = document.doc_type().unwrap();
     assert_eq!(&document_type_node, &stored_doc_type);
 }
+
+#[test]
+fn test_has_feature() {
+    let implementation = get_implementation();
+    let document_node = implementation
+        .create_document(None, Some("root"), None)
+        .unwrap();
+    let root_node = document_node.first_child().unwrap();
+    let cases = [
+        ("Core", "1.0", true),
+        ("Core", "2.0", true),
+        ("Core", "3.0", false),
+        ("XML", "2.0", true),
+        ("xml", "1.0", true),
+        ("+Core", "2.0", true),
+        ("++Core", "2.0", false),
+        ("Traversal", "2.0", true),
+        ("traversal", "", true),
+        ("Range", "1.0", false),
+        ("XPath", "3.0", true),
+        ("+XPath", "", true),
+        ("XPath", "2.0", false),
+        ("LS", "3.0", false),
+        ("Unknown", "", false),
+        ("", "", false),
+    ];
+    for (feature, version, expected) in cases {
+        assert_eq!(
+            implementation.has_feature(feature, version),
+            expected,
+            "{} {}",
+            feature,
+            version
+        );
+        for node in [&document_node, &root_node] {
+            assert_eq!(node.is_supported(feature, version), expected);
+            assert_eq!(
+                node.get_feature(feature, version),
+                if expected { Some(node.clone()) } else { None }
+            );
+        }
+    }
+}
@@ -0,0 +1,166 @@
+#![cfg(feature = "quick_parser")]
+
+use xml_dom::level2::convert::*;
+use xml_dom::level2::ext::*;
+use xml_dom::level2::*;
+use xml_dom::parser::read_xml;
+
+pub mod common;
+
+fn node_names<I: Iterator<Item = RefNode>>(nodes: I) -> Vec<String> {
+    nodes.map(|node| node.node_name().to_string()).collect()
+}
+
+#[test]
+fn test_descendants() {
+    let document_node = read_xml(r#"<a><b x="1"><c/><!--note--></b><d>text<e/></d></a>"#).unwrap();
+    assert_eq!(
+        node_names(document_node.descendants()),
+        vec!["a", "b", "c", "#comment", "d", "#text", "e"]
+    );
+
+    let root_node = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    assert_eq!(
+        node_names(root_node.descendant_elements()),
+        vec!["b", "c", "d", "e"]
+    );
+
+    let leaf_node = root_node.descendant_elements().last().unwrap();
+    assert_eq!(leaf_node.descendants().count(), 0);
+}
+
+#[test]
+fn test_descendants_deeply_nested() {
+    const DEPTH: usize = 50_000;
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    //
+    // Build the chain bottom-up, appending it to a new detached parent each time, as appending to
+    // the deepest leaf walks all of its ancestors.
+    //
+    let mut chain_node = document.create_element("nested").unwrap();
+    for _ in 1..DEPTH {
+        let mut parent_node = document.create_element("nested").unwrap();
+        let _safe_to_ignore = parent_node.append_child(chain_node).unwrap();
+        chain_node = parent_node;
+    }
+    let mut root_node = document.document_element().unwrap();
+    let _safe_to_ignore = root_node.append_child(chain_node).unwrap();
+
+    assert_eq!(document_node.descendant_elements().count(), DEPTH + 1);
+}
+
+#[test]
+fn test_descendants_borrow_while_iterating() {
+    let document_node = common::create_example_rdf_document();
+    let mut count = 0;
+    for mut element_node in document_node.descendant_elements() {
+        let element = as_element_mut(&mut element_node).unwrap();
+        let _safe_to_ignore = element.get_attribute("id");
+        assert!(element.set_attribute("visited", "yes").is_ok());
+        count += 1;
+    }
+    assert_eq!(count, document_node.descendant_elements().count());
+    assert!(document_node
+        .descendant_elements()
+        .all(|element_node| { as_element(&element_node).unwrap().has_attribute("visited") }));
+}
+
+#[test]
+fn test_ancestors() {
+    let document_node = read_xml(r#"<a><b><c x="1"/></b></a>"#).unwrap();
+    let leaf_node = document_node.descendant_elements().last().unwrap();
+    assert_eq!(
+        node_names(leaf_node.ancestors()),
+        vec!["b", "a", "#document"]
+    );
+    assert_eq!(leaf_node.ancestors().last().unwrap(), document_node);
+    assert_eq!(document_node.ancestors().count(), 0);
+
+    let attribute_node = leaf_node.attributes().values().next().unwrap().clone();
+    assert_eq!(attribute_node.ancestors().count(), 0);
+
+    //
+    // The nearest ancestor with a given attribute.
+    //
+    let document_node = read_xml(r#"<a lang="en"><b lang="fr"><c><d/></c></b></a>"#).unwrap();
+    let leaf_node = document_node.descendant_elements().last().unwrap();
+    let nearest = leaf_node
+        .ancestors()
+        .find(|node| as_element(node).is_ok_and(|element| element.has_attribute("lang")))
+        .unwrap();
+    assert_eq!(nearest.node_name().to_string(), "b");
+}
+
+#[test]
+fn test_ancestors_detached() {
+    let document_node = read_xml(r#"<a><b><c/></b></a>"#).unwrap();
+    let mut root_node = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    let child_node = root_node.first_child().unwrap();
+    let leaf_node = child_node.first_child().unwrap();
+
+    let removed_node = root_node.remove_child(child_node.clone()).unwrap();
+    assert_eq!(node_names(leaf_node.ancestors()), vec!["b"]);
+
+    drop(removed_node);
+    drop(child_node);
+    assert_eq!(leaf_node.ancestors().count(), 0);
+}
+
+#[test]
+fn test_siblings() {
+    let document_node = read_xml(r#"<a><b/><c/>text<d/><e/></a>"#).unwrap();
+    let root_node = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    let middle_node = root_node.child_nodes().item(2).unwrap();
+
+    assert_eq!(node_names(middle_node.preceding_siblings()), vec!["c", "b"]);
+    assert_eq!(node_names(middle_node.following_siblings()), vec!["d", "e"]);
+
+    let first_node = root_node.first_child().unwrap();
+    assert_eq!(first_node.preceding_siblings().count(), 0);
+    assert_eq!(first_node.following_siblings().count(), 4);
+
+    let last_node = root_node.last_child().unwrap();
+    assert_eq!(last_node.following_siblings().count(), 0);
+    assert_eq!(last_node.preceding_siblings().count(), 4);
+
+    assert_eq!(root_node.following_siblings().count(), 0);
+    assert_eq!(document_node.preceding_siblings().count(), 0);
+}
+
+#[test]
+fn test_attribute_iter() {
+    let document_node = read_xml(r#"<a z="1" xmlns:p="urn:p" p:y="2" x="3">text</a>"#).unwrap();
+    let mut root_node = document_node.first_child().unwrap();
+    let names: Vec<String> = root_node
+        .attribute_iter()
+        .map(|(name, _)| name.to_string())
+        .collect();
+    assert_eq!(names, vec!["z", "xmlns:p", "p:y", "x"]);
+    assert!(root_node
+        .attribute_iter()
+        .all(|(name, attribute)| attribute.node_name() == name
+            && root_node.attributes().get(&name) == Some(&attribute)));
+
+    //
+    // Attributes added while iterating are reached by the iterator.
+    //
+    let mut attributes = root_node.attribute_iter();
+    assert_eq!(attributes.next().unwrap().0.to_string(), "z");
+    root_node.set_attribute("w", "4").unwrap();
+    assert_eq!(attributes.last().unwrap().0.to_string(), "w");
+
+    assert_eq!(document_node.attribute_iter().count(), 0);
+    assert_eq!(root_node.first_child().unwrap().attribute_iter().count(), 0);
+    assert!(root_node.has_attributes());
+    assert!(!document_node.has_attributes());
+}
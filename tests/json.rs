@@ -0,0 +1,198 @@
+#![cfg(all(feature = "json", feature = "quick_parser"))]
+
+use quick_xml::Reader;
+use serde_json::json;
+use xml_dom::json::*;
+use xml_dom::level2::*;
+use xml_dom::parser::{read_quick_xml, read_xml};
+
+const MIXED: &str =
+    r#"<p class="note" id="p1">mixed <b>bold &amp; <i>italic</i></b> text<br/>more</p>"#;
+
+fn badgerfish() -> JsonOptions {
+    JsonOptions::new().with_convention(JsonConvention::BadgerFish)
+}
+
+#[test]
+fn test_jsonml_mixed_content() {
+    let document_node = read_quick_xml(Reader::from_str(MIXED)).unwrap();
+    let value = to_jsonml(&document_node);
+    assert_eq!(
+        value,
+        json!([
+            "p",
+            {"class": "note", "id": "p1"},
+            "mixed ",
+            ["b", "bold & ", ["i", "italic"]],
+            " text",
+            ["br"],
+            "more"
+        ])
+    );
+
+    let copy_node = from_jsonml(&value).unwrap();
+    assert!(copy_node.is_equal_node(&document_node));
+    assert_eq!(copy_node.to_string(), document_node.to_string());
+}
+
+#[test]
+fn test_jsonml_attribute_order() {
+    let document_node = read_xml(r#"<list z="1" a="2" m="3"/>"#).unwrap();
+    let value = to_jsonml(&document_node);
+    let keys: Vec<&String> = value[1].as_object().unwrap().keys().collect();
+    assert_eq!(keys, vec!["z", "a", "m"]);
+
+    let copy_node = from_jsonml(&value).unwrap();
+    assert_eq!(copy_node.to_string(), r#"<list z="1" a="2" m="3"></list>"#);
+}
+
+#[test]
+fn test_jsonml_namespaces() {
+    let xml = r#"<a:root xmlns:a="urn:a" xmlns="urn:default"><a:item a:id="1" id="2"/><plain xmlns=""/></a:root>"#;
+    let document_node = read_quick_xml(Reader::from_str(xml)).unwrap();
+    let value = to_jsonml(&document_node);
+    assert_eq!(
+        value,
+        json!([
+            "a:root",
+            {"xmlns:a": "urn:a", "xmlns": "urn:default"},
+            ["a:item", {"a:id": "1", "id": "2"}],
+            ["plain", {"xmlns": ""}]
+        ])
+    );
+
+    let copy_node = from_jsonml(&value).unwrap();
+    assert!(copy_node.is_equal_node(&document_node));
+    let item_node = copy_node.document_element().unwrap().first_child().unwrap();
+    assert_eq!(item_node.namespace_uri(), Some("urn:a".to_string()));
+    let id_node = item_node.get_attribute_node_ns("urn:a", "id").unwrap();
+    assert_eq!(id_node.value(), Some("1".to_string()));
+}
+
+#[test]
+fn test_jsonml_undeclared_namespaces() {
+    let document_node = get_implementation()
+        .create_document(Some("urn:a"), Some("a:root"), None)
+        .unwrap();
+    let mut root_node = document_node.document_element().unwrap();
+    let item_node = document_node.create_element_ns("urn:b", "b:item").unwrap();
+    let _safe_to_ignore = root_node.append_child(item_node).unwrap();
+
+    let value = to_jsonml(&document_node);
+    assert_eq!(
+        value,
+        json!(["a:root", {"xmlns:a": "urn:a"}, ["b:item", {"xmlns:b": "urn:b"}]])
+    );
+    let copy_node = from_jsonml(&value).unwrap();
+    let item_node = copy_node.document_element().unwrap().first_child().unwrap();
+    assert_eq!(item_node.namespace_uri(), Some("urn:b".to_string()));
+}
+
+#[test]
+fn test_jsonml_comments_and_processing_instructions() {
+    let document_node = read_xml(r#"<root><!-- note --><?render fast?>text</root>"#).unwrap();
+    assert_eq!(to_jsonml(&document_node), json!(["root", "text"]));
+
+    let options = JsonOptions::new()
+        .with_comments(true)
+        .with_processing_instructions(true);
+    let value = to_json(&document_node, &options);
+    assert_eq!(
+        value,
+        json!(["root", ["!", " note "], ["?", "render", "fast"], "text"])
+    );
+    let copy_node = from_json(&value, &options).unwrap();
+    assert!(copy_node.is_equal_node(&document_node));
+
+    let copy_node = from_jsonml(&value).unwrap();
+    assert_eq!(copy_node.to_string(), "<root>text</root>");
+}
+
+#[test]
+fn test_badgerfish() {
+    let xml = r#"<alice xmlns="urn:default" xmlns:c="urn:c" c:id="1"><bob>one</bob><bob>two</bob><c:charlie/></alice>"#;
+    let document_node = read_quick_xml(Reader::from_str(xml)).unwrap();
+    let value = to_json(&document_node, &badgerfish());
+    assert_eq!(
+        value,
+        json!({
+            "alice": {
+                "@xmlns": {"$": "urn:default", "c": "urn:c"},
+                "@c:id": "1",
+                "bob": [
+                    {"@xmlns": {"$": "urn:default", "c": "urn:c"}, "$": "one"},
+                    {"@xmlns": {"$": "urn:default", "c": "urn:c"}, "$": "two"}
+                ],
+                "c:charlie": {"@xmlns": {"$": "urn:default", "c": "urn:c"}}
+            }
+        })
+    );
+
+    let copy_node = from_json(&value, &badgerfish()).unwrap();
+    assert!(copy_node.is_equal_node(&document_node));
+    assert_eq!(copy_node.to_string(), document_node.to_string());
+}
+
+#[test]
+fn test_badgerfish_mixed_content() {
+    let document_node = read_quick_xml(Reader::from_str(MIXED)).unwrap();
+    let value = to_json(&document_node, &badgerfish());
+    assert_eq!(
+        value,
+        json!({
+            "p": {
+                "@class": "note",
+                "@id": "p1",
+                "$": "mixed  textmore",
+                "b": {"$": "bold & ", "i": {"$": "italic"}},
+                "br": {}
+            }
+        })
+    );
+
+    // the text, and each element, is kept, but not their order.
+    let copy_node = from_json(&value, &badgerfish()).unwrap();
+    assert_eq!(
+        copy_node.to_string(),
+        r#"<p class="note" id="p1">mixed  textmore<b>bold &#38; <i>italic</i></b><br></br></p>"#
+    );
+}
+
+#[test]
+fn test_errors() {
+    assert_eq!(from_jsonml(&json!("text")).err(), Some(Error::Syntax));
+    assert_eq!(from_jsonml(&json!([1, "text"])).err(), Some(Error::Syntax));
+    assert_eq!(
+        from_jsonml(&json!(["root", {"id": 1}])).err(),
+        Some(Error::Syntax)
+    );
+    assert_eq!(
+        from_jsonml(&json!(["root", true])).err(),
+        Some(Error::Syntax)
+    );
+    assert_eq!(
+        from_jsonml(&json!(["a:root"])).err(),
+        Some(Error::Namespace)
+    );
+    assert_eq!(
+        from_jsonml(&json!(["1root"])).err(),
+        Some(Error::InvalidCharacter)
+    );
+
+    assert_eq!(
+        from_json(&json!({"a": {}, "b": {}}), &badgerfish()).err(),
+        Some(Error::Syntax)
+    );
+    assert_eq!(
+        from_json(&json!({"a": {"b": "text"}}), &badgerfish()).err(),
+        Some(Error::Syntax)
+    );
+
+    let document_node = read_xml(r#"<list id="1"/>"#).unwrap();
+    let attribute_node = document_node
+        .document_element()
+        .unwrap()
+        .get_attribute_node("id")
+        .unwrap();
+    assert_eq!(to_jsonml(&attribute_node), serde_json::Value::Null);
+}
@@ -0,0 +1,72 @@
+#![cfg(feature = "macros")]
+
+use xml_dom::level2::*;
+use xml_dom::parser::read_xml;
+use xml_dom::xml;
+
+#[test]
+fn test_simple_tree() {
+    let document_node = read_xml("<catalog/>").unwrap();
+    let name = "two";
+    let element_node = xml!(document_node,
+        root["version" = "1.0", id = 1 + 1] {
+            child { "text" }
+            other[attr = "x"]
+            (name)
+            empty {}
+        }
+    )
+    .unwrap();
+
+    assert!(element_node.parent_node().is_none());
+    assert_eq!(element_node.owner_document(), Some(document_node.clone()));
+    assert_eq!(
+        element_node.to_string(),
+        r#"<root version="1.0" id="2"><child>text</child><other attr="x"></other>two<empty></empty></root>"#
+    );
+    assert_eq!(
+        xml!(document_node, root).unwrap().to_string(),
+        "<root></root>"
+    );
+}
+
+#[test]
+fn test_namespaces() {
+    let document_node = read_xml("<catalog/>").unwrap();
+    let element_node = xml!(document_node,
+        a:root["xmlns:a" = "urn:a", xmlns = "urn:default"] {
+            item[a:id = "1"]
+            a:item
+        }
+    )
+    .unwrap();
+
+    assert_eq!(element_node.namespace_uri(), Some("urn:a".to_string()));
+    assert_eq!(element_node.prefix(), Some("a".to_string()));
+    let item_node = element_node.first_child().unwrap();
+    assert_eq!(item_node.namespace_uri(), Some("urn:default".to_string()));
+    assert_eq!(
+        item_node.get_attribute_ns("urn:a", "id"),
+        Some("1".to_string())
+    );
+    let item_node = element_node.last_child().unwrap();
+    assert_eq!(item_node.namespace_uri(), Some("urn:a".to_string()));
+    assert_eq!(item_node.local_name(), "item".to_string());
+}
+
+#[test]
+fn test_errors() {
+    let document_node = read_xml("<catalog/>").unwrap();
+    let root_node = document_node.document_element().unwrap();
+
+    assert_eq!(xml!(root_node, item), Err(Error::InvalidState));
+    assert_eq!(xml!(document_node, a:item), Err(Error::Namespace));
+    assert_eq!(
+        xml!(document_node, item { other[a:id = 1] }),
+        Err(Error::Namespace)
+    );
+    assert_eq!(
+        xml!(document_node, item["1d" = 1]),
+        Err(Error::InvalidCharacter)
+    );
+}
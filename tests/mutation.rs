@@ -0,0 +1,347 @@
+#![cfg(feature = "quick_parser")]
+
+use std::sync::{Arc, Mutex};
+use xml_dom::level2::convert::*;
+use xml_dom::level2::ext::BulkChildren;
+use xml_dom::level2::mutation::*;
+use xml_dom::level2::*;
+use xml_dom::parser::read_xml;
+
+//
+// Record each mutation as a short string, so that the expected sequence is easy to read.
+//
+fn describe(mutation: &Mutation) -> String {
+    match mutation {
+        Mutation::NodeInserted { parent, node } => {
+            format!("inserted {} into {}", node.node_name(), parent.node_name())
+        }
+        Mutation::NodeRemoved { parent, node } => {
+            format!("removed {} from {}", node.node_name(), parent.node_name())
+        }
+        Mutation::AttributeChanged {
+            element,
+            name,
+            old_value,
+            new_value,
+        } => format!(
+            "attribute {}@{} {:?} -> {:?}",
+            element.node_name(),
+            name,
+            old_value,
+            new_value
+        ),
+        Mutation::CharacterDataChanged {
+            node,
+            old_value,
+            new_value,
+        } => format!(
+            "data {} {:?} -> {:?}",
+            node.node_name(),
+            old_value,
+            new_value
+        ),
+    }
+}
+
+fn listen(document_node: &mut RefNode) -> (ListenerId, Arc<Mutex<Vec<String>>>) {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let listener_log = log.clone();
+    let listener_id = document_node.add_mutation_listener(Box::new(move |mutation| {
+        listener_log.lock().unwrap().push(describe(mutation))
+    }));
+    let listener_id = listener_id.unwrap();
+    (listener_id, log)
+}
+
+fn take(log: &Arc<Mutex<Vec<String>>>) -> Vec<String> {
+    log.lock().unwrap().drain(..).collect()
+}
+
+#[test]
+fn test_tree_mutations() {
+    let mut document_node = read_xml(r#"<a><b/><c/></a>"#).unwrap();
+    let (_, log) = listen(&mut document_node);
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let b_node = root_node.first_child().unwrap();
+    let c_node = root_node.last_child().unwrap();
+
+    let _safe_to_ignore = root_node
+        .append_child(document.create_element("d").unwrap())
+        .unwrap();
+    let _safe_to_ignore = root_node.remove_child(b_node.clone()).unwrap();
+    let _safe_to_ignore = root_node
+        .insert_before(b_node.clone(), Some(c_node.clone()))
+        .unwrap();
+    assert_eq!(
+        take(&log),
+        vec!["inserted d into a", "removed b from a", "inserted b into a"]
+    );
+
+    //
+    // Moving a node is a removal from its old parent, then an insertion.
+    //
+    let mut c = c_node.clone();
+    let _safe_to_ignore = c.append_child(b_node.clone()).unwrap();
+    assert_eq!(take(&log), vec!["removed b from a", "inserted b into c"]);
+
+    let _safe_to_ignore = root_node
+        .replace_child(document.create_comment("gone").unwrap(), c_node)
+        .unwrap();
+    assert_eq!(
+        take(&log),
+        vec!["removed c from a", "inserted #comment into a"]
+    );
+
+    let mut fragment = document.create_document_fragment().unwrap();
+    let _safe_to_ignore = fragment
+        .append_child(document.create_element("e").unwrap())
+        .unwrap();
+    let _safe_to_ignore = fragment
+        .append_child(document.create_element("f").unwrap())
+        .unwrap();
+    assert_eq!(
+        take(&log),
+        vec![
+            "inserted e into #document-fragment",
+            "inserted f into #document-fragment"
+        ]
+    );
+    let _safe_to_ignore = root_node.append_child(fragment).unwrap();
+    assert_eq!(
+        take(&log),
+        vec![
+            "removed e from #document-fragment",
+            "removed f from #document-fragment",
+            "inserted e into a",
+            "inserted f into a"
+        ]
+    );
+}
+
+#[test]
+fn test_attribute_mutations() {
+    let mut document_node = read_xml(r#"<a x="1"/>"#).unwrap();
+    let (_, log) = listen(&mut document_node);
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+
+    root_node.set_attribute("x", "2").unwrap();
+    root_node.set_attribute("y", "3").unwrap();
+    root_node.remove_attribute("x").unwrap();
+    assert_eq!(
+        take(&log),
+        vec![
+            r#"attribute a@x Some("1") -> Some("2")"#,
+            r#"attribute a@y None -> Some("3")"#,
+            r#"attribute a@x Some("2") -> None"#,
+        ]
+    );
+
+    let mut attribute_node = root_node.get_attribute_node("y").unwrap();
+    attribute_node.set_value("4").unwrap();
+    let mut text_node = attribute_node.first_child().unwrap();
+    let _safe_to_ignore = attribute_node
+        .append_child(document.create_text_node("5"))
+        .unwrap();
+    let _safe_to_ignore = attribute_node.remove_child(text_node.clone()).unwrap();
+    assert_eq!(
+        take(&log),
+        vec![
+            r#"attribute a@y Some("3") -> Some("4")"#,
+            r#"attribute a@y Some("4") -> Some("45")"#,
+            r#"attribute a@y Some("45") -> Some("5")"#,
+        ]
+    );
+    text_node.set_node_value("detached").unwrap();
+    assert_eq!(
+        take(&log),
+        vec![r#"data #text Some("4") -> Some("detached")"#]
+    );
+
+    //
+    // Changes to an attribute that is not attached to an element are not reported.
+    //
+    let mut detached_node = document.create_attribute("z").unwrap();
+    detached_node.set_value("6").unwrap();
+    assert!(take(&log).is_empty());
+
+    root_node
+        .set_attribute_ns("http://example.org/", "p:y", "7")
+        .unwrap();
+    assert_eq!(take(&log), vec![r#"attribute a@p:y None -> Some("7")"#]);
+}
+
+#[test]
+fn test_character_data_mutations() {
+    let mut document_node = read_xml(r#"<a>one<!--two--><?pi three?></a>"#).unwrap();
+    let (_, log) = listen(&mut document_node);
+    let document = as_document(&document_node).unwrap();
+    let root_node = document.document_element().unwrap();
+    let mut text_node = root_node.first_child().unwrap();
+    let mut comment_node = text_node.next_sibling().unwrap();
+    let mut pi_node = root_node.last_child().unwrap();
+
+    {
+        let text = as_character_data_mut(&mut text_node).unwrap();
+        text.append_data(" more").unwrap();
+        text.insert_data(0, "just ").unwrap();
+        text.delete_data(0, 5).unwrap();
+        text.replace_data(0, 3, "1").unwrap();
+    }
+    as_character_data_mut(&mut comment_node)
+        .unwrap()
+        .set_data("2")
+        .unwrap();
+    pi_node.set_node_value("3").unwrap();
+    assert_eq!(
+        take(&log),
+        vec![
+            r#"data #text Some("one") -> Some("one more")"#,
+            r#"data #text Some("one more") -> Some("just one more")"#,
+            r#"data #text Some("just one more") -> Some("one more")"#,
+            r#"data #text Some("one more") -> Some("1 more")"#,
+            r#"data #comment Some("two") -> Some("2")"#,
+            r#"data pi Some("three") -> Some("3")"#,
+        ]
+    );
+
+    //
+    // Errors do not report a change.
+    //
+    assert!(as_character_data_mut(&mut text_node)
+        .unwrap()
+        .delete_data(99, 1)
+        .is_err());
+    assert!(take(&log).is_empty());
+}
+
+#[test]
+fn test_remove_mutation_listener() {
+    let mut document_node = read_xml(r#"<a/>"#).unwrap();
+    let (first_id, first_log) = listen(&mut document_node);
+    let (second_id, second_log) = listen(&mut document_node);
+    assert_ne!(first_id, second_id);
+
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    root_node.set_attribute("x", "1").unwrap();
+    assert_eq!(take(&first_log).len(), 1);
+    assert_eq!(take(&second_log).len(), 1);
+
+    document_node.remove_mutation_listener(first_id).unwrap();
+    assert_eq!(
+        document_node.remove_mutation_listener(first_id),
+        Err(Error::NotFound)
+    );
+    root_node.set_attribute("x", "2").unwrap();
+    assert!(take(&first_log).is_empty());
+    assert_eq!(take(&second_log).len(), 1);
+
+    //
+    // Only a document has listeners.
+    //
+    assert_eq!(
+        root_node.add_mutation_listener(Box::new(|_| {})),
+        Err(Error::InvalidState)
+    );
+    assert_eq!(
+        root_node.remove_mutation_listener(second_id),
+        Err(Error::InvalidState)
+    );
+
+    //
+    // Listeners are not copied to a cloned document.
+    //
+    let clone_node = document_node.clone_node(true).unwrap();
+    let mut clone_root = as_document(&clone_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    clone_root.set_attribute("x", "3").unwrap();
+    assert!(take(&second_log).is_empty());
+}
+
+#[test]
+fn test_reentrant_mutation_listener() {
+    let mut document_node = read_xml(r#"<a/>"#).unwrap();
+    let (_, log) = listen(&mut document_node);
+
+    //
+    // A listener may read, and change, the document; here each inserted element is given an
+    // attribute, which is itself reported.
+    //
+    let _safe_to_ignore = document_node
+        .add_mutation_listener(Box::new(|mutation| {
+            if let Mutation::NodeInserted { node, .. } = mutation {
+                if node.node_type() == NodeType::Element && node.get_attribute("seen").is_none() {
+                    let mut node = node.clone();
+                    node.set_attribute("seen", "yes").unwrap();
+                }
+            }
+        }))
+        .unwrap();
+
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    let new_node = root_node
+        .append_child(document.create_element("b").unwrap())
+        .unwrap();
+    assert_eq!(new_node.get_attribute("seen"), Some("yes".to_string()));
+    assert_eq!(
+        take(&log),
+        vec![
+            "inserted b into a",
+            r#"attribute b@seen None -> Some("yes")"#
+        ]
+    );
+}
+
+#[test]
+fn test_bulk_children_mutations() {
+    let mut document_node = read_xml(r#"<a><b/><c/></a>"#).unwrap();
+    let (_, log) = listen(&mut document_node);
+    let root_node = document_node.document_element().unwrap();
+    let mut c_node = root_node.last_child().unwrap();
+    let b_node = root_node.first_child().unwrap();
+
+    c_node
+        .append_children(vec![
+            document_node.create_element("d").unwrap(),
+            b_node,
+            document_node.create_element("e").unwrap(),
+        ])
+        .unwrap();
+    assert_eq!(
+        take(&log),
+        vec![
+            "inserted d into c",
+            "removed b from a",
+            "inserted b into c",
+            "inserted e into c"
+        ]
+    );
+
+    let _safe_to_ignore = c_node
+        .retain_children(|node| node.node_name().to_string() != "b")
+        .unwrap();
+    assert_eq!(take(&log), vec!["removed b from c"]);
+
+    c_node
+        .sort_children_by(|lhs, rhs| {
+            rhs.node_name()
+                .to_string()
+                .cmp(&lhs.node_name().to_string())
+        })
+        .unwrap();
+    assert_eq!(
+        take(&log),
+        vec![
+            "removed d from c",
+            "removed e from c",
+            "inserted d into c",
+            "inserted e into c"
+        ]
+    );
+    assert_eq!(root_node.to_string(), "<a><c><e></e><d></d></c></a>");
+}
@@ -0,0 +1,96 @@
+#![cfg(feature = "quick_parser")]
+
+use common::allocator::{allocated_bytes, CountingAllocator};
+use std::sync::Mutex;
+use xml_dom::level2::convert::*;
+use xml_dom::level2::ext::*;
+use xml_dom::level2::*;
+use xml_dom::parser::read_xml;
+
+mod common;
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+//
+// The heap used by a document is measured from the bytes currently allocated, so the tests in
+// this file hold `SERIAL` so that no other test allocates during a measurement.
+//
+static SERIAL: Mutex<()> = Mutex::new(());
+
+const ROWS: usize = 500;
+
+//
+// A document with `ROWS` elements, each with one attribute; the names are either all the same,
+// or all different but of the same length.
+//
+fn create_rows(same_names: bool) -> RefNode {
+    let mut document_node = get_implementation()
+        .create_document(None, Some("table"), None)
+        .unwrap();
+    let document = as_document_mut(&mut document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    for index in 0..ROWS {
+        let suffix = if same_names { 0 } else { index };
+        let mut row_node = document
+            .create_element(&format!("row_element_with_a_long_name_{:04}", suffix))
+            .unwrap();
+        row_node
+            .set_attribute(
+                &format!("row_attribute_with_a_long_name_{:04}", suffix),
+                "1",
+            )
+            .unwrap();
+        let _safe_to_ignore = root_node.append_child(row_node).unwrap();
+    }
+    document_node
+}
+
+fn heap_used(same_names: bool) -> usize {
+    let before = allocated_bytes();
+    let document_node = create_rows(same_names);
+    let used = allocated_bytes() - before;
+    drop(document_node);
+    used
+}
+
+#[test]
+fn test_names_are_shared() {
+    let _serial = SERIAL.lock().unwrap();
+    let document_node = create_rows(true);
+    assert_eq!(document_node.name_pool_len(), 3);
+
+    let root_node = document_node.document_element().unwrap();
+    let first = root_node.first_child().unwrap();
+    let last = root_node.last_child().unwrap();
+    assert_eq!(first.node_name(), last.node_name());
+    assert_eq!(
+        first.node_name().local_name(),
+        "row_element_with_a_long_name_0000"
+    );
+    assert_eq!(create_rows(false).name_pool_len(), ROWS * 2 + 1);
+}
+
+#[test]
+fn test_parsed_names_are_shared() {
+    let _serial = SERIAL.lock().unwrap();
+    let document_node =
+        read_xml(r#"<list><item id="1"/><item id="2"/><other id="3"/></list>"#).unwrap();
+    assert_eq!(document_node.name_pool_len(), 4);
+    assert_eq!(document_node.document_element().unwrap().name_pool_len(), 0);
+}
+
+#[test]
+fn test_heap_used() {
+    let _serial = SERIAL.lock().unwrap();
+    //
+    // Each distinct element, or attribute, name costs at least its own string, and so each row of
+    // the second document costs more than twice the length of the names more than the first. The
+    // first document is built once beforehand, so that anything cached on first use, such as the
+    // compiled regular expressions, is not counted.
+    //
+    let _safe_to_ignore = heap_used(true);
+    let same_names = heap_used(true);
+    let different_names = heap_used(false);
+    assert!(same_names + ROWS * 2 * 33 < different_names);
+}
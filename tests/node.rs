@@ -1,13 +1,25 @@
+#[cfg(feature = "quick_parser")]
+use std::sync::Mutex;
 use xml_dom::level2::convert::*;
 use xml_dom::level2::ext::dom_impl as ext_dom_impl;
+#[cfg(feature = "quick_parser")]
+use xml_dom::level2::ext::wrap::{wrap_nodes, wrap_nodes_ns};
+#[cfg(feature = "quick_parser")]
+use xml_dom::level2::ext::{
+    BulkChildren, InsertPosition, NodePath, ResolvePath, SelfManipulation, TreeIterators,
+    TreeMembership, WrapElements,
+};
 use xml_dom::level2::*;
+#[cfg(feature = "quick_parser")]
+use xml_dom::parser::read_xml;
 
 pub mod common;
 
 #[test]
 fn test_is_child_allowed() {
     //
-    // This logic is shared by append, insert, and replace, so we only test once.
+    // This logic is shared by append, insert, and replace, so we only test once. The document
+    // fragments created here are empty, so they may be added to any node that may have children.
     //
     let test_matrix: Vec<(NodeType, Vec<NodeType>)> = vec![
         (
@@ -19,11 +31,16 @@ fn test_is_child_allowed() {
                 NodeType::ProcessingInstruction,
                 NodeType::CData,
                 NodeType::EntityReference,
+                NodeType::DocumentFragment,
             ],
         ),
         (
             NodeType::Attribute,
-            vec![NodeType::Text, NodeType::EntityReference],
+            vec![
+                NodeType::Text,
+                NodeType::EntityReference,
+                NodeType::DocumentFragment,
+            ],
         ),
         (NodeType::Text, vec![]),
         (NodeType::CData, vec![]),
@@ -36,6 +53,7 @@ fn test_is_child_allowed() {
                 NodeType::ProcessingInstruction,
                 NodeType::CData,
                 NodeType::EntityReference,
+                NodeType::DocumentFragment,
             ],
         ),
         (
@@ -47,6 +65,7 @@ fn test_is_child_allowed() {
                 NodeType::ProcessingInstruction,
                 NodeType::CData,
                 NodeType::EntityReference,
+                NodeType::DocumentFragment,
             ],
         ),
         (NodeType::ProcessingInstruction, vec![]),
@@ -61,6 +80,7 @@ fn test_is_child_allowed() {
                 NodeType::ProcessingInstruction,
                 NodeType::CData,
                 NodeType::EntityReference,
+                NodeType::DocumentFragment,
             ],
         ),
         (NodeType::Notation, vec![]),
@@ -91,7 +111,7 @@ fn test_insert_child_node() {
 
     {
         common::sub_test("test_insert_child_node", "insert_before(_, mid_node)");
-        let mid_node = child_nodes.get(2).unwrap();
+        let mid_node = child_nodes.item(2).unwrap();
         let new_child_node = ref_document.create_element("inserted-1").unwrap();
         let result = mut_root.insert_before(new_child_node, Some(mid_node.clone()));
         assert!(result.is_ok());
@@ -164,6 +184,44 @@ fn test_insert_child_node() {
         let result = mut_root.insert_before(new_child_node, Some(not_a_child));
         assert!(result.is_err());
     }
+
+    {
+        common::sub_test(
+            "test_insert_child_node",
+            "insert_before(other_child, other_child)",
+        );
+        let mut other_node = ref_document.create_element("other").unwrap();
+        let other_child_node = ref_document.create_element("other-child").unwrap();
+        let _safe_to_ignore = other_node.append_child(other_child_node.clone()).unwrap();
+        let result =
+            mut_root.insert_before(other_child_node.clone(), Some(other_child_node.clone()));
+        assert_eq!(result, Err(Error::NotFound));
+        assert_eq!(other_child_node.parent_node(), Some(other_node));
+        assert_eq!(mut_root.child_nodes().len(), 8);
+    }
+
+    {
+        common::sub_test(
+            "test_insert_child_node",
+            "insert_before(mid_node, mid_node)",
+        );
+        let mid_node = child_nodes.item(2).unwrap();
+        let result = mut_root.insert_before(mid_node.clone(), Some(mid_node.clone()));
+        assert!(result.is_ok());
+        compare_node_names(
+            &mut_root.child_nodes(),
+            &[
+                "inserted-2",
+                "child-1",
+                "child-2",
+                "inserted-1",
+                "child-3",
+                "child-4",
+                "child-5",
+                "inserted-3",
+            ],
+        );
+    }
 }
 
 #[test]
@@ -181,7 +239,7 @@ fn test_replace_child_node() {
 
     {
         common::sub_test("test_replace_child_node", "remove_child(mid_node)");
-        let mid_node = child_nodes.get(2).unwrap();
+        let mid_node = child_nodes.item(2).unwrap();
         let new_child_node = ref_document.create_element("inserted-1").unwrap();
         let result = mut_root.replace_child(new_child_node, mid_node.clone());
         assert!(result.is_ok());
@@ -226,7 +284,7 @@ fn test_replace_child_node() {
         let not_a_child = ref_document.create_element("not-a-child").unwrap();
         let new_child_node = ref_document.create_element("not-inserted").unwrap();
         let result = mut_root.replace_child(new_child_node, not_a_child);
-        assert!(result.is_err());
+        assert_eq!(result, Err(Error::NotFound));
     }
 }
 
@@ -245,7 +303,7 @@ fn test_remove_child_node() {
 
     {
         common::sub_test("test_remove_child_node", "remove_child(mid_node)");
-        let mid_node = child_nodes.get(2).unwrap();
+        let mid_node = child_nodes.item(2).unwrap();
         let result = mut_root.remove_child(mid_node.clone());
         assert!(result.is_ok());
         compare_node_names(
@@ -274,10 +332,64 @@ fn test_remove_child_node() {
         common::sub_test("test_remove_child_node", "remove_child(not_a_child)");
         let not_a_child = ref_document.create_element("not-a-child").unwrap();
         let result = mut_root.remove_child(not_a_child);
-        assert!(result.is_err());
+        assert_eq!(result, Err(Error::NotFound));
     }
 }
 
+#[test]
+fn test_remove_child_twice() {
+    let document_node = make_sibling_document();
+    let mut root_node = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    let child_node = root_node.first_child().unwrap();
+
+    let removed = root_node.remove_child(child_node.clone()).unwrap();
+    assert_eq!(removed, child_node);
+    assert!(removed.parent_node().is_none());
+    assert_eq!(
+        root_node.remove_child(child_node.clone()),
+        Err(Error::NotFound)
+    );
+    assert_eq!(root_node.child_nodes().len(), 4);
+
+    //
+    // The removed node is a valid orphan that can be added again.
+    //
+    let mut last_node = root_node.last_child().unwrap();
+    assert!(last_node.append_child(removed).is_ok());
+    assert_eq!(child_node.parent_node(), Some(last_node));
+    assert_eq!(child_node.owner_document(), Some(document_node.clone()));
+}
+
+#[test]
+fn test_remove_child_clone() {
+    let document_node = make_sibling_document();
+    let mut root_node = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    let child_node = root_node.first_child().unwrap();
+    let clone_node = child_node.clone_node(true).unwrap();
+    assert!(child_node.is_equal_node(&clone_node));
+
+    assert_eq!(
+        root_node.remove_child(clone_node.clone()),
+        Err(Error::NotFound)
+    );
+    let new_node = as_document(&document_node)
+        .unwrap()
+        .create_element("new")
+        .unwrap();
+    assert_eq!(
+        root_node.replace_child(new_node, clone_node),
+        Err(Error::NotFound)
+    );
+    assert_eq!(root_node.child_nodes().len(), 5);
+    assert_eq!(child_node.parent_node(), Some(root_node));
+}
+
 #[test]
 fn test_next_sibling() {
     let document_node = make_sibling_document();
@@ -289,8 +401,8 @@ fn test_next_sibling() {
     //
     // Ask for siblings
     //
-    let mid_node = child_nodes.get(2).unwrap();
-    let ref_mid = as_element(mid_node).unwrap();
+    let mid_node = child_nodes.item(2).unwrap();
+    let ref_mid = as_element(&mid_node).unwrap();
     assert_eq!(ref_mid.node_name().to_string(), "child-3".to_string());
 
     common::sub_test("test_next_sibling", "next_sibling() 1");
@@ -319,8 +431,8 @@ fn test_previous_sibling() {
     //
     // Ask for siblings
     //
-    let mid_node = child_nodes.get(2).unwrap();
-    let ref_mid = as_element(mid_node).unwrap();
+    let mid_node = child_nodes.item(2).unwrap();
+    let ref_mid = as_element(&mid_node).unwrap();
     assert_eq!(ref_mid.node_name().to_string(), "child-3".to_string());
 
     common::sub_test("test_previous_sibling", "previous_sibling() 1");
@@ -339,9 +451,95 @@ fn test_previous_sibling() {
 }
 
 #[test]
-#[ignore]
 fn test_clone_node() {
-    unimplemented!()
+    let document_node = common::create_example_rdf_document();
+    let ref_document = as_document(&document_node).unwrap();
+    let title_node = ref_document.get_element_by_id("title").unwrap();
+
+    {
+        common::sub_test("test_clone_node", "shallow");
+        let mut cloned_node = title_node.clone_node(false).unwrap();
+        assert_ne!(cloned_node, title_node);
+        assert!(cloned_node.parent_node().is_none());
+        assert_eq!(cloned_node.owner_document().unwrap(), document_node);
+        assert_eq!(cloned_node.node_name(), title_node.node_name());
+        assert!(!cloned_node.has_child_nodes());
+        assert!(cloned_node.has_attributes());
+
+        let cloned_attribute = cloned_node.get_attribute_node("xml:id").unwrap();
+        let original_attribute = title_node.get_attribute_node("xml:id").unwrap();
+        assert_ne!(cloned_attribute, original_attribute);
+        assert_eq!(cloned_attribute.owner_element().unwrap(), cloned_node);
+
+        let _safe_to_ignore = cloned_node.set_attribute("xml:lang", "en");
+        assert!(!title_node.has_attribute("xml:lang"));
+        let mut cloned_attribute = cloned_attribute;
+        let _safe_to_ignore = cloned_attribute.set_value("changed");
+        assert_eq!(
+            title_node.get_attribute("xml:id"),
+            Some("title".to_string())
+        );
+    }
+
+    {
+        common::sub_test("test_clone_node", "deep");
+        let cloned_node = title_node.clone_node(true).unwrap();
+        assert!(cloned_node.parent_node().is_none());
+        assert_eq!(cloned_node.child_nodes().len(), 1);
+        let cloned_text = cloned_node.first_child().unwrap();
+        assert_ne!(cloned_text, title_node.first_child().unwrap());
+        assert_eq!(cloned_text.parent_node().unwrap(), cloned_node);
+        assert_eq!(cloned_node.to_string(), title_node.to_string());
+    }
+}
+
+#[test]
+fn test_clone_document_node() {
+    let implementation = get_implementation();
+    let document_type = implementation
+        .create_document_type("rdf:RDF", None, Some("http://example.org/rdf.dtd"))
+        .unwrap();
+    let document_node = implementation
+        .create_document(Some(common::RDF_NS), Some("rdf:RDF"), Some(document_type))
+        .unwrap();
+    let ref_document = as_document(&document_node).unwrap();
+    let mut root_node = ref_document.document_element().unwrap();
+    let _safe_to_ignore = root_node.set_attribute("xml:id", "main");
+    let _safe_to_ignore = append_element_node(&mut root_node, "child");
+
+    {
+        common::sub_test("test_clone_document_node", "shallow");
+        let cloned_node = document_node.clone_node(false).unwrap();
+        let cloned_document = as_document(&cloned_node).unwrap();
+        assert!(cloned_document.doc_type().is_none());
+        assert!(cloned_document.document_element().is_none());
+    }
+
+    {
+        common::sub_test("test_clone_document_node", "deep");
+        let cloned_node = document_node.clone_node(true).unwrap();
+        let cloned_document = as_document(&cloned_node).unwrap();
+
+        let cloned_doc_type = cloned_document.doc_type().unwrap();
+        assert_ne!(cloned_doc_type, ref_document.doc_type().unwrap());
+        assert_eq!(
+            as_document_type(&cloned_doc_type).unwrap().system_id(),
+            Some("http://example.org/rdf.dtd".to_string())
+        );
+
+        let cloned_root = cloned_document.document_element().unwrap();
+        assert_ne!(cloned_root, root_node);
+        assert_eq!(cloned_root.parent_node().unwrap(), cloned_node);
+        assert_eq!(cloned_root.owner_document().unwrap(), cloned_node);
+        let cloned_child = cloned_root.first_child().unwrap();
+        assert_eq!(cloned_child.owner_document().unwrap(), cloned_node);
+        assert_eq!(cloned_root.to_string(), root_node.to_string());
+
+        assert_eq!(
+            cloned_document.get_element_by_id("main").unwrap(),
+            cloned_root
+        );
+    }
 }
 
 #[test]
@@ -373,6 +571,72 @@ fn test_normalize() {
     }
 }
 
+#[test]
+fn test_normalize_deep() {
+    let document_node = get_implementation()
+        .create_document(Some("http://example.org/"), Some("root"), None)
+        .unwrap();
+    let ref_document = as_document(&document_node).unwrap();
+    let mut root_node = ref_document.document_element().unwrap();
+    let mut child_node = append_element_node(&mut root_node, "element-1");
+    let mut grand_child_node = append_element_node(&mut child_node, "element-2");
+    {
+        let _safe_to_ignore = append_text_node(&mut grand_child_node, "text-1");
+        let _safe_to_ignore = append_text_node(&mut grand_child_node, "");
+        let _safe_to_ignore = append_text_node(&mut grand_child_node, "text-2");
+        let _safe_to_ignore =
+            grand_child_node.append_child(ref_document.create_cdata_section("cdata-1").unwrap());
+        let _safe_to_ignore = append_text_node(&mut grand_child_node, "text-3");
+        let _safe_to_ignore = append_text_node(&mut grand_child_node, "text-4");
+    }
+
+    {
+        assert_eq!(grand_child_node.child_nodes().len(), 6);
+    }
+
+    root_node.normalize();
+
+    {
+        let child_nodes = grand_child_node.child_nodes();
+        assert_eq!(child_nodes.len(), 3);
+        assert_eq!(
+            child_nodes.item(0).unwrap().node_value(),
+            Some("text-1text-2".to_string())
+        );
+        assert_eq!(child_nodes.item(1).unwrap().node_type(), NodeType::CData);
+        assert_eq!(
+            child_nodes.item(2).unwrap().node_value(),
+            Some("text-3text-4".to_string())
+        );
+    }
+}
+
+#[test]
+fn test_normalize_attribute() {
+    let document_node = get_implementation()
+        .create_document(Some("http://example.org/"), Some("root"), None)
+        .unwrap();
+    let ref_document = as_document(&document_node).unwrap();
+    let mut root_node = ref_document.document_element().unwrap();
+    let mut attribute_node = ref_document.create_attribute_with("test", "one").unwrap();
+    {
+        let _safe_to_ignore = attribute_node.append_child(ref_document.create_text_node(""));
+        let _safe_to_ignore = attribute_node.append_child(ref_document.create_text_node("two"));
+        let _safe_to_ignore = root_node.set_attribute_node(attribute_node.clone());
+    }
+
+    {
+        assert_eq!(attribute_node.child_nodes().len(), 3);
+    }
+
+    root_node.normalize();
+
+    {
+        assert_eq!(attribute_node.child_nodes().len(), 1);
+        assert_eq!(root_node.get_attribute("test"), Some("onetwo".to_string()));
+    }
+}
+
 #[test]
 fn test_normalize_empty() {
     let document_node = get_implementation()
@@ -397,6 +661,273 @@ fn test_normalize_empty() {
     }
 }
 
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_append_self() {
+    let document_node = read_xml("<root><child/></root>").unwrap();
+    let mut child_node = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap()
+        .first_child()
+        .unwrap();
+    assert_eq!(
+        child_node.append_child(child_node.clone()),
+        Err(Error::HierarchyRequest)
+    );
+    assert_eq!(
+        child_node.insert_before(child_node.clone(), None),
+        Err(Error::HierarchyRequest)
+    );
+    assert!(!child_node.has_child_nodes());
+    assert_eq!(document_node.to_string(), "<root><child></child></root>");
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_append_ancestor() {
+    let document_node = read_xml("<root><child><grandchild/></child></root>").unwrap();
+    let root_node = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    let child_node = root_node.first_child().unwrap();
+    let mut grandchild_node = child_node.first_child().unwrap();
+    assert_eq!(
+        grandchild_node.append_child(child_node.clone()),
+        Err(Error::HierarchyRequest)
+    );
+    assert_eq!(
+        grandchild_node.append_child(root_node.clone()),
+        Err(Error::HierarchyRequest)
+    );
+
+    let mut other_node = as_document(&document_node)
+        .unwrap()
+        .create_element("other")
+        .unwrap();
+    let _safe_to_ignore = grandchild_node.append_child(other_node.clone()).unwrap();
+    assert_eq!(
+        other_node.replace_child(child_node.clone(), other_node.clone()),
+        Err(Error::HierarchyRequest)
+    );
+
+    //
+    // The failed calls leave the tree, and its serialization, unchanged.
+    //
+    assert_eq!(child_node.parent_node(), Some(root_node));
+    assert_eq!(
+        document_node.to_string(),
+        "<root><child><grandchild><other></other></grandchild></child></root>"
+    );
+}
+
+fn make_fragment(document_node: &RefNode, names: &[&str]) -> RefNode {
+    let document = as_document(document_node).unwrap();
+    let mut fragment_node = document.create_document_fragment().unwrap();
+    for name in names {
+        let _safe_to_ignore = fragment_node
+            .append_child(document.create_element(name).unwrap())
+            .unwrap();
+    }
+    fragment_node
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_fragment_splice() {
+    let document_node = read_xml("<root><a/><b/></root>").unwrap();
+    let mut root_node = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+
+    let fragment_node = make_fragment(&document_node, &["x", "y"]);
+    let result = root_node.append_child(fragment_node.clone()).unwrap();
+    assert_eq!(result, fragment_node);
+    assert!(!fragment_node.has_child_nodes());
+    assert_eq!(
+        root_node.to_string(),
+        "<root><a></a><b></b><x></x><y></y></root>"
+    );
+    assert_eq!(
+        root_node.last_child().unwrap().parent_node(),
+        Some(root_node.clone())
+    );
+
+    let b_node = root_node.child_nodes().item(1).unwrap();
+    let fragment_node = make_fragment(&document_node, &["p", "q"]);
+    let result = root_node
+        .insert_before(fragment_node.clone(), Some(b_node.clone()))
+        .unwrap();
+    assert_eq!(result, fragment_node);
+    assert!(!fragment_node.has_child_nodes());
+    assert_eq!(
+        root_node.to_string(),
+        "<root><a></a><p></p><q></q><b></b><x></x><y></y></root>"
+    );
+
+    let fragment_node = make_fragment(&document_node, &["r", "s"]);
+    let result = root_node
+        .replace_child(fragment_node.clone(), b_node.clone())
+        .unwrap();
+    assert_eq!(result, b_node);
+    assert!(b_node.parent_node().is_none());
+    assert!(!fragment_node.has_child_nodes());
+    assert_eq!(
+        root_node.to_string(),
+        "<root><a></a><p></p><q></q><r></r><s></s><x></x><y></y></root>"
+    );
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_fragment_splice_empty() {
+    let document_node = read_xml("<root><a/></root>").unwrap();
+    let mut root_node = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    let fragment_node = make_fragment(&document_node, &[]);
+    let result = root_node.append_child(fragment_node.clone());
+    assert_eq!(result, Ok(fragment_node.clone()));
+    let a_node = root_node.first_child();
+    assert!(root_node.insert_before(fragment_node, a_node).is_ok());
+    assert_eq!(root_node.to_string(), "<root><a></a></root>");
+}
+
+#[test]
+fn test_fragment_splice_invalid() {
+    //
+    // A fragment is checked as a whole, so nothing is moved when any child is not allowed.
+    //
+    let mut document_node = get_implementation()
+        .create_document(None, None, None)
+        .unwrap();
+    let fragment_node = make_fragment(&document_node, &["one", "two"]);
+    assert_eq!(
+        document_node.append_child(fragment_node.clone()),
+        Err(Error::HierarchyRequest)
+    );
+    assert_eq!(fragment_node.child_nodes().len(), 2);
+    assert!(!document_node.has_child_nodes());
+
+    let mut fragment_node = make_fragment(&document_node, &["one"]);
+    let text_node = as_document(&document_node)
+        .unwrap()
+        .create_text_node("text");
+    let _safe_to_ignore = fragment_node.append_child(text_node).unwrap();
+    assert_eq!(
+        document_node.append_child(fragment_node.clone()),
+        Err(Error::HierarchyRequest)
+    );
+    assert_eq!(fragment_node.child_nodes().len(), 2);
+
+    let fragment_node = make_fragment(&document_node, &["one"]);
+    assert!(document_node.append_child(fragment_node).is_ok());
+    assert!(as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .is_some());
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_read_only_entity_reference() {
+    let document_node = read_xml("<root/>").unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut reference_node = document.create_entity_reference("ref").unwrap();
+    let mut element_node = document.create_element("content").unwrap();
+    assert!(element_node.set_attribute("attr", "value").is_ok());
+    let mut text_node = element_node
+        .append_child(document.create_text_node("text"))
+        .unwrap();
+    assert!(reference_node.append_child(element_node.clone()).is_ok());
+
+    assert_eq!(
+        element_node.append_child(document.create_comment("comment").unwrap()),
+        Err(Error::NoModificationAllowed)
+    );
+    assert_eq!(
+        element_node.remove_child(text_node.clone()),
+        Err(Error::NoModificationAllowed)
+    );
+    assert_eq!(
+        element_node.set_attribute("attr", "other"),
+        Err(Error::NoModificationAllowed)
+    );
+    assert_eq!(
+        element_node.remove_attribute("attr"),
+        Err(Error::NoModificationAllowed)
+    );
+    let mut attribute_node = element_node.get_attribute_node("attr").unwrap();
+    assert_eq!(
+        attribute_node.set_value("other"),
+        Err(Error::NoModificationAllowed)
+    );
+    assert_eq!(
+        text_node.set_node_value("other"),
+        Err(Error::NoModificationAllowed)
+    );
+    assert_eq!(
+        text_node.append_data("other"),
+        Err(Error::NoModificationAllowed)
+    );
+    assert_eq!(text_node.split(1), Err(Error::NoModificationAllowed));
+    assert_eq!(
+        element_node.to_string(),
+        "<content attr=\"value\">text</content>"
+    );
+
+    //
+    // A clone is writable, except for the children of a cloned entity reference.
+    //
+    let mut clone_node = element_node.clone_node(true).unwrap();
+    assert!(clone_node.set_attribute("attr", "other").is_ok());
+    let mut reference_clone = reference_node.clone_node(true).unwrap();
+    assert_eq!(
+        reference_clone
+            .first_child()
+            .unwrap()
+            .set_attribute("attr", "other"),
+        Err(Error::NoModificationAllowed)
+    );
+    assert!(reference_clone
+        .remove_child(reference_clone.first_child().unwrap())
+        .is_ok());
+
+    //
+    // Once removed from the entity reference the subtree is writable again.
+    //
+    let _safe_to_ignore = reference_node.remove_child(element_node.clone()).unwrap();
+    assert!(text_node.set_node_value("other").is_ok());
+    assert!(element_node.set_attribute("attr", "other").is_ok());
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_read_only_entity_and_notation() {
+    let document_node = read_xml("<root/>").unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut entity_node =
+        ext_dom_impl::create_internal_entity(document_node.clone(), "entity", "value").unwrap();
+    let mut text_node = entity_node
+        .append_child(document.create_text_node("value"))
+        .unwrap();
+    assert_eq!(
+        text_node.set_node_value("other"),
+        Err(Error::NoModificationAllowed)
+    );
+
+    let mut notation_node =
+        ext_dom_impl::create_notation(document_node.clone(), "notation", None, Some("file.xml"))
+            .unwrap();
+    assert_eq!(
+        notation_node.set_node_value("other"),
+        Err(Error::NoModificationAllowed)
+    );
+}
+
 #[test]
 fn test_wrong_document() {
     let document_1_node = get_implementation()
@@ -418,6 +949,58 @@ fn test_wrong_document() {
     assert_eq!(result, Err(Error::WrongDocument))
 }
 
+#[test]
+fn test_wrong_document_insertion() {
+    let document_1_node = get_implementation()
+        .create_document(None, Some("root-1"), None)
+        .unwrap();
+    let document_1 = as_document(&document_1_node).unwrap();
+    let document_2_node = get_implementation()
+        .create_document(None, Some("root-2"), None)
+        .unwrap();
+    let document_2 = as_document(&document_2_node).unwrap();
+    let mut root_2_node = document_2.document_element().unwrap();
+    let existing_node = root_2_node
+        .append_child(document_2.create_element("existing").unwrap())
+        .unwrap();
+
+    let foreign_node = document_1.create_element("foreign").unwrap();
+    assert_eq!(
+        root_2_node.append_child(foreign_node.clone()),
+        Err(Error::WrongDocument)
+    );
+    assert_eq!(
+        root_2_node.insert_before(foreign_node.clone(), Some(existing_node.clone())),
+        Err(Error::WrongDocument)
+    );
+    assert_eq!(
+        root_2_node.replace_child(foreign_node.clone(), existing_node.clone()),
+        Err(Error::WrongDocument)
+    );
+    assert_eq!(existing_node.parent_node(), Some(root_2_node.clone()));
+    assert_eq!(root_2_node.child_nodes().len(), 1);
+
+    let foreign_attribute = document_1.create_attribute_with("x", "1").unwrap();
+    assert_eq!(
+        as_element_mut(&mut root_2_node)
+            .unwrap()
+            .set_attribute_node(foreign_attribute.clone()),
+        Err(Error::WrongDocument)
+    );
+
+    //
+    // Once adopted, or imported, the node may be added.
+    //
+    let imported_attribute = document_2.import_node(foreign_attribute, true).unwrap();
+    assert!(as_element_mut(&mut root_2_node)
+        .unwrap()
+        .set_attribute_node(imported_attribute)
+        .is_ok());
+    let adopted_node = document_2.adopt_node(foreign_node).unwrap();
+    assert!(root_2_node.append_child(adopted_node).is_ok());
+    assert_eq!(root_2_node.child_nodes().len(), 2);
+}
+
 // ------------------------------------------------------------------------------------------------
 // Private Functions
 // ------------------------------------------------------------------------------------------------
@@ -433,7 +1016,7 @@ fn make_node(document: RefNode, node_type: NodeType, prefix: &str) -> RefNode {
         NodeType::ProcessingInstruction => document
             .create_processing_instruction(&named("pi"), None)
             .unwrap(),
-        NodeType::Comment => document.create_comment(&named("comment")),
+        NodeType::Comment => document.create_comment(&named("comment")).unwrap(),
         NodeType::Document => document,
         NodeType::DocumentType => {
             let implementation = get_implementation();
@@ -472,8 +1055,8 @@ const ALL_CHILDREN: [NodeType; 12] = [
     NodeType::Notation,
 ];
 
-fn test_parent(document: RefNode, parent_type: NodeType, allowed: &Vec<NodeType>) {
-    let mut parent_node = make_node(document.clone(), parent_type.clone(), "parent");
+fn test_parent(document: RefNode, parent_type: NodeType, allowed: &[NodeType]) {
+    let mut parent_node = make_node(document.clone(), parent_type, "parent");
     for child_type in ALL_CHILDREN.iter() {
         common::sub_test(
             "test_is_child_allowed",
@@ -481,13 +1064,13 @@ fn test_parent(document: RefNode, parent_type: NodeType, allowed: &Vec<NodeType>
                 "{:?}.append_child({:?}) -> {}?",
                 parent_type,
                 child_type,
-                allowed.contains(&child_type)
+                allowed.contains(child_type)
             ),
         );
-        let child_node = make_node(document.clone(), child_type.clone(), "child");
+        let child_node = make_node(document.clone(), *child_type, "child");
         assert_eq!(
             parent_node.append_child(child_node).is_ok(),
-            allowed.contains(&child_type)
+            allowed.contains(child_type)
         );
     }
 }
@@ -534,8 +1117,1221 @@ fn make_sibling_document() -> RefNode {
     document_node
 }
 
-fn compare_node_names(nodes: &Vec<RefNode>, expected_names: &[&str]) {
+#[test]
+fn test_node_list_is_live() {
+    let document_node = make_sibling_document();
+    let ref_document = as_document(&document_node).unwrap();
+
+    let mut root_node = ref_document.document_element().unwrap();
+    let child_nodes = root_node.child_nodes();
+    assert_eq!(child_nodes.len(), 5);
+    assert_eq!(child_nodes.item(0), root_node.first_child());
+    assert_eq!(child_nodes.last(), root_node.last_child());
+    assert!(child_nodes.item(5).is_none());
+
+    let new_child_node = ref_document.create_element("child-6").unwrap();
+    let _safe_to_ignore = root_node.append_child(new_child_node.clone()).unwrap();
+    assert_eq!(child_nodes.len(), 6);
+    assert_eq!(child_nodes.item(5).unwrap(), new_child_node);
+
+    let snapshot = child_nodes.to_vec();
+    for child_node in snapshot {
+        let _safe_to_ignore = root_node.remove_child(child_node).unwrap();
+    }
+    assert!(child_nodes.is_empty());
+    assert_eq!(child_nodes.iter().count(), 0);
+}
+
+#[test]
+fn test_node_list_iter() {
+    let document_node = make_sibling_document();
+    let ref_document = as_document(&document_node).unwrap();
+    let root_node = ref_document.document_element().unwrap();
+
+    let mut names = Vec::default();
+    for child_node in root_node.child_nodes() {
+        names.push(child_node.node_name().to_string());
+    }
+    assert_eq!(
+        names,
+        vec!["child-1", "child-2", "child-3", "child-4", "child-5"]
+    );
+
+    let child_nodes = {
+        let element = ref_document.create_element("dropped").unwrap();
+        let _safe_to_ignore = element
+            .clone()
+            .append_child(ref_document.create_text_node("text"));
+        element.child_nodes()
+    };
+    assert!(child_nodes.is_empty());
+    assert!(child_nodes.first().is_none());
+}
+
+fn compare_node_names(nodes: &NodeList, expected_names: &[&str]) {
     let names: Vec<String> = nodes.iter().map(|n| n.node_name().to_string()).collect();
     let expected_names: Vec<String> = expected_names.iter().map(|s| String::from(*s)).collect();
     assert_eq!(names, expected_names);
 }
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_text_content() {
+    let document_node =
+        read_xml(r#"<a x="1">one<!--two--><b>three<?pi four?></b><![CDATA[<five>]]></a>"#).unwrap();
+    let document = as_document(&document_node).unwrap();
+    assert_eq!(document_node.text_content(), None);
+
+    let root_node = document.document_element().unwrap();
+    assert_eq!(root_node.text_content(), Some("onethree<five>".to_string()));
+
+    let comment_node = root_node.child_nodes().item(1).unwrap();
+    assert_eq!(comment_node.text_content(), Some("two".to_string()));
+
+    let attribute_node = as_element(&root_node)
+        .unwrap()
+        .get_attribute_node("x")
+        .unwrap();
+    assert_eq!(attribute_node.text_content(), Some("1".to_string()));
+
+    let empty_node = document.create_element("empty").unwrap();
+    assert_eq!(empty_node.text_content(), Some(String::new()));
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_set_text_content() {
+    let mut document_node = read_xml(r#"<a>one<b>two</b><!--three--></a>"#).unwrap();
+    let mut root_node = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    let b_node = root_node.child_nodes().item(1).unwrap();
+
+    assert!(root_node.set_text_content("a < b").is_ok());
+    assert_eq!(root_node.child_nodes().len(), 1);
+    let text_node = root_node.first_child().unwrap();
+    assert_eq!(text_node.node_type(), NodeType::Text);
+    assert_eq!(text_node.node_value(), Some("a < b".to_string()));
+    assert_eq!(b_node.parent_node(), None);
+
+    assert!(root_node.set_text_content("").is_ok());
+    assert!(!root_node.has_child_nodes());
+
+    let mut text_node = text_node;
+    assert!(text_node.set_text_content("changed").is_ok());
+    assert_eq!(text_node.node_value(), Some("changed".to_string()));
+
+    assert!(document_node.set_text_content("ignored").is_ok());
+    assert_eq!(document_node.child_nodes().len(), 1);
+}
+
+#[test]
+fn test_set_text_content_without_document() {
+    let mut root_node = {
+        let document_node = common::create_empty_rdf_document();
+        let document = as_document(&document_node).unwrap();
+        document.document_element().unwrap()
+    };
+    assert!(root_node.owner_document().is_none());
+    assert_eq!(
+        root_node.set_text_content("text"),
+        Err(Error::WrongDocument)
+    );
+    assert!(root_node.set_text_content("").is_ok());
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_lookup_namespace() {
+    let document_node = read_xml(
+        r#"<a xmlns="urn:default" xmlns:x="urn:x"><b xmlns:y="urn:y" y:attr="1"><c xmlns:x="urn:other" xmlns="">text</c></b></a>"#,
+    )
+    .unwrap();
+    let root_node = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    let b_node = root_node.first_child().unwrap();
+    let c_node = b_node.first_child().unwrap();
+    let text_node = c_node.first_child().unwrap();
+
+    assert_eq!(
+        document_node.lookup_namespace_uri(None),
+        Some("urn:default".to_string())
+    );
+    assert_eq!(
+        b_node.lookup_namespace_uri(Some("x")),
+        Some("urn:x".to_string())
+    );
+    assert_eq!(
+        b_node.lookup_namespace_uri(Some("y")),
+        Some("urn:y".to_string())
+    );
+    assert_eq!(root_node.lookup_namespace_uri(Some("y")), None);
+    assert_eq!(
+        text_node.lookup_namespace_uri(Some("x")),
+        Some("urn:other".to_string())
+    );
+    assert_eq!(c_node.lookup_namespace_uri(None), None);
+    assert_eq!(
+        c_node.lookup_namespace_uri(Some("xml")),
+        Some("http://www.w3.org/XML/1998/namespace".to_string())
+    );
+
+    let attribute_node = as_element(&b_node)
+        .unwrap()
+        .get_attribute_node("y:attr")
+        .unwrap();
+    assert_eq!(
+        attribute_node.lookup_namespace_uri(Some("y")),
+        Some("urn:y".to_string())
+    );
+
+    assert_eq!(b_node.lookup_prefix("urn:x"), Some("x".to_string()));
+    assert_eq!(b_node.lookup_prefix("urn:default"), None);
+    assert_eq!(c_node.lookup_prefix("urn:x"), None);
+    assert_eq!(c_node.lookup_prefix("urn:other"), Some("x".to_string()));
+    assert_eq!(
+        c_node.lookup_prefix("http://www.w3.org/2000/xmlns/"),
+        Some("xmlns".to_string())
+    );
+
+    assert!(root_node.is_default_namespace("urn:default"));
+    assert!(b_node.is_default_namespace("urn:default"));
+    assert!(!c_node.is_default_namespace("urn:default"));
+    assert!(c_node.is_default_namespace(""));
+}
+
+#[test]
+fn test_lookup_namespace_from_element_name() {
+    let document_node = get_implementation()
+        .create_document(Some("urn:root"), Some("r:root"), None)
+        .unwrap();
+    let root_node = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    assert_eq!(
+        root_node.lookup_namespace_uri(Some("r")),
+        Some("urn:root".to_string())
+    );
+    assert_eq!(root_node.lookup_prefix("urn:root"), Some("r".to_string()));
+    assert!(!root_node.is_default_namespace("urn:root"));
+
+    let fragment_node = as_document(&document_node)
+        .unwrap()
+        .create_document_fragment()
+        .unwrap();
+    assert_eq!(fragment_node.lookup_namespace_uri(Some("r")), None);
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_compare_document_position() {
+    use xml_dom::level2::document_position::*;
+
+    let document_node = read_xml(r#"<a x="1" y="2"><b><c/></b><d/></a>"#).unwrap();
+    let root_node = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    let b_node = root_node.first_child().unwrap();
+    let c_node = b_node.first_child().unwrap();
+    let d_node = root_node.last_child().unwrap();
+    let root_element = as_element(&root_node).unwrap();
+    let x_node = root_element.get_attribute_node("x").unwrap();
+    let y_node = root_element.get_attribute_node("y").unwrap();
+
+    assert_eq!(b_node.compare_document_position(&b_node), 0);
+    assert_eq!(b_node.compare_document_position(&d_node), FOLLOWING);
+    assert_eq!(d_node.compare_document_position(&b_node), PRECEDING);
+    assert_eq!(
+        c_node.compare_document_position(&root_node),
+        CONTAINS | PRECEDING
+    );
+    assert_eq!(
+        root_node.compare_document_position(&c_node),
+        CONTAINED_BY | FOLLOWING
+    );
+    assert_eq!(c_node.compare_document_position(&d_node), FOLLOWING);
+
+    assert_eq!(
+        x_node.compare_document_position(&root_node),
+        CONTAINS | PRECEDING
+    );
+    assert_eq!(
+        root_node.compare_document_position(&x_node),
+        CONTAINED_BY | FOLLOWING
+    );
+    assert_eq!(x_node.compare_document_position(&y_node), FOLLOWING);
+    assert_eq!(y_node.compare_document_position(&x_node), PRECEDING);
+    assert_eq!(x_node.compare_document_position(&b_node), FOLLOWING);
+    assert_eq!(c_node.compare_document_position(&x_node), PRECEDING);
+
+    let other_document = read_xml(r#"<a/>"#).unwrap();
+    let position = root_node.compare_document_position(&other_document);
+    assert_eq!(position & DISCONNECTED, DISCONNECTED);
+    assert_eq!(position & IMPLEMENTATION_SPECIFIC, IMPLEMENTATION_SPECIFIC);
+    let reverse = other_document.compare_document_position(&root_node);
+    assert_ne!(
+        position & (PRECEDING | FOLLOWING),
+        reverse & (PRECEDING | FOLLOWING)
+    );
+
+    //
+    // Sorting nodes into document order.
+    //
+    let mut nodes = vec![
+        d_node.clone(),
+        c_node.clone(),
+        root_node.clone(),
+        b_node.clone(),
+    ];
+    nodes.sort_by(|lhs, rhs| {
+        if lhs == rhs {
+            std::cmp::Ordering::Equal
+        } else if lhs.compare_document_position(rhs) & FOLLOWING == FOLLOWING {
+            std::cmp::Ordering::Less
+        } else {
+            std::cmp::Ordering::Greater
+        }
+    });
+    assert_eq!(nodes, vec![root_node, b_node, c_node, d_node]);
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_is_same_node() {
+    let document_node = read_xml(r#"<a><b/></a>"#).unwrap();
+    let root_node = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    let b_node = root_node.first_child().unwrap();
+    assert!(b_node.is_same_node(&root_node.first_child().unwrap()));
+    assert!(!b_node.is_same_node(&b_node.clone_node(true).unwrap()));
+    assert!(!b_node.is_same_node(&root_node));
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_is_equal_node() {
+    let document_node = read_xml(r#"<a x="1" y="2"><b>text</b><!--note--><c z="3"/></a>"#).unwrap();
+    let same_document = read_xml(r#"<a y="2" x="1"><b>text</b><!--note--><c z="3"/></a>"#).unwrap();
+    assert!(document_node.is_equal_node(&same_document));
+    assert!(document_node.is_equal_node(&document_node));
+
+    let root_node = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    let clone_node = root_node.clone_node(true).unwrap();
+    assert!(root_node.is_equal_node(&clone_node));
+    assert!(!root_node.is_same_node(&clone_node));
+
+    let shallow_node = root_node.clone_node(false).unwrap();
+    assert!(!root_node.is_equal_node(&shallow_node));
+
+    for other in &[
+        r#"<a x="1" y="3"><b>text</b><!--note--><c z="3"/></a>"#,
+        r#"<a x="1"><b>text</b><!--note--><c z="3"/></a>"#,
+        r#"<a x="1" y="2"><b>text!</b><!--note--><c z="3"/></a>"#,
+        r#"<a x="1" y="2"><b>text</b><c z="3"/></a>"#,
+        r#"<a x="1" y="2"><b>text</b><!--note--><d z="3"/></a>"#,
+        r#"<a x="1" y="2"><b>text</b><!--note--><c z="3"><d/></c></a>"#,
+    ] {
+        let other_node = read_xml(other).unwrap();
+        assert!(!document_node.is_equal_node(&other_node), "{}", other);
+    }
+
+    //
+    // Normalization can affect equality.
+    //
+    let mut clone_node = clone_node;
+    let b_node = clone_node.first_child().unwrap();
+    let mut text_node = b_node.first_child().unwrap();
+    let _safe_to_ignore = as_text_mut(&mut text_node).unwrap().split(2).unwrap();
+    assert!(!root_node.is_equal_node(&clone_node));
+    clone_node.normalize();
+    assert!(root_node.is_equal_node(&clone_node));
+}
+
+#[test]
+fn test_set_prefix() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+
+    let mut element_node = document
+        .create_element_ns(common::DC_NS, "dc:title")
+        .unwrap();
+    assert!(element_node.set_prefix(Some("purl")).is_ok());
+    assert_eq!(element_node.prefix(), Some("purl".to_string()));
+    assert_eq!(element_node.local_name(), "title".to_string());
+    assert_eq!(
+        element_node.namespace_uri(),
+        Some(common::DC_NS.to_string())
+    );
+    assert_eq!(
+        element_node.to_string(),
+        "<purl:title></purl:title>".to_string()
+    );
+
+    assert!(element_node.set_prefix(None).is_ok());
+    assert_eq!(element_node.prefix(), None);
+    assert_eq!(element_node.to_string(), "<title></title>".to_string());
+
+    {
+        let element = as_element_mut(&mut element_node).unwrap();
+        element.set_attribute("first", "1").unwrap();
+        element
+            .set_attribute_ns(common::DC_NS, "dc:creator", "me")
+            .unwrap();
+        element.set_attribute("last", "3").unwrap();
+    }
+    let mut attribute_node = as_element(&element_node)
+        .unwrap()
+        .get_attribute_node_ns(common::DC_NS, "creator")
+        .unwrap();
+    assert!(attribute_node.set_prefix(Some("purl")).is_ok());
+    assert_eq!(
+        element_node.to_string(),
+        r#"<title first="1" purl:creator="me" last="3"></title>"#.to_string()
+    );
+    let element = as_element(&element_node).unwrap();
+    assert_eq!(
+        element.get_attribute("purl:creator"),
+        Some("me".to_string())
+    );
+    assert_eq!(element.get_attribute("dc:creator"), None);
+}
+
+#[test]
+fn test_set_prefix_errors() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+
+    let mut element_node = document
+        .create_element_ns(common::DC_NS, "dc:title")
+        .unwrap();
+    for (prefix, error) in [
+        ("bad prefix", Error::InvalidCharacter),
+        ("a:b", Error::Namespace),
+        ("", Error::Namespace),
+        ("xml", Error::Namespace),
+        ("xmlns", Error::Namespace),
+    ] {
+        assert_eq!(element_node.set_prefix(Some(prefix)), Err(error));
+        assert_eq!(element_node.prefix(), Some("dc".to_string()));
+    }
+
+    let mut plain_node = document.create_element("title").unwrap();
+    assert_eq!(plain_node.set_prefix(Some("dc")), Err(Error::Namespace));
+    assert!(plain_node.set_prefix(None).is_ok());
+
+    let mut attribute_node = document
+        .create_attribute_ns(common::XML_NS_URI, "xml:lang")
+        .unwrap();
+    assert_eq!(attribute_node.set_prefix(Some("x")), Err(Error::Namespace));
+    assert_eq!(attribute_node.set_prefix(None), Err(Error::Namespace));
+
+    let mut xmlns_node = document
+        .create_attribute_ns(common::XMLNS_NS, "xmlns")
+        .unwrap();
+    assert_eq!(xmlns_node.set_prefix(Some("p")), Err(Error::Namespace));
+
+    let mut text_node = document.create_text_node("text");
+    assert!(text_node.set_prefix(Some("dc")).is_ok());
+    assert_eq!(text_node.prefix(), None);
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_base_uri() {
+    let document_node = read_xml(
+        r#"<doc xml:base="http://example.org/today/">
+  <paragraph>See <link xml:base="new/" href="pic1.gif"/>.</paragraph>
+  <olist xml:base="/hotpicks/">
+    <item xml:base="../archive/"><?pi data?>one</item>
+    <item xml:base="pic3.gif" lang="en"/>
+  </olist>
+</doc>"#,
+    )
+    .unwrap();
+    assert_eq!(document_node.base_uri(), None);
+
+    let root_node = document_node.document_element().unwrap();
+    assert_eq!(
+        root_node.base_uri(),
+        Some("http://example.org/today/".to_string())
+    );
+
+    let elements: Vec<RefNode> = root_node.descendant_elements().collect();
+    let paragraph_node = &elements[0];
+    assert_eq!(
+        paragraph_node.base_uri(),
+        Some("http://example.org/today/".to_string())
+    );
+    assert_eq!(
+        paragraph_node.first_child().unwrap().base_uri(),
+        Some("http://example.org/today/".to_string())
+    );
+
+    let link_node = &elements[1];
+    assert_eq!(
+        link_node.base_uri(),
+        Some("http://example.org/today/new/".to_string())
+    );
+
+    let item_node = &elements[3];
+    assert_eq!(
+        item_node.base_uri(),
+        Some("http://example.org/archive/".to_string())
+    );
+    for child_node in item_node.child_nodes().iter() {
+        assert_eq!(
+            child_node.base_uri(),
+            Some("http://example.org/archive/".to_string())
+        );
+    }
+
+    let item_node = &elements[4];
+    assert_eq!(
+        item_node.base_uri(),
+        Some("http://example.org/hotpicks/pic3.gif".to_string())
+    );
+    let attribute_node = item_node.get_attribute_node("lang").unwrap();
+    assert_eq!(
+        attribute_node.base_uri(),
+        Some("http://example.org/hotpicks/pic3.gif".to_string())
+    );
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_base_uri_relative() {
+    let document_node =
+        read_xml(r#"<a xml:base="docs/"><b xml:base="../images/"><c/></b></a>"#).unwrap();
+    let root_node = document_node.document_element().unwrap();
+    let c_node = root_node.descendant_elements().last().unwrap();
+    assert_eq!(c_node.base_uri(), Some("images/".to_string()));
+
+    let document_node = read_xml(r#"<a><!--none--><b/></a>"#).unwrap();
+    let root_node = document_node.document_element().unwrap();
+    assert_eq!(root_node.base_uri(), None);
+    assert_eq!(root_node.first_child().unwrap().base_uri(), None);
+}
+
+#[cfg(feature = "quick_parser")]
+#[derive(Default)]
+struct RecordingHandler {
+    calls: Mutex<Vec<(UserDataOperation, String, bool, bool)>>,
+}
+
+#[cfg(feature = "quick_parser")]
+impl UserDataHandler for RecordingHandler {
+    type NodeRef = RefNode;
+
+    fn handle(
+        &self,
+        operation: UserDataOperation,
+        key: &str,
+        data: &UserData,
+        source: Option<&RefNode>,
+        destination: Option<&RefNode>,
+    ) {
+        assert_eq!(data.downcast_ref::<u32>(), Some(&42));
+        if let (Some(source), Some(destination)) = (source, destination) {
+            assert_ne!(source, destination);
+            assert!(destination.get_user_data(key).is_none());
+        }
+        self.calls.lock().unwrap().push((
+            operation,
+            key.to_string(),
+            source.is_some(),
+            destination.is_some(),
+        ));
+    }
+}
+
+#[cfg(feature = "quick_parser")]
+impl RecordingHandler {
+    fn take(&self) -> Vec<(UserDataOperation, String, bool, bool)> {
+        self.calls.lock().unwrap().drain(..).collect()
+    }
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_user_data() {
+    let document_node = read_xml("<root><child/></root>").unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    assert!(root_node.get_user_data("model").is_none());
+
+    let previous = root_node.set_user_data("model", Shared::new(String::from("one")), None);
+    assert!(previous.is_none());
+    let previous = root_node.set_user_data("model", Shared::new(2_u32), None);
+    assert_eq!(
+        previous.unwrap().downcast_ref::<String>(),
+        Some(&"one".to_string())
+    );
+    assert_eq!(
+        root_node
+            .get_user_data("model")
+            .unwrap()
+            .downcast_ref::<u32>(),
+        Some(&2)
+    );
+    assert!(root_node.get_user_data("other").is_none());
+
+    //
+    // User data is not copied to clones.
+    //
+    let clone_node = root_node.clone_node(true).unwrap();
+    assert!(clone_node.get_user_data("model").is_none());
+
+    let previous = root_node.unset_user_data("model");
+    assert_eq!(previous.unwrap().downcast_ref::<u32>(), Some(&2));
+    assert!(root_node.get_user_data("model").is_none());
+    assert!(root_node.unset_user_data("model").is_none());
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_user_data_handler() {
+    let handler = Shared::new(RecordingHandler::default());
+    let mut document_node = read_xml("<root><child a=\"1\"/></root>").unwrap();
+    let document = as_document_mut(&mut document_node).unwrap();
+    let root_node = document.document_element().unwrap();
+    let mut child_node = root_node.first_child().unwrap();
+    let mut attribute_node = child_node.get_attribute_node("a").unwrap();
+    let _safe_to_ignore =
+        child_node.set_user_data("key", Shared::new(42_u32), Some(handler.clone()));
+    let _safe_to_ignore =
+        attribute_node.set_user_data("attr", Shared::new(42_u32), Some(handler.clone()));
+
+    let _safe_to_ignore = root_node.clone_node(false).unwrap();
+    assert!(handler.take().is_empty());
+
+    let clone_node = root_node.clone_node(true).unwrap();
+    assert_eq!(
+        handler.take(),
+        vec![
+            (
+                UserDataOperation::NodeCloned,
+                "attr".to_string(),
+                true,
+                true
+            ),
+            (UserDataOperation::NodeCloned, "key".to_string(), true, true),
+        ]
+    );
+
+    //
+    // The clone, and its copy of the attribute, are deleted when dropped; neither has user data.
+    //
+    std::mem::drop(clone_node);
+    assert!(handler.take().is_empty());
+
+    let other_node = get_implementation()
+        .create_document(None, Some("other"), None)
+        .unwrap();
+    let other = as_document(&other_node).unwrap();
+    let _safe_to_ignore = other.import_node(child_node.clone(), false).unwrap();
+    assert_eq!(
+        handler.take(),
+        vec![
+            (
+                UserDataOperation::NodeImported,
+                "attr".to_string(),
+                true,
+                true
+            ),
+            (
+                UserDataOperation::NodeImported,
+                "key".to_string(),
+                true,
+                true
+            ),
+        ]
+    );
+
+    let renamed_node = document
+        .rename_node(child_node.clone(), None, "renamed")
+        .unwrap();
+    assert_eq!(renamed_node, child_node);
+    std::mem::drop(renamed_node);
+    assert_eq!(
+        handler.take(),
+        vec![(
+            UserDataOperation::NodeRenamed,
+            "key".to_string(),
+            true,
+            false
+        )]
+    );
+
+    let adopted_node = other.adopt_node(child_node.clone()).unwrap();
+    std::mem::drop(adopted_node);
+    let mut calls = handler.take();
+    calls.sort_by(|lhs, rhs| lhs.1.cmp(&rhs.1));
+    assert_eq!(
+        calls,
+        vec![
+            (
+                UserDataOperation::NodeAdopted,
+                "attr".to_string(),
+                true,
+                false
+            ),
+            (
+                UserDataOperation::NodeAdopted,
+                "key".to_string(),
+                true,
+                false
+            ),
+        ]
+    );
+
+    //
+    // Dropping the last references deletes the element and its attribute.
+    //
+    std::mem::drop(attribute_node);
+    std::mem::drop(child_node);
+    let mut calls = handler.take();
+    calls.sort_by(|lhs, rhs| lhs.1.cmp(&rhs.1));
+    assert_eq!(
+        calls,
+        vec![
+            (
+                UserDataOperation::NodeDeleted,
+                "attr".to_string(),
+                false,
+                false
+            ),
+            (
+                UserDataOperation::NodeDeleted,
+                "key".to_string(),
+                false,
+                false
+            ),
+        ]
+    );
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+#[cfg(not(feature = "sync"))]
+fn test_mutate_while_borrowed() {
+    let document_node = read_xml("<list><item/><item/></list>").unwrap();
+    let document = as_document(&document_node).unwrap();
+    let mut root = document_node.document_element().unwrap();
+
+    let held = root.clone();
+    let borrowed = held.borrow();
+    for child in root.child_nodes() {
+        let new_child = document.create_element("item").unwrap();
+        assert_eq!(root.append_child(new_child), Err(Error::Borrowed));
+        assert_eq!(root.remove_child(child), Err(Error::Borrowed));
+    }
+    assert_eq!(root.set_attribute("id", "1"), Err(Error::Borrowed));
+    drop(borrowed);
+    assert_eq!(root.child_nodes().len(), 2);
+    assert!(!root.has_attribute("id"));
+
+    let mut child = root.first_child().unwrap();
+    let borrowed = child.borrow_mut();
+    assert_eq!(root.remove_child(child.clone()), Err(Error::Borrowed));
+    assert_eq!(root.append_child(child.clone()), Err(Error::Borrowed));
+    drop(borrowed);
+    assert_eq!(root.first_child(), Some(child.clone()));
+
+    let text = document.create_text_node("text");
+    let borrowed = text.borrow();
+    assert_eq!(child.append_child(text.clone()), Err(Error::Borrowed));
+    drop(borrowed);
+    assert!(child.append_child(text).is_ok());
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+#[cfg(not(feature = "sync"))]
+fn test_display_while_borrowed() {
+    use std::fmt::Write;
+    use xml_dom::io::{Serializer, XmlWriteOptions};
+
+    let document_node = read_xml("<list><item/><item/></list>").unwrap();
+    let child = document_node
+        .document_element()
+        .unwrap()
+        .first_child()
+        .unwrap();
+
+    let borrowed = child.borrow_mut();
+    let mut output = String::new();
+    assert!(write!(output, "{}", document_node).is_err());
+    assert!(document_node
+        .write_to(Vec::new(), &XmlWriteOptions::default())
+        .is_err());
+    drop(borrowed);
+    assert_eq!(
+        document_node.to_string(),
+        "<list><item></item><item></item></list>"
+    );
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_self_manipulation() {
+    let document_node = read_xml("<list><b/><d/></list>").unwrap();
+    let root_node = document_node.document_element().unwrap();
+    let mut b_node = root_node.first_child().unwrap();
+    let mut d_node = root_node.last_child().unwrap();
+
+    let a_node = document_node.create_element("a").unwrap();
+    let inserted = b_node
+        .insert_adjacent(InsertPosition::BeforeSelf, a_node.clone())
+        .unwrap();
+    assert_eq!(inserted, a_node);
+    let _safe_to_ignore = b_node
+        .insert_adjacent(
+            InsertPosition::AfterSelf,
+            document_node.create_element("c").unwrap(),
+        )
+        .unwrap();
+    let _safe_to_ignore = d_node
+        .insert_adjacent(
+            InsertPosition::AfterSelf,
+            document_node.create_element("e").unwrap(),
+        )
+        .unwrap();
+    let _safe_to_ignore = d_node
+        .insert_adjacent(
+            InsertPosition::LastChild,
+            document_node.create_text_node("2"),
+        )
+        .unwrap();
+    let _safe_to_ignore = d_node
+        .insert_adjacent(
+            InsertPosition::FirstChild,
+            document_node.create_text_node("1"),
+        )
+        .unwrap();
+    assert_eq!(
+        document_node.to_string(),
+        "<list><a></a><b></b><c></c><d>12</d><e></e></list>"
+    );
+
+    let x_node = document_node.create_element("x").unwrap();
+    assert_eq!(b_node.replace_with(x_node.clone()).unwrap(), b_node);
+    assert!(b_node.parent_node().is_none());
+    assert_eq!(x_node.parent_node(), Some(root_node.clone()));
+    assert_eq!(d_node.remove_self().unwrap(), d_node);
+    assert!(d_node.parent_node().is_none());
+    assert_eq!(
+        document_node.to_string(),
+        "<list><a></a><x></x><c></c><e></e></list>"
+    );
+
+    assert_eq!(d_node.remove_self(), Ok(d_node.clone()));
+    assert_eq!(
+        d_node.replace_with(document_node.create_element("y").unwrap()),
+        Err(Error::HierarchyRequest)
+    );
+    assert_eq!(
+        d_node.insert_adjacent(InsertPosition::BeforeSelf, b_node.clone()),
+        Err(Error::HierarchyRequest)
+    );
+
+    let other_node = read_xml("<other/>").unwrap();
+    let foreign_node = other_node.create_element("f").unwrap();
+    let mut a_node = root_node.first_child().unwrap();
+    assert_eq!(
+        a_node.insert_adjacent(InsertPosition::AfterSelf, foreign_node.clone()),
+        Err(Error::WrongDocument)
+    );
+    assert_eq!(a_node.replace_with(foreign_node), Err(Error::WrongDocument));
+    assert_eq!(
+        a_node.insert_adjacent(InsertPosition::FirstChild, root_node.clone()),
+        Err(Error::HierarchyRequest)
+    );
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_unwrap_children() {
+    let document_node = read_xml("<p>one<b>two<i>three</i>four</b>five</p>").unwrap();
+    let root_node = document_node.document_element().unwrap();
+    let mut b_node = root_node.child_nodes().to_vec()[1].clone();
+
+    let moved = b_node.unwrap_children().unwrap();
+    assert_eq!(moved.len(), 3);
+    assert!(moved
+        .iter()
+        .all(|node| node.parent_node() == Some(root_node.clone())));
+    assert!(b_node.parent_node().is_none());
+    assert!(!b_node.has_child_nodes());
+    assert_eq!(
+        document_node.to_string(),
+        "<p>onetwo<i>three</i>fourfive</p>"
+    );
+
+    assert_eq!(b_node.unwrap_children(), Err(Error::HierarchyRequest));
+
+    let document_node = read_xml("<root><a/><b/></root>").unwrap();
+    let mut root_node = document_node.document_element().unwrap();
+    assert_eq!(root_node.unwrap_children(), Err(Error::HierarchyRequest));
+    assert_eq!(root_node.parent_node(), Some(document_node.clone()));
+    assert_eq!(document_node.to_string(), "<root><a></a><b></b></root>");
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_wrap_children() {
+    let document_node = read_xml("<p>one<b>two</b>three</p>").unwrap();
+    let mut root_node = document_node.document_element().unwrap();
+
+    let span_node = root_node.wrap_children("span").unwrap();
+    assert_eq!(span_node.parent_node(), Some(root_node.clone()));
+    assert_eq!(
+        document_node.to_string(),
+        "<p><span>one<b>two</b>three</span></p>"
+    );
+
+    let mut b_node = span_node.child_nodes().to_vec()[1].clone();
+    let em_node = b_node.wrap_children_ns("urn:html", "h:em").unwrap();
+    assert_eq!(em_node.namespace_uri(), Some("urn:html".to_string()));
+    assert_eq!(
+        document_node.to_string(),
+        "<p><span>one<b><h:em>two</h:em></b>three</span></p>"
+    );
+
+    let mut empty_node = document_node.create_element("empty").unwrap();
+    let _safe_to_ignore = empty_node.wrap_children("inner").unwrap();
+    assert_eq!(empty_node.to_string(), "<empty><inner></inner></empty>");
+    assert_eq!(
+        root_node.wrap_children("1span"),
+        Err(Error::InvalidCharacter)
+    );
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_wrap_nodes() {
+    let document_node = read_xml("<list><a/><b/><c/><d/></list>").unwrap();
+    let root_node = document_node.document_element().unwrap();
+    let nodes = root_node.child_nodes().to_vec();
+
+    let group_node = wrap_nodes(
+        &document_node,
+        "group",
+        &[nodes[2].clone(), nodes[1].clone()],
+    )
+    .unwrap();
+    assert_eq!(group_node.parent_node(), Some(root_node.clone()));
+    assert_eq!(
+        document_node.to_string(),
+        "<list><a></a><group><c></c><b></b></group><d></d></list>"
+    );
+
+    let group_node =
+        wrap_nodes_ns(&document_node, "urn:g", "g:group", &[nodes[3].clone()]).unwrap();
+    assert_eq!(group_node.namespace_uri(), Some("urn:g".to_string()));
+    assert_eq!(
+        document_node.to_string(),
+        "<list><a></a><group><c></c><b></b></group><g:group><d></d></g:group></list>"
+    );
+
+    let empty_node = wrap_nodes(&document_node, "empty", &[]).unwrap();
+    assert!(empty_node.parent_node().is_none());
+    assert_eq!(
+        wrap_nodes(&root_node, "group", &nodes),
+        Err(Error::InvalidState)
+    );
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_append_children() {
+    let document_node = read_xml("<list><a/><b/></list>").unwrap();
+    let mut root_node = document_node.document_element().unwrap();
+    let mut a_node = root_node.first_child().unwrap();
+    let b_node = root_node.last_child().unwrap();
+
+    let mut fragment_node = document_node.create_document_fragment().unwrap();
+    let _safe_to_ignore = fragment_node
+        .append_child(document_node.create_text_node("f"))
+        .unwrap();
+    a_node
+        .append_children(vec![
+            document_node.create_text_node("1"),
+            b_node.clone(),
+            fragment_node,
+            document_node.create_comment("c").unwrap(),
+        ])
+        .unwrap();
+    assert_eq!(b_node.parent_node(), Some(a_node.clone()));
+    assert_eq!(
+        document_node.to_string(),
+        "<list><a>1<b></b>f<!--c--></a></list>"
+    );
+    assert_eq!(
+        b_node.previous_sibling().unwrap().node_value(),
+        Some("1".to_string())
+    );
+
+    //
+    // The nodes before an error remain appended.
+    //
+    let other_node = read_xml("<other/>").unwrap();
+    assert_eq!(
+        a_node.append_children(vec![
+            document_node.create_text_node("2"),
+            other_node.create_element("x").unwrap(),
+            document_node.create_text_node("3"),
+        ]),
+        Err(Error::WrongDocument)
+    );
+    assert_eq!(
+        a_node.append_children(vec![root_node.clone()]),
+        Err(Error::HierarchyRequest)
+    );
+    assert_eq!(
+        document_node.to_string(),
+        "<list><a>1<b></b>f<!--c-->2</a></list>"
+    );
+
+    let mut document = document_node.clone();
+    assert_eq!(
+        document.append_children(vec![document_node.create_element("second").unwrap()]),
+        Err(Error::HierarchyRequest)
+    );
+    root_node
+        .append_children((0..3).map(|index| document_node.create_text_node(&index.to_string())))
+        .unwrap();
+    assert_eq!(
+        document_node.to_string(),
+        "<list><a>1<b></b>f<!--c-->2</a>012</list>"
+    );
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_retain_children() {
+    let document_node = read_xml("<list><a/>one<b/><!--two--><c/></list>").unwrap();
+    let mut root_node = document_node.document_element().unwrap();
+    let c_node = root_node.last_child().unwrap();
+
+    let removed = root_node.retain_children(is_element).unwrap();
+    assert_eq!(removed.len(), 2);
+    assert!(removed.iter().all(|node| node.parent_node().is_none()));
+    assert_eq!(
+        document_node.to_string(),
+        "<list><a></a><b></b><c></c></list>"
+    );
+    assert_eq!(
+        c_node.previous_sibling().unwrap().node_name().to_string(),
+        "b"
+    );
+    assert!(root_node.retain_children(|_| true).unwrap().is_empty());
+
+    let removed = root_node
+        .retain_children(|node| node.node_name().to_string() != "b")
+        .unwrap();
+    assert_eq!(removed[0].node_name().to_string(), "b");
+    assert_eq!(document_node.to_string(), "<list><a></a><c></c></list>");
+    assert_eq!(c_node.previous_sibling(), root_node.first_child());
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_sort_children_by() {
+    let document_node =
+        read_xml(r#"<list><i n="3"/><i n="1"/><i n="2"/><i n="1"/></list>"#).unwrap();
+    let mut root_node = document_node.document_element().unwrap();
+    let first_node = root_node.first_child().unwrap();
+
+    root_node
+        .sort_children_by(|lhs, rhs| lhs.get_attribute("n").cmp(&rhs.get_attribute("n")))
+        .unwrap();
+    assert_eq!(
+        document_node.to_string(),
+        r#"<list><i n="1"></i><i n="1"></i><i n="2"></i><i n="3"></i></list>"#
+    );
+    assert_eq!(root_node.last_child(), Some(first_node.clone()));
+    assert_eq!(
+        first_node.previous_sibling().unwrap().get_attribute("n"),
+        Some("2".to_string())
+    );
+
+    let mut document_node = read_xml("<!DOCTYPE list><!--comment--><list/>").unwrap();
+    assert_eq!(
+        document_node.sort_children_by(|lhs, rhs| is_element(rhs).cmp(&is_element(lhs))),
+        Err(Error::HierarchyRequest)
+    );
+    document_node
+        .sort_children_by(|lhs, rhs| is_comment(lhs).cmp(&is_comment(rhs)))
+        .unwrap();
+    assert_eq!(
+        document_node.to_string(),
+        "<!DOCTYPE list><list></list><!--comment-->"
+    );
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+#[allow(clippy::mutable_key_type)]
+fn test_node_identity() {
+    use std::collections::HashSet;
+
+    let document_node = read_xml("<a><b>x</b><b>x</b></a>").unwrap();
+    let root_node = document_node.first_child().unwrap();
+    let first = root_node.first_child().unwrap();
+    let second = root_node.last_child().unwrap();
+
+    assert!(first.is_equal_node(&second));
+    assert_ne!(first, second);
+    assert_eq!(first, root_node.first_child().unwrap());
+
+    let mut visited: HashSet<RefNode> = HashSet::new();
+    for node in root_node.descendants().chain(root_node.descendants()) {
+        let _ = visited.insert(node);
+    }
+    assert_eq!(visited.len(), 4);
+    assert!(visited.contains(&first));
+
+    let mut element = first.clone();
+    element.set_attribute("changed", "yes").unwrap();
+    assert!(visited.contains(&first));
+    assert!(!first.is_equal_node(&second));
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_tree_membership() {
+    let document_node = read_xml(r#"<!--first--><a x="1"><b><c/></b></a>"#).unwrap();
+    let comment_node = document_node.first_child().unwrap();
+    let root_node = document_node.last_child().unwrap();
+    let mut b_node = root_node.first_child().unwrap();
+    let c_node = b_node.first_child().unwrap();
+    let attribute_node = root_node.get_attribute_node("x").unwrap();
+
+    assert!(document_node.contains(&c_node));
+    assert!(root_node.contains(&root_node));
+    assert!(b_node.contains(&c_node));
+    assert!(!c_node.contains(&b_node));
+    assert!(!root_node.contains(&comment_node));
+    assert!(!root_node.contains(&attribute_node));
+
+    assert_eq!(c_node.root_node(), document_node);
+    assert_eq!(document_node.root_node(), document_node);
+    assert_eq!(attribute_node.root_node(), attribute_node);
+
+    for node in [
+        &document_node,
+        &comment_node,
+        &root_node,
+        &c_node,
+        &attribute_node,
+    ] {
+        assert!(node.is_attached());
+    }
+
+    let b_node = b_node.remove_self().unwrap();
+    assert_eq!(c_node.root_node(), b_node);
+    assert!(!b_node.is_attached());
+    assert!(!c_node.is_attached());
+    assert!(!document_node.contains(&c_node));
+    assert!(root_node.is_attached());
+
+    let mut root_element = root_node.clone();
+    let attribute_node = root_element.remove_attribute_node(attribute_node).unwrap();
+    assert!(!attribute_node.is_attached());
+
+    let new_node = document_node.create_element("new").unwrap();
+    assert!(!new_node.is_attached());
+    assert_eq!(new_node.root_node(), new_node);
+}
+
+#[cfg(feature = "quick_parser")]
+#[test]
+fn test_node_path() {
+    let document_node = read_xml(
+        r#"<!DOCTYPE root><root xmlns:p="urn:p"><items><item/><item/><item id="3" p:id="4"/></items><p>one<b/><![CDATA[two]]><?pi x?><!--c--></p></root><?pi y?>"#,
+    )
+    .unwrap();
+    let document = as_document(&document_node).unwrap();
+    let root_node = document.document_element().unwrap();
+    let items_node = root_node.first_child().unwrap();
+    let item_node = items_node.last_child().unwrap();
+    let p_node = root_node.last_child().unwrap();
+
+    let expected = vec![
+        (document_node.clone(), "/"),
+        (document_node.first_child().unwrap(), "/node()"),
+        (root_node.clone(), "/root"),
+        (
+            document_node.last_child().unwrap(),
+            "/processing-instruction('pi')",
+        ),
+        (items_node.clone(), "/root/items"),
+        (items_node.first_child().unwrap(), "/root/items/item[1]"),
+        (item_node.clone(), "/root/items/item[3]"),
+        (
+            item_node.get_attribute_node("id").unwrap(),
+            "/root/items/item[3]/@id",
+        ),
+        (
+            item_node.get_attribute_node("p:id").unwrap(),
+            "/root/items/item[3]/@p:id",
+        ),
+        (
+            root_node.get_attribute_node("xmlns:p").unwrap(),
+            "/root/@xmlns:p",
+        ),
+        (p_node.first_child().unwrap(), "/root/p/text()[1]"),
+        (p_node.child_nodes().item(2).unwrap(), "/root/p/text()[2]"),
+        (
+            p_node.child_nodes().item(3).unwrap(),
+            "/root/p/processing-instruction('pi')",
+        ),
+        (p_node.last_child().unwrap(), "/root/p/comment()"),
+    ];
+    for (node, path) in expected {
+        assert_eq!(node.node_path(), path);
+        assert_eq!(document_node.node_at_path(path), Some(node));
+    }
+
+    assert_eq!(
+        document_node.node_at_path("/root/items/item"),
+        items_node.first_child()
+    );
+    for path in [
+        "",
+        "root",
+        "//root",
+        "/root/items/item[4]",
+        "/root/items/item[0]",
+        "/root/items/item[x]",
+        "/root/@xmlns:q",
+        "/root/@xmlns:p/items",
+        "/other",
+    ] {
+        assert_eq!(document_node.node_at_path(path), None, "{}", path);
+    }
+
+    let new_node = document_node.create_element("new").unwrap();
+    let mut child_node = new_node.clone();
+    let _safe_to_ignore = child_node
+        .append_child(document_node.create_element("child").unwrap())
+        .unwrap();
+    let attribute_node = document_node.create_attribute("x").unwrap();
+    assert_eq!(new_node.first_child().unwrap().node_path(), "new/child");
+    assert_eq!(attribute_node.node_path(), "@x");
+    let mut new_element = new_node.clone();
+    let _safe_to_ignore = new_element
+        .set_attribute_node(attribute_node.clone())
+        .unwrap();
+    assert_eq!(attribute_node.node_path(), "new/@x");
+}
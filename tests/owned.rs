@@ -0,0 +1,154 @@
+#![cfg(feature = "quick_parser")]
+
+use quick_xml::Reader;
+use std::str::FromStr;
+use xml_dom::level2::owned::*;
+use xml_dom::level2::*;
+use xml_dom::parser::{read_quick_xml, read_xml};
+
+const CATALOG: &str = r#"<?xml version="1.0" encoding="UTF-8"?><!-- header --><!DOCTYPE catalog PUBLIC "-//Example//DTD Catalog//EN" "catalog.dtd" [<!ENTITY publisher "ACME">]><catalog z="1" a="2" m="3"><item>one &amp; two</item><![CDATA[<raw/>]]><?render fast?></catalog>"#;
+
+#[test]
+fn test_round_trip() {
+    let document_node = read_xml(CATALOG).unwrap();
+    let tree = document_node.to_owned_tree().unwrap();
+    let copy_node = from_owned_tree(&tree).unwrap();
+
+    assert_eq!(copy_node.to_string(), document_node.to_string());
+    assert_eq!(copy_node.to_owned_tree().unwrap(), tree);
+
+    let doc_type = copy_node.doc_type().unwrap();
+    assert_eq!(doc_type.node_name().to_string(), "catalog");
+    assert_eq!(
+        DocumentType::public_id(&doc_type),
+        Some("-//Example//DTD Catalog//EN".to_string())
+    );
+    assert_eq!(
+        DocumentType::system_id(&doc_type),
+        Some("catalog.dtd".to_string())
+    );
+    assert_eq!(
+        doc_type.internal_subset(),
+        Some(r#"<!ENTITY publisher "ACME">"#.to_string())
+    );
+}
+
+#[test]
+fn test_attribute_order() {
+    let document_node = read_xml(CATALOG).unwrap();
+    if let OwnedNode::Document { children, .. } = document_node.to_owned_tree().unwrap() {
+        match children.last() {
+            Some(OwnedNode::Element { attributes, .. }) => assert_eq!(
+                attributes
+                    .iter()
+                    .map(|(name, value)| format!("{}={}", name, value))
+                    .collect::<Vec<String>>(),
+                vec!["z=1", "a=2", "m=3"]
+            ),
+            other => panic!("expected an element, not {:?}", other),
+        }
+    } else {
+        panic!("expected a document");
+    }
+}
+
+#[test]
+fn test_attribute_values() {
+    let document_node = read_xml(r#"<list title="&quot;one&quot; &amp; two"/>"#).unwrap();
+    let tree = document_node.to_owned_tree().unwrap();
+    if let OwnedNode::Document { children, .. } = &tree {
+        match children.last() {
+            Some(OwnedNode::Element { attributes, .. }) => {
+                assert_eq!(attributes[0].1, r#""one" & two"#)
+            }
+            other => panic!("expected an element, not {:?}", other),
+        }
+    } else {
+        panic!("expected a document");
+    }
+    let copy_node = from_owned_tree(&tree).unwrap();
+    assert!(copy_node.is_equal_node(&document_node));
+    assert_eq!(copy_node.to_string(), document_node.to_string());
+}
+
+#[test]
+fn test_namespaces() {
+    let xml = r#"<a:root xmlns:a="urn:a" xmlns="urn:default"><item a:id="1"/></a:root>"#;
+    let document_node = read_quick_xml(Reader::from_str(xml)).unwrap();
+    let copy_node = from_owned_tree(&document_node.to_owned_tree().unwrap()).unwrap();
+
+    let root = copy_node.document_element().unwrap();
+    assert_eq!(root.namespace_uri(), Some("urn:a".to_string()));
+    assert_eq!(root.prefix(), Some("a".to_string()));
+    let item = root.first_child().unwrap();
+    assert_eq!(item.namespace_uri(), Some("urn:default".to_string()));
+    assert_eq!(item.get_attribute_ns("urn:a", "id"), Some("1".to_string()));
+    assert_eq!(copy_node.to_string(), document_node.to_string());
+}
+
+#[test]
+fn test_import_owned_tree() {
+    let tree = OwnedNode::Element {
+        name: Name::from_str("item").unwrap(),
+        attributes: vec![(Name::from_str("id").unwrap(), "7".to_string())],
+        children: vec![OwnedNode::Text("seven".to_string())],
+    };
+    let mut document_node = read_xml("<list/>").unwrap();
+    let item = document_node.import_owned_tree(&tree).unwrap();
+    assert!(item.parent_node().is_none());
+
+    let mut root = document_node.document_element().unwrap();
+    let _safe_to_ignore = root.append_child(item).unwrap();
+    assert_eq!(
+        document_node.to_string(),
+        r#"<list><item id="7">seven</item></list>"#
+    );
+
+    let root_tree = root.to_owned_tree().unwrap();
+    assert_eq!(
+        root_tree,
+        OwnedNode::Element {
+            name: Name::from_str("list").unwrap(),
+            attributes: Vec::new(),
+            children: vec![tree],
+        }
+    );
+    document_node = read_xml("<other/>").unwrap();
+    assert!(document_node.import_owned_tree(&root_tree).is_ok());
+}
+
+#[test]
+fn test_errors() {
+    let document_node = read_xml(r#"<list id="1"/>"#).unwrap();
+    let root = document_node.document_element().unwrap();
+    let tree = document_node.to_owned_tree().unwrap();
+
+    assert_eq!(
+        root.get_attribute_node("id").unwrap().to_owned_tree(),
+        Err(Error::NotSupported)
+    );
+    assert_eq!(
+        document_node.import_owned_tree(&tree),
+        Err(Error::NotSupported)
+    );
+    assert_eq!(root.import_owned_tree(&tree), Err(Error::InvalidState));
+    assert_eq!(
+        from_owned_tree(&OwnedNode::Text("text".to_string())),
+        Err(Error::NotSupported)
+    );
+    assert_eq!(
+        document_node.import_owned_tree(&OwnedNode::Element {
+            name: Name::new_unchecked("1bad"),
+            attributes: Vec::new(),
+            children: Vec::new(),
+        }),
+        Err(Error::InvalidCharacter)
+    );
+}
+
+#[test]
+fn test_send() {
+    fn assert_send<T: Send + 'static>(_: &T) {}
+    let tree = read_xml(CATALOG).unwrap().to_owned_tree().unwrap();
+    assert_send(&tree);
+}
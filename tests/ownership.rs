@@ -0,0 +1,108 @@
+#![cfg(feature = "quick_parser")]
+
+use xml_dom::level2::convert::*;
+use xml_dom::level2::ext::*;
+use xml_dom::level2::mutation::*;
+use xml_dom::level2::*;
+use xml_dom::parser::read_xml;
+
+const XML: &str = r#"<!DOCTYPE list [<!ENTITY one "1">]><list><item id="1">one<b>two</b></item><!-- three --></list>"#;
+
+#[test]
+fn test_drop_document_frees_all_nodes() {
+    let document_node = read_xml(XML).unwrap();
+    let root = document_node.document_element().unwrap();
+    let item = root.first_child().unwrap();
+    let nodes = [
+        document_node.clone().downgrade(),
+        document_node.doc_type().unwrap().downgrade(),
+        root.clone().downgrade(),
+        item.clone().downgrade(),
+        item.get_attribute_node("id").unwrap().downgrade(),
+        item.last_child().unwrap().downgrade(),
+    ];
+    drop(item);
+    drop(root);
+    drop(document_node);
+    assert!(nodes.iter().all(|node| node.clone().upgrade().is_none()));
+}
+
+#[test]
+fn test_held_descendant_outlives_document() {
+    let document_node = read_xml(XML).unwrap();
+    let item = document_node
+        .document_element()
+        .unwrap()
+        .first_child()
+        .unwrap();
+    let root = document_node.document_element().unwrap().downgrade();
+    drop(document_node);
+
+    assert!(root.clone().upgrade().is_none());
+    assert!(item.parent_node().is_none());
+    assert!(item.owner_document().is_none());
+    assert_eq!(item.child_nodes().len(), 2);
+    assert_eq!(item.get_attribute("id"), Some("1".to_string()));
+}
+
+#[test]
+fn test_attribute_does_not_keep_element() {
+    let document_node = read_xml(XML).unwrap();
+    let item = document_node
+        .document_element()
+        .unwrap()
+        .first_child()
+        .unwrap();
+    let attribute = item.get_attribute_node("id").unwrap();
+    let element = item.clone().downgrade();
+    drop(item);
+    drop(document_node);
+
+    assert!(element.clone().upgrade().is_none());
+    assert!(as_attribute(&attribute).unwrap().owner_element().is_none());
+}
+
+#[test]
+fn test_detach_all_breaks_cycles() {
+    let mut document_node = read_xml(XML).unwrap();
+    let mut root = document_node.document_element().unwrap();
+    let item = root.first_child().unwrap().downgrade();
+
+    let listener_node = document_node.clone();
+    let _safe_to_ignore = document_node
+        .add_mutation_listener(Box::new(move |_| drop(listener_node.node_name())))
+        .unwrap();
+    let _safe_to_ignore = root.set_user_data("self", Shared::new(root.clone()), None);
+
+    let document = document_node.clone().downgrade();
+    drop(root);
+    document_node.detach_all().unwrap();
+    assert!(document_node.child_nodes().is_empty());
+    assert!(document_node.doc_type().is_none());
+    drop(document_node);
+
+    assert!(document.clone().upgrade().is_none());
+    assert!(item.clone().upgrade().is_none());
+}
+
+#[test]
+fn test_detach_all_keeps_held_nodes() {
+    let mut document_node = read_xml(XML).unwrap();
+    let item = document_node
+        .document_element()
+        .unwrap()
+        .first_child()
+        .unwrap();
+    document_node.detach_all().unwrap();
+
+    assert!(item.parent_node().is_none());
+    assert!(item.child_nodes().is_empty());
+    assert_eq!(item.get_attribute("id"), Some("1".to_string()));
+}
+
+#[test]
+fn test_detach_all_not_document() {
+    let document_node = read_xml(XML).unwrap();
+    let mut root = document_node.document_element().unwrap();
+    assert_eq!(root.detach_all(), Err(Error::InvalidState));
+}
@@ -1,4 +1,5 @@
 use xml_dom::level2::convert::{as_document, as_processing_instruction_mut};
+use xml_dom::level2::Error;
 
 pub mod common;
 
@@ -54,3 +55,46 @@ fn test_reserved_name() {
         .create_processing_instruction("xml-ok", Some("should-work"))
         .is_ok());
 }
+
+#[test]
+fn test_invalid_target() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+
+    for target in &["xml", "XML", "xMl"] {
+        assert_eq!(
+            document.create_processing_instruction(target, None).err(),
+            Some(Error::InvalidCharacter)
+        );
+    }
+
+    assert!(document.create_processing_instruction("", None).is_err());
+    assert_eq!(
+        document.create_processing_instruction("a b", None).err(),
+        Some(Error::InvalidCharacter)
+    );
+}
+
+#[test]
+fn test_invalid_data() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+
+    assert_eq!(
+        document
+            .create_processing_instruction("test", Some("some ?> data"))
+            .err(),
+        Some(Error::InvalidCharacter)
+    );
+
+    let mut node = document
+        .create_processing_instruction("test", Some("some data"))
+        .unwrap();
+    let processing_instruction = as_processing_instruction_mut(&mut node).unwrap();
+    assert_eq!(
+        processing_instruction.set_data("more ?> data"),
+        Err(Error::InvalidCharacter)
+    );
+    assert_eq!(processing_instruction.data(), Some("some data".to_string()));
+    assert!(processing_instruction.set_data("more ? > data").is_ok());
+}
@@ -0,0 +1,353 @@
+#![cfg(feature = "quick_parser")]
+
+use std::cmp::Ordering;
+use xml_dom::level2::convert::*;
+use xml_dom::level2::ext::TreeIterators;
+use xml_dom::level2::range::*;
+use xml_dom::level2::*;
+use xml_dom::parser::read_xml;
+
+const RANGE_XML: &str = r#"<a><b>one two</b><c><d>three</d>four</c><e/></a>"#;
+
+fn root_of(document_node: &RefNode) -> RefNode {
+    as_document(document_node)
+        .unwrap()
+        .document_element()
+        .unwrap()
+}
+
+fn children_xml(node: &RefNode) -> String {
+    node.child_nodes()
+        .iter()
+        .map(|child_node| child_node.to_string())
+        .collect()
+}
+
+fn text_nodes(document_node: &RefNode) -> Vec<RefNode> {
+    document_node
+        .descendants()
+        .filter(|node| node.node_type() == NodeType::Text)
+        .collect()
+}
+
+#[test]
+fn test_has_feature() {
+    let implementation = get_implementation();
+    assert!(implementation.has_feature("Range", "2.0"));
+    assert!(!implementation.has_feature("Range", "1.0"));
+}
+
+#[test]
+fn test_boundary_points() {
+    let document_node = read_xml(RANGE_XML).unwrap();
+    let root_node = root_of(&document_node);
+    let mut range = document_node.create_range();
+    assert_eq!(range.start_container(), document_node);
+    assert_eq!(range.start_offset(), 0);
+    assert!(range.collapsed());
+
+    assert!(range.set_end(root_node.clone(), 2).is_ok());
+    assert!(!range.collapsed());
+    assert_eq!(range.common_ancestor_container(), document_node);
+
+    assert!(range.set_start(root_node.clone(), 1).is_ok());
+    assert_eq!(range.common_ancestor_container(), root_node);
+
+    //
+    // Setting the start after the end collapses the range to the new start.
+    //
+    let e_node = root_node.last_child().unwrap();
+    assert!(range.set_start_after(e_node).is_ok());
+    assert!(range.collapsed());
+    assert_eq!(range.end_container(), root_node);
+    assert_eq!(range.end_offset(), 3);
+
+    assert_eq!(range.set_end(root_node.clone(), 4), Err(Error::IndexSize));
+    let text_node = text_nodes(&document_node)[0].clone();
+    assert_eq!(range.set_start(text_node.clone(), 8), Err(Error::IndexSize));
+    assert!(range.set_start(text_node, 7).is_ok());
+    assert_eq!(
+        range.set_start_before(document_node.clone()),
+        Err(Error::InvalidNodeType)
+    );
+
+    let other_document = read_xml(RANGE_XML).unwrap();
+    assert_eq!(
+        range.set_start(other_document, 0),
+        Err(Error::WrongDocument)
+    );
+}
+
+#[test]
+fn test_select_and_compare() {
+    let document_node = read_xml(RANGE_XML).unwrap();
+    let root_node = root_of(&document_node);
+    let c_node = root_node.child_nodes().item(1).unwrap();
+
+    let mut range = document_node.create_range();
+    assert!(range.select_node(c_node.clone()).is_ok());
+    assert_eq!(range.start_container(), root_node);
+    assert_eq!(range.start_offset(), 1);
+    assert_eq!(range.end_offset(), 2);
+    assert_eq!(range.to_string(), "threefour");
+
+    let mut inner_range = document_node.create_range();
+    assert!(inner_range.select_node_contents(c_node.clone()).is_ok());
+    assert_eq!(inner_range.start_container(), c_node);
+    assert_eq!(inner_range.end_offset(), 2);
+
+    assert_eq!(
+        range.compare_boundary_points(CompareHow::StartToStart, &inner_range),
+        Ok(Ordering::Less)
+    );
+    assert_eq!(
+        range.compare_boundary_points(CompareHow::EndToEnd, &inner_range),
+        Ok(Ordering::Greater)
+    );
+    assert_eq!(
+        inner_range.compare_boundary_points(CompareHow::EndToStart, &range),
+        Ok(Ordering::Less)
+    );
+
+    let copy = range.clone_range().unwrap();
+    assert!(range.collapse(true).is_ok());
+    assert!(range.collapsed());
+    assert_eq!(copy.end_offset(), 2);
+}
+
+#[test]
+fn test_to_string_split_text() {
+    let document_node = read_xml(RANGE_XML).unwrap();
+    let texts = text_nodes(&document_node);
+    let mut range = document_node.create_range();
+    assert!(range.set_start(texts[0].clone(), 4).is_ok());
+    assert!(range.set_end(texts[0].clone(), 7).is_ok());
+    assert_eq!(range.to_string(), "two");
+
+    assert!(range.set_end(texts[2].clone(), 2).is_ok());
+    assert_eq!(range.to_string(), "twothreefo");
+}
+
+#[test]
+fn test_extract_within_text() {
+    let document_node = read_xml(RANGE_XML).unwrap();
+    let texts = text_nodes(&document_node);
+    let mut range = document_node.create_range();
+    assert!(range.set_start(texts[0].clone(), 1).is_ok());
+    assert!(range.set_end(texts[0].clone(), 5).is_ok());
+
+    let fragment = range.extract_contents().unwrap();
+    assert_eq!(fragment.node_type(), NodeType::DocumentFragment);
+    assert_eq!(children_xml(&fragment), "ne t");
+    assert_eq!(texts[0].node_value(), Some("owo".to_string()));
+    assert!(range.collapsed());
+    assert_eq!(range.start_container(), texts[0]);
+    assert_eq!(range.start_offset(), 1);
+}
+
+#[test]
+fn test_extract_across_levels() {
+    let document_node = read_xml(RANGE_XML).unwrap();
+    let root_node = root_of(&document_node);
+    let texts = text_nodes(&document_node);
+    let mut range = document_node.create_range();
+    assert!(range.set_start(texts[0].clone(), 4).is_ok());
+    assert!(range.set_end(texts[1].clone(), 2).is_ok());
+
+    let fragment = range.extract_contents().unwrap();
+    assert_eq!(children_xml(&fragment), "<b>two</b><c><d>th</d></c>");
+    assert_eq!(
+        root_node.to_string(),
+        "<a><b>one </b><c><d>ree</d>four</c><e></e></a>"
+    );
+    assert!(range.collapsed());
+    assert_eq!(range.start_container(), root_node);
+    assert_eq!(range.start_offset(), 1);
+
+    //
+    // Whole elements between the boundary-points are moved into the fragment.
+    //
+    let document_node = read_xml(RANGE_XML).unwrap();
+    let root_node = root_of(&document_node);
+    let texts = text_nodes(&document_node);
+    let mut range = document_node.create_range();
+    assert!(range.set_start(texts[0].clone(), 3).is_ok());
+    assert!(range.set_end(root_node.clone(), 3).is_ok());
+    let fragment = range.extract_contents().unwrap();
+    assert_eq!(
+        children_xml(&fragment),
+        "<b> two</b><c><d>three</d>four</c><e></e>"
+    );
+    assert_eq!(root_node.to_string(), "<a><b>one</b></a>");
+}
+
+#[test]
+fn test_clone_contents() {
+    let document_node = read_xml(RANGE_XML).unwrap();
+    let texts = text_nodes(&document_node);
+    let before = document_node.to_string();
+    let mut range = document_node.create_range();
+    assert!(range.set_start(texts[0].clone(), 4).is_ok());
+    assert!(range.set_end(texts[2].clone(), 2).is_ok());
+
+    let fragment = range.clone_contents().unwrap();
+    assert_eq!(children_xml(&fragment), "<b>two</b><c><d>three</d>fo</c>");
+    assert_eq!(document_node.to_string(), before);
+    assert!(!range.collapsed());
+}
+
+#[test]
+fn test_delete_contents() {
+    let document_node = read_xml(RANGE_XML).unwrap();
+    let root_node = root_of(&document_node);
+    let texts = text_nodes(&document_node);
+    let mut range = document_node.create_range();
+    assert!(range.set_start(texts[0].clone(), 3).is_ok());
+    assert!(range.set_end(texts[2].clone(), 1).is_ok());
+
+    assert!(range.delete_contents().is_ok());
+    assert_eq!(root_node.to_string(), "<a><b>one</b><c>our</c><e></e></a>");
+    assert!(range.collapsed());
+    assert_eq!(range.start_container(), root_node);
+    assert_eq!(range.start_offset(), 1);
+}
+
+#[test]
+fn test_insert_node() {
+    let document_node = read_xml(RANGE_XML).unwrap();
+    let root_node = root_of(&document_node);
+    let texts = text_nodes(&document_node);
+    let document = as_document(&document_node).unwrap();
+
+    //
+    // Inserting into a text node splits it, and a collapsed range then selects the new node.
+    //
+    let mut range = document_node.create_range();
+    assert!(range.set_start(texts[0].clone(), 3).is_ok());
+    assert!(range.collapse(true).is_ok());
+    let new_node = document.create_element("new").unwrap();
+    assert!(range.insert_node(new_node.clone()).is_ok());
+    assert_eq!(
+        root_node.first_child().unwrap().to_string(),
+        "<b>one<new></new> two</b>"
+    );
+    assert_eq!(range.start_container(), texts[0]);
+    assert_eq!(range.start_offset(), 3);
+    assert_eq!(range.end_container(), root_node.first_child().unwrap());
+    assert_eq!(range.end_offset(), 2);
+
+    //
+    // Inserting at an element boundary moves the end of a range over the new node.
+    //
+    let mut range = document_node.create_range();
+    assert!(range.select_node_contents(root_node.clone()).is_ok());
+    let other_node = document.create_element("other").unwrap();
+    assert!(range.insert_node(other_node.clone()).is_ok());
+    assert_eq!(root_node.first_child().unwrap(), other_node);
+    assert_eq!(range.start_offset(), 0);
+    assert_eq!(range.end_offset(), 4);
+
+    let comment_node = document.create_comment("note").unwrap();
+    let mut range = document_node.create_range();
+    assert!(range.select_node_contents(comment_node).is_ok());
+    assert_eq!(
+        range.insert_node(document.create_element("bad").unwrap()),
+        Err(Error::HierarchyRequest)
+    );
+    assert_eq!(
+        range.insert_node(root_node.clone()),
+        Err(Error::HierarchyRequest)
+    );
+}
+
+#[test]
+fn test_insert_node_failure_changes_nothing() {
+    let document_node = read_xml(RANGE_XML).unwrap();
+    let texts = text_nodes(&document_node);
+    let other_document_node = read_xml("<o><p/></o>").unwrap();
+    let other_root_node = root_of(&other_document_node);
+    let foreign_node = other_root_node.first_child().unwrap();
+
+    //
+    // A node from another document is rejected before the text node is split, and is left in
+    // its own tree.
+    //
+    let mut range = document_node.create_range();
+    assert!(range.set_start(texts[0].clone(), 3).is_ok());
+    assert!(range.collapse(true).is_ok());
+    assert_eq!(
+        range.insert_node(foreign_node.clone()),
+        Err(Error::WrongDocument)
+    );
+    assert_eq!(other_root_node.to_string(), "<o><p></p></o>");
+    assert_eq!(foreign_node.parent_node(), Some(other_root_node));
+    assert_eq!(
+        root_of(&document_node).first_child().unwrap().to_string(),
+        "<b>one two</b>"
+    );
+    assert_eq!(range.end_container(), texts[0]);
+    assert_eq!(range.end_offset(), 3);
+}
+
+#[test]
+fn test_surround_contents() {
+    let document_node = read_xml(RANGE_XML).unwrap();
+    let root_node = root_of(&document_node);
+    let texts = text_nodes(&document_node);
+    let document = as_document(&document_node).unwrap();
+
+    let mut range = document_node.create_range();
+    assert!(range.set_start(texts[0].clone(), 4).is_ok());
+    assert!(range.set_end(texts[0].clone(), 7).is_ok());
+    let new_parent = document.create_element("em").unwrap();
+    assert!(range.surround_contents(new_parent.clone()).is_ok());
+    assert_eq!(
+        root_node.first_child().unwrap().to_string(),
+        "<b>one <em>two</em></b>"
+    );
+
+    let mut range = document_node.create_range();
+    assert!(range.set_start(texts[1].clone(), 1).is_ok());
+    assert!(range.set_end(texts[2].clone(), 1).is_ok());
+    assert_eq!(
+        range.surround_contents(document.create_element("em").unwrap()),
+        Err(Error::InvalidState)
+    );
+}
+
+#[test]
+fn test_range_tracks_removal() {
+    let document_node = read_xml(RANGE_XML).unwrap();
+    let mut root_node = root_of(&document_node);
+    let texts = text_nodes(&document_node);
+    let mut range = document_node.create_range();
+    assert!(range.set_start(texts[1].clone(), 2).is_ok());
+    assert!(range.set_end(root_node.clone(), 3).is_ok());
+
+    //
+    // Removing the start container's ancestor moves the start to where that ancestor was.
+    //
+    let c_node = root_node.child_nodes().item(1).unwrap();
+    let _safe_to_ignore = root_node.remove_child(c_node).unwrap();
+    assert_eq!(range.start_container(), root_node);
+    assert_eq!(range.start_offset(), 1);
+    assert_eq!(range.end_offset(), 2);
+
+    let b_node = root_node.first_child().unwrap();
+    let _safe_to_ignore = root_node.remove_child(b_node).unwrap();
+    assert_eq!(range.start_offset(), 0);
+    assert_eq!(range.end_offset(), 1);
+    assert_eq!(range.to_string(), "");
+}
+
+#[test]
+fn test_detach() {
+    let document_node = read_xml(RANGE_XML).unwrap();
+    let root_node = root_of(&document_node);
+    let mut range = document_node.create_range();
+    range.detach();
+    assert_eq!(range.set_start(root_node, 0), Err(Error::InvalidState));
+    assert_eq!(range.extract_contents().err(), Some(Error::InvalidState));
+    assert_eq!(range.clone_range().err(), Some(Error::InvalidState));
+    assert_eq!(range.to_string(), "");
+}
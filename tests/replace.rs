@@ -0,0 +1,80 @@
+#![cfg(feature = "quick_parser")]
+
+#[cfg(feature = "regex")]
+use regex::Regex;
+use xml_dom::level2::ext::*;
+use xml_dom::parser::read_xml;
+
+#[test]
+fn test_replace_text() {
+    let mut document_node =
+        read_xml(r#"<a x="cat"><b>cat cat</b><!--cat--><![CDATA[cat]]><?cat cat?>c<!---->at</a>"#)
+            .unwrap();
+    assert_eq!(document_node.replace_text("cat", "dog"), 3);
+    assert_eq!(
+        document_node.to_string(),
+        r#"<a x="cat"><b>dog dog</b><!--cat--><![CDATA[dog]]><?cat cat?>c<!---->at</a>"#
+    );
+    assert_eq!(document_node.replace_text("cat", "dog"), 0);
+    assert_eq!(document_node.replace_text("", "dog"), 0);
+}
+
+#[test]
+fn test_replace_text_with() {
+    let xml = r#"<a xmlns:p="urn:p" p:x="urn:p" y="[urn:p]"><b>urn:p</b><![CDATA[urn:p]]></a>"#;
+
+    let mut document_node = read_xml(xml).unwrap();
+    let mut config = ReplaceConfig::new();
+    config.set_include_attributes();
+    config.set_exclude_cdata();
+    assert_eq!(
+        document_node.replace_text_with("urn:p", "urn:q", &config),
+        3
+    );
+    assert_eq!(
+        document_node.to_string(),
+        r#"<a xmlns:p="urn:p" p:x="urn:q" y="[urn:q]"><b>urn:q</b><![CDATA[urn:p]]></a>"#
+    );
+
+    let mut document_node = read_xml(xml).unwrap();
+    let mut config = ReplaceConfig::new();
+    config.set_remove_empty_text();
+    assert_eq!(document_node.replace_text_with("urn:p", "", &config), 2);
+    assert_eq!(
+        document_node.to_string(),
+        r#"<a xmlns:p="urn:p" p:x="urn:p" y="[urn:p]"><b></b></a>"#
+    );
+}
+
+#[test]
+fn test_replace_illegal_text() {
+    let mut document_node = read_xml(r#"<a><b>]]</b><![CDATA[]]]></a>"#).unwrap();
+    assert_eq!(document_node.replace_text("]]", "]]>"), 1);
+    assert_eq!(
+        document_node.to_string(),
+        r#"<a><b>]]&#62;</b><![CDATA[]]]></a>"#
+    );
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn test_replace_regex() {
+    let mut document_node = read_xml(r#"<a title="${title}">Hello ${first} ${last}!</a>"#).unwrap();
+    let placeholder = Regex::new(r"\$\{(\w+)\}").unwrap();
+    assert_eq!(document_node.replace_regex(&placeholder, "[$1]"), 2);
+    assert_eq!(
+        document_node.to_string(),
+        r#"<a title="${title}">Hello [first] [last]!</a>"#
+    );
+
+    let mut config = ReplaceConfig::new();
+    config.set_include_attributes();
+    assert_eq!(
+        document_node.replace_regex_with(&placeholder, "Greeting", &config),
+        1
+    );
+    assert_eq!(
+        document_node.to_string(),
+        r#"<a title="Greeting">Hello [first] [last]!</a>"#
+    );
+}
@@ -0,0 +1,165 @@
+#![cfg(feature = "quick_parser")]
+
+use xml_dom::level2::convert::as_attribute_mut;
+use xml_dom::level2::selector::*;
+use xml_dom::level2::*;
+use xml_dom::parser::read_xml;
+
+const PAGE: &str = r#"<page>
+  <nav><link href="/home" rel="home"/><link href="https://example.org/about" rel="external"/></nav>
+  <section xml:id="main" id="s1">
+    <p class="intro">Hello</p>
+    <div><p class="note">Inner</p><x:p xmlns:x="urn:x">Prefixed</x:p></div>
+  </section>
+</page>"#;
+
+fn names(nodes: Vec<RefNode>) -> Vec<String> {
+    nodes
+        .iter()
+        .map(|node| match node.get_attribute("class") {
+            Some(class) => format!("{}.{}", node.node_name(), class),
+            None => node.node_name().to_string(),
+        })
+        .collect()
+}
+
+#[test]
+fn test_type_and_combinator_selectors() {
+    let document_node = read_xml(PAGE).unwrap();
+
+    assert_eq!(
+        names(document_node.query_selector_all("p").unwrap()),
+        vec!["p.intro", "p.note"]
+    );
+    assert_eq!(
+        names(document_node.query_selector_all("section > p").unwrap()),
+        vec!["p.intro"]
+    );
+    assert_eq!(
+        names(document_node.query_selector_all("section p").unwrap()),
+        vec!["p.intro", "p.note"]
+    );
+    assert_eq!(
+        names(
+            document_node
+                .query_selector_all("page section div > *")
+                .unwrap()
+        ),
+        vec!["p.note", "x:p"]
+    );
+    assert_eq!(
+        names(document_node.query_selector_all("x|p").unwrap()),
+        vec!["x:p"]
+    );
+    assert_eq!(document_node.query_selector_all("*").unwrap().len(), 9);
+
+    //
+    // Selector lists are merged into document order, without duplicates.
+    //
+    assert_eq!(
+        names(document_node.query_selector_all("div *, p, nav").unwrap()),
+        vec!["nav", "p.intro", "p.note", "x:p"]
+    );
+
+    //
+    // Only descendants are matched, but their ancestors may be outside the context element.
+    //
+    let div = document_node.query_selector("div").unwrap().unwrap();
+    assert_eq!(
+        names(div.query_selector_all("section p").unwrap()),
+        vec!["p.note"]
+    );
+    assert!(div.query_selector("div").unwrap().is_none());
+}
+
+#[test]
+fn test_attribute_selectors() {
+    let document_node = read_xml(PAGE).unwrap();
+    let hrefs = |selectors: &str| -> Vec<String> {
+        document_node
+            .query_selector_all(selectors)
+            .unwrap()
+            .iter()
+            .filter_map(|node| node.get_attribute("href"))
+            .collect()
+    };
+
+    assert_eq!(hrefs("[href]").len(), 2);
+    assert_eq!(hrefs("link[rel=home]"), vec!["/home"]);
+    assert_eq!(
+        hrefs("link[rel = 'external']"),
+        vec!["https://example.org/about"]
+    );
+    assert_eq!(
+        hrefs(r#"[href^="https:"]"#),
+        vec!["https://example.org/about"]
+    );
+    assert_eq!(hrefs("[href$=home]"), vec!["/home"]);
+    assert_eq!(
+        hrefs("[href*=example][rel]"),
+        vec!["https://example.org/about"]
+    );
+    assert!(hrefs("[href^='']").is_empty());
+    assert!(hrefs("[href=Home]").is_empty());
+    assert_eq!(
+        names(
+            document_node
+                .query_selector_all("[xml|id=main] > p")
+                .unwrap()
+        ),
+        vec!["p.intro"]
+    );
+}
+
+#[test]
+fn test_id_selector() {
+    let document_node = read_xml(PAGE).unwrap();
+
+    assert_eq!(
+        names(document_node.query_selector_all("#main p").unwrap()),
+        vec!["p.intro", "p.note"]
+    );
+    //
+    // An attribute named `id` is not an ID unless it is declared, or marked, as one.
+    //
+    assert!(document_node.query_selector("#s1").unwrap().is_none());
+    let mut note = document_node
+        .query_selector("p[class=note]")
+        .unwrap()
+        .unwrap();
+    note.set_attribute("id", "n1").unwrap();
+    let mut id_node = note.get_attribute_node("id").unwrap();
+    as_attribute_mut(&mut id_node)
+        .unwrap()
+        .set_is_id(true)
+        .unwrap();
+    assert_eq!(document_node.query_selector("#n1").unwrap(), Some(note));
+}
+
+#[test]
+fn test_invalid_selectors() {
+    let document_node = read_xml(PAGE).unwrap();
+
+    for selectors in [
+        "",
+        "p,",
+        "p >",
+        "p:first-child",
+        ".intro",
+        "nav + section",
+        "[href",
+    ] {
+        assert_eq!(
+            document_node.query_selector(selectors),
+            Err(Error::Syntax),
+            "{}",
+            selectors
+        );
+        assert_eq!(
+            document_node.query_selector_all(selectors),
+            Err(Error::Syntax),
+            "{}",
+            selectors
+        );
+    }
+}
@@ -0,0 +1,176 @@
+#![cfg(all(feature = "serde", feature = "quick_parser"))]
+
+use xml_dom::level2::ext::*;
+use xml_dom::level2::*;
+use xml_dom::parser::read_xml;
+
+const CATALOG: &str = r#"<?xml version="1.0" encoding="UTF-8"?><!-- header --><!DOCTYPE catalog PUBLIC "-//Example//DTD Catalog//EN" "catalog.dtd" [<!ENTITY publisher "ACME">]><catalog z="1" a="2" m="3"><item>one &amp; two</item><![CDATA[<raw/>]]><?render fast?><p>mixed <b>bold</b> text</p></catalog>"#;
+
+fn round_trip(document_node: &RefNode) -> RefNode {
+    let json = serde_json::to_string(document_node).unwrap();
+    serde_json::from_str(&json).unwrap()
+}
+
+//
+// Check that every node below `node` has the right parent, and `document_node` as its owner.
+//
+fn assert_links(node: &RefNode, document_node: &RefNode) {
+    for child_node in node.child_nodes() {
+        assert_eq!(child_node.parent_node().as_ref(), Some(node));
+        if child_node.node_type() != NodeType::DocumentType {
+            assert_eq!(child_node.owner_document().as_ref(), Some(document_node));
+        }
+        for (_, attribute_node) in child_node.attributes() {
+            assert_eq!(attribute_node.owner_element().as_ref(), Some(&child_node));
+            assert_eq!(
+                attribute_node.owner_document().as_ref(),
+                Some(document_node)
+            );
+        }
+        assert_links(&child_node, document_node);
+    }
+}
+
+#[test]
+fn test_round_trip() {
+    let document_node = read_xml(CATALOG).unwrap();
+    let copy_node = round_trip(&document_node);
+
+    assert!(copy_node.is_equal_node(&document_node));
+    assert_eq!(copy_node.to_string(), document_node.to_string());
+    assert_eq!(copy_node.xml_declaration(), document_node.xml_declaration());
+    assert_links(&copy_node, &copy_node);
+    assert!(copy_node
+        .to_string()
+        .contains(r#"<catalog z="1" a="2" m="3">"#));
+}
+
+#[test]
+fn test_round_trip_namespaces() {
+    let document_node = get_implementation()
+        .create_document(Some("urn:a"), Some("a:root"), None)
+        .unwrap();
+    let mut root_node = document_node.document_element().unwrap();
+    let mut item_node = document_node.create_element_ns("urn:a", "a:item").unwrap();
+    item_node.set_attribute_ns("urn:a", "a:id", "1").unwrap();
+    item_node.set_attribute("id", "2").unwrap();
+    let _safe_to_ignore = root_node.append_child(item_node).unwrap();
+    let plain_node = document_node.create_element("plain").unwrap();
+    let _safe_to_ignore = root_node.append_child(plain_node).unwrap();
+    let copy_node = round_trip(&document_node);
+
+    assert!(copy_node.is_equal_node(&document_node));
+    let item_node = copy_node.document_element().unwrap().first_child().unwrap();
+    assert_eq!(item_node.namespace_uri(), Some("urn:a".to_string()));
+    let id_node = item_node.get_attribute_node_ns("urn:a", "id").unwrap();
+    assert_eq!(id_node.value(), Some("1".to_string()));
+}
+
+#[test]
+fn test_serialized_form() {
+    let document_node = read_xml(r#"<list><item id="1">one</item></list>"#).unwrap();
+    let value = serde_json::to_value(&document_node).unwrap();
+    assert_eq!(
+        value,
+        serde_json::json!({
+            "Document": {
+                "declaration": null,
+                "children": [{
+                    "Element": {
+                        "name": { "name": "list" },
+                        "attributes": [],
+                        "children": [{
+                            "Element": {
+                                "name": { "name": "item" },
+                                "attributes": [[{ "name": "id" }, "1"]],
+                                "children": [{ "Text": "one" }]
+                            }
+                        }]
+                    }
+                }]
+            }
+        })
+    );
+}
+
+#[test]
+fn test_errors() {
+    // only a document can be deserialized, as any other node needs a document to own it.
+    assert!(serde_json::from_str::<RefNode>(r#"{"Text":"one"}"#).is_err());
+    // names are checked as they are deserialized.
+    assert!(serde_json::from_str::<RefNode>(
+        r#"{"Document":{"declaration":null,"children":[{"Element":{"name":{"name":"1item"},"attributes":[],"children":[]}}]}}"#
+    )
+    .is_err());
+
+    let document_node = read_xml(r#"<list id="1"/>"#).unwrap();
+    let root_node = document_node.document_element().unwrap();
+    let attribute_node = root_node.get_attribute_node("id").unwrap();
+    assert!(serde_json::to_string(&attribute_node).is_err());
+}
+
+//
+// A small, deterministic, pseudo-random source so that the generated trees are repeatable.
+//
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self, bound: usize) -> usize {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((self.0 >> 33) as usize) % bound
+    }
+}
+
+fn add_children(document: &RefNode, parent_node: &mut RefNode, random: &mut Random, depth: usize) {
+    for index in 0..random.next(5) {
+        let child_node = match random.next(if depth > 3 { 4 } else { 6 }) {
+            0 => document.create_text_node(&format!("text {} & <{}>", index, depth)),
+            1 => document
+                .create_comment(&format!(" comment {} ", index))
+                .unwrap(),
+            2 => document
+                .create_cdata_section(&format!("<cdata {}/>", index))
+                .unwrap(),
+            3 => document
+                .create_processing_instruction(&format!("pi{}", index), Some("data"))
+                .unwrap(),
+            4 => document
+                .create_element_ns("urn:generated", &format!("g:element{}", index))
+                .unwrap(),
+            _ => document
+                .create_element(&format!("element{}", index))
+                .unwrap(),
+        };
+        let mut child_node = parent_node.append_child(child_node).unwrap();
+        if child_node.node_type() == NodeType::Element {
+            for attribute in 0..random.next(4) {
+                child_node
+                    .set_attribute(
+                        &format!("attribute{}", (attribute * 7 + index) % 5),
+                        &format!("\"{}\" & {}", attribute, depth),
+                    )
+                    .unwrap();
+            }
+            add_children(document, &mut child_node, random, depth + 1);
+        }
+    }
+}
+
+#[test]
+fn test_round_trip_generated() {
+    let mut random = Random(63);
+    for _ in 0..200 {
+        let document_node = get_implementation()
+            .create_document(None, Some("root"), None)
+            .unwrap();
+        let mut root_node = document_node.document_element().unwrap();
+        add_children(&document_node, &mut root_node, &mut random, 0);
+        let copy_node = round_trip(&document_node);
+        assert!(copy_node.is_equal_node(&document_node), "{}", document_node);
+        assert_eq!(copy_node.to_string(), document_node.to_string());
+        assert_links(&copy_node, &copy_node);
+    }
+}
@@ -0,0 +1,66 @@
+#![cfg(feature = "sync")]
+
+use std::thread;
+use xml_dom::level2::convert::*;
+use xml_dom::level2::*;
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+fn create_large_document(count: usize) -> RefNode {
+    let mut document_node = get_implementation()
+        .create_document(None, Some("catalog"), None)
+        .unwrap();
+    let document = as_document_mut(&mut document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    for index in 0..count {
+        let mut item_node = document.create_element("item").unwrap();
+        item_node.set_attribute("id", &index.to_string()).unwrap();
+        let _safe_to_ignore = item_node
+            .append_child(document.create_text_node(&format!("item {}", index)))
+            .unwrap();
+        let _safe_to_ignore = root_node.append_child(item_node).unwrap();
+    }
+    document_node
+}
+
+#[test]
+fn test_node_is_send_and_sync() {
+    assert_send_sync::<RefNode>();
+    assert_send_sync::<UserData>();
+    assert_send_sync::<traversal::NodeFilterRef>();
+    assert_send_sync::<mutation::MutationListener>();
+}
+
+#[test]
+fn test_serialize_on_worker_thread() {
+    let count = 1_000;
+    let document_node = create_large_document(count);
+    let root_node = document_node.document_element().unwrap();
+    let expected = document_node.to_string();
+
+    let worker = thread::spawn(move || document_node.to_string());
+
+    assert_eq!(root_node.node_name().to_string(), "catalog");
+    assert_eq!(root_node.child_nodes().len(), count);
+
+    let serialized = worker.join().unwrap();
+    assert_eq!(serialized, expected);
+    assert!(serialized.ends_with(r#"<item id="999">item 999</item></catalog>"#));
+}
+
+#[test]
+fn test_shared_user_data() {
+    let document_node = create_large_document(1);
+    let mut root_node = document_node.document_element().unwrap();
+    let _safe_to_ignore = root_node.set_user_data("count", Shared::new(1_u32), None);
+
+    let worker_node = root_node.clone();
+    let value = thread::spawn(move || {
+        worker_node
+            .get_user_data("count")
+            .and_then(|data| data.downcast_ref::<u32>().copied())
+    })
+    .join()
+    .unwrap();
+    assert_eq!(value, Some(1));
+}
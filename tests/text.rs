@@ -1,8 +1,13 @@
+use xml_dom::io::Quote;
 use xml_dom::level2::convert::{
-    as_cdata_section, as_cdata_section_mut, as_document, as_document_mut, as_element,
-    as_element_mut, as_text, as_text_mut,
+    as_cdata_section, as_cdata_section_mut, as_comment_mut, as_document, as_document_mut,
+    as_element, as_element_mut, as_text, as_text_mut,
 };
-use xml_dom::level2::Error;
+use xml_dom::level2::ext::{StripWhitespace, WholeText};
+use xml_dom::level2::{
+    get_implementation, Attribute, CharacterData, Document, Element, Error, Name, Node, RefNode,
+};
+use xml_dom::text::*;
 
 pub mod common;
 
@@ -11,7 +16,7 @@ fn test_text_node_contents() {
     //
     // Note, this test character escaping, "&" should be "&#38;" in the tree.
     //
-    let text_values = vec![
+    let text_values = [
         "Rose Bush",
         "A Guide to Growing Roses",
         "Describes process for planting &#38; nurturing different kinds of rose bushes.",
@@ -51,7 +56,7 @@ fn test_text_substring() {
     let children = root_element.child_nodes();
     assert_eq!(children.len(), 1);
 
-    let mut text_node = children.get(0).unwrap().clone();
+    let mut text_node = children.first().unwrap().clone();
     let text = as_text_mut(&mut text_node).unwrap();
     assert_eq!(text.data(), Some("Hello cruel world!".to_string()));
     assert_eq!(text.substring_data(0, 0), Ok("".to_string()));
@@ -75,11 +80,11 @@ fn test_text_insert() {
     let children = root_element.child_nodes();
     assert_eq!(children.len(), 1);
 
-    let mut text_node = children.get(0).unwrap().clone();
+    let mut text_node = children.first().unwrap().clone();
     let text = as_text_mut(&mut text_node).unwrap();
     assert_eq!(text.data(), Some("Hello cruel world!".to_string()));
 
-    let mut text_node = children.get(0).unwrap().clone();
+    let mut text_node = children.first().unwrap().clone();
     let text = as_text_mut(&mut text_node).unwrap();
     let result = text.insert_data(6, "my ");
     assert!(result.is_ok());
@@ -109,11 +114,11 @@ fn test_text_replace() {
     let children = root_element.child_nodes();
     assert_eq!(children.len(), 1);
 
-    let mut text_node = children.get(0).unwrap().clone();
+    let mut text_node = children.first().unwrap().clone();
     let text = as_text_mut(&mut text_node).unwrap();
     assert_eq!(text.data(), Some("Hello cruel world!".to_string()));
 
-    let mut text_node = children.get(0).unwrap().clone();
+    let mut text_node = children.first().unwrap().clone();
     let text = as_text_mut(&mut text_node).unwrap();
     let result = text.replace_data(6, 6, "my happy ");
     assert!(result.is_ok());
@@ -146,11 +151,11 @@ fn test_text_delete() {
     let children = root_element.child_nodes();
     assert_eq!(children.len(), 1);
 
-    let mut text_node = children.get(0).unwrap().clone();
+    let mut text_node = children.first().unwrap().clone();
     let text = as_text_mut(&mut text_node).unwrap();
     assert_eq!(text.data(), Some("Hello cruel world!".to_string()));
 
-    let mut text_node = children.get(0).unwrap().clone();
+    let mut text_node = children.first().unwrap().clone();
     let text = as_text_mut(&mut text_node).unwrap();
     let result = text.delete_data(6, 6);
     assert!(result.is_ok());
@@ -173,6 +178,76 @@ fn test_text_delete() {
     assert_eq!(text.data(), Some("Hello ".to_string()));
 }
 
+#[test]
+fn test_text_multi_byte_offsets() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+
+    let mut text_node = document.create_text_node("¡Grüße, 世界!");
+    let text = as_text_mut(&mut text_node).unwrap();
+    assert_eq!(text.length(), 11);
+    assert_eq!(text.substring_data(0, 1), Ok("¡".to_string()));
+    assert_eq!(text.substring_data(3, 3), Ok("üße".to_string()));
+    assert_eq!(text.substring_data(8, 100), Ok("世界!".to_string()));
+    assert_eq!(text.substring_data(11, 1), Ok("".to_string()));
+    assert_eq!(text.substring_data(12, 0), Err(Error::IndexSize));
+
+    assert!(text.insert_data(9, "大").is_ok());
+    assert_eq!(text.data(), Some("¡Grüße, 世大界!".to_string()));
+    assert!(text.insert_data(12, "¿").is_ok());
+    assert_eq!(text.data(), Some("¡Grüße, 世大界!¿".to_string()));
+    assert_eq!(text.insert_data(14, "x"), Err(Error::IndexSize));
+
+    assert!(text.delete_data(3, 2).is_ok());
+    assert_eq!(text.data(), Some("¡Gre, 世大界!¿".to_string()));
+    assert!(text.replace_data(6, usize::MAX, "Welt").is_ok());
+    assert_eq!(text.data(), Some("¡Gre, Welt".to_string()));
+    assert_eq!(text.replace_data(11, 0, "!"), Err(Error::IndexSize));
+    assert_eq!(text.delete_data(11, 1), Err(Error::IndexSize));
+    assert_eq!(text.data(), Some("¡Gre, Welt".to_string()));
+}
+
+#[test]
+fn test_empty_character_data_offsets() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+
+    let mut text_node = document.create_text_node("");
+    let text = as_text_mut(&mut text_node).unwrap();
+    assert_eq!(text.length(), 0);
+    assert_eq!(text.substring_data(0, 10), Ok("".to_string()));
+    assert_eq!(text.substring_data(1, 0), Err(Error::IndexSize));
+    assert_eq!(text.insert_data(1, "x"), Err(Error::IndexSize));
+    assert!(text.insert_data(0, "ü").is_ok());
+    assert_eq!(text.length(), 1);
+}
+
+#[test]
+fn test_data_as_str() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+
+    let mut text_node = document.create_text_node("fish & chips");
+    #[cfg(not(feature = "sync"))]
+    let mut same_node = text_node.clone();
+    let text = as_text_mut(&mut text_node).unwrap();
+    assert_eq!(&*text.data_as_str(), "fish & chips");
+    assert_eq!(text.data(), Some("fish &#38; chips".to_string()));
+    assert_eq!(text.data_as_str().to_string(), text.node_value().unwrap());
+
+    #[cfg(not(feature = "sync"))]
+    {
+        let data = text.data_as_str();
+        assert_eq!(data.len(), 12);
+        let same = as_text_mut(&mut same_node).unwrap();
+        assert_eq!(same.append_data("!"), Err(Error::Borrowed));
+    }
+    assert!(text.append_data(" & peas").is_ok());
+    assert_eq!(&*text.data_as_str(), "fish & chips & peas");
+    assert!(text.unset_data().is_ok());
+    assert_eq!(&*text.data_as_str(), "");
+}
+
 #[test]
 fn test_cdata_split() {
     let mut document_node = common::create_empty_rdf_document();
@@ -187,12 +262,12 @@ fn test_cdata_split() {
     let children = root_element.child_nodes();
     assert_eq!(children.len(), 1);
 
-    let mut text_node = children.get(0).unwrap().clone();
+    let mut text_node = children.first().unwrap().clone();
     let cdata = as_cdata_section_mut(&mut text_node).unwrap();
     let result = cdata.split(3);
     assert!(result.is_ok());
 
-    let expected = vec!["one", "two"];
+    let expected = ["one", "two"];
     for (index, child_node) in root_element.child_nodes().iter().enumerate() {
         // The following also ensures `node_type == NodeType::CData`
         let text = as_cdata_section(&child_node).unwrap();
@@ -208,7 +283,7 @@ fn test_text_split() {
     let mut root_node = document.document_element().unwrap();
     let root_element = as_element_mut(&mut root_node).unwrap();
 
-    for content in vec!["onetwo", "threefour", "fivesix"] {
+    for content in ["onetwo", "threefour", "fivesix"] {
         let text_node = document.create_text_node(content);
         let _ignore = root_element.append_child(text_node);
     }
@@ -216,7 +291,7 @@ fn test_text_split() {
     let children = root_element.child_nodes();
     assert_eq!(children.len(), 3);
 
-    let mut text_node = children.get(0).unwrap().clone();
+    let mut text_node = children.first().unwrap().clone();
     let text = as_text_mut(&mut text_node).unwrap();
     let result = text.split(3);
     assert!(result.is_ok());
@@ -224,7 +299,7 @@ fn test_text_split() {
     let children = root_element.child_nodes();
     assert_eq!(children.len(), 4);
 
-    let mut text_node = children.get(2).unwrap().clone();
+    let mut text_node = children.item(2).unwrap().clone();
     let text = as_text_mut(&mut text_node).unwrap();
     let result = text.split(0);
     assert!(result.is_ok());
@@ -232,18 +307,415 @@ fn test_text_split() {
     let children = root_element.child_nodes();
     assert_eq!(children.len(), 5);
 
-    let mut text_node = children.get(4).unwrap().clone();
+    let mut text_node = children.item(4).unwrap().clone();
     let text = as_text_mut(&mut text_node).unwrap();
     let result = text.split(9);
+    assert_eq!(result, Err(Error::IndexSize));
+    let result = text.split(7);
     assert!(result.is_ok());
 
     let children = root_element.child_nodes();
     assert_eq!(children.len(), 6);
 
-    let expected = vec!["one", "two", "", "threefour", "fivesix", ""];
+    let expected = ["one", "two", "", "threefour", "fivesix", ""];
     for (index, child_node) in root_element.child_nodes().iter().enumerate() {
         // The following also ensures `node_type == NodeType::Text`
         let text = as_text(&child_node).unwrap();
         assert_eq!(text.data().unwrap(), expected[index].to_string());
     }
 }
+
+#[test]
+fn test_text_split_multi_byte() {
+    let mut document_node = common::create_empty_rdf_document();
+    let document = as_document_mut(&mut document_node).unwrap();
+
+    let mut root_node = document.document_element().unwrap();
+    let root_element = as_element_mut(&mut root_node).unwrap();
+
+    let text_node = document.create_text_node("日本語テキスト");
+    let mut text_node = root_element.append_child(text_node).unwrap();
+    let text = as_text_mut(&mut text_node).unwrap();
+    let new_node = text.split(3).unwrap();
+    assert_eq!(text.data(), Some("日本語".to_string()));
+    assert_eq!(
+        as_text(&new_node).unwrap().data(),
+        Some("テキスト".to_string())
+    );
+    assert_eq!(text.next_sibling(), Some(new_node.clone()));
+    assert_eq!(root_element.child_nodes().len(), 2);
+
+    let mut cdata_node = document.create_cdata_section("αβγ").unwrap();
+    let cdata = as_cdata_section_mut(&mut cdata_node).unwrap();
+    let new_node = cdata.split(1).unwrap();
+    assert_eq!(cdata.data(), Some("α".to_string()));
+    // The following also ensures `node_type == NodeType::CData`
+    let new_cdata = as_cdata_section(&new_node).unwrap();
+    assert_eq!(new_cdata.data(), Some("βγ".to_string()));
+    assert!(new_cdata.parent_node().is_none());
+
+    assert_eq!(cdata.split(2), Err(Error::IndexSize));
+    assert_eq!(cdata.data(), Some("α".to_string()));
+}
+
+#[test]
+fn test_whole_text() {
+    let mut document_node = common::create_empty_rdf_document();
+    let document = as_document_mut(&mut document_node).unwrap();
+
+    let mut root_node = document.document_element().unwrap();
+    let root_element = as_element_mut(&mut root_node).unwrap();
+
+    let first = root_element
+        .append_child(document.create_text_node("one "))
+        .unwrap();
+    let mut second = root_element
+        .append_child(document.create_cdata_section("<two>").unwrap())
+        .unwrap();
+    let _safe_to_ignore = root_element
+        .append_child(document.create_text_node(" three"))
+        .unwrap();
+    let _safe_to_ignore = root_element
+        .append_child(document.create_comment("break").unwrap())
+        .unwrap();
+    let last = root_element
+        .append_child(document.create_text_node("four"))
+        .unwrap();
+
+    assert_eq!(first.whole_text(), "one <two> three".to_string());
+    assert_eq!(second.whole_text(), "one <two> three".to_string());
+    assert_eq!(last.whole_text(), "four".to_string());
+
+    let result = second.replace_whole_text("2");
+    assert_eq!(result, Ok(Some(second.clone())));
+    let children = root_element.child_nodes();
+    assert_eq!(children.len(), 3);
+    assert_eq!(children.first(), Some(second.clone()));
+    assert_eq!(second.whole_text(), "2".to_string());
+    assert_eq!(last.whole_text(), "four".to_string());
+
+    let mut last = last;
+    assert_eq!(last.replace_whole_text(""), Ok(None));
+    assert_eq!(root_element.child_nodes().len(), 2);
+}
+
+#[test]
+fn test_whole_text_no_parent() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+
+    let mut text_node = document.create_text_node("alone");
+    assert_eq!(text_node.whole_text(), "alone".to_string());
+    assert_eq!(
+        text_node.replace_whole_text("still alone"),
+        Ok(Some(text_node.clone()))
+    );
+    assert_eq!(text_node.whole_text(), "still alone".to_string());
+}
+
+#[test]
+fn test_invalid_comment_data() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+
+    assert_eq!(
+        document.create_comment("a -- b").err(),
+        Some(Error::InvalidCharacter)
+    );
+    assert_eq!(
+        document.create_comment("a -").err(),
+        Some(Error::InvalidCharacter)
+    );
+    assert!(document.create_comment("- a - b").is_ok());
+
+    let mut node = document.create_comment("a").unwrap();
+    let comment = as_comment_mut(&mut node).unwrap();
+    assert_eq!(comment.set_data("a--b"), Err(Error::InvalidCharacter));
+    assert_eq!(comment.append_data(" -"), Err(Error::InvalidCharacter));
+    assert_eq!(comment.insert_data(0, "-"), Ok(()));
+    assert_eq!(comment.insert_data(0, "-"), Err(Error::InvalidCharacter));
+    assert_eq!(
+        comment.replace_data(1, 1, "-"),
+        Err(Error::InvalidCharacter)
+    );
+    assert_eq!(comment.data(), Some("-a".to_string()));
+}
+
+#[test]
+fn test_invalid_cdata_data() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+
+    assert_eq!(
+        document.create_cdata_section("x ]]> y").err(),
+        Some(Error::InvalidCharacter)
+    );
+
+    let mut node = document.create_cdata_section("x ]] y").unwrap();
+    let cdata = as_cdata_section_mut(&mut node).unwrap();
+    assert_eq!(cdata.set_data("]]>"), Err(Error::InvalidCharacter));
+    assert_eq!(cdata.append_data(">"), Ok(()));
+    assert_eq!(cdata.insert_data(4, ">"), Err(Error::InvalidCharacter));
+    assert_eq!(cdata.replace_data(4, 1, "]>"), Err(Error::InvalidCharacter));
+    assert_eq!(cdata.data(), Some("x ]] y>".to_string()));
+    assert_eq!(node.to_string(), "<![CDATA[x ]] y>]]>");
+}
+
+#[test]
+fn test_is_element_content_whitespace() {
+    let document_node = common::create_empty_rdf_document();
+    let document = as_document(&document_node).unwrap();
+    for (data, expected) in [
+        ("", true),
+        (" \t\r\n", true),
+        ("  x ", false),
+        ("\u{A0}", false),
+    ] {
+        let text_node = document.create_text_node(data);
+        let text = as_text(&text_node).unwrap();
+        assert_eq!(text.is_element_content_whitespace(), expected);
+    }
+    let cdata_node = document.create_cdata_section("\n").unwrap();
+    let cdata = as_cdata_section(&cdata_node).unwrap();
+    assert!(cdata.is_element_content_whitespace());
+}
+
+fn make_whitespace_document() -> RefNode {
+    let implementation = get_implementation();
+    let mut document_node = implementation
+        .create_document(None, Some("root"), None)
+        .unwrap();
+    let document = as_document_mut(&mut document_node).unwrap();
+    let append = |parent: &mut RefNode, child: RefNode| {
+        let _safe_to_ignore = parent.append_child(child.clone()).unwrap();
+        child
+    };
+    let element = |name: &str| document.create_element(name).unwrap();
+    let text = |data: &str| document.create_text_node(data);
+
+    let mut root = document.document_element().unwrap();
+    let _safe_to_ignore = append(&mut root, text("\n  "));
+    let mut a = append(&mut root, element("a"));
+    let _safe_to_ignore = append(&mut a, text("\n    "));
+    let mut b = append(&mut a, element("b"));
+    let _safe_to_ignore = append(&mut b, text("x"));
+    let _safe_to_ignore = append(&mut a, text("\n  "));
+    let _safe_to_ignore = append(&mut root, text("\n  "));
+    let mut pre = append(&mut root, element("pre"));
+    let _safe_to_ignore = pre.set_attribute(Name::XML_SPACE, "preserve");
+    let _safe_to_ignore = append(&mut pre, text("  "));
+    let _safe_to_ignore = append(&mut pre, element("c"));
+    let _safe_to_ignore = append(&mut pre, text("  "));
+    let mut inner = append(&mut pre, element("inner"));
+    let _safe_to_ignore = inner.set_attribute(Name::XML_SPACE, "default");
+    let _safe_to_ignore = append(&mut inner, text(" "));
+    let _safe_to_ignore = append(&mut inner, element("d"));
+    let _safe_to_ignore = append(&mut root, text("\n  "));
+    let mut mixed = append(&mut root, element("mixed"));
+    let _safe_to_ignore = append(&mut mixed, text("text "));
+    let _safe_to_ignore = append(&mut mixed, element("e"));
+    let _safe_to_ignore = append(&mut mixed, text(" "));
+    let _safe_to_ignore = append(&mut root, text("\n"));
+    document_node
+}
+
+#[test]
+fn test_strip_whitespace_text_nodes() {
+    let mut document_node = make_whitespace_document();
+    document_node.strip_whitespace_text_nodes(false).unwrap();
+    assert_eq!(
+        document_node.to_string(),
+        "<root><a><b>x</b></a><pre xml:space=\"preserve\"><c></c><inner xml:space=\"default\">\
+<d></d></inner></pre><mixed>text <e></e> </mixed></root>"
+    );
+}
+
+#[test]
+fn test_strip_whitespace_text_nodes_xml_space() {
+    let mut document_node = make_whitespace_document();
+    document_node.strip_whitespace_text_nodes(true).unwrap();
+    assert_eq!(
+        document_node.to_string(),
+        "<root><a><b>x</b></a><pre xml:space=\"preserve\">  <c></c>  <inner xml:space=\"default\">\
+<d></d></inner></pre><mixed>text <e></e> </mixed></root>"
+    );
+
+    //
+    // Only the sub-tree of the element is changed.
+    //
+    let document_node = make_whitespace_document();
+    let mut a = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap()
+        .child_nodes()
+        .item(1)
+        .unwrap();
+    a.strip_whitespace_text_nodes(true).unwrap();
+    assert_eq!(a.to_string(), "<a><b>x</b></a>");
+    assert!(document_node.to_string().starts_with("<root>\n  <a>"));
+}
+
+#[test]
+fn test_escape_and_unescape() {
+    let value = "a < b & 'c' > \"d\"\t";
+    assert_eq!(
+        escape_text(value),
+        Ok("a &#60; b &#38; &#39;c&#39; &#62; &#34;d&#34;\t".to_string())
+    );
+    assert_eq!(
+        escape_attribute_quoted(value, Quote::Double),
+        Ok("a &#60; b &#38; 'c' &#62; &#34;d&#34;&#9;".to_string())
+    );
+    assert_eq!(
+        escape_attribute_quoted(value, Quote::Single),
+        Ok("a &#60; b &#38; &#39;c&#39; &#62; \"d\"&#9;".to_string())
+    );
+    assert_eq!(
+        escape_attribute_quoted("\u{1}", Quote::Single),
+        Err(Error::InvalidCharacter)
+    );
+    assert_eq!(
+        unescape(&escape_attribute_with("\u{1}", InvalidCharHandling::Escape).unwrap()),
+        Err(Error::InvalidCharacter)
+    );
+
+    let value = "a < b & 'c' > \"d\"\t\u{10FFFF}";
+    assert_eq!(
+        unescape(&escape_text(value).unwrap()),
+        Ok(value.to_string())
+    );
+    assert_eq!(
+        unescape(&escape_attribute(value).unwrap()),
+        Ok(value.to_string())
+    );
+    assert_eq!(
+        unescape("&lt;&gt;&amp;&apos;&quot;&#x41;&#x6a;&#66;"),
+        Ok("<>&'\"AjB".to_string())
+    );
+    assert_eq!(unescape("no references"), Ok("no references".to_string()));
+
+    for invalid in [
+        "&", "&amp", "&;", "&nbsp;", "&#;", "&#x;", "&#+65;", "&#xG;", "&# 65;",
+    ] {
+        assert_eq!(unescape(invalid), Err(Error::Syntax), "{}", invalid);
+    }
+    for invalid in [
+        "&#0;",
+        "&#xD800;",
+        "&#xFFFE;",
+        "&#x110000;",
+        "&#99999999999;",
+    ] {
+        assert_eq!(
+            unescape(invalid),
+            Err(Error::InvalidCharacter),
+            "{}",
+            invalid
+        );
+    }
+}
+
+#[test]
+fn test_character_classes() {
+    for c in [
+        '\t',
+        '\n',
+        '\r',
+        ' ',
+        'a',
+        '\u{D7FF}',
+        '\u{E000}',
+        '\u{10000}',
+    ] {
+        assert!(is_xml_char(c), "{:?}", c);
+    }
+    for c in ['\u{0}', '\u{8}', '\u{B}', '\u{1F}', '\u{FFFE}', '\u{FFFF}'] {
+        assert!(!is_xml_char(c), "{:?}", c);
+    }
+    for c in [' ', '\t', '\n', '\r'] {
+        assert!(is_xml_whitespace(c), "{:?}", c);
+    }
+    for c in ['\u{A0}', '\u{85}', '\u{2028}', 'a'] {
+        assert!(!is_xml_whitespace(c), "{:?}", c);
+    }
+}
+
+#[test]
+fn test_character_content_checks() {
+    let mut document_node = get_implementation()
+        .create_document(None, Some("root"), None)
+        .unwrap();
+    let mut root_node = document_node.document_element().unwrap();
+    assert!(document_node.strict_error_checking());
+
+    let mut invalid: Vec<char> = ('\u{0}'..='\u{1F}')
+        .filter(|c| !matches!(c, '\t' | '\n' | '\r'))
+        .collect();
+    invalid.extend(['\u{FFFE}', '\u{FFFF}']);
+    for c in invalid {
+        let data = format!("a{}b", c);
+        let mut text_node = document_node.create_text_node("text");
+        assert_eq!(
+            document_node.try_create_text_node(&data),
+            Err(Error::InvalidCharacter)
+        );
+        assert_eq!(
+            document_node.create_cdata_section(&data),
+            Err(Error::InvalidCharacter)
+        );
+        assert_eq!(
+            document_node.create_comment(&data),
+            Err(Error::InvalidCharacter)
+        );
+        assert_eq!(
+            document_node.create_processing_instruction("pi", Some(&data)),
+            Err(Error::InvalidCharacter)
+        );
+        assert_eq!(
+            document_node.create_attribute_with("a", &data),
+            Err(Error::InvalidCharacter)
+        );
+        assert_eq!(
+            root_node.set_attribute("a", &data),
+            Err(Error::InvalidCharacter)
+        );
+        assert_eq!(
+            root_node.set_attribute_ns("urn:a", "p:a", &data),
+            Err(Error::InvalidCharacter)
+        );
+        assert_eq!(text_node.set_data(&data), Err(Error::InvalidCharacter));
+        assert_eq!(text_node.append_data(&data), Err(Error::InvalidCharacter));
+        assert_eq!(
+            text_node.insert_data(1, &data),
+            Err(Error::InvalidCharacter)
+        );
+        assert_eq!(
+            text_node.replace_data(1, 2, &data),
+            Err(Error::InvalidCharacter)
+        );
+        assert_eq!(text_node.node_value(), Some("text".to_string()));
+    }
+    assert!(!root_node.has_attributes());
+
+    let valid = "\t\n\r \u{D7FF}\u{E000}\u{FFFD}\u{10000}\u{1F600}\u{10FFFF}";
+    let mut text_node = document_node.try_create_text_node(valid).unwrap();
+    assert!(document_node.create_cdata_section(valid).is_ok());
+    assert!(document_node.create_comment(valid).is_ok());
+    text_node.append_data(valid).unwrap();
+    root_node.set_attribute("a", valid).unwrap();
+    let mut attribute_node = root_node.get_attribute_node("a").unwrap();
+    assert_eq!(
+        attribute_node.set_value("\u{1}"),
+        Err(Error::InvalidCharacter)
+    );
+
+    document_node.set_strict_error_checking(false).unwrap();
+    assert!(!document_node.strict_error_checking());
+    assert!(document_node.try_create_text_node("\u{1}").is_ok());
+    assert!(document_node.create_comment("\u{1}").is_ok());
+    text_node.set_data("\u{FFFF}").unwrap();
+    attribute_node.set_value("\u{1}").unwrap();
+    root_node.set_attribute("b", "\u{B}").unwrap();
+    assert_eq!(root_node.get_attribute("b"), Some("&#11;".to_string()));
+}
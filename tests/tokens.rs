@@ -0,0 +1,60 @@
+#![cfg(feature = "quick_parser")]
+
+use xml_dom::level2::ext::*;
+use xml_dom::level2::*;
+use xml_dom::parser::read_xml;
+
+fn tokens(tokens: &AttributeTokens) -> Vec<String> {
+    tokens.iter().collect()
+}
+
+#[test]
+fn test_attribute_tokens() {
+    let document_node = read_xml("<a class=\"b\ta  b\nc \"/>").unwrap();
+    let root_node = document_node.first_child().unwrap();
+
+    let mut classes = root_node.attribute_tokens("class");
+    assert_eq!(tokens(&classes), vec!["b", "a", "c"]);
+    assert!(classes.contains("a"));
+    assert!(!classes.contains("d"));
+    assert!(!classes.contains(""));
+
+    classes.add("a").unwrap();
+    assert_eq!(root_node.get_attribute("class").unwrap(), "b a c");
+
+    classes.add("d").unwrap();
+    classes.remove("b").unwrap();
+    classes.remove("x").unwrap();
+    assert_eq!(root_node.get_attribute("class").unwrap(), "a c d");
+
+    assert!(classes.toggle("b").unwrap());
+    assert!(!classes.toggle("a").unwrap());
+    assert_eq!(tokens(&classes), vec!["c", "d", "b"]);
+
+    classes.remove("c").unwrap();
+    classes.remove("d").unwrap();
+    classes.remove("b").unwrap();
+    assert_eq!(root_node.get_attribute("class").unwrap(), "");
+    assert_eq!(classes.iter().count(), 0);
+}
+
+#[test]
+fn test_absent_attribute_tokens() {
+    let document_node = read_xml("<a/>").unwrap();
+    let root_node = document_node.first_child().unwrap();
+
+    let mut rel = root_node.attribute_tokens("rel");
+    assert_eq!(rel.iter().count(), 0);
+    assert!(!rel.contains("next"));
+
+    rel.remove("next").unwrap();
+    assert!(!root_node.has_attribute("rel"));
+
+    rel.add("next").unwrap();
+    assert_eq!(root_node.get_attribute("rel").unwrap(), "next");
+
+    assert_eq!(rel.add(""), Err(Error::Syntax));
+    assert_eq!(rel.toggle("a b"), Err(Error::InvalidCharacter));
+    assert_eq!(rel.remove("a\tb"), Err(Error::InvalidCharacter));
+    assert_eq!(root_node.get_attribute("rel").unwrap(), "next");
+}
@@ -0,0 +1,356 @@
+#![cfg(feature = "quick_parser")]
+
+use xml_dom::level2::convert::*;
+use xml_dom::level2::ext::TreeIterators;
+use xml_dom::level2::traversal::*;
+use xml_dom::level2::*;
+use xml_dom::parser::read_xml;
+
+const TRAVERSAL_XML: &str = r#"<a><b><c/>one</b><!--two--><d><e>three</e></d></a>"#;
+
+fn collect_forward(iterator: &mut NodeIterator) -> Vec<String> {
+    let mut names = Vec::new();
+    while let Some(node) = iterator.next_node().unwrap() {
+        names.push(node.node_name().to_string());
+    }
+    names
+}
+
+fn collect_backward(iterator: &mut NodeIterator) -> Vec<String> {
+    let mut names = Vec::new();
+    while let Some(node) = iterator.previous_node().unwrap() {
+        names.push(node.node_name().to_string());
+    }
+    names
+}
+
+#[test]
+fn test_has_feature() {
+    let implementation = get_implementation();
+    assert!(implementation.has_feature("Traversal", "2.0"));
+    assert!(!implementation.has_feature("Traversal", "1.0"));
+    assert!(!implementation.has_feature("Unknown", "2.0"));
+}
+
+#[test]
+fn test_what_to_show() {
+    assert!(WhatToShow::ALL.shows(&NodeType::Notation));
+    assert_eq!(WhatToShow::from(NodeType::Element), WhatToShow::ELEMENT);
+    assert_eq!(WhatToShow::from(NodeType::Notation), WhatToShow::NOTATION);
+    let what_to_show = WhatToShow::ELEMENT | WhatToShow::COMMENT;
+    assert!(what_to_show.shows(&NodeType::Element));
+    assert!(what_to_show.shows(&NodeType::Comment));
+    assert!(!what_to_show.shows(&NodeType::Text));
+    assert_eq!(u32::from(what_to_show), 0x81);
+    assert_eq!(what_to_show & WhatToShow::COMMENT, WhatToShow::COMMENT);
+}
+
+#[test]
+fn test_iterate_all() {
+    let document_node = read_xml(TRAVERSAL_XML).unwrap();
+    let mut iterator = document_node
+        .create_node_iterator(document_node.clone(), WhatToShow::ALL, None, true)
+        .unwrap();
+    assert_eq!(iterator.root(), document_node);
+    assert!(iterator.pointer_before_reference_node());
+
+    let forward = collect_forward(&mut iterator);
+    assert_eq!(
+        forward,
+        vec![
+            "#document",
+            "a",
+            "b",
+            "c",
+            "#text",
+            "#comment",
+            "d",
+            "e",
+            "#text"
+        ]
+    );
+    assert!(!iterator.pointer_before_reference_node());
+
+    let mut backward = collect_backward(&mut iterator);
+    backward.reverse();
+    assert_eq!(backward, forward);
+}
+
+#[test]
+fn test_iterate_what_to_show() {
+    let document_node = read_xml(TRAVERSAL_XML).unwrap();
+    let root_node = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    let mut iterator = document_node
+        .create_node_iterator(root_node, WhatToShow::ELEMENT, None, true)
+        .unwrap();
+    assert_eq!(
+        collect_forward(&mut iterator),
+        vec!["a", "b", "c", "d", "e"]
+    );
+
+    let mut iterator = document_node
+        .create_node_iterator(
+            document_node.clone(),
+            WhatToShow::TEXT | WhatToShow::COMMENT,
+            None,
+            true,
+        )
+        .unwrap();
+    assert_eq!(
+        collect_forward(&mut iterator),
+        vec!["#text", "#comment", "#text"]
+    );
+}
+
+#[test]
+fn test_iterate_filter() {
+    let document_node = read_xml(TRAVERSAL_XML).unwrap();
+    //
+    // Reject is the same as Skip for a NodeIterator, children are still visited.
+    //
+    let filter: NodeFilterRef = Shared::new(|node: &RefNode| {
+        if node.node_name().to_string() == "b" {
+            FilterResult::Reject
+        } else if node.node_name().to_string() == "d" {
+            FilterResult::Skip
+        } else {
+            FilterResult::Accept
+        }
+    });
+    let mut iterator = document_node
+        .create_node_iterator(
+            document_node.clone(),
+            WhatToShow::ELEMENT,
+            Some(filter),
+            true,
+        )
+        .unwrap();
+    assert!(iterator.filter().is_some());
+    assert_eq!(collect_forward(&mut iterator), vec!["a", "c", "e"]);
+}
+
+#[test]
+fn test_detach() {
+    let document_node = read_xml(TRAVERSAL_XML).unwrap();
+    let mut iterator = document_node
+        .create_node_iterator(document_node.clone(), WhatToShow::ALL, None, true)
+        .unwrap();
+    assert!(iterator.next_node().unwrap().is_some());
+    iterator.detach();
+    assert_eq!(iterator.next_node(), Err(Error::InvalidState));
+    assert_eq!(iterator.previous_node(), Err(Error::InvalidState));
+}
+
+#[test]
+fn test_remove_reference_node() {
+    let document_node = read_xml(TRAVERSAL_XML).unwrap();
+    let mut iterator = document_node
+        .create_node_iterator(document_node.clone(), WhatToShow::ELEMENT, None, true)
+        .unwrap();
+    let _ = iterator.next_node().unwrap();
+    let b_node = iterator.next_node().unwrap().unwrap();
+    assert_eq!(b_node.node_name().to_string(), "b");
+
+    //
+    // Remove the subtree containing the reference node, the iterator is after "b" and so moves
+    // back to the preceding node.
+    //
+    let mut a_node = b_node.parent_node().unwrap();
+    let _removed = a_node.remove_child(b_node).unwrap();
+    assert_eq!(iterator.reference_node(), a_node);
+    let next = iterator.next_node().unwrap().unwrap();
+    assert_eq!(next.node_name().to_string(), "d");
+
+    //
+    // Step back so the iterator is before "d", then remove "d"; it moves forward to the node
+    // following the removed subtree, which is none, and so falls back to the preceding node.
+    //
+    let d_node = iterator.previous_node().unwrap().unwrap();
+    assert_eq!(d_node.node_name().to_string(), "d");
+    assert!(iterator.pointer_before_reference_node());
+    let _removed = a_node.remove_child(d_node).unwrap();
+    assert!(!iterator.pointer_before_reference_node());
+    assert!(iterator.next_node().unwrap().is_none());
+    let previous = iterator.previous_node().unwrap().unwrap();
+    assert_eq!(previous.node_name().to_string(), "a");
+}
+
+#[test]
+fn test_remove_while_iterating() {
+    let document_node = read_xml(r#"<a><b/><c/><d/><e/></a>"#).unwrap();
+    let root_node = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    let mut iterator = document_node
+        .create_node_iterator(root_node.clone(), WhatToShow::ELEMENT, None, true)
+        .unwrap();
+    let mut visited = Vec::new();
+    while let Some(node) = iterator.next_node().unwrap() {
+        visited.push(node.node_name().to_string());
+        if node != root_node {
+            let mut parent_node = node.parent_node().unwrap();
+            let _removed = parent_node.remove_child(node).unwrap();
+        }
+    }
+    assert_eq!(visited, vec!["a", "b", "c", "d", "e"]);
+    assert!(!root_node.has_child_nodes());
+}
+
+fn name_of(node: Option<RefNode>) -> Option<String> {
+    node.map(|node| node.node_name().to_string())
+}
+
+#[test]
+fn test_tree_walker_navigation() {
+    let document_node = read_xml(TRAVERSAL_XML).unwrap();
+    let root_node = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    let mut walker = document_node
+        .create_tree_walker(root_node.clone(), WhatToShow::ELEMENT, None, true)
+        .unwrap();
+    assert_eq!(walker.current_node(), root_node);
+    assert_eq!(walker.parent_node(), None);
+
+    assert_eq!(name_of(walker.first_child()), Some("b".to_string()));
+    assert_eq!(name_of(walker.first_child()), Some("c".to_string()));
+    assert_eq!(walker.first_child(), None);
+    assert_eq!(walker.next_sibling(), None);
+    assert_eq!(name_of(walker.parent_node()), Some("b".to_string()));
+    assert_eq!(name_of(walker.next_sibling()), Some("d".to_string()));
+    assert_eq!(walker.next_sibling(), None);
+    assert_eq!(name_of(walker.previous_sibling()), Some("b".to_string()));
+    assert_eq!(walker.previous_sibling(), None);
+    assert_eq!(name_of(walker.parent_node()), Some("a".to_string()));
+    assert_eq!(name_of(walker.last_child()), Some("d".to_string()));
+    assert_eq!(walker.parent_node().unwrap(), root_node);
+    assert_eq!(walker.parent_node(), None);
+    assert_eq!(walker.current_node(), root_node);
+}
+
+#[test]
+fn test_tree_walker_document_order() {
+    let document_node = read_xml(TRAVERSAL_XML).unwrap();
+    let mut walker = document_node
+        .create_tree_walker(document_node.clone(), WhatToShow::ALL, None, true)
+        .unwrap();
+    let mut forward = Vec::new();
+    while let Some(node) = walker.next_node() {
+        forward.push(node.node_name().to_string());
+    }
+    assert_eq!(
+        forward,
+        vec!["a", "b", "c", "#text", "#comment", "d", "e", "#text"]
+    );
+    let mut backward = Vec::new();
+    while let Some(node) = walker.previous_node() {
+        backward.push(node.node_name().to_string());
+    }
+    backward.reverse();
+    assert_eq!(
+        backward,
+        vec!["#document", "a", "b", "c", "#text", "#comment", "d", "e"]
+    );
+    assert_eq!(walker.current_node(), document_node);
+}
+
+#[test]
+fn test_tree_walker_reject_and_skip() {
+    let document_node = read_xml(TRAVERSAL_XML).unwrap();
+    let reject_b: NodeFilterRef = Shared::new(|node: &RefNode| {
+        if node.node_name().to_string() == "b" {
+            FilterResult::Reject
+        } else {
+            FilterResult::Accept
+        }
+    });
+    let skip_b: NodeFilterRef = Shared::new(|node: &RefNode| {
+        if node.node_name().to_string() == "b" {
+            FilterResult::Skip
+        } else {
+            FilterResult::Accept
+        }
+    });
+
+    let walk = |filter: NodeFilterRef| {
+        let mut walker = document_node
+            .create_tree_walker(
+                document_node.clone(),
+                WhatToShow::ELEMENT,
+                Some(filter),
+                true,
+            )
+            .unwrap();
+        let mut names = Vec::new();
+        while let Some(node) = walker.next_node() {
+            names.push(node.node_name().to_string());
+        }
+        let mut reversed = Vec::new();
+        while let Some(node) = walker.previous_node() {
+            reversed.push(node.node_name().to_string());
+        }
+        (names, reversed)
+    };
+
+    //
+    // Reject removes "b" and all its descendants from the logical view.
+    //
+    let (names, reversed) = walk(reject_b.clone());
+    assert_eq!(names, vec!["a", "d", "e"]);
+    assert_eq!(reversed, vec!["d", "a"]);
+
+    //
+    // Skip removes only "b", its child "c" is still visible.
+    //
+    let (names, reversed) = walk(skip_b.clone());
+    assert_eq!(names, vec!["a", "c", "d", "e"]);
+    assert_eq!(reversed, vec!["d", "c", "a"]);
+
+    //
+    // With "b" skipped, "c" appears as the first child of "a", and a sibling of "d".
+    //
+    let root_node = as_document(&document_node)
+        .unwrap()
+        .document_element()
+        .unwrap();
+    let mut walker = document_node
+        .create_tree_walker(root_node.clone(), WhatToShow::ELEMENT, Some(skip_b), true)
+        .unwrap();
+    assert_eq!(name_of(walker.first_child()), Some("c".to_string()));
+    assert_eq!(name_of(walker.next_sibling()), Some("d".to_string()));
+    assert_eq!(name_of(walker.previous_sibling()), Some("c".to_string()));
+    assert_eq!(walker.parent_node().unwrap(), root_node);
+
+    let mut walker = document_node
+        .create_tree_walker(root_node, WhatToShow::ELEMENT, Some(reject_b), true)
+        .unwrap();
+    assert_eq!(name_of(walker.first_child()), Some("d".to_string()));
+    assert_eq!(walker.previous_sibling(), None);
+}
+
+#[test]
+fn test_tree_walker_set_current_node() {
+    let document_node = read_xml(TRAVERSAL_XML).unwrap();
+    let mut walker = document_node
+        .create_tree_walker(document_node.clone(), WhatToShow::ELEMENT, None, true)
+        .unwrap();
+    let e_node = document_node.descendant_elements().last().unwrap();
+    walker.set_current_node(e_node.clone());
+    assert_eq!(walker.current_node(), e_node);
+    assert_eq!(walker.next_node(), None);
+    assert_eq!(name_of(walker.previous_node()), Some("d".to_string()));
+
+    //
+    // The current node may be removed from the tree; navigation continues from where it is.
+    //
+    let mut parent_node = e_node.parent_node().unwrap();
+    let _removed = parent_node.remove_child(e_node.clone()).unwrap();
+    walker.set_current_node(e_node);
+    assert_eq!(walker.parent_node(), None);
+    assert_eq!(walker.next_node(), None);
+}
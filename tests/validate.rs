@@ -0,0 +1,280 @@
+#![cfg(feature = "quick_parser")]
+
+use std::str::FromStr;
+use xml_dom::level2::ext::convert::as_document_type_decl_mut;
+use xml_dom::level2::ext::*;
+use xml_dom::level2::*;
+use xml_dom::parser::read_xml;
+
+fn validated(
+    xml: &str,
+    element_decls: &[(&str, &str)],
+    attribute_decls: Vec<AttributeDecl>,
+) -> Vec<String> {
+    let document_node = read_xml(xml).unwrap();
+    if let Some(mut doc_type_node) = document_node.doc_type() {
+        let doc_type = as_document_type_decl_mut(&mut doc_type_node).unwrap();
+        for (name, content_spec) in element_decls {
+            let content_spec = ContentSpec::from_str(content_spec).unwrap();
+            let element_decl = ElementDecl::new(name, content_spec).unwrap();
+            assert_eq!(doc_type.add_element_decl(element_decl), Ok(true));
+        }
+        for attribute_decl in attribute_decls {
+            assert_eq!(doc_type.add_attribute_decl(attribute_decl), Ok(true));
+        }
+    }
+    document_node
+        .validate()
+        .iter()
+        .map(|error| error.path().to_string())
+        .collect()
+}
+
+fn attribute_decl(
+    element_name: &str,
+    attribute_name: &str,
+    attribute_type: AttributeType,
+    default: AttributeDefault,
+) -> AttributeDecl {
+    AttributeDecl::new(element_name, attribute_name, attribute_type, default).unwrap()
+}
+
+#[test]
+fn test_content_spec() {
+    for (text, expected) in [
+        ("EMPTY", "EMPTY"),
+        ("ANY", "ANY"),
+        ("( #PCDATA )", "(#PCDATA)"),
+        ("(#PCDATA)*", "(#PCDATA)"),
+        ("(#PCDATA | em | strong)*", "(#PCDATA|em|strong)*"),
+        ("(a)", "(a)"),
+        ("(a, b?, (c | d)+)*", "(a,b?,(c|d)+)*"),
+        ("((a|b),c)", "((a|b),c)"),
+    ] {
+        let content_spec = ContentSpec::from_str(text).unwrap();
+        assert_eq!(content_spec.to_string(), expected);
+        assert_eq!(ContentSpec::from_str(expected), Ok(content_spec));
+    }
+    assert_eq!(
+        ContentSpec::from_str("(a|b)?"),
+        Ok(ContentSpec::Children(ContentParticle::Choice(
+            vec![
+                ContentParticle::Name(Name::from_str("a").unwrap(), Occurrence::Once),
+                ContentParticle::Name(Name::from_str("b").unwrap(), Occurrence::Once),
+            ],
+            Occurrence::Optional
+        )))
+    );
+    for text in [
+        "",
+        "EMPTY?",
+        "a",
+        "(a",
+        "(a,b|c)",
+        "(#PCDATA|a)",
+        "(a,#PCDATA)",
+        "(a)(b)",
+        "(1a)",
+    ] {
+        assert_eq!(ContentSpec::from_str(text), Err(Error::Syntax), "{}", text);
+    }
+}
+
+#[test]
+fn test_content_spec_depth() {
+    let nested = |depth: usize| format!("{}a{}", "(".repeat(depth), ")".repeat(depth));
+    assert!(ContentSpec::from_str(&nested(128)).is_ok());
+    assert_eq!(ContentSpec::from_str(&nested(129)), Err(Error::Syntax));
+    assert_eq!(ContentSpec::from_str(&nested(20_000)), Err(Error::Syntax));
+}
+
+#[test]
+fn test_element_decls() {
+    let implementation = get_implementation();
+    let mut document_type = implementation
+        .create_document_type("list", None, None)
+        .unwrap();
+    let doc_type = as_document_type_decl_mut(&mut document_type).unwrap();
+    let item = ElementDecl::new("item", ContentSpec::Mixed(Vec::new())).unwrap();
+    assert_eq!(item.name().to_string(), "item");
+    assert_eq!(doc_type.add_element_decl(item), Ok(true));
+    let list = ElementDecl::new("list", ContentSpec::from_str("(item+)").unwrap()).unwrap();
+    assert_eq!(doc_type.add_element_decl(list), Ok(true));
+    let duplicate = ElementDecl::new("item", ContentSpec::Empty).unwrap();
+    assert_eq!(doc_type.add_element_decl(duplicate), Ok(false));
+    assert_eq!(doc_type.element_decls().len(), 2);
+    assert!(ElementDecl::new("1item", ContentSpec::Empty).is_err());
+    assert_eq!(
+        document_type.to_string(),
+        "<!DOCTYPE list [<!ELEMENT item (#PCDATA)><!ELEMENT list (item+)>]>"
+    );
+}
+
+#[test]
+fn test_validate_document() {
+    assert_eq!(
+        validated("<list/>", &[("list", "EMPTY")], Vec::new()),
+        vec!["/"]
+    );
+    assert_eq!(
+        validated("<!DOCTYPE other><list/>", &[("list", "EMPTY")], Vec::new()),
+        vec!["/list"]
+    );
+    assert_eq!(
+        validated(
+            "<!DOCTYPE list><list><x/></list>",
+            &[("list", "ANY")],
+            Vec::new()
+        ),
+        vec!["/list/x"]
+    );
+}
+
+#[test]
+fn test_validate_content() {
+    let element_decls = [
+        ("doc", "(head?, (para | list)+, foot*)"),
+        ("head", "(#PCDATA)"),
+        ("para", "(#PCDATA | em)*"),
+        ("list", "(item, item*)"),
+        ("item", "ANY"),
+        ("em", "(#PCDATA)"),
+        ("foot", "EMPTY"),
+    ];
+    let valid = r#"<!DOCTYPE doc><doc><head>title</head><para>some <em>text</em></para><list><item/><item>x<para/></item></list><para/><foot/><foot/></doc>"#;
+    assert!(validated(valid, &element_decls, Vec::new()).is_empty());
+    assert!(validated(
+        "<!DOCTYPE doc><doc><!--note--><list><item/></list></doc>",
+        &element_decls,
+        Vec::new()
+    )
+    .is_empty());
+
+    for (xml, expected) in [
+        // incomplete content is reported on the element.
+        ("<doc><head/></doc>", "/doc"),
+        ("<doc><list/></doc>", "/doc/list"),
+        // an unexpected element is reported on that element.
+        ("<doc><para/><head/></doc>", "/doc/head"),
+        ("<doc><para/><foot/><para/></doc>", "/doc/para[2]"),
+        // character data in element content.
+        ("<doc><para/>text</doc>", "/doc"),
+        ("<doc><para/><![CDATA[ ]]></doc>", "/doc"),
+        // an element not allowed in mixed content.
+        (
+            "<doc><para><list><item/></list></para></doc>",
+            "/doc/para/list",
+        ),
+        // content in an EMPTY element.
+        ("<doc><para/><foot><!--x--></foot></doc>", "/doc/foot"),
+        // an undeclared element.
+        ("<doc><para><em><b/></em></para></doc>", "/doc/para/em/b"),
+    ] {
+        let xml = format!("<!DOCTYPE doc>{}", xml);
+        let errors = validated(&xml, &element_decls, Vec::new());
+        assert!(!errors.is_empty(), "{}", xml);
+        assert_eq!(errors[0], expected, "{}", xml);
+    }
+}
+
+#[test]
+fn test_validate_content_messages() {
+    let document_node = read_xml("<!DOCTYPE a><a><b/></a>").unwrap();
+    let mut doc_type_node = document_node.doc_type().unwrap();
+    let doc_type = as_document_type_decl_mut(&mut doc_type_node).unwrap();
+    for (name, content_spec) in [("a", "(b, (c | d))"), ("b", "EMPTY")] {
+        let content_spec = ContentSpec::from_str(content_spec).unwrap();
+        let _ = doc_type
+            .add_element_decl(ElementDecl::new(name, content_spec).unwrap())
+            .unwrap();
+    }
+    let errors = document_node.validate();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0].to_string(),
+        "/a: the content of `a` ends before its content model, (b,(c|d)), is complete; expected `c` or `d`"
+    );
+}
+
+#[test]
+fn test_validate_content_depth() {
+    let document_node = read_xml("<!DOCTYPE a><a><b/></a>").unwrap();
+    let mut doc_type_node = document_node.doc_type().unwrap();
+    let doc_type = as_document_type_decl_mut(&mut doc_type_node).unwrap();
+    let mut particle = ContentParticle::Name(Name::from_str("b").unwrap(), Occurrence::Once);
+    for _ in 0..200 {
+        particle = ContentParticle::Sequence(vec![particle], Occurrence::Once);
+    }
+    for (name, content_spec) in [
+        ("a", ContentSpec::Children(particle)),
+        ("b", ContentSpec::Empty),
+    ] {
+        let _safe_to_ignore = doc_type
+            .add_element_decl(ElementDecl::new(name, content_spec).unwrap())
+            .unwrap();
+    }
+    let errors = document_node.validate();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0].to_string(),
+        "/a: the content model of `a` is nested more than 128 deep"
+    );
+}
+
+#[test]
+fn test_validate_attributes() {
+    let element_decls = [("doc", "(item*)"), ("item", "EMPTY")];
+    let attribute_decls = || {
+        vec![
+            attribute_decl(
+                "doc",
+                "version",
+                AttributeType::CData,
+                AttributeDefault::Fixed("1.0".to_string()),
+            ),
+            attribute_decl("item", "id", AttributeType::Id, AttributeDefault::Required),
+            attribute_decl(
+                "item",
+                "ref",
+                AttributeType::IdRefs,
+                AttributeDefault::Implied,
+            ),
+            attribute_decl(
+                "item",
+                "tags",
+                AttributeType::NmTokens,
+                AttributeDefault::Implied,
+            ),
+            attribute_decl(
+                "item",
+                "kind",
+                AttributeType::Enumeration(vec!["a".to_string(), "b".to_string()]),
+                AttributeDefault::Value("a".to_string()),
+            ),
+        ]
+    };
+    let valid = r#"<!DOCTYPE doc><doc xmlns:x="urn:x" version="1.0"><item id="i1" ref=" i2  i1 " tags="x-1 2" kind="b"/><item id="i2"/></doc>"#;
+    assert!(validated(valid, &element_decls, attribute_decls()).is_empty());
+
+    for (xml, expected) in [
+        (r#"<doc version="2.0"/>"#, "/doc/@version"),
+        (r#"<doc><item/></doc>"#, "/doc/item"),
+        (r#"<doc><item id="1"/></doc>"#, "/doc/item/@id"),
+        (
+            r#"<doc><item id="a"/><item id="a"/></doc>"#,
+            "/doc/item[2]/@id",
+        ),
+        (r#"<doc><item id="a" ref="b"/></doc>"#, "/doc/item/@ref"),
+        (r#"<doc><item id="a" tags="a,b"/></doc>"#, "/doc/item/@tags"),
+        (r#"<doc><item id="a" kind="c"/></doc>"#, "/doc/item/@kind"),
+        (r#"<doc><item id="a" other="c"/></doc>"#, "/doc/item/@other"),
+    ] {
+        let xml = format!("<!DOCTYPE doc>{}", xml);
+        assert_eq!(
+            validated(&xml, &element_decls, attribute_decls()),
+            vec![expected],
+            "{}",
+            xml
+        );
+    }
+}
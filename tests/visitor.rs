@@ -0,0 +1,159 @@
+#![cfg(feature = "quick_parser")]
+
+use xml_dom::level2::ext::*;
+use xml_dom::level2::*;
+use xml_dom::parser::read_xml;
+
+#[derive(Default)]
+struct Recorder {
+    visited: Vec<String>,
+    skip: Option<&'static str>,
+    stop: Option<&'static str>,
+}
+
+impl Recorder {
+    fn record(&mut self, kind: &str, node: &RefNode) -> VisitAction {
+        let name = match node.node_value() {
+            Some(value) => value,
+            None => node.node_name().to_string(),
+        };
+        self.visited.push(format!("{}:{}", kind, name));
+        if self.stop == Some(name.as_str()) {
+            VisitAction::Stop
+        } else if self.skip == Some(name.as_str()) {
+            VisitAction::SkipChildren
+        } else {
+            VisitAction::Continue
+        }
+    }
+}
+
+impl NodeVisitor for Recorder {
+    fn visit_element(&mut self, element: &mut RefNode) -> VisitAction {
+        self.record("element", element)
+    }
+    fn visit_text(&mut self, text: &mut RefNode) -> VisitAction {
+        self.record("text", text)
+    }
+    fn visit_comment(&mut self, comment: &mut RefNode) -> VisitAction {
+        self.record("comment", comment)
+    }
+    fn visit_cdata(&mut self, cdata: &mut RefNode) -> VisitAction {
+        self.record("cdata", cdata)
+    }
+    fn visit_pi(&mut self, pi: &mut RefNode) -> VisitAction {
+        self.record("pi", pi)
+    }
+}
+
+const XML: &str =
+    r#"<!DOCTYPE a><a x="1"><b>one<c/></b><!--two--><d><![CDATA[three]]></d><?go?></a>"#;
+
+#[test]
+fn test_visit_order() {
+    let document_node = read_xml(XML).unwrap();
+    let mut visitor = Recorder::default();
+    document_node.accept(&mut visitor).unwrap();
+    assert_eq!(
+        visitor.visited,
+        vec![
+            "element:a",
+            "element:b",
+            "text:one",
+            "element:c",
+            "comment:two",
+            "element:d",
+            "cdata:three",
+            "pi:go",
+        ]
+    );
+}
+
+#[test]
+fn test_visit_actions() {
+    let document_node = read_xml(XML).unwrap();
+    let mut visitor = Recorder {
+        skip: Some("b"),
+        ..Default::default()
+    };
+    document_node.accept(&mut visitor).unwrap();
+    assert_eq!(
+        visitor.visited,
+        vec![
+            "element:a",
+            "element:b",
+            "comment:two",
+            "element:d",
+            "cdata:three",
+            "pi:go"
+        ]
+    );
+
+    let mut visitor = Recorder {
+        stop: Some("c"),
+        ..Default::default()
+    };
+    document_node.accept(&mut visitor).unwrap();
+    assert_eq!(
+        visitor.visited,
+        vec!["element:a", "element:b", "text:one", "element:c"]
+    );
+}
+
+struct Transformer;
+
+impl NodeVisitor for Transformer {
+    fn visit_element(&mut self, element: &mut RefNode) -> VisitAction {
+        let mut parent = match element.parent_node() {
+            //
+            // Removed from the tree earlier in the walk.
+            //
+            None => return VisitAction::SkipChildren,
+            Some(parent) => parent,
+        };
+        let name = element.node_name().to_string();
+        if name == "b" {
+            while let Some(child) = element.first_child() {
+                let _safe_to_ignore = parent.insert_before(child, Some(element.clone())).unwrap();
+            }
+            let _safe_to_ignore = parent.remove_child(element.clone()).unwrap();
+        } else if name == "d" {
+            let document_node = element.owner_document().unwrap();
+            let new_node = document_node.create_element("e").unwrap();
+            let _safe_to_ignore = parent.replace_child(new_node, element.clone()).unwrap();
+        }
+        VisitAction::Continue
+    }
+
+    fn visit_comment(&mut self, comment: &mut RefNode) -> VisitAction {
+        let mut parent = comment.parent_node().unwrap();
+        let next_node = comment.next_sibling().unwrap();
+        let _safe_to_ignore = parent.remove_child(next_node).unwrap();
+        let _safe_to_ignore = parent.remove_child(comment.clone()).unwrap();
+        VisitAction::Continue
+    }
+
+    fn visit_text(&mut self, text: &mut RefNode) -> VisitAction {
+        let value = text.node_value().unwrap().to_uppercase();
+        text.set_node_value(&value).unwrap();
+        VisitAction::Continue
+    }
+}
+
+#[test]
+fn test_visit_with_changes() {
+    //
+    // The children of `b` are moved into `a` after its children were listed, and `d` is removed
+    // before it is visited, so neither text node is changed.
+    //
+    let document_node = read_xml(r#"<a><b>one<c/></b><!--x--><d>two</d><f>three</f></a>"#).unwrap();
+    document_node.accept(&mut Transformer).unwrap();
+    assert_eq!(document_node.to_string(), "<a>one<c></c><f>THREE</f></a>");
+
+    let document_node = read_xml(r#"<a><d><![CDATA[x]]></d></a>"#).unwrap();
+    let root_node = document_node.first_child().unwrap();
+    root_node.accept(&mut Transformer).unwrap();
+    let mut visitor = Recorder::default();
+    root_node.accept(&mut visitor).unwrap();
+    assert_eq!(visitor.visited, vec!["element:a", "element:e"]);
+}
@@ -0,0 +1,131 @@
+use std::time::{Duration, Instant};
+use xml_dom::level2::convert::*;
+use xml_dom::level2::*;
+
+//
+// Each of these walks, or empties, an element with many children; with a scan of the parent's
+// children for every step they are quadratic, and would take minutes rather than milliseconds.
+//
+const WIDTH: usize = 100_000;
+
+fn create_wide_element() -> (RefNode, RefNode) {
+    let mut document_node = get_implementation()
+        .create_document(None, Some("log"), None)
+        .unwrap();
+    let document = as_document_mut(&mut document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    for index in 0..WIDTH {
+        let _safe_to_ignore = root_node
+            .append_child(document.create_text_node(&index.to_string()))
+            .unwrap();
+    }
+    (document_node, root_node)
+}
+
+#[test]
+fn test_next_sibling() {
+    let (_document_node, root_node) = create_wide_element();
+    let mut count = 0;
+    let mut child_node = root_node.first_child();
+    while let Some(node) = child_node {
+        assert_eq!(node.node_value(), Some(count.to_string()));
+        count += 1;
+        child_node = node.next_sibling();
+    }
+    assert_eq!(count, WIDTH);
+}
+
+#[test]
+fn test_previous_sibling() {
+    let (_document_node, root_node) = create_wide_element();
+    let mut count = 0;
+    let mut child_node = root_node.last_child();
+    while let Some(node) = child_node {
+        count += 1;
+        child_node = node.previous_sibling();
+    }
+    assert_eq!(count, WIDTH);
+}
+
+#[test]
+fn test_remove_from_end() {
+    let (_document_node, mut root_node) = create_wide_element();
+    let mut count = 0;
+    while let Some(last_node) = root_node.last_child() {
+        let removed = root_node.remove_child(last_node).unwrap();
+        assert_eq!(removed.node_value(), Some((WIDTH - count - 1).to_string()));
+        assert!(removed.parent_node().is_none());
+        count += 1;
+    }
+    assert_eq!(count, WIDTH);
+}
+
+//
+// Each child is inserted before the previous one, so every index hint is stale by the time the
+// children are walked.
+//
+fn create_wide_element_from_front() -> (RefNode, RefNode) {
+    let mut document_node = get_implementation()
+        .create_document(None, Some("log"), None)
+        .unwrap();
+    let document = as_document_mut(&mut document_node).unwrap();
+    let mut root_node = document.document_element().unwrap();
+    for index in (0..WIDTH).rev() {
+        let _safe_to_ignore = root_node
+            .insert_before(
+                document.create_text_node(&index.to_string()),
+                root_node.first_child(),
+            )
+            .unwrap();
+    }
+    (document_node, root_node)
+}
+
+fn walk(root_node: &RefNode) -> usize {
+    let mut count = 0;
+    let mut child_node = root_node.first_child();
+    while let Some(node) = child_node {
+        count += 1;
+        child_node = node.next_sibling();
+    }
+    count
+}
+
+#[test]
+fn test_next_sibling_after_insert_at_front() {
+    let (_document_node, root_node) = create_wide_element_from_front();
+    let mut count = 0;
+    let mut child_node = root_node.first_child();
+    while let Some(node) = child_node {
+        assert_eq!(node.node_value(), Some(count.to_string()));
+        count += 1;
+        child_node = node.next_sibling();
+    }
+    assert_eq!(count, WIDTH);
+}
+
+//
+// Timing is unreliable on a loaded machine, so this is only run on request, with
+// `cargo test --release -- --ignored`.
+//
+#[test]
+#[ignore]
+fn test_walk_time_after_insert_at_front() {
+    let (_appended_document, appended_root) = create_wide_element();
+    let (_inserted_document, inserted_root) = create_wide_element_from_front();
+
+    let start = Instant::now();
+    assert_eq!(walk(&appended_root), WIDTH);
+    let appended = start.elapsed();
+
+    let start = Instant::now();
+    assert_eq!(walk(&inserted_root), WIDTH);
+    let inserted = start.elapsed();
+
+    assert!(
+        inserted < appended * 10 + Duration::from_millis(50),
+        "walk took {:?} after inserting at the front, and {:?} after appending",
+        inserted,
+        appended
+    );
+}
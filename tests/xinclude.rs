@@ -0,0 +1,172 @@
+#![cfg(feature = "xinclude")]
+
+use std::collections::HashMap;
+use xml_dom::level2::convert::*;
+use xml_dom::level2::*;
+use xml_dom::parser::read_xml;
+use xml_dom::xinclude::*;
+
+const XI: &str = r#"xmlns:xi="http://www.w3.org/2001/XInclude""#;
+
+fn resources(entries: &[(&str, &str)]) -> HashMap<String, String> {
+    entries
+        .iter()
+        .map(|(uri, content)| (uri.to_string(), content.to_string()))
+        .collect()
+}
+
+fn process(xml: &str, resolver: &dyn IncludeResolver) -> Result<String> {
+    let mut document_node = read_xml(&xml.replace("XI", XI)).unwrap();
+    document_node.process_xincludes(resolver)?;
+    Ok(document_node.to_string().replace(XI, "XI"))
+}
+
+#[test]
+fn test_include_xml_and_text() {
+    let resolver = resources(&[
+        (
+            "a.xml",
+            r#"<?pi?><!DOCTYPE a><a><b xml:id="x">x</b><b xml:id="y">y</b></a><!--end-->"#,
+        ),
+        ("a.txt", "1 < 2 & 3"),
+    ]);
+    assert_eq!(
+        process(
+            r#"<doc XI><xi:include href="a.xml"/><xi:include href="a.xml" xpointer="y"/><t><xi:include href="a.txt" parse="text"/></t></doc>"#,
+            &resolver
+        )
+        .unwrap(),
+        r#"<doc XI><?pi?><a><b xml:id="x">x</b><b xml:id="y">y</b></a><!--end--><b xml:id="y">y</b><t>1 &#60; 2 &#38; 3</t></doc>"#
+    );
+
+    //
+    // The document element may itself be replaced.
+    //
+    assert_eq!(
+        process(r#"<xi:include XI href="a.xml" xpointer="x"/>"#, &resolver).unwrap(),
+        r#"<b xml:id="x">x</b>"#
+    );
+}
+
+#[test]
+fn test_include_same_document() {
+    assert_eq!(
+        process(
+            r#"<doc XI><note xml:id="n">text</note><xi:include xpointer="n"/></doc>"#,
+            &resources(&[])
+        )
+        .unwrap(),
+        r#"<doc XI><note xml:id="n">text</note><note xml:id="n">text</note></doc>"#
+    );
+    assert_eq!(
+        process(
+            r#"<doc XI xml:id="d"><xi:include xpointer="d"/></doc>"#,
+            &resources(&[])
+        ),
+        Err(Error::HierarchyRequest)
+    );
+}
+
+#[test]
+fn test_include_nested() {
+    let files = resources(&[
+        (
+            "http://example.org/book/chapters/ch1.xml",
+            r#"<ch XI><xi:include href="parts/p1.xml"/></ch>"#,
+        ),
+        (
+            "http://example.org/book/chapters/parts/p1.xml",
+            "<p>one</p>",
+        ),
+        (
+            "http://example.org/book/loop.xml",
+            r#"<l XI><xi:include href="loop.xml"/></l>"#,
+        ),
+    ]);
+    let resolver = |uri: &str| {
+        files
+            .get(uri)
+            .map(|content| content.replace("XI", XI))
+            .ok_or(Error::NotFound)
+    };
+
+    let mut document_node = read_xml(&format!(
+        r#"<book {}><xi:include href="chapters/ch1.xml"/></book>"#,
+        XI
+    ))
+    .unwrap();
+    let document = as_document_mut(&mut document_node).unwrap();
+    document
+        .set_document_uri("http://example.org/book/main.xml")
+        .unwrap();
+    document_node.process_xincludes(&resolver).unwrap();
+    assert_eq!(
+        document_node.to_string().replace(XI, "XI"),
+        r#"<book XI><ch XI><p>one</p></ch></book>"#
+    );
+
+    let mut document_node = read_xml(&format!(
+        r#"<book {}><xi:include href="loop.xml"/></book>"#,
+        XI
+    ))
+    .unwrap();
+    let document = as_document_mut(&mut document_node).unwrap();
+    document
+        .set_document_uri("http://example.org/book/main.xml")
+        .unwrap();
+    assert_eq!(
+        document_node.process_xincludes(&resolver),
+        Err(Error::HierarchyRequest)
+    );
+}
+
+#[test]
+fn test_include_fallback() {
+    let resolver = resources(&[("a.xml", "<a/>")]);
+    assert_eq!(
+        process(
+            r#"<doc XI><xi:include href="missing.xml"><xi:fallback>none<xi:include href="a.xml"/></xi:fallback></xi:include></doc>"#,
+            &resolver
+        )
+        .unwrap(),
+        r#"<doc XI>none<a></a></doc>"#
+    );
+    assert_eq!(
+        process(
+            r#"<doc XI><xi:include href="a.xml" xpointer="element(/1)"><xi:fallback/></xi:include><xi:include href="a.xml" xpointer="none"><xi:fallback>-</xi:fallback></xi:include></doc>"#,
+            &resolver
+        )
+        .unwrap(),
+        r#"<doc XI>-</doc>"#
+    );
+    assert_eq!(
+        process(
+            r#"<doc XI><xi:include href="missing.xml"/></doc>"#,
+            &resolver
+        ),
+        Err(Error::NotFound)
+    );
+}
+
+#[test]
+fn test_include_errors() {
+    let resolver = resources(&[("a.xml", "<a/>"), ("bad.xml", "<a>")]);
+    for xml in [
+        r#"<doc XI><xi:include/></doc>"#,
+        r#"<doc XI><xi:include href="a.xml#x"/></doc>"#,
+        r#"<doc XI><xi:include href="a.xml" parse="html"/></doc>"#,
+        r#"<doc XI><xi:include href="a.xml" parse="text" xpointer="x"/></doc>"#,
+        r#"<doc XI><xi:include href="a.xml"><xi:fallback/><xi:fallback/></xi:include></doc>"#,
+        r#"<doc XI><xi:include href="a.xml"><xi:include href="a.xml"/></xi:include></doc>"#,
+        r#"<doc XI><xi:include href="bad.xml"/></doc>"#,
+    ] {
+        assert_eq!(process(xml, &resolver), Err(Error::Syntax), "{}", xml);
+    }
+    assert_eq!(
+        process(
+            r#"<xi:include XI href="missing.xml"><xi:fallback><a/><b/></xi:fallback></xi:include>"#,
+            &resolver
+        ),
+        Err(Error::HierarchyRequest)
+    );
+}
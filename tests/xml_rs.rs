@@ -0,0 +1,179 @@
+#![cfg(feature = "xml_rs")]
+
+use xml::reader::ParserConfig;
+use xml::{EmitterConfig, EventReader};
+use xml_dom::interop::xml_rs::{from_event_reader, to_event_writer, Error};
+use xml_dom::level2::ext::*;
+use xml_dom::level2::*;
+
+fn read(xml: &str) -> RefNode {
+    from_event_reader(EventReader::from_str(xml)).unwrap()
+}
+
+fn write(node: &RefNode) -> String {
+    let mut output = Vec::new();
+    let writer = EmitterConfig::new()
+        .write_document_declaration(false)
+        .create_writer(&mut output);
+    to_event_writer(node, writer).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn test_read_document() {
+    let xml = r#"<?xml version="1.1" encoding="UTF-8" standalone="yes"?><?style sheet?><root z="1" a="2">one &amp; two<![CDATA[<raw/>]]><item/><?render fast?></root>"#;
+    let document_node = read(xml);
+
+    assert_eq!(
+        document_node.xml_declaration(),
+        Some(XmlDecl::new(
+            XmlVersion::V11,
+            Some("UTF-8".to_string()),
+            Some(true)
+        ))
+    );
+    let first_node = document_node.first_child().unwrap();
+    assert_eq!(first_node.node_type(), NodeType::ProcessingInstruction);
+
+    let root_node = document_node.document_element().unwrap();
+    assert!(root_node.to_string().starts_with(r#"<root z="1" a="2">"#));
+    let types: Vec<NodeType> = root_node
+        .child_nodes()
+        .iter()
+        .map(|child_node| child_node.node_type())
+        .collect();
+    assert_eq!(
+        types,
+        vec![
+            NodeType::Text,
+            NodeType::CData,
+            NodeType::Element,
+            NodeType::ProcessingInstruction
+        ]
+    );
+    assert_eq!(
+        root_node.first_child().unwrap().node_value(),
+        Some("one & two".to_string())
+    );
+}
+
+#[test]
+fn test_read_comments() {
+    let xml = r#"<root><!-- note --></root>"#;
+    let document_node = read(xml);
+    let root_node = document_node.document_element().unwrap();
+    assert!(root_node.first_child().is_none());
+
+    let reader = ParserConfig::new()
+        .ignore_comments(false)
+        .create_reader(xml.as_bytes());
+    let document_node = from_event_reader(reader).unwrap();
+    let root_node = document_node.document_element().unwrap();
+    let comment_node = root_node.first_child().unwrap();
+    assert_eq!(comment_node.node_type(), NodeType::Comment);
+    assert_eq!(comment_node.node_value(), Some(" note ".to_string()));
+}
+
+#[test]
+fn test_read_namespaces() {
+    let xml = r#"<a:root xmlns:a="urn:a" xmlns="urn:default"><a:item a:id="1" id="2"/><plain xmlns=""><inner xmlns="urn:default"/></plain></a:root>"#;
+    let document_node = read(xml);
+
+    let root_node = document_node.document_element().unwrap();
+    assert_eq!(root_node.namespace_uri(), Some("urn:a".to_string()));
+    assert_eq!(
+        root_node.get_attribute_ns("http://www.w3.org/2000/xmlns/", "a"),
+        Some("urn:a".to_string())
+    );
+
+    let item_node = root_node.first_child().unwrap();
+    assert_eq!(item_node.namespace_uri(), Some("urn:a".to_string()));
+    assert!(!item_node.has_attribute("xmlns:a"));
+    let id_node = item_node.get_attribute_node_ns("urn:a", "id").unwrap();
+    assert_eq!(id_node.value(), Some("1".to_string()));
+
+    let plain_node = item_node.next_sibling().unwrap();
+    assert_eq!(plain_node.namespace_uri(), None);
+    let inner_node = plain_node.first_child().unwrap();
+    assert_eq!(inner_node.namespace_uri(), Some("urn:default".to_string()));
+
+    assert_eq!(
+        write(&document_node),
+        r#"<?xml version="1.0" encoding="UTF-8"?><a:root xmlns="urn:default" xmlns:a="urn:a"><a:item a:id="1" id="2" /><plain xmlns=""><inner xmlns="urn:default" /></plain></a:root>"#
+    );
+}
+
+#[test]
+fn test_round_trip() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?><catalog z="1" a="2"><item>one &amp; two</item><![CDATA[<raw/>]]><?render fast?><p>mixed <b>bold</b> text</p></catalog>"#;
+    let document_node = read(xml);
+    let output = write(&document_node);
+    assert_eq!(
+        output,
+        r#"<?xml version="1.0" encoding="UTF-8"?><catalog z="1" a="2"><item>one &amp; two</item><![CDATA[<raw/>]]><?render fast?><p>mixed <b>bold</b> text</p></catalog>"#
+    );
+    assert!(read(&output).is_equal_node(&document_node));
+}
+
+#[test]
+fn test_write_undeclared_namespaces() {
+    let document_node = get_implementation()
+        .create_document(Some("urn:a"), Some("a:root"), None)
+        .unwrap();
+    let mut root_node = document_node.document_element().unwrap();
+    let mut item_node = document_node.create_element_ns("urn:b", "item").unwrap();
+    item_node.set_attribute_ns("urn:c", "c:id", "1").unwrap();
+    let mut item_node = root_node.append_child(item_node).unwrap();
+    let plain_node = document_node.create_element("plain").unwrap();
+    let _safe_to_ignore = item_node.append_child(plain_node).unwrap();
+
+    let output = write(&document_node);
+    assert_eq!(
+        output,
+        r#"<a:root xmlns:a="urn:a"><item c:id="1" xmlns="urn:b" xmlns:c="urn:c"><plain xmlns="" /></item></a:root>"#
+    );
+
+    let copy_node = read(&output);
+    let item_node = copy_node.document_element().unwrap().first_child().unwrap();
+    assert_eq!(item_node.namespace_uri(), Some("urn:b".to_string()));
+    assert_eq!(
+        item_node.get_attribute_ns("urn:c", "id"),
+        Some("1".to_string())
+    );
+    let plain_node = item_node.first_child().unwrap();
+    assert_eq!(plain_node.namespace_uri(), None);
+}
+
+#[test]
+fn test_write_element() {
+    let document_node = read(r#"<root><item id="1">one</item></root>"#);
+    let item_node = document_node
+        .document_element()
+        .unwrap()
+        .first_child()
+        .unwrap();
+    assert_eq!(write(&item_node), r#"<item id="1">one</item>"#);
+}
+
+#[test]
+fn test_errors() {
+    match from_event_reader(EventReader::from_str("<root><item></root>")) {
+        Err(Error::Reader(_)) => {}
+        result => panic!("expected a reader error, not {:?}", result),
+    }
+
+    let document_node = read(r#"<root id="1"/>"#);
+    let attribute_node = document_node
+        .document_element()
+        .unwrap()
+        .get_attribute_node("id")
+        .unwrap();
+    let mut output = Vec::new();
+    match to_event_writer(
+        &attribute_node,
+        EmitterConfig::new().create_writer(&mut output),
+    ) {
+        Err(Error::Dom(err)) => assert_eq!(err, xml_dom::level2::Error::NotSupported),
+        result => panic!("expected a DOM error, not {:?}", result),
+    }
+}
@@ -0,0 +1,313 @@
+#![cfg(feature = "quick_parser")]
+
+use std::collections::HashMap;
+use xml_dom::level2::xpath::*;
+use xml_dom::level2::*;
+use xml_dom::parser::read_xml;
+
+const CATALOG: &str = r#"<catalog xmlns:dc="http://purl.org/dc/elements/1.1/">
+  <book id="b1" lang="en"><dc:title>Rust</dc:title><price>30</price></book>
+  <book id="b2"><dc:title>XML</dc:title><price>12.5</price><!--note--></book>
+  <magazine id="m1"><dc:title>Monthly<![CDATA[ Rust]]></dc:title><price>5</price></magazine>
+</catalog>"#;
+
+fn names(value: XPathValue) -> Vec<String> {
+    value
+        .into_node_set()
+        .unwrap()
+        .iter()
+        .map(|node| match node.node_type() {
+            NodeType::Element => node
+                .get_attribute("id")
+                .unwrap_or_else(|| node.node_name().to_string()),
+            _ => node.node_name().to_string(),
+        })
+        .collect()
+}
+
+fn string(context: &RefNode, expression: &str) -> String {
+    evaluate(context, expression).unwrap().string()
+}
+
+#[test]
+fn test_location_paths() {
+    let document_node = read_xml(CATALOG).unwrap();
+
+    assert_eq!(
+        names(evaluate(&document_node, "/catalog/book").unwrap()),
+        vec!["b1", "b2"]
+    );
+    assert_eq!(
+        names(evaluate(&document_node, "/catalog/*").unwrap()),
+        vec!["b1", "b2", "m1"]
+    );
+    assert_eq!(
+        names(evaluate(&document_node, "//price/..").unwrap()),
+        vec!["b1", "b2", "m1"]
+    );
+    assert_eq!(
+        names(evaluate(&document_node, "//book/@id").unwrap()),
+        vec!["id", "id"]
+    );
+    assert_eq!(
+        names(evaluate(&document_node, "//book/@*").unwrap()),
+        vec!["id", "lang", "id"]
+    );
+    assert_eq!(
+        names(evaluate(&document_node, "//book[2]/comment()").unwrap()),
+        vec!["#comment"]
+    );
+    assert_eq!(
+        names(evaluate(&document_node, "//price/text()/ancestor::*[2]").unwrap()),
+        vec!["b1", "b2", "m1"]
+    );
+    assert_eq!(
+        names(evaluate(&document_node, "//book[1]/following-sibling::*").unwrap()),
+        vec!["b2", "m1"]
+    );
+    assert_eq!(
+        names(evaluate(&document_node, "//magazine/preceding-sibling::*[1]").unwrap()),
+        vec!["b2"]
+    );
+    assert_eq!(
+        names(evaluate(&document_node, "//magazine | //book[1] | /catalog/book[1]").unwrap()),
+        vec!["b1", "m1"]
+    );
+
+    //
+    // Relative paths are evaluated from the context node, and `/` always selects the document.
+    //
+    let magazine_node = evaluate(&document_node, "//magazine")
+        .unwrap()
+        .into_node_set()
+        .unwrap()
+        .remove(0);
+    assert_eq!(
+        names(evaluate(&magazine_node, "price").unwrap()),
+        vec!["price"]
+    );
+    assert_eq!(
+        names(evaluate(&magazine_node, "self::book").unwrap()).len(),
+        0
+    );
+    assert_eq!(
+        evaluate(&magazine_node, "/").unwrap(),
+        XPathValue::NodeSet(vec![document_node.clone()])
+    );
+}
+
+#[test]
+fn test_predicates() {
+    let document_node = read_xml(CATALOG).unwrap();
+
+    assert_eq!(
+        names(evaluate(&document_node, "//book[@lang = 'en']").unwrap()),
+        vec!["b1"]
+    );
+    assert_eq!(
+        names(evaluate(&document_node, "//*[@id != 'b1']").unwrap()),
+        vec!["b2", "m1"]
+    );
+    assert_eq!(
+        names(evaluate(&document_node, "//*[price > 10]").unwrap()),
+        vec!["b1", "b2"]
+    );
+    assert_eq!(
+        names(evaluate(&document_node, "//*[price < 10 or @lang]").unwrap()),
+        vec!["b1", "m1"]
+    );
+    assert_eq!(
+        names(evaluate(&document_node, "/catalog/*[last()]").unwrap()),
+        vec!["m1"]
+    );
+    assert_eq!(
+        names(evaluate(&document_node, "/catalog/*[position() < 3][2]").unwrap()),
+        vec!["b2"]
+    );
+    assert_eq!(
+        names(evaluate(&document_node, "(//price)[2]/..").unwrap()),
+        vec!["b2"]
+    );
+    assert_eq!(
+        names(evaluate(&document_node, "//*[starts-with(@id, 'b')][not(@lang)]").unwrap()),
+        vec!["b2"]
+    );
+    assert_eq!(
+        names(evaluate(&document_node, "//*[contains(., 'Rust')]/@id").unwrap()),
+        vec!["id", "id"]
+    );
+}
+
+#[test]
+fn test_functions() {
+    let document_node = read_xml(CATALOG).unwrap();
+
+    assert_eq!(
+        evaluate(&document_node, "count(//book)").unwrap(),
+        XPathValue::Number(2.0)
+    );
+    assert_eq!(
+        evaluate(&document_node, "sum(//price)").unwrap(),
+        XPathValue::Number(47.5)
+    );
+    assert_eq!(string(&document_node, "//magazine/*[1]"), "Monthly Rust");
+    assert_eq!(string(&document_node, "name(//book/*[1])"), "dc:title");
+    assert_eq!(string(&document_node, "local-name(//book/*[1])"), "title");
+    assert_eq!(
+        string(&document_node, "namespace-uri(//book/*[1])"),
+        "http://purl.org/dc/elements/1.1/"
+    );
+    assert_eq!(
+        string(&document_node, "concat(//book[1]/@id, '-', 1 + 1)"),
+        "b1-2"
+    );
+    assert_eq!(
+        string(&document_node, "substring('12345', 1.5, 2.6)"),
+        "234"
+    );
+    assert_eq!(string(&document_node, "substring-after('a=b', '=')"), "b");
+    assert_eq!(string(&document_node, "substring-before('a=b', '=')"), "a");
+    assert_eq!(
+        string(&document_node, "normalize-space('  a \n b ')"),
+        "a b"
+    );
+    assert_eq!(
+        string(&document_node, "translate('bar', 'abc', 'AB')"),
+        "BAr"
+    );
+    assert_eq!(string(&document_node, "string-length('héllo')"), "5");
+    assert_eq!(string(&document_node, "10 div 4"), "2.5");
+    assert_eq!(string(&document_node, "7 mod 3 * -1"), "-1");
+    assert_eq!(
+        string(&document_node, "round(2.5) + floor(-1.5) + ceiling(0.1)"),
+        "2"
+    );
+    assert_eq!(string(&document_node, "1 div 0"), "Infinity");
+    assert_eq!(string(&document_node, "1 div round(-0.5)"), "-Infinity");
+    assert_eq!(string(&document_node, "1 div round(-0.2)"), "-Infinity");
+    assert_eq!(string(&document_node, "1 div round(0.2)"), "Infinity");
+    assert_eq!(string(&document_node, "number('x')"), "NaN");
+    assert_eq!(
+        evaluate(&document_node, "boolean(//book[3]) = false()").unwrap(),
+        XPathValue::Boolean(true)
+    );
+    assert_eq!(
+        evaluate(&document_node, "//book/@id = 'b2'").unwrap(),
+        XPathValue::Boolean(true)
+    );
+    assert_eq!(
+        evaluate(&document_node, "//book/@id != 'b2'").unwrap(),
+        XPathValue::Boolean(true)
+    );
+}
+
+#[test]
+fn test_namespaces() {
+    let document_node = read_xml(CATALOG).unwrap();
+    let mut namespaces = HashMap::new();
+    let _safe_to_ignore = namespaces.insert(
+        "d".to_string(),
+        "http://purl.org/dc/elements/1.1/".to_string(),
+    );
+
+    assert_eq!(
+        evaluate_ns(&document_node, "count(//d:title)", &namespaces).unwrap(),
+        XPathValue::Number(3.0)
+    );
+    assert_eq!(
+        evaluate_ns(&document_node, "count(//book/d:*)", &namespaces).unwrap(),
+        XPathValue::Number(2.0)
+    );
+    //
+    // The prefix in the document is not used, and an unprefixed name has no namespace.
+    //
+    assert_eq!(
+        evaluate(&document_node, "count(//dc:title)"),
+        Err(Error::Namespace)
+    );
+    //
+    // Prefixes are resolved before evaluation, so an unknown prefix is an error even where no
+    // node is tested against it.
+    //
+    assert_eq!(evaluate(&document_node, "dc:title"), Err(Error::Namespace));
+    assert_eq!(
+        evaluate(&document_node, "false() and //dc:*"),
+        Err(Error::Namespace)
+    );
+    assert_eq!(
+        evaluate_ns(&document_node, "count(//title)", &namespaces).unwrap(),
+        XPathValue::Number(0.0)
+    );
+
+    let document_node = read_xml(r#"<a xmlns="urn:a" xml:lang="en-GB"><b/></a>"#).unwrap();
+    let _safe_to_ignore = namespaces.insert("a".to_string(), "urn:a".to_string());
+    assert_eq!(
+        evaluate_ns(&document_node, "count(/a:a/a:b)", &namespaces).unwrap(),
+        XPathValue::Number(1.0)
+    );
+    assert_eq!(
+        evaluate(&document_node, "count(/a)").unwrap(),
+        XPathValue::Number(0.0)
+    );
+    assert_eq!(
+        evaluate_ns(&document_node, "/a:a/@xml:lang = 'en-GB'", &namespaces).unwrap(),
+        XPathValue::Boolean(true)
+    );
+    assert_eq!(
+        evaluate_ns(&document_node, "count(/a:a/@*)", &namespaces).unwrap(),
+        XPathValue::Number(1.0)
+    );
+    assert_eq!(
+        evaluate_ns(&document_node, "//a:b[lang('en')]", &namespaces)
+            .unwrap()
+            .into_node_set()
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn test_errors() {
+    let document_node = read_xml(CATALOG).unwrap();
+
+    assert_eq!(
+        evaluate(&document_node, "//book["),
+        Err(Error::InvalidExpression)
+    );
+    assert_eq!(
+        evaluate(&document_node, "unknown(1)"),
+        Err(Error::InvalidExpression)
+    );
+    assert_eq!(
+        evaluate(&document_node, "count()"),
+        Err(Error::InvalidExpression)
+    );
+    assert_eq!(evaluate(&document_node, "count(1)"), Err(Error::XPathType));
+    assert_eq!(
+        evaluate(&document_node, "'a' | //book"),
+        Err(Error::XPathType)
+    );
+    assert_eq!(
+        evaluate(&document_node, "1 + 1").unwrap().into_node_set(),
+        Err(Error::XPathType)
+    );
+
+    //
+    // Deeply nested expressions are rejected, rather than overflowing the stack.
+    //
+    let nested = |depth: usize| format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+    assert_eq!(string(&document_node, &nested(50)), "1");
+    assert_eq!(
+        evaluate(&document_node, &nested(3_000)),
+        Err(Error::InvalidExpression)
+    );
+    assert_eq!(
+        string(&document_node, &format!("0{}", " + 1".repeat(200))),
+        "200"
+    );
+    assert_eq!(
+        evaluate(&document_node, &format!("0{}", " + 1".repeat(100_000))),
+        Err(Error::InvalidExpression)
+    );
+}